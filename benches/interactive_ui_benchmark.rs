@@ -23,19 +23,22 @@ fn create_test_search_results(count: usize) -> Vec<SearchResult> {
             };
 
             SearchResult {
-                file: format!("/path/to/file{}.jsonl", i % 3),
+                line_number: None,
+                file: format!("/path/to/file{}.jsonl", i % 3).into(),
                 uuid: format!("uuid-{i}"),
                 timestamp: "2024-01-01T00:00:00Z".to_string(),
-                session_id: format!("session-{}", i % 10),
-                role: "user".to_string(),
+                session_id: format!("session-{}", i % 10).into(),
+                role: "user".into(),
                 text: content,
-                message_type: "user".to_string(),
+                message_type: "user".into(),
                 query: QueryCondition::Literal {
                     pattern: "test".to_string(),
                     case_sensitive: false
                 },
-                cwd: "/test".to_string(),
+                cwd: "/test".into(),
                 raw_json: None,
+                matched_in: Vec::new(),
+                has_images: false,
             }
         })
         .collect()
@@ -486,19 +489,22 @@ fn create_test_session_results(count: usize) -> Vec<SearchResult> {
             let session_num = i % 10;
 
             SearchResult {
-                file: "test.jsonl".to_string(),
+                line_number: None,
+                file: "test.jsonl".into(),
                 uuid: format!("uuid-{i}"),
                 timestamp: "2024-01-01T00:00:00Z".to_string(),
-                session_id: format!("session-{session_num}"),
-                role: role.to_string(),
+                session_id: format!("session-{session_num}").into(),
+                role: role.into(),
                 text,
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: "test".to_string(),
                     case_sensitive: false,
                 },
-                cwd: "/test".to_string(),
+                cwd: "/test".into(),
                 raw_json: Some(raw_json),
+                matched_in: Vec::new(),
+                has_images: false,
             }
         })
         .collect()
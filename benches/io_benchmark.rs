@@ -0,0 +1,135 @@
+//! Buffered reads against memory-mapped reads (`--io mmap`).
+//!
+//! Run with `cargo bench --bench io_benchmark --features mmap`.
+
+use ccms::search::compression::session_reader_with;
+use ccms::search::io::{IoMode, MappedFile};
+use ccms::{RayonEngine, SearchEngineTrait, SearchOptions, SmolEngine, parse_query};
+use codspeed_criterion_compat::{
+    BenchmarkId, Criterion, black_box, criterion_group, criterion_main,
+};
+use std::fs::File;
+use std::io::{BufRead, BufWriter, Write};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const IO_MODES: [IoMode; 2] = [IoMode::Buffered, IoMode::Mmap];
+
+struct TestEnvironment {
+    _temp_dir: TempDir,
+    file: PathBuf,
+}
+
+impl TestEnvironment {
+    /// One large session file of `num_lines` messages
+    fn new(num_lines: usize) -> Self {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("large_session.jsonl");
+        let mut writer = BufWriter::new(File::create(&file).unwrap());
+
+        for i in 0..num_lines {
+            let (role, content) = if i % 2 == 0 {
+                (
+                    "user",
+                    format!("Why does request {i} time out after the retry?"),
+                )
+            } else {
+                (
+                    "assistant",
+                    format!(
+                        "Request {i} times out because the connection pool is exhausted; raising the pool size or releasing connections earlier fixes it. {}",
+                        "Padding to make lines closer to real assistant replies. ".repeat(8)
+                    ),
+                )
+            };
+            writeln!(
+                writer,
+                r#"{{"type":"{role}","message":{{"role":"{role}","content":"{content}"}},"uuid":"{i}","timestamp":"2024-01-01T00:00:{:02}Z","sessionId":"session1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/test","version":"1.0"}}"#,
+                i % 60
+            )
+            .unwrap();
+        }
+        writer.flush().unwrap();
+
+        Self {
+            _temp_dir: temp_dir,
+            file,
+        }
+    }
+}
+
+fn benchmark_line_reading(c: &mut Criterion) {
+    let env = TestEnvironment::new(100_000);
+    let mut group = c.benchmark_group("read_lines");
+
+    for io in IO_MODES {
+        group.bench_with_input(
+            BenchmarkId::new("reader", format!("{io:?}")),
+            &io,
+            |b, &io| {
+                b.iter(|| {
+                    let file = File::open(&env.file).unwrap();
+                    let mut reader = session_reader_with(&env.file, file, 64 * 1024, io).unwrap();
+                    let mut line = Vec::with_capacity(16 * 1024);
+                    let mut count = 0;
+                    while reader.read_until(b'\n', &mut line).unwrap() > 0 {
+                        count += 1;
+                        line.clear();
+                    }
+                    black_box(count)
+                });
+            },
+        );
+    }
+
+    // The map's own iterator, which borrows lines instead of copying them
+    group.bench_function("mapped_lines", |b| {
+        b.iter(|| {
+            let mapped = MappedFile::open(&File::open(&env.file).unwrap()).unwrap();
+            black_box(mapped.lines().count())
+        });
+    });
+
+    group.finish();
+}
+
+fn benchmark_engines(c: &mut Criterion) {
+    let env = TestEnvironment::new(100_000);
+    let pattern = env.file.to_string_lossy().to_string();
+    let query = parse_query("pool AND exhausted").unwrap();
+    let mut group = c.benchmark_group("engine_io");
+    group.sample_size(10);
+
+    for io in IO_MODES {
+        let options = SearchOptions {
+            max_results: Some(50),
+            io,
+            ..Default::default()
+        };
+        group.bench_with_input(
+            BenchmarkId::new("smol", format!("{io:?}")),
+            &options,
+            |b, options| {
+                b.iter(|| {
+                    let engine = SmolEngine::new(options.clone());
+                    black_box(engine.search(&pattern, query.clone()).unwrap())
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("rayon", format!("{io:?}")),
+            &options,
+            |b, options| {
+                b.iter(|| {
+                    let engine = RayonEngine::new(options.clone());
+                    black_box(engine.search(&pattern, query.clone()).unwrap())
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_line_reading, benchmark_engines);
+criterion_main!(benches);
@@ -8,14 +8,19 @@ use tempfile::TempDir;
 
 struct TestEnvironment {
     _temp_dir: TempDir,
+    /// Glob matching the session files
+    pattern: String,
 }
 
 impl TestEnvironment {
     fn new(num_files: usize, lines_per_file: usize) -> Self {
         let temp_dir = TempDir::new().unwrap();
+        // File discovery skips hidden directories such as the temp dir itself
+        let projects = temp_dir.path().join("projects");
+        std::fs::create_dir(&projects).unwrap();
 
         for file_idx in 0..num_files {
-            let file_path = temp_dir.path().join(format!("session_{file_idx}.jsonl"));
+            let file_path = projects.join(format!("session_{file_idx}.jsonl"));
             let mut file = File::create(&file_path).unwrap();
 
             for line_idx in 0..lines_per_file {
@@ -29,6 +34,7 @@ impl TestEnvironment {
 
         TestEnvironment {
             _temp_dir: temp_dir,
+            pattern: format!("{}/*.jsonl", projects.display()),
         }
     }
 }
@@ -46,7 +52,7 @@ fn benchmark_rayon_search_sizes(c: &mut Criterion) {
 
     for (size_name, num_files, lines_per_file) in workloads {
         let env = TestEnvironment::new(num_files, lines_per_file);
-        let pattern = format!("{}/*.jsonl", env._temp_dir.path().display());
+        let pattern = env.pattern.clone();
 
         // Simple search
         let query = parse_query("test").unwrap();
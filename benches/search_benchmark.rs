@@ -86,6 +86,40 @@ fn benchmark_json_parsing(c: &mut Criterion) {
     });
 }
 
+/// Ruling out a message that does not match, from one string built from its
+/// text versus from the borrowed pieces of it
+fn benchmark_message_text(c: &mut Criterion) {
+    let output = "compiling crate v0.1.0\n".repeat(200);
+    let json_line = format!(
+        r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"text","text":"Build it"}},{{"type":"tool_result","tool_use_id":"t1","content":"{}"}}]}},"uuid":"u1","timestamp":"2024-01-01T00:00:00Z","sessionId":"session1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/test","version":"1.0"}}"#,
+        output.replace('\n', "\\n")
+    );
+    let message: ccms::SessionMessage = sonic_rs::from_str(&json_line).unwrap();
+    let queries = [
+        ("ignore_case", parse_query("deploy").unwrap()),
+        (
+            "case_sensitive",
+            ccms::QueryCondition::Literal {
+                pattern: "deploy".to_string(),
+                case_sensitive: true,
+            },
+        ),
+    ];
+
+    for (name, query) in &queries {
+        c.bench_function(&format!("miss_joined_text_{name}"), |b| {
+            b.iter(|| {
+                let text = black_box(&message).get_searchable_text();
+                query.evaluate(&text).unwrap()
+            });
+        });
+
+        c.bench_function(&format!("miss_borrowed_segments_{name}"), |b| {
+            b.iter(|| query.rules_out(&black_box(&message).searchable_segments()));
+        });
+    }
+}
+
 fn benchmark_query_parsing(c: &mut Criterion) {
     c.bench_function("parse_simple_query", |b| {
         b.iter(|| parse_query(black_box("\"hello world\"")).unwrap());
@@ -103,6 +137,7 @@ criterion_group!(
     benchmark_regex_search,
     benchmark_large_file_search,
     benchmark_json_parsing,
+    benchmark_message_text,
     benchmark_query_parsing
 );
 criterion_main!(benches);
@@ -25,7 +25,8 @@ fn generate_search_results(
         let message_type = if i % 5 == 0 { "summary" } else { "message" };
 
         results.push(SearchResult {
-            file: file_id,
+            line_number: None,
+            file: file_id.into(),
             uuid: format!("uuid-{i}"),
             timestamp: format!(
                 "2024-01-01T{:02}:{:02}:{:02}Z",
@@ -33,16 +34,18 @@ fn generate_search_results(
                 i / 60 % 60,
                 i % 60
             ),
-            session_id,
-            role: role.to_string(),
+            session_id: session_id.into(),
+            role: role.into(),
             text: format!("Test message content {i}"),
-            message_type: message_type.to_string(),
+            message_type: message_type.into(),
             query: QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: format!("/project{}", i % 5),
+            cwd: format!("/project{}", i % 5).into(),
             raw_json: None,
+            matched_in: Vec::new(),
+            has_images: false,
         });
     }
 
@@ -0,0 +1,212 @@
+//! `--answers-only`: the assistant's final word on each exchange with a match.
+//!
+//! An exchange runs from a prompt typed by the user up to the next one, and
+//! its answer is the last assistant reply with text in it. Tool calls, tool
+//! results and the replies in between are the chatter leading up to it.
+//! Subagent and meta messages neither start exchanges nor answer them, so a
+//! match inside a subagent run is answered by the exchange that started it.
+
+use crate::query::SearchResult;
+use crate::recap::reply_text;
+use crate::schemas::SessionMessage;
+use crate::search::compression::open_session;
+use crate::search::context::message_result;
+use anyhow::Result;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+use std::path::Path;
+
+/// The answers of the exchanges `results` fall in, ordered like the first
+/// result of each exchange. Several matches in one exchange give one answer,
+/// and exchanges without a reply with text give none.
+pub fn final_answers(results: &[SearchResult]) -> Result<Vec<SearchResult>> {
+    // Line number -> index of the first result on that line, per file
+    let mut by_file: HashMap<&str, BTreeMap<usize, usize>> = HashMap::new();
+    for (index, result) in results.iter().enumerate() {
+        if let Some(line_number) = result.line_number {
+            by_file
+                .entry(result.file.as_str())
+                .or_default()
+                .entry(line_number)
+                .or_insert(index);
+        }
+    }
+
+    let mut found: Vec<(usize, SearchResult)> = by_file
+        .into_par_iter()
+        .map(|(file, matches)| file_answers(file, &matches, results))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+    found.sort_by_key(|(index, _)| *index);
+    Ok(found.into_iter().map(|(_, answer)| answer).collect())
+}
+
+/// Answers in `file`, each with the index of the first result it answers
+fn file_answers(
+    file: &str,
+    matches: &BTreeMap<usize, usize>,
+    results: &[SearchResult],
+) -> Result<Vec<(usize, SearchResult)>> {
+    let Some(&last_match) = matches.keys().next_back() else {
+        return Ok(Vec::new());
+    };
+
+    let mut found = Vec::new();
+    // First result in the current exchange, and its latest reply so far
+    let mut first_result: Option<usize> = None;
+    let mut answer: Option<SearchResult> = None;
+
+    for (i, line) in open_session(Path::new(file))?.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line?;
+        let Ok(message) = sonic_rs::from_str::<SessionMessage>(&line) else {
+            continue;
+        };
+        let main_thread = !message.is_sidechain() && !message.is_meta();
+
+        if main_thread && starts_exchange(&message) {
+            found.extend(first_result.take().zip(answer.take()));
+            if line_number > last_match {
+                break;
+            }
+        }
+        if let Some(&index) = matches.get(&line_number) {
+            first_result.get_or_insert(index);
+        }
+        if main_thread
+            && reply_text(&message).is_some()
+            && let Some(session_id) = message.get_session_id()
+        {
+            answer = Some(message_result(file, line_number, &message, session_id));
+        }
+    }
+    found.extend(first_result.zip(answer));
+
+    Ok(found
+        .into_iter()
+        .map(|(index, answer)| {
+            (
+                index,
+                SearchResult {
+                    query: results[index].query.clone(),
+                    ..answer
+                },
+            )
+        })
+        .collect())
+}
+
+/// Whether `message` is a prompt typed by the user
+fn starts_exchange(message: &SessionMessage) -> bool {
+    matches!(
+        message,
+        SessionMessage::User {
+            is_compact_summary,
+            ..
+        } if *is_compact_summary != Some(true)
+    ) && !message.is_tool_result_only()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{QueryCondition, SharedStr};
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    const BASE: &str = r#""sessionId":"s1","parentUuid":null,"userType":"external","cwd":"/","version":"1","timestamp":"2024-01-01T00:00:00Z""#;
+
+    fn user(uuid: &str, content: &str) -> String {
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":{content}}},"uuid":"{uuid}","isSidechain":false,{BASE}}}"#
+        )
+    }
+
+    fn assistant(uuid: &str, content: &str, sidechain: bool) -> String {
+        format!(
+            r#"{{"type":"assistant","message":{{"id":"m{uuid}","type":"message","role":"assistant","model":"claude","content":[{content}],"stop_reason":"end_turn","stop_sequence":null,"usage":{{"input_tokens":1,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":1}}}},"uuid":"{uuid}","isSidechain":{sidechain},{BASE}}}"#
+        )
+    }
+
+    fn text(text: &str) -> String {
+        format!(r#"{{"type":"text","text":"{text}"}}"#)
+    }
+
+    fn result(file: &Path, line_number: usize) -> SearchResult {
+        SearchResult {
+            file: file.display().to_string().into(),
+            uuid: String::new(),
+            timestamp: String::new(),
+            session_id: "s1".into(),
+            role: SharedStr::default(),
+            text: String::new(),
+            message_type: SharedStr::default(),
+            query: QueryCondition::Literal {
+                pattern: "build".to_string(),
+                case_sensitive: false,
+            },
+            cwd: "/".into(),
+            raw_json: None,
+            line_number: Some(line_number),
+            matched_in: Vec::new(),
+            has_images: false,
+        }
+    }
+
+    #[test]
+    fn test_final_answers() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("session.jsonl");
+        let mut file = File::create(&path)?;
+        for line in [
+            user("u1", r#""Why does the build fail?""#),
+            assistant("a1", &text("Let me look."), false),
+            assistant(
+                "a2",
+                r#"{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"make"}}"#,
+                false,
+            ),
+            user(
+                "u2",
+                r#"[{"type":"tool_result","tool_use_id":"t1","content":"build error"}]"#,
+            ),
+            assistant("a3", &text("A header is missing; added it."), false),
+            assistant("a4", &text("Subagent notes"), true),
+            user("u3", r#""Now deploy""#),
+            assistant("a5", &text("Deployed."), false),
+            user("u4", r#""And the build cache?""#),
+            assistant(
+                "a6",
+                r#"{"type":"tool_use","id":"t2","name":"Bash","input":{"command":"ls"}}"#,
+                false,
+            ),
+        ] {
+            writeln!(file, "{line}")?;
+        }
+
+        // Matches in the prompt and the tool result share an answer; the
+        // unanswered exchange gives none
+        let answers = final_answers(&[result(&path, 4), result(&path, 1), result(&path, 9)])?;
+        assert_eq!(
+            answers
+                .iter()
+                .map(|answer| (answer.uuid.as_str(), answer.line_number))
+                .collect::<Vec<_>>(),
+            vec![("a3", Some(5))]
+        );
+        assert_eq!(answers[0].text, "A header is missing; added it.");
+        assert_eq!(answers[0].query, result(&path, 1).query);
+
+        // Ordered like the results they answer
+        let answers = final_answers(&[result(&path, 8), result(&path, 2)])?;
+        assert_eq!(
+            answers.iter().map(|a| a.uuid.as_str()).collect::<Vec<_>>(),
+            vec!["a5", "a3"]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,447 @@
+mod template;
+
+pub use template::{BUILTIN_TEMPLATES, ExportTemplate, TemplateContext, TemplateValue};
+
+use crate::schemas::SessionMessage;
+use crate::search::compression::open_session;
+use crate::utils::config::config_dir;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+/// Topic used for bookmarks without tags
+pub const UNTAGGED_TOPIC: &str = "untagged";
+
+/// Maximum number of characters of the preceding message kept as context
+const CONTEXT_MAX_CHARS: usize = 500;
+
+/// A starred message, identified by its session file and uuid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub file: String,
+    pub uuid: String,
+    #[serde(default)]
+    pub session_id: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+impl Bookmark {
+    /// Topic the bookmark is filed under when exporting: its first tag
+    pub fn topic(&self) -> &str {
+        self.tags
+            .first()
+            .map(String::as_str)
+            .unwrap_or(UNTAGGED_TOPIC)
+    }
+}
+
+/// Bookmarks persisted as JSON in the config directory
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BookmarkStore {
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl BookmarkStore {
+    /// `bookmarks.json` in the ccms config directory
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(config_dir()?.join("bookmarks.json"))
+    }
+
+    /// Load the store, treating a missing file as empty
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read bookmarks: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse bookmarks: {}", path.display()))
+    }
+
+    pub fn contains(&self, uuid: &str) -> bool {
+        self.bookmarks.iter().any(|bookmark| bookmark.uuid == uuid)
+    }
+
+    /// Add `bookmark`, or remove the existing bookmark of the same message.
+    ///
+    /// Returns whether the message is bookmarked afterwards.
+    pub fn toggle(&mut self, bookmark: Bookmark) -> bool {
+        if self.contains(&bookmark.uuid) {
+            self.bookmarks
+                .retain(|existing| existing.uuid != bookmark.uuid);
+            false
+        } else {
+            self.bookmarks.push(bookmark);
+            true
+        }
+    }
+
+    /// Uuids of all bookmarked messages
+    pub fn uuids(&self) -> HashSet<String> {
+        self.bookmarks
+            .iter()
+            .map(|bookmark| bookmark.uuid.clone())
+            .collect()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write bookmarks: {}", path.display()))
+    }
+}
+
+/// A bookmark joined with the message it points to
+#[derive(Debug, Clone)]
+pub struct BookmarkedMessage {
+    pub bookmark: Bookmark,
+    /// `None` when the message can no longer be found in its session file
+    pub message: Option<ResolvedMessage>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedMessage {
+    pub role: String,
+    pub timestamp: String,
+    pub text: String,
+    /// The message's line in the session file
+    pub raw_json: String,
+    /// Role and text of the message right before the bookmarked one
+    pub context: Option<(String, String)>,
+}
+
+/// Look up the bookmarked message and the one preceding it in its session file
+pub fn resolve_bookmark(bookmark: &Bookmark) -> Result<BookmarkedMessage> {
+    let mut resolved = BookmarkedMessage {
+        bookmark: bookmark.clone(),
+        message: None,
+    };
+    let Ok(reader) = open_session(Path::new(&bookmark.file)) else {
+        return Ok(resolved);
+    };
+
+    let mut previous: Option<(String, String)> = None;
+    for line in reader.lines() {
+        let line = line?;
+        let Ok(message) = serde_json::from_str::<SessionMessage>(&line) else {
+            continue;
+        };
+        if matches!(message, SessionMessage::Summary { .. }) {
+            continue;
+        }
+
+        let role = message.get_type().to_string();
+        let text = message.get_content_text();
+        if message.get_uuid() == Some(bookmark.uuid.as_str()) {
+            resolved.message = Some(ResolvedMessage {
+                role,
+                timestamp: message.get_timestamp().unwrap_or("").to_string(),
+                text,
+                raw_json: line,
+                context: previous,
+            });
+            break;
+        }
+        if !text.trim().is_empty() {
+            previous = Some((role, text));
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Write bookmarks into one Markdown file per topic under `out_dir`, rendered with `template`.
+///
+/// Returns the paths of the files written, in topic order.
+pub fn export_bookmarks(
+    bookmarks: &[Bookmark],
+    out_dir: &Path,
+    template: &ExportTemplate,
+) -> Result<Vec<PathBuf>> {
+    let mut by_topic: BTreeMap<&str, Vec<BookmarkedMessage>> = BTreeMap::new();
+    for bookmark in bookmarks {
+        by_topic
+            .entry(bookmark.topic())
+            .or_default()
+            .push(resolve_bookmark(bookmark)?);
+    }
+
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("failed to create {}", out_dir.display()))?;
+
+    let exported_at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let mut written = Vec::new();
+    for (topic, entries) in by_topic {
+        let path = out_dir.join(format!("{}.md", topic_file_stem(topic)));
+        let content = template.render(&topic_context(topic, &entries, &exported_at))?;
+        fs::write(&path, content).with_context(|| format!("failed to write {}", path.display()))?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// File name stem for a topic: lowercase, with anything but alphanumerics collapsed to '-'
+pub fn topic_file_stem(topic: &str) -> String {
+    let mut stem = String::new();
+    for ch in topic.chars() {
+        if ch.is_alphanumeric() {
+            stem.extend(ch.to_lowercase());
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_end_matches('-');
+    if stem.is_empty() {
+        UNTAGGED_TOPIC.to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+/// Values available to export templates for one topic file
+pub fn topic_context(
+    topic: &str,
+    entries: &[BookmarkedMessage],
+    exported_at: &str,
+) -> TemplateContext {
+    let text = |value: &str| TemplateValue::Text(value.to_string());
+
+    let mut context = TemplateContext::new();
+    context.insert("topic".to_string(), text(topic));
+    context.insert("topic_slug".to_string(), text(&topic_file_stem(topic)));
+    context.insert("exported_at".to_string(), text(exported_at));
+    context.insert("count".to_string(), text(&entries.len().to_string()));
+    context.insert(
+        "entries".to_string(),
+        TemplateValue::List(entries.iter().map(entry_context).collect()),
+    );
+    context
+}
+
+fn entry_context(entry: &BookmarkedMessage) -> TemplateContext {
+    let text = |value: &str| TemplateValue::Text(value.to_string());
+    let bookmark = &entry.bookmark;
+
+    let mut context = TemplateContext::new();
+    context.insert("uuid".to_string(), text(&bookmark.uuid));
+    context.insert("session_id".to_string(), text(&bookmark.session_id));
+    context.insert("created_at".to_string(), text(&bookmark.created_at));
+    context.insert(
+        "note".to_string(),
+        text(bookmark.note.as_deref().unwrap_or("")),
+    );
+    context.insert("tags".to_string(), text(&bookmark.tags.join(", ")));
+    let tags_code: Vec<String> = bookmark.tags.iter().map(|tag| format!("`{tag}`")).collect();
+    context.insert("tags_code".to_string(), text(&tags_code.join(" ")));
+    let tags_hashtags: Vec<String> = bookmark
+        .tags
+        .iter()
+        .map(|tag| format!("#{}", topic_file_stem(tag)))
+        .collect();
+    context.insert("tags_hashtags".to_string(), text(&tags_hashtags.join(" ")));
+    context.insert("source_file".to_string(), text(&bookmark.file));
+    context.insert(
+        "source_name".to_string(),
+        text(
+            &Path::new(&bookmark.file)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| bookmark.file.clone()),
+        ),
+    );
+    context.insert("source_url".to_string(), text(&file_url(&bookmark.file)));
+
+    if let Some(message) = &entry.message {
+        context.insert("found".to_string(), text("true"));
+        context.insert("role".to_string(), text(&message.role));
+        context.insert("timestamp".to_string(), text(&message.timestamp));
+        context.insert("text".to_string(), text(message.text.trim_end()));
+        if let Some((role, context_text)) = &message.context {
+            let truncated = truncate_chars(context_text, CONTEXT_MAX_CHARS);
+            context.insert("context_role".to_string(), text(role));
+            context.insert("context".to_string(), text(&truncated));
+            context.insert("context_quoted".to_string(), text(&quote(&truncated)));
+        }
+    }
+
+    context
+}
+
+fn quote(text: &str) -> String {
+    text.lines().collect::<Vec<_>>().join("\n> ")
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        format!("{}…", text.chars().take(max_chars).collect::<String>())
+    }
+}
+
+fn file_url(path: &str) -> String {
+    format!("file://{}", path.replace(' ', "%20"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn write_session(dir: &Path) -> Result<PathBuf> {
+        let path = dir.join("session.jsonl");
+        let mut file = File::create(&path)?;
+        writeln!(
+            file,
+            r#"{{"type":"user","message":{{"role":"user","content":"How do I draw a scrollbar?"}},"uuid":"u1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/proj","version":"1.0"}}"#
+        )?;
+        writeln!(
+            file,
+            r#"{{"type":"assistant","message":{{"id":"msg1","type":"message","role":"assistant","model":"claude","content":[{{"type":"text","text":"Use ScrollbarState."}}],"stop_reason":"end_turn","stop_sequence":null,"usage":{{"input_tokens":10,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":5}}}},"uuid":"a1","timestamp":"2024-01-01T00:00:01Z","sessionId":"s1","parentUuid":"u1","isSidechain":false,"userType":"external","cwd":"/proj","version":"1.0"}}"#
+        )?;
+        Ok(path)
+    }
+
+    fn bookmark(file: &Path, uuid: &str, tags: &[&str]) -> Bookmark {
+        Bookmark {
+            file: file.to_string_lossy().to_string(),
+            uuid: uuid.to_string(),
+            session_id: "s1".to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            note: None,
+            created_at: "2024-02-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_store_round_trip_and_missing_file() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("nested").join("bookmarks.json");
+        assert_eq!(BookmarkStore::load(&path)?, BookmarkStore::default());
+
+        let store = BookmarkStore {
+            bookmarks: vec![bookmark(Path::new("/tmp/s.jsonl"), "a1", &["rust"])],
+        };
+        store.save(&path)?;
+        assert_eq!(BookmarkStore::load(&path)?, store);
+        Ok(())
+    }
+
+    #[test]
+    fn test_toggle_bookmark() {
+        let mut store = BookmarkStore::default();
+        assert!(store.toggle(bookmark(Path::new("/tmp/s.jsonl"), "a1", &[])));
+        assert!(store.toggle(bookmark(Path::new("/tmp/s.jsonl"), "u1", &[])));
+        assert!(store.contains("a1"));
+        assert_eq!(store.uuids().len(), 2);
+
+        assert!(!store.toggle(bookmark(Path::new("/tmp/s.jsonl"), "a1", &[])));
+        assert!(!store.contains("a1"));
+        assert_eq!(store.bookmarks.len(), 1);
+    }
+
+    #[test]
+    fn test_topic_file_stem() {
+        assert_eq!(topic_file_stem("Rust TUI"), "rust-tui");
+        assert_eq!(topic_file_stem("  c++/ffi "), "c-ffi");
+        assert_eq!(topic_file_stem("!!!"), UNTAGGED_TOPIC);
+    }
+
+    #[test]
+    fn test_resolve_bookmark_with_context() -> Result<()> {
+        let dir = tempdir()?;
+        let session = write_session(dir.path())?;
+
+        let resolved = resolve_bookmark(&bookmark(&session, "a1", &[]))?;
+        let message = resolved.message.unwrap();
+        assert_eq!(message.role, "assistant");
+        assert_eq!(message.text, "Use ScrollbarState.");
+        assert_eq!(
+            message.context,
+            Some(("user".to_string(), "How do I draw a scrollbar?".to_string()))
+        );
+
+        let missing = resolve_bookmark(&bookmark(&session, "nope", &[]))?;
+        assert!(missing.message.is_none());
+        Ok(())
+    }
+
+    fn plain_template() -> ExportTemplate {
+        ExportTemplate::builtin("plain").unwrap()
+    }
+
+    #[test]
+    fn test_export_plain_per_topic() -> Result<()> {
+        let dir = tempdir()?;
+        let session = write_session(dir.path())?;
+        let out_dir = dir.path().join("notes");
+
+        let mut tagged = bookmark(&session, "a1", &["Ratatui", "tui"]);
+        tagged.note = Some("Handy".to_string());
+        let bookmarks = vec![tagged, bookmark(&session, "u1", &[])];
+
+        let written = export_bookmarks(&bookmarks, &out_dir, &plain_template())?;
+        assert_eq!(
+            written,
+            vec![out_dir.join("ratatui.md"), out_dir.join("untagged.md")]
+        );
+
+        let ratatui = fs::read_to_string(&written[0])?;
+        assert!(ratatui.starts_with("# Ratatui\n\n## assistant · 2024-01-01T00:00:01Z\n\n"));
+        assert!(ratatui.contains("## assistant · 2024-01-01T00:00:01Z"));
+        assert!(ratatui.contains("> **user:** How do I draw a scrollbar?"));
+        assert!(ratatui.contains("Use ScrollbarState."));
+        assert!(ratatui.contains("**Note:** Handy"));
+        assert!(ratatui.contains("**Tags:** `Ratatui` `tui`"));
+        assert!(ratatui.contains("**Source:** [session.jsonl](file://"));
+
+        let untagged = fs::read_to_string(&written[1])?;
+        assert!(untagged.contains("## user · 2024-01-01T00:00:00Z"));
+        assert!(!untagged.contains("> **"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_obsidian_and_notion_templates() -> Result<()> {
+        let dir = tempdir()?;
+        let session = write_session(dir.path())?;
+        let mut tagged = bookmark(&session, "a1", &["Rust TUI"]);
+        tagged.note = Some("Handy".to_string());
+        let entries = vec![resolve_bookmark(&tagged)?];
+        let context = topic_context("Rust TUI", &entries, "2024-03-01T00:00:00Z");
+
+        let obsidian = ExportTemplate::builtin("obsidian")
+            .unwrap()
+            .render(&context)?;
+        assert!(obsidian.starts_with("---\ntopic: \"Rust TUI\"\n"));
+        assert!(obsidian.contains("tags: [ccms, rust-tui]"));
+        assert!(obsidian.contains("> [!quote] user\n> How do I draw a scrollbar?"));
+        assert!(obsidian.contains("> [!note]\n> Handy"));
+        assert!(obsidian.contains("#rust-tui"));
+        assert!(obsidian.contains("Source: [[s1]]"));
+
+        let notion = ExportTemplate::builtin("notion")
+            .unwrap()
+            .render(&context)?;
+        assert!(notion.contains("Exported: 2024-03-01T00:00:00Z"));
+        assert!(notion.contains("<aside>💡 Handy</aside>"));
+        assert!(notion.contains("Tags: Rust TUI"));
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+//! Minimal mustache-style templates for bookmark exports.
+//!
+//! Supported tags:
+//! - `{{name}}` inserts a value (unknown names render as nothing)
+//! - `{{#name}}...{{/name}}` renders its body once per list item, or once when a
+//!   string value is non-empty
+//! - `{{^name}}...{{/name}}` renders its body when the value is missing or empty
+
+use crate::utils::config::config_dir;
+use anyhow::{Context, Result, bail};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Templates shipped with ccms, by name
+pub const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("plain", include_str!("templates/plain.md")),
+    ("obsidian", include_str!("templates/obsidian.md")),
+    ("notion", include_str!("templates/notion.md")),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateValue {
+    Text(String),
+    List(Vec<TemplateContext>),
+}
+
+impl TemplateValue {
+    fn is_truthy(&self) -> bool {
+        match self {
+            TemplateValue::Text(text) => !text.is_empty(),
+            TemplateValue::List(items) => !items.is_empty(),
+        }
+    }
+}
+
+pub type TemplateContext = BTreeMap<String, TemplateValue>;
+
+/// An export template, either built in or loaded from the config directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportTemplate {
+    pub name: String,
+    pub source: String,
+}
+
+impl ExportTemplate {
+    /// Directory searched for user templates: `<config dir>/templates`
+    pub fn user_dir() -> Result<PathBuf> {
+        Ok(config_dir()?.join("templates"))
+    }
+
+    /// Load `<name>.md` from the user template directory, falling back to the
+    /// built-in template of the same name
+    pub fn load(name: &str) -> Result<Self> {
+        let user_path = Self::user_dir()?.join(format!("{name}.md"));
+        if user_path.is_file() {
+            let source = fs::read_to_string(&user_path)
+                .with_context(|| format!("failed to read template: {}", user_path.display()))?;
+            return Ok(Self {
+                name: name.to_string(),
+                source,
+            });
+        }
+
+        Self::builtin(name).with_context(|| {
+            let builtins: Vec<&str> = BUILTIN_TEMPLATES.iter().map(|(n, _)| *n).collect();
+            format!(
+                "unknown template '{name}' (built-in: {}; or create {})",
+                builtins.join(", "),
+                user_path.display()
+            )
+        })
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        BUILTIN_TEMPLATES
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(name, source)| Self {
+                name: name.to_string(),
+                source: source.to_string(),
+            })
+    }
+
+    pub fn render(&self, context: &TemplateContext) -> Result<String> {
+        let mut output = String::new();
+        render_into(&self.source, &[context], &mut output)
+            .with_context(|| format!("failed to render template '{}'", self.name))?;
+        Ok(output)
+    }
+}
+
+fn lookup<'a>(scopes: &[&'a TemplateContext], name: &str) -> Option<&'a TemplateValue> {
+    scopes.iter().rev().find_map(|scope| scope.get(name))
+}
+
+fn render_into(template: &str, scopes: &[&TemplateContext], output: &mut String) -> Result<()> {
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        let end = after_open.find("}}").context("unclosed '{{' tag")?;
+        let tag = after_open[..end].trim();
+        rest = &after_open[end + 2..];
+
+        if let Some(name) = tag.strip_prefix('#').or_else(|| tag.strip_prefix('^')) {
+            let name = name.trim();
+            let (body, remaining) = split_section(rest, name)?;
+            rest = remaining;
+
+            let value = lookup(scopes, name);
+            if tag.starts_with('^') {
+                if !value.is_some_and(TemplateValue::is_truthy) {
+                    render_into(body, scopes, output)?;
+                }
+                continue;
+            }
+
+            match value {
+                Some(TemplateValue::List(items)) => {
+                    for item in items {
+                        let mut nested = scopes.to_vec();
+                        nested.push(item);
+                        render_into(body, &nested, output)?;
+                    }
+                }
+                Some(value) if value.is_truthy() => render_into(body, scopes, output)?,
+                _ => {}
+            }
+        } else if let Some(name) = tag.strip_prefix('/') {
+            bail!("unexpected closing tag '{{{{/{}}}}}'", name.trim());
+        } else if let Some(TemplateValue::Text(text)) = lookup(scopes, tag) {
+            output.push_str(text);
+        }
+    }
+
+    output.push_str(rest);
+    Ok(())
+}
+
+/// Split `rest` at the `{{/name}}` closing the section that was just opened.
+/// Returns the section body and the text after the closing tag.
+fn split_section<'a>(rest: &'a str, name: &str) -> Result<(&'a str, &'a str)> {
+    let mut depth = 0usize;
+    let mut offset = 0usize;
+
+    while let Some(start) = rest[offset..].find("{{") {
+        let tag_start = offset + start;
+        let end = rest[tag_start + 2..]
+            .find("}}")
+            .context("unclosed '{{' tag")?;
+        let tag = rest[tag_start + 2..tag_start + 2 + end].trim();
+        offset = tag_start + 2 + end + 2;
+
+        let opens = tag
+            .strip_prefix('#')
+            .or_else(|| tag.strip_prefix('^'))
+            .is_some_and(|n| n.trim() == name);
+        if opens {
+            depth += 1;
+        } else if tag.strip_prefix('/').is_some_and(|n| n.trim() == name) {
+            if depth == 0 {
+                return Ok((&rest[..tag_start], &rest[offset..]));
+            }
+            depth -= 1;
+        }
+    }
+
+    bail!("section '{name}' is never closed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> TemplateValue {
+        TemplateValue::Text(value.to_string())
+    }
+
+    fn template(source: &str) -> ExportTemplate {
+        ExportTemplate {
+            name: "test".to_string(),
+            source: source.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_variables_and_sections() -> Result<()> {
+        let mut item_a = TemplateContext::new();
+        item_a.insert("name".to_string(), text("a"));
+        item_a.insert("note".to_string(), text("hi"));
+        let mut item_b = TemplateContext::new();
+        item_b.insert("name".to_string(), text("b"));
+
+        let mut context = TemplateContext::new();
+        context.insert("title".to_string(), text("T"));
+        context.insert(
+            "items".to_string(),
+            TemplateValue::List(vec![item_a, item_b]),
+        );
+
+        let rendered = template(
+            "# {{title}}\n{{#items}}- {{name}}@{{title}}{{#note}} ({{note}}){{/note}}{{^note}} (none){{/note}}\n{{/items}}{{missing}}",
+        )
+        .render(&context)?;
+        assert_eq!(rendered, "# T\n- a@T (hi)\n- b@T (none)\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let context = TemplateContext::new();
+        assert!(template("{{#open}}never closed").render(&context).is_err());
+        assert!(template("{{/stray}}").render(&context).is_err());
+        assert!(template("{{unclosed").render(&context).is_err());
+    }
+
+    #[test]
+    fn test_builtin_templates() {
+        for (name, _) in BUILTIN_TEMPLATES {
+            assert!(ExportTemplate::builtin(name).is_some());
+        }
+        assert!(ExportTemplate::builtin("nope").is_none());
+    }
+}
@@ -0,0 +1,302 @@
+//! Branches of a session, where a prompt was edited or retried.
+//!
+//! Claude Code writes the new prompt with the same `parentUuid` as the one it
+//! replaces and carries on from there, so the session file holds both
+//! conversations, interleaved. A message with more than one prompt among its
+//! children is a fork; each of those prompts starts an alternative, and every
+//! message descending from it belongs to that alternative.
+//!
+//! Tool results, meta messages and sidechain (subagent) messages are not
+//! prompts, so parallel tool calls and subagents do not make forks.
+
+use crate::schemas::SessionMessage;
+use std::collections::HashMap;
+
+/// A message the conversation went on from more than once
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fork {
+    pub parent_uuid: String,
+    /// First message of each alternative, in file order
+    pub alternatives: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct BranchMessage {
+    role: &'static str,
+    text: String,
+    timestamp: Option<String>,
+    /// The alternatives the message descends from, as (fork, alternative)
+    /// indices, outermost first
+    path: Vec<(usize, usize)>,
+}
+
+/// The forks of one session and which alternatives each message belongs to
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionBranches {
+    forks: Vec<Fork>,
+    /// Every message with a uuid, in file order
+    messages: Vec<BranchMessage>,
+    positions: HashMap<String, usize>,
+}
+
+impl SessionBranches {
+    /// The branches of a session from its messages in file order
+    pub fn from_messages(messages: &[SessionMessage]) -> Self {
+        let mut prompts: HashMap<&str, Vec<&str>> = HashMap::new();
+        for message in messages {
+            if let (SessionMessage::User { .. }, Some(parent), Some(uuid)) =
+                (message, message.get_parent_uuid(), message.get_uuid())
+                && !message.is_tool_result_only()
+                && !message.is_meta()
+                && !message.is_sidechain()
+            {
+                let children = prompts.entry(parent).or_default();
+                // Resumed sessions can repeat a line
+                if !children.contains(&uuid) {
+                    children.push(uuid);
+                }
+            }
+        }
+
+        let mut branches = Self::default();
+        let mut starts: HashMap<&str, (usize, usize)> = HashMap::new();
+        for message in messages {
+            let Some(uuid) = message.get_uuid() else {
+                continue;
+            };
+            if matches!(message, SessionMessage::Summary { .. })
+                || branches.positions.contains_key(uuid)
+            {
+                continue;
+            }
+            if let Some(parent) = message.get_parent_uuid()
+                && let Some(children) = prompts.get(parent)
+                && children.len() > 1
+                && !starts.contains_key(children[0])
+            {
+                let fork = branches.forks.len();
+                for (alternative, child) in children.iter().enumerate() {
+                    starts.insert(child, (fork, alternative));
+                }
+                branches.forks.push(Fork {
+                    parent_uuid: parent.to_string(),
+                    alternatives: children.iter().map(|child| child.to_string()).collect(),
+                });
+            }
+
+            let mut path = message
+                .get_parent_uuid()
+                .and_then(|parent| branches.positions.get(parent))
+                .map(|&position| branches.messages[position].path.clone())
+                .unwrap_or_default();
+            if let Some(&start) = starts.get(uuid) {
+                path.push(start);
+            }
+            branches
+                .positions
+                .insert(uuid.to_string(), branches.messages.len());
+            branches.messages.push(BranchMessage {
+                role: message.get_type(),
+                text: message.get_content_text(),
+                timestamp: message.get_timestamp().map(str::to_string),
+                path,
+            });
+        }
+        branches
+    }
+
+    pub fn forks(&self) -> &[Fork] {
+        &self.forks
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forks.is_empty()
+    }
+
+    /// The last alternative of every fork, the one the session went on with
+    /// most recently
+    pub fn latest(&self) -> Vec<usize> {
+        self.forks
+            .iter()
+            .map(|fork| fork.alternatives.len() - 1)
+            .collect()
+    }
+
+    /// Whether the message is on the alternatives in `chosen` (one per fork).
+    /// Messages the branches do not know, such as ones written later, are.
+    pub fn is_visible(&self, uuid: &str, chosen: &[usize]) -> bool {
+        self.positions.get(uuid).is_none_or(|&position| {
+            self.messages[position]
+                .path
+                .iter()
+                .all(|&(fork, alternative)| chosen.get(fork) == Some(&alternative))
+        })
+    }
+
+    /// The fork and alternative the message starts, if it starts one
+    pub fn branch_start(&self, uuid: &str) -> Option<(usize, usize)> {
+        let position = *self.positions.get(uuid)?;
+        self.messages[position]
+            .path
+            .last()
+            .copied()
+            .filter(|&(fork, alternative)| self.forks[fork].alternatives[alternative] == uuid)
+    }
+
+    /// The fork switching branches at the message acts on: the innermost one
+    /// it belongs to, else the next one after it, else the last one
+    pub fn fork_at(&self, uuid: &str) -> Option<usize> {
+        let position = self.positions.get(uuid).copied();
+        if let Some(&(fork, _)) = position.and_then(|position| self.messages[position].path.last())
+        {
+            return Some(fork);
+        }
+        let position = position.unwrap_or(0);
+        self.forks
+            .iter()
+            .position(|fork| {
+                self.positions
+                    .get(&fork.parent_uuid)
+                    .is_some_and(|&parent| parent >= position)
+            })
+            .or_else(|| self.forks.len().checked_sub(1))
+    }
+
+    /// When an alternative was started
+    pub fn timestamp(&self, fork: usize, alternative: usize) -> Option<&str> {
+        let uuid = self.forks.get(fork)?.alternatives.get(alternative)?;
+        self.messages[self.positions[uuid]].timestamp.as_deref()
+    }
+
+    /// The user and assistant messages of an alternative as `role: text`,
+    /// following the alternatives in `chosen` at the forks nested in it
+    pub fn transcript(&self, fork: usize, alternative: usize, chosen: &[usize]) -> String {
+        self.messages
+            .iter()
+            .filter(|message| matches!(message.role, "user" | "assistant"))
+            .filter(|message| message.path.contains(&(fork, alternative)))
+            .filter(|message| {
+                message
+                    .path
+                    .iter()
+                    .all(|&(f, a)| f == fork || chosen.get(f) == Some(&a))
+            })
+            .map(|message| format!("{}: {}", message.role, message.text))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, uuid: &str, parent: Option<&str>, content: &str) -> SessionMessage {
+        let message = if role == "user" {
+            serde_json::json!({"role": "user", "content": content})
+        } else {
+            serde_json::json!({
+                "id": format!("msg_{uuid}"),
+                "type": "message",
+                "role": "assistant",
+                "model": "claude-sonnet-4",
+                "content": [{"type": "text", "text": content}],
+                "stop_reason": "end_turn",
+                "stop_sequence": null,
+                "usage": {
+                    "input_tokens": 1,
+                    "cache_creation_input_tokens": 0,
+                    "cache_read_input_tokens": 0,
+                    "output_tokens": 1
+                }
+            })
+        };
+        serde_json::from_value(serde_json::json!({
+            "type": role,
+            "parentUuid": parent,
+            "isSidechain": false,
+            "userType": "external",
+            "cwd": "/project",
+            "sessionId": "s1",
+            "version": "1.0.0",
+            "uuid": uuid,
+            "timestamp": format!("2024-01-01T00:00:0{}Z", uuid.len()),
+            "message": message
+        }))
+        .unwrap()
+    }
+
+    /// u1 → a1, then u1's answer was followed by two attempts at the next
+    /// prompt: u2 → a2 and its retry u3 → a3
+    fn retried_session() -> Vec<SessionMessage> {
+        vec![
+            message("user", "u1", None, "hello"),
+            message("assistant", "a1", Some("u1"), "hi"),
+            message("user", "u2", Some("a1"), "fix the bug"),
+            message("assistant", "a2", Some("u2"), "done badly"),
+            message("user", "u3", Some("a1"), "fix the bug carefully"),
+            message("assistant", "a3", Some("u3"), "done well"),
+        ]
+    }
+
+    #[test]
+    fn test_detects_retried_prompts() {
+        let branches = SessionBranches::from_messages(&retried_session());
+        assert_eq!(
+            branches.forks(),
+            [Fork {
+                parent_uuid: "a1".to_string(),
+                alternatives: vec!["u2".to_string(), "u3".to_string()],
+            }]
+        );
+        assert_eq!(branches.latest(), vec![1]);
+        assert_eq!(branches.branch_start("u3"), Some((0, 1)));
+        assert_eq!(branches.branch_start("a3"), None);
+
+        let latest = branches.latest();
+        let visible: Vec<&str> = ["u1", "a1", "u2", "a2", "u3", "a3", "later"]
+            .into_iter()
+            .filter(|uuid| branches.is_visible(uuid, &latest))
+            .collect();
+        assert_eq!(visible, ["u1", "a1", "u3", "a3", "later"]);
+        assert!(branches.is_visible("a2", &[0]));
+        assert!(!branches.is_visible("a3", &[0]));
+
+        assert_eq!(
+            branches.transcript(0, 0, &latest),
+            "user: fix the bug\n\nassistant: done badly"
+        );
+    }
+
+    #[test]
+    fn test_tool_results_do_not_fork() {
+        let mut messages = retried_session();
+        messages.truncate(4);
+        let mut tool_result = message("user", "t1", Some("a1"), "");
+        if let SessionMessage::User { message, .. } = &mut tool_result {
+            *message = serde_json::from_value(serde_json::json!({
+                "role": "user",
+                "content": [{"type": "tool_result", "tool_use_id": "x", "content": "ok"}]
+            }))
+            .unwrap();
+        }
+        messages.push(tool_result);
+        assert!(SessionBranches::from_messages(&messages).is_empty());
+    }
+
+    #[test]
+    fn test_fork_at() {
+        let mut messages = retried_session();
+        // A second fork inside the retry
+        messages.push(message("user", "u4", Some("a3"), "and test it"));
+        messages.push(message("user", "u5", Some("a3"), "and document it"));
+        let branches = SessionBranches::from_messages(&messages);
+        assert_eq!(branches.forks().len(), 2);
+        assert_eq!(branches.fork_at("a2"), Some(0));
+        assert_eq!(branches.fork_at("u5"), Some(1));
+        // Before any fork, the next one
+        assert_eq!(branches.fork_at("u1"), Some(0));
+        assert!(!branches.is_visible("u4", &[0, 0]));
+        assert!(branches.is_visible("u4", &[1, 0]));
+    }
+}
@@ -0,0 +1,136 @@
+//! Named points in time that `ccms new --since <name>` catches up from
+//! (`ccms checkpoint`).
+
+use crate::utils::config::config_dir;
+use crate::utils::time_range::parse_time;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Checkpoint names and their times, stored as RFC 3339 like message timestamps
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Checkpoints {
+    pub checkpoints: BTreeMap<String, String>,
+}
+
+impl Checkpoints {
+    /// `checkpoints.json` in the ccms config directory
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(config_dir()?.join("checkpoints.json"))
+    }
+
+    /// Load the checkpoints, treating a missing file as empty
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read checkpoints: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse checkpoints: {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write checkpoints: {}", path.display()))
+    }
+
+    /// The time of checkpoint `name`; `None` when there is no such
+    /// checkpoint or its stored time does not parse
+    pub fn get(&self, name: &str) -> Option<DateTime<Utc>> {
+        let at = DateTime::parse_from_rfc3339(self.checkpoints.get(name)?).ok()?;
+        Some(at.with_timezone(&Utc))
+    }
+
+    /// Set `name` to `at`, replacing any previous time of that name
+    pub fn set(&mut self, name: &str, at: DateTime<Utc>) {
+        self.checkpoints.insert(name.to_string(), at.to_rfc3339());
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.checkpoints.remove(name)
+    }
+
+    /// The time `since` stands for: a checkpoint name, or anything
+    /// `parse_time` understands ("yesterday", "2 hours ago", a Unix timestamp)
+    pub fn resolve(&self, since: &str) -> Result<DateTime<Utc>> {
+        match self.get(since) {
+            Some(at) => Ok(at),
+            None => parse_time(since).with_context(|| {
+                format!("'{since}' is neither a checkpoint nor a time (see `ccms checkpoint list`)")
+            }),
+        }
+    }
+}
+
+/// The files of `files` modified at or after `since`. A session file last
+/// written before the checkpoint cannot hold messages from after it, so
+/// `ccms new` skips reading it; files whose time is unknown are kept.
+pub fn files_modified_since(files: Vec<PathBuf>, since: DateTime<Utc>) -> Vec<PathBuf> {
+    files
+        .into_iter()
+        .filter(|file| {
+            file.metadata()
+                .and_then(|metadata| metadata.modified())
+                .map_or(true, |modified| DateTime::<Utc>::from(modified) >= since)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_save_load_and_resolve() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("checkpoints.json");
+        assert_eq!(Checkpoints::load(&path).unwrap(), Checkpoints::default());
+
+        let at: DateTime<Utc> = "2024-05-01T09:00:00Z".parse().unwrap();
+        let mut checkpoints = Checkpoints::default();
+        checkpoints.set("standup", at);
+        checkpoints.save(&path).unwrap();
+
+        let loaded = Checkpoints::load(&path).unwrap();
+        assert_eq!(loaded.resolve("standup").unwrap(), at);
+        assert_eq!(
+            loaded.resolve("1700000000").unwrap().timestamp(),
+            1_700_000_000
+        );
+        let err = loaded.resolve("lunch").unwrap_err();
+        assert!(format!("{err:#}").contains("neither a checkpoint nor a time"));
+    }
+
+    #[test]
+    fn test_files_modified_since() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("old.jsonl");
+        let fresh = dir.path().join("fresh.jsonl");
+        fs::write(&old, "").unwrap();
+        fs::write(&fresh, "").unwrap();
+        let day_ago = SystemTime::now() - Duration::from_secs(24 * 60 * 60);
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(day_ago - Duration::from_secs(60))
+            .unwrap();
+
+        let files = vec![old, fresh.clone(), dir.path().join("missing.jsonl")];
+        assert_eq!(
+            files_modified_since(files, day_ago.into()),
+            vec![fresh, dir.path().join("missing.jsonl")]
+        );
+    }
+}
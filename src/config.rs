@@ -0,0 +1,408 @@
+//! User defaults from `config.toml` in the ccms config directory.
+//!
+//! Every setting is optional and only fills in what the command line leaves
+//! out; flags always win. `ccms config init` writes [`TEMPLATE`] as a starting
+//! point.
+
+use crate::cost::{ModelPrice, PricingTable};
+use crate::interactive_ratatui::constants::{PREVIEW_SIZE_MAX, PREVIEW_SIZE_MIN};
+use crate::interactive_ratatui::ui::theme::{Theme, ThemeName};
+use crate::search::AutoEngine;
+use crate::search::result_cache::DEFAULT_MEMORY_CAP;
+use crate::utils::config::config_dir;
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Commented-out copy of every setting, written by `ccms config init`
+pub const TEMPLATE: &str = r##"# ccms configuration
+#
+# Each setting is a default for the search command and the interactive mode;
+# command-line flags take precedence. Uncomment the ones you want to change.
+
+# Session files to search (--pattern)
+# pattern = "~/.claude/projects/**/*.jsonl"
+
+# Search engine: "auto", "smol" or "rayon" (--engine). auto picks rayon once
+# the searched files reach either size below, and smol otherwise.
+# engine = "auto"
+# auto_engine_files = 1000
+# auto_engine_mb = 1024
+
+# Memory in MiB each cache of the interactive mode may use: the matches of
+# recent queries, and the session files opened in the viewer
+# cache_memory_mb = 512
+
+# Maximum number of results printed by a search (--max-results)
+# max_results = 200
+
+# Colored output; false is the same as --no-color
+# color = true
+
+# Only search messages with this role: user, assistant, system or summary (--role)
+# role = "user"
+
+# Leave out subagent (sidechain) and meta messages unless a query asks for
+# them with is:sidechain / is:meta; false is the same as --include-sidechain
+# / --include-meta
+# exclude_sidechain = true
+# exclude_meta = true
+
+# Leave out user messages that only carry tool results; true is the same as
+# --hide-tool-results. T in the session viewer and Alt+T in the search tab
+# toggle it at runtime.
+# hide_tool_results = false
+
+# Where the interactive mode shows the preview: "right", "bottom" or
+# "hidden", and the share of the screen it takes in percent (20-80).
+# Alt+P and Alt+- / Alt+= change these at runtime and save them here.
+# preview = "right"
+# preview_size = 60
+
+# Colors of the interactive mode: "dark", "light" or "solarized". F2 picks a
+# theme at runtime and saves it here.
+# theme = "dark"
+
+# Let the interactive mode take the mouse: click a result or a tab, scroll
+# lists and previews with the wheel and drag the border of the preview. Off
+# by default because the terminal then no longer selects text on its own;
+# most terminals still do while Shift is held.
+# mouse = false
+
+# Keep the results dismissed with Alt+X while triaging search results, so
+# the same query hides them in later runs too; Alt+U restores them.
+# Without it, dismissals last until ccms exits.
+# persist_dismissals = false
+
+# Extra keys for the interactive mode, each mapped to the key it acts as.
+# Keys are written like "ctrl+j", "alt+b", "f2", "pagedown" or "x".
+[keybindings]
+# "ctrl+j" = "down"
+# "ctrl+k" = "up"
+
+# Colors laid over the theme, as names such as "lightblue" or as "#rrggbb":
+# primary, secondary, accent, text, text_dim, background, selection,
+# success, warning, error, match (query matches) and the role colors user,
+# assistant, system and summary.
+[colors]
+# match = "#ff8700"
+# assistant = "lightblue"
+
+# Prices in USD per million tokens for the cost estimates of `ccms stats`,
+# `--format json` and the session list, by model name prefix. They replace
+# or add to the built-in list prices; the longest matching prefix wins.
+[pricing]
+# "claude-sonnet-4" = { input = 3.0, output = 15.0, cache_write = 3.75, cache_read = 0.3 }
+"##;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub pattern: Option<String>,
+    pub engine: Option<String>,
+    /// File count from which `engine = "auto"` picks rayon
+    pub auto_engine_files: Option<usize>,
+    /// Total size in MiB from which `engine = "auto"` picks rayon
+    pub auto_engine_mb: Option<u64>,
+    /// Memory cap of the result and session caches in MiB, each
+    pub cache_memory_mb: Option<u64>,
+    pub max_results: Option<usize>,
+    pub color: Option<bool>,
+    pub role: Option<String>,
+    pub exclude_sidechain: Option<bool>,
+    pub exclude_meta: Option<bool>,
+    pub hide_tool_results: Option<bool>,
+    pub preview: Option<PreviewSetting>,
+    /// Percent of the list area the preview takes
+    pub preview_size: Option<u16>,
+    pub theme: Option<ThemeName>,
+    /// Mouse capture in the interactive mode
+    pub mouse: Option<bool>,
+    /// Remembering dismissed results between runs of the interactive mode
+    pub persist_dismissals: Option<bool>,
+    /// Interactive-mode key remappings, from the pressed key to the key it
+    /// stands for
+    pub keybindings: BTreeMap<String, String>,
+    /// Interactive-mode colors laid over the theme, by slot
+    pub colors: BTreeMap<String, String>,
+    /// Model prices laid over the built-in ones, by model name prefix
+    pub pricing: BTreeMap<String, ModelPrice>,
+}
+
+/// Placement of the interactive-mode preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewSetting {
+    Right,
+    Bottom,
+    Hidden,
+}
+
+impl PreviewSetting {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PreviewSetting::Right => "right",
+            PreviewSetting::Bottom => "bottom",
+            PreviewSetting::Hidden => "hidden",
+        }
+    }
+}
+
+impl Config {
+    /// `config.toml` in the ccms config directory
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(config_dir()?.join("config.toml"))
+    }
+
+    /// Load the config, treating a missing file as empty
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config: {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("invalid config: {}", path.display()))
+    }
+
+    /// Thresholds of `--engine auto`, the defaults where unset
+    pub fn auto_engine(&self) -> AutoEngine {
+        let defaults = AutoEngine::default();
+        AutoEngine {
+            min_files: self.auto_engine_files.unwrap_or(defaults.min_files),
+            min_bytes: self
+                .auto_engine_mb
+                .map_or(defaults.min_bytes, |mb| mb * 1024 * 1024),
+        }
+    }
+
+    /// Memory cap of each cache in bytes, the default where unset
+    pub fn cache_memory_cap(&self) -> usize {
+        self.cache_memory_mb
+            .map_or(DEFAULT_MEMORY_CAP, |mb| (mb * 1024 * 1024) as usize)
+    }
+
+    /// Prices for cost estimates: the built-in table with `[pricing]` laid
+    /// over it
+    pub fn pricing(&self) -> PricingTable {
+        PricingTable::with_overrides(&self.pricing)
+    }
+
+    pub fn parse(source: &str) -> Result<Self> {
+        let config: Self = toml::from_str(source)?;
+        if let Some(size) = config.preview_size
+            && !(PREVIEW_SIZE_MIN..=PREVIEW_SIZE_MAX).contains(&size)
+        {
+            bail!(
+                "preview_size must be between {PREVIEW_SIZE_MIN} and {PREVIEW_SIZE_MAX}, got {size}"
+            );
+        }
+        Theme::with_colors(config.theme.unwrap_or_default(), &config.colors)
+            .context("invalid [colors]")?;
+        Ok(config)
+    }
+}
+
+/// Set the top-level `key` of the config at `path` to `value`, creating the
+/// file if needed. The rest of the file, comments included, is kept.
+pub fn set_value(path: &Path, key: &str, value: toml::Value) -> Result<()> {
+    let content = if path.exists() {
+        fs::read_to_string(path)
+            .with_context(|| format!("failed to read config: {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let updated = with_value(&content, key, &value);
+    Config::parse(&updated).with_context(|| format!("invalid config: {}", path.display()))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, updated).with_context(|| format!("failed to write config: {}", path.display()))
+}
+
+/// `source` with the top-level `key` replaced, or added above the first table
+fn with_value(source: &str, key: &str, value: &toml::Value) -> String {
+    let setting = format!("{key} = {value}");
+    let mut lines: Vec<&str> = source.lines().collect();
+    let first_table = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+
+    let existing = lines[..first_table].iter().position(|line| {
+        line.split_once('=')
+            .is_some_and(|(name, _)| name.trim() == key)
+    });
+    match existing {
+        Some(index) => lines[index] = &setting,
+        None if first_table == lines.len() => lines.push(&setting),
+        None => {
+            // Keep the comment describing the table with it
+            let mut index = first_table;
+            while index > 0 && lines[index - 1].trim_start().starts_with('#') {
+                index -= 1;
+            }
+            lines.splice(index..index, [setting.as_str(), ""]);
+        }
+    }
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    updated
+}
+
+/// Write [`TEMPLATE`] to `path`, refusing to replace an existing file unless
+/// `force` is set
+pub fn init(path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        bail!(
+            "{} already exists; pass --force to overwrite it",
+            path.display()
+        );
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, TEMPLATE).with_context(|| format!("failed to write config: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let config = Config::parse(
+            r##"
+            pattern = "~/archive/**/*.jsonl"
+            engine = "rayon"
+            max_results = 20
+            color = false
+            role = "assistant"
+            auto_engine_mb = 64
+            cache_memory_mb = 128
+
+            theme = "solarized"
+            mouse = true
+
+            [keybindings]
+            "ctrl+j" = "down"
+
+            [colors]
+            match = "#ff8700"
+
+            [pricing]
+            "claude-local" = { input = 1.0, output = 2.0, cache_write = 0.0, cache_read = 0.0 }
+            "##,
+        )?;
+        assert_eq!(config.pattern.as_deref(), Some("~/archive/**/*.jsonl"));
+        assert_eq!(config.engine.as_deref(), Some("rayon"));
+        assert_eq!(config.max_results, Some(20));
+        assert_eq!(config.color, Some(false));
+        assert_eq!(config.role.as_deref(), Some("assistant"));
+        assert_eq!(config.keybindings["ctrl+j"], "down");
+        assert_eq!(config.theme, Some(ThemeName::Solarized));
+        assert_eq!(config.mouse, Some(true));
+        assert_eq!(config.colors["match"], "#ff8700");
+        assert_eq!(
+            config.pricing().price("claude-local-7b").unwrap().output,
+            2.0
+        );
+        assert!(config.pricing().price("claude-sonnet-4").is_some());
+        assert_eq!(
+            config.auto_engine(),
+            AutoEngine {
+                min_files: AutoEngine::default().min_files,
+                min_bytes: 64 * 1024 * 1024,
+            }
+        );
+
+        assert_eq!(config.cache_memory_cap(), 128 * 1024 * 1024);
+        assert_eq!(Config::default().cache_memory_cap(), DEFAULT_MEMORY_CAP);
+
+        // A typo should not be silently ignored
+        assert!(Config::parse("max_result = 20").is_err());
+        assert!(Config::parse("max_results = \"many\"").is_err());
+        assert!(Config::parse("theme = \"neon\"").is_err());
+        assert!(Config::parse("[colors]\nmatch = \"reddish\"").is_err());
+        assert!(Config::parse("[colors]\nborder = \"red\"").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_is_all_defaults() -> Result<()> {
+        assert_eq!(Config::parse(TEMPLATE)?, Config::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_init_and_load() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("nested").join("config.toml");
+
+        assert_eq!(Config::load(&path)?, Config::default());
+        init(&path, false)?;
+        assert_eq!(fs::read_to_string(&path)?, TEMPLATE);
+
+        fs::write(&path, "max_results = 5")?;
+        assert!(init(&path, false).is_err());
+        assert_eq!(Config::load(&path)?.max_results, Some(5));
+        init(&path, true)?;
+        assert_eq!(Config::load(&path)?, Config::default());
+
+        fs::write(&path, "color = ")?;
+        assert!(Config::load(&path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_preview_settings() -> Result<()> {
+        let config = Config::parse("preview = \"bottom\"\npreview_size = 30")?;
+        assert_eq!(config.preview, Some(PreviewSetting::Bottom));
+        assert_eq!(config.preview_size, Some(30));
+
+        assert!(Config::parse("preview = \"left\"").is_err());
+        assert!(Config::parse("preview_size = 95").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_value() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("config.toml");
+
+        // A missing file is created
+        set_value(&path, "preview_size", toml::Value::Integer(40))?;
+        assert_eq!(fs::read_to_string(&path)?, "preview_size = 40\n");
+
+        // Settings go above the tables, and comments stay where they are
+        init(&path, true)?;
+        set_value(&path, "preview", toml::Value::String("bottom".into()))?;
+        let content = fs::read_to_string(&path)?;
+        assert!(content.contains("# preview = \"right\"\n"));
+        assert!(content.contains("\npreview = \"bottom\"\n\n# Extra keys"));
+        assert!(
+            content.contains("[keybindings]\n# \"ctrl+j\" = \"down\"\n# \"ctrl+k\" = \"up\"\n")
+        );
+        assert!(content.contains("[colors]\n# match = \"#ff8700\"\n# assistant = \"lightblue\"\n"));
+        assert!(content.ends_with("[pricing]\n# \"claude-sonnet-4\" = { input = 3.0, output = 15.0, cache_write = 3.75, cache_read = 0.3 }\n"));
+
+        // An existing setting is replaced in place
+        set_value(&path, "preview", toml::Value::String("hidden".into()))?;
+        let config = Config::load(&path)?;
+        assert_eq!(config.preview, Some(PreviewSetting::Hidden));
+        assert_eq!(fs::read_to_string(&path)?.matches("\npreview =").count(), 1);
+
+        // Values the config would reject are not written
+        assert!(set_value(&path, "preview_size", toml::Value::Integer(5)).is_err());
+        assert_eq!(Config::load(&path)?.preview_size, None);
+        assert!(set_value(&path, "theme", toml::Value::String("neon".into())).is_err());
+        set_value(&path, "theme", toml::Value::String("light".into()))?;
+        assert_eq!(Config::load(&path)?.theme, Some(ThemeName::Light));
+        Ok(())
+    }
+}
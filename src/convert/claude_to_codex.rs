@@ -1,10 +1,11 @@
 use crate::schemas::SessionMessage;
+use crate::search::compression::session_reader;
 use crate::search::discover_claude_files;
 use anyhow::{Context, Result, bail};
 use chrono::{DateTime, Datelike, Utc};
 use serde_json::{Value, json};
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::BufRead;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
@@ -160,7 +161,7 @@ fn file_contains_session_id(path: &Path, session_id: &str) -> Result<bool> {
             path.display()
         )
     })?;
-    let reader = BufReader::new(file);
+    let reader = session_reader(path, file, 8 * 1024)?;
 
     for line in reader.lines() {
         let line = line.with_context(|| format!("failed to read line from {}", path.display()))?;
@@ -199,7 +200,7 @@ fn build_rollout_jsonl(
 ) -> Result<RolloutBuild> {
     let file = fs::File::open(source_file)
         .with_context(|| format!("failed to open source file: {}", source_file.display()))?;
-    let reader = BufReader::new(file);
+    let reader = session_reader(source_file, file, 8 * 1024)?;
 
     let mut responses: Vec<RawResponseItem> = Vec::new();
     let mut skipped_summaries = 0usize;
@@ -0,0 +1,163 @@
+//! Estimated cost of token usage, from a pricing table of Claude models.
+//!
+//! The built-in prices are the public list prices in USD per million tokens;
+//! the `[pricing]` table of config.toml overrides or extends them. Session
+//! files do not record what was actually billed, so every cost is an
+//! estimate.
+
+use crate::usage::TokenTotals;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Prices of one model in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    /// Writing to the prompt cache (`cache_creation_input_tokens`)
+    pub cache_write: f64,
+    /// Reading from the prompt cache (`cache_read_input_tokens`)
+    pub cache_read: f64,
+}
+
+impl ModelPrice {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    pub fn cost(&self, tokens: &TokenTotals) -> f64 {
+        (tokens.input_tokens as f64 * self.input
+            + tokens.output_tokens as f64 * self.output
+            + tokens.cache_creation_input_tokens as f64 * self.cache_write
+            + tokens.cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+/// Prices by model name prefix, so that `claude-sonnet-4` covers
+/// `claude-sonnet-4-20250514` and later dated releases
+const BUILTIN_PRICES: &[(&str, ModelPrice)] = &[
+    ("claude-opus-4", ModelPrice::new(15.0, 75.0, 18.75, 1.5)),
+    ("claude-opus-4-5", ModelPrice::new(5.0, 25.0, 6.25, 0.5)),
+    ("claude-sonnet-4", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-haiku-4", ModelPrice::new(1.0, 5.0, 1.25, 0.1)),
+    ("claude-3-opus", ModelPrice::new(15.0, 75.0, 18.75, 1.5)),
+    ("claude-3-7-sonnet", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-5-sonnet", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-5-haiku", ModelPrice::new(0.8, 4.0, 1.0, 0.08)),
+    ("claude-3-haiku", ModelPrice::new(0.25, 1.25, 0.3, 0.03)),
+];
+
+/// Model name prefixes and their prices; the longest prefix of a model wins
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricingTable {
+    prices: BTreeMap<String, ModelPrice>,
+}
+
+impl Default for PricingTable {
+    fn default() -> Self {
+        Self {
+            prices: BUILTIN_PRICES
+                .iter()
+                .map(|(prefix, price)| (prefix.to_string(), *price))
+                .collect(),
+        }
+    }
+}
+
+impl PricingTable {
+    /// The built-in prices with `overrides` (the `[pricing]` table of
+    /// config.toml) laid over them
+    pub fn with_overrides(overrides: &BTreeMap<String, ModelPrice>) -> Self {
+        let mut table = Self::default();
+        table.prices.extend(
+            overrides
+                .iter()
+                .map(|(prefix, price)| (prefix.clone(), *price)),
+        );
+        table
+    }
+
+    /// The price of `model`, `None` for models the table does not know
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| price)
+    }
+
+    /// Estimated cost in USD of `tokens` used by `model`
+    pub fn cost(&self, model: &str, tokens: &TokenTotals) -> Option<f64> {
+        self.price(model).map(|price| price.cost(tokens))
+    }
+}
+
+/// `cost` rounded to a millionth of a dollar, to keep float noise out of
+/// JSON output
+pub fn round_usd(cost: f64) -> f64 {
+    (cost * 1_000_000.0).round() / 1_000_000.0
+}
+
+/// `cost` as a dollar amount for text output
+pub fn format_usd(cost: f64) -> String {
+    if cost > 0.0 && cost < 0.01 {
+        "<$0.01".to_string()
+    } else {
+        format!("${cost:.2}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let table = PricingTable::default();
+        assert_eq!(table.price("claude-opus-4-1-20250805").unwrap().input, 15.0);
+        assert_eq!(table.price("claude-opus-4-5-20251101").unwrap().input, 5.0);
+        assert_eq!(table.price("claude-sonnet-4-5").unwrap().output, 15.0);
+        assert!(table.price("<synthetic>").is_none());
+    }
+
+    #[test]
+    fn test_cost() {
+        let tokens = TokenTotals {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_creation_input_tokens: 200_000,
+            cache_read_input_tokens: 2_000_000,
+        };
+        let table = PricingTable::default();
+        // 3 + 1.5 + 0.75 + 0.6
+        assert_eq!(
+            round_usd(table.cost("claude-sonnet-4-20250514", &tokens).unwrap()),
+            5.85
+        );
+
+        let overrides = BTreeMap::from([(
+            "claude-sonnet-4".to_string(),
+            ModelPrice::new(1.0, 1.0, 0.0, 0.0),
+        )]);
+        let table = PricingTable::with_overrides(&overrides);
+        assert_eq!(
+            round_usd(table.cost("claude-sonnet-4-20250514", &tokens).unwrap()),
+            1.1
+        );
+        assert_eq!(table.cost("claude-local", &tokens), None);
+    }
+
+    #[test]
+    fn test_format_usd() {
+        assert_eq!(format_usd(0.0), "$0.00");
+        assert_eq!(format_usd(0.004), "<$0.01");
+        assert_eq!(format_usd(12.346), "$12.35");
+    }
+}
@@ -0,0 +1,380 @@
+//! `ccms daemon`: keeps the session files parsed in memory and answers
+//! searches over a unix domain socket, so repeated searches (`ccms --daemon`)
+//! skip reading and parsing the JSONL files.
+//!
+//! The warm corpus is a `SearchIndex` held in memory. It is refreshed as soon
+//! as the session files change, as reported by a file watcher: files that
+//! grew or changed are parsed again, new ones are added and removed ones
+//! dropped. Searches consult it exactly like the on-disk index, so a file
+//! changed but not yet refreshed, or a search the
+//! index cannot answer (thinking, tool results, outcomes, tool calls), still
+//! reads the files and gets the same results as a local search.
+//!
+//! Requests use the JSON-RPC framing of `ccms lsp-like` (see `rpc::protocol`):
+//! - `search` takes a [`DaemonSearch`] and returns a [`DaemonReply`]
+//! - `status` returns the number of files and messages held in memory
+
+use crate::index::{BuildStats, SearchIndex};
+use crate::interactive_ratatui::domain::models::SearchOrder;
+use crate::query::{QueryCondition, SearchOptions, SearchResult};
+use crate::rpc::protocol::{
+    INTERNAL_ERROR, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR, RpcError, error_response,
+    read_message, response, write_message,
+};
+use crate::search::scan_stats::ScanStats;
+use crate::search::{SearchEngineTrait, SmolEngine, discover_claude_files, pattern_root};
+use crate::tail::EVENT_SETTLE_TIME;
+use anyhow::{Context, Result, anyhow, bail};
+use notify::{RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::io::BufReader;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, mpsc};
+use std::thread;
+
+/// A search as the CLI would run it locally
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonSearch {
+    pub pattern: String,
+    pub query: QueryCondition,
+    pub options: SearchOptions,
+    pub order: SearchOrder,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonReply {
+    pub results: Vec<SearchResult>,
+    /// Number of matches before `max_results` was applied
+    pub total_count: usize,
+    pub duration_ms: u64,
+    pub scan: ScanStats,
+}
+
+/// `ccms/daemon.sock` in the user cache directory, overridable with
+/// `CCMS_DAEMON_SOCKET`
+pub fn default_socket_path() -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os("CCMS_DAEMON_SOCKET") {
+        return Ok(PathBuf::from(path));
+    }
+
+    let cache_dir = dirs::cache_dir().context("failed to resolve cache directory")?;
+    Ok(cache_dir.join("ccms").join("daemon.sock"))
+}
+
+pub struct Daemon {
+    pattern: String,
+    corpus: RwLock<Arc<SearchIndex>>,
+}
+
+impl Daemon {
+    /// A daemon for the session files matching `pattern`, with nothing
+    /// loaded until the first `refresh`
+    pub fn new(pattern: String) -> Self {
+        Self {
+            pattern,
+            corpus: RwLock::default(),
+        }
+    }
+
+    /// Bring the warm corpus up to date with the files on disk
+    pub fn refresh(&self) -> Result<BuildStats> {
+        let files = discover_claude_files(Some(&self.pattern))?;
+        // Parse outside the lock so searches go on meanwhile
+        let changes = self.snapshot().changes(&files);
+        let stats = changes.stats;
+        if !changes.is_empty() {
+            let mut corpus = self.corpus.write().unwrap();
+            // Searches still holding the previous corpus keep their copy
+            Arc::make_mut(&mut corpus).apply(changes);
+        }
+        Ok(stats)
+    }
+
+    fn snapshot(&self) -> Arc<SearchIndex> {
+        Arc::clone(&self.corpus.read().unwrap())
+    }
+
+    pub fn search(&self, request: DaemonSearch) -> Result<DaemonReply> {
+        let options = SearchOptions {
+            index: Some(self.snapshot()),
+            // Nobody reads the daemon's stderr
+            verbose: false,
+            ..request.options
+        };
+        let engine = SmolEngine::new(options);
+        let (results, duration, total_count) = engine.search_with_role_filter_and_order(
+            &request.pattern,
+            request.query,
+            None,
+            request.order,
+        )?;
+        Ok(DaemonReply {
+            results,
+            total_count,
+            duration_ms: duration.as_millis() as u64,
+            scan: engine.last_scan_stats(),
+        })
+    }
+
+    /// Answer connections on `listener` until the process exits, refreshing
+    /// the corpus whenever the session files change
+    pub fn serve(self: Arc<Self>, listener: UnixListener) -> Result<()> {
+        Arc::clone(&self).watch()?;
+
+        for stream in listener.incoming().flatten() {
+            let daemon = Arc::clone(&self);
+            thread::spawn(move || {
+                let _ = daemon.handle_connection(stream);
+            });
+        }
+        Ok(())
+    }
+
+    /// Refresh the corpus in the background after each burst of changes
+    /// under the pattern's root directory
+    fn watch(self: Arc<Self>) -> Result<()> {
+        let root = pattern_root(Some(&self.pattern));
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .with_context(|| format!("failed to watch {}", root.display()))?;
+
+        thread::spawn(move || {
+            // Events stop when the watcher is dropped
+            let _watcher = watcher;
+            while events.recv().is_ok() {
+                while events.recv_timeout(EVENT_SETTLE_TIME).is_ok() {}
+                if let Err(e) = self.refresh() {
+                    eprintln!("Warning: failed to refresh the session files: {e:#}");
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn handle_connection(&self, stream: UnixStream) -> Result<()> {
+        let mut output = stream.try_clone()?;
+        let mut input = BufReader::new(stream);
+        while let Some(body) = read_message(&mut input)? {
+            let reply = match serde_json::from_slice::<Value>(&body) {
+                Ok(message) => self.handle(message),
+                Err(e) => error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string())),
+            };
+            write_message(&mut output, &reply)?;
+        }
+        Ok(())
+    }
+
+    fn handle(&self, message: Value) -> Value {
+        let id = message.get("id").cloned().unwrap_or(Value::Null);
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let result = match message.get("method").and_then(Value::as_str) {
+            Some("search") => serde_json::from_value::<DaemonSearch>(params)
+                .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+                .and_then(|request| {
+                    self.search(request)
+                        .and_then(|reply| Ok(serde_json::to_value(reply)?))
+                        .map_err(|e| RpcError::new(INTERNAL_ERROR, format!("{e:#}")))
+                }),
+            Some("status") => {
+                let corpus = self.snapshot();
+                Ok(json!({
+                    "pattern": self.pattern,
+                    "files": corpus.files.len(),
+                    "messages": corpus.message_count(),
+                }))
+            }
+            method => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method: {}", method.unwrap_or("(none)")),
+            )),
+        };
+        match result {
+            Ok(result) => response(id, result),
+            Err(error) => error_response(id, error),
+        }
+    }
+}
+
+/// Listen on `socket`, replacing a socket file left behind by a daemon that
+/// is no longer running
+pub fn bind(socket: &Path) -> Result<UnixListener> {
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            bail!("a daemon is already listening on {}", socket.display());
+        }
+        std::fs::remove_file(socket)
+            .with_context(|| format!("failed to remove stale socket {}", socket.display()))?;
+    }
+    if let Some(parent) = socket.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    UnixListener::bind(socket).with_context(|| format!("failed to listen on {}", socket.display()))
+}
+
+/// Run `request` on the daemon listening on `socket`
+pub fn search(socket: &Path, request: &DaemonSearch) -> Result<DaemonReply> {
+    let result = call(socket, "search", serde_json::to_value(request)?)?;
+    Ok(serde_json::from_value(result)?)
+}
+
+fn call(socket: &Path, method: &str, params: Value) -> Result<Value> {
+    let mut stream = UnixStream::connect(socket)
+        .with_context(|| format!("no daemon listening on {}", socket.display()))?;
+    write_message(
+        &mut stream,
+        &json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }),
+    )?;
+    let body = read_message(&mut BufReader::new(stream))?
+        .context("the daemon closed the connection without answering")?;
+    let mut reply: Value = serde_json::from_slice(&body)?;
+    if let Some(error) = reply.get("error") {
+        return Err(anyhow!(
+            "daemon error: {}",
+            error["message"].as_str().unwrap_or("unknown error")
+        ));
+    }
+    Ok(reply["result"].take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::parse_query;
+    use std::fs::{File, OpenOptions};
+    use std::io::Write;
+    use std::time::{Duration, Instant};
+    use tempfile::tempdir;
+
+    fn user_line(uuid: &str, content: &str) -> String {
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":"{content}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:0{uuid}Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/test","version":"1"}}"#
+        )
+    }
+
+    fn request(pattern: &str, query: &str) -> DaemonSearch {
+        DaemonSearch {
+            pattern: pattern.to_string(),
+            query: parse_query(query).unwrap(),
+            options: SearchOptions::default(),
+            order: SearchOrder::Ascending,
+        }
+    }
+
+    #[test]
+    fn test_search_over_socket() -> Result<()> {
+        let dir = tempdir()?;
+        let projects = dir.path().join("projects");
+        std::fs::create_dir(&projects)?;
+        let session = projects.join("session.jsonl");
+        let mut file = File::create(&session)?;
+        writeln!(file, "{}", user_line("1", "warm hello"))?;
+        drop(file);
+        let pattern = projects.join("*.jsonl").display().to_string();
+
+        let daemon = Arc::new(Daemon::new(pattern.clone()));
+        let stats = daemon.refresh()?;
+        assert_eq!(stats.indexed, 1);
+
+        let socket = dir.path().join("daemon.sock");
+        let listener = bind(&socket)?;
+        thread::spawn(move || daemon.serve(listener));
+        assert!(bind(&socket).is_err());
+
+        let reply = search(&socket, &request(&pattern, "hello"))?;
+        assert_eq!(reply.total_count, 1);
+        assert_eq!(reply.results[0].uuid, "1");
+        // Answered from memory
+        assert_eq!(reply.scan.cache_hits, 1);
+        assert_eq!(reply.scan.files_scanned, 0);
+
+        // Lines appended since the last refresh are found all the same
+        let mut file = OpenOptions::new().append(true).open(&session)?;
+        writeln!(file, "{}", user_line("2", "hello again"))?;
+        drop(file);
+        let reply = search(&socket, &request(&pattern, "hello"))?;
+        assert_eq!(
+            reply
+                .results
+                .iter()
+                .map(|r| r.uuid.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1", "2"]
+        );
+
+        let status = call(&socket, "status", Value::Null)?;
+        assert_eq!(status["files"], 1);
+        let error = call(&socket, "nope", Value::Null).unwrap_err().to_string();
+        assert!(error.contains("unknown method: nope"), "{error}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_refresh_follows_the_files() -> Result<()> {
+        let dir = tempdir()?;
+        let projects = dir.path().join("projects");
+        std::fs::create_dir(&projects)?;
+        let first = projects.join("a.jsonl");
+        let second = projects.join("b.jsonl");
+        std::fs::write(&first, format!("{}\n", user_line("1", "one")))?;
+        let daemon = Daemon::new(projects.join("*.jsonl").display().to_string());
+        daemon.refresh()?;
+        assert_eq!(daemon.snapshot().message_count(), 1);
+
+        // Held by a search in flight
+        let before = daemon.snapshot();
+        std::fs::write(&second, format!("{}\n", user_line("2", "two")))?;
+        let stats = daemon.refresh()?;
+        assert_eq!((stats.indexed, stats.reused), (1, 1));
+        assert_eq!(daemon.snapshot().message_count(), 2);
+        assert_eq!(before.message_count(), 1);
+
+        std::fs::remove_file(&first)?;
+        assert_eq!(daemon.refresh()?.removed, 1);
+        assert_eq!(daemon.snapshot().files.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_serve_refreshes_on_changes() -> Result<()> {
+        let dir = tempdir()?;
+        let projects = dir.path().join("projects");
+        std::fs::create_dir(&projects)?;
+        let daemon = Arc::new(Daemon::new(projects.join("*.jsonl").display().to_string()));
+        daemon.refresh()?;
+        let socket = dir.path().join("daemon.sock");
+        let listener = bind(&socket)?;
+        thread::spawn({
+            let daemon = Arc::clone(&daemon);
+            move || daemon.serve(listener)
+        });
+        // Let the watcher start before writing
+        thread::sleep(Duration::from_millis(200));
+
+        std::fs::write(
+            projects.join("new.jsonl"),
+            format!("{}\n", user_line("1", "fresh")),
+        )?;
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while daemon.snapshot().message_count() == 0 {
+            assert!(Instant::now() < deadline, "the corpus was not refreshed");
+            thread::sleep(Duration::from_millis(20));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_stale_socket_is_replaced() -> Result<()> {
+        let dir = tempdir()?;
+        let socket = dir.path().join("daemon.sock");
+        drop(UnixListener::bind(&socket)?);
+        assert!(socket.exists());
+        assert!(bind(&socket).is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,487 @@
+//! Sandboxed execution of external commands for hooks.
+//!
+//! The hook point is `--watch --exec <TEMPLATE>`, which runs a command for
+//! each new match through a [`MatchHook`].
+//!
+//! Every hook command goes through [`Sandbox::run`], which enforces:
+//! - an allowlist of programs (nothing runs unless it is listed)
+//! - argument templating without a shell (see [`template`])
+//! - a timeout, after which the process is killed, along with anything it
+//!   started (on Unix, it runs in a process group of its own)
+//! - a cap on captured stdout/stderr, so a chatty command cannot flood the TUI
+//! - a per-hook rate limit
+//!
+//! Limits are read from `<config dir>/hooks.json`; every field is optional.
+
+pub mod template;
+
+pub use template::CommandTemplate;
+
+use crate::query::SearchResult;
+use crate::utils::config::config_dir;
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Name `--exec` commands are rate limited under
+const MATCH_HOOK: &str = "exec";
+
+/// How long to keep reading output after the command exits or is killed
+const CAPTURE_GRACE: Duration = Duration::from_millis(100);
+
+/// Environment variables passed through to hook commands; everything else is dropped
+const INHERITED_ENV: &[&str] = &["PATH", "HOME", "LANG", "LC_ALL", "TERM", "TMPDIR", "USER"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    /// Programs hooks may run, as bare names (`notify-send`) or absolute paths
+    pub allowed_commands: Vec<String>,
+    pub timeout_ms: u64,
+    /// Bytes kept from each of stdout and stderr
+    pub max_output_bytes: usize,
+    /// Runs allowed per hook within `rate_window_ms`
+    pub max_runs_per_window: usize,
+    pub rate_window_ms: u64,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            allowed_commands: Vec::new(),
+            timeout_ms: 5_000,
+            max_output_bytes: 64 * 1024,
+            max_runs_per_window: 10,
+            rate_window_ms: 60_000,
+        }
+    }
+}
+
+impl SandboxConfig {
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(config_dir()?.join("hooks.json"))
+    }
+
+    /// Load the config, treating a missing file as the (deny-all) default
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read hook config: {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse hook config: {}", path.display()))
+    }
+
+    pub fn allows(&self, program: &str) -> bool {
+        let is_path = program.contains('/');
+        self.allowed_commands.iter().any(|allowed| {
+            // A bare name in the allowlist only permits the program looked up on PATH
+            allowed == program && (is_path == allowed.contains('/'))
+        })
+    }
+}
+
+/// What a finished (or killed) hook command produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookOutput {
+    /// Exit code; `None` when the process was killed
+    pub status: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Output beyond `max_output_bytes` was discarded
+    pub truncated: bool,
+    pub timed_out: bool,
+}
+
+impl HookOutput {
+    pub fn success(&self) -> bool {
+        self.status == Some(0) && !self.timed_out
+    }
+}
+
+pub struct Sandbox {
+    config: SandboxConfig,
+    /// Start times of recent runs, per hook name
+    runs: HashMap<String, VecDeque<Instant>>,
+}
+
+impl Sandbox {
+    pub fn new(config: SandboxConfig) -> Self {
+        Self {
+            config,
+            runs: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &SandboxConfig {
+        &self.config
+    }
+
+    /// Run `template` for the hook named `hook` with `values` substituted
+    pub fn run(
+        &mut self,
+        hook: &str,
+        template: &CommandTemplate,
+        values: &BTreeMap<&str, String>,
+    ) -> Result<HookOutput> {
+        if !self.config.allows(template.program()) {
+            bail!(
+                "hook '{hook}': command '{}' is not in allowed_commands",
+                template.program()
+            );
+        }
+        self.check_rate_limit(hook)?;
+
+        let argv = template.render(values);
+        let mut command = Command::new(&argv[0]);
+        command
+            .args(&argv[1..])
+            .env_clear()
+            .envs(
+                INHERITED_ENV.iter().filter_map(|name| {
+                    std::env::var_os(name).map(|value| (name.to_string(), value))
+                }),
+            )
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+        let child = command
+            .spawn()
+            .with_context(|| format!("hook '{hook}': failed to start '{}'", argv[0]))?;
+        self.wait_with_limits(child)
+    }
+
+    fn check_rate_limit(&mut self, hook: &str) -> Result<()> {
+        let window = Duration::from_millis(self.config.rate_window_ms);
+        let now = Instant::now();
+        let runs = self.runs.entry(hook.to_string()).or_default();
+        while runs
+            .front()
+            .is_some_and(|start| now.duration_since(*start) >= window)
+        {
+            runs.pop_front();
+        }
+        if runs.len() >= self.config.max_runs_per_window {
+            bail!(
+                "hook '{hook}' is rate limited ({} runs per {}s)",
+                self.config.max_runs_per_window,
+                window.as_secs()
+            );
+        }
+        runs.push_back(now);
+        Ok(())
+    }
+
+    fn wait_with_limits(&self, mut child: Child) -> Result<HookOutput> {
+        let cap = self.config.max_output_bytes;
+        let stdout = child.stdout.take().map(|pipe| capture(pipe, cap));
+        let stderr = child.stderr.take().map(|pipe| capture(pipe, cap));
+
+        let deadline = Instant::now() + Duration::from_millis(self.config.timeout_ms);
+        let mut timed_out = false;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break Some(status);
+            }
+            if Instant::now() >= deadline {
+                timed_out = true;
+                kill_group(&child);
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        // Processes the command started may still hold the pipes open. Give
+        // the readers a moment, then kill those processes so the readers
+        // reach the end of their pipes and can be joined.
+        let captures = || stdout.iter().chain(stderr.iter());
+        if !wait_for_captures(captures()) {
+            kill_group(&child);
+            wait_for_captures(captures());
+        }
+        let (stdout, stdout_truncated) = take_capture(stdout);
+        let (stderr, stderr_truncated) = take_capture(stderr);
+        Ok(HookOutput {
+            status: status.and_then(|status| status.code()),
+            stdout,
+            stderr,
+            truncated: stdout_truncated || stderr_truncated,
+            timed_out,
+        })
+    }
+}
+
+/// Wait up to `CAPTURE_GRACE` for `captures` to read their pipes to the
+/// end, returning whether they all did
+fn wait_for_captures<'a>(captures: impl Iterator<Item = &'a Capture>) -> bool {
+    let grace = Instant::now() + CAPTURE_GRACE;
+    let mut finished = true;
+    for capture in captures {
+        while !capture.handle.is_finished() && Instant::now() < grace {
+            thread::sleep(Duration::from_millis(5));
+        }
+        finished &= capture.handle.is_finished();
+    }
+    finished
+}
+
+/// Kill every process left in the command's process group
+#[cfg(unix)]
+fn kill_group(child: &Child) {
+    // The group keeps the command's pid as its id for as long as any member
+    // lives, so this cannot reach an unrelated process
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_group(_child: &Child) {}
+
+struct Capture {
+    /// Bytes kept so far and whether any were dropped
+    buffer: Arc<Mutex<(Vec<u8>, bool)>>,
+    handle: thread::JoinHandle<()>,
+}
+
+/// Read a pipe to its end on a separate thread, keeping at most `cap` bytes.
+/// The rest is read and dropped so the child never blocks on a full pipe.
+fn capture(mut pipe: impl Read + Send + 'static, cap: usize) -> Capture {
+    let buffer = Arc::new(Mutex::new((Vec::new(), false)));
+    let shared = Arc::clone(&buffer);
+    let handle = thread::spawn(move || {
+        let mut chunk = [0u8; 8192];
+        while let Ok(read) = pipe.read(&mut chunk) {
+            if read == 0 {
+                break;
+            }
+            let Ok(mut guard) = shared.lock() else {
+                break;
+            };
+            let (kept, truncated) = &mut *guard;
+            let room = cap.saturating_sub(kept.len());
+            kept.extend_from_slice(&chunk[..read.min(room)]);
+            *truncated |= read > room;
+        }
+    });
+    Capture { buffer, handle }
+}
+
+fn take_capture(capture: Option<Capture>) -> (String, bool) {
+    capture
+        .and_then(|capture| {
+            if capture.handle.is_finished() {
+                let _ = capture.handle.join();
+            }
+            let guard = capture.buffer.lock().ok()?;
+            Some((String::from_utf8_lossy(&guard.0).into_owned(), guard.1))
+        })
+        .unwrap_or_default()
+}
+
+/// The command `--watch --exec` runs for each new match
+pub struct MatchHook {
+    sandbox: Sandbox,
+    template: CommandTemplate,
+}
+
+impl MatchHook {
+    /// Fails up front when the template is invalid or its program is not
+    /// allowed, rather than on every match
+    pub fn new(template: &str, config: SandboxConfig) -> Result<Self> {
+        let template = CommandTemplate::parse(template)?;
+        if !config.allows(template.program()) {
+            bail!(
+                "'{}' is not in allowed_commands of the hook config",
+                template.program()
+            );
+        }
+        Ok(Self {
+            sandbox: Sandbox::new(config),
+            template,
+        })
+    }
+
+    pub fn run(&mut self, result: &SearchResult) -> Result<HookOutput> {
+        self.sandbox
+            .run(MATCH_HOOK, &self.template, &match_values(result))
+    }
+}
+
+/// Placeholders a match fills in: `{{text}}`, `{{role}}`, `{{uuid}}`,
+/// `{{session_id}}`, `{{timestamp}}`, `{{file}}` and `{{cwd}}`
+pub fn match_values(result: &SearchResult) -> BTreeMap<&'static str, String> {
+    BTreeMap::from([
+        ("text", result.text.clone()),
+        ("role", result.role.to_string()),
+        ("uuid", result.uuid.clone()),
+        ("session_id", result.session_id.to_string()),
+        ("timestamp", result.timestamp.clone()),
+        ("file", result.file.to_string()),
+        ("cwd", result.cwd.to_string()),
+    ])
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn sandbox(config: SandboxConfig) -> Sandbox {
+        Sandbox::new(SandboxConfig {
+            allowed_commands: vec!["sh".to_string(), "echo".to_string()],
+            ..config
+        })
+    }
+
+    #[test]
+    fn test_allowlist_and_templating() -> Result<()> {
+        let mut sandbox = sandbox(SandboxConfig::default());
+        let values = BTreeMap::from([("text", "hi; echo injected".to_string())]);
+
+        let output = sandbox.run("greet", &CommandTemplate::parse("echo {{text}}")?, &values)?;
+        assert!(output.success());
+        assert_eq!(output.stdout, "hi; echo injected\n");
+
+        let denied = sandbox.run("rm", &CommandTemplate::parse("rm -rf /tmp/x")?, &values);
+        assert!(
+            denied
+                .unwrap_err()
+                .to_string()
+                .contains("not in allowed_commands")
+        );
+        let by_path = sandbox.run("echo", &CommandTemplate::parse("/bin/echo hi")?, &values);
+        assert!(by_path.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeout_and_output_cap() -> Result<()> {
+        let mut sandbox = sandbox(SandboxConfig {
+            timeout_ms: 200,
+            max_output_bytes: 16,
+            ..Default::default()
+        });
+
+        let slow = sandbox.run(
+            "slow",
+            &CommandTemplate::parse("sh -c 'sleep 5'")?,
+            &BTreeMap::new(),
+        )?;
+        assert!(slow.timed_out);
+        assert_eq!(slow.status, None);
+
+        let loud = sandbox.run(
+            "loud",
+            &CommandTemplate::parse("sh -c 'head -c 100000 /dev/zero | tr \"\\0\" x'")?,
+            &BTreeMap::new(),
+        )?;
+        assert!(loud.success());
+        assert!(loud.truncated);
+        assert_eq!(loud.stdout, "x".repeat(16));
+        Ok(())
+    }
+
+    #[test]
+    fn test_kills_what_the_command_started() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let marker = dir.path().join("marker");
+        let mut sandbox = sandbox(SandboxConfig {
+            timeout_ms: 200,
+            ..Default::default()
+        });
+
+        // The background job keeps stdout open after the command is killed
+        let template = format!("sh -c '(sleep 1; touch {}) & sleep 5'", marker.display());
+        let output = sandbox.run(
+            "slow",
+            &CommandTemplate::parse(&template)?,
+            &BTreeMap::new(),
+        )?;
+        assert!(output.timed_out);
+        thread::sleep(Duration::from_millis(1500));
+        assert!(!marker.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rate_limit() -> Result<()> {
+        let mut sandbox = sandbox(SandboxConfig {
+            max_runs_per_window: 2,
+            ..Default::default()
+        });
+        let template = CommandTemplate::parse("echo ok")?;
+
+        assert!(sandbox.run("h", &template, &BTreeMap::new()).is_ok());
+        assert!(sandbox.run("h", &template, &BTreeMap::new()).is_ok());
+        let limited = sandbox.run("h", &template, &BTreeMap::new());
+        assert!(limited.unwrap_err().to_string().contains("rate limited"));
+        // Limits are per hook
+        assert!(sandbox.run("other", &template, &BTreeMap::new()).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_match_hook() -> Result<()> {
+        let config = SandboxConfig {
+            allowed_commands: vec!["echo".to_string()],
+            ..Default::default()
+        };
+        let result = SearchResult {
+            file: "/p/s1.jsonl".into(),
+            uuid: "u1".to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s1".into(),
+            role: "user".into(),
+            text: "it's $(broken)".to_string(),
+            message_type: "user".into(),
+            query: crate::query::QueryCondition::Literal {
+                pattern: "broken".to_string(),
+                case_sensitive: false,
+            },
+            cwd: "/proj".into(),
+            raw_json: None,
+            line_number: Some(1),
+            matched_in: Vec::new(),
+            has_images: false,
+        };
+
+        let mut hook = MatchHook::new("echo {{role}} {{session_id}}: {{text}}", config.clone())?;
+        let output = hook.run(&result)?;
+        assert_eq!(output.stdout, "user s1: it's $(broken)\n");
+
+        let denied = MatchHook::new("notify-send {{text}}", config);
+        assert!(denied.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_config() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("hooks.json");
+        assert_eq!(SandboxConfig::load(&path)?, SandboxConfig::default());
+
+        fs::write(&path, r#"{"allowed_commands": ["say"], "timeout_ms": 100}"#)?;
+        let config = SandboxConfig::load(&path)?;
+        assert!(config.allows("say"));
+        assert!(!config.allows("/usr/bin/say"));
+        assert_eq!(config.timeout_ms, 100);
+        assert_eq!(
+            config.max_output_bytes,
+            SandboxConfig::default().max_output_bytes
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+//! Command templates for hooks.
+//!
+//! A template is split into arguments once, with shell-like quoting
+//! (`'single'`, `"double"`, `\` escapes), and `{{name}}` placeholders are then
+//! substituted inside each argument. Values never go through a shell and are
+//! never re-split, so a message containing spaces, quotes or `; rm -rf ~`
+//! always arrives as part of a single argument.
+
+use anyhow::{Result, bail};
+use std::collections::BTreeMap;
+
+/// Longest value substituted for a placeholder, in characters
+pub const MAX_VALUE_CHARS: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandTemplate {
+    /// Program followed by its arguments, placeholders still in place
+    words: Vec<String>,
+}
+
+impl CommandTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        let words = split_words(template)?;
+        if words.is_empty() {
+            bail!("empty command template");
+        }
+        Ok(Self { words })
+    }
+
+    pub fn program(&self) -> &str {
+        &self.words[0]
+    }
+
+    /// Program and arguments with every `{{name}}` replaced by its escaped value.
+    /// Unknown placeholders are replaced with nothing.
+    pub fn render(&self, values: &BTreeMap<&str, String>) -> Vec<String> {
+        self.words
+            .iter()
+            .map(|word| substitute(word, values))
+            .collect()
+    }
+}
+
+/// Make a value safe to pass as (part of) an argument: drop control
+/// characters other than newlines and tabs, and cap its length
+pub fn escape_value(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
+        .take(MAX_VALUE_CHARS)
+        .collect()
+}
+
+fn substitute(word: &str, values: &BTreeMap<&str, String>) -> String {
+    let mut output = String::new();
+    let mut rest = word;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let name = rest[start + 2..start + 2 + end].trim();
+        if let Some(value) = values.get(name) {
+            output.push_str(&escape_value(value));
+        }
+        rest = &rest[start + 2 + end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn split_words(input: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => bail!("unterminated single quote in command template"),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => bail!("unterminated double quote in command template"),
+                        },
+                        Some(c) => current.push(c),
+                        None => bail!("unterminated double quote in command template"),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some(c) => current.push(c),
+                    None => bail!("trailing backslash in command template"),
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quoting() -> Result<()> {
+        let template =
+            CommandTemplate::parse(r#"notify-send 'ccms: {{role}}' "said \"{{text}}\"" a\ b ''"#)?;
+        assert_eq!(template.program(), "notify-send");
+        assert_eq!(
+            template.words,
+            vec![
+                "notify-send",
+                "ccms: {{role}}",
+                "said \"{{text}}\"",
+                "a b",
+                ""
+            ]
+        );
+
+        assert!(CommandTemplate::parse("").is_err());
+        assert!(CommandTemplate::parse("echo 'open").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_keeps_values_in_one_argument() -> Result<()> {
+        let template = CommandTemplate::parse("echo {{text}} [{{missing}}]")?;
+        let values = BTreeMap::from([("text", "a b; rm -rf ~ \u{1b}[31m$(x)".to_string())]);
+        assert_eq!(
+            template.render(&values),
+            vec!["echo", "a b; rm -rf ~ [31m$(x)", "[]"]
+        );
+
+        let long = BTreeMap::from([("text", "x".repeat(MAX_VALUE_CHARS * 2))]);
+        assert_eq!(template.render(&long)[1].len(), MAX_VALUE_CHARS);
+        Ok(())
+    }
+}
@@ -0,0 +1,537 @@
+//! `ccms serve --http`: a read-only REST API over the service layer, for
+//! editor plugins and dashboards that would otherwise run `ccms` per request.
+//!
+//! Every endpoint answers `GET` with JSON:
+//! - `/search` - matching messages and a summary, as `--format json` prints them
+//! - `/sessions` - matching sessions, as `--group-by session` describes them
+//! - `/sessions/{id}` - every message of a session, oldest first
+//! - `/messages/{uuid}` - a single message
+//!
+//! `/search` and `/sessions` take the `query`, `role`, `session_id`, `project`,
+//! `limit`, `before` and `after` parameters. Errors are `{"error": "..."}`.
+//!
+//! Requests must name the server as `localhost` or by IP address in their
+//! `Host` header, so a web page cannot reach the API through DNS rebinding.
+//! Header lines and the number of connections served at once are capped, so
+//! a misbehaving client cannot exhaust memory or threads.
+
+use crate::scheduler::{Scheduler, TaskKind};
+use crate::service::{SearchParams, Service};
+use crate::utils::time_range::parse_time;
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// How long a connection may take to send its request or read the response
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest request or header line accepted, in bytes
+const MAX_HEAD_LINE: usize = 8 * 1024;
+/// Most header lines accepted in one request
+const MAX_HEADERS: usize = 100;
+/// Connections served at once; further ones are answered 503 right away
+const MAX_CONNECTIONS: usize = 64;
+
+/// Query parameters understood by `/search` and `/sessions`
+const SEARCH_PARAMETERS: &[&str] = &[
+    "query",
+    "role",
+    "session_id",
+    "project",
+    "limit",
+    "before",
+    "after",
+];
+
+#[derive(Debug, PartialEq)]
+pub struct HttpError {
+    status: &'static str,
+    message: String,
+}
+
+impl HttpError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: "400 Bad Request",
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: "404 Not Found",
+            message: message.into(),
+        }
+    }
+
+    fn internal(error: anyhow::Error) -> Self {
+        Self {
+            status: "500 Internal Server Error",
+            message: format!("{error:#}"),
+        }
+    }
+}
+
+/// Serve the API on an already bound listener
+pub fn serve_on(listener: TcpListener, service: Arc<Service>) {
+    let port = listener.local_addr().map_or(0, |addr| addr.port());
+    let active = Arc::new(AtomicUsize::new(0));
+    for mut stream in listener.incoming().flatten() {
+        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            active.fetch_sub(1, Ordering::SeqCst);
+            let _ = stream.set_write_timeout(Some(IO_TIMEOUT)).and_then(|()| {
+                let body = json!({ "error": "too many connections; try again" });
+                write_response(&mut stream, "503 Service Unavailable", &body)
+            });
+            continue;
+        }
+        let service = Arc::clone(&service);
+        let active = Arc::clone(&active);
+        // Searches can take a while; keep one from holding up the others.
+        // The connection thread only does I/O, the work itself is queued on
+        // the shared scheduler, and idle connections time out.
+        thread::spawn(move || {
+            let _ = handle_connection(stream, &service, port);
+            active.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+}
+
+fn handle_connection(stream: TcpStream, service: &Arc<Service>, port: u16) -> Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+
+    let routed = match read_head(&mut reader) {
+        Ok((request_line, host)) => {
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap_or("").to_string();
+            let target = parts.next().unwrap_or("").to_string();
+
+            if host.as_deref().is_some_and(|host| allowed_host(host, port)) {
+                let service = Arc::clone(service);
+                Scheduler::global().run(task_kind(&target), move || {
+                    route(&service, &method, &target)
+                })
+            } else {
+                Err(HttpError {
+                    status: "403 Forbidden",
+                    message: "the Host header must be localhost or an IP address".to_string(),
+                })
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => Err(HttpError {
+            status: "431 Request Header Fields Too Large",
+            message: e.to_string(),
+        }),
+        Err(e) => return Err(e.into()),
+    };
+    let (status, body) = match routed {
+        Ok(body) => ("200 OK", body),
+        Err(error) => (error.status, json!({ "error": error.message })),
+    };
+
+    write_response(&mut reader.into_inner(), status, &body)?;
+    Ok(())
+}
+
+/// The request line and `Host` header of a request, draining the other
+/// headers; GET requests carry no body
+fn read_head(reader: &mut impl BufRead) -> io::Result<(String, Option<String>)> {
+    let request_line = read_head_line(reader)?;
+    let mut host = None;
+    for _ in 0..=MAX_HEADERS {
+        let header = read_head_line(reader)?;
+        if header.trim().is_empty() {
+            return Ok((request_line, host));
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("host")
+        {
+            host = Some(value.trim().to_string());
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("more than {MAX_HEADERS} header lines"),
+    ))
+}
+
+/// One line of the request head, reading no more than `MAX_HEAD_LINE` bytes
+fn read_head_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    reader.take(MAX_HEAD_LINE as u64 + 1).read_line(&mut line)?;
+    if line.len() > MAX_HEAD_LINE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("header line longer than {MAX_HEAD_LINE} bytes"),
+        ));
+    }
+    Ok(line)
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &Value) -> io::Result<()> {
+    let body = serde_json::to_string(body)?;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+/// Whether a request with this `Host` header is meant for the server on
+/// `port`. A DNS rebinding attack reaches it under the attacker's domain
+/// name, never as `localhost` or an IP address.
+fn allowed_host(host: &str, port: u16) -> bool {
+    let (name, host_port) = match host.strip_prefix('[') {
+        Some(rest) => match rest.split_once(']') {
+            Some((name, rest)) => (name, rest.strip_prefix(':')),
+            None => return false,
+        },
+        None => match host.split_once(':') {
+            Some((name, host_port)) => (name, Some(host_port)),
+            None => (host, None),
+        },
+    };
+    let name_allowed = name.eq_ignore_ascii_case("localhost") || name.parse::<IpAddr>().is_ok();
+    name_allowed && host_port.is_none_or(|host_port| host_port.parse() == Ok(port))
+}
+
+/// What kind of work answering `target` is, for the scheduler
+fn task_kind(target: &str) -> TaskKind {
+    let path = target.split_once('?').map_or(target, |(path, _)| path);
+    match path.trim_matches('/').split('/').collect::<Vec<_>>()[..] {
+        ["search"] => TaskKind::Search,
+        ["sessions"] => TaskKind::SessionList,
+        _ => TaskKind::SessionLoad,
+    }
+}
+
+/// Answer a request for `target` (path and query string)
+pub fn route(service: &Service, method: &str, target: &str) -> Result<Value, HttpError> {
+    if method != "GET" {
+        return Err(HttpError {
+            status: "405 Method Not Allowed",
+            message: format!("{method} is not supported; use GET"),
+        });
+    }
+
+    let (path, query_string) = target.split_once('?').unwrap_or((target, ""));
+    let parameters = parse_query_string(query_string)?;
+    let segments: Vec<String> = path
+        .trim_matches('/')
+        .split('/')
+        .map(percent_decode)
+        .collect::<Result<_, _>>()?;
+
+    match segments.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["search"] => {
+            let params = search_params(service, &parameters)?;
+            let page = service.search(&params).map_err(HttpError::internal)?;
+            Ok(json!({
+                "results": page.results,
+                "summary": {
+                    "duration_ms": page.duration_ms,
+                    "total_count": page.total_count,
+                    "returned_count": page.results.len(),
+                },
+            }))
+        }
+        ["sessions"] => {
+            let params = search_params(service, &parameters)?;
+            let sessions = service
+                .list_sessions(&params)
+                .map_err(HttpError::internal)?;
+            Ok(json!({ "sessions": sessions }))
+        }
+        ["sessions", session_id] => {
+            let messages = service
+                .get_session(session_id)
+                .map_err(HttpError::internal)?;
+            if messages.is_empty() {
+                return Err(HttpError::not_found(format!(
+                    "session not found: {session_id}"
+                )));
+            }
+            Ok(json!({ "session_id": session_id, "messages": messages }))
+        }
+        ["messages", uuid] => match service.get_message(uuid).map_err(HttpError::internal)? {
+            Some(message) => Ok(json!(message)),
+            None => Err(HttpError::not_found(format!("message not found: {uuid}"))),
+        },
+        _ => Err(HttpError::not_found(format!("no endpoint at {path}"))),
+    }
+}
+
+fn search_params(
+    service: &Service,
+    parameters: &HashMap<String, String>,
+) -> Result<SearchParams, HttpError> {
+    if let Some(name) = parameters
+        .keys()
+        .find(|name| !SEARCH_PARAMETERS.contains(&name.as_str()))
+    {
+        return Err(HttpError::bad_request(format!(
+            "unknown parameter '{name}' (available: {})",
+            SEARCH_PARAMETERS.join(", ")
+        )));
+    }
+
+    let get = |name: &str| parameters.get(name).cloned();
+    let time = |name: &str| {
+        get(name)
+            .map(|value| {
+                parse_time(&value)
+                    .map(|time| time.to_rfc3339())
+                    .map_err(|e| HttpError::bad_request(format!("invalid {name}: {e:#}")))
+            })
+            .transpose()
+    };
+    let params = SearchParams {
+        query: get("query").unwrap_or_default(),
+        role: get("role"),
+        session_id: get("session_id"),
+        project: get("project"),
+        max_results: get("limit")
+            .map(|limit| {
+                limit
+                    .parse()
+                    .map_err(|_| HttpError::bad_request(format!("invalid limit: {limit}")))
+            })
+            .transpose()?,
+        before: time("before")?,
+        after: time("after")?,
+    };
+    // Report a malformed query as the client's mistake rather than a failed search
+    service
+        .parse(&params.query)
+        .map_err(|e| HttpError::bad_request(format!("invalid query: {e:#}")))?;
+    Ok(params)
+}
+
+fn parse_query_string(query_string: &str) -> Result<HashMap<String, String>, HttpError> {
+    query_string
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((percent_decode(name)?, percent_decode(value)?))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes, and `+` as a space
+fn percent_decode(input: &str) -> Result<String, HttpError> {
+    let invalid = || HttpError::bad_request(format!("invalid percent-encoding in '{input}'"));
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut rest = input.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = tail.get(..2).ok_or_else(invalid)?;
+                let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+                rest = &tail[2..];
+                continue;
+            }
+            byte => bytes.push(byte),
+        }
+        rest = tail;
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::SearchOptions;
+    use std::fs;
+    use std::io::Read;
+    use tempfile::tempdir;
+
+    fn message(uuid: &str, session_id: &str, timestamp: &str, text: &str) -> String {
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":"{text}"}},"uuid":"{uuid}","timestamp":"{timestamp}","sessionId":"{session_id}","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/work","version":"1"}}"#
+        ) + "\n"
+    }
+
+    fn service(dir: &std::path::Path) -> Result<Service> {
+        fs::write(
+            dir.join("s1.jsonl"),
+            message("u1", "s1", "2024-01-01T00:00:00Z", "deploy the app")
+                + &message("u2", "s1", "2024-01-02T00:00:00Z", "check the logs"),
+        )?;
+        fs::write(
+            dir.join("s2.jsonl"),
+            message("u3", "s2", "2024-03-01T00:00:00Z", "deploy again"),
+        )?;
+        Ok(Service::new(
+            format!("{}/**/*.jsonl", dir.display()),
+            SearchOptions::default(),
+        ))
+    }
+
+    fn uuids(messages: &Value) -> Vec<&str> {
+        messages
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|message| message["uuid"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_endpoints() -> Result<()> {
+        let dir = tempdir()?;
+        let service = service(dir.path())?;
+        let get = |target: &str| route(&service, "GET", target);
+
+        let page = get("/search?query=deploy").unwrap();
+        assert_eq!(uuids(&page["results"]), vec!["u3", "u1"]);
+        assert_eq!(page["summary"]["total_count"], 2);
+
+        let page = get("/search?query=deploy+AND+app&before=2024-02-01").unwrap();
+        assert_eq!(uuids(&page["results"]), vec!["u1"]);
+        let page = get("/search?query=%22check%20the%22&limit=5").unwrap();
+        assert_eq!(uuids(&page["results"]), vec!["u2"]);
+
+        let sessions = get("/sessions?query=deploy").unwrap();
+        assert_eq!(sessions["sessions"][0]["session_id"], "s2");
+        assert_eq!(sessions["sessions"][1]["message_count"], 1);
+        assert_eq!(
+            get("/sessions?limit=1").unwrap()["sessions"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let session = get("/sessions/s1").unwrap();
+        assert_eq!(uuids(&session["messages"]), vec!["u1", "u2"]);
+        assert_eq!(get("/messages/u2").unwrap()["text"], "check the logs");
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() -> Result<()> {
+        let dir = tempdir()?;
+        let service = service(dir.path())?;
+        let status =
+            |method: &str, target: &str| route(&service, method, target).unwrap_err().status;
+
+        assert_eq!(status("GET", "/sessions/missing"), "404 Not Found");
+        assert_eq!(status("GET", "/messages/missing"), "404 Not Found");
+        assert_eq!(status("GET", "/nope"), "404 Not Found");
+        assert_eq!(status("POST", "/search"), "405 Method Not Allowed");
+        for target in [
+            "/search?query=(unclosed",
+            "/search?limit=many",
+            "/search?after=whenever",
+            "/search?q=deploy",
+            "/search?query=%zz",
+        ] {
+            assert_eq!(status("GET", target), "400 Bad Request", "{target}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_kind() {
+        assert_eq!(task_kind("/search?query=deploy"), TaskKind::Search);
+        assert_eq!(task_kind("/sessions?project=/work"), TaskKind::SessionList);
+        assert_eq!(task_kind("/sessions/s1"), TaskKind::SessionLoad);
+        assert_eq!(task_kind("/messages/u1"), TaskKind::SessionLoad);
+    }
+
+    #[test]
+    fn test_serve() -> Result<()> {
+        let dir = tempdir()?;
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let service = Arc::new(service(dir.path())?);
+        thread::spawn(move || serve_on(listener, service));
+
+        let request = |target: &str| -> Result<String> {
+            let mut stream = TcpStream::connect(addr)?;
+            write!(stream, "GET {target} HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+            let mut response = String::new();
+            stream.read_to_string(&mut response)?;
+            Ok(response)
+        };
+
+        let response = request("/messages/u1")?;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: application/json\r\n"));
+        assert!(response.contains(r#""uuid":"u1""#));
+
+        let response = request("/messages/missing")?;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(response.ends_with(r#"{"error":"message not found: missing"}"#));
+
+        // A page that rebound its own domain to 127.0.0.1
+        let mut stream = TcpStream::connect(addr)?;
+        write!(
+            stream,
+            "GET /messages/u1 HTTP/1.1\r\nHost: evil.example:{}\r\n\r\n",
+            addr.port()
+        )?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+        assert!(!response.contains("u1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_head_limits() {
+        let (line, host) =
+            read_head(&mut "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n".as_bytes()).unwrap();
+        assert_eq!(line, "GET / HTTP/1.1\r\n");
+        assert_eq!(host.as_deref(), Some("localhost"));
+
+        let long = format!(
+            "GET / HTTP/1.1\r\nX-Long: {}\r\n\r\n",
+            "a".repeat(MAX_HEAD_LINE)
+        );
+        let error = read_head(&mut long.as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let many = format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            "X-A: b\r\n".repeat(MAX_HEADERS + 1)
+        );
+        assert!(read_head(&mut many.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_allowed_host() {
+        for host in [
+            "localhost",
+            "localhost:8080",
+            "LOCALHOST:8080",
+            "127.0.0.1:8080",
+            "192.168.1.5:8080",
+            "[::1]:8080",
+            "[::1]",
+        ] {
+            assert!(allowed_host(host, 8080), "{host}");
+        }
+        for host in [
+            "evil.example:8080",
+            "localhost.evil.example:8080",
+            "127.0.0.1:9090",
+            "localhost:",
+            "[::1",
+            "",
+        ] {
+            assert!(!allowed_host(host, 8080), "{host}");
+        }
+    }
+}
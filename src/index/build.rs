@@ -0,0 +1,189 @@
+use super::{FileStamp, IndexedFile, IndexedMessage, SearchIndex};
+use crate::metrics;
+use crate::schemas::SessionMessage;
+use crate::search::compression::session_reader;
+use anyhow::Result;
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+/// What an index build did with each file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuildStats {
+    /// Files that were (re)parsed
+    pub indexed: usize,
+    /// Files whose previous entry was still fresh
+    pub reused: usize,
+    /// Previously indexed files that no longer exist or were not part of this build
+    pub removed: usize,
+    /// Files that could not be read
+    pub failed: usize,
+}
+
+/// Entries to bring an index up to date with a set of files, worked out
+/// without holding on to the index so it can keep serving searches meanwhile
+#[derive(Debug, Default)]
+pub struct IndexChanges {
+    /// Fresh entries for new and changed files
+    updated: Vec<(String, IndexedFile)>,
+    /// Files to drop: gone, no longer searched, or unreadable
+    removed: Vec<String>,
+    pub stats: BuildStats,
+}
+
+impl IndexChanges {
+    pub fn is_empty(&self) -> bool {
+        self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl SearchIndex {
+    /// Build an index for `files`, reusing entries of `previous` whose file has
+    /// not changed since
+    pub fn build(files: &[PathBuf], previous: Option<SearchIndex>) -> (SearchIndex, BuildStats) {
+        let mut index = previous.unwrap_or_default();
+        let changes = index.changes(files);
+        let stats = changes.stats;
+        index.apply(changes);
+        (index, stats)
+    }
+
+    /// What it takes to index exactly `files`: files that are new or changed
+    /// since they were indexed are parsed again, the others keep their entry
+    pub fn changes(&self, files: &[PathBuf]) -> IndexChanges {
+        let entries: Vec<(String, Option<Option<IndexedFile>>)> = files
+            .par_iter()
+            .map(|path| {
+                let key = path.display().to_string();
+                if self.fresh_entry(path).is_some() {
+                    return (key, None);
+                }
+                (key, Some(index_file(path).ok()))
+            })
+            .collect();
+
+        let mut changes = IndexChanges::default();
+        let mut searched = HashSet::new();
+        for (key, entry) in entries {
+            match entry {
+                None => changes.stats.reused += 1,
+                Some(Some(entry)) => {
+                    changes.stats.indexed += 1;
+                    changes.updated.push((key.clone(), entry));
+                }
+                Some(None) => {
+                    changes.stats.failed += 1;
+                    changes.removed.push(key.clone());
+                }
+            }
+            searched.insert(key);
+        }
+        let gone: Vec<String> = self
+            .files
+            .keys()
+            .filter(|key| !searched.contains(*key))
+            .cloned()
+            .collect();
+        changes.stats.removed = gone.len();
+        changes.removed.extend(gone);
+
+        changes
+    }
+
+    pub fn apply(&mut self, changes: IndexChanges) {
+        for key in changes.removed {
+            self.files.remove(&key);
+        }
+        self.files.extend(changes.updated);
+    }
+}
+
+/// Extract every message of a session file the way a full scan sees it
+pub fn index_file(path: &Path) -> Result<IndexedFile> {
+    // Take the stamp before reading so a write during indexing leaves the entry stale
+    let stamp = FileStamp::of(path)?;
+    let mut reader = session_reader(path, File::open(path)?, 64 * 1024)?;
+
+    // Fallback timestamp for messages without one, as in the search engines
+    let file_ctime =
+        chrono::DateTime::<chrono::Utc>::from_timestamp((stamp.mtime_ms / 1000) as i64, 0)
+            .unwrap_or_else(chrono::Utc::now)
+            .to_rfc3339();
+
+    let mut messages = Vec::new();
+    let mut latest_timestamp: Option<String> = None;
+    let mut first_timestamp: Option<String> = None;
+    let mut line_buffer = Vec::with_capacity(16 * 1024);
+    let mut is_first_line = true;
+    let mut found_summary_first = false;
+    let mut line_number = 0usize;
+    let mut offset = 0u64;
+
+    loop {
+        line_buffer.clear();
+        let line_offset = offset;
+        let read = reader.read_until(b'\n', &mut line_buffer)?;
+        if read == 0 {
+            break;
+        }
+        offset += read as u64;
+        line_number += 1;
+        if line_buffer.trim_ascii().is_empty() {
+            continue;
+        }
+
+        let Ok(message) = sonic_rs::from_slice::<SessionMessage>(line_buffer.trim_ascii_end())
+        else {
+            metrics::PARSE_ERRORS.inc();
+            continue;
+        };
+        let message_type = message.get_type();
+
+        if is_first_line {
+            is_first_line = false;
+            found_summary_first = message_type == "summary";
+        }
+
+        if let Some(ts) = message.get_timestamp() {
+            latest_timestamp = Some(ts.to_string());
+            if first_timestamp.is_none() && found_summary_first {
+                first_timestamp = Some(ts.to_string());
+            }
+        }
+
+        let timestamp = message
+            .get_timestamp()
+            .map(|ts| ts.to_string())
+            .or_else(|| {
+                if message_type == "summary" {
+                    first_timestamp.clone()
+                } else {
+                    latest_timestamp.clone()
+                }
+            })
+            .unwrap_or_else(|| file_ctime.clone());
+
+        let searchable_text = message.get_searchable_text();
+        let content_text = message.get_content_text();
+        messages.push(IndexedMessage {
+            line: line_number,
+            offset: line_offset,
+            uuid: message.get_uuid().unwrap_or("").to_string(),
+            timestamp,
+            session_id: message.get_session_id().unwrap_or("").to_string(),
+            message_type: message_type.to_string(),
+            cwd: message.get_cwd().unwrap_or("").to_string(),
+            is_sidechain: message.is_sidechain(),
+            is_meta: message.is_meta(),
+            has_images: message.has_images(),
+            is_tool_result_only: message.is_tool_result_only(),
+            content_text: (content_text != searchable_text).then_some(content_text),
+            searchable_text,
+        });
+    }
+
+    metrics::FILES_INDEXED.inc();
+    Ok(IndexedFile { stamp, messages })
+}
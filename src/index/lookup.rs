@@ -0,0 +1,65 @@
+use super::SearchIndex;
+use crate::query::{Interner, QueryCondition, SearchResult, SharedStr};
+use crate::schemas::SessionMessage;
+use crate::search::compression::{is_compressed, session_reader};
+use anyhow::Result;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+impl SearchIndex {
+    /// Find the message with `uuid` among `files` and read its line straight
+    /// from its session file.
+    ///
+    /// Returns `None` when the message is not indexed or its line is no
+    /// longer where the index left it; callers then scan for it instead.
+    /// Session files only ever grow, so an entry whose file has been appended
+    /// to since is still usable.
+    pub fn find_message(&self, uuid: &str, files: &[PathBuf]) -> Option<SearchResult> {
+        let (path, file, message) = files.iter().find_map(|path| {
+            let (file, entry) = self.files.get_key_value(&path.display().to_string())?;
+            let message = entry.messages.iter().find(|message| message.uuid == uuid)?;
+            Some((path, file, message))
+        })?;
+
+        let line = read_line_at(path, message.offset).ok()?;
+        let parsed: SessionMessage = sonic_rs::from_str(&line).ok()?;
+        if parsed.get_uuid() != Some(uuid) {
+            return None;
+        }
+
+        let query = QueryCondition::Literal {
+            pattern: uuid.to_string(),
+            case_sensitive: false,
+        };
+        let interner = Interner::new();
+        let mut result = message.to_result(&SharedStr::from(file), &query, &mut interner.local());
+        result.raw_json = Some(line);
+        Some(result)
+    }
+}
+
+/// Look `uuid` up among `files` in the index at `index_path`, if there is one
+pub fn lookup_message(index_path: &Path, uuid: &str, files: &[PathBuf]) -> Option<SearchResult> {
+    SearchIndex::load(index_path)
+        .ok()?
+        .find_message(uuid, files)
+}
+
+/// The line starting `offset` bytes into the (decompressed) session file
+fn read_line_at(path: &Path, offset: u64) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut reader: Box<dyn BufRead> = if is_compressed(path) {
+        // Archives cannot seek; decompress up to the line instead
+        let mut reader = session_reader(path, file, 64 * 1024)?;
+        io::copy(&mut reader.by_ref().take(offset), &mut io::sink())?;
+        reader
+    } else {
+        file.seek(SeekFrom::Start(offset))?;
+        Box::new(BufReader::new(file))
+    };
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    Ok(line.trim_end().to_string())
+}
@@ -0,0 +1,449 @@
+//! Persistent on-disk search index.
+//!
+//! The index stores the already-extracted text of every message, per session
+//! file, together with the file's mtime and size. Searches consult it for files
+//! that have not changed since `ccms index build` ran and fall back to a full
+//! scan for anything new or modified.
+
+mod build;
+mod lookup;
+
+pub use build::{BuildStats, IndexChanges, index_file};
+pub use lookup::lookup_message;
+
+use crate::query::interner::LocalInterner;
+use crate::query::{
+    Interner, QueryCondition, SearchOptions, SearchResult, SessionBounds, SharedStr,
+};
+use crate::utils::path_encoding;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+/// Bumped whenever the on-disk layout or the extracted fields change
+pub const INDEX_VERSION: u32 = 6;
+
+/// Size and modification time used to tell whether an indexed file is stale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub mtime_ms: u64,
+    pub size: u64,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> Result<Self> {
+        let metadata = fs::metadata(path)?;
+        let mtime_ms = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        Ok(Self {
+            mtime_ms,
+            size: metadata.len(),
+        })
+    }
+}
+
+/// A message as extracted during indexing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedMessage {
+    /// 1-based line of the message in its session file
+    pub line: usize,
+    /// Byte offset of that line, in the decompressed stream for archives
+    pub offset: u64,
+    pub uuid: String,
+    /// Resolved the same way a full scan resolves it (summaries and fallbacks included)
+    pub timestamp: String,
+    pub session_id: String,
+    pub message_type: String,
+    pub cwd: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_sidechain: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_meta: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub has_images: bool,
+    /// A user message carrying nothing but tool results (`--hide-tool-results`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_tool_result_only: bool,
+    /// Text queries are evaluated against
+    pub searchable_text: String,
+    /// Text shown in results; omitted when identical to `searchable_text`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_text: Option<String>,
+}
+
+impl IndexedMessage {
+    pub fn content_text(&self) -> &str {
+        self.content_text
+            .as_deref()
+            .unwrap_or(&self.searchable_text)
+    }
+
+    /// The search result a full scan of `file` would produce for this message
+    pub fn to_result(
+        &self,
+        file: &SharedStr,
+        query: &QueryCondition,
+        strings: &mut LocalInterner<'_>,
+    ) -> SearchResult {
+        let message_type = strings.intern(&self.message_type);
+        SearchResult {
+            file: file.clone(),
+            uuid: self.uuid.clone(),
+            timestamp: self.timestamp.clone(),
+            session_id: strings.intern(&self.session_id),
+            role: message_type.clone(),
+            text: self.content_text().to_string(),
+            message_type,
+            query: query.clone(),
+            cwd: strings.intern(&self.cwd),
+            raw_json: None,
+            line_number: Some(self.line),
+            matched_in: Vec::new(),
+            has_images: self.has_images,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedFile {
+    pub stamp: FileStamp,
+    pub messages: Vec<IndexedMessage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchIndex {
+    pub version: u32,
+    /// Keyed by the session file path as displayed in search results
+    pub files: HashMap<String, IndexedFile>,
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            files: HashMap::new(),
+        }
+    }
+}
+
+impl SearchIndex {
+    /// `ccms/index.json` in the user cache directory, overridable with `CCMS_INDEX_PATH`
+    pub fn default_path() -> Result<PathBuf> {
+        if let Some(path) = std::env::var_os("CCMS_INDEX_PATH") {
+            return Ok(PathBuf::from(path));
+        }
+
+        let cache_dir = dirs::cache_dir().context("failed to resolve cache directory")?;
+        Ok(cache_dir.join("ccms").join("index.json"))
+    }
+
+    /// Load the index, treating a missing file or an index written by another
+    /// version of ccms as empty
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let bytes =
+            fs::read(path).with_context(|| format!("failed to read index: {}", path.display()))?;
+        let index: Self = sonic_rs::from_slice(&bytes)
+            .with_context(|| format!("failed to parse index: {}", path.display()))?;
+        if index.version != INDEX_VERSION {
+            return Ok(Self::default());
+        }
+        Ok(index)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first so a concurrent search never reads a partial index
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec(self)?)
+            .with_context(|| format!("failed to write index: {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("failed to write index: {}", path.display()))
+    }
+
+    pub fn message_count(&self) -> usize {
+        self.files.values().map(|file| file.messages.len()).sum()
+    }
+
+    /// Indexed entry for `path`, if it is still fresh
+    pub fn fresh_entry(&self, path: &Path) -> Option<&IndexedFile> {
+        let entry = self.files.get(&path.display().to_string())?;
+        let stamp = FileStamp::of(path).ok()?;
+        (entry.stamp == stamp).then_some(entry)
+    }
+
+    /// Search a file through the index.
+    ///
+    /// Returns `None` when the file is not indexed or has changed since it was,
+    /// or when the search needs thinking, tool result or image sections,
+    /// session outcomes or tool calls, which the index does not keep, in which
+    /// case the caller should scan the file itself.
+    pub fn search_file(
+        &self,
+        path: &Path,
+        query: &QueryCondition,
+        options: &SearchOptions,
+        interner: &Interner,
+    ) -> Option<Vec<SearchResult>> {
+        if !options.sections_for(query).is_empty()
+            || !query.outcomes().is_empty()
+            || options.tool.is_some()
+        {
+            return None;
+        }
+        let entry = self.fresh_entry(path)?;
+        let file = path.display().to_string();
+
+        if let Some(project_path) = &options.project_path
+            && !path_encoding::file_belongs_to_project(&file, project_path)
+        {
+            return Some(Vec::new());
+        }
+
+        let positions = options.positions_for(query);
+        let kinds = query.kinds();
+        let mut bounds = SessionBounds::default();
+        if !positions.is_empty() {
+            for message in entry
+                .messages
+                .iter()
+                .filter(|m| m.message_type != "summary")
+            {
+                bounds.record(message.line);
+            }
+        }
+
+        let file = interner.intern(&file);
+        let mut strings = interner.local();
+        let results = entry
+            .messages
+            .iter()
+            .filter(|message| options.admits(&kinds, message.is_sidechain, message.is_meta))
+            .filter(|message| {
+                !(options.exclude_tool_result_messages && message.is_tool_result_only)
+            })
+            .filter(|message| {
+                query
+                    .evaluate_at(&message.searchable_text, Some(&message.timestamp))
+                    .unwrap_or(false)
+            })
+            .filter(|message| positions.is_empty() || bounds.holds(&positions, Some(message.line)))
+            .filter(|message| match &options.role {
+                // Summaries only match when explicitly filtering for them
+                Some(role) => &message.message_type == role,
+                None => true,
+            })
+            .filter(|message| {
+                options
+                    .session_id
+                    .as_ref()
+                    .is_none_or(|session_id| &message.session_id == session_id)
+            })
+            .map(|message| message.to_result(&file, query, &mut strings))
+            .collect();
+
+        Some(results)
+    }
+}
+
+/// Load the index a search should consult, if any.
+///
+/// Searches that need the raw JSON of each message (session viewer, message
+/// lookups) always scan the files, since the index does not keep it.
+pub fn load_for_search(options: &SearchOptions) -> Option<Arc<SearchIndex>> {
+    if options.session_id.is_some() || options.message_id.is_some() {
+        return None;
+    }
+    if let Some(index) = &options.index {
+        return Some(Arc::clone(index));
+    }
+
+    match SearchIndex::load(options.index_path.as_ref()?) {
+        Ok(index) => Some(Arc::new(index)),
+        Err(e) => {
+            if options.verbose {
+                eprintln!("Ignoring search index: {e:#}");
+            }
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::parse_query;
+    use std::fs::{File, OpenOptions};
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    const USER_LINE: &str = r#"{"type":"user","message":{"role":"user","content":"Hello world"},"uuid":"u1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/test","version":"1.0"}"#;
+    const SYSTEM_LINE: &str = r#"{"type":"system","content":"hello from system","uuid":"u2","timestamp":"2024-01-01T00:00:01Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/test","version":"1","isMeta":false}"#;
+
+    const TOOL_RESULT_LINE: &str = r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"hello from a tool"}]},"uuid":"u3","timestamp":"2024-01-01T00:00:02Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/test","version":"1.0"}"#;
+
+    fn write_session(path: &Path, lines: &[&str]) -> Result<()> {
+        let mut file = File::create(path)?;
+        for line in lines {
+            writeln!(file, "{line}")?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_save_and_load() -> Result<()> {
+        let dir = tempdir()?;
+        let session = dir.path().join("session.jsonl");
+        write_session(&session, &[USER_LINE, SYSTEM_LINE])?;
+
+        let (index, stats) = SearchIndex::build(std::slice::from_ref(&session), None);
+        assert_eq!(stats.indexed, 1);
+        assert_eq!(index.message_count(), 2);
+
+        let index_path = dir.path().join("cache").join("index.json");
+        index.save(&index_path)?;
+        assert_eq!(SearchIndex::load(&index_path)?, index);
+        assert_eq!(
+            SearchIndex::load(&dir.path().join("missing.json"))?,
+            SearchIndex::default()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_file_through_index() -> Result<()> {
+        let dir = tempdir()?;
+        let session = dir.path().join("session.jsonl");
+        write_session(&session, &[USER_LINE, SYSTEM_LINE])?;
+        let (index, _) = SearchIndex::build(std::slice::from_ref(&session), None);
+
+        let query = parse_query("hello")?;
+        let results = index
+            .search_file(
+                &session,
+                &query,
+                &SearchOptions::default(),
+                &Interner::new(),
+            )
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].uuid, "u1");
+        assert_eq!(results[0].text, "Hello world");
+
+        let options = SearchOptions {
+            role: Some("system".to_string()),
+            ..Default::default()
+        };
+        let results = index
+            .search_file(&session, &query, &options, &Interner::new())
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].role, "system");
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_file_hides_tool_results() -> Result<()> {
+        let dir = tempdir()?;
+        let session = dir.path().join("session.jsonl");
+        write_session(&session, &[USER_LINE, TOOL_RESULT_LINE])?;
+        let (index, _) = SearchIndex::build(std::slice::from_ref(&session), None);
+
+        let query = parse_query("hello")?;
+        let search = |options: &SearchOptions| {
+            index
+                .search_file(&session, &query, options, &Interner::new())
+                .unwrap()
+        };
+        assert_eq!(search(&SearchOptions::default()).len(), 2);
+
+        let options = SearchOptions {
+            exclude_tool_result_messages: true,
+            ..Default::default()
+        };
+        let results = search(&options);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].uuid, "u1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_stale_entries_fall_back_and_rebuild_incrementally() -> Result<()> {
+        let dir = tempdir()?;
+        let first = dir.path().join("a.jsonl");
+        let second = dir.path().join("b.jsonl");
+        write_session(&first, &[USER_LINE])?;
+        write_session(&second, &[SYSTEM_LINE])?;
+        let files = vec![first.clone(), second.clone()];
+        let (index, _) = SearchIndex::build(&files, None);
+
+        let mut file = OpenOptions::new().append(true).open(&first)?;
+        writeln!(file, "{SYSTEM_LINE}")?;
+        drop(file);
+
+        let query = parse_query("hello")?;
+        assert!(
+            index
+                .search_file(&first, &query, &SearchOptions::default(), &Interner::new())
+                .is_none()
+        );
+        assert!(
+            index
+                .search_file(&second, &query, &SearchOptions::default(), &Interner::new())
+                .is_some()
+        );
+
+        let (rebuilt, stats) = SearchIndex::build(&files[..1], Some(index));
+        assert_eq!(stats.indexed, 1);
+        assert_eq!(stats.reused, 0);
+        assert_eq!(stats.removed, 1);
+        assert_eq!(rebuilt.message_count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_message() -> Result<()> {
+        let dir = tempdir()?;
+        let plain = dir.path().join("a.jsonl");
+        write_session(&plain, &[USER_LINE, SYSTEM_LINE])?;
+        let archived = dir.path().join("b.jsonl.gz");
+        let mut encoder =
+            flate2::write::GzEncoder::new(File::create(&archived)?, flate2::Compression::default());
+        writeln!(encoder, "{}", USER_LINE.replace("u1", "u3"))?;
+        writeln!(encoder, "{}", SYSTEM_LINE.replace("u2", "u4"))?;
+        encoder.finish()?;
+        let files = vec![plain.clone(), archived.clone()];
+        let (index, _) = SearchIndex::build(&files, None);
+
+        let result = index.find_message("u2", &files).unwrap();
+        assert_eq!(result.line_number, Some(2));
+        assert_eq!(result.raw_json.as_deref(), Some(SYSTEM_LINE));
+        let result = index.find_message("u4", &files).unwrap();
+        assert_eq!(result.file, archived.display().to_string());
+        assert!(result.raw_json.unwrap().contains("\"u4\""));
+        assert!(index.find_message("missing", &files).is_none());
+        // Files outside the search are left alone
+        assert!(index.find_message("u2", &files[1..]).is_none());
+
+        // Appending keeps the offsets valid; rewriting the file does not
+        let mut file = OpenOptions::new().append(true).open(&plain)?;
+        writeln!(file, "{USER_LINE}")?;
+        drop(file);
+        assert!(index.find_message("u2", &files).is_some());
+        write_session(&plain, &[SYSTEM_LINE])?;
+        assert!(index.find_message("u2", &files).is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+//! Plain text announcements of the interactive mode for screen readers
+//! (`--announce`).
+//!
+//! After every frame the state is described in a few short lines: the screen
+//! in view, the number of results once a search settles, the selected item and
+//! status messages. Only lines that changed since the last frame are written,
+//! so the output reads like a transcript of the session.
+
+use super::domain::models::{Mode, SearchTab};
+use super::ui::app_state::{AppState, SessionInfo};
+use super::ui::components::list_item::{ListItem, truncate_message};
+use crate::query::SearchResult;
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Characters of message text read out for the selected item
+const SPOKEN_TEXT_LENGTH: usize = 80;
+
+/// What is announced about one frame
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Announcement {
+    pub view: String,
+    /// Result count, paired with the search it belongs to so that loading the
+    /// same number of results for a new query is announced again
+    pub results: Option<(u64, String)>,
+    pub selected: Option<String>,
+    pub message: Option<String>,
+}
+
+impl Announcement {
+    pub fn describe(state: &AppState) -> Self {
+        let view = if state.ui.show_help {
+            "Help".to_string()
+        } else if state.ui.context_chart.is_some() {
+            "Token context chart".to_string()
+        } else if let Some(name) = state.ui.theme_picker {
+            format!("Theme picker, {} highlighted", name.as_str())
+        } else {
+            match state.mode {
+                Mode::Search => match state.search.current_tab {
+                    SearchTab::Search => "Search tab",
+                    SearchTab::SessionList => "Session List tab",
+                    SearchTab::Bookmarks => "Bookmarks tab",
+                    SearchTab::Projects => "Projects tab",
+                }
+                .to_string(),
+                Mode::MessageDetail => "Message detail".to_string(),
+                Mode::SessionViewer => "Session viewer".to_string(),
+                Mode::MessageDiff => "Message diff".to_string(),
+            }
+        };
+
+        let (results, selected) = match (state.mode, state.search.current_tab) {
+            // The help covers the screen
+            _ if state.ui.show_help => (None, None),
+            _ if state.ui.context_chart.is_some() => (None, None),
+            _ if state.ui.theme_picker.is_some() => (None, None),
+            (Mode::Search, SearchTab::Search) => {
+                let search = &state.search;
+                // The results still belong to the previous query while it is typed
+                let typing = state.ui.message.as_deref() == Some("[typing...]");
+                let results = (!search.is_searching && !typing).then(|| {
+                    let mut text = count(search.results.len(), "result");
+                    if !search.query.is_empty() {
+                        text.push_str(&format!(" for \"{}\"", search.query));
+                    }
+                    if search.has_more_results {
+                        text.push_str(", more available");
+                    }
+                    (search.current_search_id, text)
+                });
+                let selected = selected_result(&search.results, search.selected_index);
+                (results, selected)
+            }
+            (Mode::Search, SearchTab::SessionList) => {
+                let list = &state.session_list;
+                let results =
+                    (!list.is_loading && !list.is_searching && !list.is_typing).then(|| {
+                        (
+                            list.current_search_id,
+                            count(list.filtered_sessions.len(), "session"),
+                        )
+                    });
+                let selected = list
+                    .filtered_sessions
+                    .get(list.selected_index)
+                    .map(|session| {
+                        position(list.selected_index, list.filtered_sessions.len())
+                            + &describe_session(session)
+                    });
+                (results, selected)
+            }
+            (Mode::Search, SearchTab::Bookmarks) => {
+                let bookmarks = &state.bookmarks;
+                let results = (!bookmarks.is_loading)
+                    .then(|| (0, count(bookmarks.results.len(), "bookmark")));
+                let selected = selected_result(&bookmarks.results, bookmarks.selected_index);
+                (results, selected)
+            }
+            (Mode::Search, SearchTab::Projects) => {
+                let projects = &state.projects;
+                let results =
+                    (!projects.is_loading).then(|| (0, count(projects.projects.len(), "project")));
+                (results, None)
+            }
+            (Mode::SessionViewer, _) => {
+                let session = &state.session;
+                let results = Some((0, count(session.search_results.len(), "message")));
+                let selected = selected_result(&session.search_results, session.selected_index);
+                (results, selected)
+            }
+            (Mode::MessageDetail, _) => {
+                let selected = state.ui.selected_result.as_ref().map(describe_result);
+                (None, selected)
+            }
+            (Mode::MessageDiff, _) => (None, None),
+        };
+
+        // Bracketed messages such as "[typing...]" only show progress
+        let message = state
+            .ui
+            .message
+            .clone()
+            .filter(|message| !message.starts_with('['));
+
+        Self {
+            view,
+            results,
+            selected,
+            message,
+        }
+    }
+
+    /// Lines announcing what changed since `previous`
+    pub fn changes(&self, previous: &Announcement) -> Vec<String> {
+        let mut lines = Vec::new();
+        let view_changed = self.view != previous.view;
+        if view_changed {
+            lines.push(self.view.clone());
+        }
+        if let Some((_, results)) = &self.results
+            && (view_changed || self.results != previous.results)
+        {
+            lines.push(results.clone());
+        }
+        if let Some(selected) = &self.selected
+            && (view_changed || self.selected != previous.selected)
+        {
+            lines.push(selected.clone());
+        }
+        if let Some(message) = &self.message
+            && self.message != previous.message
+        {
+            lines.push(message.clone());
+        }
+        lines
+    }
+}
+
+/// Writes the announcements of each frame to stderr or a file
+pub struct Announcer {
+    out: Box<dyn Write + Send>,
+    last: Announcement,
+}
+
+impl Announcer {
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out,
+            last: Announcement::default(),
+        }
+    }
+
+    /// `-` for stderr, otherwise a file or named pipe to append to
+    pub fn open(target: &str) -> Result<Self> {
+        if target == "-" {
+            return Ok(Self::new(Box::new(io::stderr())));
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Path::new(target))
+            .with_context(|| format!("failed to open announcement output: {target}"))?;
+        Ok(Self::new(Box::new(file)))
+    }
+
+    pub fn update(&mut self, state: &AppState) -> io::Result<()> {
+        let announcement = Announcement::describe(state);
+        let lines = announcement.changes(&self.last);
+        self.last = announcement;
+        if lines.is_empty() {
+            return Ok(());
+        }
+        for line in lines {
+            writeln!(self.out, "{line}")?;
+        }
+        self.out.flush()
+    }
+}
+
+fn count(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{n} {noun}s")
+    }
+}
+
+fn position(index: usize, total: usize) -> String {
+    format!("{} of {total}: ", index + 1)
+}
+
+fn selected_result(results: &[SearchResult], index: usize) -> Option<String> {
+    results
+        .get(index)
+        .map(|result| position(index, results.len()) + &describe_result(result))
+}
+
+fn describe_result(result: &SearchResult) -> String {
+    let mut text = result.role.to_string();
+    let timestamp = result.format_timestamp();
+    if timestamp != "N/A" {
+        text.push_str(&format!(", {timestamp}"));
+    }
+    text.push_str(&format!(
+        ", {}",
+        truncate_message(result.text.trim(), SPOKEN_TEXT_LENGTH)
+    ));
+    text
+}
+
+fn describe_session(session: &SessionInfo) -> String {
+    let first_message = session.summary.as_deref().unwrap_or(&session.first_message);
+    format!(
+        "{}, {}",
+        count(session.message_count, "message"),
+        truncate_message(first_message.trim(), SPOKEN_TEXT_LENGTH)
+    )
+}
@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use super::super::announce::{Announcement, Announcer};
+    use super::super::domain::models::{Mode, SearchTab};
+    use super::super::ui::app_state::AppState;
+    use super::super::ui::events::Message;
+    use crate::query::condition::{QueryCondition, SearchResult};
+    use tempfile::tempdir;
+
+    fn result(text: &str) -> SearchResult {
+        SearchResult {
+            file: "test.jsonl".into(),
+            uuid: text.to_string(),
+            timestamp: String::new(),
+            session_id: "s1".into(),
+            role: "user".into(),
+            text: text.to_string(),
+            message_type: "user".into(),
+            query: QueryCondition::Literal {
+                pattern: "test".to_string(),
+                case_sensitive: false,
+            },
+            cwd: "/".into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        }
+    }
+
+    /// Lines announced for `state` after `previous`
+    fn announce(previous: &mut Announcement, state: &AppState) -> Vec<String> {
+        let announcement = Announcement::describe(state);
+        let lines = announcement.changes(previous);
+        *previous = announcement;
+        lines
+    }
+
+    #[test]
+    fn test_announcements() {
+        let mut state = AppState::new();
+        let mut last = Announcement::default();
+
+        state.update(Message::SearchRequested);
+        assert_eq!(announce(&mut last, &state), ["Search tab"]);
+
+        state.update(Message::SearchCompleted(vec![
+            result("first\nline"),
+            result("second"),
+        ]));
+        assert_eq!(
+            announce(&mut last, &state),
+            ["2 results", "1 of 2: user, first line"]
+        );
+        // Nothing changed, nothing to say
+        assert!(announce(&mut last, &state).is_empty());
+
+        state.update(Message::SelectResult(1));
+        assert_eq!(announce(&mut last, &state), ["2 of 2: user, second"]);
+        state.update(Message::SelectResult(0));
+        assert_eq!(announce(&mut last, &state), ["1 of 2: user, first line"]);
+
+        // The old count is not repeated for the query being typed
+        state.update(Message::QueryChanged("sec".to_string()));
+        assert!(announce(&mut last, &state).is_empty());
+        state.update(Message::SearchRequested);
+        assert!(announce(&mut last, &state).is_empty());
+        state.update(Message::SearchCompleted(vec![result("second")]));
+        assert_eq!(
+            announce(&mut last, &state),
+            ["1 result for \"sec\"", "1 of 1: user, second"]
+        );
+
+        state.update(Message::EnterMessageDetail);
+        assert_eq!(state.mode, Mode::MessageDetail);
+        assert_eq!(
+            announce(&mut last, &state),
+            ["Message detail", "user, second"]
+        );
+
+        state.ui.message = Some("Copied to clipboard".to_string());
+        assert_eq!(announce(&mut last, &state), ["Copied to clipboard"]);
+
+        // Coming back announces the results again
+        state.update(Message::ExitToSearch);
+        assert_eq!(
+            announce(&mut last, &state),
+            ["Search tab", "1 result for \"sec\"", "1 of 1: user, second"]
+        );
+
+        state.search.current_tab = SearchTab::Bookmarks;
+        state.update(Message::BookmarksLoaded(Vec::new()));
+        assert_eq!(
+            announce(&mut last, &state),
+            ["Bookmarks tab", "0 bookmarks"]
+        );
+
+        state.ui.show_help = true;
+        assert_eq!(announce(&mut last, &state), ["Help"]);
+    }
+
+    #[test]
+    fn test_announcer_writes_changes() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("announce.txt");
+        let mut announcer = Announcer::open(path.to_str().unwrap()).unwrap();
+
+        let mut state = AppState::new();
+        state.update(Message::SearchCompleted(vec![result("hello")]));
+        announcer.update(&state).unwrap();
+        announcer.update(&state).unwrap();
+        state.mode = Mode::SessionViewer;
+        announcer.update(&state).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "Search tab\n1 result\n1 of 1: user, hello\nSession viewer\n0 messages\n"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::bookmarks::{Bookmark, BookmarkStore, resolve_bookmark};
+use crate::query::condition::{QueryCondition, SearchResult};
+use crate::query::interner::SharedStr;
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// Reads and updates the bookmark store for the Bookmarks tab and the `b` key
+pub struct BookmarkService {
+    path: PathBuf,
+}
+
+impl BookmarkService {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Bookmarked messages, most recently bookmarked first.
+    ///
+    /// Bookmarks whose message can no longer be found are left out.
+    pub fn load(&self) -> Result<Vec<SearchResult>> {
+        let store = BookmarkStore::load(&self.path)?;
+        let mut results = Vec::new();
+        for bookmark in store.bookmarks.iter().rev() {
+            let resolved = resolve_bookmark(bookmark)?;
+            let Some(message) = resolved.message else {
+                continue;
+            };
+            let role = SharedStr::from(message.role);
+            results.push(SearchResult {
+                file: bookmark.file.as_str().into(),
+                uuid: bookmark.uuid.clone(),
+                timestamp: message.timestamp,
+                session_id: bookmark.session_id.as_str().into(),
+                role: role.clone(),
+                text: message.text,
+                message_type: role,
+                query: QueryCondition::Literal {
+                    pattern: String::new(),
+                    case_sensitive: false,
+                },
+                cwd: SharedStr::default(),
+                raw_json: Some(message.raw_json),
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
+            });
+        }
+        Ok(results)
+    }
+
+    /// Bookmark `result`, or remove its bookmark if it already has one.
+    ///
+    /// Returns whether the message is bookmarked afterwards.
+    pub fn toggle(&self, result: &SearchResult) -> Result<bool> {
+        let mut store = BookmarkStore::load(&self.path)?;
+        let bookmarked = store.toggle(Bookmark {
+            file: result.file.to_string(),
+            uuid: result.uuid.clone(),
+            session_id: result.session_id.to_string(),
+            tags: Vec::new(),
+            note: None,
+            created_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        });
+        store.save(&self.path)?;
+        Ok(bookmarked)
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use super::super::bookmark_service::*;
+    use crate::query::condition::{QueryCondition, SearchResult};
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn result(file: &str, uuid: &str) -> SearchResult {
+        SearchResult {
+            file: file.into(),
+            uuid: uuid.to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s1".into(),
+            role: "user".into(),
+            text: "hello".to_string(),
+            message_type: "user".into(),
+            query: QueryCondition::Literal {
+                pattern: String::new(),
+                case_sensitive: false,
+            },
+            cwd: "/".into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        }
+    }
+
+    #[test]
+    fn test_toggle_and_load() {
+        let dir = tempdir().unwrap();
+        let session = dir.path().join("session.jsonl");
+        fs::write(
+            &session,
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"first"},"uuid":"u1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"second"},"uuid":"u2","timestamp":"2024-01-01T00:00:01Z","sessionId":"s1","parentUuid":"u1","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+        let file = session.to_str().unwrap();
+        let service = BookmarkService::new(dir.path().join("bookmarks.json"));
+
+        assert!(service.load().unwrap().is_empty());
+        assert!(service.toggle(&result(file, "u1")).unwrap());
+        assert!(service.toggle(&result(file, "u2")).unwrap());
+        // A bookmark whose message is gone is skipped
+        assert!(service.toggle(&result(file, "gone")).unwrap());
+
+        let loaded = service.load().unwrap();
+        let uuids: Vec<&str> = loaded.iter().map(|r| r.uuid.as_str()).collect();
+        assert_eq!(uuids, vec!["u2", "u1"]);
+        assert_eq!(loaded[0].text, "second");
+        assert!(loaded[0].raw_json.as_deref().unwrap().contains("\"u2\""));
+
+        assert!(!service.toggle(&result(file, "u2")).unwrap());
+        assert_eq!(service.load().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_load_invalid_store() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("bookmarks.json");
+        fs::write(&path, "not json").unwrap();
+
+        assert!(BookmarkService::new(path).load().is_err());
+    }
+}
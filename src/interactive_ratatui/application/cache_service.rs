@@ -1,18 +1,79 @@
 use crate::SessionMessage;
 use crate::interactive_ratatui::constants::*;
 use crate::interactive_ratatui::domain::models::CachedFile;
+use crate::search::compression::session_reader;
+use crate::search::result_cache::DEFAULT_MEMORY_CAP;
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
 
+/// Session files read by the interactive mode, parsed once and kept until
+/// they change.
+///
+/// The cache is bounded by an approximate memory size (`cache_memory_mb` in
+/// config.toml, as for the result cache): the files used longest ago are
+/// dropped first, and the file just read is always kept.
 pub struct CacheService {
     files: HashMap<PathBuf, CachedFile>,
+    /// Cached paths, most recently used first
+    recent: VecDeque<PathBuf>,
+    /// Approximate bytes held by `files`
+    size: usize,
+    memory_cap: usize,
+}
+
+/// How much the session cache holds, for the help overlay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionCacheStats {
+    pub files: usize,
+    /// Approximate bytes held by the files
+    pub bytes: usize,
+    pub memory_cap: usize,
+}
+
+impl SessionCacheStats {
+    pub fn summary(&self) -> String {
+        const MB: f64 = 1024.0 * 1024.0;
+        format!(
+            "{} files, {:.1} MB of {:.0} MB",
+            self.files,
+            self.bytes as f64 / MB,
+            self.memory_cap as f64 / MB
+        )
+    }
+}
+
+impl Default for CacheService {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CacheService {
     pub fn new() -> Self {
+        Self::with_memory_cap(DEFAULT_MEMORY_CAP)
+    }
+
+    pub fn with_memory_cap(memory_cap: usize) -> Self {
         Self {
             files: HashMap::new(),
+            recent: VecDeque::new(),
+            size: 0,
+            memory_cap,
+        }
+    }
+
+    /// Change the memory cap, dropping files until the cache fits it
+    pub fn set_memory_cap(&mut self, memory_cap: usize) {
+        self.memory_cap = memory_cap;
+        self.evict(0);
+    }
+
+    pub fn stats(&self) -> SessionCacheStats {
+        SessionCacheStats {
+            files: self.files.len(),
+            bytes: self.size,
+            memory_cap: self.memory_cap,
         }
     }
 
@@ -27,7 +88,7 @@ impl CacheService {
 
         if needs_reload {
             let file = std::fs::File::open(path)?;
-            let reader = std::io::BufReader::with_capacity(FILE_READ_BUFFER_SIZE, file);
+            let reader = session_reader(path, file, FILE_READ_BUFFER_SIZE)?;
             use std::io::BufRead;
 
             let mut messages = Vec::new();
@@ -46,16 +107,21 @@ impl CacheService {
                 }
             }
 
-            self.files.insert(
-                path.to_path_buf(),
-                CachedFile {
-                    messages,
-                    raw_lines,
-                    last_modified: modified,
-                },
-            );
+            let cached = CachedFile {
+                messages,
+                raw_lines,
+                last_modified: modified,
+            };
+            self.size += cached.approximate_size();
+            if let Some(previous) = self.files.insert(path.to_path_buf(), cached) {
+                self.size -= previous.approximate_size();
+            }
         }
 
+        self.recent.retain(|recent| recent != path);
+        self.recent.push_front(path.to_path_buf());
+        self.evict(1);
+
         self.files.get(path).ok_or_else(|| {
             anyhow::anyhow!(
                 "Failed to retrieve cached file from path: {}",
@@ -63,4 +129,16 @@ impl CacheService {
             )
         })
     }
+
+    /// Drop the files used longest ago until the cache is within its memory
+    /// cap, keeping the `keep` most recent ones
+    fn evict(&mut self, keep: usize) {
+        while self.recent.len() > keep && self.size > self.memory_cap {
+            if let Some(path) = self.recent.pop_back()
+                && let Some(evicted) = self.files.remove(&path)
+            {
+                self.size -= evicted.approximate_size();
+            }
+        }
+    }
 }
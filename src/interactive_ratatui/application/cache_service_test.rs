@@ -1,7 +1,19 @@
 #[cfg(test)]
 mod tests {
     use super::super::cache_service::*;
+    use std::fs::File;
+    use std::io::Write;
     use std::path::Path;
+    use tempfile::tempdir;
+
+    const LINE: &str = r#"{"type":"user","message":{"role":"user","content":"Hello world"},"uuid":"u1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/test","version":"1.0"}"#;
+
+    fn write_session(path: &Path, lines: usize) {
+        let mut file = File::create(path).unwrap();
+        for _ in 0..lines {
+            writeln!(file, "{LINE}").unwrap();
+        }
+    }
 
     #[test]
     fn test_cache_service_creation() {
@@ -16,4 +28,41 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let dir = tempdir().unwrap();
+        let path = |name: &str| dir.path().join(format!("{name}.jsonl"));
+        write_session(&path("a"), 10);
+        write_session(&path("b"), 20);
+        write_session(&path("c"), 10);
+
+        let mut cache = CacheService::new();
+        let size = |cache: &mut CacheService, name: &str| {
+            cache.get_messages(&path(name)).unwrap().approximate_size()
+        };
+        let (a, b, c) = (
+            size(&mut cache, "a"),
+            size(&mut cache, "b"),
+            size(&mut cache, "c"),
+        );
+        assert!(b > a);
+        assert_eq!(cache.stats().files, 3);
+        assert_eq!(cache.stats().bytes, a + b + c);
+        assert_eq!(cache.get_messages(&path("b")).unwrap().messages.len(), 20);
+
+        // Using a file makes it the most recent, so a goes first, then c
+        cache.set_memory_cap(b + c);
+        assert_eq!(cache.stats().files, 2);
+        assert_eq!(cache.stats().bytes, b + c);
+        size(&mut cache, "a");
+        assert_eq!(cache.stats().bytes, a + b);
+
+        // A file larger than the cap is still kept while it is in use
+        cache.set_memory_cap(1);
+        assert_eq!(cache.stats().files, 0);
+        size(&mut cache, "b");
+        assert_eq!(cache.stats().files, 1);
+        assert!(cache.stats().summary().starts_with("1 files, "));
+    }
 }
@@ -0,0 +1,55 @@
+use crate::interactive_ratatui::domain::models::Dismissal;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Reads and writes the results dismissed with Alt+X, by query, when
+/// `persist_dismissals` is set in config.toml.
+///
+/// The file is a JSON object from each query to its dismissed results. Saving
+/// a query rewrites only its own entry, so several ccms windows can share it.
+pub struct DismissalService {
+    path: PathBuf,
+}
+
+impl DismissalService {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// `dismissals.json` in the ccms data directory
+    /// (`~/.local/share/ccms/dismissals.json` on Linux)
+    pub fn default_path() -> Result<PathBuf> {
+        let data_dir = dirs::data_dir().context("failed to resolve data directory")?;
+        Ok(data_dir.join("ccms").join("dismissals.json"))
+    }
+
+    /// Dismissed results by query
+    pub fn load(&self) -> Result<BTreeMap<String, Vec<Dismissal>>> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("failed to read dismissals: {}", self.path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("invalid dismissals: {}", self.path.display()))
+    }
+
+    /// Replace the dismissed results of `query`; none forgets the query
+    pub fn save(&self, query: &str, dismissals: &[Dismissal]) -> Result<()> {
+        let mut all = self.load()?;
+        if dismissals.is_empty() {
+            all.remove(query);
+        } else {
+            all.insert(query.to_string(), dismissals.to_vec());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&all)?)
+            .with_context(|| format!("failed to write dismissals: {}", self.path.display()))
+    }
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use super::super::dismissal_service::*;
+    use crate::interactive_ratatui::domain::models::Dismissal;
+    use tempfile::tempdir;
+
+    fn dismissal(uuid: &str) -> Dismissal {
+        Dismissal {
+            file: "/sessions/s1.jsonl".to_string(),
+            uuid: uuid.to_string(),
+            line_number: Some(3),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempdir().unwrap();
+        let service = DismissalService::new(dir.path().join("ccms").join("dismissals.json"));
+        assert!(service.load().unwrap().is_empty());
+
+        service.save("error", &[dismissal("u1")]).unwrap();
+        // Another window saving a different query
+        DismissalService::new(dir.path().join("ccms").join("dismissals.json"))
+            .save("deploy", &[dismissal("u2"), dismissal("u3")])
+            .unwrap();
+
+        let loaded = service.load().unwrap();
+        assert_eq!(loaded["error"], vec![dismissal("u1")]);
+        assert_eq!(loaded["deploy"].len(), 2);
+
+        // Nothing left to hide forgets the query
+        service.save("error", &[]).unwrap();
+        let loaded = service.load().unwrap();
+        assert!(!loaded.contains_key("error"));
+        assert!(loaded.contains_key("deploy"));
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("dismissals.json");
+        std::fs::write(&path, "not json").unwrap();
+        let error = DismissalService::new(path).load().unwrap_err();
+        assert!(error.to_string().contains("invalid dismissals"));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::interactive_ratatui::constants::MAX_SEARCH_HISTORY;
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Reads and appends to the search history behind Up and Ctrl+R in the search bar.
+///
+/// The file holds one query per line and is only ever appended to, so several
+/// ccms windows can share it; repeats are dropped when it is read.
+pub struct HistoryService {
+    path: PathBuf,
+}
+
+impl HistoryService {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// `$CCMS_HISTORY_PATH`, or `history` in the ccms data directory
+    /// (`~/.local/share/ccms/history` on Linux)
+    pub fn default_path() -> Result<PathBuf> {
+        if let Some(path) = std::env::var_os("CCMS_HISTORY_PATH") {
+            return Ok(PathBuf::from(path));
+        }
+
+        let data_dir = dirs::data_dir().context("failed to resolve data directory")?;
+        Ok(data_dir.join("ccms").join("history"))
+    }
+
+    /// Remembered queries, oldest first, each at the position it was last used
+    pub fn load(&self) -> Result<Vec<String>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("failed to read history: {}", self.path.display()))?;
+        // Walk back from the newest so each query is kept where it was last used
+        let mut seen = HashSet::new();
+        let mut queries: Vec<String> = content
+            .lines()
+            .rev()
+            .filter(|line| !line.trim().is_empty() && seen.insert(*line))
+            .take(MAX_SEARCH_HISTORY)
+            .map(str::to_string)
+            .collect();
+        queries.reverse();
+        Ok(queries)
+    }
+
+    /// Add `queries` to the end of the history.
+    ///
+    /// Once the file holds twice as many lines as are kept, it is rewritten
+    /// with only the queries `load` would return.
+    pub fn append(&self, queries: &[String]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("failed to open history: {}", self.path.display()))?;
+        for query in queries {
+            // A query spanning lines would come back as several
+            writeln!(file, "{}", query.replace('\n', " "))?;
+        }
+        drop(file);
+
+        let lines = fs::read_to_string(&self.path)?.lines().count();
+        if lines > 2 * MAX_SEARCH_HISTORY {
+            let mut compacted = self.load()?.join("\n");
+            compacted.push('\n');
+            fs::write(&self.path, compacted)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use super::super::history_service::*;
+    use crate::interactive_ratatui::constants::MAX_SEARCH_HISTORY;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = tempdir().unwrap();
+        let service = HistoryService::new(dir.path().join("ccms").join("history"));
+        assert!(service.load().unwrap().is_empty());
+
+        service
+            .append(&["error".to_string(), "deploy".to_string()])
+            .unwrap();
+        // Another window appending to the same file
+        service
+            .append(&["error".to_string(), "line\nbreak".to_string()])
+            .unwrap();
+
+        // Repeats keep only their latest position
+        assert_eq!(
+            service.load().unwrap(),
+            vec!["deploy", "error", "line break"]
+        );
+    }
+
+    #[test]
+    fn test_compacts_long_history() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history");
+        let service = HistoryService::new(path.clone());
+
+        let queries: Vec<String> = (0..2 * MAX_SEARCH_HISTORY + 1)
+            .map(|i| format!("query {i}"))
+            .collect();
+        service.append(&queries).unwrap();
+
+        let loaded = service.load().unwrap();
+        assert_eq!(loaded.len(), MAX_SEARCH_HISTORY);
+        assert_eq!(
+            loaded.last().unwrap(),
+            &format!("query {}", 2 * MAX_SEARCH_HISTORY)
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().lines().count(),
+            MAX_SEARCH_HISTORY
+        );
+    }
+}
@@ -1,9 +1,18 @@
-// pub mod cache_service; // No longer used - session service removed
+pub mod bookmark_service;
+pub mod cache_service;
+pub mod dismissal_service;
+pub mod history_service;
 pub mod search_service;
 // pub mod session_service; // No longer used - unified with search_service
 
-// #[cfg(test)]
-// mod cache_service_test; // No longer used - session service removed
+#[cfg(test)]
+mod bookmark_service_test;
+#[cfg(test)]
+mod cache_service_test;
+#[cfg(test)]
+mod dismissal_service_test;
+#[cfg(test)]
+mod history_service_test;
 #[cfg(test)]
 mod search_service_test;
 // #[cfg(test)]
@@ -1,12 +1,30 @@
-use crate::interactive_ratatui::domain::models::{SearchRequest, SearchResponse};
+use crate::cost::PricingTable;
+use crate::interactive_ratatui::constants::SEARCH_BATCH_INTERVAL_MS;
+use crate::interactive_ratatui::domain::models::{
+    SearchOrder, SearchProgress, SearchRequest, SearchResponse, SessionListRequest,
+    SessionListResponse,
+};
+use crate::interactive_ratatui::ui::app_state::ProjectInfo;
+use crate::outcome::{OutcomeTracker, SessionOutcome};
 use crate::query::condition::{QueryCondition, SearchResult};
+use crate::schemas::SessionMessage;
 use crate::search::SmolEngine;
+use crate::search::compression::{open_session, read_session};
 use crate::search::engine::SearchEngineTrait;
-use crate::search::file_discovery::discover_claude_files;
+use crate::search::file_discovery::{discover_claude_files, pattern_root};
+use crate::search::smol_engine::BatchCallback;
+use crate::tail::Tail;
+use crate::usage::CostTracker;
 use crate::{SearchOptions, parse_query};
-use anyhow::Result;
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fs;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-// Type alias for session data: (file_path, session_id, timestamp, message_count, first_message, preview_messages, summary)
+// Type alias for session data: (file_path, session_id, timestamp, message_count, first_message, preview_messages, summary, outcome, compactions, cost_usd)
 pub type SessionData = (
     String,
     String,
@@ -15,34 +33,95 @@ pub type SessionData = (
     String,
     Vec<(String, String, String)>, // (role, content, timestamp)
     Option<String>,
+    Option<SessionOutcome>,
+    usize,
+    Option<f64>,
 );
 
 pub struct SearchService {
     base_options: SearchOptions,
+    /// Prices for the estimated cost of each session in the session list
+    pricing: PricingTable,
 }
 
 impl SearchService {
     pub fn new(options: SearchOptions) -> Self {
         Self {
             base_options: options,
+            pricing: PricingTable::default(),
         }
     }
 
+    pub fn set_pricing(&mut self, pricing: PricingTable) {
+        self.pricing = pricing;
+    }
+
     pub fn search(&self, request: SearchRequest) -> Result<SearchResponse> {
         let results = self.execute_search(
             &request.query,
-            &request.pattern,
+            request.project.as_deref().unwrap_or(&request.pattern),
             request.role_filter,
             request.order,
             None, // No session_id filter for general search
             request.limit,
             request.offset,
+            request.hide_tool_results,
+            None,
+        )?;
+
+        Ok(SearchResponse {
+            id: request.id,
+            results,
+            error: None,
+            progress: None,
+        })
+    }
+
+    /// Like `search`, but hands partial responses to `on_partial` while files
+    /// are still being searched, at most once per `SEARCH_BATCH_INTERVAL_MS`.
+    /// Partial batches are unsorted and ignore `offset`; the returned final
+    /// response supersedes them.
+    pub fn search_streaming(
+        &self,
+        request: SearchRequest,
+        mut on_partial: impl FnMut(SearchResponse),
+    ) -> Result<SearchResponse> {
+        let interval = Duration::from_millis(SEARCH_BATCH_INTERVAL_MS);
+        let mut pending = Vec::new();
+        let mut last_sent = Instant::now();
+        let mut on_batch = |batch: &[SearchResult], files_searched: usize, files_total: usize| {
+            pending.extend_from_slice(batch);
+            if last_sent.elapsed() >= interval {
+                last_sent = Instant::now();
+                on_partial(SearchResponse {
+                    id: request.id,
+                    results: std::mem::take(&mut pending),
+                    error: None,
+                    progress: Some(SearchProgress {
+                        files_searched,
+                        files_total,
+                    }),
+                });
+            }
+        };
+
+        let results = self.execute_search(
+            &request.query,
+            request.project.as_deref().unwrap_or(&request.pattern),
+            request.role_filter.clone(),
+            request.order,
+            None,
+            request.limit,
+            request.offset,
+            request.hide_tool_results,
+            Some(&mut on_batch),
         )?;
 
         Ok(SearchResponse {
             id: request.id,
             results,
             error: None,
+            progress: None,
         })
     }
 
@@ -60,25 +139,103 @@ impl SearchService {
             Some(session_id),
             request.limit,
             request.offset,
+            request.hide_tool_results,
+            None,
         )?;
 
         Ok(SearchResponse {
             id: request.id,
             results,
             error: None,
+            progress: None,
+        })
+    }
+
+    /// Search every listed session file in one pass with the full query
+    /// language and the time, project and role filters, and report which
+    /// files matched
+    pub fn search_sessions(&self, request: SessionListRequest) -> Result<SessionListResponse> {
+        let query_condition = if request.query.trim().is_empty() {
+            QueryCondition::And { conditions: vec![] }
+        } else {
+            parse_query(&request.query)?
+        };
+
+        let mut options = self.base_options.clone();
+        // Every matching session counts, including ones whose messages were
+        // copied into a later session
+        options.max_results = None;
+        options.dedupe = false;
+        let engine = SmolEngine::new(options);
+
+        let files = request.files.iter().map(PathBuf::from).collect();
+        let (results, _, _) = engine.search_files(
+            files,
+            query_condition,
+            request.role_filter,
+            SearchOrder::Descending,
+        )?;
+
+        Ok(SessionListResponse {
+            id: request.id,
+            matching_files: results
+                .into_iter()
+                .map(|result| result.file.into())
+                .collect(),
+            error: None,
         })
     }
 
+    /// Messages appended to the session file since `tail` last looked,
+    /// filtered like `search_session` would filter them
+    pub fn poll_session(
+        &self,
+        tail: &mut Tail,
+        request: &SearchRequest,
+        session_id: String,
+    ) -> Result<Vec<SearchResult>> {
+        let query_condition = if request.query.trim().is_empty() {
+            QueryCondition::And { conditions: vec![] }
+        } else {
+            parse_query(&request.query)?
+        };
+
+        let mut options = self.base_options.clone();
+        options.session_id = Some(session_id);
+        // Like the session viewer itself, show every kind of message
+        options.exclude_sidechain = false;
+        options.exclude_meta = false;
+        options.session_summaries = true;
+        options.exclude_tool_result_messages = request.hide_tool_results;
+        if let Some(role) = &request.role_filter {
+            options.role = Some(role.clone());
+        }
+        if !options.positions_for(&query_condition).is_empty()
+            || !query_condition.outcomes().is_empty()
+        {
+            bail!("is:first, is:last and outcome: cannot be used when following a session");
+        }
+        let query_condition = options.scope_query(query_condition);
+
+        tail.poll(
+            &[PathBuf::from(&request.pattern)],
+            &query_condition,
+            &options,
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn execute_search(
         &self,
         query: &str,
         pattern: &str,
         role_filter: Option<String>,
-        order: crate::interactive_ratatui::domain::models::SearchOrder,
+        order: SearchOrder,
         session_id: Option<String>,
         limit: Option<usize>,
         offset: Option<usize>,
+        hide_tool_results: bool,
+        on_batch: Option<BatchCallback>,
     ) -> Result<Vec<SearchResult>> {
         let query_condition = if query.trim().is_empty() {
             // Empty query means "match all" - use empty AND condition
@@ -89,11 +246,15 @@ impl SearchService {
 
         // Create a new options with session_id if provided
         let mut options = self.base_options.clone();
+        options.exclude_tool_result_messages = hide_tool_results;
 
         if let Some(sid) = session_id {
             options.session_id = Some(sid);
             // For session viewer, show all messages without limit
             options.max_results = None;
+            options.exclude_sidechain = false;
+            options.exclude_meta = false;
+            options.session_summaries = true;
         } else if limit.is_none() {
             // For regular search without explicit limit, use default
             // If limit is specified, we'll apply it after getting all results
@@ -103,12 +264,17 @@ impl SearchService {
         // Create a new engine with the updated options
         let engine = SmolEngine::new(options);
 
-        let (mut results, _, _) = engine.search_with_role_filter_and_order(
-            pattern,
-            query_condition,
-            role_filter,
-            order,
-        )?;
+        let (mut results, _, _) = match on_batch {
+            Some(on_batch) => {
+                engine.search_streaming(pattern, query_condition, role_filter, order, on_batch)?
+            }
+            None => engine.search_with_role_filter_and_order(
+                pattern,
+                query_condition,
+                role_filter,
+                order,
+            )?,
+        };
 
         // Apply pagination if specified
         if let Some(offset_val) = offset {
@@ -158,18 +324,28 @@ impl SearchService {
         // Find all session files
         for path in files {
             // Read first line to get session info
-            if let Ok(content) = std::fs::read_to_string(&path) {
+            if let Ok(content) = read_session(&path) {
                 let mut session_id = String::new();
                 let mut timestamp = String::new();
                 let mut message_count = 0;
                 let mut first_message = String::new();
                 let mut preview_messages: Vec<(String, String, String)> = Vec::new();
                 let mut summary_message: Option<String> = None;
+                let mut outcome = OutcomeTracker::default();
+                let mut compactions = 0;
+                let mut cost = CostTracker::default();
                 const MAX_PREVIEW_MESSAGES: usize = 5;
 
                 for line in content.lines() {
+                    if let Ok(message) = serde_json::from_str::<SessionMessage>(line) {
+                        outcome.observe(&message);
+                        if message.starts_compaction() {
+                            compactions += 1;
+                        }
+                    }
                     if let Ok(json) = serde_json::from_str::<serde_json::Value>(line) {
                         message_count += 1;
+                        cost.observe(&json, &self.pricing);
 
                         // First message - get session info
                         if message_count == 1 {
@@ -271,6 +447,9 @@ impl SearchService {
                         first_message,
                         preview_messages,
                         summary_message,
+                        outcome.outcome(),
+                        compactions,
+                        cost.total(),
                     ));
                 }
             }
@@ -281,4 +460,63 @@ impl SearchService {
 
         Ok(sessions)
     }
+    /// Every project directory under `~/.claude/projects`, most recently
+    /// active first
+    pub fn get_all_projects(&self) -> Result<Vec<ProjectInfo>> {
+        projects_under(&pattern_root(None))
+    }
+}
+
+/// Project directories directly below `root` that hold session files, most
+/// recently active first
+pub fn projects_under(root: &Path) -> Result<Vec<ProjectInfo>> {
+    let pattern = format!("{}/**/*.jsonl", root.display());
+    // Newest first, so the first file seen for a project is its latest session
+    let files = discover_claude_files(Some(&pattern))?;
+
+    let mut projects: Vec<ProjectInfo> = Vec::new();
+    let mut positions: HashMap<PathBuf, usize> = HashMap::new();
+    for file in &files {
+        let Some(dir) = file
+            .strip_prefix(root)
+            .ok()
+            .and_then(|relative| relative.components().next())
+            .filter(|_| file.parent() != Some(root))
+            .map(|first| root.join(first))
+        else {
+            continue;
+        };
+
+        match positions.entry(dir) {
+            Entry::Occupied(entry) => projects[*entry.get()].session_count += 1,
+            Entry::Vacant(entry) => {
+                let dir = entry.key();
+                projects.push(ProjectInfo {
+                    dir: dir.to_string_lossy().to_string(),
+                    name: session_cwd(file).unwrap_or_else(|| {
+                        dir.file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default()
+                    }),
+                    session_count: 1,
+                    last_activity: fs::metadata(file).and_then(|m| m.modified()).ok(),
+                });
+                entry.insert(projects.len() - 1);
+            }
+        }
+    }
+
+    Ok(projects)
+}
+
+/// The working directory recorded in a session file, if any line has one
+fn session_cwd(file: &Path) -> Option<String> {
+    open_session(file)
+        .ok()?
+        .lines()
+        .map_while(|line| line.ok())
+        .find_map(|line| {
+            let value: serde_json::Value = serde_json::from_str(&line).ok()?;
+            value.get("cwd")?.as_str().map(str::to_string)
+        })
 }
@@ -28,9 +28,11 @@ mod tests {
             query: "   ".to_string(), // Empty/whitespace query
             role_filter: None,
             pattern: "/nonexistent/test/path/*.jsonl".to_string(),
+            project: None,
             order: SearchOrder::Descending,
             limit: None,
             offset: None,
+            hide_tool_results: false,
         };
 
         let response = service.search(request).unwrap();
@@ -55,9 +57,11 @@ mod tests {
             query: "test".to_string(),
             role_filter: Some("user".to_string()),
             pattern: "/nonexistent/test/path/*.jsonl".to_string(),
+            project: None,
             order: SearchOrder::Descending,
             limit: None,
             offset: None,
+            hide_tool_results: false,
         };
 
         // This would normally search files, but without test files it returns empty
@@ -83,9 +87,11 @@ mod tests {
                 query: "test".to_string(),
                 role_filter: None,
                 pattern: "/nonexistent/test/path/*.jsonl".to_string(),
+                project: None,
                 order: SearchOrder::Descending,
                 limit: None,
                 offset: None,
+                hide_tool_results: false,
             };
 
             let response = service.search(request).unwrap();
@@ -106,9 +112,11 @@ mod tests {
             query: "[[invalid regex".to_string(),
             role_filter: None,
             pattern: "/nonexistent/test/path/*.jsonl".to_string(),
+            project: None,
             order: SearchOrder::Descending,
             limit: None,
             offset: None,
+            hide_tool_results: false,
         };
 
         // Should handle invalid regex gracefully
@@ -135,9 +143,11 @@ mod tests {
             query: "".to_string(),
             role_filter: None,
             pattern: "/nonexistent/test/path/*.jsonl".to_string(),
+            project: None,
             order: SearchOrder::Descending,
             limit: None,
             offset: None,
+            hide_tool_results: false,
         };
 
         // Request with role filter should get only that role
@@ -146,9 +156,11 @@ mod tests {
             query: "".to_string(),
             role_filter: Some("user".to_string()),
             pattern: "/nonexistent/test/path/*.jsonl".to_string(),
+            project: None,
             order: SearchOrder::Descending,
             limit: None,
             offset: None,
+            hide_tool_results: false,
         };
 
         // Both will return empty due to missing file, but the structure is correct
@@ -254,4 +266,181 @@ mod tests {
             assert!(result.is_ok());
         }
     }
+
+    #[test]
+    fn test_poll_session() {
+        use crate::tail::Tail;
+        use std::io::Write;
+        use std::path::PathBuf;
+
+        let line = |uuid: &str, session: &str, role: &str, text: &str| {
+            format!(
+                r#"{{"type":"{role}","message":{{"role":"{role}","content":"{text}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:00Z","sessionId":"{session}","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+            ) + "\n"
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        std::fs::write(&path, line("u1", "s1", "user", "deploy first")).unwrap();
+
+        let service = SearchService::new(SearchOptions::default());
+        let mut tail = Tail::from_end(&[PathBuf::from(&path)]);
+        let request = SearchRequest {
+            id: 1,
+            query: "deploy".to_string(),
+            role_filter: None,
+            pattern: path.to_str().unwrap().to_string(),
+            project: None,
+            order: SearchOrder::Ascending,
+            limit: None,
+            offset: None,
+            hide_tool_results: false,
+        };
+        assert!(
+            service
+                .poll_session(&mut tail, &request, "s1".to_string())
+                .unwrap()
+                .is_empty()
+        );
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        for (uuid, session, text) in [
+            ("u2", "s1", "deploy second"),
+            ("u3", "s1", "unrelated"),
+            ("u4", "s2", "deploy elsewhere"),
+        ] {
+            file.write_all(line(uuid, session, "user", text).as_bytes())
+                .unwrap();
+        }
+
+        let results = service
+            .poll_session(&mut tail, &request, "s1".to_string())
+            .unwrap();
+        let uuids: Vec<&str> = results.iter().map(|r| r.uuid.as_str()).collect();
+        assert_eq!(uuids, vec!["u2"]);
+        assert_eq!(results[0].line_number, Some(2));
+
+        // Queries that need the whole session are refused
+        let request = SearchRequest {
+            query: "is:last".to_string(),
+            ..request
+        };
+        assert!(
+            service
+                .poll_session(&mut tail, &request, "s1".to_string())
+                .is_err()
+        );
+    }
+    #[test]
+    fn test_search_sessions() {
+        use crate::interactive_ratatui::domain::models::SessionListRequest;
+
+        let line = |uuid: &str, text: &str| {
+            format!(
+                r#"{{"type":"user","message":{{"role":"user","content":"{text}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:00Z","sessionId":"s-{uuid}","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+            ) + "\n"
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: String| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+        let deploy = write("deploy.jsonl", line("1", "deploy to production"));
+        let staging = write("staging.jsonl", line("2", "deploy to staging"));
+        let answer = write(
+            "answer.jsonl",
+            r#"{"type":"assistant","message":{"id":"msg1","type":"message","role":"assistant","model":"claude","content":[{"type":"text","text":"deploy done"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":10,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":5}},"uuid":"3","timestamp":"2024-01-01T00:00:00Z","sessionId":"s-3","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#.to_string(),
+        );
+        // Files outside the list are never searched
+        write("unlisted.jsonl", line("4", "deploy again"));
+
+        // A limit on printed results must not hide matching sessions
+        let service = SearchService::new(SearchOptions {
+            max_results: Some(1),
+            ..Default::default()
+        });
+        let request = SessionListRequest {
+            id: 7,
+            query: "deploy AND NOT staging".to_string(),
+            role_filter: None,
+            files: vec![deploy.clone(), staging.clone(), answer.clone()],
+        };
+
+        let response = service.search_sessions(request.clone()).unwrap();
+        assert_eq!(response.id, 7);
+        assert!(response.error.is_none());
+        let mut matching: Vec<String> = response.matching_files.into_iter().collect();
+        matching.sort();
+        let mut expected = vec![deploy.clone(), answer.clone()];
+        expected.sort();
+        assert_eq!(matching, expected);
+
+        let response = service
+            .search_sessions(SessionListRequest {
+                role_filter: Some("assistant".to_string()),
+                ..request.clone()
+            })
+            .unwrap();
+        assert_eq!(
+            response.matching_files.into_iter().collect::<Vec<_>>(),
+            vec![answer]
+        );
+
+        assert!(
+            service
+                .search_sessions(SessionListRequest {
+                    query: "(deploy".to_string(),
+                    ..request
+                })
+                .is_err()
+        );
+    }
+    #[test]
+    fn test_projects_and_project_scope() {
+        let line = |uuid: &str, cwd: &str| {
+            format!(
+                r#"{{"type":"user","message":{{"role":"user","content":"deploy {uuid}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:00Z","sessionId":"s-{uuid}","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"{cwd}","version":"1"}}"#
+            ) + "\n"
+        };
+        let root = tempfile::tempdir().unwrap();
+        let api = root.path().join("-work-api");
+        let web = root.path().join("-work-web");
+        std::fs::create_dir_all(&api).unwrap();
+        std::fs::create_dir_all(&web).unwrap();
+        std::fs::write(api.join("1.jsonl"), line("1", "/work/api")).unwrap();
+        std::fs::write(api.join("2.jsonl"), line("2", "/work/api")).unwrap();
+        // Written last, so this project is the most recently active
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(web.join("3.jsonl"), line("3", "/work/web")).unwrap();
+
+        let projects = projects_under(root.path()).unwrap();
+        let listed: Vec<(&str, usize)> = projects
+            .iter()
+            .map(|p| (p.name.as_str(), p.session_count))
+            .collect();
+        assert_eq!(listed, vec![("/work/web", 1), ("/work/api", 2)]);
+        assert!(projects.iter().all(|p| p.last_activity.is_some()));
+
+        // A project overrides the pattern
+        let service = SearchService::new(SearchOptions::default());
+        let response = service
+            .search(SearchRequest {
+                id: 1,
+                query: "deploy".to_string(),
+                role_filter: None,
+                pattern: format!("{}/**/*.jsonl", root.path().display()),
+                project: Some(projects[1].dir.clone()),
+                order: SearchOrder::Ascending,
+                limit: None,
+                offset: None,
+                hide_tool_results: false,
+            })
+            .unwrap();
+        let mut uuids: Vec<&str> = response.results.iter().map(|r| r.uuid.as_str()).collect();
+        uuids.sort();
+        assert_eq!(uuids, vec!["1", "2"]);
+    }
 }
@@ -10,6 +10,25 @@ pub const MESSAGE_CLEAR_DELAY_MS: u64 = 3000;
 /// Event polling interval in milliseconds
 pub const EVENT_POLL_INTERVAL_MS: u64 = 50;
 
+/// Minimum time between partial result batches streamed to the UI during a search
+pub const SEARCH_BATCH_INTERVAL_MS: u64 = 100;
+
+/// How often a followed session file is checked for new messages
+pub const FOLLOW_POLL_INTERVAL_MS: u64 = 500;
+
+/// Quiet time after a session file changes before the corpus counts as updated
+pub const CORPUS_SETTLE_MS: u64 = 1000;
+
+/// Time without key presses after which a corpus update reruns the search
+/// instead of only showing the refresh banner
+pub const CORPUS_REFRESH_IDLE_MS: u64 = 10_000;
+
+/// Longest a `wait` in a `ccms script` lets a search run before giving up
+pub const SCRIPT_WAIT_TIMEOUT_MS: u64 = 30_000;
+
+/// Longest payload description in an `--event-log` line, in characters
+pub const EVENT_LOG_DETAIL_MAX_CHARS: usize = 200;
+
 /// Double Ctrl+C timeout in seconds
 pub const DOUBLE_CTRL_C_TIMEOUT_SECS: u64 = 1;
 
@@ -17,12 +36,32 @@ pub const DOUBLE_CTRL_C_TIMEOUT_SECS: u64 = 1;
 /// Height of the search bar component
 pub const SEARCH_BAR_HEIGHT: u16 = 3;
 
+/// Height of the match timeline above the search results
+pub const TIMELINE_HEIGHT: u16 = 5;
+
+/// Share of the search and bookmark tabs the preview takes, in percent
+pub const PREVIEW_SIZE_PERCENT: u16 = 60;
+
+/// Share of the session list tab the preview takes, in percent
+pub const SESSION_PREVIEW_SIZE_PERCENT: u16 = 50;
+
+/// Smallest and largest preview size, and the step Alt+- / Alt+= resize by
+pub const PREVIEW_SIZE_MIN: u16 = 20;
+pub const PREVIEW_SIZE_MAX: u16 = 80;
+pub const PREVIEW_SIZE_STEP: u16 = 10;
+
 /// Page size for PageUp/PageDown navigation
 pub const PAGE_SIZE: usize = 10;
 
+/// Lines of text one turn of the mouse wheel scrolls a preview or detail view by
+pub const MOUSE_SCROLL_LINES: usize = 3;
+
 // Buffer sizes
-// /// Buffer size for file reading (32KB)
-// pub const FILE_READ_BUFFER_SIZE: usize = 32 * 1024; // No longer used - cache service removed
+/// Buffer size for file reading (32KB)
+pub const FILE_READ_BUFFER_SIZE: usize = 32 * 1024;
+
+/// Context window the token context chart measures sessions against
+pub const CONTEXT_WINDOW_TOKENS: u64 = 200_000;
 
 // Help dialog dimensions
 /// Maximum width for help dialog
@@ -48,6 +87,9 @@ pub const MIN_MESSAGE_WIDTH: u16 = 20;
 /// Maximum navigation history entries
 pub const MAX_NAVIGATION_HISTORY: usize = 50;
 
+/// Most queries kept in the search history
+pub const MAX_SEARCH_HISTORY: usize = 1000;
+
 // Message detail layout constants
 /// Height of the details header section (role, time, file, project, UUID, session)
 pub const MESSAGE_DETAIL_HEADER_HEIGHT: u16 = 8;
@@ -55,6 +97,25 @@ pub const MESSAGE_DETAIL_HEADER_HEIGHT: u16 = 8;
 /// Height of the status bar in message detail view
 pub const MESSAGE_DETAIL_STATUS_HEIGHT: u16 = 1;
 
+/// Line count above which the message detail offers opening the text in `$PAGER`
+pub const PAGER_THRESHOLD_LINES: usize = 200;
+
+/// Pager used when `$PAGER` is not set
+#[cfg(not(windows))]
+pub const DEFAULT_PAGER: &str = "less";
+
+/// Pager used when `$PAGER` is not set
+#[cfg(windows)]
+pub const DEFAULT_PAGER: &str = "more";
+
+/// Editor used when `$EDITOR` is not set
+#[cfg(not(windows))]
+pub const DEFAULT_EDITOR: &str = "vi";
+
+/// Editor used when `$EDITOR` is not set
+#[cfg(windows)]
+pub const DEFAULT_EDITOR: &str = "notepad";
+
 // General UI layout constants
 /// Height of the exit prompt displayed at the bottom
 pub const EXIT_PROMPT_HEIGHT: u16 = 1;
@@ -85,3 +146,7 @@ pub const STATUS_BAR_MIN_HEIGHT: u16 = 1;
 
 /// Maximum height for status bar
 pub const STATUS_BAR_MAX_HEIGHT: u16 = 3;
+
+// Message diff
+/// Role of the stand-in result holding clipboard text in a message diff
+pub const CLIPBOARD_ROLE: &str = "clipboard";
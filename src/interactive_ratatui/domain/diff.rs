@@ -0,0 +1,155 @@
+//! Line diff between two message texts, shown by the diff view
+
+/// Above this many cells the LCS table is skipped and the changed middle is
+/// shown as a plain removal followed by an addition
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+impl DiffLine {
+    pub fn text(&self) -> &str {
+        match self {
+            DiffLine::Same(text) | DiffLine::Removed(text) | DiffLine::Added(text) => text,
+        }
+    }
+
+    pub fn prefix(&self) -> char {
+        match self {
+            DiffLine::Same(_) => ' ',
+            DiffLine::Removed(_) => '-',
+            DiffLine::Added(_) => '+',
+        }
+    }
+}
+
+/// Full-text line diff of `old` against `new`
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Regenerated files usually share most of their text, so only the
+    // changed middle goes through the quadratic LCS
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut lines: Vec<DiffLine> = old[..prefix]
+        .iter()
+        .map(|line| DiffLine::Same(line.to_string()))
+        .collect();
+    if old_middle.len() * new_middle.len() > MAX_LCS_CELLS {
+        lines.extend(
+            old_middle
+                .iter()
+                .map(|line| DiffLine::Removed(line.to_string())),
+        );
+        lines.extend(
+            new_middle
+                .iter()
+                .map(|line| DiffLine::Added(line.to_string())),
+        );
+    } else {
+        lcs_diff(old_middle, new_middle, &mut lines);
+    }
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::Same(line.to_string())),
+    );
+    lines
+}
+
+fn lcs_diff(old: &[&str], new: &[&str], out: &mut Vec<DiffLine>) {
+    let (n, m) = (old.len(), new.len());
+    let width = m + 1;
+    // lengths[i * width + j] is the LCS length of old[i..] and new[j..]
+    let mut lengths = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            out.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            out.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            out.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    out.extend(
+        old[i..]
+            .iter()
+            .map(|line| DiffLine::Removed(line.to_string())),
+    );
+    out.extend(
+        new[j..]
+            .iter()
+            .map(|line| DiffLine::Added(line.to_string())),
+    );
+}
+
+/// Render `lines` as unified diff text with every line as context
+pub fn unified_diff(lines: &[DiffLine], old_label: &str, new_label: &str) -> String {
+    let mut text = format!("--- {old_label}\n+++ {new_label}\n");
+    for line in lines {
+        text.push(line.prefix());
+        text.push_str(line.text());
+        text.push('\n');
+    }
+    text
+}
+
+/// Rows of a side-by-side view: unchanged lines appear on both sides, and each
+/// run of removals is paired row by row with the additions that follow it
+pub fn side_by_side(lines: &[DiffLine]) -> Vec<(Option<&DiffLine>, Option<&DiffLine>)> {
+    let mut rows = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        if let DiffLine::Same(_) = lines[index] {
+            rows.push((Some(&lines[index]), Some(&lines[index])));
+            index += 1;
+            continue;
+        }
+
+        let removed_end = index
+            + lines[index..]
+                .iter()
+                .take_while(|line| matches!(line, DiffLine::Removed(_)))
+                .count();
+        let added_end = removed_end
+            + lines[removed_end..]
+                .iter()
+                .take_while(|line| matches!(line, DiffLine::Added(_)))
+                .count();
+        let removed = &lines[index..removed_end];
+        let added = &lines[removed_end..added_end];
+        for row in 0..removed.len().max(added.len()) {
+            rows.push((removed.get(row), added.get(row)));
+        }
+        index = added_end;
+    }
+    rows
+}
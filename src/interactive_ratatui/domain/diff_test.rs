@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use super::super::diff::*;
+
+    #[test]
+    fn test_diff_lines_identical() {
+        let lines = diff_lines("a\nb", "a\nb");
+        assert_eq!(
+            lines,
+            vec![
+                DiffLine::Same("a".to_string()),
+                DiffLine::Same("b".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_lines_changed_middle() {
+        let lines = diff_lines(
+            "fn main() {\n    old();\n    keep();\n}",
+            "fn main() {\n    keep();\n    new();\n}",
+        );
+        assert_eq!(
+            lines,
+            vec![
+                DiffLine::Same("fn main() {".to_string()),
+                DiffLine::Removed("    old();".to_string()),
+                DiffLine::Same("    keep();".to_string()),
+                DiffLine::Added("    new();".to_string()),
+                DiffLine::Same("}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_lines_empty_sides() {
+        assert_eq!(diff_lines("", "x"), vec![DiffLine::Added("x".to_string())]);
+        assert_eq!(
+            diff_lines("x", ""),
+            vec![DiffLine::Removed("x".to_string())]
+        );
+        assert!(diff_lines("", "").is_empty());
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let lines = diff_lines("a\nb", "a\nc");
+        assert_eq!(
+            unified_diff(&lines, "old", "new"),
+            "--- old\n+++ new\n a\n-b\n+c\n"
+        );
+    }
+
+    #[test]
+    fn test_side_by_side_pairs_replacements() {
+        let lines = diff_lines("a\nb\nc\nd", "a\nB\nd\ne");
+        let rows: Vec<(Option<&str>, Option<&str>)> = side_by_side(&lines)
+            .into_iter()
+            .map(|(left, right)| (left.map(DiffLine::text), right.map(DiffLine::text)))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (Some("a"), Some("a")),
+                (Some("b"), Some("B")),
+                (Some("c"), None),
+                (Some("d"), Some("d")),
+                (None, Some("e")),
+            ]
+        );
+    }
+}
@@ -5,19 +5,22 @@ mod tests {
 
     fn create_test_result(role: &str, text: &str) -> SearchResult {
         SearchResult {
-            file: "test.jsonl".to_string(),
+            file: "test.jsonl".into(),
             uuid: "test-uuid".to_string(),
             timestamp: "2024-01-01T00:00:00Z".to_string(),
-            session_id: "test-session".to_string(),
-            role: role.to_string(),
+            session_id: "test-session".into(),
+            role: role.into(),
             text: text.to_string(),
-            message_type: role.to_string(),
+            message_type: role.into(),
             query: QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: "/test".to_string(),
+            cwd: "/test".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         }
     }
 
@@ -1,7 +1,10 @@
+pub mod diff;
 pub mod filter;
 pub mod models;
 pub mod session_list_item;
 
+#[cfg(test)]
+mod diff_test;
 #[cfg(test)]
 mod filter_test;
 #[cfg(test)]
@@ -1,5 +1,8 @@
 use crate::SessionMessage;
 use crate::query::condition::SearchResult;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashSet;
 use std::time::SystemTime;
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -7,6 +10,7 @@ pub enum Mode {
     Search,
     MessageDetail,
     SessionViewer,
+    MessageDiff,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug, Default)]
@@ -14,6 +18,18 @@ pub enum SearchTab {
     #[default]
     Search,
     SessionList,
+    Bookmarks,
+    Projects,
+}
+
+/// Where the preview pane sits in the search, bookmark and session list tabs
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum PreviewPosition {
+    /// Beside the list
+    #[default]
+    Right,
+    /// Under the list
+    Bottom,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -22,10 +38,49 @@ pub enum SessionOrder {
     Descending,
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
 pub enum SearchOrder {
+    #[default]
+    #[value(name = "newest")]
     Descending, // Default - newest first
-    Ascending,  // Reverse - oldest first
+    #[value(name = "oldest")]
+    Ascending, // Reverse - oldest first
+    /// Best matches first (see `QueryCondition::relevance`), newest first among equals
+    Relevance,
+}
+
+impl SearchOrder {
+    /// The order Ctrl+O switches to
+    pub fn next(self) -> Self {
+        match self {
+            SearchOrder::Descending => SearchOrder::Ascending,
+            SearchOrder::Ascending => SearchOrder::Relevance,
+            SearchOrder::Relevance => SearchOrder::Descending,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchOrder::Descending => "Desc",
+            SearchOrder::Ascending => "Asc",
+            SearchOrder::Relevance => "Rel",
+        }
+    }
+
+    /// Sort `results` into this order
+    pub fn sort(self, results: &mut [SearchResult]) {
+        match self {
+            SearchOrder::Descending => results.sort_by(|a, b| b.timestamp.cmp(&a.timestamp)),
+            SearchOrder::Ascending => results.sort_by(|a, b| a.timestamp.cmp(&b.timestamp)),
+            SearchOrder::Relevance => results.sort_by_cached_key(|result| {
+                (
+                    Reverse(result.query.relevance(&result.text)),
+                    Reverse(result.timestamp.clone()),
+                )
+            }),
+        }
+    }
 }
 
 pub struct CachedFile {
@@ -34,6 +89,20 @@ pub struct CachedFile {
     pub last_modified: SystemTime,
 }
 
+impl CachedFile {
+    /// Bytes held by the file, counting each parsed message as the size of
+    /// its line
+    pub fn approximate_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.messages.len() * std::mem::size_of::<SessionMessage>()
+            + self
+                .raw_lines
+                .iter()
+                .map(|line| std::mem::size_of::<String>() + line.len() * 2)
+                .sum::<usize>()
+    }
+}
+
 // Search request and response for async communication
 #[derive(Clone)]
 pub struct SearchRequest {
@@ -41,13 +110,69 @@ pub struct SearchRequest {
     pub query: String,
     pub role_filter: Option<String>,
     pub pattern: String,
+    /// Project directory to search instead of `pattern`
+    pub project: Option<String>,
     pub order: SearchOrder,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    /// Leave out user messages that only carry tool results
+    pub hide_tool_results: bool,
 }
 
 pub struct SearchResponse {
     pub id: u64,
     pub results: Vec<SearchResult>,
     pub error: Option<String>,
+    /// Set on partial batches streamed while files are still being searched;
+    /// `None` on the final, sorted response
+    pub progress: Option<SearchProgress>,
+}
+
+/// Which of the listed session files have a message matching `query`
+#[derive(Clone)]
+pub struct SessionListRequest {
+    pub id: u64,
+    pub query: String,
+    pub role_filter: Option<String>,
+    pub files: Vec<String>,
+}
+
+pub struct SessionListResponse {
+    pub id: u64,
+    /// The requested files with at least one match
+    pub matching_files: HashSet<String>,
+    pub error: Option<String>,
+}
+
+/// How far a streamed search has got
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchProgress {
+    pub files_searched: usize,
+    pub files_total: usize,
+}
+
+/// A result dismissed from the results of a query during triage, by the
+/// message it shows
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dismissal {
+    pub file: String,
+    pub uuid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<usize>,
+}
+
+impl Dismissal {
+    pub fn of(result: &SearchResult) -> Self {
+        Self {
+            file: result.file.to_string(),
+            uuid: result.uuid.clone(),
+            line_number: result.line_number,
+        }
+    }
+
+    pub fn matches(&self, result: &SearchResult) -> bool {
+        result.file.as_str() == self.file
+            && result.uuid == self.uuid
+            && result.line_number == self.line_number
+    }
 }
@@ -43,9 +43,11 @@ mod tests {
             query: "test query".to_string(),
             role_filter: Some("user".to_string()),
             pattern: "*.jsonl".to_string(),
+            project: None,
             order: SearchOrder::Descending,
             limit: None,
             offset: None,
+            hide_tool_results: false,
         };
 
         assert_eq!(request.id, 42);
@@ -59,25 +61,29 @@ mod tests {
         use crate::query::condition::{QueryCondition, SearchResult};
 
         let results = vec![SearchResult {
-            file: "test.jsonl".to_string(),
+            file: "test.jsonl".into(),
             uuid: "uuid1".to_string(),
             timestamp: "2024-01-01T00:00:00Z".to_string(),
-            session_id: "session1".to_string(),
-            role: "user".to_string(),
+            session_id: "session1".into(),
+            role: "user".into(),
             text: "Hello".to_string(),
-            message_type: "user".to_string(),
+            message_type: "user".into(),
             query: QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: "/test".to_string(),
+            cwd: "/test".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         }];
 
         let response = SearchResponse {
             id: 42,
             results: results.clone(),
             error: None,
+            progress: None,
         };
 
         assert_eq!(response.id, 42);
@@ -104,9 +110,11 @@ mod tests {
             query: "test".to_string(),
             role_filter: None,
             pattern: "*.jsonl".to_string(),
+            project: None,
             order: SearchOrder::Ascending,
             limit: Some(100),
             offset: Some(50),
+            hide_tool_results: false,
         };
 
         let cloned = original.clone();
@@ -1,6 +1,7 @@
 use crate::interactive_ratatui::ui::components::list_item::{ListItem, highlight_text, wrap_text};
+use crate::interactive_ratatui::ui::theme;
 use crate::schemas::session_message::SessionMessage;
-use ratatui::style::{Color, Style};
+use ratatui::style::Style;
 use ratatui::text::{Line, Span};
 
 #[derive(Debug, Clone)]
@@ -203,7 +204,7 @@ impl ListItem for SessionListItem {
         let mut spans = vec![
             Span::styled(
                 format!("{timestamp:16} "),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme::current().text_dim),
             ),
             Span::styled(
                 format!("{:10} ", self.get_role()),
@@ -227,7 +228,7 @@ impl ListItem for SessionListItem {
         let mut first_line_spans = vec![
             Span::styled(
                 format!("{timestamp:16} "),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme::current().text_dim),
             ),
             Span::styled(
                 format!("{:10} ", self.get_role()),
@@ -0,0 +1,108 @@
+//! Log of what the state machine of the interactive mode processed
+//! (`--event-log`).
+//!
+//! Every message handled by `AppState::update` and every command executed
+//! afterwards is written as one JSON line, in order:
+//!
+//! ```json
+//! {"seq":3,"kind":"message","name":"QueryChanged","detail":"\"needle\"","mode":"Search"}
+//! {"seq":4,"kind":"command","name":"ScheduleSearch","detail":"300","mode":"Search"}
+//! ```
+//!
+//! `seq`, `kind` and `name` are stable, so tests can assert on the flow of an
+//! interaction. `detail` is the `Debug` output of the payload, cut short, and
+//! is meant for reading. No timestamps are written, so replaying the same
+//! script gives the same log.
+
+use super::constants::EVENT_LOG_DETAIL_MAX_CHARS;
+use super::domain::models::Mode;
+use super::ui::commands::Command;
+use super::ui::events::Message;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// One line of the log
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Event {
+    pub seq: u64,
+    /// `message` or `command`
+    pub kind: &'static str,
+    /// The enum variant, like `QueryChanged`
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// The mode after a message was handled, or when a command ran
+    pub mode: String,
+}
+
+impl Event {
+    fn new(seq: u64, kind: &'static str, value: &impl Debug, mode: Mode) -> Self {
+        let debug = format!("{value:?}");
+        let split = debug.find(['(', ' ', '{']).unwrap_or(debug.len());
+        let (name, payload) = debug.split_at(split);
+        let payload = payload.trim();
+        let payload = payload
+            .strip_prefix('(')
+            .and_then(|payload| payload.strip_suffix(')'))
+            .unwrap_or(payload);
+        let detail = (!payload.is_empty()).then(|| {
+            let mut detail: String = payload.chars().take(EVENT_LOG_DETAIL_MAX_CHARS).collect();
+            if detail.len() < payload.len() {
+                detail.push('…');
+            }
+            detail
+        });
+        Self {
+            seq,
+            kind,
+            name: name.to_string(),
+            detail,
+            mode: format!("{mode:?}"),
+        }
+    }
+}
+
+/// Writes the messages and commands of the interactive mode as JSONL
+pub struct EventLog {
+    out: Box<dyn Write + Send>,
+    seq: u64,
+}
+
+impl EventLog {
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Self { out, seq: 0 }
+    }
+
+    /// Log to a new file at `path`, replacing an existing one
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("failed to create event log: {}", path.display()))?;
+        Ok(Self::new(Box::new(BufWriter::new(file))))
+    }
+
+    /// Log a message handled by the state, with the mode it left
+    pub fn message(&mut self, message: &Message, mode: Mode) -> io::Result<()> {
+        self.write("message", message, mode)
+    }
+
+    /// Log a command about to run; `Command::None` is left out
+    pub fn command(&mut self, command: &Command, mode: Mode) -> io::Result<()> {
+        if *command == Command::None {
+            return Ok(());
+        }
+        self.write("command", command, mode)
+    }
+
+    fn write(&mut self, kind: &'static str, value: &impl Debug, mode: Mode) -> io::Result<()> {
+        self.seq += 1;
+        let event = Event::new(self.seq, kind, value, mode);
+        serde_json::to_writer(&mut self.out, &event)?;
+        writeln!(self.out)?;
+        // Each line is complete on disk even if the TUI is killed
+        self.out.flush()
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use super::super::InteractiveSearch;
+    use super::super::domain::models::Mode;
+    use super::super::event_log::EventLog;
+    use super::super::script::load_actions;
+    use super::super::ui::commands::Command;
+    use super::super::ui::events::Message;
+    use crate::SearchOptions;
+    use serde_json::Value;
+    use std::fs::File;
+    use std::io::Write;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn read_events(path: &Path) -> Vec<Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_event_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let mut log = EventLog::create(&path).unwrap();
+        log.message(&Message::QueryChanged("needle".to_string()), Mode::Search)
+            .unwrap();
+        log.command(&Command::None, Mode::Search).unwrap();
+        log.command(&Command::ScheduleSearch(300), Mode::Search)
+            .unwrap();
+        log.message(&Message::ShowHelp, Mode::Search).unwrap();
+        log.message(&Message::QueryChanged("x".repeat(500)), Mode::Search)
+            .unwrap();
+
+        let events = read_events(&path);
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[0],
+            serde_json::json!({
+                "seq": 1,
+                "kind": "message",
+                "name": "QueryChanged",
+                "detail": "\"needle\"",
+                "mode": "Search"
+            })
+        );
+        assert_eq!(
+            events[1],
+            serde_json::json!({
+                "seq": 2,
+                "kind": "command",
+                "name": "ScheduleSearch",
+                "detail": "300",
+                "mode": "Search"
+            })
+        );
+        // Variants without a payload have no detail
+        assert_eq!(
+            events[2],
+            serde_json::json!({"seq": 3, "kind": "message", "name": "ShowHelp", "mode": "Search"})
+        );
+        let detail = events[3]["detail"].as_str().unwrap();
+        assert_eq!(detail.chars().count(), 201);
+        assert!(detail.ends_with('…'));
+    }
+
+    #[test]
+    fn test_script_event_log() {
+        let dir = tempdir().unwrap();
+        let mut file = File::create(dir.path().join("session.jsonl")).unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"user","message":{{"role":"user","content":"find the needle"}},"uuid":"u1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+        )
+        .unwrap();
+        let pattern = dir.path().join("session.jsonl").display().to_string();
+        let path = dir.path().join("events.jsonl");
+
+        let actions = load_actions(r#"["wait", {"type": "n"}, "wait", {"key": "?"}]"#).unwrap();
+        let mut app = InteractiveSearch::new(SearchOptions::default());
+        app.set_event_log(EventLog::create(&path).unwrap());
+        app.run_script(&pattern, &actions, 100, 20).unwrap();
+
+        let events = read_events(&path);
+        let seqs: Vec<u64> = events
+            .iter()
+            .map(|event| event["seq"].as_u64().unwrap())
+            .collect();
+        assert_eq!(seqs, (1..=events.len() as u64).collect::<Vec<_>>());
+
+        let flow: Vec<String> = events
+            .iter()
+            .map(|event| format!("{} {}", event["kind"], event["name"]))
+            .map(|line| line.replace('"', ""))
+            .collect();
+        let position = |line: &str| {
+            flow.iter()
+                .position(|event| event == line)
+                .unwrap_or_else(|| panic!("{line} missing from {flow:?}"))
+        };
+        assert_eq!(flow[0], "command ExecuteSearch");
+        assert!(position("message QueryChanged") < position("command ScheduleSearch"));
+        assert!(position("command ScheduleSearch") < position("message ShowHelp"));
+        assert_eq!(flow.last().unwrap(), "message ShowHelp");
+    }
+}
@@ -10,19 +10,22 @@ mod tests {
 
     fn create_mock_search_result() -> SearchResult {
         SearchResult {
-            file: "test.jsonl".to_string(),
+            file: "test.jsonl".into(),
             uuid: "test-uuid".to_string(),
             timestamp: "2024-08-04".to_string(),
-            session_id: "test-session".to_string(),
-            role: "user".to_string(),
+            session_id: "test-session".into(),
+            role: "user".into(),
             text: "test message".to_string(),
-            message_type: "text".to_string(),
+            message_type: "text".into(),
             query: QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: "/test".to_string(),
+            cwd: "/test".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         }
     }
 
@@ -131,19 +131,22 @@ mod tests {
 
         // Test handling of invalid session file
         app.state.ui.selected_result = Some(SearchResult {
-            file: "/nonexistent/file.jsonl".to_string(),
+            file: "/nonexistent/file.jsonl".into(),
             uuid: "12345678-1234-5678-1234-567812345678".to_string(),
             timestamp: "2024-01-01T00:00:00Z".to_string(),
-            session_id: "87654321-4321-8765-4321-876543218765".to_string(),
-            role: "user".to_string(),
+            session_id: "87654321-4321-8765-4321-876543218765".into(),
+            role: "user".into(),
             text: "test".to_string(),
-            message_type: "user".to_string(),
+            message_type: "user".into(),
             query: QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: "/test".to_string(),
+            cwd: "/test".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         });
 
         // Test session loading failure handling
@@ -229,19 +232,22 @@ mod tests {
     // Helper functions
     fn create_test_result(role: &str, text: &str, timestamp: &str) -> SearchResult {
         SearchResult {
-            file: "/test/file.jsonl".to_string(),
+            file: "/test/file.jsonl".into(),
             uuid: "12345678-1234-5678-1234-567812345678".to_string(),
             timestamp: timestamp.to_string(),
-            session_id: "87654321-4321-8765-4321-876543218765".to_string(),
-            role: role.to_string(),
+            session_id: "87654321-4321-8765-4321-876543218765".into(),
+            role: role.into(),
             text: text.to_string(),
-            message_type: role.to_string(),
+            message_type: role.into(),
             query: QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: "/test/project".to_string(),
+            cwd: "/test/project".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         }
     }
 
@@ -436,6 +442,7 @@ mod tests {
                 file_path: None,
                 session_id: None,
                 role_filter: None,
+                highlight: None,
             },
             ui_state: UiStateSnapshot {
                 message: None,
@@ -443,6 +450,7 @@ mod tests {
                 selected_result: None,
                 truncation_enabled: true,
                 show_help: false,
+                diff_pair: None,
             },
         };
 
@@ -476,6 +484,7 @@ mod tests {
                 file_path: None,
                 session_id: None,
                 role_filter: None,
+                highlight: None,
             },
             ui_state: UiStateSnapshot {
                 message: None,
@@ -483,6 +492,7 @@ mod tests {
                 selected_result: None,
                 truncation_enabled: true,
                 show_help: false,
+                diff_pair: None,
             },
         };
         history.push(result_detail_state);
@@ -751,6 +761,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_selection_keys_in_search_mode() {
+        let mut app = InteractiveSearch::new(SearchOptions::default());
+        app.state.search.results = vec![SearchResult {
+            file: "/test.jsonl".into(),
+            uuid: "u1".to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s1".into(),
+            role: "user".into(),
+            text: "hello".to_string(),
+            message_type: "user".into(),
+            query: QueryCondition::Literal {
+                pattern: String::new(),
+                case_sensitive: false,
+            },
+            cwd: "/".into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        }];
+
+        app.handle_input(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(app.state.search.selection.len(), 1);
+        // Plain letters still go to the query
+        app.handle_input(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(app.state.search.selection.len(), 1);
+        app.handle_input(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::ALT))
+            .unwrap();
+        assert!(app.state.search.selection.is_empty());
+        app.handle_input(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::ALT))
+            .unwrap();
+        assert_eq!(app.state.mode, Mode::Search);
+    }
+
     /// Test empty search query returns all results
     #[test]
     fn test_empty_search_returns_all() {
@@ -897,34 +944,40 @@ mod tests {
         // Load search results into session viewer (unified architecture)
         app.state.session.search_results = vec![
             SearchResult {
-                file: "test.jsonl".to_string(),
+                file: "test.jsonl".into(),
                 uuid: "uuid1".to_string(),
                 timestamp: "2024-01-01T00:00:00Z".to_string(),
-                session_id: "session1".to_string(),
-                role: "user".to_string(),
+                session_id: "session1".into(),
+                role: "user".into(),
                 text: "Hello".to_string(),
-                message_type: "user".to_string(),
+                message_type: "user".into(),
                 query: crate::query::condition::QueryCondition::Literal {
                     pattern: "".to_string(),
                     case_sensitive: false,
                 },
-                cwd: "/test".to_string(),
+                cwd: "/test".into(),
                 raw_json: Some(r#"{"type":"user","message":{"content":"Hello"},"timestamp":"2024-01-01T00:00:00Z"}"#.to_string()),
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
             SearchResult {
-                file: "test.jsonl".to_string(),
+                file: "test.jsonl".into(),
                 uuid: "uuid2".to_string(),
                 timestamp: "2024-01-01T00:01:00Z".to_string(),
-                session_id: "session1".to_string(),
-                role: "assistant".to_string(),
+                session_id: "session1".into(),
+                role: "assistant".into(),
                 text: "Hi".to_string(),
-                message_type: "assistant".to_string(),
+                message_type: "assistant".into(),
                 query: crate::query::condition::QueryCondition::Literal {
                     pattern: "".to_string(),
                     case_sensitive: false,
                 },
-                cwd: "/test".to_string(),
+                cwd: "/test".into(),
                 raw_json: Some(r#"{"type":"assistant","message":{"content":"Hi"},"timestamp":"2024-01-01T00:01:00Z"}"#.to_string()),
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
         ];
         app.state.session.file_path = Some("test.jsonl".to_string());
@@ -1110,19 +1163,22 @@ mod tests {
 
         // Add some search results
         app.state.search.results = vec![SearchResult {
-            file: "test.jsonl".to_string(),
+            file: "test.jsonl".into(),
             uuid: "test-uuid".to_string(),
             timestamp: "2024-01-01T12:00:00Z".to_string(),
-            session_id: "test-session".to_string(),
-            role: "user".to_string(),
+            session_id: "test-session".into(),
+            role: "user".into(),
             text: "Test message".to_string(),
-            message_type: "message".to_string(),
+            message_type: "message".into(),
             query: QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: "/test".to_string(),
+            cwd: "/test".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         }];
 
         // Initially preview should be disabled
@@ -1170,6 +1226,9 @@ mod tests {
                 first_message: "Hello from session 1".to_string(),
                 preview_messages: vec![],
                 summary: None,
+                outcome: None,
+                compactions: 0,
+                cost_usd: None,
             },
             crate::interactive_ratatui::ui::app_state::SessionInfo {
                 file_path: "/path/to/session2.jsonl".to_string(),
@@ -1179,6 +1238,9 @@ mod tests {
                 first_message: "Hello from session 2".to_string(),
                 preview_messages: vec![],
                 summary: None,
+                outcome: None,
+                compactions: 0,
+                cost_usd: None,
             },
         ];
 
@@ -1240,6 +1302,9 @@ mod tests {
                 first_message: format!("Hello from session {i}"),
                 preview_messages: vec![],
                 summary: None,
+                outcome: None,
+                compactions: 0,
+                cost_usd: None,
             });
         }
         app.state.session_list.sessions = sessions.clone();
@@ -1296,6 +1361,9 @@ mod tests {
                 ),
             ],
             summary: Some("Test session with summary".to_string()),
+            outcome: None,
+            compactions: 0,
+            cost_usd: None,
         }];
         app.state.session_list.sessions = sessions.clone();
         app.state.session_list.filtered_sessions = sessions;
@@ -1331,4 +1399,62 @@ mod tests {
             "Should show first message"
         );
     }
+
+    #[test]
+    fn test_bookmark_from_detail_and_remove_in_bookmarks_tab() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = dir.path().join("session.jsonl");
+        std::fs::write(
+            &session,
+            r#"{"type":"user","message":{"role":"user","content":"Remember this"},"uuid":"u1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+        )
+        .unwrap();
+
+        let mut app = InteractiveSearch::new(SearchOptions::default());
+        app.set_bookmarks_path(dir.path().join("bookmarks.json"));
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let mut result = create_test_result("user", "Remember this", "2024-01-01T00:00:00Z");
+        result.file = session.to_string_lossy().to_string().into();
+        result.uuid = "u1".to_string();
+        result.session_id = "s1".into();
+        app.state.ui.selected_result = Some(result);
+        app.set_mode(Mode::MessageDetail);
+        terminal
+            .draw(|f| app.renderer.render(f, &app.state))
+            .unwrap();
+
+        app.handle_input(KeyEvent::from(KeyCode::Char('b')))
+            .unwrap();
+        assert_eq!(app.state.ui.message.as_deref(), Some("★ Bookmarked"));
+
+        app.set_mode(Mode::Search);
+        app.handle_message(Message::SwitchToBookmarksTab);
+        assert_eq!(app.state.search.current_tab, SearchTab::Bookmarks);
+        assert!(!app.state.bookmarks.is_loading);
+        assert_eq!(app.state.bookmarks.results.len(), 1);
+        assert_eq!(app.state.bookmarks.results[0].text, "Remember this");
+
+        terminal
+            .draw(|f| app.renderer.render(f, &app.state))
+            .unwrap();
+        assert!(buffer_contains(
+            terminal.backend().buffer(),
+            "Bookmarks - 1 total"
+        ));
+
+        // b on the Bookmarks tab removes the selected bookmark
+        app.handle_input(KeyEvent::from(KeyCode::Char('b')))
+            .unwrap();
+        assert!(app.state.bookmarks.results.is_empty());
+
+        app.handle_input(KeyEvent::from(KeyCode::BackTab)).unwrap();
+        assert_eq!(app.state.search.current_tab, SearchTab::Projects);
+        terminal
+            .draw(|f| app.renderer.render(f, &app.state))
+            .unwrap();
+        app.handle_input(KeyEvent::from(KeyCode::BackTab)).unwrap();
+        assert_eq!(app.state.search.current_tab, SearchTab::Search);
+    }
 }
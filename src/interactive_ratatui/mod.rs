@@ -1,9 +1,13 @@
 use anyhow::{Context, Result};
 use crossterm::{
-    event::{self, KeyCode, KeyEvent, KeyEventKind, poll},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyEventKind, MouseEvent,
+        poll,
+    },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use notify::{RecursiveMode, Watcher};
 use ratatui::{Terminal, backend::CrosstermBackend};
 #[cfg(unix)]
 use signal_hook::{
@@ -12,41 +16,82 @@ use signal_hook::{
     low_level::raise,
 };
 use smol::channel::{Receiver, Sender};
-use std::io::{self, Stdout};
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::io::{self, Stdout, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
 use std::time::Duration;
 
 use crate::SearchOptions;
+use crate::bookmarks::BookmarkStore;
+use crate::branches::SessionBranches;
+use crate::config::{self, Config, PreviewSetting};
 use crate::convert::{ConvertMode, ConvertRequest, convert_session_to_codex};
-
+use crate::cost::PricingTable;
+use crate::query::SavedSearches;
+use crate::scheduler::{self, Scheduler, TaskKind};
+use crate::search::compression::is_compressed;
+use crate::search::pattern_root;
+use crate::tail::Tail;
+
+pub mod announce;
 mod application;
-mod constants;
+pub(crate) mod constants;
 pub mod domain;
+pub mod event_log;
+pub mod record;
+pub mod script;
 pub mod ui;
 
+#[cfg(test)]
+mod announce_test;
+#[cfg(test)]
+mod event_log_test;
 #[cfg(test)]
 mod help_overlay_test;
 #[cfg(test)]
 mod integration_tests;
 #[cfg(test)]
+mod mouse_test;
+#[cfg(test)]
+mod record_test;
+#[cfg(test)]
+mod script_test;
+#[cfg(test)]
 mod session_preview_test;
 #[cfg(test)]
 mod session_view_integration_test;
 #[cfg(test)]
 mod tests;
 
-use self::application::search_service::SearchService;
+use self::announce::Announcer;
+use self::application::bookmark_service::BookmarkService;
+use self::application::cache_service::CacheService;
+use self::application::dismissal_service::DismissalService;
+use self::application::history_service::HistoryService;
+use self::application::search_service::{SearchService, SessionData};
 use self::constants::*;
-use self::domain::models::{Mode, SearchOrder, SearchRequest, SearchResponse, SessionOrder};
+use self::domain::models::{
+    Mode, PreviewPosition, SearchOrder, SearchRequest, SearchResponse, SessionListRequest,
+    SessionListResponse, SessionOrder,
+};
+use self::event_log::EventLog;
+use self::record::{Recorder, Tee};
 use self::ui::{
-    app_state::AppState, commands::Command, components::Component, events::Message,
+    app_state::AppState,
+    commands::Command,
+    components::Component,
+    events::Message,
+    keymap::KeyMap,
     renderer::Renderer,
+    theme::{self, Theme, ThemeName},
 };
 
-// Event type that can handle both key events and signals
+// Event type that can handle key and mouse events and signals
 enum Event {
     Key(KeyEvent),
+    Mouse(MouseEvent),
     #[cfg(unix)]
     Signal(i32),
 }
@@ -62,9 +107,26 @@ pub struct InteractiveSearch {
     state: AppState,
     renderer: Renderer,
     search_service: Arc<SearchService>,
+    /// `None` when the config directory cannot be located
+    bookmark_service: Option<Arc<BookmarkService>>,
+    /// `None` when the data directory cannot be located, or when replaying a script
+    history_service: Option<HistoryService>,
+    /// Session files opened in the viewer, parsed for their branches
+    session_cache: CacheService,
+    /// Keeps dismissed results between runs; only with `persist_dismissals`
+    dismissal_service: Option<DismissalService>,
+    /// config.toml, where preview layout changes are saved
+    config_path: Option<PathBuf>,
+    /// `[colors]` from config.toml, laid over whichever theme is picked
+    theme_colors: BTreeMap<String, String>,
     search_sender: Option<Sender<SearchRequest>>,
     search_receiver: Option<Receiver<SearchResponse>>,
     search_task: Option<smol::Task<()>>,
+    session_list_sender: Option<Sender<SessionListRequest>>,
+    session_list_receiver: Option<Receiver<SessionListResponse>>,
+    session_list_task: Option<smol::Task<()>>,
+    /// Session list loaded in the background before the tab is first opened
+    session_list_prefetch: Option<scheduler::Task<Result<Vec<SessionData>>>>,
     event_receiver: Option<Receiver<Event>>,
     event_tasks: Vec<smol::Task<()>>,
     current_search_id: u64,
@@ -75,19 +137,53 @@ pub struct InteractiveSearch {
     last_ctrl_c_press: Option<std::time::Instant>,
     message_timer: Option<std::time::Instant>,
     message_clear_delay: u64,
+    input_paused: Arc<AtomicBool>,
+    needs_full_redraw: bool,
+    /// Read position in the session file while the session viewer follows it
+    session_tail: Option<Tail>,
+    last_follow_poll: Option<std::time::Instant>,
+    /// Kept alive to hear about session files changing while results are shown
+    corpus_watcher: Option<notify::RecommendedWatcher>,
+    corpus_events: Option<mpsc::Receiver<notify::Result<notify::Event>>>,
+    corpus_changed_at: Option<std::time::Instant>,
+    last_input: Option<std::time::Instant>,
+    key_map: KeyMap,
+    /// `--record`: gets the terminal output and every drawn screen
+    recorder: Option<Arc<Mutex<Recorder>>>,
+    /// `--announce`: describes state changes for screen readers
+    announcer: Option<Announcer>,
+    /// `--event-log`: gets every message and command processed
+    event_log: Option<EventLog>,
+    /// `mouse = true` in config.toml: the terminal reports the mouse
+    mouse: bool,
 }
 
 impl InteractiveSearch {
     pub fn new(options: SearchOptions) -> Self {
         let search_service = Arc::new(SearchService::new(options.clone()));
 
+        let mut state = AppState::new();
+        state.ui.hide_tool_results = options.exclude_tool_result_messages;
+
         Self {
-            state: AppState::new(),
+            state,
             renderer: Renderer::new(),
             search_service,
+            bookmark_service: BookmarkStore::default_path()
+                .ok()
+                .map(|path| Arc::new(BookmarkService::new(path))),
+            history_service: HistoryService::default_path().ok().map(HistoryService::new),
+            session_cache: CacheService::new(),
+            dismissal_service: None,
+            config_path: Config::default_path().ok(),
+            theme_colors: BTreeMap::new(),
             search_sender: None,
             search_receiver: None,
             search_task: None,
+            session_list_sender: None,
+            session_list_receiver: None,
+            session_list_task: None,
+            session_list_prefetch: None,
             event_receiver: None,
             event_tasks: Vec::new(),
             current_search_id: 0,
@@ -98,6 +194,19 @@ impl InteractiveSearch {
             last_ctrl_c_press: None,
             message_timer: None,
             message_clear_delay: MESSAGE_CLEAR_DELAY_MS,
+            input_paused: Arc::new(AtomicBool::new(false)),
+            needs_full_redraw: false,
+            session_tail: None,
+            last_follow_poll: None,
+            corpus_watcher: None,
+            corpus_events: None,
+            corpus_changed_at: None,
+            last_input: None,
+            key_map: KeyMap::default(),
+            recorder: None,
+            announcer: None,
+            event_log: None,
+            mouse: false,
         }
     }
 
@@ -113,6 +222,113 @@ impl InteractiveSearch {
         };
     }
 
+    /// Start with syntax highlighting of code blocks on or off (`h` toggles it)
+    pub fn set_syntax_highlight(&mut self, enabled: bool) {
+        self.state.ui.syntax_highlight = enabled;
+    }
+
+    /// Order of the search results until Ctrl+O changes it (`--sort`)
+    pub fn set_search_order(&mut self, order: SearchOrder) {
+        self.state.search.order = order;
+    }
+
+    /// Remap keys as configured in the `[keybindings]` table of config.toml
+    pub fn set_key_map(&mut self, key_map: KeyMap) {
+        self.key_map = key_map;
+    }
+
+    /// Place the preview as configured by `preview` and `preview_size` in
+    /// config.toml
+    pub fn set_preview(&mut self, preview: Option<PreviewSetting>, size: Option<u16>) {
+        match preview {
+            Some(PreviewSetting::Hidden) => {
+                self.state.search.preview_enabled = false;
+                self.state.session_list.preview_enabled = false;
+            }
+            Some(setting) => {
+                self.state.search.preview_enabled = true;
+                self.state.session_list.preview_enabled = true;
+                self.state.ui.preview_position = if setting == PreviewSetting::Bottom {
+                    PreviewPosition::Bottom
+                } else {
+                    PreviewPosition::Right
+                };
+            }
+            None => {}
+        }
+        self.state.ui.preview_size = size;
+    }
+
+    /// Draw with `theme` from config.toml and its `[colors]` laid over it;
+    /// the colors stay laid over any theme picked with F2
+    pub fn set_theme(&mut self, name: ThemeName, colors: BTreeMap<String, String>) -> Result<()> {
+        theme::set(Theme::with_colors(name, &colors)?);
+        self.state.ui.theme = name;
+        self.theme_colors = colors;
+        Ok(())
+    }
+
+    /// Estimate session costs in the session list at `pricing`, the
+    /// built-in prices with config.toml's `[pricing]` laid over them
+    pub fn set_pricing(&mut self, pricing: PricingTable) {
+        // Only shared once `run` starts the session list loader
+        if let Some(service) = Arc::get_mut(&mut self.search_service) {
+            service.set_pricing(pricing);
+        }
+    }
+
+    /// Save preview layout changes to `path` instead of the default config.toml
+    #[cfg(test)]
+    pub fn set_config_path(&mut self, path: Option<PathBuf>) {
+        self.config_path = path;
+    }
+
+    /// Record the session to `path`: an asciinema recording for `.cast`,
+    /// text dumps of the screens otherwise
+    pub fn set_record_path(&mut self, path: &Path) -> Result<()> {
+        let (width, height) = crossterm::terminal::size()
+            .ok()
+            .filter(|&(width, height)| width > 0 && height > 0)
+            .unwrap_or((80, 24));
+        let recorder = Recorder::create(path, width, height)?;
+        self.recorder = Some(Arc::new(Mutex::new(recorder)));
+        Ok(())
+    }
+
+    /// Announce state changes in plain text to `target`: `-` for stderr, or a
+    /// file or named pipe a screen reader follows
+    pub fn set_announce_target(&mut self, target: &str) -> Result<()> {
+        self.announcer = Some(Announcer::open(target)?);
+        Ok(())
+    }
+
+    /// Log every message and command processed to `log`
+    pub fn set_event_log(&mut self, log: EventLog) {
+        self.event_log = Some(log);
+    }
+
+    /// Capture the mouse to click results and tabs, scroll with the wheel and
+    /// drag the preview border
+    pub fn set_mouse(&mut self, enabled: bool) {
+        self.mouse = enabled;
+    }
+
+    /// Memory the session file cache may take (`cache_memory_mb` in
+    /// config.toml)
+    pub fn set_cache_memory_cap(&mut self, memory_cap: usize) {
+        self.session_cache.set_memory_cap(memory_cap);
+    }
+
+    /// Remember the results dismissed with Alt+X in the data directory, so
+    /// they stay hidden from the same query in later runs
+    /// (`persist_dismissals` in config.toml)
+    pub fn set_persist_dismissals(&mut self, enabled: bool) {
+        self.dismissal_service = enabled
+            .then(DismissalService::default_path)
+            .and_then(Result::ok)
+            .map(DismissalService::new);
+    }
+
     pub fn set_start_latest_message_detail(&mut self, start_latest: bool) {
         self.initial_view = if start_latest {
             InitialView::LatestMessageDetail
@@ -127,7 +343,12 @@ impl InteractiveSearch {
         // Resolve the latest session before terminal setup so errors can return cleanly.
         let latest_session = if self.initial_view != InitialView::Search {
             let search_service = self.search_service.clone();
-            let sessions = blocking::unblock(move || search_service.get_all_sessions()).await?;
+            let sessions = Scheduler::global()
+                .spawn(TaskKind::SessionList, move || {
+                    search_service.get_all_sessions()
+                })
+                .join()
+                .await?;
 
             if sessions.is_empty() {
                 anyhow::bail!("No sessions found");
@@ -146,11 +367,12 @@ impl InteractiveSearch {
         self.event_receiver = Some(event_rx);
         self.event_tasks = event_tasks;
 
-        // Start search worker task
-        let (tx, rx, task) = self.start_search_worker();
-        self.search_sender = Some(tx);
-        self.search_receiver = Some(rx);
-        self.search_task = Some(task);
+        // Watching is best effort; without it results only refresh on demand
+        self.watch_corpus();
+
+        self.start_workers();
+        self.load_history();
+        self.load_dismissals();
 
         if let Some((file_path, session_id)) = latest_session {
             // Save initial Search state so Esc / Alt+Left can restore it.
@@ -191,93 +413,149 @@ impl InteractiveSearch {
             // Initial search (even with empty pattern to show all results)
             // Note: pattern is stored internally but not shown in search bar
             self.execute_command(Command::ExecuteSearch).await;
+
+            // Have the session list ready by the time its tab is opened
+            let search_service = self.search_service.clone();
+            self.session_list_prefetch =
+                Some(Scheduler::global().spawn(TaskKind::Background, move || {
+                    search_service.get_all_sessions()
+                }));
         }
 
         let result = self.run_app(&mut terminal, pattern).await;
 
         // Clean up tasks
-        if let Some(task) = self.search_task.take() {
-            task.cancel().await;
-        }
+        self.stop_workers().await;
         for task in self.event_tasks.drain(..) {
             task.cancel().await;
         }
 
         self.cleanup_terminal(&mut terminal)?;
+        self.save_history();
+        if let Some(recorder) = &self.recorder {
+            recorder
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .finish()
+                .context("Failed to write recording")?;
+        }
         result
     }
 
-    fn setup_terminal(&self) -> Result<Terminal<CrosstermBackend<Stdout>>> {
+    fn load_history(&mut self) {
+        let Some(history_service) = &self.history_service else {
+            return;
+        };
+        match history_service.load() {
+            Ok(entries) => self.handle_message(Message::HistoryLoaded(entries)),
+            Err(e) => self.state.ui.message = Some(format!("Failed to load search history: {e}")),
+        }
+    }
+
+    fn load_dismissals(&mut self) {
+        let Some(dismissal_service) = &self.dismissal_service else {
+            return;
+        };
+        match dismissal_service.load() {
+            Ok(dismissed) => self.handle_message(Message::DismissalsLoaded(dismissed)),
+            Err(e) => self.state.ui.message = Some(format!("Failed to load dismissals: {e:#}")),
+        }
+    }
+
+    /// Append the queries searched this session, once the terminal is restored
+    fn save_history(&mut self) {
+        let Some(history_service) = &self.history_service else {
+            return;
+        };
+        if self.state.history.added.is_empty() {
+            return;
+        }
+        if let Err(e) = history_service.append(&self.state.history.added) {
+            eprintln!("Failed to save search history: {e}");
+        }
+    }
+
+    /// Start the search and session list workers
+    fn start_workers(&mut self) {
+        let (tx, rx, task) = self.start_search_worker();
+        self.search_sender = Some(tx);
+        self.search_receiver = Some(rx);
+        self.search_task = Some(task);
+
+        let (tx, rx, task) = self.start_session_list_worker();
+        self.session_list_sender = Some(tx);
+        self.session_list_receiver = Some(rx);
+        self.session_list_task = Some(task);
+    }
+
+    async fn stop_workers(&mut self) {
+        if let Some(task) = self.search_task.take() {
+            task.cancel().await;
+        }
+        if let Some(task) = self.session_list_task.take() {
+            task.cancel().await;
+        }
+    }
+
+    fn setup_terminal(&self) -> Result<Terminal<CrosstermBackend<Tee<Stdout>>>> {
         enable_raw_mode()?;
-        let mut stdout = io::stdout();
+        let mut stdout = Tee::new(io::stdout(), self.recorder.clone());
         execute!(stdout, EnterAlternateScreen)?;
+        self.capture_mouse(&mut stdout, true)?;
         let backend = CrosstermBackend::new(stdout);
         let terminal = Terminal::new(backend)?;
         Ok(terminal)
     }
 
-    fn cleanup_terminal(&self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+    fn cleanup_terminal(
+        &self,
+        terminal: &mut Terminal<CrosstermBackend<Tee<Stdout>>>,
+    ) -> Result<()> {
         disable_raw_mode()?;
+        self.capture_mouse(terminal.backend_mut(), false)?;
         execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
         terminal.show_cursor()?;
         Ok(())
     }
 
+    /// Turn mouse reporting on or off, if the mouse is captured at all
+    fn capture_mouse(&self, out: &mut impl Write, enabled: bool) -> io::Result<()> {
+        match (self.mouse, enabled) {
+            (false, _) => Ok(()),
+            (true, true) => execute!(out, EnableMouseCapture),
+            (true, false) => execute!(out, DisableMouseCapture),
+        }
+    }
+
     async fn run_app(
         &mut self,
-        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        terminal: &mut Terminal<CrosstermBackend<Tee<Stdout>>>,
         _pattern: &str,
     ) -> Result<()> {
         loop {
-            terminal.draw(|f| {
+            if self.needs_full_redraw {
+                // Screen contents were lost while an external program owned the terminal
+                self.needs_full_redraw = false;
+                terminal.clear()?;
+            }
+
+            let frame = terminal.draw(|f| {
                 self.renderer.render(f, &self.state);
             })?;
-
-            // Check for search results
-            if let Some(receiver) = &self.search_receiver
-                && let Ok(response) = receiver.try_recv()
-                && response.id == self.state.search.current_search_id
-            {
-                // Check if there's an error in the response
-                if let Some(error) = response.error {
-                    self.state.ui.message = Some(error);
-                    self.state.search.is_searching = false;
-                    self.state.search.loading_more = false;
-                } else {
-                    // Check if this is a pagination response (loading more)
-                    let msg = if self.state.search.loading_more {
-                        Message::MoreResultsLoaded(response.results)
-                    } else {
-                        Message::SearchCompleted(response.results)
-                    };
-                    self.handle_message(msg);
-                }
+            if let Some(recorder) = &self.recorder {
+                recorder
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .frame(frame.buffer)?;
             }
-
-            // Check for scheduled search
-            if let Some(delay) = self.scheduled_search_delay
-                && let Some(timer) = self.last_search_timer
-                && timer.elapsed() >= Duration::from_millis(delay)
+            if let Some(announcer) = &mut self.announcer
+                && let Err(e) = announcer.update(&self.state)
             {
-                self.scheduled_search_delay = None;
-                self.last_search_timer = None;
-                // Check which type of search to execute based on current tab
-                if self.state.mode == Mode::Search
-                    && self.state.search.current_tab == domain::models::SearchTab::SessionList
-                {
-                    self.handle_message(Message::SessionListSearchRequested);
-                } else {
-                    self.execute_command(Command::ExecuteSearch).await;
-                }
+                self.announcer = None;
+                self.state.ui.message = Some(format!("Stopped announcing: {e}"));
             }
 
-            // Check for scheduled message clear
-            if let Some(timer) = self.message_timer
-                && timer.elapsed() >= Duration::from_millis(self.message_clear_delay)
-            {
-                self.message_timer = None;
-                self.execute_command(Command::ClearMessage).await;
-            }
+            self.poll_background().await;
 
             // Check for events (key presses or signals)
             if let Some(event_receiver) = &self.event_receiver {
@@ -289,11 +567,13 @@ impl InteractiveSearch {
                             break;
                         }
                     }
+                    Ok(Event::Mouse(mouse)) => self.handle_mouse(mouse),
                     #[cfg(unix)]
                     Ok(Event::Signal(SIGCONT)) => {
                         // Terminal was resumed from background, reinitialize
                         enable_raw_mode()?;
                         execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+                        self.capture_mouse(terminal.backend_mut(), true)?;
                         terminal.clear()?;
                     }
                     #[cfg(unix)]
@@ -312,14 +592,106 @@ impl InteractiveSearch {
         Ok(())
     }
 
+    /// Take in whatever finished since the last frame: search responses,
+    /// debounced searches, the followed session and expired messages
+    async fn poll_background(&mut self) {
+        // Check for search results
+        if let Some(receiver) = &self.search_receiver
+            && let Ok(response) = receiver.try_recv()
+            && response.id == self.state.search.current_search_id
+        {
+            // Check if there's an error in the response
+            if let Some(error) = response.error {
+                self.state.ui.message = Some(error);
+                self.state.search.is_searching = false;
+                self.state.search.loading_more = false;
+                self.state.search.progress = None;
+            } else if let Some(progress) = response.progress {
+                self.handle_message(Message::SearchBatchReceived(response.results, progress));
+            } else {
+                // Check if this is a pagination response (loading more)
+                let msg = if self.state.search.loading_more {
+                    Message::MoreResultsLoaded(response.results)
+                } else {
+                    Message::SearchCompleted(response.results)
+                };
+                self.handle_message(msg);
+            }
+        }
+
+        // Check for session list search results
+        if let Some(receiver) = &self.session_list_receiver
+            && let Ok(response) = receiver.try_recv()
+            && response.id == self.state.session_list.current_search_id
+        {
+            if let Some(error) = response.error {
+                self.state.ui.message = Some(error);
+                self.state.session_list.is_searching = false;
+            } else {
+                let filtered = self
+                    .state
+                    .session_list
+                    .sessions
+                    .iter()
+                    .filter(|session| response.matching_files.contains(&session.file_path))
+                    .cloned()
+                    .collect();
+                self.handle_message(Message::SessionListSearchCompleted(filtered));
+            }
+        }
+
+        // Check for scheduled search
+        if let Some(delay) = self.scheduled_search_delay
+            && let Some(timer) = self.last_search_timer
+            && timer.elapsed() >= Duration::from_millis(delay)
+        {
+            self.scheduled_search_delay = None;
+            self.last_search_timer = None;
+            // Check which type of search to execute based on current tab
+            if self.state.mode == Mode::Search
+                && self.state.search.current_tab == domain::models::SearchTab::SessionList
+            {
+                self.handle_message(Message::SessionListSearchRequested);
+            } else {
+                self.execute_command(Command::ExecuteSearch).await;
+            }
+        }
+
+        // Check the followed session for new messages
+        if self.state.session.following
+            && self.state.mode == Mode::SessionViewer
+            && self
+                .last_follow_poll
+                .is_none_or(|last| last.elapsed() >= Duration::from_millis(FOLLOW_POLL_INTERVAL_MS))
+        {
+            self.last_follow_poll = Some(std::time::Instant::now());
+            self.poll_followed_session();
+        }
+
+        // Check for changes to the session files behind the results
+        self.check_corpus_changes();
+
+        // Check for scheduled message clear
+        if let Some(timer) = self.message_timer
+            && timer.elapsed() >= Duration::from_millis(self.message_clear_delay)
+        {
+            self.message_timer = None;
+            self.execute_command(Command::ClearMessage).await;
+        }
+    }
+
     fn handle_input(&mut self, key: KeyEvent) -> Result<bool> {
         use crossterm::event::KeyModifiers;
 
+        self.last_input = Some(std::time::Instant::now());
+        let key = self.key_map.translate(key);
+
         // Handle Ctrl+Z for background suspend (Unix only — no Windows equivalent)
         #[cfg(unix)]
         if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
             // Cleanup terminal before suspending
             disable_raw_mode()?;
+            self.capture_mouse(&mut io::stdout(), false)?;
             execute!(io::stdout(), LeaveAlternateScreen)?;
 
             // Raise SIGTSTP to actually suspend the process
@@ -361,6 +733,24 @@ impl InteractiveSearch {
             // Block all other input when help is showing
             return Ok(false);
         }
+        if self.state.ui.context_chart.is_some() {
+            if let Some(msg) = self.renderer.get_context_chart_mut().handle_key(key) {
+                self.handle_message(msg);
+            }
+            return Ok(false);
+        }
+        if self.state.ui.theme_picker.is_some() {
+            if let Some(msg) = self.renderer.get_theme_picker_mut().handle_key(key) {
+                self.handle_message(msg);
+            }
+            return Ok(false);
+        }
+        if self.state.ui.saved_searches.is_some() {
+            if let Some(msg) = self.renderer.get_saved_search_picker_mut().handle_key(key) {
+                self.handle_message(msg);
+            }
+            return Ok(false);
+        }
 
         // Global keys (only when help is not showing)
         match key.code {
@@ -368,6 +758,19 @@ impl InteractiveSearch {
                 self.handle_message(Message::ShowHelp);
                 return Ok(false);
             }
+            KeyCode::F(2) => {
+                self.handle_message(Message::ShowThemePicker);
+                return Ok(false);
+            }
+            KeyCode::F(3) if self.state.mode == Mode::Search => {
+                match SavedSearches::default_path().and_then(|path| SavedSearches::load(&path)) {
+                    Ok(saved) => self.handle_message(Message::ShowSavedSearches(
+                        saved.searches.into_iter().collect(),
+                    )),
+                    Err(e) => self.state.ui.message = Some(format!("{e:#}")),
+                }
+                return Ok(false);
+            }
             KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 // Send appropriate preview message based on current mode
                 let message = match self.state.mode {
@@ -406,6 +809,7 @@ impl InteractiveSearch {
             Mode::Search => self.handle_search_mode_input(key),
             Mode::MessageDetail => self.renderer.get_message_detail_mut().handle_key(key),
             Mode::SessionViewer => self.renderer.get_session_viewer_mut().handle_key(key),
+            Mode::MessageDiff => self.renderer.get_diff_viewer_mut().handle_key(key),
         };
 
         if let Some(msg) = message {
@@ -415,10 +819,48 @@ impl InteractiveSearch {
         Ok(false)
     }
 
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        self.last_input = Some(std::time::Instant::now());
+
+        // Overlays are driven by the keyboard alone
+        if self.state.ui.show_help
+            || self.state.ui.context_chart.is_some()
+            || self.state.ui.theme_picker.is_some()
+            || self.state.ui.saved_searches.is_some()
+        {
+            return;
+        }
+        if let Some(message) = self.renderer.handle_mouse(mouse, &self.state) {
+            self.handle_message(message);
+        }
+    }
+
     fn handle_search_mode_input(&mut self, key: KeyEvent) -> Option<Message> {
         use self::domain::models::SearchTab;
         use crossterm::event::KeyModifiers;
 
+        // Ctrl+R history search takes every key until it is closed
+        if self.state.search.current_tab == SearchTab::Search && self.state.history.search.is_some()
+        {
+            return self.renderer.get_search_bar_mut().handle_history_key(key);
+        }
+
+        // Preview placement, in every tab that has a preview
+        if self.state.search.current_tab != SearchTab::Projects
+            && key.modifiers.contains(KeyModifiers::ALT)
+            && !key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            let step = PREVIEW_SIZE_STEP as i16;
+            match key.code {
+                KeyCode::Char('p') => return Some(Message::CyclePreviewPosition),
+                KeyCode::Char('=') | KeyCode::Char('+') => {
+                    return Some(Message::ResizePreview(step));
+                }
+                KeyCode::Char('-') => return Some(Message::ResizePreview(-step)),
+                _ => {}
+            }
+        }
+
         // Handle tab bar navigation first
         if self.state.search.current_tab == SearchTab::Search {
             // Check if tab bar can handle the key
@@ -446,6 +888,22 @@ impl InteractiveSearch {
                     return self.renderer.get_session_list_mut().handle_key(key);
                 }
             }
+        } else if self.state.search.current_tab == SearchTab::Bookmarks {
+            if let Some(msg) = self.renderer.get_tab_bar_mut().handle_key(key) {
+                return Some(msg);
+            }
+            let bookmark_list = self.renderer.get_bookmark_list_mut();
+            return match key.code {
+                KeyCode::Char('b') | KeyCode::Delete => bookmark_list
+                    .selected_result()
+                    .map(|result| Message::ToggleBookmark(Box::new(result.clone()))),
+                _ => bookmark_list.handle_key(key),
+            };
+        } else if self.state.search.current_tab == SearchTab::Projects {
+            if let Some(msg) = self.renderer.get_tab_bar_mut().handle_key(key) {
+                return Some(msg);
+            }
+            return self.renderer.get_project_list_mut().handle_key(key);
         }
 
         match key.code {
@@ -464,6 +922,54 @@ impl InteractiveSearch {
             KeyCode::Char('o') if key.modifiers == KeyModifiers::CONTROL => {
                 Some(Message::ToggleSearchOrder)
             }
+            KeyCode::Char('r') if key.modifiers == KeyModifiers::CONTROL => {
+                Some(Message::StartHistorySearch)
+            }
+            KeyCode::Char('t') if key.modifiers == KeyModifiers::ALT => {
+                Some(Message::ToggleToolResults)
+            }
+            KeyCode::Char('h') if key.modifiers == KeyModifiers::ALT => {
+                Some(Message::ToggleTimeline)
+            }
+            KeyCode::F(5) => Some(Message::RefreshSearch),
+            // Up on the first result of an empty query recalls earlier queries;
+            // Up/Down keep cycling until the query is edited
+            KeyCode::Up
+                if self.state.history.cycle.is_some()
+                    || (self.state.search.query.is_empty()
+                        && self.state.search.selected_index == 0) =>
+            {
+                Some(Message::HistoryPrevious)
+            }
+            KeyCode::Down if self.state.history.cycle.is_some() => Some(Message::HistoryNext),
+            // Multi-select; plain keys are typed into the query
+            KeyCode::Char('x') if key.modifiers == KeyModifiers::CONTROL => {
+                Some(Message::ToggleResultSelection)
+            }
+            KeyCode::Char('a') if key.modifiers == KeyModifiers::ALT => {
+                Some(Message::ToggleSelectAll)
+            }
+            KeyCode::Char('y') if key.modifiers == KeyModifiers::CONTROL => {
+                Some(Message::CopySelection)
+            }
+            KeyCode::Char('s') if key.modifiers == KeyModifiers::ALT => {
+                Some(Message::ExportSelection)
+            }
+            KeyCode::Char('d') if key.modifiers == KeyModifiers::ALT => {
+                Some(Message::ViewSelection)
+            }
+            // Triage; plain x is typed into the query
+            KeyCode::Char('x') if key.modifiers == KeyModifiers::ALT => {
+                Some(Message::DismissResult)
+            }
+            KeyCode::Char('u') if key.modifiers == KeyModifiers::ALT => {
+                Some(Message::RestoreDismissed)
+            }
+            KeyCode::Char('e') if key.modifiers == KeyModifiers::ALT => self
+                .renderer
+                .get_result_list_mut()
+                .selected_result()
+                .map(|result| Message::OpenInEditor(result.file.to_string(), result.line_number)),
             KeyCode::Up
             | KeyCode::Down
             | KeyCode::PageUp
@@ -499,16 +1005,45 @@ impl InteractiveSearch {
     }
 
     fn handle_message(&mut self, message: Message) {
+        let logged = self.event_log.is_some().then(|| message.clone());
         let command = self.state.update(message);
+        if let (Some(log), Some(message)) = (&mut self.event_log, logged)
+            && let Err(e) = log.message(&message, self.state.mode)
+        {
+            self.event_log = None;
+            self.state.ui.message = Some(format!("Stopped logging events: {e}"));
+        }
         smol::block_on(self.execute_command(command));
     }
 
+    /// Set `key` in config.toml, leaving the "saved" message up for a while
+    fn save_config(&mut self, key: &str, value: toml::Value) {
+        let saved = match &self.config_path {
+            Some(path) => config::set_value(path, key, value),
+            None => Ok(()),
+        };
+        match saved {
+            Ok(()) => self.message_timer = Some(std::time::Instant::now()),
+            Err(e) => self.state.ui.message = Some(format!("Failed to save {key}: {e:#}")),
+        }
+    }
+
     async fn execute_command(&mut self, command: Command) {
+        if let Some(log) = &mut self.event_log
+            && let Err(e) = log.command(&command, self.state.mode)
+        {
+            self.event_log = None;
+            self.state.ui.message = Some(format!("Stopped logging events: {e}"));
+        }
         match command {
             Command::None => {}
             Command::ExecuteSearch => {
                 self.execute_search().await;
             }
+            Command::RefreshSearch => {
+                crate::search::discovery_cache::refresh();
+                self.execute_search().await;
+            }
             Command::ExecuteSessionSearch => {
                 self.execute_session_search().await;
             }
@@ -526,12 +1061,43 @@ impl InteractiveSearch {
             Command::LoadSession(file_path) => {
                 self.load_session_messages(&file_path);
             }
+            Command::FollowSession(file_path) => {
+                self.follow_session(&file_path);
+            }
             Command::LoadSessionList => {
                 self.load_session_list().await;
             }
             Command::LoadMore(offset) => {
                 self.load_more_results(offset).await;
             }
+            Command::LoadBookmarks => {
+                self.load_bookmarks().await;
+            }
+            Command::LoadProjects => {
+                self.load_projects().await;
+            }
+            Command::ToggleBookmark(result) => {
+                let Some(bookmark_service) = self.bookmark_service.clone() else {
+                    self.state.ui.message = Some("⚠ Bookmarks are unavailable".to_string());
+                    return;
+                };
+                match bookmark_service.toggle(&result) {
+                    Ok(bookmarked) => {
+                        self.state.ui.message = Some(if bookmarked {
+                            "★ Bookmarked".to_string()
+                        } else {
+                            "Bookmark removed".to_string()
+                        });
+                        self.message_timer = Some(std::time::Instant::now());
+                        if self.state.search.current_tab == domain::models::SearchTab::Bookmarks {
+                            self.load_bookmarks().await;
+                        }
+                    }
+                    Err(e) => {
+                        self.state.ui.message = Some(format!("Failed to save bookmark: {e}"));
+                    }
+                }
+            }
             Command::ConvertSessionToCodex {
                 session_id,
                 file_path,
@@ -580,6 +1146,11 @@ impl InteractiveSearch {
                     ui::events::CopyContent::SessionMarkdown(markdown) => {
                         (markdown, "✓ Copied session as Markdown".to_string())
                     }
+                    ui::events::CopyContent::Urls(urls) => (urls, "✓ Copied URLs".to_string()),
+                    ui::events::CopyContent::Diff(diff) => (diff, "✓ Copied diff".to_string()),
+                    ui::events::CopyContent::Jsonl(jsonl) => {
+                        (jsonl, "✓ Copied selected results as JSONL".to_string())
+                    }
                 };
 
                 if let Err(e) = self.copy_to_clipboard(&text) {
@@ -591,6 +1162,77 @@ impl InteractiveSearch {
                     self.message_timer = Some(std::time::Instant::now());
                 }
             }
+            Command::ExportResults(results) => {
+                let path = PathBuf::from(format!(
+                    "ccms-selection-{}.jsonl",
+                    chrono::Local::now().format("%Y%m%d-%H%M%S")
+                ));
+                match std::fs::write(&path, ui::app_state::to_jsonl(&results)) {
+                    Ok(()) => {
+                        self.state.ui.message = Some(format!(
+                            "✓ Exported {} results to {}",
+                            results.len(),
+                            path.display()
+                        ));
+                        self.message_timer = Some(std::time::Instant::now());
+                    }
+                    Err(e) => {
+                        self.state.ui.message =
+                            Some(format!("Failed to export {}: {e}", path.display()));
+                    }
+                }
+            }
+            Command::ReadClipboard(result) => match self.read_clipboard() {
+                Ok(text) if text.is_empty() => {
+                    self.state.ui.message = Some("⚠ Clipboard is empty".to_string());
+                    self.message_timer = Some(std::time::Instant::now());
+                }
+                Ok(text) => self.handle_message(Message::ClipboardRead(result, text)),
+                Err(e) => {
+                    self.state.ui.message = Some(format!("Failed to read clipboard: {e}"));
+                }
+            },
+            Command::OpenInPager(text) => {
+                if let Err(e) = self.open_in_pager(&text) {
+                    self.state.ui.message = Some(format!("Failed to open pager: {e}"));
+                }
+                self.needs_full_redraw = true;
+            }
+            Command::LoadContextChart(file_path) => {
+                match crate::usage::session_context(std::path::Path::new(&file_path)) {
+                    Ok(points) => self.handle_message(Message::ContextChartLoaded(points)),
+                    Err(e) => {
+                        self.state.ui.message = Some(format!("Failed to read token usage: {e:#}"));
+                    }
+                }
+            }
+            Command::OpenInEditor(file, line) => {
+                if let Err(e) = self.open_in_editor(&file, line) {
+                    self.state.ui.message = Some(format!("Failed to open editor: {e:#}"));
+                }
+                self.needs_full_redraw = true;
+            }
+            Command::SaveConfig(key, value) => self.save_config(key, value),
+            Command::SaveDismissals(query, dismissed) => {
+                let saved = match &self.dismissal_service {
+                    Some(service) => service.save(&query, &dismissed),
+                    None => Ok(()),
+                };
+                match saved {
+                    Ok(()) => self.message_timer = Some(std::time::Instant::now()),
+                    Err(e) => {
+                        self.state.ui.message = Some(format!("Failed to save dismissals: {e:#}"))
+                    }
+                }
+            }
+            Command::ApplyTheme(name) => {
+                // The colors were checked when the config was loaded
+                theme::set(
+                    Theme::with_colors(name, &self.theme_colors)
+                        .unwrap_or_else(|_| Theme::builtin(name)),
+                );
+                self.save_config("theme", toml::Value::String(name.as_str().to_string()));
+            }
             Command::ShowMessage(msg) => {
                 self.state.ui.message = Some(msg);
             }
@@ -616,6 +1258,7 @@ impl InteractiveSearch {
         self.current_search_id += 1;
         self.state.search.current_search_id = self.current_search_id;
         self.state.search.is_searching = true;
+        self.state.search.progress = None;
 
         if let Some(sender) = &self.search_sender {
             let request = SearchRequest {
@@ -623,21 +1266,39 @@ impl InteractiveSearch {
                 query: self.state.search.query.clone(),
                 role_filter: self.state.search.role_filter.clone(),
                 pattern: self.pattern.clone(),
+                project: self
+                    .state
+                    .search
+                    .project
+                    .as_ref()
+                    .map(|project| project.dir.clone()),
                 order: self.state.search.order,
                 limit: Some(100), // Initial load limit for pagination
                 offset: None,
+                hide_tool_results: self.state.ui.hide_tool_results,
             };
             let _ = sender.send(request).await;
         }
     }
 
     fn load_session_messages(&mut self, file_path: &str) {
+        // A session that cannot be split into branches is shown whole
+        let branches = self
+            .session_cache
+            .get_messages(Path::new(file_path))
+            .map(|cached| SessionBranches::from_messages(&cached.messages))
+            .unwrap_or_default();
+        self.state.session.set_branches(branches);
+        self.renderer
+            .get_help_dialog_mut()
+            .set_session_cache(self.session_cache.stats());
         // Use search service to load session messages with session_id filter
         if let Some(session_id) = &self.state.session.session_id {
             let request = SearchRequest {
                 id: self.state.search.current_search_id,
                 query: self.state.session.query.clone(),
                 pattern: file_path.to_string(),
+                project: None,
                 role_filter: self.state.session.role_filter.clone(),
                 order: match self.state.session.order {
                     SessionOrder::Ascending => SearchOrder::Ascending,
@@ -645,6 +1306,7 @@ impl InteractiveSearch {
                 },
                 limit: None, // No limit for session viewer
                 offset: None,
+                hide_tool_results: self.state.ui.hide_tool_results,
             };
 
             match self
@@ -652,7 +1314,7 @@ impl InteractiveSearch {
                 .search_session(request, session_id.clone())
             {
                 Ok(response) => {
-                    self.state.session.search_results = response.results;
+                    self.state.session.set_results(response.results);
                     // Clear old messages - will be removed later after full migration
                     self.state.session.messages = vec![];
                     self.state.session.filtered_indices = vec![];
@@ -666,6 +1328,98 @@ impl InteractiveSearch {
         }
     }
 
+    fn watch_corpus(&mut self) {
+        let root = pattern_root(Some(&self.pattern));
+        let (sender, events) = mpsc::channel();
+        let Ok(mut watcher) = notify::recommended_watcher(sender) else {
+            return;
+        };
+        if watcher.watch(&root, RecursiveMode::Recursive).is_ok() {
+            self.corpus_watcher = Some(watcher);
+            self.corpus_events = Some(events);
+        }
+    }
+
+    fn check_corpus_changes(&mut self) {
+        if let Some(events) = &self.corpus_events {
+            while let Ok(event) = events.try_recv() {
+                if let Ok(event) = event
+                    && changes_session_file(&event)
+                {
+                    self.corpus_changed_at = Some(std::time::Instant::now());
+                }
+            }
+        }
+
+        // Wait for a burst of writes to settle before reacting
+        if let Some(changed_at) = self.corpus_changed_at
+            && changed_at.elapsed() >= Duration::from_millis(CORPUS_SETTLE_MS)
+        {
+            self.corpus_changed_at = None;
+            let idle = self
+                .last_input
+                .is_none_or(|last| last.elapsed() >= Duration::from_millis(CORPUS_REFRESH_IDLE_MS));
+            self.handle_message(Message::CorpusChanged { idle });
+        }
+    }
+
+    fn follow_session(&mut self, file_path: &str) {
+        // Start at the current end, then reload so nothing written in between is missed
+        self.session_tail = Some(Tail::from_end(&[PathBuf::from(file_path)]));
+        self.load_session_messages(file_path);
+        self.state.session.selected_index = match self.state.session.order {
+            SessionOrder::Ascending => self.state.session.search_results.len().saturating_sub(1),
+            SessionOrder::Descending => 0,
+        };
+        self.message_timer = Some(std::time::Instant::now());
+    }
+
+    fn poll_followed_session(&mut self) {
+        let (Some(session_id), Some(file_path)) = (
+            self.state.session.session_id.clone(),
+            self.state.session.file_path.clone(),
+        ) else {
+            return;
+        };
+        // Navigating back may have switched to another session since following began
+        if !self
+            .session_tail
+            .as_ref()
+            .is_some_and(|tail| tail.is_following(Path::new(&file_path)))
+        {
+            self.follow_session(&file_path);
+            return;
+        }
+        let Some(tail) = self.session_tail.as_mut() else {
+            return;
+        };
+
+        let request = SearchRequest {
+            id: self.state.search.current_search_id,
+            query: self.state.session.query.clone(),
+            pattern: file_path,
+            project: None,
+            role_filter: self.state.session.role_filter.clone(),
+            order: SearchOrder::Ascending,
+            limit: None,
+            offset: None,
+            hide_tool_results: self.state.ui.hide_tool_results,
+        };
+        match self.search_service.poll_session(tail, &request, session_id) {
+            Ok(results) => {
+                if !results.is_empty() {
+                    self.handle_message(Message::SessionMessagesAppended(results));
+                }
+            }
+            Err(e) => {
+                self.state.session.following = false;
+                self.session_tail = None;
+                self.state.ui.message = Some(format!("⚠ Stopped following session: {e}"));
+                self.message_timer = Some(std::time::Instant::now());
+            }
+        }
+    }
+
     async fn execute_session_search(&mut self) {
         // Execute search with session_id filter
         if let Some(session_id) = &self.state.session.session_id
@@ -675,6 +1429,7 @@ impl InteractiveSearch {
                 id: self.state.search.current_search_id,
                 query: self.state.session.query.clone(),
                 pattern: file_path.clone(),
+                project: None,
                 role_filter: self.state.session.role_filter.clone(),
                 order: match self.state.session.order {
                     SessionOrder::Ascending => SearchOrder::Ascending,
@@ -682,6 +1437,7 @@ impl InteractiveSearch {
                 },
                 limit: None, // No limit for session viewer
                 offset: None,
+                hide_tool_results: self.state.ui.hide_tool_results,
             };
 
             match self
@@ -689,7 +1445,7 @@ impl InteractiveSearch {
                 .search_session(request, session_id.clone())
             {
                 Ok(response) => {
-                    self.state.session.search_results = response.results;
+                    self.state.session.set_results(response.results);
                     // Clear old messages - will be removed later after full migration
                     self.state.session.messages = vec![];
                     self.state.session.filtered_indices = vec![];
@@ -706,7 +1462,18 @@ impl InteractiveSearch {
         // Get list of all session files
         let search_service = self.search_service.clone();
 
-        let sessions = blocking::unblock(move || search_service.get_all_sessions()).await;
+        let sessions = match self.session_list_prefetch.take() {
+            Some(prefetch) if prefetch.is_finished() => prefetch.join().await,
+            // Still waiting behind other work; someone is waiting now
+            _ => {
+                Scheduler::global()
+                    .spawn(TaskKind::SessionList, move || {
+                        search_service.get_all_sessions()
+                    })
+                    .join()
+                    .await
+            }
+        };
 
         match sessions {
             Ok(session_list) => {
@@ -720,6 +1487,43 @@ impl InteractiveSearch {
         }
     }
 
+    async fn load_bookmarks(&mut self) {
+        let Some(bookmark_service) = self.bookmark_service.clone() else {
+            self.handle_message(Message::BookmarksLoaded(Vec::new()));
+            return;
+        };
+
+        let bookmarks = Scheduler::global()
+            .spawn(TaskKind::SessionLoad, move || bookmark_service.load())
+            .join()
+            .await;
+        match bookmarks {
+            Ok(results) => self.handle_message(Message::BookmarksLoaded(results)),
+            Err(e) => {
+                self.state.ui.message = Some(format!("Failed to load bookmarks: {e}"));
+                self.state.bookmarks.is_loading = false;
+            }
+        }
+    }
+
+    async fn load_projects(&mut self) {
+        let search_service = self.search_service.clone();
+
+        let projects = Scheduler::global()
+            .spawn(TaskKind::SessionList, move || {
+                search_service.get_all_projects()
+            })
+            .join()
+            .await;
+        match projects {
+            Ok(projects) => self.handle_message(Message::ProjectsLoaded(projects)),
+            Err(e) => {
+                self.state.ui.message = Some(format!("Failed to load projects: {e}"));
+                self.state.projects.is_loading = false;
+            }
+        }
+    }
+
     async fn load_more_results(&mut self, offset: usize) {
         // Create request with offset for pagination
         if let Some(sender) = &self.search_sender {
@@ -728,9 +1532,16 @@ impl InteractiveSearch {
                 query: self.state.search.query.clone(),
                 role_filter: self.state.search.role_filter.clone(),
                 pattern: self.pattern.clone(),
+                project: self
+                    .state
+                    .search
+                    .project
+                    .as_ref()
+                    .map(|project| project.dir.clone()),
                 order: self.state.search.order,
                 limit: Some(100), // Load next 100 results
                 offset: Some(offset),
+                hide_tool_results: self.state.ui.hide_tool_results,
             };
             let _ = sender.send(request).await;
         }
@@ -740,57 +1551,28 @@ impl InteractiveSearch {
         self.state.session_list.current_search_id += 1;
         let current_search_id = self.state.session_list.current_search_id;
 
-        // Get the query and all sessions
-        let query = self.state.session_list.query.clone();
-        let all_sessions = self.state.session_list.sessions.clone();
-
         // If query is empty, show all sessions
+        let query = self.state.session_list.query.clone();
         if query.is_empty() {
-            let msg = Message::SessionListSearchCompleted(all_sessions);
+            let msg = Message::SessionListSearchCompleted(self.state.session_list.sessions.clone());
             self.handle_message(msg);
             return;
         }
 
-        // Get the search service
-        let search_service = self.search_service.clone();
-
-        // Perform async filtering using full-text search with parallel processing
-        let filtered_sessions = blocking::unblock(move || {
-            use rayon::prelude::*;
-
-            all_sessions
-                .into_par_iter() // Use parallel iterator
-                .filter(|session| {
-                    // Create search request for this specific session
-                    // Use the session's file path as the pattern to search only in that file
-                    let request = SearchRequest {
-                        id: 0,
-                        query: query.clone(),
-                        pattern: session.file_path.clone(),
-                        role_filter: None,
-                        order: crate::interactive_ratatui::domain::models::SearchOrder::Descending,
-                        limit: None, // No limit for session list search
-                        offset: None,
-                    };
-
-                    // Search within this specific session
-                    if let Ok(response) =
-                        search_service.search_session(request, session.session_id.clone())
-                    {
-                        // If any messages match, include this session
-                        !response.results.is_empty()
-                    } else {
-                        false
-                    }
-                })
-                .collect::<Vec<_>>()
-        })
-        .await;
-
-        // Only update if this is still the current search
-        if self.state.session_list.current_search_id == current_search_id {
-            let msg = Message::SessionListSearchCompleted(filtered_sessions);
-            self.handle_message(msg);
+        if let Some(sender) = &self.session_list_sender {
+            let request = SessionListRequest {
+                id: current_search_id,
+                query,
+                role_filter: self.state.search.role_filter.clone(),
+                files: self
+                    .state
+                    .session_list
+                    .sessions
+                    .iter()
+                    .map(|session| session.file_path.clone())
+                    .collect(),
+            };
+            let _ = sender.send(request).await;
         }
     }
 
@@ -800,16 +1582,32 @@ impl InteractiveSearch {
 
         // Spawn key event task
         let key_tx = tx.clone();
+        let input_paused = self.input_paused.clone();
         let key_task = smol::spawn(async move {
             loop {
+                // Leave stdin alone while an external pager is reading it
+                if input_paused.load(Ordering::Acquire) {
+                    smol::Timer::after(Duration::from_millis(EVENT_POLL_INTERVAL_MS)).await;
+                    continue;
+                }
+
                 // Check for key events every 50ms
-                if poll(Duration::from_millis(EVENT_POLL_INTERVAL_MS)).unwrap_or(false)
-                    && let Ok(crossterm::event::Event::Key(key)) = event::read()
-                    // Windows reports Press, Repeat, and Release. We only act on Press
-                    // (Unix backends already collapse to Press-only by default).
-                    && key.kind == KeyEventKind::Press
-                {
-                    let _ = key_tx.send(Event::Key(key)).await;
+                if poll(Duration::from_millis(EVENT_POLL_INTERVAL_MS)).unwrap_or(false) {
+                    let event = match event::read() {
+                        // Windows reports Press, Repeat, and Release. We only act on Press
+                        // (Unix backends already collapse to Press-only by default).
+                        Ok(crossterm::event::Event::Key(key))
+                            if key.kind == KeyEventKind::Press =>
+                        {
+                            Some(Event::Key(key))
+                        }
+                        // Only reported while the mouse is captured
+                        Ok(crossterm::event::Event::Mouse(mouse)) => Some(Event::Mouse(mouse)),
+                        _ => None,
+                    };
+                    if let Some(event) = event {
+                        let _ = key_tx.send(event).await;
+                    }
                 }
                 smol::Timer::after(Duration::from_millis(10)).await;
             }
@@ -851,14 +1649,26 @@ impl InteractiveSearch {
 
         let task = smol::spawn(async move {
             while let Ok(request) = request_rx.recv().await {
-                // Use blocking::unblock to run the synchronous search in a separate thread
+                // Run the synchronous search on a worker thread
                 // This prevents deadlock when SmolEngine uses block_on internally
-                let result = blocking::unblock({
-                    let search_service = search_service.clone();
-                    let request = request.clone();
-                    move || search_service.search(request)
-                })
-                .await;
+                let result = Scheduler::global()
+                    .spawn(TaskKind::Search, {
+                        let search_service = search_service.clone();
+                        let request = request.clone();
+                        let response_tx = response_tx.clone();
+                        move || {
+                            // Later pages are appended to a list that is already on
+                            // screen, so only the first page is streamed
+                            if request.offset.is_some() {
+                                return search_service.search(request);
+                            }
+                            search_service.search_streaming(request, |partial| {
+                                let _ = response_tx.try_send(partial);
+                            })
+                        }
+                    })
+                    .join()
+                    .await;
 
                 match result {
                     Ok(response) => {
@@ -870,6 +1680,7 @@ impl InteractiveSearch {
                                 id: request.id,
                                 results: Vec::new(),
                                 error: Some(format!("Search error: {e}")),
+                                progress: None,
                             })
                             .await;
                     }
@@ -880,6 +1691,130 @@ impl InteractiveSearch {
         (request_tx, response_rx, task)
     }
 
+    fn start_session_list_worker(
+        &self,
+    ) -> (
+        Sender<SessionListRequest>,
+        Receiver<SessionListResponse>,
+        smol::Task<()>,
+    ) {
+        let (request_tx, request_rx) = smol::channel::unbounded::<SessionListRequest>();
+        let (response_tx, response_rx) = smol::channel::unbounded::<SessionListResponse>();
+        let search_service = self.search_service.clone();
+
+        let task = smol::spawn(async move {
+            while let Ok(mut request) = request_rx.recv().await {
+                // Requests queued behind a running search are already stale;
+                // only the newest one is worth answering
+                while let Ok(newer) = request_rx.try_recv() {
+                    request = newer;
+                }
+
+                let id = request.id;
+                let result = Scheduler::global()
+                    .spawn(TaskKind::SessionList, {
+                        let search_service = search_service.clone();
+                        move || search_service.search_sessions(request)
+                    })
+                    .join()
+                    .await;
+
+                let response = result.unwrap_or_else(|e| SessionListResponse {
+                    id,
+                    matching_files: Default::default(),
+                    error: Some(format!("Search error: {e}")),
+                });
+                let _ = response_tx.send(response).await;
+            }
+        });
+
+        (request_tx, response_rx, task)
+    }
+
+    /// Suspend the TUI, show `text` in `$PAGER` (falling back to a platform default),
+    /// and restore the TUI once the pager exits.
+    fn open_in_pager(&self, text: &str) -> Result<()> {
+        use std::process::{Command, Stdio};
+
+        let pager = std::env::var("PAGER")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+        let mut parts = pager.split_whitespace();
+        let program = parts.next().unwrap_or(DEFAULT_PAGER);
+
+        self.with_terminal_released(|| {
+            let mut child = Command::new(program)
+                .args(parts)
+                .stdin(Stdio::piped())
+                .spawn()
+                .with_context(|| format!("Failed to spawn {program}"))?;
+
+            if let Some(mut stdin) = child.stdin.take() {
+                use std::io::Write;
+                // The pager may exit before reading everything; that's not an error
+                let _ = stdin.write_all(text.as_bytes());
+            }
+
+            child
+                .wait()
+                .with_context(|| format!("Failed to wait for {program}"))?;
+            Ok(())
+        })
+    }
+
+    /// Suspend the TUI, open `file` in `$EDITOR` at `line` (`$EDITOR +<line> <file>`),
+    /// and restore the TUI once the editor exits.
+    fn open_in_editor(&self, file: &str, line: Option<usize>) -> Result<()> {
+        use std::process::Command;
+
+        if crate::search::compression::is_compressed(std::path::Path::new(file)) {
+            anyhow::bail!("{file} is compressed");
+        }
+        let editor = std::env::var("EDITOR")
+            .ok()
+            .filter(|e| !e.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+        let mut parts = editor.split_whitespace();
+        let program = parts.next().unwrap_or(DEFAULT_EDITOR);
+
+        let mut command = Command::new(program);
+        command.args(parts);
+        if let Some(line) = line {
+            command.arg(format!("+{line}"));
+        }
+        command.arg(file);
+
+        self.with_terminal_released(|| {
+            let status = command
+                .status()
+                .with_context(|| format!("Failed to spawn {program}"))?;
+            anyhow::ensure!(status.success(), "{program} exited with {status}");
+            Ok(())
+        })
+    }
+
+    /// Hand the terminal to an external program for the duration of `run`,
+    /// then take it back
+    fn with_terminal_released(&self, run: impl FnOnce() -> Result<()>) -> Result<()> {
+        // Stop the key reader first and give an in-flight poll time to finish
+        self.input_paused.store(true, Ordering::Release);
+        std::thread::sleep(Duration::from_millis(EVENT_POLL_INTERVAL_MS));
+
+        disable_raw_mode()?;
+        self.capture_mouse(&mut io::stdout(), false)?;
+        execute!(io::stdout(), LeaveAlternateScreen)?;
+
+        let result = run();
+
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        self.capture_mouse(&mut io::stdout(), true)?;
+        self.input_paused.store(false, Ordering::Release);
+
+        result
+    }
+
     fn copy_to_clipboard(&self, text: &str) -> Result<()> {
         #[cfg(target_os = "macos")]
         {
@@ -960,8 +1895,73 @@ impl InteractiveSearch {
         }
     }
 
+    fn read_clipboard(&self) -> Result<String> {
+        #[cfg(target_os = "macos")]
+        {
+            let output = std::process::Command::new("pbpaste")
+                .output()
+                .context("Failed to run pbpaste")?;
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let output = std::process::Command::new("xclip")
+                .args(["-selection", "clipboard", "-o"])
+                .output()
+                .context("Failed to run xclip")?;
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            // Match copy_to_clipboard: UTF-8 so non-ASCII text survives
+            let output = std::process::Command::new("powershell")
+                .args([
+                    "-NoProfile",
+                    "-NonInteractive",
+                    "-Command",
+                    "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; \
+                     Get-Clipboard -Raw",
+                ])
+                .output()
+                .context("Failed to run powershell Get-Clipboard")?;
+            Ok(String::from_utf8_lossy(&output.stdout).replace("\r\n", "\n"))
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+        {
+            Err(anyhow::anyhow!("Clipboard not supported on this platform"))
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_bookmarks_path(&mut self, path: PathBuf) {
+        self.bookmark_service = Some(Arc::new(BookmarkService::new(path)));
+    }
+
     #[cfg(test)]
     pub(crate) fn set_mode(&mut self, mode: Mode) {
         self.state.mode = mode;
     }
 }
+
+/// Whether `event` adds, writes to, renames or removes a session file.
+///
+/// Metadata changes are left out, since reading the files for a search can
+/// update their access time.
+fn changes_session_file(event: &notify::Event) -> bool {
+    use notify::EventKind;
+    use notify::event::ModifyKind;
+
+    matches!(
+        event.kind,
+        EventKind::Create(_)
+            | EventKind::Remove(_)
+            | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any)
+    ) && event.paths.iter().any(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == "jsonl")
+            || is_compressed(path)
+    })
+}
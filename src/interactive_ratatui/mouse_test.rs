@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod tests {
+    use super::super::InteractiveSearch;
+    use super::super::domain::models::{PreviewPosition, SearchTab};
+    use crate::SearchOptions;
+    use crate::query::condition::{QueryCondition, SearchResult};
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use ratatui::{Terminal, backend::TestBackend};
+    use tempfile::tempdir;
+
+    fn result(uuid: &str, text: &str) -> SearchResult {
+        SearchResult {
+            file: "session.jsonl".into(),
+            uuid: uuid.to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s1".into(),
+            role: "user".into(),
+            text: text.to_string(),
+            message_type: "user".into(),
+            query: QueryCondition::Literal {
+                pattern: String::new(),
+                case_sensitive: false,
+            },
+            cwd: "/project".into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        }
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    fn app_with_results() -> (InteractiveSearch, Terminal<TestBackend>) {
+        let mut app = InteractiveSearch::new(SearchOptions::default());
+        app.state.search.results = vec![result("u1", "first"), result("u2", "second")];
+        app.state.search.preview_enabled = true;
+        app.state.ui.preview_position = PreviewPosition::Right;
+        (app, Terminal::new(TestBackend::new(100, 30)).unwrap())
+    }
+
+    fn draw(app: &mut InteractiveSearch, terminal: &mut Terminal<TestBackend>) -> Vec<String> {
+        terminal
+            .draw(|f| app.renderer.render(f, &app.state))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer.cell((x, y)).unwrap().symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Screen column where `text` starts on a row
+    fn column_of(row: &str, text: &str) -> u16 {
+        row[..row.find(text).unwrap()].chars().count() as u16
+    }
+
+    #[test]
+    fn test_click_result_and_tab() {
+        let dir = tempdir().unwrap();
+        let (mut app, mut terminal) = app_with_results();
+        app.set_bookmarks_path(dir.path().join("bookmarks.json"));
+        let screen = draw(&mut app, &mut terminal);
+
+        let row = screen
+            .iter()
+            .position(|row| row.contains("second"))
+            .unwrap();
+        let column = column_of(&screen[row], "second");
+        app.handle_mouse(mouse(
+            MouseEventKind::Down(MouseButton::Left),
+            column,
+            row as u16,
+        ));
+        assert_eq!(app.state.search.selected_index, 1);
+
+        // The wheel over the list moves the selection
+        draw(&mut app, &mut terminal);
+        app.handle_mouse(mouse(MouseEventKind::ScrollUp, column, row as u16));
+        assert_eq!(app.state.search.selected_index, 0);
+
+        // Overlays ignore the mouse
+        let column = column_of(&screen[1], "Bookmarks");
+        app.state.ui.show_help = true;
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), column, 1));
+        assert_eq!(app.state.search.current_tab, SearchTab::Search);
+
+        app.state.ui.show_help = false;
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), column, 1));
+        assert_eq!(app.state.search.current_tab, SearchTab::Bookmarks);
+    }
+
+    #[test]
+    fn test_drag_preview_border() {
+        let dir = tempdir().unwrap();
+        let config = dir.path().join("config.toml");
+        let (mut app, mut terminal) = app_with_results();
+        app.set_config_path(Some(config.clone()));
+        draw(&mut app, &mut terminal);
+
+        // The preview takes 60% of the 100 columns, so its border is at 40
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 40, 15));
+        app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 30, 15));
+        assert_eq!(app.state.ui.preview_size, Some(70));
+        draw(&mut app, &mut terminal);
+        app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 5, 15));
+        assert_eq!(app.state.ui.preview_size, Some(80));
+        assert!(!config.exists());
+
+        // Saved once the button is released
+        app.handle_mouse(mouse(MouseEventKind::Up(MouseButton::Left), 5, 15));
+        assert_eq!(
+            std::fs::read_to_string(&config).unwrap(),
+            "preview_size = 80\n"
+        );
+
+        // Away from the border a drag does nothing
+        draw(&mut app, &mut terminal);
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 60, 15));
+        app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 50, 15));
+        assert_eq!(app.state.ui.preview_size, Some(80));
+    }
+}
@@ -0,0 +1,174 @@
+//! Recording of an interactive session (`--record`).
+//!
+//! A `.cast` file gets an asciinema v2 recording of everything written to the
+//! terminal, so `asciinema play` shows the session with its colors and timing.
+//! Any other file gets plain text dumps of each screen that differs from the
+//! one before, which read well in a bug report.
+
+use anyhow::{Context, Result};
+use ratatui::buffer::Buffer;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// asciinema v2: a JSON header, then `[seconds, "o", output]` per write
+    Cast,
+    /// Screens as text, each under a `=== <seconds>s` line
+    Text,
+}
+
+impl RecordFormat {
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("cast") => RecordFormat::Cast,
+            _ => RecordFormat::Text,
+        }
+    }
+}
+
+pub struct Recorder {
+    out: BufWriter<File>,
+    format: RecordFormat,
+    started: Instant,
+    last_screen: Vec<String>,
+    last_output: Vec<u8>,
+}
+
+impl Recorder {
+    /// Record to `path`, in the format its extension asks for, for a terminal
+    /// of `width`×`height`
+    pub fn create(path: &Path, width: u16, height: u16) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("failed to create recording: {}", path.display()))?;
+        let mut recorder = Self {
+            out: BufWriter::new(file),
+            format: RecordFormat::for_path(path),
+            started: Instant::now(),
+            last_screen: Vec::new(),
+            last_output: Vec::new(),
+        };
+        if recorder.format == RecordFormat::Cast {
+            recorder.write_header(width, height)?;
+        }
+        Ok(recorder)
+    }
+
+    fn write_header(&mut self, width: u16, height: u16) -> io::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        let header = serde_json::json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": timestamp,
+            "env": {"TERM": std::env::var("TERM").unwrap_or_default()},
+        });
+        writeln!(self.out, "{header}")
+    }
+
+    /// Bytes sent to the terminal
+    pub fn output(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.format != RecordFormat::Cast || bytes.is_empty() {
+            return Ok(());
+        }
+        // Frames with no change still reset the colors and hide the cursor;
+        // leaving out the repeats keeps the recording to one event per change
+        if bytes == self.last_output && is_escape_only(bytes) {
+            return Ok(());
+        }
+        self.last_output = bytes.to_vec();
+        let event = serde_json::json!([self.elapsed(), "o", String::from_utf8_lossy(bytes)]);
+        writeln!(self.out, "{event}")
+    }
+
+    /// A screen the renderer finished drawing
+    pub fn frame(&mut self, buffer: &Buffer) -> io::Result<()> {
+        if self.format != RecordFormat::Text {
+            return Ok(());
+        }
+        let screen: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                let row: String = (0..buffer.area.width)
+                    .filter_map(|x| buffer.cell((x, y)).map(|cell| cell.symbol()))
+                    .collect();
+                row.trim_end().to_string()
+            })
+            .collect();
+        if screen == self.last_screen {
+            return Ok(());
+        }
+
+        writeln!(self.out, "=== {:.3}s", self.elapsed())?;
+        for row in &screen {
+            writeln!(self.out, "{row}")?;
+        }
+        self.last_screen = screen;
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    fn elapsed(&self) -> f64 {
+        // Milliseconds are plenty, and keep the file small
+        (self.started.elapsed().as_secs_f64() * 1000.0).round() / 1000.0
+    }
+}
+
+/// Whether `bytes` are nothing but CSI sequences such as color changes
+fn is_escape_only(bytes: &[u8]) -> bool {
+    let mut rest = bytes;
+    while let [0x1b, b'[', tail @ ..] = rest {
+        // Parameters, then a final byte in 0x40..=0x7e
+        let Some(end) = tail.iter().position(|b| (0x40..=0x7e).contains(b)) else {
+            return false;
+        };
+        rest = &tail[end + 1..];
+    }
+    rest.is_empty()
+}
+
+/// The terminal's writer: passes everything through and hands what was
+/// written to the recorder each time the terminal flushes
+pub struct Tee<W: Write> {
+    inner: W,
+    recorder: Option<Arc<Mutex<Recorder>>>,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> Tee<W> {
+    pub fn new(inner: W, recorder: Option<Arc<Mutex<Recorder>>>) -> Self {
+        Self {
+            inner,
+            recorder,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for Tee<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if self.recorder.is_some() {
+            self.pending.extend_from_slice(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        if let Some(recorder) = &self.recorder {
+            let mut recorder = recorder.lock().unwrap_or_else(|e| e.into_inner());
+            recorder.output(&self.pending)?;
+            self.pending.clear();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use super::super::record::{RecordFormat, Recorder, Tee};
+    use ratatui::{Terminal, backend::TestBackend, widgets::Paragraph};
+    use std::io::Write;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_record_format_for_path() {
+        assert_eq!(
+            RecordFormat::for_path(Path::new("out.cast")),
+            RecordFormat::Cast
+        );
+        assert_eq!(
+            RecordFormat::for_path(Path::new("frames.txt")),
+            RecordFormat::Text
+        );
+        assert_eq!(RecordFormat::for_path(Path::new("out")), RecordFormat::Text);
+    }
+
+    #[test]
+    fn test_cast_recording() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("out.cast");
+        let recorder = Arc::new(Mutex::new(Recorder::create(&path, 80, 24).unwrap()));
+
+        let mut terminal_output = Vec::new();
+        let mut tee = Tee::new(&mut terminal_output, Some(recorder.clone()));
+        write!(tee, "\x1b[1;1Hhello").unwrap();
+        write!(tee, " world").unwrap();
+        tee.flush().unwrap();
+        // Nothing written since the last flush: no event
+        tee.flush().unwrap();
+        // An unchanged frame only repeats its color reset: one event
+        for _ in 0..3 {
+            write!(tee, "\x1b[0m\x1b[?25l").unwrap();
+            tee.flush().unwrap();
+        }
+        drop(tee);
+        recorder.lock().unwrap().finish().unwrap();
+        assert!(terminal_output.starts_with(b"\x1b[1;1Hhello world\x1b[0m"));
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[0]["height"], 24);
+        assert!(lines[1][0].as_f64().unwrap() >= 0.0);
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "\x1b[1;1Hhello world");
+        assert_eq!(lines[2][2], "\x1b[0m\x1b[?25l");
+    }
+
+    #[test]
+    fn test_text_recording() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("frames.txt");
+        let mut recorder = Recorder::create(&path, 80, 24).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(10, 2)).unwrap();
+        for text in ["first", "first", "second"] {
+            let frame = terminal
+                .draw(|f| f.render_widget(Paragraph::new(text), f.area()))
+                .unwrap();
+            recorder.frame(frame.buffer).unwrap();
+        }
+        // Terminal output only goes into casts
+        recorder.output(b"ignored").unwrap();
+        recorder.finish().unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        // The repeated screen is skipped
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with("=== ") && lines[0].ends_with('s'));
+        assert_eq!(&lines[1..3], ["first", ""]);
+        assert!(lines[3].starts_with("=== "));
+        assert_eq!(&lines[4..], ["second", ""]);
+    }
+}
@@ -0,0 +1,232 @@
+//! Headless replay of the interactive mode (`ccms script`).
+//!
+//! A script is a JSON array of actions. Key presses go through the same input
+//! handling, messages and commands as a real terminal, and the screen is drawn
+//! to an off-screen buffer after every action, so a script reproduces what a
+//! user would have seen:
+//!
+//! ```json
+//! [{"type": "error"}, "wait", {"key": "down"}, {"key": "enter"}, "wait", "dump"]
+//! ```
+
+use super::InteractiveSearch;
+use super::constants::SCRIPT_WAIT_TIMEOUT_MS;
+use super::domain::models::{Mode, SearchTab};
+use super::ui::commands::Command;
+use super::ui::keymap::parse_key;
+use anyhow::{Context, Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{Terminal, backend::TestBackend};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Press one key, written like a `[keybindings]` entry: `ctrl+r`, `enter`, `x`
+    Key(String),
+    /// Type each character of the text
+    Type(String),
+    /// Let searches and loads in flight finish
+    Wait,
+    /// Record the state and screen
+    Dump,
+}
+
+/// Parse a script, rejecting keys that cannot be replayed
+pub fn load_actions(json: &str) -> Result<Vec<Action>> {
+    let actions: Vec<Action> = serde_json::from_str(json).context("invalid script")?;
+    for action in &actions {
+        if let Action::Key(spec) = action {
+            let (code, modifiers) =
+                parse_key(spec).with_context(|| format!("invalid key '{spec}'"))?;
+            if code == KeyCode::Char('z') && modifiers.contains(KeyModifiers::CONTROL) {
+                bail!("'{spec}' suspends the terminal and cannot be replayed");
+            }
+        }
+    }
+    Ok(actions)
+}
+
+/// What the interactive mode looked like at one point of a script
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snapshot {
+    /// Number of actions replayed before it was taken
+    pub after_actions: usize,
+    pub mode: String,
+    pub tab: String,
+    pub query: String,
+    pub results: usize,
+    /// Selected row of the list in view
+    pub selected_index: usize,
+    pub message: Option<String>,
+    /// Screen rows, trailing blanks trimmed
+    pub screen: Vec<String>,
+}
+
+impl Snapshot {
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "=== after {} actions: mode={} tab={} query={:?} results={} selected={}",
+            self.after_actions, self.mode, self.tab, self.query, self.results, self.selected_index
+        );
+        if let Some(message) = &self.message {
+            text.push_str(&format!(" message={message:?}"));
+        }
+        text.push('\n');
+        for row in &self.screen {
+            text.push_str(row);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl InteractiveSearch {
+    /// Replay `actions` on a `width`×`height` screen, searching `pattern`.
+    ///
+    /// Returns a snapshot for each `dump`, or one of the final state when the
+    /// script has none. Pressing Ctrl+C twice ends the script early. The search
+    /// history and config.toml are neither read nor written, so scripts replay
+    /// the same way everywhere.
+    pub fn run_script(
+        &mut self,
+        pattern: &str,
+        actions: &[Action],
+        width: u16,
+        height: u16,
+    ) -> Result<Vec<Snapshot>> {
+        smol::block_on(async {
+            self.pattern = pattern.to_string();
+            self.history_service = None;
+            self.config_path = None;
+            let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+
+            self.start_workers();
+            self.execute_command(Command::ExecuteSearch).await;
+            let snapshots = self.replay(&mut terminal, actions).await;
+            self.stop_workers().await;
+            snapshots
+        })
+    }
+
+    async fn replay(
+        &mut self,
+        terminal: &mut Terminal<TestBackend>,
+        actions: &[Action],
+    ) -> Result<Vec<Snapshot>> {
+        let mut snapshots = Vec::new();
+        draw(self, terminal)?;
+
+        let mut replayed = 0;
+        for action in actions {
+            let quit = match action {
+                Action::Key(spec) => {
+                    let (code, modifiers) = parse_key(spec)?;
+                    self.handle_input(KeyEvent::new(code, modifiers))?
+                }
+                Action::Type(text) => {
+                    let mut quit = false;
+                    for c in text.chars() {
+                        quit =
+                            self.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))?;
+                        // Components pick up state changes when they are drawn
+                        draw(self, terminal)?;
+                        if quit {
+                            break;
+                        }
+                    }
+                    quit
+                }
+                Action::Wait => {
+                    self.wait_until_idle(terminal).await?;
+                    false
+                }
+                Action::Dump => {
+                    snapshots.push(self.snapshot(replayed, terminal));
+                    false
+                }
+            };
+            replayed += 1;
+            if quit {
+                break;
+            }
+            self.poll_background().await;
+            draw(self, terminal)?;
+        }
+
+        if snapshots.is_empty() {
+            snapshots.push(self.snapshot(replayed, terminal));
+        }
+        Ok(snapshots)
+    }
+
+    async fn wait_until_idle(&mut self, terminal: &mut Terminal<TestBackend>) -> Result<()> {
+        let started = Instant::now();
+        loop {
+            self.poll_background().await;
+            draw(self, terminal)?;
+            if self.is_idle() {
+                return Ok(());
+            }
+            if started.elapsed() >= Duration::from_millis(SCRIPT_WAIT_TIMEOUT_MS) {
+                bail!("still busy after {SCRIPT_WAIT_TIMEOUT_MS}ms");
+            }
+            smol::Timer::after(Duration::from_millis(10)).await;
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.scheduled_search_delay.is_none()
+            && !self.state.search.is_searching
+            && !self.state.search.loading_more
+            && !self.state.session_list.is_searching
+    }
+
+    fn snapshot(&self, after_actions: usize, terminal: &Terminal<TestBackend>) -> Snapshot {
+        let state = &self.state;
+        let (query, results, selected_index) = match (state.mode, state.search.current_tab) {
+            (Mode::SessionViewer, _) => (
+                state.session.query.clone(),
+                state.session.search_results.len(),
+                state.session.selected_index,
+            ),
+            (_, SearchTab::SessionList) => (
+                state.session_list.query.clone(),
+                state.session_list.filtered_sessions.len(),
+                state.session_list.selected_index,
+            ),
+            _ => (
+                state.search.query.clone(),
+                state.search.results.len(),
+                state.search.selected_index,
+            ),
+        };
+
+        let buffer = terminal.backend().buffer();
+        let screen = (0..buffer.area.height)
+            .map(|y| {
+                let row: String = (0..buffer.area.width)
+                    .filter_map(|x| buffer.cell((x, y)).map(|cell| cell.symbol()))
+                    .collect();
+                row.trim_end().to_string()
+            })
+            .collect();
+
+        Snapshot {
+            after_actions,
+            mode: format!("{:?}", state.mode),
+            tab: format!("{:?}", state.search.current_tab),
+            query,
+            results,
+            selected_index,
+            message: state.ui.message.clone(),
+            screen,
+        }
+    }
+}
+
+fn draw(app: &mut InteractiveSearch, terminal: &mut Terminal<TestBackend>) -> Result<()> {
+    terminal.draw(|f| app.renderer.render(f, &app.state))?;
+    Ok(())
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use super::super::InteractiveSearch;
+    use super::super::script::{Action, load_actions};
+    use crate::SearchOptions;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_load_actions() {
+        let actions =
+            load_actions(r#"[{"type": "hello"}, {"key": "ctrl+r"}, "wait", "dump"]"#).unwrap();
+        assert_eq!(
+            actions,
+            vec![
+                Action::Type("hello".to_string()),
+                Action::Key("ctrl+r".to_string()),
+                Action::Wait,
+                Action::Dump,
+            ]
+        );
+
+        assert!(load_actions(r#"[{"key": "hyper+x"}]"#).is_err());
+        assert!(load_actions(r#"[{"key": "ctrl+z"}]"#).is_err());
+        assert!(load_actions(r#"["jump"]"#).is_err());
+    }
+
+    #[test]
+    fn test_run_script() {
+        let dir = tempdir().unwrap();
+        let mut file = File::create(dir.path().join("session.jsonl")).unwrap();
+        for (uuid, text) in [("u1", "first question"), ("u2", "find the needle")] {
+            writeln!(
+                file,
+                r#"{{"type":"user","message":{{"role":"user","content":"{text}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+            )
+            .unwrap();
+        }
+        let pattern = dir.path().join("session.jsonl").display().to_string();
+
+        let actions =
+            load_actions(r#"["wait", "dump", {"type": "needle"}, "wait", "dump"]"#).unwrap();
+        let mut app = InteractiveSearch::new(SearchOptions::default());
+        let snapshots = app.run_script(&pattern, &actions, 100, 20).unwrap();
+
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].after_actions, 1);
+        assert_eq!(snapshots[0].results, 2);
+        assert_eq!(snapshots[1].mode, "Search");
+        assert_eq!(snapshots[1].query, "needle");
+        assert_eq!(snapshots[1].results, 1);
+        assert_eq!(snapshots[1].screen.len(), 20);
+        assert!(
+            snapshots[1]
+                .screen
+                .iter()
+                .any(|row| row.contains("find the needle"))
+        );
+        assert!(snapshots[1].to_text().starts_with(
+            "=== after 4 actions: mode=Search tab=Search query=\"needle\" results=1 selected=0\n"
+        ));
+
+        // Without a dump the final state is returned
+        let mut app = InteractiveSearch::new(SearchOptions::default());
+        let snapshots = app
+            .run_script(&pattern, &[Action::Key("down".to_string())], 100, 20)
+            .unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].after_actions, 1);
+    }
+}
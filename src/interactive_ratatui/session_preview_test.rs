@@ -38,38 +38,44 @@ mod tests {
         // Add some test results
         let results = vec![
             SearchResult {
-                file: "test.jsonl".to_string(),
+                file: "test.jsonl".into(),
                 uuid: "uuid1".to_string(),
                 timestamp: "2024-01-01T00:00:00Z".to_string(),
-                session_id: "test-session".to_string(),
-                role: "user".to_string(),
+                session_id: "test-session".into(),
+                role: "user".into(),
                 text: "Test message 1".to_string(),
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: "test".to_string(),
                     case_sensitive: false,
                 },
-                cwd: "/test".to_string(),
+                cwd: "/test".into(),
                 raw_json: Some(
                     r#"{"type":"user","message":{"content":"Test message 1"}}"#.to_string(),
                 ),
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
             SearchResult {
-                file: "test.jsonl".to_string(),
+                file: "test.jsonl".into(),
                 uuid: "uuid2".to_string(),
                 timestamp: "2024-01-01T00:01:00Z".to_string(),
-                session_id: "test-session".to_string(),
-                role: "assistant".to_string(),
+                session_id: "test-session".into(),
+                role: "assistant".into(),
                 text: "Test response 1".to_string(),
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: "test".to_string(),
                     case_sensitive: false,
                 },
-                cwd: "/test".to_string(),
+                cwd: "/test".into(),
                 raw_json: Some(
                     r#"{"type":"assistant","message":{"content":"Test response 1"}}"#.to_string(),
                 ),
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
         ];
 
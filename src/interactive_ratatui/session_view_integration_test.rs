@@ -9,34 +9,40 @@ mod tests {
     fn create_test_search_results() -> Vec<SearchResult> {
         vec![
             SearchResult {
-                file: "/path/to/session.jsonl".to_string(),
+                file: "/path/to/session.jsonl".into(),
                 uuid: "user-uuid-1".to_string(),
                 timestamp: "2024-01-01T00:00:00Z".to_string(),
-                session_id: "test-session".to_string(),
-                role: "user".to_string(),
+                session_id: "test-session".into(),
+                role: "user".into(),
                 text: "Hello Claude".to_string(),
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: String::new(),
                     case_sensitive: false,
                 },
-                cwd: "/test".to_string(),
+                cwd: "/test".into(),
                 raw_json: Some(r#"{"type":"user","message":{"role":"user","content":"Hello Claude"}}"#.to_string()),
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
             SearchResult {
-                file: "/path/to/session.jsonl".to_string(),
+                file: "/path/to/session.jsonl".into(),
                 uuid: "assistant-uuid-1".to_string(),
                 timestamp: "2024-01-01T00:00:01Z".to_string(),
-                session_id: "test-session".to_string(),
-                role: "assistant".to_string(),
+                session_id: "test-session".into(),
+                role: "assistant".into(),
                 text: "Hello! How can I help you today?".to_string(),
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: String::new(),
                     case_sensitive: false,
                 },
-                cwd: "/test".to_string(),
+                cwd: "/test".into(),
                 raw_json: Some(r#"{"type":"assistant","message":{"role":"assistant","content":"Hello! How can I help you today?"}}"#.to_string()),
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
         ]
     }
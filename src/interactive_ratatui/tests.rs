@@ -44,19 +44,22 @@ fn test_message_handling() {
 
     // Test mode change
     state.search.results = vec![SearchResult {
-        file: "test.jsonl".to_string(),
+        file: "test.jsonl".into(),
         uuid: "test-uuid".to_string(),
         timestamp: "2024-01-01T00:00:00Z".to_string(),
-        session_id: "test-session".to_string(),
-        role: "user".to_string(),
+        session_id: "test-session".into(),
+        role: "user".into(),
         text: "test text".to_string(),
-        message_type: "user".to_string(),
+        message_type: "user".into(),
         query: crate::query::condition::QueryCondition::Literal {
             pattern: "test".to_string(),
             case_sensitive: false,
         },
-        cwd: "/test".to_string(),
+        cwd: "/test".into(),
         raw_json: None,
+        line_number: None,
+        matched_in: Vec::new(),
+        has_images: false,
     }];
 
     let command = state.update(Message::EnterMessageDetail);
@@ -71,34 +74,40 @@ fn test_search_filter() {
 
     let mut results = vec![
         SearchResult {
-            file: "test1.jsonl".to_string(),
+            file: "test1.jsonl".into(),
             uuid: "uuid1".to_string(),
             timestamp: "2024-01-01T00:00:00Z".to_string(),
-            session_id: "session1".to_string(),
-            role: "user".to_string(),
+            session_id: "session1".into(),
+            role: "user".into(),
             text: "user message".to_string(),
-            message_type: "user".to_string(),
+            message_type: "user".into(),
             query: crate::query::condition::QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: "/test".to_string(),
+            cwd: "/test".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         },
         SearchResult {
-            file: "test2.jsonl".to_string(),
+            file: "test2.jsonl".into(),
             uuid: "uuid2".to_string(),
             timestamp: "2024-01-01T00:01:00Z".to_string(),
-            session_id: "session1".to_string(),
-            role: "assistant".to_string(),
+            session_id: "session1".into(),
+            role: "assistant".into(),
             text: "assistant message".to_string(),
-            message_type: "assistant".to_string(),
+            message_type: "assistant".into(),
             query: crate::query::condition::QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: "/test".to_string(),
+            cwd: "/test".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         },
     ];
 
@@ -108,3 +117,55 @@ fn test_search_filter() {
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].role, "user");
 }
+
+#[test]
+fn test_changes_session_file() {
+    use notify::event::{AccessKind, CreateKind, DataChange, MetadataKind, ModifyKind};
+    use notify::{Event as FsEvent, EventKind};
+
+    let event = |kind, path: &str| FsEvent::new(kind).add_path(PathBuf::from(path));
+    let write = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+
+    assert!(changes_session_file(&event(write, "/p/s.jsonl")));
+    assert!(changes_session_file(&event(
+        EventKind::Create(CreateKind::File),
+        "/p/old.jsonl.zst"
+    )));
+    assert!(!changes_session_file(&event(write, "/p/notes.txt")));
+    // Searching reads the files, which must not look like a change
+    assert!(!changes_session_file(&event(
+        EventKind::Access(AccessKind::Any),
+        "/p/s.jsonl"
+    )));
+    assert!(!changes_session_file(&event(
+        EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime)),
+        "/p/s.jsonl"
+    )));
+}
+
+#[test]
+fn test_preview_keys_save_config() {
+    use crate::interactive_ratatui::domain::models::PreviewPosition;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    let mut interactive = InteractiveSearch::new(SearchOptions::default());
+    interactive.set_preview(Some(PreviewSetting::Right), Some(50));
+    interactive.set_config_path(Some(path.clone()));
+    assert!(interactive.state.search.preview_enabled);
+
+    let alt = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT);
+    interactive.handle_input(alt('p')).unwrap();
+    interactive.handle_input(alt('=')).unwrap();
+    assert_eq!(
+        interactive.state.ui.preview_position,
+        PreviewPosition::Bottom
+    );
+    // Not typed into the query
+    assert_eq!(interactive.state.search.query, "");
+
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.preview, Some(PreviewSetting::Bottom));
+    assert_eq!(config.preview_size, Some(60));
+}
@@ -1,11 +1,21 @@
+use crate::branches::SessionBranches;
 use crate::interactive_ratatui::constants::*;
-use crate::interactive_ratatui::domain::models::{SearchOrder, SearchTab, SessionOrder};
+use crate::interactive_ratatui::domain::models::{
+    Dismissal, PreviewPosition, SearchOrder, SearchProgress, SearchTab, SessionOrder,
+};
 use crate::interactive_ratatui::ui::commands::Command;
-use crate::interactive_ratatui::ui::events::Message;
+use crate::interactive_ratatui::ui::events::{CopyContent, Message};
 use crate::interactive_ratatui::ui::navigation::{
     NavigationHistory, NavigationState, SearchStateSnapshot, SessionStateSnapshot, UiStateSnapshot,
 };
+use crate::interactive_ratatui::ui::theme::ThemeName;
+use crate::outcome::SessionOutcome;
+use crate::query::SavedSearch;
 use crate::query::condition::{QueryCondition, SearchResult};
+use crate::query::interner::SharedStr;
+use crate::usage::ContextPoint;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::SystemTime;
 
 // Re-export Mode
 pub use crate::interactive_ratatui::domain::models::Mode;
@@ -16,9 +26,93 @@ pub struct AppState {
     pub search: SearchState,
     pub session: SessionState,
     pub session_list: SessionListState,
+    pub bookmarks: BookmarkListState,
+    pub projects: ProjectListState,
+    pub history: HistoryState,
     pub ui: UiState,
 }
 
+/// Queries searched before, recalled with Up and Ctrl+R in the search bar
+#[derive(Default)]
+pub struct HistoryState {
+    /// Oldest first
+    pub entries: Vec<String>,
+    /// Entry recalled with Up/Down, counting back from the newest
+    pub cycle: Option<usize>,
+    /// Ctrl+R search in progress
+    pub search: Option<HistorySearch>,
+    /// Queries remembered since startup, saved on exit
+    pub added: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HistorySearch {
+    pub filter: String,
+    /// Newer matches passed over by pressing Ctrl+R again
+    pub skip: usize,
+}
+
+impl HistoryState {
+    /// Entries containing the Ctrl+R filter, newest first
+    pub fn matches(&self) -> Vec<&str> {
+        let filter = self
+            .search
+            .as_ref()
+            .map(|search| search.filter.to_lowercase())
+            .unwrap_or_default();
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.to_lowercase().contains(&filter))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The entry the Ctrl+R search currently points at
+    pub fn current_match(&self) -> Option<&str> {
+        let skip = self.search.as_ref()?.skip;
+        self.matches().get(skip).copied()
+    }
+
+    /// Remember `query` as the newest entry
+    fn remember(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        self.entries.retain(|entry| entry != query);
+        self.entries.push(query.to_string());
+        let excess = self.entries.len().saturating_sub(MAX_SEARCH_HISTORY);
+        self.entries.drain(..excess);
+        self.added.push(query.to_string());
+    }
+}
+
+/// Messages listed in the Bookmarks tab
+pub struct BookmarkListState {
+    pub results: Vec<SearchResult>,
+    pub selected_index: usize,
+    pub is_loading: bool,
+}
+
+/// Project directories listed in the Projects tab
+pub struct ProjectListState {
+    pub projects: Vec<ProjectInfo>,
+    pub is_loading: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectInfo {
+    /// The project's directory under `~/.claude/projects`
+    pub dir: String,
+    /// Working directory the project's sessions ran in, or the directory
+    /// name when no session records one
+    pub name: String,
+    pub session_count: usize,
+    /// Modification time of the newest session file
+    pub last_activity: Option<SystemTime>,
+}
+
 pub struct SessionListState {
     pub sessions: Vec<SessionInfo>,
     pub filtered_sessions: Vec<SessionInfo>,
@@ -30,6 +124,15 @@ pub struct SessionListState {
     pub is_typing: bool,
     pub current_search_id: u64,
     pub preview_enabled: bool,
+    /// Show the estimated cost of each session (Ctrl+G)
+    pub show_cost: bool,
+}
+
+/// The saved searches shown by the F3 palette, by name, and the highlighted one
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SavedSearchPalette {
+    pub searches: Vec<(String, SavedSearch)>,
+    pub highlighted: usize,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -41,6 +144,14 @@ pub struct SessionInfo {
     pub first_message: String,
     pub preview_messages: Vec<(String, String, String)>, // (role, content, timestamp) triples
     pub summary: Option<String>,
+    /// How the session ended, see `crate::outcome`
+    pub outcome: Option<SessionOutcome>,
+    /// Times the session's context was compacted, see
+    /// `SessionMessage::starts_compaction`
+    pub compactions: usize,
+    /// Estimated cost in USD, `None` when no response of the session is
+    /// priced; see `crate::cost`
+    pub cost_usd: Option<f64>,
 }
 
 pub struct SearchState {
@@ -58,6 +169,47 @@ pub struct SearchState {
     pub has_more_results: bool,
     pub loading_more: bool,
     pub total_loaded: usize,
+    /// Set while partial results are streaming in for the current search
+    pub progress: Option<SearchProgress>,
+    /// Results picked for bulk actions, in the order they were picked. Kept
+    /// across searches until deselected.
+    pub selection: Vec<SearchResult>,
+    /// Session files changed since the results were loaded
+    pub stale: bool,
+    /// Project picked in the Projects tab that searches are limited to
+    pub project: Option<ProjectInfo>,
+    /// Show how the results spread over time above the result list
+    pub timeline_enabled: bool,
+    /// Results dismissed with Alt+X, by the (trimmed) query they were
+    /// dismissed from
+    pub dismissed: BTreeMap<String, Vec<Dismissal>>,
+    /// Loaded results left out of `results` because they were dismissed,
+    /// put back by Alt+U
+    pub hidden: Vec<SearchResult>,
+}
+
+impl SearchState {
+    pub fn is_selected(&self, result: &SearchResult) -> bool {
+        self.selection
+            .iter()
+            .any(|selected| same_message(selected, result))
+    }
+
+    /// Whether the result was dismissed from the current query
+    pub fn is_dismissed(&self, result: &SearchResult) -> bool {
+        self.dismissed
+            .get(self.query.trim())
+            .is_some_and(|dismissed| dismissed.iter().any(|d| d.matches(result)))
+    }
+
+    /// `results` without the dismissed ones, which are kept in `hidden`
+    fn hide_dismissed(&mut self, results: Vec<SearchResult>) -> Vec<SearchResult> {
+        let (hidden, shown): (Vec<_>, Vec<_>) = results
+            .into_iter()
+            .partition(|result| self.is_dismissed(result));
+        self.hidden.extend(hidden);
+        shown
+    }
 }
 
 pub struct SessionState {
@@ -72,6 +224,63 @@ pub struct SessionState {
     pub session_id: Option<String>,
     pub role_filter: Option<String>,
     pub preview_enabled: bool,
+    /// Pick up messages appended to the session file while it is open
+    pub following: bool,
+    /// Message to select once the session is loaded
+    pub target_uuid: Option<String>,
+    /// Search query the session was opened from, marked in its messages
+    /// while the viewer has no query of its own
+    pub highlight: Option<QueryCondition>,
+    /// Where prompts of the session were edited or retried
+    pub branches: SessionBranches,
+    /// The alternative shown at each fork of `branches`
+    pub branch_choice: Vec<usize>,
+}
+
+impl SessionState {
+    /// Show `results` in the viewer, selecting `target_uuid` the first time
+    /// it is among them
+    pub fn set_results(&mut self, mut results: Vec<SearchResult>) {
+        results.retain(|result| self.branches.is_visible(&result.uuid, &self.branch_choice));
+        self.mark_highlight(&mut results);
+        if let Some(uuid) = &self.target_uuid
+            && let Some(index) = results.iter().position(|result| &result.uuid == uuid)
+        {
+            self.selected_index = index;
+            self.target_uuid = None;
+        }
+        self.search_results = results;
+    }
+
+    /// Take the branches of a newly loaded session, showing the latest
+    /// alternative at each fork
+    pub fn set_branches(&mut self, branches: SessionBranches) {
+        self.branch_choice = branches.latest();
+        self.branches = branches;
+    }
+
+    /// Labels like `2/3` for the results that start an alternative, by index
+    pub fn branch_labels(&self) -> HashMap<usize, String> {
+        self.search_results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, result)| {
+                let (fork, alternative) = self.branches.branch_start(&result.uuid)?;
+                let count = self.branches.forks()[fork].alternatives.len();
+                Some((index, format!("{}/{count}", alternative + 1)))
+            })
+            .collect()
+    }
+
+    fn mark_highlight(&self, results: &mut [SearchResult]) {
+        if let Some(highlight) = &self.highlight
+            && self.query.is_empty()
+        {
+            for result in results {
+                result.query = highlight.clone();
+            }
+        }
+    }
 }
 
 pub struct UiState {
@@ -79,7 +288,27 @@ pub struct UiState {
     pub detail_scroll_offset: usize,
     pub selected_result: Option<SearchResult>,
     pub truncation_enabled: bool,
+    /// Leave out user messages that only carry tool results
+    pub hide_tool_results: bool,
+    /// Token context chart shown over the session viewer
+    pub context_chart: Option<Vec<ContextPoint>>,
+    /// Color fenced code blocks in the preview and message detail
+    pub syntax_highlight: bool,
+    /// Theme the screen is drawn with
+    pub theme: ThemeName,
+    /// Theme highlighted in the F2 picker, while it is open
+    pub theme_picker: Option<ThemeName>,
+    /// Saved searches of the F3 palette, while it is open
+    pub saved_searches: Option<SavedSearchPalette>,
     pub show_help: bool,
+    /// Assistant message waiting for a second one to diff against
+    pub diff_mark: Option<SearchResult>,
+    /// Older and newer message shown by the diff view
+    pub diff_pair: Option<(SearchResult, SearchResult)>,
+    pub preview_position: PreviewPosition,
+    /// Percent of the list area the preview takes; `None` keeps each tab's
+    /// default
+    pub preview_size: Option<u16>,
 }
 
 impl Default for AppState {
@@ -107,6 +336,13 @@ impl AppState {
                 has_more_results: false,
                 loading_more: false,
                 total_loaded: 0,
+                progress: None,
+                selection: Vec::new(),
+                stale: false,
+                project: None,
+                timeline_enabled: false,
+                dismissed: BTreeMap::new(),
+                hidden: Vec::new(),
             },
             session: SessionState {
                 messages: Vec::new(),
@@ -120,6 +356,11 @@ impl AppState {
                 session_id: None,
                 role_filter: None,
                 preview_enabled: false,
+                following: false,
+                target_uuid: None,
+                highlight: None,
+                branches: SessionBranches::default(),
+                branch_choice: Vec::new(),
             },
             session_list: SessionListState {
                 sessions: Vec::new(),
@@ -132,13 +373,34 @@ impl AppState {
                 is_typing: false,
                 current_search_id: 0,
                 preview_enabled: true, // Default to true for better UX
+                show_cost: false,
+            },
+            bookmarks: BookmarkListState {
+                results: Vec::new(),
+                selected_index: 0,
+                is_loading: false,
             },
+            projects: ProjectListState {
+                projects: Vec::new(),
+                is_loading: false,
+            },
+            history: HistoryState::default(),
             ui: UiState {
                 message: None,
                 detail_scroll_offset: 0,
                 selected_result: None,
                 truncation_enabled: true,
+                hide_tool_results: false,
+                context_chart: None,
+                syntax_highlight: true,
+                theme: ThemeName::default(),
+                theme_picker: None,
+                saved_searches: None,
                 show_help: false,
+                diff_mark: None,
+                diff_pair: None,
+                preview_position: PreviewPosition::default(),
+                preview_size: None,
             },
         }
     }
@@ -147,6 +409,7 @@ impl AppState {
         match msg {
             Message::QueryChanged(q) => {
                 self.search.query = q;
+                self.history.cycle = None;
                 self.ui.message = Some("[typing...]".to_string());
                 Command::ScheduleSearch(300) // 300ms debounce
             }
@@ -158,18 +421,59 @@ impl AppState {
                 self.search.has_more_results = false;
                 self.search.loading_more = false;
                 self.search.total_loaded = 0;
+                self.search.progress = None;
                 Command::ExecuteSearch
             }
+            Message::SearchBatchReceived(results, progress) => {
+                if self.search.progress.is_none() {
+                    // First batch of a new search replaces the previous results
+                    self.search.results.clear();
+                }
+                // The final response sorts the dismissed ones into `hidden`
+                let results: Vec<SearchResult> = results
+                    .into_iter()
+                    .filter(|result| !self.search.is_dismissed(result))
+                    .collect();
+                self.search.results.extend(results);
+                self.search.order.sort(&mut self.search.results);
+                // Same as the initial load limit; the final response has the real top 100
+                self.search.results.truncate(100);
+                self.search.total_loaded = self.search.results.len();
+                self.search.progress = Some(progress);
+                Command::None
+            }
             Message::SearchCompleted(results) => {
                 // Check if we got the full initial limit (100)
                 self.search.has_more_results = results.len() == 100;
                 self.search.total_loaded = results.len();
-                self.search.results = results;
+                self.search.hidden.clear();
+                self.search.results = self.search.hide_dismissed(results);
                 self.search.is_searching = false;
+                self.search.progress = None;
+                self.search.stale = false;
                 // Results are already sorted by the search engine based on current order
                 self.ui.message = None;
                 Command::None
             }
+            Message::CorpusChanged { idle } => {
+                // Only the Search tab's results are kept around to go stale
+                if self.mode != Mode::Search || self.search.current_tab != SearchTab::Search {
+                    return Command::None;
+                }
+                if idle && !self.search.is_searching && !self.search.loading_more {
+                    self.search.current_search_id += 1;
+                    Command::ExecuteSearch
+                } else {
+                    self.search.stale = true;
+                    Command::None
+                }
+            }
+            Message::RefreshSearch => {
+                self.search.is_searching = true;
+                self.ui.message = Some("[searching...]".to_string());
+                self.search.current_search_id += 1;
+                Command::RefreshSearch
+            }
             Message::LoadMoreResults => {
                 if self.search.has_more_results && !self.search.loading_more {
                     self.search.loading_more = true;
@@ -185,6 +489,7 @@ impl AppState {
                 self.search.total_loaded += new_results.len();
 
                 // Append new results to existing ones
+                let new_results = self.search.hide_dismissed(new_results);
                 self.search.results.extend(new_results);
                 self.search.loading_more = false;
 
@@ -196,7 +501,11 @@ impl AppState {
                 Command::None
             }
             Message::SelectResult(index) => {
-                if index < self.search.results.len() {
+                if self.search.current_tab == SearchTab::Bookmarks {
+                    if index < self.bookmarks.results.len() {
+                        self.bookmarks.selected_index = index;
+                    }
+                } else if index < self.search.results.len() {
                     self.search.selected_index = index;
                 }
                 Command::None
@@ -210,45 +519,22 @@ impl AppState {
                 Command::None
             }
             Message::EnterMessageDetail => {
-                if let Some(result) = self.search.results.get(self.search.selected_index).cloned() {
-                    // Only save state if we're actually changing modes
-                    if self.mode != Mode::MessageDetail {
-                        // If this is our first navigation, save the initial state
-                        if self.navigation_history.is_empty() {
-                            let initial_state = self.create_navigation_state();
-                            self.navigation_history.push(initial_state);
-                        } else if self.mode == Mode::Search {
-                            // Update the current search state before transitioning
-                            // This ensures the current selection is saved
-                            if let Some(_current_pos) = self.navigation_history.current_position() {
-                                self.navigation_history
-                                    .update_current(self.create_navigation_state());
-                            }
-                        }
-
-                        self.ui.selected_result = Some(result);
-                        self.ui.detail_scroll_offset = 0;
-                        self.mode = Mode::MessageDetail;
-
-                        // Save the new state after transitioning
-                        let new_state = self.create_navigation_state();
-                        self.navigation_history.push(new_state);
-                    } else {
-                        self.ui.selected_result = Some(result);
-                        self.ui.detail_scroll_offset = 0;
-                    }
+                if let Some(result) = self.selected_list_result().cloned() {
+                    self.remember_query();
+                    self.enter_message_detail(result);
                 }
                 Command::None
             }
             Message::EnterSessionViewer => {
                 // Try to get result from selected result (when in detail view) or search results
                 let result = if self.mode == Mode::MessageDetail {
-                    self.ui.selected_result.as_ref()
+                    self.ui.selected_result.clone()
                 } else {
-                    self.search.results.get(self.search.selected_index)
+                    self.selected_list_result().cloned()
                 };
 
                 if let Some(result) = result {
+                    self.remember_query();
                     // If this is our first navigation, save the initial state
                     if self.navigation_history.is_empty() {
                         let initial_state = self.create_navigation_state();
@@ -264,17 +550,20 @@ impl AppState {
 
                     let file = result.file.clone();
                     self.mode = Mode::SessionViewer;
-                    self.session.file_path = Some(file.clone());
-                    self.session.session_id = Some(result.session_id.clone());
+                    self.session.file_path = Some(file.to_string());
+                    self.session.session_id = Some(result.session_id.to_string());
                     self.session.query.clear();
+                    self.session.following = false;
                     self.session.selected_index = 0;
                     self.session.scroll_offset = 0;
+                    self.session.target_uuid = Some(result.uuid.clone());
+                    self.session.highlight = Some(result.query.clone());
 
                     // Save the new state after transitioning
                     let new_state = self.create_navigation_state();
                     self.navigation_history.push(new_state);
 
-                    Command::LoadSession(file)
+                    Command::LoadSession(file.to_string())
                 } else {
                     Command::None
                 }
@@ -302,13 +591,7 @@ impl AppState {
                 Command::None
             }
             Message::ToggleRoleFilter => {
-                self.search.role_filter = match &self.search.role_filter {
-                    None => Some("user".to_string()),
-                    Some(r) if r == "user" => Some("assistant".to_string()),
-                    Some(r) if r == "assistant" => Some("system".to_string()),
-                    Some(r) if r == "system" => Some("summary".to_string()),
-                    _ => None,
-                };
+                self.search.role_filter = next_role_filter(self.search.role_filter.as_deref());
                 // Update navigation history to preserve filter state
                 if self.navigation_history.current_position().is_some() {
                     self.navigation_history
@@ -321,10 +604,7 @@ impl AppState {
                 Command::ExecuteSearch
             }
             Message::ToggleSearchOrder => {
-                self.search.order = match self.search.order {
-                    SearchOrder::Descending => SearchOrder::Ascending,
-                    SearchOrder::Ascending => SearchOrder::Descending,
-                };
+                self.search.order = self.search.order.next();
                 // Update navigation history to preserve sort order
                 if self.navigation_history.current_position().is_some() {
                     self.navigation_history
@@ -341,6 +621,102 @@ impl AppState {
                 self.search.preview_enabled = !self.search.preview_enabled;
                 Command::None
             }
+            Message::ToggleTimeline => {
+                self.search.timeline_enabled = !self.search.timeline_enabled;
+                Command::None
+            }
+            Message::CyclePreviewPosition => {
+                let (enabled, position) = match (self.preview_visible(), self.ui.preview_position) {
+                    (false, _) => (true, PreviewPosition::Right),
+                    (true, PreviewPosition::Right) => (true, PreviewPosition::Bottom),
+                    (true, PreviewPosition::Bottom) => (false, PreviewPosition::Right),
+                };
+                match self.search.current_tab {
+                    SearchTab::SessionList => self.session_list.preview_enabled = enabled,
+                    _ => self.search.preview_enabled = enabled,
+                }
+                self.ui.preview_position = position;
+
+                let setting = match (enabled, position) {
+                    (false, _) => "hidden",
+                    (true, PreviewPosition::Right) => "right",
+                    (true, PreviewPosition::Bottom) => "bottom",
+                };
+                self.ui.message = Some(format!("Preview: {setting}"));
+                Command::SaveConfig("preview", toml::Value::String(setting.to_string()))
+            }
+            Message::ResizePreview(delta) => {
+                let size = self
+                    .preview_size()
+                    .saturating_add_signed(delta)
+                    .clamp(PREVIEW_SIZE_MIN, PREVIEW_SIZE_MAX);
+                self.ui.preview_size = Some(size);
+                self.ui.message = Some(format!("Preview size: {size}%"));
+                Command::SaveConfig("preview_size", toml::Value::Integer(size.into()))
+            }
+            Message::SetPreviewSize(size) => {
+                let size = size.clamp(PREVIEW_SIZE_MIN, PREVIEW_SIZE_MAX);
+                self.ui.preview_size = Some(size);
+                self.ui.message = Some(format!("Preview size: {size}%"));
+                Command::None
+            }
+            Message::ShowThemePicker => {
+                self.ui.theme_picker = Some(self.ui.theme);
+                Command::None
+            }
+            Message::HighlightTheme(name) => {
+                self.ui.theme_picker = Some(name);
+                Command::None
+            }
+            Message::ApplyTheme(name) => {
+                self.ui.theme = name;
+                self.ui.theme_picker = None;
+                self.ui.message = Some(format!("Theme: {}", name.as_str()));
+                Command::ApplyTheme(name)
+            }
+            Message::CloseThemePicker => {
+                self.ui.theme_picker = None;
+                Command::None
+            }
+            Message::ShowSavedSearches(searches) => {
+                self.ui.saved_searches = Some(SavedSearchPalette {
+                    searches,
+                    highlighted: 0,
+                });
+                Command::None
+            }
+            Message::HighlightSavedSearch(index) => {
+                if let Some(palette) = &mut self.ui.saved_searches {
+                    palette.highlighted = index;
+                }
+                Command::None
+            }
+            Message::ApplySavedSearch(index) => {
+                let Some((name, search)) = self
+                    .ui
+                    .saved_searches
+                    .take()
+                    .and_then(|palette| palette.searches.into_iter().nth(index))
+                else {
+                    return Command::None;
+                };
+                self.search.current_tab = SearchTab::Search;
+                self.search.query = search.interactive_query(&name);
+                self.search.role_filter = search.filters.role;
+                self.history.cycle = None;
+                self.search.is_searching = true;
+                self.ui.message = Some(format!("Saved search: ${name}"));
+                self.search.current_search_id += 1;
+                Command::ExecuteSearch
+            }
+            Message::CloseSavedSearches => {
+                self.ui.saved_searches = None;
+                Command::None
+            }
+            Message::ToggleSyntaxHighlight => {
+                self.ui.syntax_highlight = !self.ui.syntax_highlight;
+                Command::None
+            }
             Message::SwitchToSearchTab => {
                 if self.mode == Mode::Search {
                     self.search.current_tab = SearchTab::Search;
@@ -368,6 +744,105 @@ impl AppState {
                     Command::None
                 }
             }
+            Message::SwitchToBookmarksTab => {
+                if self.mode == Mode::Search {
+                    self.search.current_tab = SearchTab::Bookmarks;
+                    // Always reload, bookmarks may have changed in other views
+                    self.bookmarks.is_loading = true;
+                    Command::LoadBookmarks
+                } else {
+                    Command::None
+                }
+            }
+            Message::BookmarksLoaded(results) => {
+                self.bookmarks.selected_index = self
+                    .bookmarks
+                    .selected_index
+                    .min(results.len().saturating_sub(1));
+                self.bookmarks.results = results;
+                self.bookmarks.is_loading = false;
+                Command::None
+            }
+            Message::ToggleBookmark(result) => Command::ToggleBookmark(result),
+            Message::HistoryLoaded(entries) => {
+                self.history.entries = entries;
+                Command::None
+            }
+            Message::HistoryPrevious => {
+                let count = self.history.entries.len();
+                if count == 0 {
+                    return Command::None;
+                }
+                let back = self
+                    .history
+                    .cycle
+                    .map_or(0, |back| (back + 1).min(count - 1));
+                self.recall_history(Some(back))
+            }
+            Message::HistoryNext => match self.history.cycle {
+                None => Command::None,
+                // Past the newest entry is the empty query again
+                Some(0) => self.recall_history(None),
+                Some(back) => self.recall_history(Some(back - 1)),
+            },
+            Message::StartHistorySearch => {
+                let count = self.history.matches().len();
+                match &mut self.history.search {
+                    // Again: the next older match, if there is one
+                    Some(search) if search.skip + 1 < count => search.skip += 1,
+                    Some(_) => {}
+                    None => self.history.search = Some(HistorySearch::default()),
+                }
+                Command::None
+            }
+            Message::HistorySearchChanged(filter) => {
+                self.history.search = Some(HistorySearch { filter, skip: 0 });
+                Command::None
+            }
+            Message::AcceptHistorySearch => {
+                let query = self.history.current_match().map(str::to_string);
+                self.history.search = None;
+                let Some(query) = query else {
+                    return Command::None;
+                };
+                self.history.cycle = None;
+                self.search.query = query;
+                self.search.selected_index = 0;
+                self.search.scroll_offset = 0;
+                self.search.is_searching = true;
+                self.ui.message = Some("[searching...]".to_string());
+                self.search.current_search_id += 1;
+                Command::ExecuteSearch
+            }
+            Message::CancelHistorySearch => {
+                self.history.search = None;
+                Command::None
+            }
+            Message::SwitchToProjectsTab => {
+                if self.mode == Mode::Search {
+                    self.search.current_tab = SearchTab::Projects;
+                    if self.projects.projects.is_empty() && !self.projects.is_loading {
+                        self.projects.is_loading = true;
+                        return Command::LoadProjects;
+                    }
+                }
+                Command::None
+            }
+            Message::ProjectsLoaded(projects) => {
+                self.projects.projects = projects;
+                self.projects.is_loading = false;
+                Command::None
+            }
+            Message::ScopeToProject(project) => {
+                self.search.project = project;
+                self.search.current_tab = SearchTab::Search;
+                self.search.selected_index = 0;
+                self.search.scroll_offset = 0;
+                self.search.is_searching = true;
+                self.ui.message = Some("[searching...]".to_string());
+                self.search.current_search_id += 1;
+                Command::ExecuteSearch
+            }
             Message::LoadSessionList => {
                 self.session_list.is_loading = true;
                 Command::LoadSessionList
@@ -384,6 +859,9 @@ impl AppState {
                             first_message,
                             preview_messages,
                             summary,
+                            outcome,
+                            compactions,
+                            cost_usd,
                         )| {
                             SessionInfo {
                                 file_path,
@@ -393,6 +871,9 @@ impl AppState {
                                 first_message,
                                 preview_messages,
                                 summary,
+                                outcome,
+                                compactions,
+                                cost_usd,
                             }
                         },
                     )
@@ -463,6 +944,10 @@ impl AppState {
                 self.session_list.preview_enabled = !self.session_list.preview_enabled;
                 Command::None
             }
+            Message::ToggleSessionListCost => {
+                self.session_list.show_cost = !self.session_list.show_cost;
+                Command::None
+            }
             Message::EnterSessionViewerFromList(file_path) => {
                 // Find the session info to get the session_id
                 if let Some(session_info) = self
@@ -488,8 +973,11 @@ impl AppState {
                     self.session.session_id = Some(session_info.session_id.clone());
                     // Inherit query from SessionList
                     self.session.query = self.session_list.query.clone();
+                    self.session.following = false;
                     self.session.selected_index = 0;
                     self.session.scroll_offset = 0;
+                    self.session.target_uuid = None;
+                    self.session.highlight = None;
 
                     // Save the new state after transitioning
                     let new_state = self.create_navigation_state();
@@ -513,6 +1001,8 @@ impl AppState {
                 self.session_list.query = q;
                 self.session_list.is_typing = true;
                 self.session_list.is_searching = false;
+                // A search still running for the previous query is no longer wanted
+                self.session_list.current_search_id += 1;
                 Command::ScheduleSessionListSearch(300) // 300ms debounce
             }
             Message::SessionListSearchRequested => {
@@ -560,13 +1050,7 @@ impl AppState {
                 }
             }
             Message::ToggleSessionRoleFilter => {
-                self.session.role_filter = match &self.session.role_filter {
-                    None => Some("user".to_string()),
-                    Some(r) if r == "user" => Some("assistant".to_string()),
-                    Some(r) if r == "assistant" => Some("system".to_string()),
-                    Some(r) if r == "system" => Some("summary".to_string()),
-                    _ => None,
-                };
+                self.session.role_filter = next_role_filter(self.session.role_filter.as_deref());
                 // Update navigation history to preserve filter state
                 if self.navigation_history.current_position().is_some() {
                     self.navigation_history
@@ -579,10 +1063,150 @@ impl AppState {
                     Command::None
                 }
             }
+            Message::ToggleToolResults => {
+                self.ui.hide_tool_results = !self.ui.hide_tool_results;
+                match self.mode {
+                    Mode::SessionViewer => {
+                        self.ui.message = Some(if self.ui.hide_tool_results {
+                            "Hiding tool results".to_string()
+                        } else {
+                            "Showing tool results".to_string()
+                        });
+                        if self.session.session_id.is_some() {
+                            Command::ExecuteSessionSearch
+                        } else {
+                            Command::None
+                        }
+                    }
+                    Mode::Search if self.search.current_tab == SearchTab::Search => {
+                        self.search.is_searching = true;
+                        self.ui.message = Some("[searching...]".to_string());
+                        self.search.current_search_id += 1;
+                        Command::ExecuteSearch
+                    }
+                    _ => Command::None,
+                }
+            }
             Message::ToggleSessionPreview => {
                 self.session.preview_enabled = !self.session.preview_enabled;
                 Command::None
             }
+            Message::ToggleSessionFollow => {
+                let Some(file_path) = self.session.file_path.clone() else {
+                    return Command::None;
+                };
+                self.session.following = !self.session.following;
+                if self.session.following {
+                    self.ui.message = Some("Following session for new messages".to_string());
+                    Command::FollowSession(file_path)
+                } else {
+                    self.ui.message = Some("Stopped following session".to_string());
+                    Command::ScheduleClearMessage(MESSAGE_CLEAR_DELAY_MS)
+                }
+            }
+            Message::SessionMessagesAppended(results) => {
+                // A reload may already hold some of them
+                let known: HashSet<Option<usize>> = self
+                    .session
+                    .search_results
+                    .iter()
+                    .map(|result| result.line_number)
+                    .collect();
+                let mut appended: Vec<SearchResult> = results
+                    .into_iter()
+                    .filter(|result| !known.contains(&result.line_number))
+                    .collect();
+                if appended.is_empty() {
+                    return Command::None;
+                }
+                self.session.mark_highlight(&mut appended);
+                // Keep the newest message in view
+                match self.session.order {
+                    SessionOrder::Ascending => {
+                        self.session.search_results.append(&mut appended);
+                        self.session.selected_index =
+                            self.session.search_results.len().saturating_sub(1);
+                    }
+                    SessionOrder::Descending => {
+                        appended.reverse();
+                        appended.append(&mut self.session.search_results);
+                        self.session.search_results = appended;
+                        self.session.selected_index = 0;
+                    }
+                }
+                Command::None
+            }
+            Message::MarkForDiff(result) => {
+                if result.role != "assistant" {
+                    self.ui.message = Some("⚠ Only assistant messages can be diffed".to_string());
+                    return Command::None;
+                }
+                match self.ui.diff_mark.take() {
+                    None => {
+                        self.ui.diff_mark = Some(*result);
+                        self.ui.message = Some(
+                            "Marked for diff, press d on another assistant message".to_string(),
+                        );
+                    }
+                    Some(marked) if marked.uuid == result.uuid => {
+                        self.ui.message = Some("Diff mark cleared".to_string());
+                    }
+                    Some(marked) => {
+                        // Keep the older message on the left whichever was marked first
+                        let pair = if marked.timestamp <= result.timestamp {
+                            (marked, *result)
+                        } else {
+                            (*result, marked)
+                        };
+                        self.enter_diff(pair);
+                    }
+                }
+                Command::None
+            }
+            Message::SwitchBranch(uuid, forward) => {
+                let Some(fork) = self.session.branches.fork_at(&uuid) else {
+                    self.ui.message =
+                        Some("No prompt of this session was edited or retried".to_string());
+                    return Command::ScheduleClearMessage(MESSAGE_CLEAR_DELAY_MS);
+                };
+                let alternatives = &self.session.branches.forks()[fork].alternatives;
+                let count = alternatives.len();
+                let choice = &mut self.session.branch_choice[fork];
+                *choice = if forward {
+                    (*choice + 1) % count
+                } else {
+                    (*choice + count - 1) % count
+                };
+                self.session.target_uuid = Some(alternatives[*choice].clone());
+                Command::ExecuteSessionSearch
+            }
+            Message::DiffBranches(result) => {
+                let branches = &self.session.branches;
+                let Some(fork) = branches.fork_at(&result.uuid) else {
+                    self.ui.message =
+                        Some("No prompt of this session was edited or retried".to_string());
+                    return Command::ScheduleClearMessage(MESSAGE_CLEAR_DELAY_MS);
+                };
+                // The shown branch against the one before it, or the second for the first
+                let shown = self.session.branch_choice[fork];
+                let other = shown.checked_sub(1).unwrap_or(1);
+                let alternatives = &branches.forks()[fork].alternatives;
+                let side = |alternative: usize| SearchResult {
+                    uuid: alternatives[alternative].clone(),
+                    timestamp: branches
+                        .timestamp(fork, alternative)
+                        .unwrap_or_default()
+                        .to_string(),
+                    role: format!("branch {}/{}", alternative + 1, alternatives.len()).into(),
+                    text: branches.transcript(fork, alternative, &self.session.branch_choice),
+                    raw_json: None,
+                    line_number: None,
+                    ..(*result).clone()
+                };
+                let pair = (side(shown.min(other)), side(shown.max(other)));
+                self.enter_diff(pair);
+                Command::None
+            }
             Message::ConvertSessionToCodex => {
                 if let (Some(session_id), Some(file_path)) = (
                     self.session.session_id.clone(),
@@ -691,19 +1315,22 @@ impl AppState {
 
                     // Create a SearchResult
                     let result = SearchResult {
-                        file: file_path,
+                        file: file_path.into(),
                         uuid,
                         timestamp,
-                        session_id: session_id.unwrap_or_default(),
-                        role,
+                        session_id: session_id.unwrap_or_default().into(),
+                        role: role.into(),
                         text: content, // Store extracted content
-                        message_type: "message".to_string(),
+                        message_type: "message".into(),
                         query: QueryCondition::Literal {
                             pattern: String::new(),
                             case_sensitive: false,
                         },
-                        cwd: String::new(), // Not available from session viewer
-                        raw_json: Some(raw_json), // Store full JSON
+                        cwd: SharedStr::default(), // Not available from session viewer
+                        raw_json: Some(raw_json),  // Store full JSON
+                        line_number: None,
+                        matched_in: Vec::new(),
+                        has_images: false,
                     };
 
                     // If this is our first navigation, save the initial state
@@ -774,6 +1401,167 @@ impl AppState {
                 Command::None
             }
             Message::CopyToClipboard(content) => Command::CopyToClipboard(content),
+            Message::ToggleResultSelection => {
+                let Some(result) = self.search.results.get(self.search.selected_index) else {
+                    return Command::None;
+                };
+                match self
+                    .search
+                    .selection
+                    .iter()
+                    .position(|selected| same_message(selected, result))
+                {
+                    Some(position) => {
+                        self.search.selection.remove(position);
+                    }
+                    None => self.search.selection.push(result.clone()),
+                }
+                self.ui.message = Some(self.selection_status());
+                Command::None
+            }
+            Message::ToggleSelectAll => {
+                let unselected: Vec<SearchResult> = self
+                    .search
+                    .results
+                    .iter()
+                    .filter(|result| !self.search.is_selected(result))
+                    .cloned()
+                    .collect();
+                if unselected.is_empty() {
+                    self.search.selection.clear();
+                } else {
+                    self.search.selection.extend(unselected);
+                }
+                self.ui.message = Some(self.selection_status());
+                Command::None
+            }
+            Message::CopySelection | Message::ExportSelection | Message::ViewSelection
+                if self.search.selection.is_empty() =>
+            {
+                self.ui.message = Some("⚠ No results selected (Ctrl+X to select)".to_string());
+                Command::None
+            }
+            Message::CopySelection => {
+                Command::CopyToClipboard(CopyContent::Jsonl(to_jsonl(&self.search.selection)))
+            }
+            Message::ExportSelection => Command::ExportResults(self.search.selection.clone()),
+            Message::ViewSelection => {
+                let combined = combined_result(&self.search.selection);
+                self.enter_message_detail(combined);
+                Command::None
+            }
+            Message::DismissResult => {
+                let picked: Vec<usize> = if self.search.selection.is_empty() {
+                    (self.search.selected_index < self.search.results.len())
+                        .then_some(self.search.selected_index)
+                        .into_iter()
+                        .collect()
+                } else {
+                    (0..self.search.results.len())
+                        .filter(|&index| self.search.is_selected(&self.search.results[index]))
+                        .collect()
+                };
+                if picked.is_empty() {
+                    self.ui.message = Some("⚠ No result to dismiss".to_string());
+                    return Command::None;
+                }
+
+                let query = self.search.query.trim().to_string();
+                let before_selected = picked
+                    .iter()
+                    .filter(|&&index| index < self.search.selected_index)
+                    .count();
+                for &index in picked.iter().rev() {
+                    let result = self.search.results.remove(index);
+                    self.search
+                        .selection
+                        .retain(|selected| !same_message(selected, &result));
+                    self.search
+                        .dismissed
+                        .entry(query.clone())
+                        .or_default()
+                        .push(Dismissal::of(&result));
+                    self.search.hidden.push(result);
+                }
+                // Stay on the same row, which now shows the next result
+                self.search.selected_index = (self.search.selected_index - before_selected)
+                    .min(self.search.results.len().saturating_sub(1));
+                self.ui.message = Some(match picked.len() {
+                    1 => "Dismissed 1 result (Alt+U to restore)".to_string(),
+                    count => format!("Dismissed {count} results (Alt+U to restore)"),
+                });
+                let dismissed = self.search.dismissed[&query].clone();
+                Command::SaveDismissals(query, dismissed)
+            }
+            Message::RestoreDismissed => {
+                let query = self.search.query.trim().to_string();
+                if self.search.dismissed.remove(&query).is_none() {
+                    self.ui.message = Some("No results dismissed from this query".to_string());
+                    return Command::None;
+                }
+                let selected = self.search.results.get(self.search.selected_index).cloned();
+                let restored = self.search.hidden.len();
+                self.search.results.append(&mut self.search.hidden);
+                self.search.order.sort(&mut self.search.results);
+                if let Some(selected) = selected {
+                    self.search.selected_index = self
+                        .search
+                        .results
+                        .iter()
+                        .position(|result| same_message(result, &selected))
+                        .unwrap_or(0);
+                }
+                self.ui.message = Some(match restored {
+                    1 => "Restored 1 dismissed result".to_string(),
+                    count => format!("Restored {count} dismissed results"),
+                });
+                Command::SaveDismissals(query, Vec::new())
+            }
+            Message::DismissalsLoaded(dismissed) => {
+                self.search.dismissed = dismissed;
+                let results = std::mem::take(&mut self.search.results);
+                self.search.hidden.clear();
+                self.search.results = self.search.hide_dismissed(results);
+                self.search.selected_index = self
+                    .search
+                    .selected_index
+                    .min(self.search.results.len().saturating_sub(1));
+                Command::None
+            }
+            Message::CompareWithClipboard(result) => Command::ReadClipboard(result),
+            Message::ClipboardRead(result, clipboard) => {
+                let clipboard = SearchResult {
+                    uuid: String::new(),
+                    timestamp: String::new(),
+                    role: CLIPBOARD_ROLE.into(),
+                    text: clipboard,
+                    message_type: CLIPBOARD_ROLE.into(),
+                    raw_json: None,
+                    line_number: None,
+                    ..(*result).clone()
+                };
+                self.enter_diff((*result, clipboard));
+                Command::None
+            }
+            Message::OpenInPager(text) => Command::OpenInPager(text),
+            Message::ShowContextChart => match &self.session.file_path {
+                Some(file_path) => Command::LoadContextChart(file_path.clone()),
+                None => Command::None,
+            },
+            Message::ContextChartLoaded(points) => {
+                if points.is_empty() {
+                    self.ui.message = Some("No token usage recorded in this session".to_string());
+                    Command::ScheduleClearMessage(MESSAGE_CLEAR_DELAY_MS)
+                } else {
+                    self.ui.context_chart = Some(points);
+                    Command::None
+                }
+            }
+            Message::CloseContextChart => {
+                self.ui.context_chart = None;
+                Command::None
+            }
+            Message::OpenInEditor(file, line) => Command::OpenInEditor(file, line),
             Message::Quit => {
                 Command::None // Handle in main loop
             }
@@ -781,6 +1569,107 @@ impl AppState {
         }
     }
 
+    /// The selected entry of the list shown in the current tab
+    /// Percent of the current tab's list area its preview takes
+    pub fn preview_size(&self) -> u16 {
+        self.ui
+            .preview_size
+            .unwrap_or(match self.search.current_tab {
+                SearchTab::SessionList => SESSION_PREVIEW_SIZE_PERCENT,
+                _ => PREVIEW_SIZE_PERCENT,
+            })
+    }
+
+    fn preview_visible(&self) -> bool {
+        match self.search.current_tab {
+            SearchTab::SessionList => self.session_list.preview_enabled,
+            _ => self.search.preview_enabled,
+        }
+    }
+
+    fn enter_message_detail(&mut self, result: SearchResult) {
+        // Only save state if we're actually changing modes
+        if self.mode != Mode::MessageDetail {
+            // If this is our first navigation, save the initial state
+            if self.navigation_history.is_empty() {
+                let initial_state = self.create_navigation_state();
+                self.navigation_history.push(initial_state);
+            } else if self.mode == Mode::Search {
+                // Update the current search state before transitioning
+                // This ensures the current selection is saved
+                if let Some(_current_pos) = self.navigation_history.current_position() {
+                    self.navigation_history
+                        .update_current(self.create_navigation_state());
+                }
+            }
+
+            self.ui.selected_result = Some(result);
+            self.ui.detail_scroll_offset = 0;
+            self.mode = Mode::MessageDetail;
+
+            // Save the new state after transitioning
+            let new_state = self.create_navigation_state();
+            self.navigation_history.push(new_state);
+        } else {
+            self.ui.selected_result = Some(result);
+            self.ui.detail_scroll_offset = 0;
+        }
+    }
+
+    fn selection_status(&self) -> String {
+        match self.search.selection.len() {
+            0 => "Selection cleared".to_string(),
+            count => format!("{count} selected"),
+        }
+    }
+
+    /// Remember the query of the Search tab when one of its results is opened
+    fn remember_query(&mut self) {
+        if self.mode == Mode::Search && self.search.current_tab == SearchTab::Search {
+            self.history.remember(&self.search.query);
+        }
+    }
+
+    /// Put the history entry `back` steps from the newest in the search bar,
+    /// or clear it for `None`
+    fn recall_history(&mut self, back: Option<usize>) -> Command {
+        self.history.cycle = back;
+        self.search.query = back
+            .and_then(|back| self.history.entries.iter().rev().nth(back))
+            .cloned()
+            .unwrap_or_default();
+        self.ui.message = Some("[typing...]".to_string());
+        Command::ScheduleSearch(300)
+    }
+
+    fn selected_list_result(&self) -> Option<&SearchResult> {
+        if self.search.current_tab == SearchTab::Bookmarks {
+            self.bookmarks.results.get(self.bookmarks.selected_index)
+        } else {
+            self.search.results.get(self.search.selected_index)
+        }
+    }
+
+    /// Show the diff of `pair`, older or message side first, as a new history entry
+    fn enter_diff(&mut self, pair: (SearchResult, SearchResult)) {
+        // If this is our first navigation, save the initial state
+        if self.navigation_history.is_empty() {
+            let initial_state = self.create_navigation_state();
+            self.navigation_history.push(initial_state);
+        } else if let Some(_current_pos) = self.navigation_history.current_position() {
+            self.navigation_history
+                .update_current(self.create_navigation_state());
+        }
+
+        self.ui.diff_pair = Some(pair);
+        self.ui.message = None;
+        self.mode = Mode::MessageDiff;
+
+        // Save the new state after transitioning
+        let new_state = self.create_navigation_state();
+        self.navigation_history.push(new_state);
+    }
+
     // Create a snapshot of current state
     pub fn create_navigation_state(&self) -> NavigationState {
         NavigationState {
@@ -806,6 +1695,7 @@ impl AppState {
                 file_path: self.session.file_path.clone(),
                 session_id: self.session.session_id.clone(),
                 role_filter: self.session.role_filter.clone(),
+                highlight: self.session.highlight.clone(),
             },
             ui_state: UiStateSnapshot {
                 message: self.ui.message.clone(),
@@ -813,6 +1703,7 @@ impl AppState {
                 selected_result: self.ui.selected_result.clone(),
                 truncation_enabled: self.ui.truncation_enabled,
                 show_help: self.ui.show_help,
+                diff_pair: self.ui.diff_pair.clone(),
             },
         }
     }
@@ -842,6 +1733,8 @@ impl AppState {
         self.session.file_path = state.session_state.file_path.clone();
         self.session.session_id = state.session_state.session_id.clone();
         self.session.role_filter = state.session_state.role_filter.clone();
+        self.session.highlight = state.session_state.highlight.clone();
+        self.session.target_uuid = None;
 
         // Restore UI state
         self.ui.message = state.ui_state.message.clone();
@@ -849,6 +1742,7 @@ impl AppState {
         self.ui.selected_result = state.ui_state.selected_result.clone();
         self.ui.truncation_enabled = state.ui_state.truncation_enabled;
         self.ui.show_help = state.ui_state.show_help;
+        self.ui.diff_pair = state.ui_state.diff_pair.clone();
 
         // Execute mode-specific initialization
         self.initialize_mode()
@@ -876,6 +1770,10 @@ impl AppState {
                 self.ui.detail_scroll_offset = 0;
                 Command::None
             }
+            Mode::MessageDiff => {
+                // The compared pair is part of the snapshot; nothing to reload
+                Command::None
+            }
             Mode::Search => {
                 // Search mode maintains its state across transitions
                 // No special initialization needed
@@ -891,3 +1789,73 @@ impl AppState {
         self.initialize_mode()
     }
 }
+
+/// Whether two results are the same message; summaries share an empty uuid,
+/// so the line tells them apart
+fn same_message(a: &SearchResult, b: &SearchResult) -> bool {
+    a.file == b.file && a.uuid == b.uuid && a.line_number == b.line_number
+}
+
+/// `results` one JSON object per line, as `--format jsonl` prints them
+pub fn to_jsonl(results: &[SearchResult]) -> String {
+    results
+        .iter()
+        .filter_map(|result| serde_json::to_string(result).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// A single result holding the text of all `results`, oldest first, for
+/// reading them together in the message detail view
+fn combined_result(results: &[SearchResult]) -> SearchResult {
+    let mut ordered: Vec<&SearchResult> = results.iter().collect();
+    ordered.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    let shared = |field: fn(&SearchResult) -> &SharedStr| -> SharedStr {
+        let first = field(ordered[0]);
+        if ordered.iter().all(|result| field(result) == first) {
+            first.clone()
+        } else {
+            SharedStr::default()
+        }
+    };
+
+    let text = ordered
+        .iter()
+        .map(|result| {
+            format!(
+                "── {} · {} · {} ──\n{}",
+                result.role, result.timestamp, result.session_id, result.text
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    SearchResult {
+        file: shared(|result| &result.file),
+        uuid: String::new(),
+        timestamp: ordered[0].timestamp.clone(),
+        session_id: shared(|result| &result.session_id),
+        role: format!("{} messages", ordered.len()).into(),
+        text,
+        message_type: "selection".into(),
+        query: ordered[0].query.clone(),
+        cwd: shared(|result| &result.cwd),
+        raw_json: None,
+        line_number: None,
+        matched_in: Vec::new(),
+        has_images: false,
+    }
+}
+
+/// The role filter Tab switches to after `current`, the same in the search
+/// tab and the session viewer: all → user → assistant → system → summary
+fn next_role_filter(current: Option<&str>) -> Option<String> {
+    let next = match current {
+        None => "user",
+        Some("user") => "assistant",
+        Some("assistant") => "system",
+        Some("system") => "summary",
+        Some(_) => return None,
+    };
+    Some(next.to_string())
+}
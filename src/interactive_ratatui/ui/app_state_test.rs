@@ -3,8 +3,11 @@ mod tests {
     use super::super::app_state::*;
     use super::super::commands::Command;
     use super::super::events::{CopyContent, Message};
+    use crate::interactive_ratatui::constants::CLIPBOARD_ROLE;
     use crate::interactive_ratatui::domain::models::SearchTab;
-    use crate::interactive_ratatui::domain::models::{Mode, SearchOrder, SessionOrder};
+    use crate::interactive_ratatui::domain::models::{
+        Dismissal, Mode, PreviewPosition, SearchOrder, SessionOrder,
+    };
     use crate::interactive_ratatui::ui::app_state::SessionInfo;
     use crate::query::condition::{QueryCondition, SearchResult};
 
@@ -14,19 +17,22 @@ mod tests {
 
     fn create_test_result() -> SearchResult {
         SearchResult {
-            file: "test.jsonl".to_string(),
+            file: "test.jsonl".into(),
             uuid: "test-uuid".to_string(),
             timestamp: "2024-01-01T00:00:00Z".to_string(),
-            session_id: "test-session".to_string(),
-            role: "user".to_string(),
+            session_id: "test-session".into(),
+            role: "user".into(),
             text: "Test message".to_string(),
-            message_type: "user".to_string(),
+            message_type: "user".into(),
             query: QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: "/test".to_string(),
+            cwd: "/test".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         }
     }
 
@@ -158,6 +164,48 @@ mod tests {
         assert!(matches!(command, Command::LoadSession(_)));
     }
 
+    #[test]
+    fn test_session_viewer_opens_at_the_matched_message() {
+        let mut state = create_test_state();
+        state.search.results = vec![create_test_result()];
+        state.update(Message::EnterSessionViewer);
+
+        let message = |uuid: &str| SearchResult {
+            uuid: uuid.to_string(),
+            query: QueryCondition::Literal {
+                pattern: String::new(),
+                case_sensitive: false,
+            },
+            ..create_test_result()
+        };
+        state.session.set_results(vec![
+            message("first"),
+            message("test-uuid"),
+            message("last"),
+        ]);
+
+        // Selected once, then left to the user
+        assert_eq!(state.session.selected_index, 1);
+        assert_eq!(state.session.target_uuid, None);
+        state.session.selected_index = 2;
+        state
+            .session
+            .set_results(vec![message("first"), message("test-uuid")]);
+        assert_eq!(state.session.selected_index, 2);
+
+        // Marked with the search query until the viewer gets its own
+        assert_eq!(
+            state.session.search_results[0].query,
+            create_test_result().query
+        );
+        state.session.query = "other".to_string();
+        state.session.set_results(vec![message("first")]);
+        assert_eq!(
+            state.session.search_results[0].query,
+            message("first").query
+        );
+    }
+
     #[test]
     fn test_clipboard_command() {
         let mut state = create_test_state();
@@ -220,6 +268,19 @@ mod tests {
         assert!(matches!(command, Command::None));
     }
 
+    #[test]
+    fn test_toggle_syntax_highlight() {
+        let mut state = create_test_state();
+        assert!(state.ui.syntax_highlight);
+
+        let command = state.update(Message::ToggleSyntaxHighlight);
+        assert!(!state.ui.syntax_highlight);
+        assert!(matches!(command, Command::None));
+
+        state.update(Message::ToggleSyntaxHighlight);
+        assert!(state.ui.syntax_highlight);
+    }
+
     #[test]
     fn test_toggle_session_order() {
         let mut state = create_test_state();
@@ -273,7 +334,12 @@ mod tests {
         assert_eq!(command, Command::ExecuteSearch); // Should trigger new search
         assert_eq!(state.search.order, SearchOrder::Ascending);
 
-        // Toggle back to Descending
+        // Then to Relevance (best matches first)
+        let command = state.update(Message::ToggleSearchOrder);
+        assert_eq!(command, Command::ExecuteSearch); // Should trigger new search
+        assert_eq!(state.search.order, SearchOrder::Relevance);
+
+        // And back to Descending
         let command = state.update(Message::ToggleSearchOrder);
         assert_eq!(command, Command::ExecuteSearch); // Should trigger new search
         assert_eq!(state.search.order, SearchOrder::Descending);
@@ -411,6 +477,9 @@ mod tests {
                 ),
             ],
             summary: Some(format!("Summary about {message}")),
+            outcome: None,
+            compactions: 0,
+            cost_usd: None,
         }
     }
 
@@ -437,6 +506,9 @@ mod tests {
                         s.first_message.clone(),
                         s.preview_messages.clone(),
                         s.summary.clone(),
+                        s.outcome,
+                        s.compactions,
+                        s.cost_usd,
                     )
                 })
                 .collect(),
@@ -543,6 +615,9 @@ mod tests {
                         s.first_message.clone(),
                         s.preview_messages.clone(),
                         s.summary.clone(),
+                        s.outcome,
+                        s.compactions,
+                        s.cost_usd,
                     )
                 })
                 .collect(),
@@ -586,6 +661,9 @@ mod tests {
                         s.first_message.clone(),
                         s.preview_messages.clone(),
                         s.summary.clone(),
+                        s.outcome,
+                        s.compactions,
+                        s.cost_usd,
                     )
                 })
                 .collect(),
@@ -639,6 +717,9 @@ mod tests {
                         s.first_message.clone(),
                         s.preview_messages.clone(),
                         s.summary.clone(),
+                        s.outcome,
+                        s.compactions,
+                        s.cost_usd,
                     )
                 })
                 .collect(),
@@ -689,6 +770,9 @@ mod tests {
                         s.first_message.clone(),
                         s.preview_messages.clone(),
                         s.summary.clone(),
+                        s.outcome,
+                        s.compactions,
+                        s.cost_usd,
                     )
                 })
                 .collect(),
@@ -745,6 +829,9 @@ mod tests {
             session.first_message.clone(),
             session.preview_messages.clone(),
             session.summary.clone(),
+            session.outcome,
+            session.compactions,
+            session.cost_usd,
         )]));
 
         // Search for text in summary - triggers debounced search
@@ -960,4 +1047,772 @@ mod tests {
         assert!(!state.search.loading_more);
         assert_eq!(state.ui.message, Some("[all results loaded]".to_string()));
     }
+    #[test]
+    fn test_mark_two_messages_for_diff() {
+        let mut state = create_test_state();
+        state.mode = Mode::SessionViewer;
+
+        let mut user = create_test_result();
+        user.uuid = "user-uuid".to_string();
+        state.update(Message::MarkForDiff(Box::new(user)));
+        assert!(state.ui.diff_mark.is_none());
+        assert_eq!(
+            state.ui.message,
+            Some("⚠ Only assistant messages can be diffed".to_string())
+        );
+
+        let mut newer = create_test_result();
+        newer.role = "assistant".into();
+        newer.uuid = "newer".to_string();
+        newer.timestamp = "2024-01-01T00:02:00Z".to_string();
+        let mut older = newer.clone();
+        older.uuid = "older".to_string();
+        older.timestamp = "2024-01-01T00:01:00Z".to_string();
+
+        // Marking the same message twice clears the mark
+        state.update(Message::MarkForDiff(Box::new(newer.clone())));
+        state.update(Message::MarkForDiff(Box::new(newer.clone())));
+        assert!(state.ui.diff_mark.is_none());
+        assert_eq!(state.mode, Mode::SessionViewer);
+
+        state.update(Message::MarkForDiff(Box::new(newer.clone())));
+        assert!(state.ui.diff_mark.is_some());
+        let command = state.update(Message::MarkForDiff(Box::new(older.clone())));
+        assert_eq!(command, Command::None);
+        assert_eq!(state.mode, Mode::MessageDiff);
+        assert!(state.ui.diff_mark.is_none());
+        let (left, right) = state.ui.diff_pair.clone().unwrap();
+        assert_eq!(left.uuid, "older");
+        assert_eq!(right.uuid, "newer");
+
+        // Going back returns to the session viewer
+        state.update(Message::ExitToSearch);
+        assert_eq!(state.mode, Mode::SessionViewer);
+    }
+
+    #[test]
+    fn test_switch_and_diff_branches() {
+        use crate::branches::SessionBranches;
+
+        let line = |uuid: &str, parent: Option<&str>, text: &str| {
+            serde_json::from_value(serde_json::json!({
+                "type": "user",
+                "parentUuid": parent,
+                "isSidechain": false,
+                "userType": "external",
+                "cwd": "/project",
+                "sessionId": "test-session",
+                "version": "1.0.0",
+                "uuid": uuid,
+                "timestamp": format!("2024-01-01T00:00:0{}Z", &uuid[1..]),
+                "message": {"role": "user", "content": text}
+            }))
+            .unwrap()
+        };
+        let mut state = create_test_state();
+        state.mode = Mode::SessionViewer;
+        state.session.session_id = Some("test-session".to_string());
+        state.session.set_branches(SessionBranches::from_messages(&[
+            line("u1", None, "hello"),
+            line("u2", Some("u1"), "fix the bug"),
+            line("u3", Some("u1"), "fix the parser bug"),
+        ]));
+        let results: Vec<SearchResult> = ["u1", "u2", "u3"]
+            .iter()
+            .map(|uuid| {
+                let mut result = create_test_result();
+                result.uuid = uuid.to_string();
+                result
+            })
+            .collect();
+
+        // The latest attempt is shown first
+        state.session.set_results(results.clone());
+        let shown: Vec<&str> = state
+            .session
+            .search_results
+            .iter()
+            .map(|result| result.uuid.as_str())
+            .collect();
+        assert_eq!(shown, ["u1", "u3"]);
+        assert_eq!(
+            state.session.branch_labels(),
+            std::collections::HashMap::from([(1, "2/2".to_string())])
+        );
+
+        let command = state.update(Message::SwitchBranch("u3".to_string(), true));
+        assert_eq!(command, Command::ExecuteSessionSearch);
+        assert_eq!(state.session.branch_choice, vec![0]);
+        state.session.set_results(results.clone());
+        assert_eq!(state.session.search_results[1].uuid, "u2");
+        assert_eq!(state.session.selected_index, 1);
+
+        let command = state.update(Message::DiffBranches(Box::new(results[1].clone())));
+        assert_eq!(command, Command::None);
+        assert_eq!(state.mode, Mode::MessageDiff);
+        let (left, right) = state.ui.diff_pair.clone().unwrap();
+        assert_eq!(left.role.as_str(), "branch 1/2");
+        assert_eq!(left.text, "user: fix the bug");
+        assert_eq!(right.text, "user: fix the parser bug");
+    }
+
+    #[test]
+    fn test_switch_branch_without_branches() {
+        let mut state = create_test_state();
+        state.mode = Mode::SessionViewer;
+        let command = state.update(Message::SwitchBranch("u1".to_string(), true));
+        assert!(matches!(command, Command::ScheduleClearMessage(_)));
+        assert_eq!(
+            state.ui.message,
+            Some("No prompt of this session was edited or retried".to_string())
+        );
+    }
+
+    #[test]
+    fn test_result_selection() {
+        let mut state = create_test_state();
+        state.search.results = ["u1", "u2", "u3"]
+            .iter()
+            .enumerate()
+            .map(|(i, uuid)| {
+                let mut result = create_test_result();
+                result.uuid = uuid.to_string();
+                result.text = format!("text {uuid}");
+                result.timestamp = format!("2024-01-0{}T00:00:00Z", 3 - i);
+                result
+            })
+            .collect();
+
+        assert_eq!(state.update(Message::CopySelection), Command::None);
+        assert_eq!(
+            state.ui.message,
+            Some("⚠ No results selected (Ctrl+X to select)".to_string())
+        );
+
+        state.search.selected_index = 1;
+        state.update(Message::ToggleResultSelection);
+        assert_eq!(state.ui.message, Some("1 selected".to_string()));
+        assert!(state.search.is_selected(&state.search.results[1]));
+        assert!(!state.search.is_selected(&state.search.results[0]));
+
+        // Select all adds the rest; once everything is selected it clears
+        state.update(Message::ToggleSelectAll);
+        assert_eq!(state.search.selection.len(), 3);
+        state.update(Message::ToggleSelectAll);
+        assert!(state.search.selection.is_empty());
+        assert_eq!(state.ui.message, Some("Selection cleared".to_string()));
+
+        state.update(Message::ToggleResultSelection);
+        state.search.selected_index = 0;
+        state.update(Message::ToggleResultSelection);
+        let Command::CopyToClipboard(CopyContent::Jsonl(jsonl)) =
+            state.update(Message::CopySelection)
+        else {
+            panic!("expected the selection to be copied");
+        };
+        let copied: Vec<SearchResult> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(copied, state.search.selection);
+        assert_eq!(
+            state.update(Message::ExportSelection),
+            Command::ExportResults(state.search.selection.clone())
+        );
+
+        // The combined view reads oldest first
+        state.update(Message::ViewSelection);
+        assert_eq!(state.mode, Mode::MessageDetail);
+        let combined = state.ui.selected_result.clone().unwrap();
+        assert_eq!(combined.role, "2 messages");
+        assert!(combined.text.find("text u2").unwrap() < combined.text.find("text u1").unwrap());
+        assert_eq!(combined.session_id, state.search.results[0].session_id);
+
+        // Selections survive the round trip
+        state.update(Message::ExitToSearch);
+        assert_eq!(state.mode, Mode::Search);
+        assert_eq!(state.search.selection.len(), 2);
+    }
+
+    fn triage_results() -> Vec<SearchResult> {
+        ["u1", "u2", "u3", "u4"]
+            .iter()
+            .enumerate()
+            .map(|(i, uuid)| {
+                let mut result = create_test_result();
+                result.uuid = uuid.to_string();
+                result.timestamp = format!("2024-01-0{}T00:00:00Z", 4 - i);
+                result
+            })
+            .collect()
+    }
+
+    fn uuids(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|result| result.uuid.as_str()).collect()
+    }
+
+    #[test]
+    fn test_dismiss_and_restore_results() {
+        let mut state = create_test_state();
+        state.search.query = "error ".to_string();
+        state.update(Message::SearchCompleted(triage_results()));
+
+        state.search.selected_index = 1;
+        let Command::SaveDismissals(query, dismissed) = state.update(Message::DismissResult) else {
+            panic!("expected the dismissal to be saved");
+        };
+        assert_eq!(query, "error");
+        assert_eq!(dismissed, vec![Dismissal::of(&triage_results()[1])]);
+        assert_eq!(uuids(&state.search.results), ["u1", "u3", "u4"]);
+        // The row now shows the next result
+        assert_eq!(state.search.selected_index, 1);
+        assert_eq!(
+            state.ui.message,
+            Some("Dismissed 1 result (Alt+U to restore)".to_string())
+        );
+
+        // With a selection, the selected results are dismissed instead
+        state.search.selected_index = 0;
+        state.update(Message::ToggleResultSelection);
+        state.search.selected_index = 2;
+        state.update(Message::ToggleResultSelection);
+        let Command::SaveDismissals(_, dismissed) = state.update(Message::DismissResult) else {
+            panic!("expected the dismissals to be saved");
+        };
+        assert_eq!(dismissed.len(), 3);
+        assert_eq!(uuids(&state.search.results), ["u3"]);
+        assert!(state.search.selection.is_empty());
+        assert_eq!(state.search.selected_index, 0);
+
+        // Searching the same query again keeps them hidden
+        state.update(Message::SearchCompleted(triage_results()));
+        assert_eq!(uuids(&state.search.results), ["u3"]);
+        assert_eq!(state.search.hidden.len(), 3);
+        assert_eq!(state.search.total_loaded, 4);
+
+        // Other queries are not affected
+        state.search.query = "deploy".to_string();
+        state.update(Message::SearchCompleted(triage_results()));
+        assert_eq!(state.search.results.len(), 4);
+        assert_eq!(state.update(Message::RestoreDismissed), Command::None);
+        assert_eq!(
+            state.ui.message,
+            Some("No results dismissed from this query".to_string())
+        );
+
+        state.search.query = "error".to_string();
+        state.update(Message::SearchCompleted(triage_results()));
+        assert_eq!(
+            state.update(Message::RestoreDismissed),
+            Command::SaveDismissals("error".to_string(), Vec::new())
+        );
+        assert_eq!(uuids(&state.search.results), ["u1", "u2", "u3", "u4"]);
+        assert_eq!(state.search.selected_index, 2);
+        assert!(state.search.hidden.is_empty());
+        assert_eq!(
+            state.ui.message,
+            Some("Restored 3 dismissed results".to_string())
+        );
+
+        state.search.results.clear();
+        assert_eq!(state.update(Message::DismissResult), Command::None);
+        assert_eq!(state.ui.message, Some("⚠ No result to dismiss".to_string()));
+    }
+
+    #[test]
+    fn test_dismissals_loaded() {
+        let mut state = create_test_state();
+        state.search.query = "error".to_string();
+        state.update(Message::SearchCompleted(triage_results()));
+        state.search.selected_index = 3;
+
+        let dismissed = triage_results()[2..].iter().map(Dismissal::of).collect();
+        state.update(Message::DismissalsLoaded(
+            [("error".to_string(), dismissed)].into_iter().collect(),
+        ));
+        assert_eq!(uuids(&state.search.results), ["u1", "u2"]);
+        assert_eq!(state.search.selected_index, 1);
+        assert_eq!(state.search.hidden.len(), 2);
+    }
+
+    #[test]
+    fn test_compare_with_clipboard() {
+        let mut state = create_test_state();
+        state.mode = Mode::MessageDetail;
+        let result = create_test_result();
+
+        let command = state.update(Message::CompareWithClipboard(Box::new(result.clone())));
+        assert_eq!(command, Command::ReadClipboard(Box::new(result.clone())));
+        assert_eq!(state.mode, Mode::MessageDetail);
+
+        state.update(Message::ClipboardRead(
+            Box::new(result.clone()),
+            "pasted code".to_string(),
+        ));
+        assert_eq!(state.mode, Mode::MessageDiff);
+        let (message, clipboard) = state.ui.diff_pair.clone().unwrap();
+        assert_eq!(message, result);
+        assert_eq!(clipboard.role, CLIPBOARD_ROLE);
+        assert_eq!(clipboard.text, "pasted code");
+        assert!(clipboard.uuid.is_empty());
+
+        state.update(Message::ExitToSearch);
+        assert_eq!(state.mode, Mode::MessageDetail);
+    }
+    #[test]
+    fn test_search_batches_stream_into_results() {
+        use crate::interactive_ratatui::domain::models::SearchProgress;
+
+        let mut state = create_test_state();
+        state.search.results = vec![create_test_result()];
+
+        let batch = |timestamps: &[&str]| -> Vec<SearchResult> {
+            timestamps
+                .iter()
+                .map(|timestamp| {
+                    let mut result = create_test_result();
+                    result.timestamp = timestamp.to_string();
+                    result
+                })
+                .collect()
+        };
+        let progress = |files_searched| SearchProgress {
+            files_searched,
+            files_total: 3,
+        };
+
+        // The first batch replaces the previous search's results
+        state.update(Message::SearchBatchReceived(
+            batch(&["2024-01-02T00:00:00Z"]),
+            progress(1),
+        ));
+        assert_eq!(state.search.results.len(), 1);
+        assert_eq!(state.search.results[0].timestamp, "2024-01-02T00:00:00Z");
+
+        state.update(Message::SearchBatchReceived(
+            batch(&["2024-01-01T00:00:00Z", "2024-01-03T00:00:00Z"]),
+            progress(2),
+        ));
+        let timestamps: Vec<&str> = state
+            .search
+            .results
+            .iter()
+            .map(|result| result.timestamp.as_str())
+            .collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                "2024-01-03T00:00:00Z",
+                "2024-01-02T00:00:00Z",
+                "2024-01-01T00:00:00Z"
+            ]
+        );
+        assert_eq!(state.search.progress, Some(progress(2)));
+        assert_eq!(state.search.total_loaded, 3);
+
+        state.update(Message::SearchCompleted(batch(&["2024-01-03T00:00:00Z"])));
+        assert_eq!(state.search.results.len(), 1);
+        assert!(state.search.progress.is_none());
+    }
+
+    #[test]
+    fn test_bookmarks_tab() {
+        let mut state = create_test_state();
+
+        let command = state.update(Message::SwitchToBookmarksTab);
+        assert_eq!(command, Command::LoadBookmarks);
+        assert_eq!(state.search.current_tab, SearchTab::Bookmarks);
+        assert!(state.bookmarks.is_loading);
+
+        let mut second = create_test_result();
+        second.uuid = "second".to_string();
+        state.update(Message::BookmarksLoaded(vec![create_test_result(), second]));
+        assert!(!state.bookmarks.is_loading);
+
+        // Selection and Enter act on the bookmark list, not the search results
+        state.update(Message::SelectResult(1));
+        assert_eq!(state.bookmarks.selected_index, 1);
+        assert_eq!(state.search.selected_index, 0);
+        state.update(Message::EnterMessageDetail);
+        assert_eq!(state.mode, Mode::MessageDetail);
+        assert_eq!(state.ui.selected_result.as_ref().unwrap().uuid, "second");
+
+        // Reloading a shorter list keeps the selection in range
+        state.update(Message::BookmarksLoaded(vec![create_test_result()]));
+        assert_eq!(state.bookmarks.selected_index, 0);
+    }
+
+    #[test]
+    fn test_projects_tab() {
+        let mut state = create_test_state();
+
+        let command = state.update(Message::SwitchToProjectsTab);
+        assert_eq!(command, Command::LoadProjects);
+        assert_eq!(state.search.current_tab, SearchTab::Projects);
+        assert!(state.projects.is_loading);
+
+        let project = ProjectInfo {
+            dir: "/home/me/.claude/projects/-api".to_string(),
+            name: "/api".to_string(),
+            session_count: 2,
+            last_activity: None,
+        };
+        state.update(Message::ProjectsLoaded(vec![project.clone()]));
+        assert!(!state.projects.is_loading);
+
+        // Projects are only listed once
+        state.update(Message::SwitchToSearchTab);
+        assert_eq!(state.update(Message::SwitchToProjectsTab), Command::None);
+
+        // Picking a project goes back to the Search tab and searches it
+        let command = state.update(Message::ScopeToProject(Some(project.clone())));
+        assert_eq!(command, Command::ExecuteSearch);
+        assert_eq!(state.search.current_tab, SearchTab::Search);
+        assert_eq!(state.search.project, Some(project));
+        assert!(state.search.is_searching);
+
+        state.update(Message::ScopeToProject(None));
+        assert_eq!(state.search.project, None);
+    }
+
+    #[test]
+    fn test_toggle_bookmark_command() {
+        let mut state = create_test_state();
+        let result = Box::new(create_test_result());
+
+        let command = state.update(Message::ToggleBookmark(result.clone()));
+        assert_eq!(command, Command::ToggleBookmark(result));
+    }
+
+    #[test]
+    fn test_session_role_and_tool_result_filters() {
+        let mut state = create_test_state();
+        state.mode = Mode::SessionViewer;
+        state.session.session_id = Some("s1".to_string());
+
+        // Same cycle as the search tab, summaries included
+        let mut roles = Vec::new();
+        for _ in 0..5 {
+            assert_eq!(
+                state.update(Message::ToggleSessionRoleFilter),
+                Command::ExecuteSessionSearch
+            );
+            roles.push(state.session.role_filter.clone());
+        }
+        assert_eq!(
+            roles,
+            [
+                Some("user".to_string()),
+                Some("assistant".to_string()),
+                Some("system".to_string()),
+                Some("summary".to_string()),
+                None
+            ]
+        );
+
+        assert_eq!(
+            state.update(Message::ToggleToolResults),
+            Command::ExecuteSessionSearch
+        );
+        assert!(state.ui.hide_tool_results);
+        assert_eq!(state.ui.message.as_deref(), Some("Hiding tool results"));
+
+        // The same setting reruns the search in the search tab
+        state.mode = Mode::Search;
+        let search_id = state.search.current_search_id;
+        assert_eq!(
+            state.update(Message::ToggleToolResults),
+            Command::ExecuteSearch
+        );
+        assert!(!state.ui.hide_tool_results);
+        assert_eq!(state.search.current_search_id, search_id + 1);
+    }
+
+    #[test]
+    fn test_context_chart() {
+        use crate::usage::{ContextPoint, TokenTotals};
+
+        let mut state = create_test_state();
+        assert_eq!(state.update(Message::ShowContextChart), Command::None);
+
+        state.mode = Mode::SessionViewer;
+        state.session.file_path = Some("test.jsonl".to_string());
+        assert_eq!(
+            state.update(Message::ShowContextChart),
+            Command::LoadContextChart("test.jsonl".to_string())
+        );
+
+        state.update(Message::ContextChartLoaded(Vec::new()));
+        assert!(state.ui.context_chart.is_none());
+        assert_eq!(
+            state.ui.message.as_deref(),
+            Some("No token usage recorded in this session")
+        );
+
+        let points = vec![ContextPoint {
+            timestamp: None,
+            tokens: TokenTotals::default(),
+        }];
+        state.update(Message::ContextChartLoaded(points.clone()));
+        assert_eq!(state.ui.context_chart, Some(points));
+        state.update(Message::CloseContextChart);
+        assert!(state.ui.context_chart.is_none());
+    }
+
+    #[test]
+    fn test_theme_picker() {
+        use crate::interactive_ratatui::ui::theme::ThemeName;
+
+        let mut state = create_test_state();
+        state.update(Message::ShowThemePicker);
+        assert_eq!(state.ui.theme_picker, Some(ThemeName::Dark));
+        state.update(Message::HighlightTheme(ThemeName::Solarized));
+        assert_eq!(state.ui.theme_picker, Some(ThemeName::Solarized));
+        assert_eq!(state.ui.theme, ThemeName::Dark);
+
+        assert_eq!(
+            state.update(Message::ApplyTheme(ThemeName::Solarized)),
+            Command::ApplyTheme(ThemeName::Solarized)
+        );
+        assert_eq!(state.ui.theme, ThemeName::Solarized);
+        assert!(state.ui.theme_picker.is_none());
+        assert_eq!(state.ui.message.as_deref(), Some("Theme: solarized"));
+
+        // Reopening starts from the theme in use
+        state.update(Message::ShowThemePicker);
+        assert_eq!(state.ui.theme_picker, Some(ThemeName::Solarized));
+        state.update(Message::CloseThemePicker);
+        assert!(state.ui.theme_picker.is_none());
+    }
+
+    #[test]
+    fn test_apply_saved_search() {
+        use crate::query::{SavedSearch, SearchFilters};
+
+        let mut state = create_test_state();
+        state.search.current_tab = SearchTab::SessionList;
+        let nightly = SavedSearch {
+            query: "error".to_string(),
+            filters: SearchFilters {
+                role: Some("assistant".to_string()),
+                since: Some("1 day ago".to_string()),
+                ..Default::default()
+            },
+        };
+        state.update(Message::ShowSavedSearches(vec![
+            ("failures".to_string(), "panic".to_string().into()),
+            ("nightly".to_string(), nightly),
+        ]));
+        state.update(Message::HighlightSavedSearch(1));
+        assert_eq!(state.ui.saved_searches.as_ref().unwrap().highlighted, 1);
+
+        assert_eq!(
+            state.update(Message::ApplySavedSearch(1)),
+            Command::ExecuteSearch
+        );
+        assert!(state.ui.saved_searches.is_none());
+        assert_eq!(state.search.current_tab, SearchTab::Search);
+        assert_eq!(state.search.query, r#"$nightly AND since:"1 day ago""#);
+        assert_eq!(state.search.role_filter.as_deref(), Some("assistant"));
+        assert_eq!(state.ui.message.as_deref(), Some("Saved search: $nightly"));
+
+        state.update(Message::ShowSavedSearches(Vec::new()));
+        assert_eq!(state.update(Message::ApplySavedSearch(0)), Command::None);
+        assert!(state.ui.saved_searches.is_none());
+    }
+
+    #[test]
+    fn test_session_follow() {
+        let mut state = create_test_state();
+        // Nothing to follow without an open session
+        assert_eq!(state.update(Message::ToggleSessionFollow), Command::None);
+        assert!(!state.session.following);
+
+        state.mode = Mode::SessionViewer;
+        state.session.file_path = Some("test.jsonl".to_string());
+        let line = |n: usize| {
+            let mut result = create_test_result();
+            result.uuid = format!("u{n}");
+            result.line_number = Some(n);
+            result
+        };
+        state.session.search_results = vec![line(1), line(2)];
+
+        assert_eq!(
+            state.update(Message::ToggleSessionFollow),
+            Command::FollowSession("test.jsonl".to_string())
+        );
+        assert!(state.session.following);
+
+        // Messages a reload already picked up are not added twice
+        state.update(Message::SessionMessagesAppended(vec![line(2), line(3)]));
+        let uuids: Vec<&str> = state
+            .session
+            .search_results
+            .iter()
+            .map(|result| result.uuid.as_str())
+            .collect();
+        assert_eq!(uuids, vec!["u1", "u2", "u3"]);
+        assert_eq!(state.session.selected_index, 2);
+
+        // Newest first puts them on top
+        state.session.order = SessionOrder::Descending;
+        state.session.search_results.reverse();
+        state.update(Message::SessionMessagesAppended(vec![line(4), line(5)]));
+        let uuids: Vec<&str> = state
+            .session
+            .search_results
+            .iter()
+            .map(|result| result.uuid.as_str())
+            .collect();
+        assert_eq!(uuids, vec!["u5", "u4", "u3", "u2", "u1"]);
+        assert_eq!(state.session.selected_index, 0);
+
+        assert!(matches!(
+            state.update(Message::ToggleSessionFollow),
+            Command::ScheduleClearMessage(_)
+        ));
+        assert!(!state.session.following);
+    }
+
+    #[test]
+    fn test_corpus_changed() {
+        let mut state = create_test_state();
+
+        // Busy: only flag the results as stale
+        assert_eq!(
+            state.update(Message::CorpusChanged { idle: false }),
+            Command::None
+        );
+        assert!(state.search.stale);
+        assert_eq!(state.update(Message::RefreshSearch), Command::RefreshSearch);
+        state.update(Message::SearchCompleted(vec![create_test_result()]));
+        assert!(!state.search.stale);
+
+        // Idle: rerun the query right away
+        assert_eq!(
+            state.update(Message::CorpusChanged { idle: true }),
+            Command::ExecuteSearch
+        );
+        assert!(!state.search.stale);
+
+        // Other screens are left alone
+        state.mode = Mode::SessionViewer;
+        assert_eq!(
+            state.update(Message::CorpusChanged { idle: true }),
+            Command::None
+        );
+        assert!(!state.search.stale);
+    }
+
+    #[test]
+    fn test_search_history() {
+        let mut state = create_test_state();
+        state.update(Message::HistoryLoaded(vec![
+            "deploy".to_string(),
+            "error log".to_string(),
+            "Error rate".to_string(),
+        ]));
+
+        // Up/Down walk back and forth from the newest query
+        assert_eq!(
+            state.update(Message::HistoryPrevious),
+            Command::ScheduleSearch(300)
+        );
+        assert_eq!(state.search.query, "Error rate");
+        state.update(Message::HistoryPrevious);
+        state.update(Message::HistoryPrevious);
+        state.update(Message::HistoryPrevious);
+        assert_eq!(state.search.query, "deploy");
+        state.update(Message::HistoryNext);
+        assert_eq!(state.search.query, "error log");
+        state.update(Message::HistoryNext);
+        state.update(Message::HistoryNext);
+        assert_eq!(state.search.query, "");
+        assert_eq!(state.history.cycle, None);
+
+        // Editing the query stops cycling
+        state.update(Message::HistoryPrevious);
+        state.update(Message::QueryChanged("Error rates".to_string()));
+        assert_eq!(state.history.cycle, None);
+
+        // Ctrl+R: newest match first, again for older ones, ignoring case
+        state.update(Message::StartHistorySearch);
+        assert_eq!(state.history.current_match(), Some("Error rate"));
+        state.update(Message::HistorySearchChanged("err".to_string()));
+        state.update(Message::StartHistorySearch);
+        assert_eq!(state.history.current_match(), Some("error log"));
+        state.update(Message::StartHistorySearch);
+        assert_eq!(state.history.current_match(), Some("error log"));
+        assert_eq!(
+            state.update(Message::AcceptHistorySearch),
+            Command::ExecuteSearch
+        );
+        assert_eq!(state.search.query, "error log");
+        assert!(state.history.search.is_none());
+
+        // Nothing matches: accepting keeps the query
+        state.update(Message::StartHistorySearch);
+        state.update(Message::HistorySearchChanged("missing".to_string()));
+        assert_eq!(state.history.current_match(), None);
+        assert_eq!(state.update(Message::AcceptHistorySearch), Command::None);
+        assert_eq!(state.search.query, "error log");
+
+        state.update(Message::StartHistorySearch);
+        state.update(Message::CancelHistorySearch);
+        assert!(state.history.search.is_none());
+
+        // Opening a result remembers its query as the newest
+        state.search.results = vec![create_test_result()];
+        state.update(Message::EnterMessageDetail);
+        assert_eq!(
+            state.history.entries,
+            vec!["deploy", "Error rate", "error log"]
+        );
+        assert_eq!(state.history.added, vec!["error log"]);
+    }
+
+    #[test]
+    fn test_preview_layout() {
+        let mut state = create_test_state();
+        assert!(!state.search.preview_enabled);
+        assert_eq!(state.preview_size(), 60);
+
+        // Hidden -> right -> bottom -> hidden, saved as it goes
+        assert_eq!(
+            state.update(Message::CyclePreviewPosition),
+            Command::SaveConfig("preview", toml::Value::String("right".to_string()))
+        );
+        assert!(state.search.preview_enabled);
+        assert_eq!(state.ui.preview_position, PreviewPosition::Right);
+        state.update(Message::CyclePreviewPosition);
+        assert_eq!(state.ui.preview_position, PreviewPosition::Bottom);
+        assert_eq!(state.ui.message.as_deref(), Some("Preview: bottom"));
+        assert_eq!(
+            state.update(Message::CyclePreviewPosition),
+            Command::SaveConfig("preview", toml::Value::String("hidden".to_string()))
+        );
+        assert!(!state.search.preview_enabled);
+
+        // The session list has its own preview toggle, shown by default
+        state.search.current_tab = SearchTab::SessionList;
+        assert_eq!(state.preview_size(), 50);
+        state.update(Message::CyclePreviewPosition);
+        assert!(state.session_list.preview_enabled);
+        assert_eq!(state.ui.preview_position, PreviewPosition::Bottom);
+        assert!(!state.search.preview_enabled);
+
+        // Resizing starts from the tab's default and stays within bounds
+        assert_eq!(
+            state.update(Message::ResizePreview(10)),
+            Command::SaveConfig("preview_size", toml::Value::Integer(60))
+        );
+        state.search.current_tab = SearchTab::Search;
+        assert_eq!(state.preview_size(), 60);
+        for _ in 0..10 {
+            state.update(Message::ResizePreview(-10));
+        }
+        assert_eq!(state.preview_size(), 20);
+        assert_eq!(state.ui.message.as_deref(), Some("Preview size: 20%"));
+    }
 }
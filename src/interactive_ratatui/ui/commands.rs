@@ -1,21 +1,37 @@
 use super::events::CopyContent;
+use super::theme::ThemeName;
+use crate::interactive_ratatui::domain::models::Dismissal;
+use crate::query::condition::SearchResult;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Command {
     None,
     ExecuteSearch,
+    RefreshSearch,        // Forget cached file lists, then search again
     ExecuteSessionSearch, // Execute search with session_id filter
     ExecuteSessionListSearch,
     ScheduleSearch(u64),            // delay in milliseconds
     ScheduleSessionListSearch(u64), // delay in milliseconds
     LoadSession(String),
+    FollowSession(String), // Reload the session and tail its file from there
     LoadSessionList,
     LoadMore(usize), // Load more results starting from offset
+    LoadBookmarks,
+    LoadProjects,
+    ToggleBookmark(Box<SearchResult>),
     ConvertSessionToCodex {
         session_id: String,
         file_path: String,
     },
     CopyToClipboard(CopyContent),
+    ExportResults(Vec<SearchResult>), // Write as JSONL to a new file
+    ReadClipboard(Box<SearchResult>), // Diff the message against the clipboard
+    OpenInPager(String),
+    LoadContextChart(String), // Session file to read token usage from
+    OpenInEditor(String, Option<usize>), // (file, line)
+    SaveConfig(&'static str, toml::Value), // Set a top-level key in config.toml
+    ApplyTheme(ThemeName),    // Draw with the theme and save it
+    SaveDismissals(String, Vec<Dismissal>), // The query and all results dismissed from it
     ShowMessage(String),
     ClearMessage,
     ScheduleClearMessage(u64), // delay in milliseconds
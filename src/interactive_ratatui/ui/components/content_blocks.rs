@@ -0,0 +1,133 @@
+use crate::interactive_ratatui::ui::components::list_item::{mark_matches, wrap_highlighted};
+use crate::interactive_ratatui::ui::components::view_layout::Styles;
+use crate::interactive_ratatui::ui::theme;
+use crate::query::condition::QueryCondition;
+use crate::schemas::{Content, SessionMessage};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+
+/// What a block of a message holds
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlockKind {
+    Text,
+    Thinking,
+    ToolUse { name: String },
+    ToolResult { is_error: bool },
+}
+
+/// A content block of a message as the message detail shows it. Everything
+/// but plain text can be collapsed to a one-line header.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContentBlock {
+    pub kind: BlockKind,
+    pub text: String,
+    pub collapsed: bool,
+}
+
+impl ContentBlock {
+    pub fn is_collapsible(&self) -> bool {
+        self.kind != BlockKind::Text
+    }
+
+    fn header(&self) -> String {
+        let lines = self.text.lines().count();
+        let marker = if self.collapsed { '▶' } else { '▼' };
+        let title = match &self.kind {
+            BlockKind::Text => String::new(),
+            BlockKind::Thinking => "Thinking".to_string(),
+            BlockKind::ToolUse { name } => format!("Tool call: {name}"),
+            BlockKind::ToolResult { is_error: false } => "Tool result".to_string(),
+            BlockKind::ToolResult { is_error: true } => "Tool result (error)".to_string(),
+        };
+        let plural = if lines == 1 { "" } else { "s" };
+        format!("{marker} {title} ({lines} line{plural})")
+    }
+}
+
+/// Split the raw JSON line of a message into its content blocks.
+///
+/// Messages without thinking, tool calls or tool results give no blocks and
+/// are shown as plain text. Collapsible blocks start collapsed unless they
+/// contain a match of `query`.
+pub fn extract_content_blocks(raw_json: &str, query: &QueryCondition) -> Vec<ContentBlock> {
+    let Ok(message) = serde_json::from_str::<SessionMessage>(raw_json) else {
+        return Vec::new();
+    };
+
+    let blocks: Vec<ContentBlock> = message
+        .content_blocks()
+        .iter()
+        .map(|content| {
+            let (kind, text) = match content {
+                Content::Thinking { thinking, .. } => (BlockKind::Thinking, thinking.clone()),
+                // The whole input rather than the gist the search results show
+                Content::ToolUse { name, input, .. } => (
+                    BlockKind::ToolUse { name: name.clone() },
+                    serde_json::to_string_pretty(input).unwrap_or_default(),
+                ),
+                Content::ToolResult { is_error, .. } => (
+                    BlockKind::ToolResult {
+                        is_error: is_error.unwrap_or(false),
+                    },
+                    content.display_text(),
+                ),
+                Content::Text { .. } | Content::Image { .. } => {
+                    (BlockKind::Text, content.display_text())
+                }
+            };
+            let matched = query.find_match(&text).is_some_and(|(_, len)| len > 0);
+            let collapsed = kind != BlockKind::Text && !matched;
+            ContentBlock {
+                kind,
+                text,
+                collapsed,
+            }
+        })
+        .collect();
+
+    if blocks.iter().any(ContentBlock::is_collapsible) {
+        blocks
+    } else {
+        Vec::new()
+    }
+}
+
+/// Render blocks wrapped to `max_width` columns, with the header of the
+/// `selected`-th collapsible block highlighted. Also returns the line each
+/// collapsible block's header is on.
+pub fn render_content_blocks(
+    blocks: &[ContentBlock],
+    selected: usize,
+    max_width: usize,
+    query: &QueryCondition,
+    syntax_highlight: bool,
+) -> (Vec<Line<'static>>, Vec<usize>) {
+    let mut lines = Vec::new();
+    let mut header_lines = Vec::new();
+
+    for block in blocks {
+        if block.is_collapsible() {
+            let style = if header_lines.len() == selected {
+                Styles::selected()
+            } else {
+                Style::default()
+                    .fg(theme::current().accent)
+                    .add_modifier(Modifier::BOLD)
+            };
+            header_lines.push(lines.len());
+            lines.push(Line::from(Span::styled(block.header(), style)));
+            if block.collapsed {
+                continue;
+            }
+        }
+        lines.extend(
+            wrap_highlighted(&block.text, max_width, syntax_highlight)
+                .into_iter()
+                .map(|line| mark_matches(line, query)),
+        );
+    }
+
+    (lines, header_lines)
+}
@@ -0,0 +1,181 @@
+use crate::interactive_ratatui::constants::*;
+use crate::interactive_ratatui::ui::components::Component;
+use crate::interactive_ratatui::ui::events::Message;
+use crate::interactive_ratatui::ui::theme;
+use crate::usage::ContextPoint;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::Style,
+    symbols::Marker,
+    text::Line,
+    widgets::{Axis, Block, Borders, Chart, Clear, Dataset, GraphType, LegendPosition, Paragraph},
+};
+
+/// Context size of each API response in a session, drawn over the session
+/// viewer
+#[derive(Default)]
+pub struct ContextChart {
+    points: Vec<ContextPoint>,
+}
+
+impl ContextChart {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_points(&mut self, points: Vec<ContextPoint>) {
+        self.points = points;
+    }
+
+    /// Peak and last context, and the share of context read from the cache
+    fn summary(&self) -> String {
+        let peak = self
+            .points
+            .iter()
+            .map(|point| point.tokens.context())
+            .max()
+            .unwrap_or(0);
+        let last = self
+            .points
+            .last()
+            .map(|point| point.tokens.context())
+            .unwrap_or(0);
+        let context: u64 = self.points.iter().map(|p| p.tokens.context()).sum();
+        let cache_read: u64 = self
+            .points
+            .iter()
+            .map(|point| point.tokens.cache_read_input_tokens)
+            .sum();
+        let output: u64 = self.points.iter().map(|p| p.tokens.output_tokens).sum();
+
+        format!(
+            "{} responses | peak {} ({}% of {}) | last {} | cache hits {}% | output {}",
+            self.points.len(),
+            format_tokens(peak),
+            percent(peak, CONTEXT_WINDOW_TOKENS),
+            format_tokens(CONTEXT_WINDOW_TOKENS),
+            format_tokens(last),
+            percent(cache_read, context),
+            format_tokens(output),
+        )
+    }
+}
+
+impl Component for ContextChart {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let dialog_area = area.inner(Margin::new(2, 1));
+        f.render_widget(Clear, dialog_area);
+
+        let block = Block::default()
+            .title(" Token Context (Esc to close) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::current().primary))
+            .style(Style::default().bg(theme::current().background));
+        let inner = block.inner(dialog_area);
+        f.render_widget(block, dialog_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(inner);
+        f.render_widget(Paragraph::new(self.summary()), chunks[0]);
+
+        let context: Vec<(f64, f64)> = self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| ((i + 1) as f64, point.tokens.context() as f64))
+            .collect();
+        let cache_read: Vec<(f64, f64)> = self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| ((i + 1) as f64, point.tokens.cache_read_input_tokens as f64))
+            .collect();
+        let last_x = self.points.len().max(1) as f64;
+        let peak = context.iter().map(|(_, y)| *y).fold(0.0, f64::max);
+
+        // The limit is drawn once the context gets to half of it; smaller
+        // sessions get a scale of their own
+        let limit = CONTEXT_WINDOW_TOKENS as f64;
+        let limit_line = [(1.0, limit), (last_x, limit)];
+        let y_max = if peak * 2.0 >= limit {
+            peak.max(limit) * 1.05
+        } else {
+            (peak * 1.2).max(1.0)
+        };
+
+        let mut datasets = vec![
+            Dataset::default()
+                .name("context")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(theme::current().primary))
+                .data(&context),
+            Dataset::default()
+                .name("cache read")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(theme::current().success))
+                .data(&cache_read),
+        ];
+        if limit <= y_max {
+            datasets.push(
+                Dataset::default()
+                    .name("limit")
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(theme::current().error))
+                    .data(&limit_line),
+            );
+        }
+
+        // The context climbs to the right, so the legend goes on the left
+        let chart = Chart::new(datasets)
+            .legend_position(Some(LegendPosition::TopLeft))
+            .hidden_legend_constraints((Constraint::Ratio(1, 3), Constraint::Ratio(1, 3)))
+            .x_axis(
+                Axis::default()
+                    .title("response")
+                    .style(Style::default().fg(theme::current().text_dim))
+                    .bounds([1.0, last_x])
+                    .labels([Line::from("1"), Line::from(self.points.len().to_string())]),
+            )
+            .y_axis(
+                Axis::default()
+                    .title("tokens")
+                    .style(Style::default().fg(theme::current().text_dim))
+                    .bounds([0.0, y_max])
+                    .labels([
+                        Line::from("0"),
+                        Line::from(format_tokens((y_max / 2.0) as u64)),
+                        Line::from(format_tokens(y_max as u64)),
+                    ]),
+            );
+        f.render_widget(chart, chunks[1]);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Message> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('U') => {
+                Some(Message::CloseContextChart)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Token counts in thousands or millions, e.g. `12.3k`
+pub fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0..1_000 => tokens.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+fn percent(part: u64, whole: u64) -> u64 {
+    (part * 100).checked_div(whole).unwrap_or(0)
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use super::super::context_chart::{ContextChart, format_tokens};
+    use crate::interactive_ratatui::ui::components::Component;
+    use crate::interactive_ratatui::ui::events::Message;
+    use crate::usage::{ContextPoint, TokenTotals};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn point(input: u64, cache_read: u64) -> ContextPoint {
+        ContextPoint {
+            timestamp: None,
+            tokens: TokenTotals {
+                input_tokens: input,
+                output_tokens: 100,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: cache_read,
+            },
+        }
+    }
+
+    fn render(chart: &mut ContextChart) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|f| chart.render(f, f.area())).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn test_format_tokens() {
+        assert_eq!(format_tokens(999), "999");
+        assert_eq!(format_tokens(12_345), "12.3k");
+        assert_eq!(format_tokens(1_500_000), "1.5M");
+    }
+
+    #[test]
+    fn test_context_summary() {
+        let mut chart = ContextChart::new();
+        chart.set_points(vec![
+            point(20_000, 0),
+            point(10_000, 90_000),
+            point(10_000, 150_000),
+        ]);
+
+        let content = render(&mut chart);
+        assert!(content.contains("Token Context"));
+        assert!(
+            content.contains("3 responses | peak 160.0k (80% of 200.0k) | last 160.0k"),
+            "{content}"
+        );
+        // 240k of the 280k context came from the cache
+        assert!(content.contains("cache hits 85%"));
+        // Past half the window, the limit is drawn
+        assert!(content.contains("limit"));
+
+        let mut small = ContextChart::new();
+        small.set_points(vec![point(1_000, 0), point(500, 1_000)]);
+        assert!(!render(&mut small).contains("limit"));
+    }
+
+    #[test]
+    fn test_close_keys() {
+        let mut chart = ContextChart::new();
+        for code in [KeyCode::Esc, KeyCode::Char('q'), KeyCode::Char('U')] {
+            assert_eq!(
+                chart.handle_key(KeyEvent::new(code, KeyModifiers::NONE)),
+                Some(Message::CloseContextChart)
+            );
+        }
+        assert_eq!(
+            chart.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)),
+            None
+        );
+    }
+}
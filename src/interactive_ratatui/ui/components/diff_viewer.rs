@@ -0,0 +1,333 @@
+use crate::interactive_ratatui::constants::*;
+use crate::interactive_ratatui::domain::diff::{DiffLine, diff_lines, side_by_side, unified_diff};
+use crate::interactive_ratatui::ui::components::{
+    Component, is_exit_prompt,
+    view_layout::{Styles, ViewLayout, render_vertical_scrollbar},
+};
+use crate::interactive_ratatui::ui::events::{CopyContent, Message};
+use crate::interactive_ratatui::ui::theme;
+use crate::query::condition::SearchResult;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+const STATUS_TEXT: &str = "↑/↓ j/k: Scroll | Ctrl+U/D PageUp/Down: Page | g/G: Top/Bottom | v: Unified/Side-by-side | c: Copy diff | Alt+←/→: Navigate history | Esc: Back";
+
+#[derive(Default)]
+pub struct DiffViewer {
+    pub(super) old: Option<SearchResult>,
+    pub(super) new: Option<SearchResult>,
+    pub(super) lines: Vec<DiffLine>,
+    pub(super) scroll_offset: usize,
+    pub(super) side_by_side: bool,
+    pub(super) message: Option<String>,
+}
+
+impl DiffViewer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare `old` against `new`, keeping the scroll position if the pair is unchanged
+    pub fn set_pair(&mut self, old: SearchResult, new: SearchResult) {
+        // The clipboard side has no uuid, so its text tells comparisons apart
+        let unchanged = self.old.as_ref().map(|result| &result.uuid) == Some(&old.uuid)
+            && self.new.as_ref().map(|result| (&result.uuid, &result.text))
+                == Some((&new.uuid, &new.text));
+        if !unchanged {
+            // Diffing long messages is too costly to repeat on every frame
+            self.lines = diff_lines(&old.text, &new.text);
+            self.scroll_offset = 0;
+        }
+        self.old = Some(old);
+        self.new = Some(new);
+    }
+
+    pub fn set_message(&mut self, message: Option<String>) {
+        self.message = message;
+    }
+
+    /// The diff as unified diff text, for copying
+    pub fn unified_text(&self) -> Option<String> {
+        let (old, new) = (self.old.as_ref()?, self.new.as_ref()?);
+        Some(unified_diff(&self.lines, &label(old), &label(new)))
+    }
+
+    /// Names of the two sides: a message and the clipboard, or two messages
+    fn side_names(&self) -> (&'static str, &'static str) {
+        match &self.new {
+            Some(new) if new.role == CLIPBOARD_ROLE => ("Message", "Clipboard"),
+            _ => ("Older", "Newer"),
+        }
+    }
+
+    fn counts(&self) -> (usize, usize) {
+        let added = self
+            .lines
+            .iter()
+            .filter(|line| matches!(line, DiffLine::Added(_)))
+            .count();
+        let removed = self
+            .lines
+            .iter()
+            .filter(|line| matches!(line, DiffLine::Removed(_)))
+            .count();
+        (added, removed)
+    }
+
+    fn unified_rows(&self, width: usize) -> Vec<Line<'static>> {
+        let mut rows = Vec::new();
+        for line in &self.lines {
+            let style = line_style(line);
+            for chunk in wrap_chars(line.text(), width.saturating_sub(2)) {
+                rows.push(Line::from(Span::styled(
+                    format!("{} {chunk}", line.prefix()),
+                    style,
+                )));
+            }
+        }
+        rows
+    }
+
+    fn side_by_side_rows(&self, width: usize) -> (Vec<Line<'static>>, Vec<Line<'static>>) {
+        let mut left_rows = Vec::new();
+        let mut right_rows = Vec::new();
+        for (left, right) in side_by_side(&self.lines) {
+            let left_chunks = side_chunks(left, width);
+            let right_chunks = side_chunks(right, width);
+            let height = left_chunks.len().max(right_chunks.len());
+            left_rows.extend(pad_rows(left_chunks, left, height));
+            right_rows.extend(pad_rows(right_chunks, right, height));
+        }
+        (left_rows, right_rows)
+    }
+
+    fn render_content(&mut self, f: &mut Frame, area: Rect) {
+        let is_exit = is_exit_prompt(&self.message);
+        let chunks = if is_exit || self.message.is_some() {
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)])
+                .split(area)
+        } else {
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0)])
+                .split(area)
+        };
+
+        let (added, removed) = self.counts();
+        let (old_name, new_name) = self.side_names();
+        let visible_height = chunks[0].height.saturating_sub(2) as usize;
+
+        if self.side_by_side {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(chunks[0]);
+            let width = columns[0].width.saturating_sub(2) as usize;
+            let (left_rows, right_rows) = self.side_by_side_rows(width);
+            let max_scroll = left_rows.len().saturating_sub(visible_height);
+            self.scroll_offset = self.scroll_offset.min(max_scroll);
+
+            let visible = |rows: Vec<Line<'static>>| -> Vec<Line<'static>> {
+                rows.into_iter()
+                    .skip(self.scroll_offset)
+                    .take(visible_height)
+                    .collect()
+            };
+            let left = Paragraph::new(visible(left_rows)).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("{old_name} (-{removed})")),
+            );
+            let right = Paragraph::new(visible(right_rows)).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("{new_name} (+{added})")),
+            );
+            f.render_widget(left, columns[0]);
+            f.render_widget(right, columns[1]);
+            if max_scroll > 0 {
+                render_vertical_scrollbar(
+                    f,
+                    columns[1],
+                    max_scroll + 1,
+                    visible_height,
+                    self.scroll_offset,
+                );
+            }
+        } else {
+            let width = chunks[0].width.saturating_sub(2) as usize;
+            let rows = self.unified_rows(width);
+            let total_rows = rows.len();
+            let max_scroll = total_rows.saturating_sub(visible_height);
+            self.scroll_offset = self.scroll_offset.min(max_scroll);
+
+            let display_rows: Vec<Line> = rows
+                .into_iter()
+                .skip(self.scroll_offset)
+                .take(visible_height)
+                .collect();
+            let diff = Paragraph::new(display_rows).block(
+                Block::default().borders(Borders::ALL).title(format!(
+                    "Unified diff (+{added} -{removed}, line {}-{} of {total_rows})",
+                    if total_rows > 0 {
+                        self.scroll_offset + 1
+                    } else {
+                        0
+                    },
+                    (self.scroll_offset + visible_height).min(total_rows),
+                )),
+            );
+            f.render_widget(diff, chunks[0]);
+            if max_scroll > 0 {
+                render_vertical_scrollbar(
+                    f,
+                    chunks[0],
+                    max_scroll + 1,
+                    visible_height,
+                    self.scroll_offset,
+                );
+            }
+        }
+
+        if let Some(msg) = &self.message
+            && chunks.len() > 1
+        {
+            let widget = if is_exit {
+                Paragraph::new(msg.clone()).style(
+                    Style::default()
+                        .fg(theme::current().secondary)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else if msg.starts_with('✓') {
+                Paragraph::new(msg.clone()).style(Styles::success())
+            } else if msg.starts_with('⚠') {
+                Paragraph::new(msg.clone()).style(Styles::warning())
+            } else {
+                Paragraph::new(msg.clone()).style(Styles::normal().add_modifier(Modifier::BOLD))
+            };
+            f.render_widget(
+                widget.alignment(ratatui::layout::Alignment::Center),
+                chunks[1],
+            );
+        }
+    }
+}
+
+/// How a message is named in headers and in the copied diff
+fn label(result: &SearchResult) -> String {
+    [result.role.as_str(), &result.uuid, &result.timestamp]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn line_style(line: &DiffLine) -> Style {
+    match line {
+        DiffLine::Same(_) => Styles::normal(),
+        DiffLine::Removed(_) => Style::default().fg(theme::current().error),
+        DiffLine::Added(_) => Style::default().fg(theme::current().success),
+    }
+}
+
+/// Split `text` into rows of at most `width` characters, keeping empty lines
+fn wrap_chars(text: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = text.replace('\t', "    ").chars().collect();
+    if chars.is_empty() || width == 0 {
+        return vec![String::new()];
+    }
+    chars
+        .chunks(width)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+fn side_chunks(line: Option<&DiffLine>, width: usize) -> Vec<String> {
+    line.map(|line| wrap_chars(line.text(), width))
+        .unwrap_or_default()
+}
+
+fn pad_rows(chunks: Vec<String>, line: Option<&DiffLine>, height: usize) -> Vec<Line<'static>> {
+    let style = line.map(line_style).unwrap_or_default();
+    let mut rows: Vec<Line<'static>> = chunks
+        .into_iter()
+        .map(|chunk| Line::from(Span::styled(chunk, style)))
+        .collect();
+    rows.resize(height, Line::from(""));
+    rows
+}
+
+impl Component for DiffViewer {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let (Some(old), Some(new)) = (&self.old, &self.new) else {
+            return;
+        };
+
+        let (old_name, new_name) = self.side_names();
+        let subtitle = format!("{old_name}: {}\n{new_name}: {}", label(old), label(new));
+        let layout = ViewLayout::new("Message Diff".to_string())
+            .with_subtitle(subtitle)
+            .with_status_bar(true)
+            .with_status_text(STATUS_TEXT.to_string());
+
+        layout.render(f, area, |f, content_area| {
+            self.render_content(f, content_area);
+        });
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Message> {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+                None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                // Clamped to the content when rendering
+                self.scroll_offset = self.scroll_offset.saturating_add(1);
+                None
+            }
+            KeyCode::Char('u') if key.modifiers == KeyModifiers::CONTROL => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(PAGE_SIZE);
+                None
+            }
+            KeyCode::Char('d') if key.modifiers == KeyModifiers::CONTROL => {
+                self.scroll_offset = self.scroll_offset.saturating_add(PAGE_SIZE);
+                None
+            }
+            KeyCode::PageUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(PAGE_SIZE);
+                None
+            }
+            KeyCode::PageDown => {
+                self.scroll_offset = self.scroll_offset.saturating_add(PAGE_SIZE);
+                None
+            }
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.scroll_offset = 0;
+                None
+            }
+            KeyCode::End | KeyCode::Char('G') => {
+                self.scroll_offset = usize::MAX;
+                None
+            }
+            KeyCode::Char('v') => {
+                self.side_by_side = !self.side_by_side;
+                self.scroll_offset = 0;
+                None
+            }
+            KeyCode::Char('c') => self
+                .unified_text()
+                .map(|diff| Message::CopyToClipboard(CopyContent::Diff(diff))),
+            KeyCode::Esc => Some(Message::ExitToSearch),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests {
+    use super::super::diff_viewer::DiffViewer;
+    use crate::interactive_ratatui::constants::CLIPBOARD_ROLE;
+    use crate::interactive_ratatui::ui::components::Component;
+    use crate::interactive_ratatui::ui::events::{CopyContent, Message};
+    use crate::query::condition::{QueryCondition, SearchResult};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{Terminal, backend::TestBackend, buffer::Buffer};
+
+    fn create_test_result(uuid: &str, timestamp: &str, text: &str) -> SearchResult {
+        SearchResult {
+            file: "/path/to/test.jsonl".into(),
+            cwd: "/path/to/project".into(),
+            uuid: uuid.to_string(),
+            session_id: "session-123".into(),
+            timestamp: timestamp.to_string(),
+            role: "assistant".into(),
+            text: text.to_string(),
+            message_type: "assistant".into(),
+            query: QueryCondition::Literal {
+                pattern: String::new(),
+                case_sensitive: false,
+            },
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        }
+    }
+
+    fn create_test_viewer() -> DiffViewer {
+        let mut viewer = DiffViewer::new();
+        viewer.set_pair(
+            create_test_result("old-uuid", "2024-01-01T00:00:00Z", "fn a() {}\nold line"),
+            create_test_result("new-uuid", "2024-01-01T00:01:00Z", "fn a() {}\nnew line"),
+        );
+        viewer
+    }
+
+    fn render_component(component: &mut DiffViewer, width: u16, height: u16) -> Buffer {
+        let backend = TestBackend::new(width, height);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|f| component.render(f, f.area())).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    fn buffer_text(buffer: &Buffer) -> String {
+        let mut text = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                text.push_str(buffer[(x, y)].symbol());
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    #[test]
+    fn test_render_unified() {
+        let mut viewer = create_test_viewer();
+        let text = buffer_text(&render_component(&mut viewer, 100, 20));
+
+        assert!(text.contains("Message Diff"));
+        assert!(text.contains("Unified diff (+1 -1"));
+        assert!(text.contains("- old line"));
+        assert!(text.contains("+ new line"));
+    }
+
+    #[test]
+    fn test_toggle_side_by_side() {
+        let mut viewer = create_test_viewer();
+        let key = KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE);
+        assert_eq!(viewer.handle_key(key), None);
+
+        let text = buffer_text(&render_component(&mut viewer, 100, 20));
+        assert!(text.contains("Older (-1)"));
+        assert!(text.contains("Newer (+1)"));
+        let row = text.lines().find(|line| line.contains("old line")).unwrap();
+        assert!(row.contains("new line"));
+    }
+
+    #[test]
+    fn test_scroll_is_clamped_on_render() {
+        let mut viewer = create_test_viewer();
+        viewer.handle_key(KeyEvent::new(KeyCode::End, KeyModifiers::NONE));
+        render_component(&mut viewer, 100, 20);
+        // Three diff lines fit on screen, so there is nothing to scroll
+        assert_eq!(viewer.scroll_offset, 0);
+
+        viewer.handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE));
+        assert_eq!(viewer.scroll_offset, 1);
+        viewer.handle_key(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE));
+        assert_eq!(viewer.scroll_offset, 0);
+    }
+
+    #[test]
+    fn test_same_pair_keeps_scroll() {
+        let mut viewer = create_test_viewer();
+        viewer.scroll_offset = 2;
+        viewer.set_pair(
+            create_test_result("old-uuid", "2024-01-01T00:00:00Z", "fn a() {}\nold line"),
+            create_test_result("new-uuid", "2024-01-01T00:01:00Z", "fn a() {}\nnew line"),
+        );
+        assert_eq!(viewer.scroll_offset, 2);
+
+        viewer.set_pair(
+            create_test_result("other-uuid", "2024-01-01T00:00:00Z", "x"),
+            create_test_result("new-uuid", "2024-01-01T00:01:00Z", "y"),
+        );
+        assert_eq!(viewer.scroll_offset, 0);
+        assert_eq!(viewer.lines.len(), 2);
+    }
+
+    #[test]
+    fn test_copy_diff() {
+        let mut viewer = create_test_viewer();
+        let key = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE);
+        let Some(Message::CopyToClipboard(CopyContent::Diff(diff))) = viewer.handle_key(key) else {
+            panic!("expected a diff to copy");
+        };
+        assert!(diff.starts_with("--- assistant old-uuid 2024-01-01T00:00:00Z\n"));
+        assert!(diff.ends_with(" fn a() {}\n-old line\n+new line\n"));
+    }
+
+    #[test]
+    fn test_clipboard_pair() {
+        let clipboard = |text: &str| {
+            let mut result = create_test_result("", "", text);
+            result.role = CLIPBOARD_ROLE.into();
+            result
+        };
+        let message = create_test_result("old-uuid", "2024-01-01T00:00:00Z", "fn a() {}\nold line");
+        let mut viewer = DiffViewer::new();
+        viewer.set_pair(message.clone(), clipboard("fn a() {}\nnew line"));
+        viewer.side_by_side = true;
+        let text = buffer_text(&render_component(&mut viewer, 100, 20));
+        assert!(text.contains("Message (-1)"));
+        assert!(text.contains("Clipboard (+1)"));
+        assert!(text.contains("Clipboard: clipboard"));
+
+        // Comparing again after the clipboard changed diffs the new text
+        viewer.set_pair(message, clipboard("fn a() {}\nold line"));
+        assert!(
+            viewer
+                .unified_text()
+                .unwrap()
+                .starts_with("--- assistant old-uuid 2024-01-01T00:00:00Z\n+++ clipboard\n fn a")
+        );
+        assert_eq!(viewer.lines.len(), 2);
+    }
+
+    #[test]
+    fn test_escape_goes_back() {
+        let mut viewer = create_test_viewer();
+        let key = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(viewer.handle_key(key), Some(Message::ExitToSearch));
+    }
+}
@@ -1,21 +1,31 @@
+use crate::interactive_ratatui::application::cache_service::SessionCacheStats;
 use crate::interactive_ratatui::constants::*;
 use crate::interactive_ratatui::ui::components::Component;
 use crate::interactive_ratatui::ui::events::Message;
+use crate::interactive_ratatui::ui::theme;
+use crate::search::result_cache;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     Frame,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph},
 };
 
 #[derive(Default)]
-pub struct HelpDialog;
+pub struct HelpDialog {
+    session_cache: SessionCacheStats,
+}
 
 impl HelpDialog {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// What the session file cache holds, shown under the result cache
+    pub fn set_session_cache(&mut self, stats: SessionCacheStats) {
+        self.session_cache = stats;
     }
 
     fn get_help_text() -> Vec<Line<'static>> {
@@ -23,14 +33,14 @@ impl HelpDialog {
             Line::from(vec![Span::styled(
                 "Claude Session Message Search - Interactive Mode",
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme::current().primary)
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from(""),
             Line::from(vec![Span::styled(
                 "Navigation (All Scrollable Views):",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme::current().secondary)
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from("  ↑/↓         - Move up/down"),
@@ -42,7 +52,7 @@ impl HelpDialog {
             Line::from(vec![Span::styled(
                 "Copy Operations (Unified Across Modes):",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme::current().secondary)
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from("  c           - Copy content/text"),
@@ -54,18 +64,20 @@ impl HelpDialog {
             Line::from(vec![Span::styled(
                 "Global Shortcuts:",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme::current().secondary)
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from("  Alt+←       - Navigate back through history"),
             Line::from("  Alt+→       - Navigate forward through history"),
             Line::from("  Ctrl+T      - Toggle message truncation"),
             Line::from("  ?           - Show this help"),
+            Line::from("  F2          - Pick a color theme"),
+            Line::from("  F3          - Apply a saved search"),
             Line::from(""),
             Line::from(vec![Span::styled(
                 "Search Mode:",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme::current().secondary)
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from("  ↑/↓         - Navigate results"),
@@ -73,15 +85,33 @@ impl HelpDialog {
             Line::from("  Enter       - View message details"),
             Line::from("  Ctrl+S      - Jump directly to session viewer"),
             Line::from("  Tab         - Toggle role filter (user/assistant/system/summary)"),
-            Line::from("  Ctrl+O      - Toggle sort order (newest/oldest first)"),
-            Line::from("  Shift+Tab   - Switch between Search/Session List tabs"),
+            Line::from("  Ctrl+O      - Cycle sort order (newest/oldest first, best match)"),
+            Line::from("  Alt+T       - Hide user messages that only carry tool results"),
+            Line::from("  Alt+H       - Show/hide the timeline of matches over time"),
+            Line::from("  ↑/↓         - Recall earlier queries (empty query, first result)"),
+            Line::from("  Ctrl+R      - Search earlier queries (again for older matches)"),
+            Line::from("  F5          - Refresh results after session files changed"),
+            Line::from("  Ctrl+X      - Select/deselect the result for bulk actions"),
+            Line::from("  Alt+A       - Select all results (again to clear the selection)"),
+            Line::from("  Ctrl+Y      - Copy selected results as JSONL"),
+            Line::from("  Alt+S       - Export selected results to ccms-selection-*.jsonl"),
+            Line::from("  Alt+D       - View selected results together"),
+            Line::from("  Alt+X       - Dismiss the result, or the selected ones, from the list"),
+            Line::from("  Alt+U       - Restore the results dismissed from this query"),
+            Line::from("  Alt+E       - Open the result's session file in $EDITOR"),
+            Line::from("  Alt+P       - Preview right/below/hidden (saved to config.toml)"),
+            Line::from("  Alt+-/=     - Shrink/grow the preview"),
+            Line::from("  Mouse       - Click, scroll, drag the preview border (mouse = true)"),
+            Line::from("  Shift+Tab   - Cycle Search/Session List/Bookmarks/Projects tabs"),
             Line::from("  Ctrl+←/→    - Navigate between tabs"),
+            Line::from("  b / Del     - Remove the selected bookmark (Bookmarks tab)"),
+            Line::from("  Enter       - Limit searches to the project (Projects tab)"),
             Line::from("  Esc         - Quit"),
             Line::from(""),
             Line::from(vec![Span::styled(
                 "Text Editing Shortcuts (Search & Session Viewer):",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme::current().secondary)
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from("  Ctrl+A      - Move cursor to beginning of line"),
@@ -99,24 +129,32 @@ impl HelpDialog {
             Line::from(vec![Span::styled(
                 "Message Detail Mode:",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme::current().secondary)
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from("  ↑/↓         - Scroll content"),
             Line::from("  Ctrl+u/d    - Half-page scrolling (up/down)"),
             Line::from("  Ctrl+S      - Jump to session viewer"),
+            Line::from("  Tab/S-Tab   - Select the next/previous thinking or tool block"),
+            Line::from("  z/Enter     - Collapse or expand the selected block"),
             Line::from("  c           - Copy message content to clipboard"),
             Line::from("  C           - Copy message as JSON to clipboard"),
             Line::from("  i           - Copy session ID to clipboard"),
             Line::from("  f           - Copy file path to clipboard"),
             Line::from("  p           - Copy project path to clipboard"),
+            Line::from("  u           - Copy URLs in the message to clipboard"),
+            Line::from("  b           - Bookmark the message (or remove its bookmark)"),
+            Line::from("  D           - Diff the message against the clipboard"),
+            Line::from("  h           - Toggle syntax highlighting of code blocks"),
+            Line::from("  o           - Open long messages in $PAGER"),
+            Line::from("  e           - Open the session file in $EDITOR at the message"),
             Line::from("  Backspace   - Back to search results"),
             Line::from("  Esc         - Back to search results"),
             Line::from(""),
             Line::from(vec![Span::styled(
                 "Session Viewer Mode:",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme::current().secondary)
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from("  ↑/↓         - Navigate messages"),
@@ -130,14 +168,33 @@ impl HelpDialog {
             Line::from("  f           - Copy file path to clipboard"),
             Line::from("  p           - Copy project path to clipboard"),
             Line::from("  v           - Convert and copy Codex session ID"),
+            Line::from("  d           - Mark assistant message; marking a second opens a diff"),
+            Line::from("  [ / ]       - Show the previous/next branch of an edited prompt"),
+            Line::from("  D           - Diff the shown branch against another"),
+            Line::from("  b           - Bookmark the message (or remove its bookmark)"),
+            Line::from("  F           - Follow the session as new messages are written"),
+            Line::from("  T           - Hide user messages that only carry tool results"),
+            Line::from("  U           - Chart the token context of the session over time"),
             Line::from("  Ctrl+O      - Toggle sort order (ascending/descending)"),
             Line::from("  Backspace   - Back to search results (or clear search)"),
             Line::from("  Esc         - Back to search results"),
             Line::from(""),
+            Line::from(vec![Span::styled(
+                "Message Diff Mode:",
+                Style::default()
+                    .fg(theme::current().secondary)
+                    .add_modifier(Modifier::BOLD),
+            )]),
+            Line::from("  ↑/↓ j/k     - Scroll diff"),
+            Line::from("  g/G         - Jump to top/bottom"),
+            Line::from("  v           - Toggle unified/side-by-side layout"),
+            Line::from("  c           - Copy diff (unified format) to clipboard"),
+            Line::from("  Esc         - Back to the session viewer or message detail"),
+            Line::from(""),
             Line::from(vec![Span::styled(
                 "Query Syntax:",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme::current().secondary)
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from("  word        - Search for 'word'"),
@@ -154,7 +211,23 @@ impl HelpDialog {
 
 impl Component for HelpDialog {
     fn render(&mut self, f: &mut Frame, area: Rect) {
-        let help_text = Self::get_help_text();
+        let mut help_text = Self::get_help_text();
+        // Above the closing hint
+        help_text.insert(
+            help_text.len() - 1,
+            Line::from(vec![Span::styled(
+                format!("Result cache: {}", result_cache::stats().summary()),
+                Style::default().fg(theme::current().text_dim),
+            )]),
+        );
+        help_text.insert(
+            help_text.len() - 1,
+            Line::from(vec![Span::styled(
+                format!("Session cache: {}", self.session_cache.summary()),
+                Style::default().fg(theme::current().text_dim),
+            )]),
+        );
+        help_text.insert(help_text.len() - 1, Line::from(""));
 
         // Calculate dialog dimensions using constraints
         let dialog_width = HELP_DIALOG_MAX_WIDTH.min(area.width.saturating_sub(HELP_DIALOG_MARGIN));
@@ -190,10 +263,10 @@ impl Component for HelpDialog {
                 Block::default()
                     .title(" Help ")
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Cyan))
-                    .style(Style::default().bg(Color::Black)),
+                    .border_style(Style::default().fg(theme::current().primary))
+                    .style(Style::default().bg(theme::current().background)),
             )
-            .style(Style::default().fg(Color::White))
+            .style(Style::default().fg(theme::current().text))
             .alignment(Alignment::Left);
 
         f.render_widget(help, dialog_area);
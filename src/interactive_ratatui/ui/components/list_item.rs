@@ -1,5 +1,8 @@
+use crate::interactive_ratatui::ui::highlight::{fence_style, highlight_line};
+use crate::interactive_ratatui::ui::theme;
+use crate::query::condition::QueryCondition;
 use ratatui::{
-    style::{Color, Modifier, Style},
+    style::{Color, Style},
     text::{Line, Span},
 };
 
@@ -16,13 +19,7 @@ pub trait ListItem: Clone {
 
     /// Returns the color for the role
     fn get_role_color(&self) -> Color {
-        match self.get_role() {
-            "user" => Color::Green,
-            "assistant" => Color::Blue,
-            "system" => Color::Yellow,
-            "summary" => Color::Magenta,
-            _ => Color::White,
-        }
+        theme::current().role_color(self.get_role())
     }
 
     /// Formats the timestamp for display
@@ -99,6 +96,196 @@ pub fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
     }
 }
 
+/// Marker prefixed to continuation segments of wrapped code lines
+pub const CONTINUATION_MARKER: &str = "↪ ";
+
+/// Wraps multi-line message text for the detail and preview panes.
+///
+/// Prose is broken at word boundaries where possible. Lines inside fenced code
+/// blocks, or lines that start with indentation, keep that indentation on every
+/// wrapped segment, and continuation segments are prefixed with a dimmed `↪`
+/// so wrapped code stays readable.
+pub fn wrap_preserving_indent(text: &str, max_width: usize) -> Vec<Line<'static>> {
+    wrap_highlighted(text, max_width, false)
+}
+
+/// `wrap_preserving_indent`, additionally syntax highlighting fenced code
+/// blocks tagged with a known language when `highlight` is set
+pub fn wrap_highlighted(text: &str, max_width: usize, highlight: bool) -> Vec<Line<'static>> {
+    if max_width == 0 {
+        return vec![];
+    }
+
+    let mut lines = Vec::new();
+    // Info string of the open code block, e.g. "rust"
+    let mut code_lang: Option<&str> = None;
+
+    for line in text.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+        let is_code = code_lang.is_some() && fence.is_none();
+        if let Some(info) = fence {
+            code_lang = match code_lang {
+                Some(_) => None,
+                None => Some(info.trim()),
+            };
+        }
+
+        let spans = match (highlight, fence, code_lang) {
+            (true, Some(_), _) => vec![Span::styled(line.to_string(), fence_style())],
+            (true, None, Some(lang)) if is_code => {
+                highlight_line(line, lang).unwrap_or_else(|| vec![Span::raw(line.to_string())])
+            }
+            _ => vec![Span::raw(line.to_string())],
+        };
+
+        if line.chars().count() <= max_width {
+            lines.push(Line::from(spans));
+        } else if is_code || line.starts_with([' ', '\t']) {
+            wrap_code_line(line, spans, max_width, &mut lines);
+        } else {
+            wrap_prose_line(line, max_width, &mut lines);
+        }
+    }
+
+    lines
+}
+
+fn wrap_prose_line(line: &str, max_width: usize, lines: &mut Vec<Line<'static>>) {
+    let mut remaining: Vec<char> = line.chars().collect();
+
+    while !remaining.is_empty() {
+        let mut end = remaining.len().min(max_width);
+
+        // If we're not at the end, try to break at a word boundary
+        if end < remaining.len()
+            && let Some(space_pos) = remaining[..end].iter().rposition(|&c| c == ' ')
+            && space_pos > max_width / 2
+        {
+            end = space_pos + 1; // Include the space
+        }
+
+        lines.push(Line::from(remaining[..end].iter().collect::<String>()));
+        remaining.drain(..end);
+    }
+}
+
+fn wrap_code_line(
+    line: &str,
+    spans: Vec<Span<'static>>,
+    max_width: usize,
+    lines: &mut Vec<Line<'static>>,
+) {
+    let chars: Vec<char> = line.chars().collect();
+
+    // Cap the carried indentation so deeply nested code still gets room to show
+    let indent_width = chars
+        .iter()
+        .take_while(|c| c.is_whitespace())
+        .count()
+        .min(max_width / 2);
+    let indent: String = chars[..indent_width].iter().collect();
+    let prefix_width = indent_width + CONTINUATION_MARKER.chars().count();
+    let segment_width = max_width.saturating_sub(prefix_width).max(1);
+
+    let (first, mut rest) = split_spans(spans, max_width);
+    lines.push(Line::from(first));
+
+    while !rest.is_empty() {
+        let (segment, remaining) = split_spans(rest, segment_width);
+        rest = remaining;
+        let mut segment_line = vec![
+            Span::raw(indent.clone()),
+            Span::styled(
+                CONTINUATION_MARKER,
+                Style::default().fg(theme::current().text_dim),
+            ),
+        ];
+        segment_line.extend(segment);
+        lines.push(Line::from(segment_line));
+    }
+}
+
+/// Split styled spans after `width` characters, keeping each piece's style
+fn split_spans(
+    spans: Vec<Span<'static>>,
+    width: usize,
+) -> (Vec<Span<'static>>, Vec<Span<'static>>) {
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    let mut remaining = width;
+
+    for span in spans {
+        let len = span.content.chars().count();
+        if remaining >= len {
+            remaining -= len;
+            head.push(span);
+        } else if remaining == 0 {
+            tail.push(span);
+        } else {
+            let split = span
+                .content
+                .char_indices()
+                .nth(remaining)
+                .map_or(span.content.len(), |(i, _)| i);
+            let (left, right) = span.content.split_at(split);
+            head.push(Span::styled(left.to_string(), span.style));
+            tail.push(Span::styled(right.to_string(), span.style));
+            remaining = 0;
+        }
+    }
+
+    (head, tail)
+}
+
+/// `line` with every match of `query` in it shown like a match in the
+/// result list, on top of the style it already has
+pub fn mark_matches(line: Line<'static>, query: &QueryCondition) -> Line<'static> {
+    let text: String = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+
+    let ranges: Vec<_> = query
+        .find_matches(&text)
+        .into_iter()
+        .map(|(start, len)| start..start + len)
+        .collect();
+    if ranges.is_empty() {
+        return line;
+    }
+
+    let match_style = theme::current().match_style();
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for span in line.spans {
+        let span_end = offset + span.content.len();
+        let mut cuts = vec![offset, span_end];
+        for range in &ranges {
+            cuts.extend(
+                [range.start, range.end]
+                    .into_iter()
+                    .filter(|cut| (offset..span_end).contains(cut)),
+            );
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+        for piece in cuts.windows(2) {
+            let style = if ranges.iter().any(|range| range.contains(&piece[0])) {
+                span.style.patch(match_style)
+            } else {
+                span.style
+            };
+            spans.push(Span::styled(
+                span.content[piece[0] - offset..piece[1] - offset].to_string(),
+                style,
+            ));
+        }
+        offset = span_end;
+    }
+    Line::from(spans).style(line.style)
+}
+
 // New helper function for highlighting
 pub fn highlight_text(text: &str, query: &str) -> Vec<Span<'static>> {
     if query.is_empty() {
@@ -114,7 +301,7 @@ pub fn highlight_text(text: &str, query: &str) -> Vec<Span<'static>> {
         }
         spans.push(Span::styled(
             matched_text.to_string(),
-            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            theme::current().match_style(),
         ));
         last_end = start + matched_text.len();
     }
@@ -61,4 +61,125 @@ mod tests {
         let truncated = truncate_message(emoji, 10);
         assert_eq!(truncated, "🔍🎯💻🎨🔧 S...");
     }
+
+    fn line_text(line: &ratatui::text::Line) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_wrap_preserving_indent_prose() {
+        let lines = wrap_preserving_indent("Hello world this is a test", 12);
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(texts, vec!["Hello world ", "this is a ", "test"]);
+    }
+
+    #[test]
+    fn test_wrap_highlighted_code_block() {
+        use ratatui::style::Color;
+
+        let text = "let x = 1;\n```rust\nlet value = \"a long string literal\";\n```";
+        let plain = wrap_highlighted(text, 20, false);
+        let highlighted = wrap_highlighted(text, 20, true);
+
+        // Highlighting changes styles, never the wrapped text
+        let texts: Vec<String> = highlighted.iter().map(line_text).collect();
+        assert_eq!(texts, plain.iter().map(line_text).collect::<Vec<_>>());
+        assert_eq!(
+            texts[2..4],
+            [
+                "let value = \"a long ".to_string(),
+                format!("{CONTINUATION_MARKER}string literal\";")
+            ]
+        );
+
+        // Prose outside the block is left alone
+        assert_eq!(highlighted[0], plain[0]);
+        assert_eq!(highlighted[2].spans[0].style.fg, Some(Color::Magenta));
+        // The string keeps its color across the wrap
+        assert_eq!(
+            highlighted[2].spans.last().unwrap().style.fg,
+            Some(Color::Green)
+        );
+        assert_eq!(highlighted[3].spans[2].style.fg, Some(Color::Green));
+        assert!(plain[2].spans.iter().all(|span| span.style.fg.is_none()));
+    }
+
+    #[test]
+    fn test_wrap_preserving_indent_code_block() {
+        let text = "```rust\n    let value = compute_something(argument_one, argument_two);\n```";
+        let lines = wrap_preserving_indent(text, 30);
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+
+        assert_eq!(texts[0], "```rust");
+        assert_eq!(texts[1], "    let value = compute_someth");
+        // Continuation lines keep the indentation and carry the marker
+        for continuation in &texts[2..texts.len() - 1] {
+            assert!(continuation.starts_with(&format!("    {CONTINUATION_MARKER}")));
+            assert!(continuation.chars().count() <= 30);
+        }
+        assert_eq!(texts.last().unwrap(), "```");
+
+        let rejoined: String = texts[1..texts.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                if i == 0 {
+                    t.clone()
+                } else {
+                    t.trim_start_matches(' ')
+                        .trim_start_matches(CONTINUATION_MARKER)
+                        .to_string()
+                }
+            })
+            .collect();
+        assert_eq!(
+            rejoined,
+            "    let value = compute_something(argument_one, argument_two);"
+        );
+    }
+
+    #[test]
+    fn test_wrap_preserving_indent_keeps_empty_lines_and_unicode() {
+        let lines = wrap_preserving_indent("日本語のテキスト\n\nend", 4);
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(texts, vec!["日本語の", "テキスト", "", "end"]);
+        assert!(wrap_preserving_indent("text", 0).is_empty());
+    }
+
+    #[test]
+    fn test_mark_matches_keeps_existing_styles() {
+        use ratatui::style::{Color, Modifier, Style};
+        use ratatui::text::{Line, Span};
+
+        let query = crate::query::parse_query("retry OR timeout").unwrap();
+        let keyword = Style::default().fg(Color::Blue);
+        let line = Line::from(vec![
+            Span::raw("on time"),
+            Span::styled("out, retry", keyword),
+        ]);
+
+        let marked = mark_matches(line, &query);
+        let pieces: Vec<(&str, Style)> = marked
+            .spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect();
+        let matched = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+        assert_eq!(
+            pieces,
+            vec![
+                ("on ", Style::default()),
+                ("time", matched),
+                ("out", keyword.patch(matched)),
+                (", ", keyword),
+                ("retry", keyword.patch(matched)),
+            ]
+        );
+
+        let unmatched = Line::from("nothing here");
+        assert_eq!(mark_matches(unmatched.clone(), &query), unmatched);
+    }
 }
@@ -1,11 +1,15 @@
 use super::list_item::ListItem;
+use super::view_layout::render_vertical_scrollbar;
 use crate::interactive_ratatui::constants::*;
+use crate::interactive_ratatui::ui::theme;
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    layout::{Constraint, Direction, Layout, Position, Rect},
+    style::{Modifier, Style},
+    text::Span,
     widgets::{Block, Borders, List, ListItem as TuiListItem, Paragraph},
 };
+use std::collections::{HashMap, HashSet};
 
 pub struct ListViewer<T: ListItem> {
     pub items: Vec<T>,
@@ -15,8 +19,20 @@ pub struct ListViewer<T: ListItem> {
     pub truncation_enabled: bool,
     pub title: String,
     pub empty_message: String,
+    /// Indices into `items` picked for bulk actions, drawn with a marker
+    pub marked: HashSet<usize>,
+    /// Indices into `items` that start a new part of the list, such as the
+    /// context after a compaction, drawn after a separator
+    pub boundaries: HashSet<usize>,
+    /// Indices into `items` that start an alternative where a prompt was
+    /// edited or retried, with labels like `2/3`
+    pub branch_starts: HashMap<usize, String>,
     query: String,
     last_viewport_height: u16,
+    /// Where the items were last drawn, and the filtered position shown on
+    /// each of its rows
+    last_inner_area: Rect,
+    row_positions: Vec<usize>,
 }
 
 impl<T: ListItem> Default for ListViewer<T> {
@@ -29,8 +45,13 @@ impl<T: ListItem> Default for ListViewer<T> {
             truncation_enabled: true,
             title: String::new(),
             empty_message: String::new(),
+            marked: HashSet::new(),
+            boundaries: HashSet::new(),
+            branch_starts: HashMap::new(),
             query: String::new(),
             last_viewport_height: DEFAULT_VIEWPORT_HEIGHT,
+            last_inner_area: Rect::default(),
+            row_positions: Vec::new(),
         }
     }
 }
@@ -58,8 +79,13 @@ impl<T: ListItem> ListViewer<T> {
             truncation_enabled: true,
             title,
             empty_message,
+            marked: HashSet::new(),
+            boundaries: HashSet::new(),
+            branch_starts: HashMap::new(),
             query: String::new(),
             last_viewport_height: DEFAULT_VIEWPORT_HEIGHT,
+            last_inner_area: Rect::default(),
+            row_positions: Vec::new(),
         }
     }
 
@@ -115,6 +141,16 @@ impl<T: ListItem> ListViewer<T> {
         self.last_viewport_height = height;
     }
 
+    /// Position in the filtered list of the item drawn at a screen cell
+    pub fn position_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !self.last_inner_area.contains(Position::new(column, row)) {
+            return None;
+        }
+        self.row_positions
+            .get((row - self.last_inner_area.y) as usize)
+            .copied()
+    }
+
     pub fn get_selected_item(&self) -> Option<&T> {
         self.filtered_indices
             .get(self.selected_index)
@@ -333,6 +369,7 @@ impl<T: ListItem> ListViewer<T> {
     }
 
     pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        self.row_positions.clear();
         if self.items.is_empty() || self.filtered_indices.is_empty() {
             let empty_message = Paragraph::new(self.empty_message.clone())
                 .block(
@@ -340,7 +377,7 @@ impl<T: ListItem> ListViewer<T> {
                         .title(self.title.clone())
                         .borders(Borders::ALL),
                 )
-                .style(Style::default().fg(Color::DarkGray));
+                .style(Style::default().fg(theme::current().text_dim));
             f.render_widget(empty_message, area);
             return;
         }
@@ -350,6 +387,7 @@ impl<T: ListItem> ListViewer<T> {
             .title(self.title.clone())
             .borders(Borders::ALL);
         let inner_area = block.inner(area);
+        self.last_inner_area = inner_area;
         let available_height = inner_area.height;
         self.last_viewport_height = available_height;
         self.adjust_scroll_offset(available_height, inner_area.width);
@@ -367,7 +405,7 @@ impl<T: ListItem> ListViewer<T> {
             .split(Rect::new(0, 0, inner_area.width, 1));
         let available_text_width = row_layout[3].width as usize;
 
-        let items: Vec<TuiListItem> = (start..end)
+        let items: Vec<(usize, TuiListItem)> = (start..end)
             .filter_map(|i| {
                 self.filtered_indices.get(i).and_then(|&item_idx| {
                     self.items.get(item_idx).map(|item| {
@@ -375,24 +413,38 @@ impl<T: ListItem> ListViewer<T> {
 
                         let style = if is_selected {
                             Style::default()
-                                .bg(Color::DarkGray)
+                                .bg(theme::current().selection)
                                 .add_modifier(Modifier::BOLD)
                         } else {
                             Style::default()
                         };
 
-                        if self.truncation_enabled {
-                            TuiListItem::new(item.create_truncated_line(&self.query)).style(style)
+                        let mut lines = if self.truncation_enabled {
+                            vec![item.create_truncated_line(&self.query)]
                         } else {
-                            TuiListItem::new(
-                                item.create_full_lines(available_text_width, &self.query),
-                            )
-                            .style(style)
+                            item.create_full_lines(available_text_width, &self.query)
+                        };
+                        if let Some(first) = lines.first_mut() {
+                            if let Some(label) = self.branch_starts.get(&item_idx) {
+                                first.spans.insert(0, branch_span(label));
+                            }
+                            if self.boundaries.contains(&item_idx) {
+                                first.spans.insert(0, boundary_span());
+                            }
+                            if self.marked.contains(&item_idx) {
+                                first.spans.insert(0, marker_span());
+                            }
                         }
+                        (i, TuiListItem::new(lines).style(style))
                     })
                 })
             })
             .collect();
+        for (i, item) in &items {
+            self.row_positions
+                .extend(std::iter::repeat_n(*i, item.height()));
+        }
+        let items: Vec<TuiListItem> = items.into_iter().map(|(_, item)| item).collect();
 
         let title = format!(
             "{} ({}/{}) - Showing {}-{}",
@@ -408,5 +460,37 @@ impl<T: ListItem> ListViewer<T> {
             .style(Style::default());
 
         f.render_widget(list, area);
+
+        let total = self.filtered_indices.len();
+        if end - start < total {
+            render_vertical_scrollbar(f, area, total, end - start, self.selected_index);
+        }
     }
 }
+
+fn marker_span() -> Span<'static> {
+    Span::styled(
+        "● ",
+        Style::default()
+            .fg(theme::current().accent)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+fn branch_span(label: &str) -> Span<'static> {
+    Span::styled(
+        format!("⎇ {label} "),
+        Style::default()
+            .fg(theme::current().accent)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+fn boundary_span() -> Span<'static> {
+    Span::styled(
+        "── compacted ── ",
+        Style::default()
+            .fg(theme::current().secondary)
+            .add_modifier(Modifier::BOLD),
+    )
+}
@@ -377,4 +377,30 @@ mod tests {
             "Full text mode should have scrolled"
         );
     }
+
+    fn render_viewer(viewer: &mut ListViewer<MockListItem>, width: u16, height: u16) -> String {
+        let backend = ratatui::backend::TestBackend::new(width, height);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|f| viewer.render(f, f.area())).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn test_scrollbar_rendered_when_items_overflow() {
+        let mut viewer = ListViewer::<MockListItem>::new("Test".to_string(), "Empty".to_string());
+        viewer.set_items(create_mock_items(3));
+        let content = render_viewer(&mut viewer, 80, 10);
+        assert!(!content.contains('█'));
+
+        viewer.set_items(create_mock_items(50));
+        let content = render_viewer(&mut viewer, 80, 10);
+        assert!(content.contains('█'));
+        assert!(content.contains("(1/50)"));
+    }
 }
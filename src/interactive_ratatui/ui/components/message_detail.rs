@@ -1,25 +1,44 @@
 use crate::interactive_ratatui::constants::*;
 use crate::interactive_ratatui::ui::components::{
-    Component, is_exit_prompt,
-    view_layout::{Styles, ViewLayout},
+    Component,
+    content_blocks::{ContentBlock, extract_content_blocks, render_content_blocks},
+    is_exit_prompt,
+    list_item::{mark_matches, wrap_highlighted},
+    tool_view::{ToolView, extract_tool_views, render_tool_views},
+    view_layout::{Styles, ViewLayout, render_vertical_scrollbar},
 };
 use crate::interactive_ratatui::ui::events::{CopyContent, Message};
+use crate::interactive_ratatui::ui::theme;
+use crate::links::{extract_links, extract_urls_from_text};
 use crate::query::condition::SearchResult;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph, Wrap},
 };
 
-#[derive(Default)]
 pub struct MessageDetail {
     pub(super) result: Option<SearchResult>,
     pub(super) scroll_offset: usize,
     pub(super) message: Option<String>,
     pub(super) current_uuid: Option<String>,
+    pub(super) tool_views: Vec<ToolView>,
+    pub(super) blocks: Vec<ContentBlock>,
+    /// Index among the collapsible blocks that `z`/Enter folds
+    pub(super) selected_block: usize,
+    /// Scroll to the selected block on the next render, once lines are wrapped
+    pub(super) scroll_to_block: bool,
+    pub(super) urls: Vec<String>,
+    pub(super) syntax_highlight: bool,
+}
+
+impl Default for MessageDetail {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MessageDetail {
@@ -29,6 +48,12 @@ impl MessageDetail {
             scroll_offset: 0,
             message: None,
             current_uuid: None,
+            tool_views: Vec::new(),
+            blocks: Vec::new(),
+            selected_block: 0,
+            scroll_to_block: false,
+            urls: Vec::new(),
+            syntax_highlight: true,
         }
     }
 
@@ -39,6 +64,19 @@ impl MessageDetail {
         if should_reset_scroll {
             self.scroll_offset = 0;
             self.current_uuid = Some(result.uuid.clone());
+            // Parsing the raw JSON is too costly to repeat on every frame
+            self.tool_views = result
+                .raw_json
+                .as_deref()
+                .map(extract_tool_views)
+                .unwrap_or_default();
+            self.blocks = result
+                .raw_json
+                .as_deref()
+                .map(|raw| extract_content_blocks(raw, &result.query))
+                .unwrap_or_default();
+            self.selected_block = 0;
+            self.urls = collect_urls(&result);
         }
 
         self.result = Some(result);
@@ -48,12 +86,49 @@ impl MessageDetail {
         self.result = None;
         self.scroll_offset = 0;
         self.current_uuid = None;
+        self.tool_views.clear();
+        self.blocks.clear();
+        self.selected_block = 0;
+        self.urls.clear();
     }
 
     pub fn set_message(&mut self, message: Option<String>) {
         self.message = message;
     }
 
+    pub fn set_syntax_highlight(&mut self, enabled: bool) {
+        self.syntax_highlight = enabled;
+    }
+
+    /// Whether the message is long enough to offer opening it in an external pager
+    fn is_pager_worthy(&self) -> bool {
+        self.result
+            .as_ref()
+            .is_some_and(|result| result.text.lines().count() > PAGER_THRESHOLD_LINES)
+    }
+
+    fn collapsible_count(&self) -> usize {
+        self.blocks.iter().filter(|b| b.is_collapsible()).count()
+    }
+
+    fn select_block(&mut self, selected: usize) {
+        self.selected_block = selected;
+        self.scroll_to_block = true;
+    }
+
+    /// Collapse or expand the selected block
+    fn toggle_selected_block(&mut self) {
+        if let Some(block) = self
+            .blocks
+            .iter_mut()
+            .filter(|b| b.is_collapsible())
+            .nth(self.selected_block)
+        {
+            block.collapsed = !block.collapsed;
+            self.scroll_to_block = true;
+        }
+    }
+
     fn render_content(&mut self, f: &mut Frame, area: Rect) {
         let Some(result) = &self.result else {
             return;
@@ -64,8 +139,15 @@ impl MessageDetail {
         let non_exit_message = if is_exit { None } else { self.message.clone() };
 
         // Calculate the actual height needed for the shortcuts bar
-        let shortcuts_text = "↑/↓: Scroll | Ctrl+S: View full session | c: Copy message text | C: Copy as JSON | i: Copy session ID | f: Copy file path | p: Copy project path | Alt+←/→: Navigate history | Esc: Back";
-        let shortcuts_paragraph = Paragraph::new(shortcuts_text).wrap(Wrap { trim: true });
+        let block_hint = if self.blocks.is_empty() {
+            ""
+        } else {
+            "Tab: Next block | z: Fold block | "
+        };
+        let shortcuts_text = format!(
+            "↑/↓: Scroll | {block_hint}Ctrl+S: View full session | c: Copy message text | C: Copy as JSON | i: Copy session ID | f: Copy file path | p: Copy project path | u: Copy URLs | b: Bookmark | D: Diff with clipboard | e: Open in $EDITOR | h: Toggle highlighting | Alt+←/→: Navigate history | Esc: Back"
+        );
+        let shortcuts_paragraph = Paragraph::new(shortcuts_text.as_str()).wrap(Wrap { trim: true });
         let shortcuts_height = (shortcuts_paragraph.line_count(area.width) as u16).clamp(1, 3);
 
         // Split the main area into header, message, shortcuts, and optionally status/exit prompt
@@ -104,7 +186,7 @@ impl MessageDetail {
         let header_lines = vec![
             Line::from(vec![
                 Span::styled("Role: ", Styles::label()),
-                Span::raw(&result.role),
+                Span::raw(result.role.as_str()),
             ]),
             Line::from(vec![
                 Span::styled("Time: ", Styles::label()),
@@ -112,11 +194,11 @@ impl MessageDetail {
             ]),
             Line::from(vec![
                 Span::styled("File: ", Styles::label()),
-                Span::raw(&result.file),
+                Span::raw(result.file.as_str()),
             ]),
             Line::from(vec![
                 Span::styled("CWD: ", Styles::label()),
-                Span::raw(&result.cwd),
+                Span::raw(result.cwd.as_str()),
             ]),
             Line::from(vec![
                 Span::styled("Message ID: ", Styles::label()),
@@ -124,7 +206,7 @@ impl MessageDetail {
             ]),
             Line::from(vec![
                 Span::styled("Session: ", Styles::label()),
-                Span::raw(&result.session_id),
+                Span::raw(result.session_id.as_str()),
             ]),
         ];
 
@@ -132,43 +214,33 @@ impl MessageDetail {
             .block(Block::default().borders(Borders::ALL).title("Details"));
         f.render_widget(header, chunks[0]);
 
-        // Process message content for the scrollable area
-        let mut message_lines = Vec::new();
-
         // Calculate visible area for wrapping
         let inner_area = Block::default().borders(Borders::ALL).inner(chunks[1]);
         let visible_height = inner_area.height as usize;
         let available_width = inner_area.width as usize;
 
-        // Wrap message text to fit width
-        for line in result.text.lines() {
-            if line.is_empty() {
-                message_lines.push(Line::from(""));
-            } else {
-                // Wrap long lines
-                let mut remaining = line;
-                while !remaining.is_empty() {
-                    let mut end_idx = remaining.len().min(available_width);
-
-                    // Find safe break point at character boundary
-                    while end_idx > 0 && !remaining.is_char_boundary(end_idx) {
-                        end_idx -= 1;
-                    }
-
-                    // If we're not at the end, try to break at a word boundary
-                    if end_idx < remaining.len()
-                        && end_idx > 0
-                        && let Some(space_pos) = remaining[..end_idx].rfind(' ')
-                        && space_pos > available_width / 2
-                    {
-                        end_idx = space_pos + 1; // Include the space
-                    }
-
-                    message_lines.push(Line::from(&remaining[..end_idx]));
-                    remaining = &remaining[end_idx..];
-                }
+        // Wrap message text to fit width, keeping code indentation intact
+        let mut message_lines: Vec<Line> = if self.blocks.is_empty() {
+            wrap_highlighted(&result.text, available_width, self.syntax_highlight)
+                .into_iter()
+                .map(|line| mark_matches(line, &result.query))
+                .collect()
+        } else {
+            let (lines, header_lines) = render_content_blocks(
+                &self.blocks,
+                self.selected_block,
+                available_width,
+                &result.query,
+                self.syntax_highlight,
+            );
+            if std::mem::take(&mut self.scroll_to_block)
+                && let Some(&line) = header_lines.get(self.selected_block)
+            {
+                self.scroll_offset = line;
             }
-        }
+            lines
+        };
+        message_lines.extend(render_tool_views(&self.tool_views, available_width));
 
         // Calculate the maximum scroll offset
         let max_scroll = message_lines.len().saturating_sub(visible_height);
@@ -187,9 +259,14 @@ impl MessageDetail {
             .collect();
 
         let total_lines = message_lines.len();
+        let pager_hint = if self.is_pager_worthy() {
+            ", o: open in pager"
+        } else {
+            ""
+        };
         let message_widget = Paragraph::new(display_lines)
             .block(Block::default().borders(Borders::ALL).title(format!(
-                "Message (↑/↓ to scroll, line {}-{} of {})",
+                "Message (↑/↓ to scroll, line {}-{} of {}{pager_hint})",
                 if total_lines > 0 {
                     self.scroll_offset + 1
                 } else {
@@ -202,13 +279,22 @@ impl MessageDetail {
                 },
                 total_lines
             )))
-            .wrap(Wrap { trim: true });
+            .wrap(Wrap { trim: false });
         f.render_widget(message_widget, chunks[1]);
 
+        if max_scroll > 0 {
+            render_vertical_scrollbar(
+                f,
+                chunks[1],
+                max_scroll + 1,
+                visible_height,
+                self.scroll_offset,
+            );
+        }
+
         // Render shortcuts bar (similar to Session Viewer style)
-        let shortcuts_text = "↑/↓: Scroll | Ctrl+S: View full session | c: Copy message text | C: Copy as JSON | i: Copy session ID | f: Copy file path | p: Copy project path | Alt+←/→: Navigate history | Esc: Back";
         let shortcuts_bar = Paragraph::new(shortcuts_text)
-            .style(Style::default().fg(Color::DarkGray))
+            .style(Style::default().fg(theme::current().text_dim))
             .alignment(ratatui::layout::Alignment::Center)
             .wrap(Wrap { trim: true });
         f.render_widget(shortcuts_bar, chunks[2]);
@@ -234,7 +320,7 @@ impl MessageDetail {
             let exit_prompt = Paragraph::new("Press Ctrl+C again to exit")
                 .style(
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme::current().secondary)
                         .add_modifier(Modifier::BOLD),
                 )
                 .alignment(ratatui::layout::Alignment::Center);
@@ -243,6 +329,26 @@ impl MessageDetail {
     }
 }
 
+/// URLs fetched, returned by a web search, or mentioned in the message, in order of appearance
+fn collect_urls(result: &SearchResult) -> Vec<String> {
+    let mut urls: Vec<String> = result
+        .raw_json
+        .as_deref()
+        .and_then(|raw| serde_json::from_str(raw).ok())
+        .map(|value| {
+            extract_links(&value)
+                .into_iter()
+                .map(|link| link.url)
+                .collect()
+        })
+        .unwrap_or_default();
+    urls.extend(extract_urls_from_text(&result.text));
+
+    let mut seen = std::collections::HashSet::new();
+    urls.retain(|url| seen.insert(url.clone()));
+    urls
+}
+
 impl Component for MessageDetail {
     fn render(&mut self, f: &mut Frame, area: Rect) {
         let Some(_result) = &self.result else {
@@ -299,6 +405,19 @@ impl Component for MessageDetail {
                 }
                 None
             }
+            KeyCode::Tab if self.collapsible_count() > 0 => {
+                self.select_block((self.selected_block + 1) % self.collapsible_count());
+                None
+            }
+            KeyCode::BackTab if self.collapsible_count() > 0 => {
+                let count = self.collapsible_count();
+                self.select_block((self.selected_block + count - 1) % count);
+                None
+            }
+            KeyCode::Char('z') | KeyCode::Enter => {
+                self.toggle_selected_block();
+                None
+            }
             KeyCode::Char('s') if key.modifiers == KeyModifiers::CONTROL => {
                 Some(Message::EnterSessionViewer) // Ctrl+S
             }
@@ -332,15 +451,40 @@ impl Component for MessageDetail {
                 }
             }
             KeyCode::Char('i') => self.result.as_ref().map(|result| {
-                Message::CopyToClipboard(CopyContent::SessionId(result.session_id.clone()))
+                Message::CopyToClipboard(CopyContent::SessionId(result.session_id.to_string()))
+            }),
+            KeyCode::Char('f') => self.result.as_ref().map(|result| {
+                Message::CopyToClipboard(CopyContent::FilePath(result.file.to_string()))
             }),
-            KeyCode::Char('f') => self
-                .result
-                .as_ref()
-                .map(|result| Message::CopyToClipboard(CopyContent::FilePath(result.file.clone()))),
             KeyCode::Char('p') => self.result.as_ref().map(|result| {
-                Message::CopyToClipboard(CopyContent::ProjectPath(result.cwd.clone()))
+                Message::CopyToClipboard(CopyContent::ProjectPath(result.cwd.to_string()))
             }),
+            KeyCode::Char('u') if self.result.is_some() => {
+                if self.urls.is_empty() {
+                    Some(Message::SetStatus("⚠ No URLs in this message".to_string()))
+                } else {
+                    Some(Message::CopyToClipboard(CopyContent::Urls(
+                        self.urls.join("\n"),
+                    )))
+                }
+            }
+            KeyCode::Char('b') => self
+                .result
+                .as_ref()
+                .map(|result| Message::ToggleBookmark(Box::new(result.clone()))),
+            KeyCode::Char('D') => self
+                .result
+                .as_ref()
+                .map(|result| Message::CompareWithClipboard(Box::new(result.clone()))),
+            KeyCode::Char('h') => Some(Message::ToggleSyntaxHighlight),
+            KeyCode::Char('o') if self.is_pager_worthy() => self
+                .result
+                .as_ref()
+                .map(|result| Message::OpenInPager(result.text.clone())),
+            KeyCode::Char('e') => self
+                .result
+                .as_ref()
+                .map(|result| Message::OpenInEditor(result.file.to_string(), result.line_number)),
             KeyCode::Esc => Some(Message::ExitToSearch),
             _ => None,
         }
@@ -9,14 +9,14 @@ mod tests {
 
     fn create_test_result() -> SearchResult {
         SearchResult {
-            file: "/path/to/test.jsonl".to_string(),
-            cwd: "/path/to/project".to_string(),
+            file: "/path/to/test.jsonl".into(),
+            cwd: "/path/to/project".into(),
             uuid: "12345678-1234-5678-1234-567812345678".to_string(),
-            session_id: "session-123".to_string(),
+            session_id: "session-123".into(),
             timestamp: "2024-01-01T00:00:00Z".to_string(),
-            role: "user".to_string(),
+            role: "user".into(),
             text: "This is a test message".to_string(),
-            message_type: "user".to_string(),
+            message_type: "user".into(),
             query: QueryCondition::Literal {
                 pattern: String::new(),
                 case_sensitive: false,
@@ -24,6 +24,9 @@ mod tests {
             raw_json: Some(
                 r#"{"type":"user","message":{"content":"This is a test message"}}"#.to_string(),
             ),
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         }
     }
 
@@ -35,21 +38,21 @@ mod tests {
 
     fn create_test_result_with_long_file_path() -> SearchResult {
         let mut result = create_test_result();
-        result.file = "/Users/masatomokusaka/.claude/projects/very-long-project-name/session-files/0ff88f7e-99a2-4c72-b7c1-fb95713d1832.jsonl".to_string();
+        result.file = "/Users/masatomokusaka/.claude/projects/very-long-project-name/session-files/0ff88f7e-99a2-4c72-b7c1-fb95713d1832.jsonl".into();
         result
     }
 
     fn create_test_result_with_long_project_path() -> SearchResult {
         let mut result = create_test_result();
-        result.cwd = "/Users/masatomokusaka/src/github/com/organization/very-long-project-name-with-multiple-segments/sub-project/workspace".to_string();
+        result.cwd = "/Users/masatomokusaka/src/github/com/organization/very-long-project-name-with-multiple-segments/sub-project/workspace".into();
         result
     }
 
     fn create_test_result_with_all_long_fields() -> SearchResult {
         let mut result = create_test_result();
-        result.file = "/Users/masatomokusaka/.claude/projects/very-long-project-name/session-files/0ff88f7e-99a2-4c72-b7c1-fb95713d1832.jsonl".to_string();
-        result.cwd = "/Users/masatomokusaka/src/github/com/organization/very-long-project-name-with-multiple-segments/sub-project/workspace".to_string();
-        result.session_id = "extremely-long-session-id-0ff88f7e-99a2-4c72-b7c1-fb95713d1832-with-additional-segments".to_string();
+        result.file = "/Users/masatomokusaka/.claude/projects/very-long-project-name/session-files/0ff88f7e-99a2-4c72-b7c1-fb95713d1832.jsonl".into();
+        result.cwd = "/Users/masatomokusaka/src/github/com/organization/very-long-project-name-with-multiple-segments/sub-project/workspace".into();
+        result.session_id = "extremely-long-session-id-0ff88f7e-99a2-4c72-b7c1-fb95713d1832-with-additional-segments".into();
         result.uuid =
             "12345678-1234-5678-1234-567812345678-extra-long-uuid-with-additional-information"
                 .to_string();
@@ -248,6 +251,15 @@ mod tests {
         assert_eq!(detail.scroll_offset, 5);
     }
 
+    #[test]
+    fn test_toggle_highlight_key() {
+        let mut detail = MessageDetail::new();
+        detail.set_result(create_test_result());
+
+        let msg = detail.handle_key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::empty()));
+        assert!(matches!(msg, Some(Message::ToggleSyntaxHighlight)));
+    }
+
     #[test]
     fn test_copy_shortcuts() {
         let mut detail = MessageDetail::new();
@@ -298,6 +310,15 @@ mod tests {
         // Test exit to search (Esc)
         let msg = detail.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::empty()));
         assert!(matches!(msg, Some(Message::ExitToSearch)));
+
+        // Test diff against the clipboard (D)
+        let msg = detail.handle_key(KeyEvent::new(KeyCode::Char('D'), KeyModifiers::SHIFT));
+        assert_eq!(
+            msg,
+            Some(Message::CompareWithClipboard(
+                Box::new(create_test_result())
+            ))
+        );
     }
 
     #[test]
@@ -671,4 +692,176 @@ mod tests {
         assert!(detail.current_uuid.is_none());
         assert_eq!(detail.scroll_offset, 0);
     }
+
+    #[test]
+    fn test_scrollbar_shown_only_when_message_overflows() {
+        let mut detail = MessageDetail::new();
+        detail.set_result(create_test_result());
+
+        let buffer = render_component(&mut detail, 80, 40);
+        let content = buffer
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(!content.contains('█'));
+
+        let mut result = create_test_result();
+        result.uuid = "long-message".to_string();
+        result.text = (0..100)
+            .map(|i| format!("Line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        detail.set_result(result);
+
+        let buffer = render_component(&mut detail, 80, 40);
+        let content = buffer
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(content.contains('█'));
+    }
+
+    #[test]
+    fn test_open_in_pager_only_for_long_messages() {
+        let mut detail = MessageDetail::new();
+        detail.set_result(create_test_result());
+        let msg = detail.handle_key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::empty()));
+        assert!(msg.is_none());
+
+        let mut result = create_test_result();
+        result.uuid = "long-message".to_string();
+        result.text = (0..500)
+            .map(|i| format!("Line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        detail.set_result(result.clone());
+
+        let buffer = render_component(&mut detail, 100, 40);
+        let content = buffer
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(content.contains("o: open in pager"));
+
+        let msg = detail.handle_key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::empty()));
+        assert_eq!(msg, Some(Message::OpenInPager(result.text)));
+    }
+
+    #[test]
+    fn test_open_in_editor() {
+        let mut detail = MessageDetail::new();
+        let mut result = create_test_result();
+        result.line_number = Some(42);
+        detail.set_result(result);
+
+        let msg = detail.handle_key(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::empty()));
+        assert_eq!(
+            msg,
+            Some(Message::OpenInEditor(
+                "/path/to/test.jsonl".to_string(),
+                Some(42)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_copy_urls() {
+        let mut detail = MessageDetail::new();
+        detail.set_result(create_test_result());
+
+        let msg = detail.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::empty()));
+        assert_eq!(
+            msg,
+            Some(Message::SetStatus("⚠ No URLs in this message".to_string()))
+        );
+
+        let mut result = create_test_result();
+        result.uuid = "with-links".to_string();
+        result.text = "Read https://docs.rs/ratatui and https://example.com/a.".to_string();
+        result.raw_json = Some(
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"t","name":"WebFetch","input":{"url":"https://example.com/a"}}]}}"#
+                .to_string(),
+        );
+        detail.set_result(result);
+
+        let msg = detail.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::empty()));
+        assert_eq!(
+            msg,
+            Some(Message::CopyToClipboard(CopyContent::Urls(
+                "https://example.com/a\nhttps://docs.rs/ratatui".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_collapsible_blocks() {
+        fn content(detail: &mut MessageDetail) -> String {
+            render_component(detail, 120, 40)
+                .content
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect()
+        }
+
+        let mut detail = MessageDetail::new();
+        let mut result = create_test_result();
+        result.role = "assistant".into();
+        result.text = "Roll back before the deploy\nBash: ./deploy.sh\nDeploying now".to_string();
+        result.raw_json = Some(
+            r#"{"type":"assistant","message":{"id":"msg1","type":"message","role":"assistant","model":"claude","content":[{"type":"thinking","thinking":"Roll back before the deploy","signature":"sig"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"./deploy.sh"}},{"type":"text","text":"Deploying now"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":10,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":5}},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#
+                .to_string(),
+        );
+        detail.set_result(result);
+
+        // Thinking and tool calls start collapsed, the answer is shown
+        let text = content(&mut detail);
+        assert!(text.contains("▶ Thinking (1 line)"));
+        assert!(text.contains("▶ Tool call: Bash (3 lines)"));
+        assert!(text.contains("Deploying now"));
+        assert!(!text.contains("Roll back"));
+
+        assert_eq!(
+            detail.handle_key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::empty())),
+            None
+        );
+        let text = content(&mut detail);
+        assert!(text.contains("▼ Thinking (1 line)"));
+        assert!(text.contains("Roll back before the deploy"));
+
+        // Tab moves on to the tool call, wrapping around at the end
+        detail.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::empty()));
+        assert_eq!(detail.selected_block, 1);
+        detail.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::empty()));
+        assert!(content(&mut detail).contains("\"command\": \"./deploy.sh\""));
+        detail.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::empty()));
+        assert_eq!(detail.selected_block, 0);
+        detail.handle_key(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT));
+        assert_eq!(detail.selected_block, 1);
+    }
+
+    #[test]
+    fn test_blocks_with_matches_start_expanded() {
+        let mut detail = MessageDetail::new();
+        let mut result = create_test_result();
+        result.query = QueryCondition::Literal {
+            pattern: "roll back".to_string(),
+            case_sensitive: false,
+        };
+        result.raw_json = Some(
+            r#"{"type":"assistant","message":{"id":"msg1","type":"message","role":"assistant","model":"claude","content":[{"type":"thinking","thinking":"Roll back before the deploy","signature":"sig"},{"type":"text","text":"Deploying now"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":10,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":5}},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#
+                .to_string(),
+        );
+        detail.set_result(result);
+        assert_eq!(detail.blocks.len(), 2);
+        assert!(!detail.blocks[0].collapsed);
+
+        // Plain-text messages have no blocks to fold
+        let mut plain = create_test_result();
+        plain.uuid = "plain".to_string();
+        detail.set_result(plain);
+        assert!(detail.blocks.is_empty());
+    }
 }
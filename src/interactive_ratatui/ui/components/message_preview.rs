@@ -1,28 +1,64 @@
+use crate::interactive_ratatui::constants::MOUSE_SCROLL_LINES;
 use crate::interactive_ratatui::ui::components::Component;
+use crate::interactive_ratatui::ui::components::list_item::wrap_highlighted;
+use crate::interactive_ratatui::ui::components::tool_view::{
+    ToolView, extract_tool_views, render_tool_views,
+};
 use crate::interactive_ratatui::ui::components::view_layout::Styles;
 use crate::interactive_ratatui::ui::events::Message;
 use crate::query::condition::SearchResult;
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent, MouseEventKind};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Position, Rect},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph, Wrap},
 };
 
 pub struct MessagePreview {
     result: Option<SearchResult>,
+    tool_views: Vec<ToolView>,
+    syntax_highlight: bool,
+    /// Lines of the message scrolled past with the mouse wheel
+    scroll_offset: usize,
+    /// Where the preview was last drawn, for the mouse
+    area: Rect,
 }
 
 impl MessagePreview {
     pub fn new() -> Self {
-        Self { result: None }
+        Self {
+            result: None,
+            tool_views: Vec::new(),
+            syntax_highlight: true,
+            scroll_offset: 0,
+            area: Rect::default(),
+        }
+    }
+
+    pub fn set_syntax_highlight(&mut self, enabled: bool) {
+        self.syntax_highlight = enabled;
     }
 
     pub fn set_result(&mut self, result: Option<SearchResult>) {
+        // Only re-parse tool calls when the selection actually changes
+        let current_uuid = self.result.as_ref().map(|r| &r.uuid);
+        if current_uuid != result.as_ref().map(|r| &r.uuid) {
+            self.tool_views = result
+                .as_ref()
+                .and_then(|r| r.raw_json.as_deref())
+                .map(extract_tool_views)
+                .unwrap_or_default();
+            self.scroll_offset = 0;
+        }
         self.result = result;
     }
 
+    /// Whether a screen cell is where the preview was last drawn
+    pub fn is_at(&self, column: u16, row: u16) -> bool {
+        self.area.contains(Position::new(column, row))
+    }
+
     fn format_timestamp(timestamp: &str) -> String {
         if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp) {
             dt.format("%Y-%m-%d %H:%M:%S").to_string()
@@ -40,6 +76,7 @@ impl Default for MessagePreview {
 
 impl Component for MessagePreview {
     fn render(&mut self, f: &mut Frame, area: Rect) {
+        self.area = area;
         let block = Block::default().borders(Borders::ALL).title("Preview");
 
         let inner = block.inner(area);
@@ -59,7 +96,7 @@ impl Component for MessagePreview {
             let header_lines = vec![
                 Line::from(vec![
                     Span::styled("Role: ", Styles::label()),
-                    Span::raw(&result.role),
+                    Span::raw(result.role.as_str()),
                 ]),
                 Line::from(vec![
                     Span::styled("Time: ", Styles::label()),
@@ -71,7 +108,7 @@ impl Component for MessagePreview {
                 ]),
                 Line::from(vec![
                     Span::styled("Session ID: ", Styles::label()),
-                    Span::raw(&result.session_id),
+                    Span::raw(result.session_id.as_str()),
                 ]),
                 Line::from("──────────────────────────────────"),
             ];
@@ -83,50 +120,23 @@ impl Component for MessagePreview {
             let content_height = chunks[1].height as usize;
             let content_width = chunks[1].width as usize;
 
-            // Process message text with word wrapping
-            let mut display_lines = Vec::new();
-            let mut total_lines = 0;
-
-            for line in result.text.lines() {
-                if total_lines >= content_height.saturating_sub(1) {
-                    // Leave room for "..." indicator
-                    break;
-                }
-
-                if line.is_empty() {
-                    display_lines.push(Line::from(""));
-                    total_lines += 1;
-                } else {
-                    // Word wrap long lines
-                    let mut remaining = line;
-                    while !remaining.is_empty() && total_lines < content_height.saturating_sub(1) {
-                        let mut end_idx = remaining.len().min(content_width);
-
-                        // Find safe break point at character boundary
-                        while end_idx > 0 && !remaining.is_char_boundary(end_idx) {
-                            end_idx -= 1;
-                        }
-
-                        // Try to break at word boundary
-                        if end_idx < remaining.len()
-                            && end_idx > 0
-                            && let Some(space_pos) = remaining[..end_idx].rfind(' ')
-                            && space_pos > content_width / 2
-                        {
-                            end_idx = space_pos + 1;
-                        }
-
-                        display_lines.push(Line::from(&remaining[..end_idx]));
-                        remaining = &remaining[end_idx..];
-                        total_lines += 1;
-                    }
-                }
-            }
+            // Wrap message text, keeping code indentation intact.
+            // Leave room for the "..." indicator.
+            let mut wrapped = wrap_highlighted(&result.text, content_width, self.syntax_highlight);
+            wrapped.extend(render_tool_views(&self.tool_views, content_width));
+            let max_lines = content_height.saturating_sub(1);
+            self.scroll_offset = self
+                .scroll_offset
+                .min(wrapped.len().saturating_sub(max_lines));
+            let is_truncated = wrapped.len() > self.scroll_offset + max_lines;
+            let mut display_lines: Vec<Line> = wrapped
+                .into_iter()
+                .skip(self.scroll_offset)
+                .take(max_lines)
+                .collect();
 
             // Add truncation indicator if content was cut off
-            if total_lines >= content_height.saturating_sub(1)
-                || result.text.lines().count() > display_lines.len()
-            {
+            if is_truncated {
                 display_lines.push(Line::from(vec![
                     Span::styled("... ", Styles::dimmed()),
                     Span::styled(
@@ -136,7 +146,7 @@ impl Component for MessagePreview {
                 ]));
             }
 
-            let content = Paragraph::new(display_lines).wrap(Wrap { trim: true });
+            let content = Paragraph::new(display_lines).wrap(Wrap { trim: false });
             f.render_widget(content, chunks[1]);
         } else {
             // No result selected
@@ -151,4 +161,20 @@ impl Component for MessagePreview {
         // Preview is read-only, no key handling needed
         None
     }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Option<Message> {
+        if self.is_at(mouse.column, mouse.row) {
+            // Clamped to the message when rendering
+            match mouse.kind {
+                MouseEventKind::ScrollUp => {
+                    self.scroll_offset = self.scroll_offset.saturating_sub(MOUSE_SCROLL_LINES);
+                }
+                MouseEventKind::ScrollDown => {
+                    self.scroll_offset = self.scroll_offset.saturating_add(MOUSE_SCROLL_LINES);
+                }
+                _ => {}
+            }
+        }
+        None
+    }
 }
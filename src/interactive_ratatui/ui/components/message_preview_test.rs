@@ -8,19 +8,22 @@ mod tests {
 
     fn create_test_result() -> SearchResult {
         SearchResult {
-            file: "test.jsonl".to_string(),
+            file: "test.jsonl".into(),
             uuid: "12345678-1234-5678-1234-567812345678".to_string(),
             timestamp: "2024-01-02T15:30:45Z".to_string(),
-            session_id: "87654321-4321-8765-4321-876543210987".to_string(),
-            role: "user".to_string(),
+            session_id: "87654321-4321-8765-4321-876543210987".into(),
+            role: "user".into(),
             text: "This is a test message".to_string(),
-            message_type: "message".to_string(),
+            message_type: "message".into(),
             query: QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: "/test/path".to_string(),
+            cwd: "/test/path".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         }
     }
 
@@ -201,4 +204,63 @@ mod tests {
             "Expected to find unicode content, but got:\n{content}"
         );
     }
+
+    #[test]
+    fn test_mouse_wheel_scrolls() {
+        use crossterm::event::{KeyModifiers, MouseEvent, MouseEventKind};
+
+        let mut preview = MessagePreview::new();
+        let mut result = create_test_result();
+        result.text = (1..=30)
+            .map(|n| format!("line {n}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        preview.set_result(Some(result.clone()));
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 16)).unwrap();
+        let mut render = |preview: &mut MessagePreview| {
+            terminal.draw(|f| preview.render(f, f.area())).unwrap();
+            buffer_to_string(terminal.backend().buffer())
+        };
+        let shows = |content: &str, line: &str| {
+            content
+                .lines()
+                .any(|row| row.trim_matches(|c| c == '│' || c == ' ') == line)
+        };
+        let wheel = |kind| MouseEvent {
+            kind,
+            column: 10,
+            row: 8,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        let content = render(&mut preview);
+        assert!(shows(&content, "line 1"));
+
+        assert_eq!(
+            preview.handle_mouse(wheel(MouseEventKind::ScrollDown)),
+            None
+        );
+        let content = render(&mut preview);
+        assert!(!shows(&content, "line 3"));
+        assert!(shows(&content, "line 4"));
+
+        // Scrolling stops at the end of the message
+        for _ in 0..20 {
+            preview.handle_mouse(wheel(MouseEventKind::ScrollDown));
+        }
+        let content = render(&mut preview);
+        assert!(shows(&content, "line 30"));
+        assert!(!content.contains("Enter for full view"));
+
+        preview.handle_mouse(wheel(MouseEventKind::ScrollUp));
+        let content = render(&mut preview);
+        assert!(!shows(&content, "line 30"));
+
+        // Another message starts at the top
+        result.uuid = "other".to_string();
+        preview.set_result(Some(result));
+        let content = render(&mut preview);
+        assert!(shows(&content, "line 1"));
+    }
 }
@@ -1,17 +1,29 @@
+pub mod content_blocks;
+pub mod context_chart;
+pub mod diff_viewer;
 pub mod help_dialog;
 pub mod list_item;
 pub mod list_viewer;
 pub mod message_detail;
 pub mod message_preview;
+pub mod project_list;
 pub mod result_list;
+pub mod result_timeline;
+pub mod saved_search_picker;
 pub mod search_bar;
 pub mod session_list;
 pub mod session_preview;
 pub mod session_viewer;
 pub mod tab_bar;
 pub mod text_input;
+pub mod theme_picker;
+pub mod tool_view;
 pub mod view_layout;
 
+#[cfg(test)]
+mod context_chart_test;
+#[cfg(test)]
+mod diff_viewer_test;
 #[cfg(test)]
 mod list_item_test;
 #[cfg(test)]
@@ -21,8 +33,14 @@ mod message_detail_test;
 #[cfg(test)]
 mod message_preview_test;
 #[cfg(test)]
+mod project_list_test;
+#[cfg(test)]
 mod result_list_test;
 #[cfg(test)]
+mod result_timeline_test;
+#[cfg(test)]
+mod saved_search_picker_test;
+#[cfg(test)]
 mod search_bar_test;
 #[cfg(test)]
 mod session_list_test;
@@ -31,15 +49,34 @@ mod session_preview_test;
 #[cfg(test)]
 mod text_input_test;
 #[cfg(test)]
+mod theme_picker_test;
+#[cfg(test)]
+mod tool_view_test;
+#[cfg(test)]
 mod view_layout_test;
 
 use crate::interactive_ratatui::ui::events::Message;
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
 use ratatui::{Frame, layout::Rect};
 
 pub trait Component {
     fn render(&mut self, f: &mut Frame, area: Rect);
     fn handle_key(&mut self, key: KeyEvent) -> Option<Message>;
+
+    /// Handle a mouse event at a position on the screen, from where the
+    /// component was last rendered
+    fn handle_mouse(&mut self, _mouse: MouseEvent) -> Option<Message> {
+        None
+    }
+}
+
+/// The arrow key a turn of the mouse wheel stands for
+pub fn wheel_key(kind: MouseEventKind) -> Option<KeyEvent> {
+    match kind {
+        MouseEventKind::ScrollUp => Some(KeyEvent::from(KeyCode::Up)),
+        MouseEventKind::ScrollDown => Some(KeyEvent::from(KeyCode::Down)),
+        _ => None,
+    }
 }
 
 /// Check if a message is the exit prompt
@@ -0,0 +1,219 @@
+use crate::interactive_ratatui::ui::app_state::ProjectInfo;
+use crate::interactive_ratatui::ui::components::Component;
+use crate::interactive_ratatui::ui::components::view_layout::render_vertical_scrollbar;
+use crate::interactive_ratatui::ui::events::Message;
+use crate::interactive_ratatui::ui::theme;
+use chrono::{DateTime, Local};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+};
+
+const STATUS_TEXT: &str = "Shift+Tab: Switch tabs | ↑/↓: Navigate | Ctrl+U/D: Half page | Enter: Search this project | ?: Help";
+
+/// The Projects tab: every project directory, with the first row standing for
+/// all of them. Picking one limits the Search tab to its sessions.
+#[derive(Default)]
+pub struct ProjectList {
+    projects: Vec<ProjectInfo>,
+    /// Directory of the project searches are currently limited to
+    scope: Option<String>,
+    /// Row under the cursor; 0 is "All projects"
+    selected_index: usize,
+    scroll_offset: usize,
+    visible_height: usize,
+    is_loading: bool,
+}
+
+impl ProjectList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_projects(&mut self, projects: Vec<ProjectInfo>) {
+        self.projects = projects;
+        self.selected_index = self.selected_index.min(self.projects.len());
+    }
+
+    pub fn set_scope(&mut self, scope: Option<String>) {
+        self.scope = scope;
+    }
+
+    pub fn set_is_loading(&mut self, is_loading: bool) {
+        self.is_loading = is_loading;
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    /// `None` while "All projects" is selected
+    pub fn selected_project(&self) -> Option<&ProjectInfo> {
+        self.selected_index
+            .checked_sub(1)
+            .and_then(|index| self.projects.get(index))
+    }
+
+    fn row_count(&self) -> usize {
+        self.projects.len() + 1
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let last = self.row_count() - 1;
+        self.selected_index = self.selected_index.saturating_add_signed(delta).min(last);
+    }
+}
+
+/// One row: a marker on the project searches are limited to, when it was last
+/// active, its name and how many sessions it has
+fn project_line(
+    scoped: bool,
+    activity: Option<String>,
+    name: &str,
+    session_count: usize,
+) -> Line<'_> {
+    let mut spans = vec![Span::styled(
+        if scoped { "● " } else { "  " },
+        Style::default().fg(theme::current().success),
+    )];
+    if let Some(activity) = activity {
+        spans.push(Span::styled(
+            format!("{activity} "),
+            Style::default().fg(theme::current().secondary),
+        ));
+    }
+    spans.push(Span::styled(
+        name,
+        Style::default().fg(theme::current().primary),
+    ));
+    spans.push(Span::styled(
+        format!("  ({session_count} sessions)"),
+        Style::default().fg(theme::current().text_dim),
+    ));
+    Line::from(spans)
+}
+
+fn format_activity(project: &ProjectInfo) -> String {
+    project
+        .last_activity
+        .map(|time| {
+            DateTime::<Local>::from(time)
+                .format("%m/%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "--/-- --:--".to_string())
+}
+
+impl Component for ProjectList {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let status_paragraph = Paragraph::new(STATUS_TEXT).wrap(Wrap { trim: true });
+        let status_height = (status_paragraph.line_count(area.width) as u16).clamp(1, 3);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),                // Project list
+                Constraint::Length(status_height), // Status bar
+            ])
+            .split(area);
+
+        let block = Block::default().borders(Borders::ALL).title(format!(
+            "Projects ({}/{})",
+            self.selected_index,
+            self.projects.len()
+        ));
+
+        if self.is_loading {
+            let loading = List::new(vec![ListItem::new("Loading...")]).block(block);
+            f.render_widget(loading, chunks[0]);
+        } else {
+            let total_sessions = self.projects.iter().map(|p| p.session_count).sum();
+            let mut lines = vec![project_line(
+                self.scope.is_none(),
+                None,
+                "All projects",
+                total_sessions,
+            )];
+            lines.extend(self.projects.iter().map(|project| {
+                project_line(
+                    self.scope.as_deref() == Some(project.dir.as_str()),
+                    Some(format_activity(project)),
+                    &project.name,
+                    project.session_count,
+                )
+            }));
+
+            self.visible_height = chunks[0].height.saturating_sub(2) as usize; // -2 for borders
+
+            // Keep the selected row visible
+            if self.selected_index < self.scroll_offset {
+                self.scroll_offset = self.selected_index;
+            } else if self.selected_index >= self.scroll_offset + self.visible_height {
+                self.scroll_offset = self.selected_index + 1 - self.visible_height;
+            }
+
+            let items: Vec<ListItem> = lines
+                .into_iter()
+                .enumerate()
+                .skip(self.scroll_offset)
+                .take(self.visible_height)
+                .map(|(i, line)| {
+                    let style = if i == self.selected_index {
+                        Style::default()
+                            .bg(theme::current().selection)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    };
+                    ListItem::new(line).style(style)
+                })
+                .collect();
+
+            f.render_widget(List::new(items).block(block), chunks[0]);
+
+            let total = self.row_count();
+            if total > self.visible_height {
+                render_vertical_scrollbar(
+                    f,
+                    chunks[0],
+                    total,
+                    self.visible_height,
+                    self.selected_index,
+                );
+            }
+        }
+
+        let status_bar = Paragraph::new(STATUS_TEXT)
+            .style(Style::default().fg(theme::current().text_dim))
+            .alignment(ratatui::layout::Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(status_bar, chunks[1]);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Message> {
+        let half_page = (self.visible_height / 2).max(1) as isize;
+        match key.code {
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-(self.visible_height.max(1) as isize)),
+            KeyCode::PageDown => self.move_selection(self.visible_height.max(1) as isize),
+            KeyCode::Char('u') if key.modifiers == KeyModifiers::CONTROL => {
+                self.move_selection(-half_page)
+            }
+            KeyCode::Char('d') if key.modifiers == KeyModifiers::CONTROL => {
+                self.move_selection(half_page)
+            }
+            KeyCode::Home => self.selected_index = 0,
+            KeyCode::End => self.selected_index = self.row_count() - 1,
+            KeyCode::Enter => {
+                return Some(Message::ScopeToProject(self.selected_project().cloned()));
+            }
+            _ => {}
+        }
+        None
+    }
+}
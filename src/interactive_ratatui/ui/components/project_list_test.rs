@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use super::super::project_list::ProjectList;
+    use crate::interactive_ratatui::ui::app_state::ProjectInfo;
+    use crate::interactive_ratatui::ui::components::Component;
+    use crate::interactive_ratatui::ui::events::Message;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{Terminal, backend::TestBackend, buffer::Buffer};
+
+    fn project(name: &str, session_count: usize) -> ProjectInfo {
+        ProjectInfo {
+            dir: format!("/home/me/.claude/projects/-{name}"),
+            name: format!("/{name}"),
+            session_count,
+            last_activity: None,
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::empty())
+    }
+
+    fn render(list: &mut ProjectList) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal.draw(|f| list.render(f, f.area())).unwrap();
+        buffer_to_string(terminal.backend().buffer())
+    }
+
+    #[test]
+    fn test_render_projects() {
+        let mut list = ProjectList::new();
+        list.set_projects(vec![project("api", 3), project("web", 2)]);
+        list.set_scope(Some("/home/me/.claude/projects/-web".to_string()));
+
+        let content = render(&mut list);
+        assert!(content.contains("All projects  (5 sessions)"));
+        assert!(content.contains("/api  (3 sessions)"));
+        // The project searches are limited to is marked
+        let web = content.lines().find(|line| line.contains("/web")).unwrap();
+        assert!(web.contains('●'));
+
+        list.set_is_loading(true);
+        assert!(render(&mut list).contains("Loading..."));
+    }
+
+    #[test]
+    fn test_select_project() {
+        let mut list = ProjectList::new();
+        list.set_projects(vec![project("api", 3), project("web", 2)]);
+
+        // The first row searches every project
+        assert_eq!(
+            list.handle_key(key(KeyCode::Enter)),
+            Some(Message::ScopeToProject(None))
+        );
+
+        assert_eq!(list.handle_key(key(KeyCode::Down)), None);
+        assert_eq!(
+            list.handle_key(key(KeyCode::Enter)),
+            Some(Message::ScopeToProject(Some(project("api", 3))))
+        );
+
+        // Selection stops at either end
+        list.handle_key(key(KeyCode::Down));
+        list.handle_key(key(KeyCode::Down));
+        assert_eq!(list.selected_index(), 2);
+        list.handle_key(key(KeyCode::Home));
+        list.handle_key(key(KeyCode::Up));
+        assert_eq!(list.selected_index(), 0);
+
+        // A shorter list keeps the selection in range
+        list.handle_key(key(KeyCode::End));
+        list.set_projects(vec![project("api", 3)]);
+        assert_eq!(list.selected_project(), Some(&project("api", 3)));
+    }
+
+    fn buffer_to_string(buffer: &Buffer) -> String {
+        let mut output = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                let cell = buffer.cell((x, y)).unwrap();
+                output.push_str(cell.symbol());
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
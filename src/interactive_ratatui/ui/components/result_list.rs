@@ -1,36 +1,61 @@
 use crate::interactive_ratatui::constants::*;
+use crate::interactive_ratatui::domain::models::SearchProgress;
 use crate::interactive_ratatui::ui::components::{
-    Component, list_viewer::ListViewer, view_layout::Styles,
+    Component, list_viewer::ListViewer, view_layout::Styles, wheel_key,
 };
 use crate::interactive_ratatui::ui::events::Message;
+use crate::interactive_ratatui::ui::theme;
 use crate::query::condition::SearchResult;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Position, Rect},
+    style::Style,
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph, Wrap},
 };
+use std::collections::{HashMap, HashSet};
+
+/// Key hints shown under the search results
+const SEARCH_STATUS_TEXT: &str = "Shift+Tab: Switch tabs | Tab: Filter | ↑/↓ or Ctrl+P/N: Navigate | Enter: View details | Ctrl+S: View full session | Ctrl+T: Toggle preview | Ctrl+X/Alt+A: Select | Esc: Exit | ?: Help";
+
+/// Key hints shown under the search results while some are selected
+const SELECTION_STATUS_TEXT: &str = "Ctrl+X: Toggle selection | Alt+A: Select all/none | Ctrl+Y: Copy as JSONL | Alt+S: Export to file | Alt+D: View combined | ↑/↓: Navigate | ?: Help";
 
 #[derive(Default)]
 pub struct ResultList {
     list_viewer: ListViewer<SearchResult>,
+    heading: String,
     preview_enabled: bool,
     show_status_bar: bool,
+    status_text: &'static str,
     has_more_results: bool,
     loading_more: bool,
     total_loaded: usize,
+    search_progress: Option<SearchProgress>,
+    selection_count: usize,
+    dismissed_count: usize,
+    stale: bool,
+    /// Where the list was last drawn, for the mouse
+    area: Rect,
 }
 
 impl ResultList {
     pub fn new() -> Self {
         Self {
             list_viewer: ListViewer::new("Results".to_string(), "No results found".to_string()),
+            heading: "Search Results".to_string(),
             preview_enabled: false,
             show_status_bar: true,
+            status_text: SEARCH_STATUS_TEXT,
             has_more_results: false,
             loading_more: false,
             total_loaded: 0,
+            search_progress: None,
+            selection_count: 0,
+            dismissed_count: 0,
+            stale: false,
+            area: Rect::default(),
         }
     }
 
@@ -39,6 +64,18 @@ impl ResultList {
         self
     }
 
+    /// Heading above the list, and the text shown when it is empty
+    pub fn with_heading(mut self, heading: &str, empty_message: &str) -> Self {
+        self.heading = heading.to_string();
+        self.list_viewer.empty_message = empty_message.to_string();
+        self
+    }
+
+    pub fn with_status_text(mut self, text: &'static str) -> Self {
+        self.status_text = text;
+        self
+    }
+
     pub fn set_results(&mut self, results: Vec<SearchResult>) {
         self.list_viewer.set_items(results);
     }
@@ -67,6 +104,39 @@ impl ResultList {
         self.total_loaded = total;
     }
 
+    /// Progress of a search whose results are still streaming in
+    pub fn set_search_progress(&mut self, progress: Option<SearchProgress>) {
+        self.search_progress = progress;
+    }
+
+    /// Results picked for bulk actions, as indices into the results
+    pub fn set_selection(&mut self, indices: HashSet<usize>, count: usize) {
+        self.list_viewer.marked = indices;
+        self.selection_count = count;
+    }
+
+    /// Results that start the context after a compaction, as indices into
+    /// the results
+    pub fn set_boundaries(&mut self, indices: HashSet<usize>) {
+        self.list_viewer.boundaries = indices;
+    }
+
+    /// Results that start an alternative of an edited or retried prompt, as
+    /// indices into the results with labels like `2/3`
+    pub fn set_branch_starts(&mut self, starts: HashMap<usize, String>) {
+        self.list_viewer.branch_starts = starts;
+    }
+
+    /// Results hidden from the current query with Alt+X
+    pub fn set_dismissed(&mut self, count: usize) {
+        self.dismissed_count = count;
+    }
+
+    /// Whether session files changed after the results were loaded
+    pub fn set_stale(&mut self, stale: bool) {
+        self.stale = stale;
+    }
+
     pub fn set_truncation_enabled(&mut self, enabled: bool) {
         self.list_viewer.set_truncation_enabled(enabled);
     }
@@ -93,10 +163,25 @@ impl ResultList {
     }
 }
 
+/// Fixed-width bar showing the share of files searched
+fn progress_bar(progress: SearchProgress) -> String {
+    const WIDTH: usize = 10;
+    let filled = (progress.files_searched * WIDTH)
+        .checked_div(progress.files_total)
+        .unwrap_or(0)
+        .min(WIDTH);
+    format!("[{}{}]", "█".repeat(filled), "░".repeat(WIDTH - filled))
+}
+
 impl Component for ResultList {
     fn render(&mut self, f: &mut Frame, area: Rect) {
+        self.area = area;
+        let status_text = if self.selection_count > 0 && self.status_text == SEARCH_STATUS_TEXT {
+            SELECTION_STATUS_TEXT
+        } else {
+            self.status_text
+        };
         // Calculate the actual height needed for the status bar
-        let status_text = "Shift+Tab: Switch tabs | Tab: Filter | ↑/↓ or Ctrl+P/N: Navigate | Enter: View details | Ctrl+S: View full session | Ctrl+T: Toggle preview | Esc: Exit | ?: Help";
         let status_paragraph = Paragraph::new(status_text).wrap(Wrap { trim: true });
         let status_height = if self.show_status_bar {
             (status_paragraph.line_count(area.width) as u16).clamp(1, 3)
@@ -124,23 +209,45 @@ impl Component for ResultList {
             .split(area);
 
         // Render title with pagination info
-        let title_text = if self.loading_more {
+        let mut title_text = if let Some(progress) = self.search_progress {
             format!(
-                "Search Results - Loading more... (loaded: {})",
-                self.total_loaded
+                "{} - {} so far {} {}/{} files",
+                self.heading,
+                self.list_viewer.items_count(),
+                progress_bar(progress),
+                progress.files_searched,
+                progress.files_total
+            )
+        } else if self.loading_more {
+            format!(
+                "{} - Loading more... (loaded: {})",
+                self.heading, self.total_loaded
             )
         } else if self.has_more_results {
             format!(
-                "Search Results - {} loaded (more available, scroll down to load)",
-                self.total_loaded
+                "{} - {} loaded (more available, scroll down to load)",
+                self.heading, self.total_loaded
             )
         } else if self.total_loaded > 0 {
-            format!("Search Results - {} total", self.total_loaded)
+            format!("{} - {} total", self.heading, self.total_loaded)
         } else {
-            "Search Results".to_string()
+            self.heading.clone()
         };
+        if self.selection_count > 0 {
+            title_text.push_str(&format!(" | {} selected", self.selection_count));
+        }
+        if self.dismissed_count > 0 {
+            title_text.push_str(&format!(" | {} dismissed", self.dismissed_count));
+        }
 
-        let title_lines = vec![Line::from(vec![Span::styled(title_text, Styles::title())])];
+        let mut title_spans = vec![Span::styled(title_text, Styles::title())];
+        if self.stale {
+            title_spans.push(Span::styled(
+                "  Corpus updated, press F5 to refresh",
+                Style::default().fg(theme::current().warning),
+            ));
+        }
+        let title_lines = vec![Line::from(title_spans)];
         let title = Paragraph::new(title_lines).block(Block::default().borders(Borders::BOTTOM));
         f.render_widget(title, chunks[0]);
 
@@ -149,7 +256,6 @@ impl Component for ResultList {
 
         // Render status bar only if enabled
         if self.show_status_bar {
-            let status_text = "Shift+Tab: Switch tabs | Tab: Filter | ↑/↓ or Ctrl+P/N: Navigate | Enter: View details | Ctrl+S: View full session | Ctrl+T: Toggle preview | Esc: Exit | ?: Help";
             let status_bar = Paragraph::new(status_text)
                 .style(Styles::dimmed())
                 .alignment(ratatui::layout::Alignment::Center)
@@ -300,4 +406,23 @@ impl Component for ResultList {
             _ => None,
         }
     }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Option<Message> {
+        if !self.area.contains(Position::new(mouse.column, mouse.row)) {
+            return None;
+        }
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let position = self.list_viewer.position_at(mouse.column, mouse.row)?;
+                if position == self.list_viewer.selected_index {
+                    // Clicking the selected result again opens it
+                    self.handle_key(KeyEvent::from(KeyCode::Enter))
+                } else {
+                    self.list_viewer.set_filtered_position(position);
+                    Some(Message::SelectResult(self.list_viewer.selected_index()))
+                }
+            }
+            kind => self.handle_key(wheel_key(kind)?),
+        }
+    }
 }
@@ -17,19 +17,22 @@ mod tests {
 
     fn create_test_result(role: &str, text: &str) -> SearchResult {
         SearchResult {
-            file: "test.jsonl".to_string(),
+            file: "test.jsonl".into(),
             uuid: "test-uuid".to_string(),
             timestamp: "2024-01-01T12:00:00Z".to_string(),
-            session_id: "test-session".to_string(),
-            role: role.to_string(),
+            session_id: "test-session".into(),
+            role: role.into(),
             text: text.to_string(),
-            message_type: role.to_string(),
+            message_type: role.into(),
             query: QueryCondition::Literal {
                 pattern: "test".to_string(),
                 case_sensitive: false,
             },
-            cwd: "/test".to_string(),
+            cwd: "/test".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         }
     }
 
@@ -349,6 +352,40 @@ mod tests {
         assert!(content.contains("↑/↓ or Ctrl+P/N: Navigate"));
     }
 
+    #[test]
+    fn test_repo_column() {
+        use ratatui::Terminal;
+        use ratatui::backend::TestBackend;
+
+        let dir = tempfile::tempdir().unwrap();
+        let checkout = dir.path().join("myrepo");
+        std::fs::create_dir_all(checkout.join(".git")).unwrap();
+        std::fs::create_dir_all(checkout.join("src")).unwrap();
+
+        let mut in_repo = create_test_result("user", "Inside the repository");
+        in_repo.cwd = checkout.join("src").display().to_string().into();
+        let mut list = ResultList::new();
+        list.update_results(
+            vec![in_repo, create_test_result("user", "Somewhere else")],
+            0,
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+        terminal.draw(|f| list.render(f, f.area())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect();
+
+        let row = |text: &str| rows.iter().find(|row| row.contains(text)).unwrap();
+        assert!(row("Inside the repository").contains("myrepo"), "{rows:#?}");
+        assert!(!row("Somewhere else").contains("myrepo"));
+    }
+
     #[test]
     fn test_esc_key_preview() {
         let mut list = ResultList::new();
@@ -516,4 +553,145 @@ mod tests {
             Some(Message::LoadMoreResults) | Some(Message::SelectResult(_))
         ));
     }
+    #[test]
+    fn test_search_progress_in_title() {
+        use crate::interactive_ratatui::domain::models::SearchProgress;
+        use ratatui::Terminal;
+        use ratatui::backend::TestBackend;
+
+        let mut list = ResultList::new();
+        list.set_results(vec![create_test_result("user", "Early hit")]);
+        list.set_search_progress(Some(SearchProgress {
+            files_searched: 5,
+            files_total: 10,
+        }));
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+        terminal.draw(|f| list.render(f, f.area())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let title: String = (0..buffer.area.width)
+            .map(|x| buffer.cell((x, 0)).unwrap().symbol().to_string())
+            .collect();
+        assert!(title.contains("Search Results - 1 so far [█████░░░░░] 5/10 files"));
+
+        list.set_search_progress(None);
+        terminal.draw(|f| list.render(f, f.area())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let title: String = (0..buffer.area.width)
+            .map(|x| buffer.cell((x, 0)).unwrap().symbol().to_string())
+            .collect();
+        assert!(!title.contains("so far"));
+    }
+
+    #[test]
+    fn test_selection_marker_and_count() {
+        use ratatui::Terminal;
+        use ratatui::backend::TestBackend;
+
+        let mut list = ResultList::new();
+        list.set_results(vec![
+            create_test_result("user", "Picked"),
+            create_test_result("assistant", "Not picked"),
+        ]);
+        list.set_selection([0].into_iter().collect(), 1);
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 12)).unwrap();
+        terminal.draw(|f| list.render(f, f.area())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer.cell((x, y)).unwrap().symbol().to_string())
+                    .collect()
+            })
+            .collect();
+        assert!(rows[0].contains("| 1 selected"));
+        let picked = rows.iter().find(|row| row.contains("Picked")).unwrap();
+        assert!(picked.contains("● "));
+        let other = rows.iter().find(|row| row.contains("Not picked")).unwrap();
+        assert!(!other.contains('●'));
+        assert!(rows.iter().any(|row| row.contains("Ctrl+Y: Copy as JSONL")));
+    }
+
+    #[test]
+    fn test_stale_banner() {
+        use ratatui::Terminal;
+        use ratatui::backend::TestBackend;
+
+        let mut list = ResultList::new();
+        list.set_results(vec![create_test_result("user", "Hello")]);
+        let title = |list: &mut ResultList| {
+            let mut terminal = Terminal::new(TestBackend::new(120, 8)).unwrap();
+            terminal.draw(|f| list.render(f, f.area())).unwrap();
+            let buffer = terminal.backend().buffer();
+            (0..buffer.area.width)
+                .map(|x| buffer.cell((x, 0)).unwrap().symbol().to_string())
+                .collect::<String>()
+        };
+
+        assert!(!title(&mut list).contains("F5"));
+        list.set_stale(true);
+        assert!(title(&mut list).contains("Corpus updated, press F5 to refresh"));
+    }
+
+    #[test]
+    fn test_mouse_click_and_wheel() {
+        use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+        use ratatui::Terminal;
+        use ratatui::backend::TestBackend;
+
+        let mut list = ResultList::new();
+        list.set_results(vec![
+            create_test_result("user", "First"),
+            create_test_result("assistant", "Second"),
+            create_test_result("user", "Third"),
+        ]);
+        let mut terminal = Terminal::new(TestBackend::new(100, 12)).unwrap();
+        terminal.draw(|f| list.render(f, f.area())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let row_of = |text: &str| {
+            (0..buffer.area.height)
+                .find(|&y| {
+                    (0..buffer.area.width)
+                        .map(|x| buffer.cell((x, y)).unwrap().symbol())
+                        .collect::<String>()
+                        .contains(text)
+                })
+                .unwrap()
+        };
+        let mouse = |kind, row| MouseEvent {
+            kind,
+            column: 20,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        let click = MouseEventKind::Down(MouseButton::Left);
+
+        let second = row_of("Second");
+        assert_eq!(
+            list.handle_mouse(mouse(click, second)),
+            Some(Message::SelectResult(1))
+        );
+        // Clicking the selected result again opens it
+        assert_eq!(
+            list.handle_mouse(mouse(click, second)),
+            Some(Message::EnterMessageDetail)
+        );
+        // The title is not a result
+        assert_eq!(list.handle_mouse(mouse(click, 0)), None);
+
+        assert_eq!(
+            list.handle_mouse(mouse(MouseEventKind::ScrollDown, second)),
+            Some(Message::SelectResult(2))
+        );
+        assert_eq!(
+            list.handle_mouse(mouse(MouseEventKind::ScrollUp, second)),
+            Some(Message::SelectResult(1))
+        );
+        // Outside of the list
+        assert_eq!(
+            list.handle_mouse(mouse(MouseEventKind::ScrollDown, 40)),
+            None
+        );
+    }
 }
@@ -0,0 +1,188 @@
+use crate::interactive_ratatui::ui::components::Component;
+use crate::interactive_ratatui::ui::events::Message;
+use crate::interactive_ratatui::ui::theme;
+use crate::query::condition::SearchResult;
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    widgets::{Block, Borders, Sparkline},
+};
+
+/// Length of the time buckets matches are counted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Hour,
+    Day,
+    Week,
+}
+
+impl Granularity {
+    const ALL: [Granularity; 3] = [Granularity::Hour, Granularity::Day, Granularity::Week];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Granularity::Hour => "hour",
+            Granularity::Day => "day",
+            Granularity::Week => "week",
+        }
+    }
+
+    fn length(self) -> TimeDelta {
+        match self {
+            Granularity::Hour => TimeDelta::hours(1),
+            Granularity::Day => TimeDelta::days(1),
+            Granularity::Week => TimeDelta::weeks(1),
+        }
+    }
+
+    /// Start of the bucket `time` falls in; weeks start on Monday
+    fn floor(self, time: NaiveDateTime) -> NaiveDateTime {
+        let day = time.date().and_time(NaiveTime::MIN);
+        match self {
+            Granularity::Hour => day + TimeDelta::hours(time.hour().into()),
+            Granularity::Day => day,
+            Granularity::Week => {
+                day - TimeDelta::days(time.weekday().num_days_from_monday().into())
+            }
+        }
+    }
+
+    fn label(self, time: NaiveDateTime) -> String {
+        match self {
+            Granularity::Hour => time.format("%m/%d %H:00").to_string(),
+            Granularity::Day | Granularity::Week => time.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// Match counts per time bucket, oldest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    pub granularity: Granularity,
+    /// Start of the first bucket
+    pub start: NaiveDateTime,
+    pub counts: Vec<u64>,
+}
+
+impl Timeline {
+    /// `times` counted in the finest buckets of which `max_buckets` cover
+    /// them all, or in the latest `max_buckets` weeks when even weeks do not;
+    /// `None` without any times
+    pub fn build(times: &[NaiveDateTime], max_buckets: usize) -> Option<Self> {
+        let first = *times.iter().min()?;
+        let last = *times.iter().max()?;
+        let max_buckets = max_buckets.max(1);
+
+        let granularity = Granularity::ALL
+            .into_iter()
+            .find(|granularity| {
+                bucket_index(*granularity, granularity.floor(first), last) < max_buckets
+            })
+            .unwrap_or(Granularity::Week);
+        let end = granularity.floor(last);
+        let start = granularity
+            .floor(first)
+            .max(end - granularity.length() * (max_buckets as i32 - 1));
+
+        let mut counts = vec![0; bucket_index(granularity, start, last) + 1];
+        for &time in times.iter().filter(|time| **time >= start) {
+            counts[bucket_index(granularity, start, time)] += 1;
+        }
+        Some(Self {
+            granularity,
+            start,
+            counts,
+        })
+    }
+
+    /// Start of the last bucket
+    pub fn end(&self) -> NaiveDateTime {
+        self.start + self.granularity.length() * (self.counts.len() as i32 - 1)
+    }
+}
+
+/// Bucket of `time` counted from the bucket starting at `start`
+fn bucket_index(granularity: Granularity, start: NaiveDateTime, time: NaiveDateTime) -> usize {
+    ((granularity.floor(time) - start).num_seconds() / granularity.length().num_seconds()) as usize
+}
+
+/// Matches of the current query over time, shown above the result list
+#[derive(Default)]
+pub struct ResultTimeline {
+    times: Vec<NaiveDateTime>,
+    partial: bool,
+}
+
+impl ResultTimeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Local times of the loaded results; `partial` while more results are
+    /// left to load
+    pub fn set_results(&mut self, results: &[SearchResult], partial: bool) {
+        self.times = results
+            .iter()
+            .filter_map(|result| DateTime::parse_from_rfc3339(&result.timestamp).ok())
+            .map(|time| time.with_timezone(&Local).naive_local())
+            .collect();
+        self.partial = partial;
+    }
+}
+
+impl Component for ResultTimeline {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::current().text_dim));
+        let inner = block.inner(area);
+
+        let Some(timeline) = Timeline::build(&self.times, inner.width as usize) else {
+            f.render_widget(block.title(" No match times (Alt+H to hide) "), area);
+            return;
+        };
+
+        let granularity = timeline.granularity;
+        let range = if timeline.counts.len() == 1 {
+            granularity.label(timeline.start)
+        } else {
+            format!(
+                "{} – {}",
+                granularity.label(timeline.start),
+                granularity.label(timeline.end())
+            )
+        };
+        let title = format!(
+            " {} {} per {}, {}, peak {} (Alt+H to hide) ",
+            self.times.len(),
+            if self.partial {
+                "loaded matches"
+            } else {
+                "matches"
+            },
+            granularity.name(),
+            range,
+            timeline.counts.iter().max().copied().unwrap_or(0),
+        );
+
+        // Buckets are drawn as wide as the space allows
+        let width = (inner.width as usize / timeline.counts.len()).max(1);
+        let data: Vec<u64> = timeline
+            .counts
+            .iter()
+            .flat_map(|&count| std::iter::repeat_n(count, width))
+            .collect();
+        let sparkline = Sparkline::default()
+            .block(block.title(title))
+            .data(&data)
+            .style(Style::default().fg(theme::current().primary));
+        f.render_widget(sparkline, area);
+    }
+
+    fn handle_key(&mut self, _key: KeyEvent) -> Option<Message> {
+        None
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use super::super::result_timeline::{Granularity, ResultTimeline, Timeline};
+    use crate::interactive_ratatui::ui::components::Component;
+    use crate::query::condition::{QueryCondition, SearchResult};
+    use chrono::{Local, NaiveDateTime, TimeZone};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn time(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn result(local: &str) -> SearchResult {
+        let timestamp = Local
+            .from_local_datetime(&time(local))
+            .unwrap()
+            .to_rfc3339();
+        SearchResult {
+            file: "test.jsonl".into(),
+            uuid: local.to_string(),
+            timestamp,
+            session_id: "s1".into(),
+            role: "user".into(),
+            text: "hello".to_string(),
+            message_type: "user".into(),
+            query: QueryCondition::Literal {
+                pattern: "hello".to_string(),
+                case_sensitive: false,
+            },
+            cwd: "/".into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        }
+    }
+
+    #[test]
+    fn test_timeline_picks_the_finest_buckets_that_fit() {
+        let times = [
+            time("2024-03-04 09:15"),
+            time("2024-03-04 09:45"),
+            time("2024-03-04 12:00"),
+        ];
+        let timeline = Timeline::build(&times, 10).unwrap();
+        assert_eq!(timeline.granularity, Granularity::Hour);
+        assert_eq!(timeline.start, time("2024-03-04 09:00"));
+        assert_eq!(timeline.counts, vec![2, 0, 0, 1]);
+        assert_eq!(timeline.end(), time("2024-03-04 12:00"));
+
+        // Too many hours for the width
+        let timeline = Timeline::build(&times, 3).unwrap();
+        assert_eq!(timeline.granularity, Granularity::Day);
+        assert_eq!(timeline.counts, vec![3]);
+
+        let times = [time("2024-03-01 10:00"), time("2024-03-20 10:00")];
+        let timeline = Timeline::build(&times, 10).unwrap();
+        assert_eq!(timeline.granularity, Granularity::Week);
+        // Weeks start on Monday
+        assert_eq!(timeline.start, time("2024-02-26 00:00"));
+        assert_eq!(timeline.counts, vec![1, 0, 0, 1]);
+
+        // Only the latest weeks when even weeks do not fit
+        let timeline = Timeline::build(&times, 2).unwrap();
+        assert_eq!(timeline.start, time("2024-03-11 00:00"));
+        assert_eq!(timeline.counts, vec![0, 1]);
+
+        assert_eq!(Timeline::build(&[], 10), None);
+    }
+
+    #[test]
+    fn test_result_timeline_render() {
+        let mut timeline = ResultTimeline::new();
+        timeline.set_results(
+            &[
+                result("2024-03-04 09:15"),
+                result("2024-03-05 10:00"),
+                result("2024-03-05 18:00"),
+            ],
+            true,
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 5)).unwrap();
+        terminal.draw(|f| timeline.render(f, f.area())).unwrap();
+        let content: String = terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(content.contains("3 loaded matches per hour, 03/04 09:00 – 03/05 18:00, peak 1"));
+        assert!(content.contains("█"));
+    }
+}
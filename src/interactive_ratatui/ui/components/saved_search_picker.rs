@@ -0,0 +1,119 @@
+use crate::interactive_ratatui::ui::app_state::SavedSearchPalette;
+use crate::interactive_ratatui::ui::components::Component;
+use crate::interactive_ratatui::ui::events::Message;
+use crate::interactive_ratatui::ui::theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// The saved searches, drawn over the search view with F3. The first nine
+/// apply with their digit.
+#[derive(Default)]
+pub struct SavedSearchPicker {
+    palette: SavedSearchPalette,
+}
+
+impl SavedSearchPicker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_palette(&mut self, palette: SavedSearchPalette) {
+        self.palette = palette;
+    }
+}
+
+impl Component for SavedSearchPicker {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let current = theme::current();
+        let width = 70.min(area.width);
+        let height = (self.palette.searches.len().max(1) as u16 + 4).min(area.height);
+        let dialog_area = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        f.render_widget(Clear, dialog_area);
+
+        let mut lines: Vec<Line> = if self.palette.searches.is_empty() {
+            vec![Line::from(Span::styled(
+                " No saved searches; add one with ccms --save NAME",
+                Style::default().fg(current.text_dim),
+            ))]
+        } else {
+            self.palette
+                .searches
+                .iter()
+                .enumerate()
+                .map(|(i, (name, search))| {
+                    let highlighted = i == self.palette.highlighted;
+                    let marker = if highlighted { "▶" } else { " " };
+                    let digit = if i < 9 {
+                        format!("{} ", i + 1)
+                    } else {
+                        "  ".to_string()
+                    };
+                    let name_style = if highlighted {
+                        Style::default()
+                            .fg(current.primary)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(current.primary)
+                    };
+                    let mut details = search.query.clone();
+                    for arg in search.filters.to_args() {
+                        details.push(' ');
+                        details.push_str(&arg);
+                    }
+                    Line::from(vec![
+                        Span::styled(marker, Style::default().fg(current.primary)),
+                        Span::styled(digit, Style::default().fg(current.text_dim)),
+                        Span::styled(format!("${name} "), name_style),
+                        Span::styled(details, Style::default().fg(current.text)),
+                    ])
+                })
+                .collect()
+        };
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "↑/↓ choose · Enter or 1-9 apply · Esc close",
+            Style::default().fg(current.text_dim),
+        )));
+
+        let picker = Paragraph::new(lines).block(
+            Block::default()
+                .title(" Saved Searches ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(current.primary))
+                .style(Style::default().bg(current.background)),
+        );
+        f.render_widget(picker, dialog_area);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Message> {
+        let count = self.palette.searches.len();
+        let highlighted = self.palette.highlighted;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::F(3) => Some(Message::CloseSavedSearches),
+            _ if count == 0 => None,
+            KeyCode::Up | KeyCode::Char('k') => Some(Message::HighlightSavedSearch(
+                (highlighted + count - 1) % count,
+            )),
+            KeyCode::Down | KeyCode::Char('j') => {
+                Some(Message::HighlightSavedSearch((highlighted + 1) % count))
+            }
+            KeyCode::Enter => Some(Message::ApplySavedSearch(highlighted)),
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c as usize - '1' as usize;
+                (index < count).then_some(Message::ApplySavedSearch(index))
+            }
+            _ => None,
+        }
+    }
+}
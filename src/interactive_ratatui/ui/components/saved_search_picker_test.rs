@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use super::super::saved_search_picker::SavedSearchPicker;
+    use crate::interactive_ratatui::ui::app_state::SavedSearchPalette;
+    use crate::interactive_ratatui::ui::components::Component;
+    use crate::interactive_ratatui::ui::events::Message;
+    use crate::query::{SavedSearch, SearchFilters};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::empty())
+    }
+
+    fn palette() -> SavedSearchPalette {
+        SavedSearchPalette {
+            searches: vec![
+                ("failures".to_string(), "/fail(ed|ure)/i".to_string().into()),
+                (
+                    "nightly-errors".to_string(),
+                    SavedSearch {
+                        query: "error".to_string(),
+                        filters: SearchFilters {
+                            role: Some("assistant".to_string()),
+                            ..Default::default()
+                        },
+                    },
+                ),
+            ],
+            highlighted: 0,
+        }
+    }
+
+    #[test]
+    fn test_saved_search_picker_keys() {
+        let mut picker = SavedSearchPicker::new();
+        picker.set_palette(palette());
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Up)),
+            Some(Message::HighlightSavedSearch(1))
+        );
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Enter)),
+            Some(Message::ApplySavedSearch(0))
+        );
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Char('2'))),
+            Some(Message::ApplySavedSearch(1))
+        );
+        assert_eq!(picker.handle_key(key(KeyCode::Char('3'))), None);
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Esc)),
+            Some(Message::CloseSavedSearches)
+        );
+
+        picker.set_palette(SavedSearchPalette::default());
+        assert_eq!(picker.handle_key(key(KeyCode::Enter)), None);
+    }
+
+    #[test]
+    fn test_saved_search_picker_lists_searches_with_filters() {
+        let mut picker = SavedSearchPicker::new();
+        picker.set_palette(palette());
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal.draw(|f| picker.render(f, f.area())).unwrap();
+
+        let content: String = terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(content.contains("Saved Searches"));
+        assert!(
+            content.contains("▶1 $failures /fail(ed|ure)/i"),
+            "{content}"
+        );
+        assert!(content.contains(" 2 $nightly-errors error --role assistant"));
+    }
+}
@@ -1,12 +1,13 @@
 use crate::interactive_ratatui::domain::models::SearchOrder;
 use crate::interactive_ratatui::ui::components::{Component, text_input::TextInput};
 use crate::interactive_ratatui::ui::events::Message;
-use crossterm::event::KeyEvent;
+use crate::interactive_ratatui::ui::theme;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Color, Style},
-    text::Line,
+    style::Style,
+    text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
 };
 
@@ -15,7 +16,12 @@ pub struct SearchBar {
     is_searching: bool,
     message: Option<String>,
     role_filter: Option<String>,
+    hide_tool_results: bool,
     search_order: SearchOrder,
+    /// Name of the project searches are limited to
+    project: Option<String>,
+    /// Ctrl+R filter and the history entry it matches, while searching history
+    history_search: Option<(String, Option<String>)>,
 }
 
 impl Default for SearchBar {
@@ -31,7 +37,10 @@ impl SearchBar {
             is_searching: false,
             message: None,
             role_filter: None,
+            hide_tool_results: false,
             search_order: SearchOrder::Descending,
+            project: None,
+            history_search: None,
         }
     }
 
@@ -54,10 +63,55 @@ impl SearchBar {
         self.role_filter = role_filter;
     }
 
+    pub fn set_hide_tool_results(&mut self, hide: bool) {
+        self.hide_tool_results = hide;
+    }
+
     pub fn set_search_order(&mut self, order: SearchOrder) {
         self.search_order = order;
     }
 
+    pub fn set_project(&mut self, project: Option<String>) {
+        self.project = project;
+    }
+
+    pub fn set_history_search(&mut self, history_search: Option<(String, Option<String>)>) {
+        self.history_search = history_search;
+    }
+
+    /// Keys while Ctrl+R is searching the history: typing edits the filter,
+    /// Ctrl+R again goes to an older match, Enter uses it and Esc gives up
+    pub fn handle_history_key(&mut self, key: KeyEvent) -> Option<Message> {
+        let filter = self
+            .history_search
+            .as_ref()
+            .map(|(filter, _)| filter.as_str())
+            .unwrap_or_default();
+        match key.code {
+            KeyCode::Char('r') if key.modifiers == KeyModifiers::CONTROL => {
+                Some(Message::StartHistorySearch)
+            }
+            KeyCode::Char('g') if key.modifiers == KeyModifiers::CONTROL => {
+                Some(Message::CancelHistorySearch)
+            }
+            KeyCode::Esc => Some(Message::CancelHistorySearch),
+            KeyCode::Enter => Some(Message::AcceptHistorySearch),
+            KeyCode::Backspace => {
+                let mut filter = filter.to_string();
+                filter.pop();
+                Some(Message::HistorySearchChanged(filter))
+            }
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                Some(Message::HistorySearchChanged(format!("{filter}{c}")))
+            }
+            _ => None,
+        }
+    }
+
     pub fn get_query(&self) -> &str {
         self.text_input.text()
     }
@@ -69,19 +123,46 @@ impl SearchBar {
 
 impl Component for SearchBar {
     fn render(&mut self, f: &mut Frame, area: Rect) {
+        if let Some((filter, found)) = &self.history_search {
+            let line = Line::from(vec![
+                Span::styled(
+                    format!("(reverse-i-search)`{filter}': "),
+                    Style::default().fg(theme::current().primary),
+                ),
+                match found {
+                    Some(query) => Span::raw(query.as_str()),
+                    None => {
+                        Span::styled("no match", Style::default().fg(theme::current().text_dim))
+                    }
+                },
+            ]);
+            let input = Paragraph::new(line)
+                .block(
+                    Block::default()
+                        .title("History [Ctrl+R: older | Enter: search | Esc: cancel]")
+                        .borders(Borders::ALL),
+                )
+                .style(Style::default().fg(theme::current().secondary));
+            f.render_widget(input, area);
+            return;
+        }
+
         let input_text = self.text_input.render_cursor_spans();
 
         let mut title = "Search".to_string();
         if let Some(role) = &self.role_filter {
             title.push_str(&format!(" [role:{role}]"));
         }
+        if self.hide_tool_results {
+            title.push_str(" [no tool results]");
+        }
+
+        if let Some(project) = &self.project {
+            title.push_str(&format!(" [project:{project}]"));
+        }
 
         // Add order info
-        let order_text = match self.search_order {
-            SearchOrder::Descending => "Desc",
-            SearchOrder::Ascending => "Asc",
-        };
-        title.push_str(&format!(" [order:{order_text}]"));
+        title.push_str(&format!(" [order:{}]", self.search_order.label()));
 
         if let Some(msg) = &self.message {
             title.push_str(&format!(" - {msg}"));
@@ -89,7 +170,7 @@ impl Component for SearchBar {
 
         let input = Paragraph::new(Line::from(input_text))
             .block(Block::default().title(title).borders(Borders::ALL))
-            .style(Style::default().fg(Color::Yellow));
+            .style(Style::default().fg(theme::current().secondary));
 
         f.render_widget(input, area);
     }
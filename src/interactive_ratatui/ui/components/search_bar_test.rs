@@ -580,4 +580,58 @@ mod tests {
         assert!(msg.is_some());
         assert_eq!(search_bar.get_query(), "XaaYa");
     }
+
+    #[test]
+    fn test_history_search() {
+        use ratatui::{Terminal, backend::TestBackend};
+
+        let mut search_bar = SearchBar::new();
+        search_bar.set_query("typed".to_string());
+        search_bar.set_history_search(Some(("er".to_string(), Some("error log".to_string()))));
+
+        assert!(matches!(
+            search_bar.handle_history_key(create_key_event(KeyCode::Char('r'))),
+            Some(Message::HistorySearchChanged(filter)) if filter == "err"
+        ));
+        assert!(matches!(
+            search_bar.handle_history_key(create_key_event(KeyCode::Backspace)),
+            Some(Message::HistorySearchChanged(filter)) if filter == "e"
+        ));
+        assert!(matches!(
+            search_bar.handle_history_key(create_key_event_with_modifiers(
+                KeyCode::Char('r'),
+                KeyModifiers::CONTROL
+            )),
+            Some(Message::StartHistorySearch)
+        ));
+        assert!(matches!(
+            search_bar.handle_history_key(create_key_event(KeyCode::Enter)),
+            Some(Message::AcceptHistorySearch)
+        ));
+        assert!(matches!(
+            search_bar.handle_history_key(create_key_event(KeyCode::Esc)),
+            Some(Message::CancelHistorySearch)
+        ));
+        assert!(
+            search_bar
+                .handle_history_key(create_key_event(KeyCode::Down))
+                .is_none()
+        );
+        // The query being edited is left alone
+        assert_eq!(search_bar.get_query(), "typed");
+
+        let screen = |search_bar: &mut SearchBar| {
+            let mut terminal = Terminal::new(TestBackend::new(80, 3)).unwrap();
+            terminal.draw(|f| search_bar.render(f, f.area())).unwrap();
+            let buffer = terminal.backend().buffer();
+            (0..buffer.area.width)
+                .map(|x| buffer.cell((x, 1)).unwrap().symbol().to_string())
+                .collect::<String>()
+        };
+        assert!(screen(&mut search_bar).contains("(reverse-i-search)`er': error log"));
+        search_bar.set_history_search(Some(("zzz".to_string(), None)));
+        assert!(screen(&mut search_bar).contains("no match"));
+        search_bar.set_history_search(None);
+        assert!(screen(&mut search_bar).contains("typed"));
+    }
 }
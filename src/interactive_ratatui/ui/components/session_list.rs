@@ -1,12 +1,15 @@
+use crate::cost::format_usd;
 use crate::interactive_ratatui::ui::app_state::SessionInfo;
 use crate::interactive_ratatui::ui::components::Component;
-use crate::interactive_ratatui::ui::components::view_layout::ColorScheme;
+use crate::interactive_ratatui::ui::components::view_layout::render_vertical_scrollbar;
 use crate::interactive_ratatui::ui::events::Message;
+use crate::interactive_ratatui::ui::theme;
+use crate::outcome::SessionOutcome;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
 };
@@ -23,6 +26,7 @@ pub struct SessionList {
     is_searching: bool,
     is_typing: bool,
     preview_enabled: bool,
+    show_cost: bool,
 }
 
 impl SessionList {
@@ -38,6 +42,7 @@ impl SessionList {
             is_searching: false,
             is_typing: false,
             preview_enabled: true, // Default to true for better UX
+            show_cost: false,
         }
     }
 
@@ -81,22 +86,39 @@ impl SessionList {
         self.preview_enabled = enabled;
     }
 
+    pub fn set_show_cost(&mut self, show_cost: bool) {
+        self.show_cost = show_cost;
+    }
+
     pub fn get_selected_session(&self) -> Option<&SessionInfo> {
         self.filtered_sessions.get(self.selected_index)
     }
 }
 
+/// Marker for how a session ended; `outcome:<name>` in the search box filters by it
+fn outcome_badge(outcome: SessionOutcome) -> Span<'static> {
+    match outcome {
+        SessionOutcome::Completed => {
+            Span::styled("✓", Style::default().fg(theme::current().success))
+        }
+        SessionOutcome::Abandoned => {
+            Span::styled("…", Style::default().fg(theme::current().text_dim))
+        }
+        SessionOutcome::Error => Span::styled("✗", Style::default().fg(theme::current().error)),
+    }
+}
+
 impl Component for SessionList {
     fn render(&mut self, f: &mut Frame, area: Rect) {
         const TITLE_HEIGHT: u16 = 2;
 
         // Calculate the actual height needed for the status bar
-        let status_text = if self.preview_enabled {
-            "Shift+Tab: Switch tabs | ↑/↓: Navigate | Ctrl+U/D: Half page | Enter: Open session | Ctrl+S: View session | Ctrl+T: Hide preview | Esc: Exit | ?: Help"
-        } else {
-            "Shift+Tab: Switch tabs | ↑/↓: Navigate | Ctrl+U/D: Half page | Enter: Open session | Ctrl+S: View session | Ctrl+T: Show preview | Esc: Exit | ?: Help"
-        };
-        let status_paragraph = Paragraph::new(status_text).wrap(Wrap { trim: true });
+        let status_text = format!(
+            "Shift+Tab: Switch tabs | ↑/↓: Navigate | Ctrl+U/D: Half page | Enter: Open session | Ctrl+S: View session | Ctrl+T: {} preview | Ctrl+G: {} cost | Esc: Exit | ?: Help",
+            if self.preview_enabled { "Hide" } else { "Show" },
+            if self.show_cost { "Hide" } else { "Show" },
+        );
+        let status_paragraph = Paragraph::new(status_text.as_str()).wrap(Wrap { trim: true });
         let status_height = (status_paragraph.line_count(area.width) as u16).clamp(1, 3);
 
         // Split area into search bar, title, sessions list and status bar
@@ -132,7 +154,7 @@ impl Component for SessionList {
             .title(format!("Search{search_status}{session_count}"));
         let input_spans = self.text_input.render_cursor_spans();
         let search_text = Paragraph::new(Line::from(input_spans))
-            .style(Style::default().fg(ColorScheme::SECONDARY))
+            .style(Style::default().fg(theme::current().secondary))
             .block(search_block);
         f.render_widget(search_text, chunks[0]);
 
@@ -140,13 +162,22 @@ impl Component for SessionList {
         let title_lines = vec![Line::from(vec![Span::styled(
             "Search Sessions",
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme::current().primary)
                 .add_modifier(Modifier::BOLD),
         )])];
         let title = Paragraph::new(title_lines).block(Block::default().borders(Borders::BOTTOM));
         f.render_widget(title, chunks[1]);
 
-        let block = Block::default().borders(Borders::ALL).title("Sessions");
+        let block_title = if self.filtered_sessions.is_empty() {
+            "Sessions".to_string()
+        } else {
+            format!(
+                "Sessions ({}/{})",
+                self.selected_index + 1,
+                self.filtered_sessions.len()
+            )
+        };
+        let block = Block::default().borders(Borders::ALL).title(block_title);
 
         if self.is_loading {
             let loading = List::new(vec![ListItem::new("Loading...")]).block(block);
@@ -173,20 +204,44 @@ impl Component for SessionList {
                         session.timestamp.chars().take(16).collect::<String>()
                     };
 
-                    let line = Line::from(vec![
-                        Span::styled(formatted_time, Style::default().fg(Color::Yellow)),
+                    let mut spans = vec![
+                        Span::styled(
+                            formatted_time,
+                            Style::default().fg(theme::current().secondary),
+                        ),
                         Span::raw(" "),
                         Span::styled(
                             format!("[{}]", session.session_id),
-                            Style::default().fg(Color::Cyan),
+                            Style::default().fg(theme::current().primary),
                         ),
                         Span::raw(format!(" ({} msgs) ", session.message_count)),
-                        Span::styled(&session.first_message, Style::default().fg(Color::White)),
-                    ]);
+                    ];
+                    if self.show_cost {
+                        let cost = session.cost_usd.map_or_else(|| "-".to_string(), format_usd);
+                        spans.push(Span::styled(
+                            format!("{cost:>7} "),
+                            Style::default().fg(theme::current().warning),
+                        ));
+                    }
+                    if session.compactions > 0 {
+                        spans.push(Span::styled(
+                            format!("⟳{} ", session.compactions),
+                            Style::default().fg(theme::current().accent),
+                        ));
+                    }
+                    if let Some(outcome) = session.outcome {
+                        spans.push(outcome_badge(outcome));
+                        spans.push(Span::raw(" "));
+                    }
+                    spans.push(Span::styled(
+                        &session.first_message,
+                        Style::default().fg(theme::current().text),
+                    ));
+                    let line = Line::from(spans);
 
                     let style = if i == self.selected_index {
                         Style::default()
-                            .bg(Color::Rgb(60, 60, 60))
+                            .bg(theme::current().selection)
                             .add_modifier(Modifier::BOLD)
                     } else {
                         Style::default()
@@ -213,14 +268,19 @@ impl Component for SessionList {
 
             let list = List::new(visible_items)
                 .block(block)
-                .style(Style::default().fg(Color::White));
+                .style(Style::default().fg(theme::current().text));
 
             f.render_widget(list, chunks[2]);
+
+            let total = self.filtered_sessions.len();
+            if total > visible_height {
+                render_vertical_scrollbar(f, chunks[2], total, visible_height, self.selected_index);
+            }
         }
 
         // Render status bar
-        let status_bar = Paragraph::new(status_text)
-            .style(Style::default().fg(Color::DarkGray))
+        let status_bar = Paragraph::new(status_text.as_str())
+            .style(Style::default().fg(theme::current().text_dim))
             .alignment(ratatui::layout::Alignment::Center)
             .wrap(Wrap { trim: true });
         f.render_widget(status_bar, chunks[3]);
@@ -269,6 +329,9 @@ impl Component for SessionList {
             KeyCode::Char('t') if key.modifiers == KeyModifiers::CONTROL => {
                 return Some(Message::ToggleSessionListPreview);
             }
+            KeyCode::Char('g') if key.modifiers == KeyModifiers::CONTROL => {
+                return Some(Message::ToggleSessionListCost);
+            }
             _ => {}
         }
 
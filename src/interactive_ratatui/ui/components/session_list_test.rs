@@ -4,6 +4,7 @@ mod tests {
     use crate::interactive_ratatui::ui::app_state::SessionInfo;
     use crate::interactive_ratatui::ui::components::Component;
     use crate::interactive_ratatui::ui::events::Message;
+    use crate::outcome::SessionOutcome;
     use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
     use ratatui::{Terminal, backend::TestBackend, buffer::Buffer};
 
@@ -27,6 +28,9 @@ mod tests {
                 ),
             ],
             summary: Some(format!("Summary about {message}")),
+            outcome: None,
+            compactions: 0,
+            cost_usd: None,
         }
     }
 
@@ -118,6 +122,51 @@ mod tests {
         assert!(content.contains("Goodbye world"));
     }
 
+    #[test]
+    fn test_session_list_render_outcome_badges() {
+        let mut session_list = SessionList::new();
+        let mut failed = create_test_session_info("session1", "Broken build");
+        failed.outcome = Some(SessionOutcome::Error);
+        failed.compactions = 2;
+        let mut done = create_test_session_info("session2", "Fixed build");
+        done.outcome = Some(SessionOutcome::Completed);
+        session_list.set_sessions(vec![failed, done]);
+
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                session_list.render(f, f.area());
+            })
+            .unwrap();
+
+        let content = buffer_to_string(terminal.backend().buffer());
+        assert!(content.contains("(5 msgs) ⟳2 ✗ Broken build"));
+        assert!(content.contains("(5 msgs) ✓ Fixed build"));
+    }
+
+    #[test]
+    fn test_session_list_render_cost_column() {
+        let mut session_list = SessionList::new();
+        let mut priced = create_test_session_info("session1", "Refactor");
+        priced.cost_usd = Some(1.234);
+        let unpriced = create_test_session_info("session2", "Offline");
+        session_list.set_sessions(vec![priced, unpriced]);
+
+        let render = |session_list: &mut SessionList| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+            terminal.draw(|f| session_list.render(f, f.area())).unwrap();
+            buffer_to_string(terminal.backend().buffer())
+        };
+
+        assert!(!render(&mut session_list).contains("$1.23"));
+
+        session_list.set_show_cost(true);
+        let content = render(&mut session_list);
+        assert!(content.contains("(5 msgs)   $1.23 Refactor"));
+        assert!(content.contains("(5 msgs)       - Offline"));
+    }
+
     #[test]
     fn test_session_list_render_with_search() {
         let mut session_list = SessionList::new();
@@ -207,6 +256,10 @@ mod tests {
         // Test Ctrl+T (toggle preview)
         let msg = session_list.handle_key(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL));
         assert!(matches!(msg, Some(Message::ToggleSessionListPreview)));
+
+        // Test Ctrl+G (toggle cost column)
+        let msg = session_list.handle_key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL));
+        assert!(matches!(msg, Some(Message::ToggleSessionListCost)));
     }
 
     #[test]
@@ -1,11 +1,12 @@
 use crate::interactive_ratatui::ui::app_state::SessionInfo;
 use crate::interactive_ratatui::ui::components::Component;
 use crate::interactive_ratatui::ui::events::Message;
+use crate::interactive_ratatui::ui::theme;
 use crossterm::event::KeyEvent;
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph, Wrap},
 };
@@ -38,47 +39,69 @@ impl Component for SessionPreview {
         let block = Block::default()
             .borders(Borders::ALL)
             .title(" Session Preview ")
-            .border_style(Style::default().fg(Color::DarkGray));
+            .border_style(Style::default().fg(theme::current().text_dim));
 
         if let Some(session) = &self.session_info {
             let mut lines = vec![];
 
             // Session ID
             lines.push(Line::from(vec![
-                Span::styled("Session ID: ", Style::default().fg(Color::Gray)),
+                Span::styled(
+                    "Session ID: ",
+                    Style::default().fg(theme::current().text_dim),
+                ),
                 Span::styled(
                     &session.session_id,
                     Style::default()
-                        .fg(Color::Cyan)
+                        .fg(theme::current().primary)
                         .add_modifier(Modifier::BOLD),
                 ),
             ]));
 
             // Timestamp
             lines.push(Line::from(vec![
-                Span::styled("Time: ", Style::default().fg(Color::Gray)),
-                Span::styled(&session.timestamp, Style::default().fg(Color::Yellow)),
+                Span::styled("Time: ", Style::default().fg(theme::current().text_dim)),
+                Span::styled(
+                    &session.timestamp,
+                    Style::default().fg(theme::current().secondary),
+                ),
             ]));
 
             // Message count
             lines.push(Line::from(vec![
-                Span::styled("Messages: ", Style::default().fg(Color::Gray)),
+                Span::styled("Messages: ", Style::default().fg(theme::current().text_dim)),
                 Span::styled(
                     format!("{}", session.message_count),
-                    Style::default().fg(Color::Green),
+                    Style::default().fg(theme::current().success),
                 ),
             ]));
 
+            // Earlier context the session continued from in a summary
+            if session.compactions > 0 {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        "Compactions: ",
+                        Style::default().fg(theme::current().text_dim),
+                    ),
+                    Span::styled(
+                        format!("{}", session.compactions),
+                        Style::default().fg(theme::current().accent),
+                    ),
+                ]));
+            }
+
             lines.push(Line::from(""));
 
             // File path (truncated if needed)
             lines.push(Line::from(vec![Span::styled(
                 "Path: ",
-                Style::default().fg(Color::Gray),
+                Style::default().fg(theme::current().text_dim),
             )]));
             lines.push(Line::from(vec![Span::styled(
                 &session.file_path,
-                Style::default().fg(Color::Blue).add_modifier(Modifier::DIM),
+                Style::default()
+                    .fg(theme::current().primary)
+                    .add_modifier(Modifier::DIM),
             )]));
 
             lines.push(Line::from(""));
@@ -88,7 +111,7 @@ impl Component for SessionPreview {
                 lines.push(Line::from(vec![Span::styled(
                     "Summary:",
                     Style::default()
-                        .fg(Color::Magenta)
+                        .fg(theme::current().accent)
                         .add_modifier(Modifier::BOLD),
                 )]));
 
@@ -108,7 +131,7 @@ impl Component for SessionPreview {
                         if absolute_start > last_end {
                             summary_spans.push(Span::styled(
                                 &summary[last_end..absolute_start],
-                                Style::default().fg(Color::White),
+                                Style::default().fg(theme::current().text),
                             ));
                         }
 
@@ -116,7 +139,7 @@ impl Component for SessionPreview {
                         summary_spans.push(Span::styled(
                             &summary[absolute_start..absolute_end],
                             Style::default()
-                                .fg(Color::Yellow)
+                                .fg(theme::current().secondary)
                                 .add_modifier(Modifier::BOLD),
                         ));
 
@@ -127,12 +150,15 @@ impl Component for SessionPreview {
                     if last_end < summary.len() {
                         summary_spans.push(Span::styled(
                             &summary[last_end..],
-                            Style::default().fg(Color::White),
+                            Style::default().fg(theme::current().text),
                         ));
                     }
                 } else {
                     // No query, just display summary normally
-                    summary_spans.push(Span::styled(summary, Style::default().fg(Color::White)));
+                    summary_spans.push(Span::styled(
+                        summary,
+                        Style::default().fg(theme::current().text),
+                    ));
                 }
 
                 lines.push(Line::from(summary_spans));
@@ -144,7 +170,7 @@ impl Component for SessionPreview {
                 lines.push(Line::from(vec![Span::styled(
                     "Recent Messages:",
                     Style::default()
-                        .fg(Color::Gray)
+                        .fg(theme::current().text_dim)
                         .add_modifier(Modifier::BOLD),
                 )]));
 
@@ -172,9 +198,8 @@ impl Component for SessionPreview {
                 let matching_count = matching_messages.len();
                 for (role, content, timestamp, is_match) in matching_messages {
                     let role_color = match role.as_str() {
-                        "user" => Color::Green,
-                        "assistant" => Color::Blue,
-                        _ => Color::Gray,
+                        "user" | "assistant" => theme::current().role_color(role),
+                        _ => theme::current().text_dim,
                     };
 
                     // Format timestamp
@@ -189,7 +214,7 @@ impl Component for SessionPreview {
                     let mut content_spans = vec![
                         Span::styled(
                             format!("[{formatted_time}] "),
-                            Style::default().fg(Color::DarkGray),
+                            Style::default().fg(theme::current().text_dim),
                         ),
                         Span::styled(
                             format!("{role}: "),
@@ -212,7 +237,7 @@ impl Component for SessionPreview {
                             if absolute_start > last_end {
                                 content_spans.push(Span::styled(
                                     &content[last_end..absolute_start],
-                                    Style::default().fg(Color::White),
+                                    Style::default().fg(theme::current().text),
                                 ));
                             }
 
@@ -220,7 +245,7 @@ impl Component for SessionPreview {
                             content_spans.push(Span::styled(
                                 &content[absolute_start..absolute_end],
                                 Style::default()
-                                    .fg(Color::Yellow)
+                                    .fg(theme::current().secondary)
                                     .add_modifier(Modifier::BOLD),
                             ));
 
@@ -231,13 +256,15 @@ impl Component for SessionPreview {
                         if last_end < content.len() {
                             content_spans.push(Span::styled(
                                 &content[last_end..],
-                                Style::default().fg(Color::White),
+                                Style::default().fg(theme::current().text),
                             ));
                         }
                     } else {
                         // No match or no query, just display content normally
-                        content_spans
-                            .push(Span::styled(content, Style::default().fg(Color::White)));
+                        content_spans.push(Span::styled(
+                            content,
+                            Style::default().fg(theme::current().text),
+                        ));
                     }
 
                     lines.push(Line::from(content_spans));
@@ -249,9 +276,8 @@ impl Component for SessionPreview {
                     non_matching_messages.into_iter().take(remaining_space)
                 {
                     let role_color = match role.as_str() {
-                        "user" => Color::Green,
-                        "assistant" => Color::Blue,
-                        _ => Color::Gray,
+                        "user" | "assistant" => theme::current().role_color(role),
+                        _ => theme::current().text_dim,
                     };
 
                     // Format timestamp
@@ -265,24 +291,24 @@ impl Component for SessionPreview {
                     lines.push(Line::from(vec![
                         Span::styled(
                             format!("[{formatted_time}] "),
-                            Style::default().fg(Color::DarkGray),
+                            Style::default().fg(theme::current().text_dim),
                         ),
                         Span::styled(
                             format!("{role}: "),
                             Style::default().fg(role_color).add_modifier(Modifier::BOLD),
                         ),
-                        Span::styled(content, Style::default().fg(Color::White)),
+                        Span::styled(content, Style::default().fg(theme::current().text)),
                     ]));
                 }
             } else {
                 // Fallback to first message if no preview messages
                 lines.push(Line::from(vec![Span::styled(
                     "First Message:",
-                    Style::default().fg(Color::Gray),
+                    Style::default().fg(theme::current().text_dim),
                 )]));
                 lines.push(Line::from(vec![Span::styled(
                     &session.first_message,
-                    Style::default().fg(Color::White),
+                    Style::default().fg(theme::current().text),
                 )]));
             }
 
@@ -290,20 +316,20 @@ impl Component for SessionPreview {
             lines.push(Line::from(vec![Span::styled(
                 "Press Enter to open this session",
                 Style::default()
-                    .fg(Color::DarkGray)
+                    .fg(theme::current().text_dim)
                     .add_modifier(Modifier::ITALIC),
             )]));
 
             let preview = Paragraph::new(lines)
                 .block(block)
                 .wrap(Wrap { trim: true })
-                .style(Style::default().fg(Color::White));
+                .style(Style::default().fg(theme::current().text));
 
             f.render_widget(preview, area);
         } else {
             let preview = Paragraph::new("No session selected")
                 .block(block)
-                .style(Style::default().fg(Color::DarkGray));
+                .style(Style::default().fg(theme::current().text_dim));
 
             f.render_widget(preview, area);
         }
@@ -41,6 +41,9 @@ mod tests {
                 ),
             ],
             summary: Some("Summary about testing".to_string()),
+            outcome: None,
+            compactions: 0,
+            cost_usd: None,
         }
     }
 
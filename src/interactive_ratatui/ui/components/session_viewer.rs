@@ -1,21 +1,20 @@
 use crate::interactive_ratatui::domain::models::SessionOrder;
 use crate::interactive_ratatui::ui::components::{
-    Component, is_exit_prompt,
-    message_preview::MessagePreview,
-    result_list::ResultList,
-    text_input::TextInput,
-    view_layout::{ColorScheme, ViewLayout},
+    Component, is_exit_prompt, message_preview::MessagePreview, result_list::ResultList,
+    text_input::TextInput, view_layout::ViewLayout,
 };
 use crate::interactive_ratatui::ui::events::{CopyContent, Message};
+use crate::interactive_ratatui::ui::theme;
 use crate::query::condition::SearchResult;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::Line,
     widgets::{Block, Borders, Paragraph},
 };
+use std::collections::{HashMap, HashSet};
 
 pub struct SessionViewer {
     result_list: ResultList,
@@ -29,6 +28,14 @@ pub struct SessionViewer {
     message: Option<String>,
     role_filter: Option<String>,
     preview_enabled: bool,
+    diff_marked: bool,
+    following: bool,
+    hide_tool_results: bool,
+    /// Messages the session continues from after a context compaction
+    compactions: usize,
+    /// The alternative shown at each fork of edited or retried prompts, as
+    /// (1-based alternative, alternatives)
+    branches: Vec<(usize, usize)>,
 }
 
 impl Default for SessionViewer {
@@ -51,14 +58,27 @@ impl SessionViewer {
             message: None,
             role_filter: None,
             preview_enabled: false,
+            diff_marked: false,
+            following: false,
+            hide_tool_results: false,
+            compactions: 0,
+            branches: Vec::new(),
         }
     }
 
     pub fn set_results(&mut self, results: Vec<SearchResult>) {
         // Extract cwd from the first result if not yet set
         if self.cwd.is_none() && !results.is_empty() {
-            self.cwd = Some(results[0].cwd.clone());
+            self.cwd = Some(results[0].cwd.to_string());
         }
+        let boundaries: HashSet<usize> = results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| result.starts_compaction())
+            .map(|(index, _)| index)
+            .collect();
+        self.compactions = boundaries.len();
+        self.result_list.set_boundaries(boundaries);
         self.result_list.set_results(results);
     }
 
@@ -108,6 +128,27 @@ impl SessionViewer {
         self.role_filter = role_filter;
     }
 
+    /// Whether a message is waiting for a second one to diff against
+    pub fn set_diff_marked(&mut self, marked: bool) {
+        self.diff_marked = marked;
+    }
+
+    /// Whether new messages written to the session file are picked up
+    pub fn set_following(&mut self, following: bool) {
+        self.following = following;
+    }
+
+    pub fn set_hide_tool_results(&mut self, hide: bool) {
+        self.hide_tool_results = hide;
+    }
+
+    /// Results that start an alternative of an edited or retried prompt, by
+    /// index with labels like `2/3`, and the alternative shown at each fork
+    pub fn set_branches(&mut self, starts: HashMap<usize, String>, shown: Vec<(usize, usize)>) {
+        self.result_list.set_branch_starts(starts);
+        self.branches = shown;
+    }
+
     /// Generate Markdown export of all session messages in Simon Willison format
     pub fn generate_session_markdown(&self) -> Option<String> {
         let results = self.result_list.get_items();
@@ -223,7 +264,7 @@ impl SessionViewer {
                 Block::default()
                     .title(format!("Search in session ({status_text}) | Tab: Role Filter | Ctrl+O: Sort | Esc to cancel"))
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(ColorScheme::SECONDARY)),
+                    .border_style(Style::default().fg(theme::current().secondary)),
             );
             f.render_widget(search_bar, chunks[0]);
         } else {
@@ -237,12 +278,45 @@ impl SessionViewer {
             };
 
             let total_count = self.result_list.items_count();
+            let diff_part = if self.diff_marked {
+                " | Diff: 1 marked"
+            } else {
+                ""
+            };
+
+            let follow_part = if self.following { " | Following" } else { "" };
+            let tool_results_part = if self.hide_tool_results {
+                " | Tool results hidden"
+            } else {
+                ""
+            };
+
+            let compactions_part = match self.compactions {
+                0 => String::new(),
+                1 => " | 1 compaction".to_string(),
+                count => format!(" | {count} compactions"),
+            };
+
+            let branches_part = match self.branches.as_slice() {
+                [] => String::new(),
+                [(shown, count)] => format!(" | Branch {shown}/{count}"),
+                forks => format!(
+                    " | Branches {}",
+                    forks
+                        .iter()
+                        .map(|(shown, count)| format!("{shown}/{count}"))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                ),
+            };
 
             let info_text = if total_count == 0 {
-                format!("No messages{order_part}{role_part} | Press '/' to search")
+                format!(
+                    "No messages{branches_part}{order_part}{role_part}{diff_part}{follow_part}{tool_results_part} | Press '/' to search"
+                )
             } else {
                 format!(
-                    "Total: {total_count} messages{order_part}{role_part} | Press '/' to search"
+                    "Total: {total_count} messages{compactions_part}{branches_part}{order_part}{role_part}{diff_part}{follow_part}{tool_results_part} | Press '/' to search"
                 )
             };
             let info_bar = Paragraph::new(info_text).block(Block::default().borders(Borders::ALL));
@@ -316,7 +390,7 @@ impl Component for SessionViewer {
         let layout = ViewLayout::new("Session Viewer".to_string())
             .with_subtitle(subtitle)
             .with_status_bar(true) // Let ViewLayout handle the status bar
-            .with_status_text("↑/↓ Ctrl+P/N Ctrl+U/D: Navigate | Tab: Filter | Enter: Detail | Ctrl+O: Sort | Ctrl+T: Preview | c/C: Copy text/JSON | m: Copy as Markdown | i/f/p: Copy IDs/paths | v: Convert+Copy Codex ID | /: Search | Esc: Back".to_string());
+            .with_status_text("↑/↓ Ctrl+P/N Ctrl+U/D: Navigate | Tab: Filter | Enter: Detail | Ctrl+O: Sort | Ctrl+T: Preview | c/C: Copy text/JSON | m: Copy as Markdown | d: Mark for diff | [/]: Switch branch | D: Diff branches | b: Bookmark | F: Follow | T: Hide tool results | U: Token chart | i/f/p: Copy IDs/paths | v: Convert+Copy Codex ID | /: Search | Esc: Back".to_string());
 
         layout.render(f, chunks[0], |f, content_area| {
             self.render_content(f, content_area);
@@ -326,10 +400,10 @@ impl Component for SessionViewer {
         if let Some(ref msg) = non_exit_message {
             let style = if msg.starts_with('✓') {
                 Style::default()
-                    .fg(ColorScheme::SUCCESS)
+                    .fg(theme::current().success)
                     .add_modifier(Modifier::BOLD)
             } else if msg.starts_with('⚠') {
-                Style::default().fg(ColorScheme::WARNING)
+                Style::default().fg(theme::current().warning)
             } else {
                 Style::default().add_modifier(Modifier::BOLD)
             };
@@ -347,7 +421,7 @@ impl Component for SessionViewer {
             let exit_prompt = Paragraph::new("Press Ctrl+C again to exit")
                 .style(
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme::current().secondary)
                         .add_modifier(Modifier::BOLD),
                 )
                 .alignment(ratatui::layout::Alignment::Center);
@@ -569,11 +643,51 @@ impl Component for SessionViewer {
                     .generate_session_markdown()
                     .map(|md| Message::CopyToClipboard(CopyContent::SessionMarkdown(md))),
                 KeyCode::Char('v') => Some(Message::ConvertSessionToCodex),
+                KeyCode::Char('F') => Some(Message::ToggleSessionFollow),
+                KeyCode::Char('T') => Some(Message::ToggleToolResults),
+                KeyCode::Char('U') => Some(Message::ShowContextChart),
+                KeyCode::Char('d') => self
+                    .result_list
+                    .selected_result()
+                    .map(|result| Message::MarkForDiff(Box::new(result.clone()))),
+                KeyCode::Char('[') => self
+                    .result_list
+                    .selected_result()
+                    .map(|result| Message::SwitchBranch(result.uuid.clone(), false)),
+                KeyCode::Char(']') => self
+                    .result_list
+                    .selected_result()
+                    .map(|result| Message::SwitchBranch(result.uuid.clone(), true)),
+                KeyCode::Char('D') => self
+                    .result_list
+                    .selected_result()
+                    .map(|result| Message::DiffBranches(Box::new(result.clone()))),
+                KeyCode::Char('b') => self
+                    .result_list
+                    .selected_result()
+                    .map(|result| Message::ToggleBookmark(Box::new(result.clone()))),
                 KeyCode::Esc => Some(Message::ExitToSearch),
                 _ => None,
             }
         }
     }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Option<Message> {
+        // Only drawn with the preview when a message is selected
+        if self.preview_enabled
+            && self.result_list.selected_result().is_some()
+            && self.message_preview.is_at(mouse.column, mouse.row)
+        {
+            return self.message_preview.handle_mouse(mouse);
+        }
+        match self.result_list.handle_mouse(mouse)? {
+            Message::EnterMessageDetail => self.handle_key(KeyEvent::from(KeyCode::Enter)),
+            _ => Some(Message::SessionNavigated(
+                self.result_list.get_selected_index(),
+                self.result_list.get_scroll_offset(),
+            )),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -689,34 +803,40 @@ mod tests {
         use crate::query::condition::QueryCondition;
         let results = vec![
             SearchResult {
-                file: "/file.jsonl".to_string(),
+                file: "/file.jsonl".into(),
                 uuid: "uuid1".to_string(),
                 timestamp: "2024-01-01T00:00:00Z".to_string(),
-                session_id: "session1".to_string(),
-                role: "user".to_string(),
+                session_id: "session1".into(),
+                role: "user".into(),
                 text: "Message 1".to_string(),
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: String::new(),
                     case_sensitive: false,
                 },
-                cwd: "/path".to_string(),
+                cwd: "/path".into(),
                 raw_json: Some("{}".to_string()),
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
             SearchResult {
-                file: "/file.jsonl".to_string(),
+                file: "/file.jsonl".into(),
                 uuid: "uuid2".to_string(),
                 timestamp: "2024-01-01T00:01:00Z".to_string(),
-                session_id: "session1".to_string(),
-                role: "assistant".to_string(),
+                session_id: "session1".into(),
+                role: "assistant".into(),
                 text: "Message 2".to_string(),
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: String::new(),
                     case_sensitive: false,
                 },
-                cwd: "/path".to_string(),
+                cwd: "/path".into(),
                 raw_json: Some("{}".to_string()),
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
         ];
         viewer.set_results(results);
@@ -801,34 +921,40 @@ mod tests {
 
         let results = vec![
             SearchResult {
-                file: "/file.jsonl".to_string(),
+                file: "/file.jsonl".into(),
                 uuid: "uuid1".to_string(),
                 timestamp: "2024-01-15T10:30:00Z".to_string(),
-                session_id: "test-session-123".to_string(),
-                role: "user".to_string(),
+                session_id: "test-session-123".into(),
+                role: "user".into(),
                 text: "Hello, how are you?".to_string(),
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: String::new(),
                     case_sensitive: false,
                 },
-                cwd: "/path".to_string(),
+                cwd: "/path".into(),
                 raw_json: Some("{}".to_string()),
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
             SearchResult {
-                file: "/file.jsonl".to_string(),
+                file: "/file.jsonl".into(),
                 uuid: "uuid2".to_string(),
                 timestamp: "2024-01-15T10:31:00Z".to_string(),
-                session_id: "test-session-123".to_string(),
-                role: "assistant".to_string(),
+                session_id: "test-session-123".into(),
+                role: "assistant".into(),
                 text: "I'm doing well, thank you!".to_string(),
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: String::new(),
                     case_sensitive: false,
                 },
-                cwd: "/path".to_string(),
+                cwd: "/path".into(),
                 raw_json: Some("{}".to_string()),
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
         ];
         viewer.set_results(results);
@@ -851,19 +977,22 @@ mod tests {
         viewer.set_session_id(Some("test-session".to_string()));
 
         let results = vec![SearchResult {
-            file: "/file.jsonl".to_string(),
+            file: "/file.jsonl".into(),
             uuid: "uuid1".to_string(),
             timestamp: "2024-01-15T10:30:00Z".to_string(),
-            session_id: "test-session".to_string(),
-            role: "user".to_string(),
+            session_id: "test-session".into(),
+            role: "user".into(),
             text: "Test message".to_string(),
-            message_type: "message".to_string(),
+            message_type: "message".into(),
             query: QueryCondition::Literal {
                 pattern: String::new(),
                 case_sensitive: false,
             },
-            cwd: "/path".to_string(),
+            cwd: "/path".into(),
             raw_json: Some("{}".to_string()),
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         }];
         viewer.set_results(results);
 
@@ -884,25 +1013,45 @@ mod tests {
         assert!(matches!(result, Some(Message::ConvertSessionToCodex)));
     }
 
+    #[test]
+    fn test_follow_shortcut() {
+        let mut viewer = SessionViewer::new();
+        let key = KeyEvent::new(KeyCode::Char('F'), KeyModifiers::SHIFT);
+        assert!(matches!(
+            viewer.handle_key(key),
+            Some(Message::ToggleSessionFollow)
+        ));
+
+        // Typed as part of a query while searching
+        viewer.start_search();
+        assert!(!matches!(
+            viewer.handle_key(key),
+            Some(Message::ToggleSessionFollow)
+        ));
+    }
+
     #[test]
     fn test_enter_without_raw_json_shows_status_message() {
         use crate::query::condition::QueryCondition;
 
         let mut viewer = SessionViewer::new();
         let results = vec![SearchResult {
-            file: "/file.jsonl".to_string(),
+            file: "/file.jsonl".into(),
             uuid: "uuid1".to_string(),
             timestamp: "2024-01-15T10:30:00Z".to_string(),
-            session_id: "test-session".to_string(),
-            role: "user".to_string(),
+            session_id: "test-session".into(),
+            role: "user".into(),
             text: "Test message".to_string(),
-            message_type: "message".to_string(),
+            message_type: "message".into(),
             query: QueryCondition::Literal {
                 pattern: String::new(),
                 case_sensitive: false,
             },
-            cwd: "/path".to_string(),
+            cwd: "/path".into(),
             raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
         }];
         viewer.set_results(results);
 
@@ -913,4 +1062,123 @@ mod tests {
             Some(Message::SetStatus(msg)) if msg.contains("Raw JSON is unavailable")
         ));
     }
+
+    #[test]
+    fn test_compaction_boundaries_are_marked() {
+        use crate::query::condition::QueryCondition;
+        use ratatui::{Terminal, backend::TestBackend};
+
+        let result = |uuid: &str, role: &str, text: &str, raw_json: &str| SearchResult {
+            file: "/file.jsonl".into(),
+            uuid: uuid.to_string(),
+            timestamp: "2024-01-15T10:30:00Z".to_string(),
+            session_id: "test-session".into(),
+            role: role.into(),
+            text: text.to_string(),
+            message_type: role.into(),
+            query: QueryCondition::Literal {
+                pattern: String::new(),
+                case_sensitive: false,
+            },
+            cwd: "/path".into(),
+            raw_json: Some(raw_json.to_string()),
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        };
+        let mut viewer = SessionViewer::new();
+        viewer.set_results(vec![
+            result("u1", "user", "Before", r#"{"type":"user"}"#),
+            result(
+                "b1",
+                "system",
+                "Conversation compacted",
+                r#"{"type":"system","subtype":"compact_boundary","content":"Conversation compacted","isMeta":false,"uuid":"b1","timestamp":"2024-01-15T10:30:00Z","sessionId":"test-session","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/path","version":"1"}"#,
+            ),
+            result("u2", "user", "After", r#"{"type":"user"}"#),
+        ]);
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|f| viewer.render(f, f.area())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect();
+
+        assert!(
+            rows.iter()
+                .any(|row| row.contains("Total: 3 messages | 1 compaction"))
+        );
+        let marked: Vec<&String> = rows
+            .iter()
+            .filter(|row| row.contains("── compacted ──"))
+            .collect();
+        assert_eq!(marked.len(), 1);
+        assert!(marked[0].contains("Conversation compacted"));
+    }
+
+    #[test]
+    fn test_branch_starts_are_marked() {
+        use crate::query::condition::QueryCondition;
+        use ratatui::{Terminal, backend::TestBackend};
+
+        let result = |uuid: &str, role: &str, text: &str| SearchResult {
+            file: "/file.jsonl".into(),
+            uuid: uuid.to_string(),
+            timestamp: "2024-01-15T10:30:00Z".to_string(),
+            session_id: "test-session".into(),
+            role: role.into(),
+            text: text.to_string(),
+            message_type: role.into(),
+            query: QueryCondition::Literal {
+                pattern: String::new(),
+                case_sensitive: false,
+            },
+            cwd: "/path".into(),
+            raw_json: Some("{}".to_string()),
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        };
+        let mut viewer = SessionViewer::new();
+        viewer.set_results(vec![
+            result("u1", "user", "Fix the bug"),
+            result("a1", "assistant", "Which bug?"),
+            result("u3", "user", "Fix the parser bug"),
+        ]);
+        viewer.set_branches(HashMap::from([(2, "2/2".to_string())]), vec![(2, 2)]);
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|f| viewer.render(f, f.area())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect();
+
+        assert!(
+            rows.iter()
+                .any(|row| row.contains("Total: 3 messages | Branch 2/2"))
+        );
+        let marked: Vec<&String> = rows.iter().filter(|row| row.contains("⎇ 2/2")).collect();
+        assert_eq!(marked.len(), 1);
+        assert!(marked[0].contains("Fix the parser bug"));
+
+        viewer.set_selected_index(2);
+        assert_eq!(
+            viewer.handle_key(KeyEvent::new(KeyCode::Char(']'), KeyModifiers::NONE)),
+            Some(Message::SwitchBranch("u3".to_string(), true))
+        );
+        assert!(matches!(
+            viewer.handle_key(KeyEvent::new(KeyCode::Char('D'), KeyModifiers::SHIFT)),
+            Some(Message::DiffBranches(_))
+        ));
+    }
 }
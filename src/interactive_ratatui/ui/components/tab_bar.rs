@@ -1,80 +1,113 @@
 use crate::interactive_ratatui::domain::models::SearchTab;
 use crate::interactive_ratatui::ui::components::Component;
 use crate::interactive_ratatui::ui::events::Message;
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::interactive_ratatui::ui::theme;
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     Frame,
-    layout::Rect,
+    layout::{Position, Rect},
     style::{Color, Modifier, Style},
     widgets::{Block, Borders},
 };
 
+/// Tabs in display order
+const TABS: [(SearchTab, &str); 4] = [
+    (SearchTab::Search, "Search"),
+    (SearchTab::SessionList, "Session List"),
+    (SearchTab::Bookmarks, "Bookmarks"),
+    (SearchTab::Projects, "Projects"),
+];
+
+fn switch_message(tab: SearchTab) -> Message {
+    match tab {
+        SearchTab::Search => Message::SwitchToSearchTab,
+        SearchTab::SessionList => Message::SwitchToSessionListTab,
+        SearchTab::Bookmarks => Message::SwitchToBookmarksTab,
+        SearchTab::Projects => Message::SwitchToProjectsTab,
+    }
+}
+
+/// Width of the gap before the first tab and of the separator between tabs
+const LEADING_WIDTH: u16 = 1;
+const SEPARATOR_WIDTH: u16 = 3;
+
 #[derive(Default)]
 pub struct TabBar {
     current_tab: SearchTab,
+    /// Where the tab bar was last drawn, for the mouse
+    area: Rect,
 }
 
 impl TabBar {
     pub fn new() -> Self {
         Self {
             current_tab: SearchTab::Search,
+            area: Rect::default(),
         }
     }
 
     pub fn set_current_tab(&mut self, tab: SearchTab) {
         self.current_tab = tab;
     }
+
+    /// The tab whose title covers a column of the screen
+    pub fn tab_at(&self, column: u16) -> Option<SearchTab> {
+        let mut start = self.area.x + LEADING_WIDTH;
+        for (index, (tab, title)) in TABS.iter().enumerate() {
+            if index > 0 {
+                start += SEPARATOR_WIDTH;
+            }
+            // Titles are drawn as " ▸ title " or "   title "
+            let end = start + title.chars().count() as u16 + 4;
+            if (start..end).contains(&column) {
+                return Some(*tab);
+            }
+            start = end;
+        }
+        None
+    }
 }
 
 impl Component for TabBar {
     fn render(&mut self, f: &mut Frame, area: Rect) {
         use ratatui::text::{Line, Span};
+
+        self.area = area;
         use ratatui::widgets::Paragraph;
 
         // Create tab titles with better visual separation
-        let search_tab = if self.current_tab == SearchTab::Search {
-            Span::styled(
-                " ▸ Search ",
-                Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )
-        } else {
-            Span::styled(
-                "   Search ",
-                Style::default().fg(Color::Gray).add_modifier(Modifier::DIM),
-            )
-        };
-
-        let session_tab = if self.current_tab == SearchTab::SessionList {
-            Span::styled(
-                " ▸ Session List ",
-                Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )
-        } else {
-            Span::styled(
-                "   Session List ",
-                Style::default().fg(Color::Gray).add_modifier(Modifier::DIM),
-            )
-        };
-
-        // Add separators and create the tab line
-        let tab_line = Line::from(vec![
-            Span::raw(" "),
-            search_tab,
-            Span::styled(" │ ", Style::default().fg(Color::DarkGray)),
-            session_tab,
-            Span::raw(" "),
-        ]);
+        let mut tab_spans = vec![Span::raw(" ")];
+        for (index, (tab, title)) in TABS.iter().enumerate() {
+            if index > 0 {
+                tab_spans.push(Span::styled(
+                    " │ ",
+                    Style::default().fg(theme::current().text_dim),
+                ));
+            }
+            tab_spans.push(if *tab == self.current_tab {
+                Span::styled(
+                    format!(" ▸ {title} "),
+                    Style::default()
+                        .fg(theme::current().background)
+                        .bg(theme::current().secondary)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                Span::styled(
+                    format!("   {title} "),
+                    Style::default()
+                        .fg(theme::current().text_dim)
+                        .add_modifier(Modifier::DIM),
+                )
+            });
+        }
+        tab_spans.push(Span::raw(" "));
+        let tab_line = Line::from(tab_spans);
 
         // Create a block with top and bottom borders for the tab bar
         let tab_block = Block::default()
             .borders(Borders::TOP | Borders::BOTTOM)
-            .border_style(Style::default().fg(Color::DarkGray));
+            .border_style(Style::default().fg(theme::current().text_dim));
 
         let tabs = Paragraph::new(vec![tab_line])
             .block(tab_block)
@@ -86,27 +119,32 @@ impl Component for TabBar {
     fn handle_key(&mut self, key: KeyEvent) -> Option<Message> {
         use crossterm::event::KeyModifiers;
 
+        let position = TABS
+            .iter()
+            .position(|(tab, _)| *tab == self.current_tab)
+            .unwrap_or(0);
+
         match key.code {
-            // Use Shift+Tab to switch tabs (Tab alone is for role filter in Search tab)
-            KeyCode::BackTab => Some(match self.current_tab {
-                SearchTab::Search => Message::SwitchToSessionListTab,
-                SearchTab::SessionList => Message::SwitchToSearchTab,
-            }),
-            KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                if self.current_tab == SearchTab::SessionList {
-                    Some(Message::SwitchToSearchTab)
-                } else {
-                    None
-                }
-            }
+            // Use Shift+Tab to cycle tabs (Tab alone is for role filter in Search tab)
+            KeyCode::BackTab => Some(switch_message(TABS[(position + 1) % TABS.len()].0)),
+            KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => position
+                .checked_sub(1)
+                .map(|previous| switch_message(TABS[previous].0)),
             KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                if self.current_tab == SearchTab::Search {
-                    Some(Message::SwitchToSessionListTab)
-                } else {
-                    None
-                }
+                TABS.get(position + 1).map(|(tab, _)| switch_message(*tab))
             }
             _ => None,
         }
     }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Option<Message> {
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left)
+            || !self.area.contains(Position::new(mouse.column, mouse.row))
+        {
+            return None;
+        }
+        self.tab_at(mouse.column)
+            .filter(|tab| *tab != self.current_tab)
+            .map(switch_message)
+    }
 }
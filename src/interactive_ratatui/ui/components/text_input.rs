@@ -1,8 +1,6 @@
+use crate::interactive_ratatui::ui::theme;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use ratatui::{
-    style::{Color, Style},
-    text::Span,
-};
+use ratatui::{style::Style, text::Span};
 
 /// A reusable text input component that handles cursor positioning and text editing
 #[derive(Debug, Clone, Default)]
@@ -108,7 +106,9 @@ impl TextInput {
             // Show cursor on empty space
             vec![Span::styled(
                 " ",
-                Style::default().bg(Color::White).fg(Color::Black),
+                Style::default()
+                    .bg(theme::current().text)
+                    .fg(theme::current().background),
             )]
         } else if self.cursor_position < self.text.chars().count() {
             // Cursor is in the middle of text
@@ -131,7 +131,9 @@ impl TextInput {
             // Add cursor span
             spans.push(Span::styled(
                 after.chars().next().unwrap_or(' ').to_string(),
-                Style::default().bg(Color::White).fg(Color::Black),
+                Style::default()
+                    .bg(theme::current().text)
+                    .fg(theme::current().background),
             ));
 
             // Add remaining text if any
@@ -145,7 +147,12 @@ impl TextInput {
             // Cursor is at the end
             vec![
                 Span::raw(self.text.clone()),
-                Span::styled(" ", Style::default().bg(Color::White).fg(Color::Black)),
+                Span::styled(
+                    " ",
+                    Style::default()
+                        .bg(theme::current().text)
+                        .fg(theme::current().background),
+                ),
             ]
         }
     }
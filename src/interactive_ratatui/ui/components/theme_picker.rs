@@ -0,0 +1,107 @@
+use crate::interactive_ratatui::ui::components::Component;
+use crate::interactive_ratatui::ui::events::Message;
+use crate::interactive_ratatui::ui::theme::{self, Theme, ThemeName};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// The built-in themes, each shown in its own colors, drawn over any view
+/// with F2
+#[derive(Default)]
+pub struct ThemePicker {
+    highlighted: ThemeName,
+}
+
+impl ThemePicker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_highlighted(&mut self, name: ThemeName) {
+        self.highlighted = name;
+    }
+
+    fn position(&self) -> usize {
+        ThemeName::ALL
+            .iter()
+            .position(|&name| name == self.highlighted)
+            .unwrap_or(0)
+    }
+
+    fn sample(name: ThemeName) -> Line<'static> {
+        let sample = Theme::builtin(name);
+        let base = Style::default().bg(sample.background);
+        Line::from(vec![
+            Span::styled(format!(" {:<10}", name.as_str()), base.fg(sample.text)),
+            Span::styled("user ", base.fg(sample.user)),
+            Span::styled("assistant ", base.fg(sample.assistant)),
+            Span::styled(
+                "match",
+                base.fg(sample.matched).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" ", base),
+        ])
+    }
+}
+
+impl Component for ThemePicker {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let current = theme::current();
+        let width = 40.min(area.width);
+        let height = (ThemeName::ALL.len() as u16 + 4).min(area.height);
+        let dialog_area = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        f.render_widget(Clear, dialog_area);
+
+        let mut lines: Vec<Line> = ThemeName::ALL
+            .iter()
+            .map(|&name| {
+                let marker = if name == self.highlighted { "▶" } else { " " };
+                let mut line = Self::sample(name);
+                line.spans.insert(
+                    0,
+                    Span::styled(marker, Style::default().fg(current.primary)),
+                );
+                line
+            })
+            .collect();
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "↑/↓ choose · Enter apply · Esc close",
+            Style::default().fg(current.text_dim),
+        )));
+
+        let picker = Paragraph::new(lines).block(
+            Block::default()
+                .title(" Theme ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(current.primary))
+                .style(Style::default().bg(current.background)),
+        );
+        f.render_widget(picker, dialog_area);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Message> {
+        let count = ThemeName::ALL.len();
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => Some(Message::HighlightTheme(
+                ThemeName::ALL[(self.position() + count - 1) % count],
+            )),
+            KeyCode::Down | KeyCode::Char('j') => Some(Message::HighlightTheme(
+                ThemeName::ALL[(self.position() + 1) % count],
+            )),
+            KeyCode::Enter => Some(Message::ApplyTheme(self.highlighted)),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::F(2) => Some(Message::CloseThemePicker),
+            _ => None,
+        }
+    }
+}
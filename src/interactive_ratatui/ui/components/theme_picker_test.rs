@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use super::super::theme_picker::ThemePicker;
+    use crate::interactive_ratatui::ui::components::Component;
+    use crate::interactive_ratatui::ui::events::Message;
+    use crate::interactive_ratatui::ui::theme::{Theme, ThemeName};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::empty())
+    }
+
+    #[test]
+    fn test_theme_picker_keys() {
+        let mut picker = ThemePicker::new();
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Down)),
+            Some(Message::HighlightTheme(ThemeName::Light))
+        );
+        // Wraps around
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Up)),
+            Some(Message::HighlightTheme(ThemeName::Solarized))
+        );
+
+        picker.set_highlighted(ThemeName::Solarized);
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Enter)),
+            Some(Message::ApplyTheme(ThemeName::Solarized))
+        );
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Esc)),
+            Some(Message::CloseThemePicker)
+        );
+    }
+
+    #[test]
+    fn test_theme_picker_shows_each_theme_in_its_colors() {
+        let mut picker = ThemePicker::new();
+        picker.set_highlighted(ThemeName::Light);
+        let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+        terminal.draw(|f| picker.render(f, f.area())).unwrap();
+        let buffer = terminal.backend().buffer();
+
+        let content: String = buffer.content.iter().map(|cell| cell.symbol()).collect();
+        assert!(content.contains("Theme"));
+        assert!(content.contains("▶ light"), "{content}");
+        assert!(content.contains("  solarized"));
+
+        let solarized = Theme::builtin(ThemeName::Solarized);
+        assert!(
+            buffer
+                .content
+                .iter()
+                .any(|cell| cell.symbol() == "a" && cell.fg == solarized.assistant)
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use crate::interactive_ratatui::ui::theme;
+use crate::links::{WebSearchLinks, extract_web_search};
+use crate::schemas::{StructuredPatchItem, TodoItem};
+use crate::todos::{extract_todos, todo_status_glyph};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+use serde_json::Value;
+
+/// Upper bound on `old_lines * new_lines` for the in-house line diff.
+/// Larger edits fall back to showing every old line removed and every new line added.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Structured rendering extracted from the tool calls and tool results of a message
+#[derive(Clone, Debug, PartialEq)]
+pub enum ToolView {
+    Diff(EditDiff),
+    Todos(Vec<TodoItem>),
+    WebSearch(WebSearchLinks),
+}
+
+/// A file edit from an Edit/MultiEdit tool call or its structured patch result
+#[derive(Clone, Debug, PartialEq)]
+pub struct EditDiff {
+    pub file_path: String,
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffHunk {
+    pub header: String,
+    /// Lines prefixed with ' ', '-' or '+' as in a unified diff
+    pub lines: Vec<String>,
+}
+
+impl From<&StructuredPatchItem> for DiffHunk {
+    fn from(patch: &StructuredPatchItem) -> Self {
+        Self {
+            header: format!(
+                "@@ -{},{} +{},{} @@",
+                patch.old_start, patch.old_lines, patch.new_start, patch.new_lines
+            ),
+            lines: patch.lines.clone(),
+        }
+    }
+}
+
+/// Extract structured tool views from the raw JSON line of a session message.
+///
+/// Assistant messages contribute diffs built from Edit/MultiEdit inputs and
+/// TodoWrite checklists; user messages contribute the `structuredPatch`,
+/// `newTodos` or web search results of the tool result they carry.
+pub fn extract_tool_views(raw_json: &str) -> Vec<ToolView> {
+    let Ok(value) = serde_json::from_str::<Value>(raw_json) else {
+        return Vec::new();
+    };
+
+    let mut views = Vec::new();
+
+    if let Some(tool_result) = value.get("toolUseResult")
+        && let Some(diff) = diff_from_tool_result(tool_result)
+    {
+        views.push(ToolView::Diff(diff));
+    }
+
+    if let Some(todos) = extract_todos(&value)
+        && !todos.is_empty()
+    {
+        views.push(ToolView::Todos(todos));
+    }
+
+    if let Some(search) = extract_web_search(&value) {
+        views.push(ToolView::WebSearch(search));
+    }
+
+    if let Some(contents) = value
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+    {
+        for item in contents {
+            if item.get("type").and_then(|t| t.as_str()) != Some("tool_use") {
+                continue;
+            }
+            let name = item.get("name").and_then(|n| n.as_str()).unwrap_or("");
+            if let Some(input) = item.get("input")
+                && let Some(diff) = diff_from_tool_input(name, input)
+            {
+                views.push(ToolView::Diff(diff));
+            }
+        }
+    }
+
+    views
+}
+
+fn diff_from_tool_result(tool_result: &Value) -> Option<EditDiff> {
+    let patches: Vec<StructuredPatchItem> =
+        serde_json::from_value(tool_result.get("structuredPatch")?.clone()).ok()?;
+    if patches.is_empty() {
+        return None;
+    }
+
+    Some(EditDiff {
+        file_path: tool_result
+            .get("filePath")
+            .and_then(|p| p.as_str())
+            .unwrap_or("")
+            .to_string(),
+        hunks: patches.iter().map(DiffHunk::from).collect(),
+    })
+}
+
+fn diff_from_tool_input(name: &str, input: &Value) -> Option<EditDiff> {
+    let file_path = input
+        .get("file_path")
+        .and_then(|p| p.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let edits: Vec<(&str, &str)> = match name {
+        "Edit" => vec![(
+            input.get("old_string")?.as_str()?,
+            input.get("new_string")?.as_str()?,
+        )],
+        "MultiEdit" => input
+            .get("edits")?
+            .as_array()?
+            .iter()
+            .filter_map(|edit| {
+                Some((
+                    edit.get("old_string")?.as_str()?,
+                    edit.get("new_string")?.as_str()?,
+                ))
+            })
+            .collect(),
+        _ => return None,
+    };
+
+    if edits.is_empty() {
+        return None;
+    }
+
+    let total = edits.len();
+    let hunks = edits
+        .into_iter()
+        .enumerate()
+        .map(|(i, (old, new))| DiffHunk {
+            header: if total > 1 {
+                format!("@@ edit {}/{total} @@", i + 1)
+            } else {
+                "@@ edit @@".to_string()
+            },
+            lines: diff_lines(old, new),
+        })
+        .collect();
+
+    Some(EditDiff { file_path, hunks })
+}
+
+/// Line diff of two strings using a longest common subsequence table
+pub fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let (n, m) = (old_lines.len(), new_lines.len());
+
+    if n.saturating_mul(m) > MAX_DIFF_CELLS {
+        return old_lines
+            .iter()
+            .map(|l| format!("-{l}"))
+            .chain(new_lines.iter().map(|l| format!("+{l}")))
+            .collect();
+    }
+
+    // lcs[i][j] = length of the LCS of old_lines[i..] and new_lines[j..]
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_lines[i] == new_lines[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_lines[i] == new_lines[j] {
+            result.push(format!(" {}", old_lines[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            result.push(format!("-{}", old_lines[i]));
+            i += 1;
+        } else {
+            result.push(format!("+{}", new_lines[j]));
+            j += 1;
+        }
+    }
+    result.extend(old_lines[i..].iter().map(|l| format!("-{l}")));
+    result.extend(new_lines[j..].iter().map(|l| format!("+{l}")));
+    result
+}
+
+/// Render tool views as styled lines wrapped to `max_width` columns
+pub fn render_tool_views(views: &[ToolView], max_width: usize) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    for view in views {
+        lines.push(Line::from(""));
+        match view {
+            ToolView::Diff(diff) => render_diff(diff, max_width, &mut lines),
+            ToolView::Todos(todos) => render_todos(todos, max_width, &mut lines),
+            ToolView::WebSearch(search) => render_web_search(search, max_width, &mut lines),
+        }
+    }
+
+    lines
+}
+
+fn render_diff(diff: &EditDiff, max_width: usize, lines: &mut Vec<Line<'static>>) {
+    let file_style = Style::default().add_modifier(Modifier::BOLD);
+    push_wrapped(
+        lines,
+        &format!("--- {}", diff.file_path),
+        file_style,
+        max_width,
+    );
+    push_wrapped(
+        lines,
+        &format!("+++ {}", diff.file_path),
+        file_style,
+        max_width,
+    );
+
+    for hunk in &diff.hunks {
+        push_wrapped(
+            lines,
+            &hunk.header,
+            Style::default().fg(theme::current().primary),
+            max_width,
+        );
+        for line in &hunk.lines {
+            let style = match line.chars().next() {
+                Some('+') => Style::default().fg(theme::current().success),
+                Some('-') => Style::default().fg(theme::current().error),
+                _ => Style::default(),
+            };
+            push_wrapped(lines, line, style, max_width);
+        }
+    }
+}
+
+fn render_todos(todos: &[TodoItem], max_width: usize, lines: &mut Vec<Line<'static>>) {
+    let done = todos.iter().filter(|t| t.status == "completed").count();
+    push_wrapped(
+        lines,
+        &format!("Todos ({done}/{} done)", todos.len()),
+        Style::default().add_modifier(Modifier::BOLD),
+        max_width,
+    );
+
+    for todo in todos {
+        let style = match todo.status.as_str() {
+            "completed" => Style::default().fg(theme::current().text_dim),
+            "in_progress" => Style::default().fg(theme::current().secondary),
+            _ => Style::default(),
+        };
+        push_wrapped(
+            lines,
+            &format!("{} {}", todo_status_glyph(&todo.status), todo.content),
+            style,
+            max_width,
+        );
+    }
+}
+
+fn render_web_search(search: &WebSearchLinks, max_width: usize, lines: &mut Vec<Line<'static>>) {
+    push_wrapped(
+        lines,
+        &format!("Web search: {}", search.query),
+        Style::default().add_modifier(Modifier::BOLD),
+        max_width,
+    );
+
+    for (title, url) in &search.results {
+        push_wrapped(lines, &format!("• {title}"), Style::default(), max_width);
+        push_wrapped(
+            lines,
+            &format!("  {url}"),
+            Style::default()
+                .fg(theme::current().primary)
+                .add_modifier(Modifier::UNDERLINED),
+            max_width,
+        );
+    }
+}
+
+fn push_wrapped(lines: &mut Vec<Line<'static>>, text: &str, style: Style, max_width: usize) {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() || max_width == 0 {
+        lines.push(Line::from(Span::styled(text.to_string(), style)));
+        return;
+    }
+
+    for chunk in chars.chunks(max_width) {
+        lines.push(Line::from(Span::styled(
+            chunk.iter().collect::<String>(),
+            style,
+        )));
+    }
+}
@@ -0,0 +1,194 @@
+#[cfg(test)]
+mod tests {
+    use super::super::tool_view::*;
+    use ratatui::style::Color;
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines("a\nb\nc", "a\nB\nc\nd");
+        assert_eq!(diff, vec![" a", "-b", "+B", " c", "+d"]);
+
+        assert_eq!(diff_lines("", "new"), vec!["+new"]);
+        assert_eq!(diff_lines("old", ""), vec!["-old"]);
+    }
+
+    #[test]
+    fn test_extract_edit_tool_use() {
+        let raw = r#"{"type":"assistant","message":{"content":[
+            {"type":"text","text":"Fixing it"},
+            {"type":"tool_use","id":"t1","name":"Edit","input":{"file_path":"/src/lib.rs","old_string":"let a = 1;","new_string":"let a = 2;"}}
+        ]}}"#;
+
+        let views = extract_tool_views(raw);
+        assert_eq!(views.len(), 1);
+        let ToolView::Diff(diff) = &views[0] else {
+            panic!("expected a diff view");
+        };
+        assert_eq!(diff.file_path, "/src/lib.rs");
+        assert_eq!(diff.hunks.len(), 1);
+        assert_eq!(diff.hunks[0].header, "@@ edit @@");
+        assert_eq!(diff.hunks[0].lines, vec!["-let a = 1;", "+let a = 2;"]);
+    }
+
+    #[test]
+    fn test_extract_multi_edit_tool_use() {
+        let raw = r#"{"type":"assistant","message":{"content":[
+            {"type":"tool_use","id":"t1","name":"MultiEdit","input":{"file_path":"/a.rs","edits":[
+                {"old_string":"x","new_string":"y"},
+                {"old_string":"p","new_string":"q","replace_all":true}
+            ]}}
+        ]}}"#;
+
+        let views = extract_tool_views(raw);
+        let ToolView::Diff(diff) = &views[0] else {
+            panic!("expected a diff view");
+        };
+        assert_eq!(diff.hunks.len(), 2);
+        assert_eq!(diff.hunks[1].header, "@@ edit 2/2 @@");
+    }
+
+    #[test]
+    fn test_extract_structured_patch() {
+        let raw = r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]},
+            "toolUseResult":{"filePath":"/b.rs","oldString":"x","newString":"y","structuredPatch":[
+                {"oldStart":3,"oldLines":2,"newStart":3,"newLines":2,"lines":[" keep","-x","+y"]}
+            ]}}"#;
+
+        let views = extract_tool_views(raw);
+        assert_eq!(views.len(), 1);
+        let ToolView::Diff(diff) = &views[0] else {
+            panic!("expected a diff view");
+        };
+        assert_eq!(diff.file_path, "/b.rs");
+        assert_eq!(diff.hunks[0].header, "@@ -3,2 +3,2 @@");
+        assert_eq!(diff.hunks[0].lines, vec![" keep", "-x", "+y"]);
+    }
+
+    #[test]
+    fn test_extract_ignores_other_tools_and_bad_json() {
+        let raw = r#"{"type":"assistant","message":{"content":[
+            {"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}
+        ]}}"#;
+        assert!(extract_tool_views(raw).is_empty());
+        assert!(extract_tool_views("not json").is_empty());
+    }
+
+    #[test]
+    fn test_render_diff_colors() {
+        let views = vec![ToolView::Diff(EditDiff {
+            file_path: "/c.rs".to_string(),
+            hunks: vec![DiffHunk {
+                header: "@@ edit @@".to_string(),
+                lines: vec![" same".to_string(), "-old".to_string(), "+new".to_string()],
+            }],
+        })];
+
+        let lines = render_tool_views(&views, 80);
+        let texts: Vec<String> = lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                "",
+                "--- /c.rs",
+                "+++ /c.rs",
+                "@@ edit @@",
+                " same",
+                "-old",
+                "+new"
+            ]
+        );
+        assert_eq!(lines[3].spans[0].style.fg, Some(Color::Cyan));
+        assert_eq!(lines[5].spans[0].style.fg, Some(Color::Red));
+        assert_eq!(lines[6].spans[0].style.fg, Some(Color::Green));
+    }
+
+    #[test]
+    fn test_render_diff_wraps_long_lines() {
+        let views = vec![ToolView::Diff(EditDiff {
+            file_path: "f".to_string(),
+            hunks: vec![DiffHunk {
+                header: "@@ edit @@".to_string(),
+                lines: vec![format!("+{}", "x".repeat(25))],
+            }],
+        })];
+
+        let lines = render_tool_views(&views, 10);
+        // blank, ---, +++, header, then 26 chars over three lines
+        assert_eq!(lines.len(), 4 + 3);
+        assert!(
+            lines[4..]
+                .iter()
+                .all(|l| l.spans[0].style.fg == Some(Color::Green))
+        );
+    }
+
+    #[test]
+    fn test_extract_and_render_todos() {
+        let raw = r#"{"type":"assistant","message":{"content":[
+            {"type":"tool_use","id":"t1","name":"TodoWrite","input":{"todos":[
+                {"content":"Write parser","status":"completed","activeForm":"Writing parser"},
+                {"content":"Add tests","status":"in_progress","activeForm":"Adding tests"},
+                {"content":"Update docs","status":"pending","activeForm":"Updating docs"}
+            ]}}
+        ]}}"#;
+
+        let views = extract_tool_views(raw);
+        assert_eq!(views.len(), 1);
+        let ToolView::Todos(todos) = &views[0] else {
+            panic!("expected a todo view");
+        };
+        assert_eq!(todos.len(), 3);
+
+        let lines = render_tool_views(&views, 80);
+        let texts: Vec<String> = lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                "",
+                "Todos (1/3 done)",
+                "☑ Write parser",
+                "◐ Add tests",
+                "☐ Update docs"
+            ]
+        );
+        assert_eq!(lines[3].spans[0].style.fg, Some(Color::Yellow));
+    }
+
+    #[test]
+    fn test_extract_and_render_web_search() {
+        let raw = r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]},
+            "toolUseResult":{"query":"ratatui scrollbar","results":[
+                {"tool_use_id":"srv","content":[{"title":"Scrollbar","url":"https://docs.rs/ratatui"}]},
+                "Summary text"
+            ],"durationSeconds":1.0}}"#;
+
+        let views = extract_tool_views(raw);
+        assert_eq!(views.len(), 1);
+        let ToolView::WebSearch(search) = &views[0] else {
+            panic!("expected a web search view");
+        };
+        assert_eq!(search.query, "ratatui scrollbar");
+
+        let lines = render_tool_views(&views, 80);
+        let texts: Vec<String> = lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                "",
+                "Web search: ratatui scrollbar",
+                "• Scrollbar",
+                "  https://docs.rs/ratatui"
+            ]
+        );
+        assert_eq!(lines[3].spans[0].style.fg, Some(Color::Cyan));
+    }
+}
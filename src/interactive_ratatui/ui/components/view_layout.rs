@@ -1,10 +1,11 @@
 use crate::interactive_ratatui::constants::*;
+use crate::interactive_ratatui::ui::theme;
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
 };
 
 pub struct ViewLayout {
@@ -41,17 +42,12 @@ impl ViewLayout {
 
     fn calculate_title_bar_height(&self, width: u16) -> u16 {
         // Create a temporary paragraph to measure its height
-        let mut lines = vec![Line::from(vec![Span::styled(
-            &self.title,
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )])];
+        let mut lines = vec![Line::from(vec![Span::styled(&self.title, Styles::title())])];
 
         if let Some(ref subtitle) = self.subtitle {
             for line in subtitle.lines() {
                 lines.push(Line::from(vec![
-                    Span::styled("", Style::default().fg(Color::DarkGray)),
+                    Span::styled("", Styles::subtitle()),
                     Span::raw(line),
                 ]));
             }
@@ -123,18 +119,13 @@ impl ViewLayout {
     }
 
     fn render_title_bar(&self, f: &mut Frame, area: Rect) {
-        let mut title_lines = vec![Line::from(vec![Span::styled(
-            &self.title,
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )])];
+        let mut title_lines = vec![Line::from(vec![Span::styled(&self.title, Styles::title())])];
 
         if let Some(ref subtitle) = self.subtitle {
             // Split subtitle by newlines to support multi-line subtitles
             for line in subtitle.lines() {
                 title_lines.push(Line::from(vec![
-                    Span::styled("", Style::default().fg(Color::DarkGray)),
+                    Span::styled("", Styles::subtitle()),
                     Span::raw(line),
                 ]));
             }
@@ -155,7 +146,7 @@ impl ViewLayout {
             .unwrap_or("↑/↓: Navigate | Enter: Select | Esc: Back | ?: Help");
 
         let status_bar = Paragraph::new(status_text)
-            .style(Style::default().fg(Color::DarkGray))
+            .style(Styles::dimmed())
             .alignment(ratatui::layout::Alignment::Center)
             .wrap(Wrap { trim: true });
 
@@ -163,20 +154,37 @@ impl ViewLayout {
     }
 }
 
-// Helper struct for consistent color scheme
-pub struct ColorScheme;
-
-impl ColorScheme {
-    pub const PRIMARY: Color = Color::Cyan;
-    pub const SECONDARY: Color = Color::Yellow;
-    pub const ACCENT: Color = Color::Magenta;
-    pub const TEXT: Color = Color::White;
-    pub const TEXT_DIM: Color = Color::DarkGray;
-    pub const BACKGROUND: Color = Color::Black;
-    pub const SELECTION: Color = Color::DarkGray;
-    pub const SUCCESS: Color = Color::Green;
-    pub const WARNING: Color = Color::Yellow;
-    pub const ERROR: Color = Color::Red;
+/// Render a vertical scrollbar on the right border of a bordered block.
+///
+/// `content_length` is the number of scroll positions (items for lists, scroll
+/// offsets for text) and `position` the current one. Callers only invoke this when
+/// the content overflows the viewport.
+pub fn render_vertical_scrollbar(
+    f: &mut Frame,
+    area: Rect,
+    content_length: usize,
+    viewport_length: usize,
+    position: usize,
+) {
+    if content_length == 0 || area.height <= 2 {
+        return;
+    }
+
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(Some("↑"))
+        .end_symbol(Some("↓"))
+        .style(Style::default().fg(theme::current().text_dim))
+        .thumb_style(Style::default().fg(theme::current().primary));
+    let mut state = ScrollbarState::new(content_length)
+        .viewport_content_length(viewport_length)
+        .position(position);
+
+    // Keep the corners of the block border intact
+    let scrollbar_area = area.inner(Margin {
+        vertical: 1,
+        horizontal: 0,
+    });
+    f.render_stateful_widget(scrollbar, scrollbar_area, &mut state);
 }
 
 // Helper struct for consistent styling
@@ -185,55 +193,55 @@ pub struct Styles;
 impl Styles {
     pub fn title() -> Style {
         Style::default()
-            .fg(ColorScheme::PRIMARY)
+            .fg(theme::current().primary)
             .add_modifier(Modifier::BOLD)
     }
 
     pub fn subtitle() -> Style {
-        Style::default().fg(ColorScheme::TEXT_DIM)
+        Style::default().fg(theme::current().text_dim)
     }
 
     pub fn label() -> Style {
-        Style::default().fg(ColorScheme::SECONDARY)
+        Style::default().fg(theme::current().secondary)
     }
 
     pub fn selected() -> Style {
         Style::default()
-            .bg(ColorScheme::SELECTION)
+            .bg(theme::current().selection)
             .add_modifier(Modifier::BOLD)
     }
 
     pub fn normal() -> Style {
-        Style::default().fg(ColorScheme::TEXT)
+        Style::default().fg(theme::current().text)
     }
 
     pub fn dimmed() -> Style {
-        Style::default().fg(ColorScheme::TEXT_DIM)
+        Style::default().fg(theme::current().text_dim)
     }
 
     pub fn action_key() -> Style {
-        Style::default().fg(ColorScheme::SECONDARY)
+        Style::default().fg(theme::current().secondary)
     }
 
     pub fn action_description() -> Style {
-        Style::default().fg(ColorScheme::TEXT)
+        Style::default().fg(theme::current().text)
     }
 
     pub fn success() -> Style {
         Style::default()
-            .fg(ColorScheme::SUCCESS)
+            .fg(theme::current().success)
             .add_modifier(Modifier::BOLD)
     }
 
     pub fn warning() -> Style {
         Style::default()
-            .fg(ColorScheme::WARNING)
+            .fg(theme::current().warning)
             .add_modifier(Modifier::BOLD)
     }
 
     pub fn error() -> Style {
         Style::default()
-            .fg(ColorScheme::ERROR)
+            .fg(theme::current().error)
             .add_modifier(Modifier::BOLD)
     }
 }
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use super::super::view_layout::{ColorScheme, Styles, ViewLayout};
+    use super::super::view_layout::{Styles, ViewLayout};
+    use crate::interactive_ratatui::ui::theme;
     use ratatui::{
         Terminal,
         backend::TestBackend,
@@ -125,17 +126,11 @@ mod tests {
     }
 
     #[test]
-    fn test_color_scheme_constants() {
-        assert_eq!(ColorScheme::PRIMARY, Color::Cyan);
-        assert_eq!(ColorScheme::SECONDARY, Color::Yellow);
-        assert_eq!(ColorScheme::ACCENT, Color::Magenta);
-        assert_eq!(ColorScheme::TEXT, Color::White);
-        assert_eq!(ColorScheme::TEXT_DIM, Color::DarkGray);
-        assert_eq!(ColorScheme::BACKGROUND, Color::Black);
-        assert_eq!(ColorScheme::SELECTION, Color::DarkGray);
-        assert_eq!(ColorScheme::SUCCESS, Color::Green);
-        assert_eq!(ColorScheme::WARNING, Color::Yellow);
-        assert_eq!(ColorScheme::ERROR, Color::Red);
+    fn test_styles_follow_the_theme() {
+        let theme = theme::current();
+        assert_eq!(Styles::title().fg, Some(theme.primary));
+        assert_eq!(Styles::selected().bg, Some(theme.selection));
+        assert_eq!(Styles::error().fg, Some(theme.error));
     }
 
     #[test]
@@ -200,51 +195,51 @@ mod tests {
     fn test_styles() {
         // Test title style
         let title_style = Styles::title();
-        assert_eq!(title_style.fg, Some(ColorScheme::PRIMARY));
+        assert_eq!(title_style.fg, Some(Color::Cyan));
         assert!(title_style.add_modifier.contains(Modifier::BOLD));
 
         // Test subtitle style
         let subtitle_style = Styles::subtitle();
-        assert_eq!(subtitle_style.fg, Some(ColorScheme::TEXT_DIM));
+        assert_eq!(subtitle_style.fg, Some(Color::DarkGray));
 
         // Test label style
         let label_style = Styles::label();
-        assert_eq!(label_style.fg, Some(ColorScheme::SECONDARY));
+        assert_eq!(label_style.fg, Some(Color::Yellow));
 
         // Test selected style
         let selected_style = Styles::selected();
-        assert_eq!(selected_style.bg, Some(ColorScheme::SELECTION));
+        assert_eq!(selected_style.bg, Some(Color::DarkGray));
         assert!(selected_style.add_modifier.contains(Modifier::BOLD));
 
         // Test normal style
         let normal_style = Styles::normal();
-        assert_eq!(normal_style.fg, Some(ColorScheme::TEXT));
+        assert_eq!(normal_style.fg, Some(Color::White));
 
         // Test dimmed style
         let dimmed_style = Styles::dimmed();
-        assert_eq!(dimmed_style.fg, Some(ColorScheme::TEXT_DIM));
+        assert_eq!(dimmed_style.fg, Some(Color::DarkGray));
 
         // Test action key style
         let action_key_style = Styles::action_key();
-        assert_eq!(action_key_style.fg, Some(ColorScheme::SECONDARY));
+        assert_eq!(action_key_style.fg, Some(Color::Yellow));
 
         // Test action description style
         let action_desc_style = Styles::action_description();
-        assert_eq!(action_desc_style.fg, Some(ColorScheme::TEXT));
+        assert_eq!(action_desc_style.fg, Some(Color::White));
 
         // Test success style
         let success_style = Styles::success();
-        assert_eq!(success_style.fg, Some(ColorScheme::SUCCESS));
+        assert_eq!(success_style.fg, Some(Color::Green));
         assert!(success_style.add_modifier.contains(Modifier::BOLD));
 
         // Test warning style
         let warning_style = Styles::warning();
-        assert_eq!(warning_style.fg, Some(ColorScheme::WARNING));
+        assert_eq!(warning_style.fg, Some(Color::Yellow));
         assert!(warning_style.add_modifier.contains(Modifier::BOLD));
 
         // Test error style
         let error_style = Styles::error();
-        assert_eq!(error_style.fg, Some(ColorScheme::ERROR));
+        assert_eq!(error_style.fg, Some(Color::Red));
         assert!(error_style.add_modifier.contains(Modifier::BOLD));
     }
 
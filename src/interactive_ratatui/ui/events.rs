@@ -1,6 +1,11 @@
 use crate::interactive_ratatui::application::search_service::SessionData;
-use crate::interactive_ratatui::ui::app_state::SessionInfo;
+use crate::interactive_ratatui::domain::models::{Dismissal, SearchProgress};
+use crate::interactive_ratatui::ui::app_state::{ProjectInfo, SessionInfo};
+use crate::interactive_ratatui::ui::theme::ThemeName;
+use crate::query::SavedSearch;
 use crate::query::condition::SearchResult;
+use crate::usage::ContextPoint;
+use std::collections::BTreeMap;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum CopyContent {
@@ -11,6 +16,9 @@ pub enum CopyContent {
     JsonData(String),
     FullMessageDetails(String),
     SessionMarkdown(String),
+    Urls(String),
+    Diff(String),
+    Jsonl(String),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -19,6 +27,11 @@ pub enum Message {
     QueryChanged(String),
     SearchRequested,
     SearchCompleted(Vec<SearchResult>),
+    CorpusChanged {
+        idle: bool,
+    }, // Session files changed on disk
+    RefreshSearch,
+    SearchBatchReceived(Vec<SearchResult>, SearchProgress), // Partial results while streaming
     LoadMoreResults,
     MoreResultsLoaded(Vec<SearchResult>),
     SelectResult(usize),
@@ -26,6 +39,15 @@ pub enum Message {
     ScrollDown,
     ToggleSearchOrder,
 
+    // Search history
+    HistoryLoaded(Vec<String>),
+    HistoryPrevious, // Up: recall an older query
+    HistoryNext,     // Down: back towards the query being typed
+    StartHistorySearch,
+    HistorySearchChanged(String),
+    AcceptHistorySearch,
+    CancelHistorySearch,
+
     // Mode changes
     EnterMessageDetail,
     EnterSessionViewer,
@@ -49,20 +71,38 @@ pub enum Message {
     ToggleSessionOrder,
     ToggleSessionRoleFilter,
     ToggleSessionPreview,
+    ToggleSessionFollow,
+    ToggleToolResults,
+    SessionMessagesAppended(Vec<SearchResult>), // New messages in the followed session
+    MarkForDiff(Box<SearchResult>),
+    SwitchBranch(String, bool),      // (selected message uuid, forward)
+    DiffBranches(Box<SearchResult>), // Diff the branch of the selected message against another
 
     // Role filter
     ToggleRoleFilter,
 
     // Display options
     TogglePreview,
+    /// Move the preview right, below, or hide it, in the current tab
+    CyclePreviewPosition,
+    /// Grow (positive) or shrink the preview by percent points
+    ResizePreview(i16),
+    /// Size the preview in percent while its border is dragged, without
+    /// saving it
+    SetPreviewSize(u16),
+    ToggleSyntaxHighlight,
+    /// Show or hide the match timeline above the search results
+    ToggleTimeline,
 
     // Tab navigation
     SwitchToSearchTab,
     SwitchToSessionListTab,
+    SwitchToBookmarksTab,
+    SwitchToProjectsTab,
 
     // Session list events
     LoadSessionList,
-    SessionListLoaded(Vec<SessionData>), // (file_path, session_id, timestamp, message_count, first_message, preview_messages, summary)
+    SessionListLoaded(Vec<SessionData>), // (file_path, session_id, timestamp, message_count, first_message, preview_messages, summary, outcome, compactions, cost_usd)
     SessionListQueryChanged(String),
     SessionListSearchRequested,
     SessionListSearchCompleted(Vec<SessionInfo>),
@@ -74,11 +114,55 @@ pub enum Message {
     SessionListHalfPageUp,
     SessionListHalfPageDown,
     ToggleSessionListPreview,
+    ToggleSessionListCost,
     EnterSessionViewerFromList(String), // file_path
     ConvertSessionToCodex,
 
+    // Multi-select in search results
+    ToggleResultSelection,
+    ToggleSelectAll,
+    CopySelection,
+    ExportSelection,
+    ViewSelection,
+
+    // Dismissing results while triaging them
+    DismissResult,    // Alt+X: the picked results, else the highlighted one
+    RestoreDismissed, // Alt+U: every result dismissed from the query
+    DismissalsLoaded(BTreeMap<String, Vec<Dismissal>>),
+
+    // Bookmarks
+    ToggleBookmark(Box<SearchResult>),
+    BookmarksLoaded(Vec<SearchResult>),
+
+    // Projects
+    ProjectsLoaded(Vec<ProjectInfo>),
+    ScopeToProject(Option<ProjectInfo>), // None searches every project again
+
     // Clipboard
     CopyToClipboard(CopyContent),
+    CompareWithClipboard(Box<SearchResult>),
+    ClipboardRead(Box<SearchResult>, String), // (message, clipboard text)
+
+    // Token context chart of the open session
+    ShowContextChart,
+    ContextChartLoaded(Vec<ContextPoint>),
+    CloseContextChart,
+
+    // Theme picker (F2)
+    ShowThemePicker,
+    HighlightTheme(ThemeName),
+    ApplyTheme(ThemeName),
+    CloseThemePicker,
+
+    // Saved-searches palette (F3)
+    ShowSavedSearches(Vec<(String, SavedSearch)>),
+    HighlightSavedSearch(usize),
+    ApplySavedSearch(usize),
+    CloseSavedSearches,
+
+    // External pager and editor
+    OpenInPager(String),
+    OpenInEditor(String, Option<usize>), // (file, line of the message)
 
     // Async events
     SearchStarted(u64),
@@ -0,0 +1,329 @@
+//! Lightweight syntax highlighting for fenced code blocks in messages.
+//!
+//! Lines are tokenized one at a time, so constructs spanning lines (block
+//! comments, multi-line strings) are only colored on the line they start.
+
+use crate::interactive_ratatui::ui::theme;
+use ratatui::{
+    style::{Modifier, Style},
+    text::Span,
+};
+
+/// Token rules of one language family
+struct Syntax {
+    keywords: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    /// Whether `'` starts a string rather than a lifetime or char literal
+    single_quote_strings: bool,
+    backtick_strings: bool,
+}
+
+const RUST: Syntax = Syntax {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+    line_comments: &["//"],
+    single_quote_strings: false,
+    backtick_strings: false,
+};
+
+const PYTHON: Syntax = Syntax {
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "False", "finally", "for", "from", "global", "if", "import",
+        "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True",
+        "try", "while", "with", "yield",
+    ],
+    line_comments: &["#"],
+    single_quote_strings: true,
+    backtick_strings: false,
+};
+
+const JAVASCRIPT: Syntax = Syntax {
+    keywords: &[
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "null",
+        "of",
+        "return",
+        "static",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "type",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "yield",
+    ],
+    line_comments: &["//"],
+    single_quote_strings: true,
+    backtick_strings: true,
+};
+
+const GO: Syntax = Syntax {
+    keywords: &[
+        "break",
+        "case",
+        "chan",
+        "const",
+        "continue",
+        "default",
+        "defer",
+        "else",
+        "false",
+        "for",
+        "func",
+        "go",
+        "if",
+        "import",
+        "interface",
+        "map",
+        "nil",
+        "package",
+        "range",
+        "return",
+        "select",
+        "struct",
+        "switch",
+        "true",
+        "type",
+        "var",
+    ],
+    line_comments: &["//"],
+    single_quote_strings: false,
+    backtick_strings: true,
+};
+
+const C_LIKE: Syntax = Syntax {
+    keywords: &[
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "do",
+        "else",
+        "enum",
+        "extends",
+        "false",
+        "final",
+        "for",
+        "if",
+        "implements",
+        "import",
+        "include",
+        "new",
+        "null",
+        "nullptr",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "static",
+        "struct",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typedef",
+        "void",
+        "while",
+    ],
+    line_comments: &["//"],
+    single_quote_strings: true,
+    backtick_strings: false,
+};
+
+const SHELL: Syntax = Syntax {
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+        "in", "local", "return", "then", "until", "while",
+    ],
+    line_comments: &["#"],
+    single_quote_strings: true,
+    backtick_strings: true,
+};
+
+const RUBY: Syntax = Syntax {
+    keywords: &[
+        "begin", "class", "def", "do", "else", "elsif", "end", "ensure", "false", "if", "module",
+        "nil", "require", "rescue", "return", "self", "true", "unless", "until", "when", "while",
+        "yield",
+    ],
+    line_comments: &["#"],
+    single_quote_strings: true,
+    backtick_strings: false,
+};
+
+const SQL: Syntax = Syntax {
+    keywords: &[
+        "and", "as", "by", "create", "delete", "from", "group", "insert", "into", "join", "limit",
+        "not", "null", "on", "or", "order", "select", "set", "table", "update", "values", "where",
+        "AND", "AS", "BY", "CREATE", "DELETE", "FROM", "GROUP", "INSERT", "INTO", "JOIN", "LIMIT",
+        "NOT", "NULL", "ON", "OR", "ORDER", "SELECT", "SET", "TABLE", "UPDATE", "VALUES", "WHERE",
+    ],
+    line_comments: &["--"],
+    single_quote_strings: true,
+    backtick_strings: false,
+};
+
+/// Data formats: strings, numbers and literals only
+const DATA: Syntax = Syntax {
+    keywords: &["true", "false", "null"],
+    line_comments: &["#"],
+    single_quote_strings: true,
+    backtick_strings: false,
+};
+
+/// Rules for a fence info string such as `rust` or `ts title="x"`
+fn syntax_for(lang: &str) -> Option<&'static Syntax> {
+    let lang = lang.split_whitespace().next()?.to_ascii_lowercase();
+    Some(match lang.as_str() {
+        "rust" | "rs" => &RUST,
+        "python" | "py" => &PYTHON,
+        "javascript" | "js" | "jsx" | "typescript" | "ts" | "tsx" => &JAVASCRIPT,
+        "go" | "golang" => &GO,
+        "c" | "h" | "cpp" | "c++" | "java" | "kotlin" | "swift" | "csharp" | "cs" => &C_LIKE,
+        "sh" | "bash" | "zsh" | "shell" | "console" => &SHELL,
+        "ruby" | "rb" => &RUBY,
+        "sql" => &SQL,
+        "json" | "jsonl" | "toml" | "yaml" | "yml" => &DATA,
+        _ => return None,
+    })
+}
+
+fn keyword_style() -> Style {
+    Style::default().fg(theme::current().accent)
+}
+
+fn string_style() -> Style {
+    Style::default().fg(theme::current().success)
+}
+
+fn comment_style() -> Style {
+    Style::default()
+        .fg(theme::current().text_dim)
+        .add_modifier(Modifier::ITALIC)
+}
+
+fn number_style() -> Style {
+    Style::default().fg(theme::current().secondary)
+}
+
+/// Style of the ``` lines opening and closing a code block
+pub fn fence_style() -> Style {
+    Style::default().fg(theme::current().text_dim)
+}
+
+/// Highlighted spans of one line of a code block in `lang`, or `None` when
+/// the language is not recognized
+pub fn highlight_line(line: &str, lang: &str) -> Option<Vec<Span<'static>>> {
+    let syntax = syntax_for(lang)?;
+    let mut spans = Vec::new();
+    let mut plain_start = 0;
+    let mut pos = 0;
+
+    while let Some(c) = line[pos..].chars().next() {
+        let rest = &line[pos..];
+        let token = if syntax
+            .line_comments
+            .iter()
+            .any(|prefix| rest.starts_with(prefix))
+            // `#` inside a word, as in `a#b` or `$#`, is not a comment
+            && (c != '#' || line[..pos].ends_with(char::is_whitespace) || pos == 0)
+        {
+            Some((rest.len(), comment_style()))
+        } else if c == '"'
+            || (c == '\'' && syntax.single_quote_strings)
+            || (c == '`' && syntax.backtick_strings)
+        {
+            Some((string_len(rest, c), string_style()))
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            Some((len, number_style()))
+        } else if is_word_char(c) {
+            let len = rest.find(|c| !is_word_char(c)).unwrap_or(rest.len());
+            if syntax.keywords.contains(&&rest[..len]) {
+                Some((len, keyword_style()))
+            } else {
+                pos += len;
+                continue;
+            }
+        } else {
+            None
+        };
+
+        match token {
+            Some((len, style)) => {
+                if plain_start < pos {
+                    spans.push(Span::raw(line[plain_start..pos].to_string()));
+                }
+                spans.push(Span::styled(rest[..len].to_string(), style));
+                pos += len;
+                plain_start = pos;
+            }
+            None => pos += c.len_utf8(),
+        }
+    }
+    if plain_start < line.len() {
+        spans.push(Span::raw(line[plain_start..].to_string()));
+    }
+
+    Some(spans)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Byte length of the string literal opening `rest`, closing quote included;
+/// an unterminated string runs to the end of the line
+fn string_len(rest: &str, quote: char) -> usize {
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return i + c.len_utf8();
+        }
+    }
+    rest.len()
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use super::super::highlight::*;
+    use ratatui::style::Color;
+    use ratatui::text::Span;
+
+    fn styled<'a>(spans: &'a [Span<'static>], color: Color) -> Vec<&'a str> {
+        spans
+            .iter()
+            .filter(|span| span.style.fg == Some(color))
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_highlight_rust_line() {
+        let line = r#"    let path = "src/main.rs"; // entry point 42"#;
+        let spans = highlight_line(line, "rust").unwrap();
+
+        let text: String = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(text, line);
+        assert_eq!(styled(&spans, Color::Magenta), vec!["let"]);
+        assert_eq!(styled(&spans, Color::Green), vec!["\"src/main.rs\""]);
+        assert_eq!(styled(&spans, Color::DarkGray), vec!["// entry point 42"]);
+    }
+
+    #[test]
+    fn test_highlight_words_and_numbers() {
+        let spans = highlight_line("lettuce = 3.14 if letter else x2", "python").unwrap();
+        assert_eq!(styled(&spans, Color::Magenta), vec!["if", "else"]);
+        assert_eq!(styled(&spans, Color::Yellow), vec!["3.14"]);
+    }
+
+    #[test]
+    fn test_highlight_strings_and_comments() {
+        // Escaped quotes stay inside the string
+        let spans = highlight_line(r#"echo "a \"b\" c" # done"#, "bash").unwrap();
+        assert_eq!(styled(&spans, Color::Green), vec![r#""a \"b\" c""#]);
+        assert_eq!(styled(&spans, Color::DarkGray), vec!["# done"]);
+
+        // `#` inside a word is not a comment
+        let spans = highlight_line("echo $#", "sh").unwrap();
+        assert!(styled(&spans, Color::DarkGray).is_empty());
+
+        // Unterminated strings run to the end of the line
+        let spans = highlight_line("const s = `abc", "ts").unwrap();
+        assert_eq!(styled(&spans, Color::Green), vec!["`abc"]);
+    }
+
+    #[test]
+    fn test_unknown_language() {
+        assert!(highlight_line("let x = 1;", "").is_none());
+        assert!(highlight_line("let x = 1;", "brainfuck").is_none());
+        assert!(highlight_line("let x = 1;", "Rust title=\"x\"").is_some());
+    }
+}
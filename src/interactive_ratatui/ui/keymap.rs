@@ -0,0 +1,98 @@
+use anyhow::{Context, Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+
+/// Extra keys from the `[keybindings]` table of config.toml, each standing in
+/// for a key the interactive mode already understands
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyMap {
+    bindings: HashMap<(KeyCode, KeyModifiers), (KeyCode, KeyModifiers)>,
+}
+
+impl KeyMap {
+    /// Build a key map from `"pressed key" = "key it acts as"` pairs
+    pub fn from_config(bindings: &BTreeMap<String, String>) -> Result<Self> {
+        let bindings = bindings
+            .iter()
+            .map(|(from, to)| {
+                let from =
+                    parse_key(from).with_context(|| format!("invalid keybinding '{from}'"))?;
+                let to =
+                    parse_key(to).with_context(|| format!("invalid keybinding target '{to}'"))?;
+                Ok((from, to))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { bindings })
+    }
+
+    /// The key `key` acts as; keys without a binding are returned unchanged
+    pub fn translate(&self, key: KeyEvent) -> KeyEvent {
+        let mut modifiers = key.modifiers;
+        // Terminals report an upper-case letter with Shift held; the letter says it already
+        if matches!(key.code, KeyCode::Char(_)) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        match self.bindings.get(&(key.code, modifiers)) {
+            Some(&(code, modifiers)) => KeyEvent {
+                code,
+                modifiers,
+                ..key
+            },
+            None => key,
+        }
+    }
+}
+
+/// Parse a key like `ctrl+j`, `alt+shift+left`, `f2` or `x`
+pub fn parse_key(spec: &str) -> Result<(KeyCode, KeyModifiers)> {
+    let mut parts: Vec<&str> = spec.split('+').collect();
+    // `ctrl++` binds the plus key itself
+    if spec.ends_with("++") {
+        parts.truncate(parts.len() - 2);
+        parts.push("+");
+    }
+    let Some((key, modifier_names)) = parts.split_last() else {
+        bail!("empty key");
+    };
+
+    let mut modifiers = KeyModifiers::NONE;
+    for name in modifier_names {
+        modifiers |= match name.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" | "meta" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => bail!("unknown modifier '{name}' (use ctrl, alt or shift)"),
+        };
+    }
+
+    let mut chars = key.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match key.to_ascii_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "space" => KeyCode::Char(' '),
+            name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => bail!("unknown key '{key}'"),
+            },
+        },
+    };
+    if matches!(code, KeyCode::Char(_)) {
+        modifiers.remove(KeyModifiers::SHIFT);
+    }
+    Ok((code, modifiers))
+}
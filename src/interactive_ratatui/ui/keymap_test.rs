@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use super::super::keymap::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::collections::BTreeMap;
+
+    fn key_map(bindings: &[(&str, &str)]) -> anyhow::Result<KeyMap> {
+        let bindings: BTreeMap<String, String> = bindings
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect();
+        KeyMap::from_config(&bindings)
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(
+            parse_key("ctrl+j").unwrap(),
+            (KeyCode::Char('j'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key("Alt+Shift+Left").unwrap(),
+            (KeyCode::Left, KeyModifiers::ALT | KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            parse_key("f2").unwrap(),
+            (KeyCode::F(2), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_key("ctrl++").unwrap(),
+            (KeyCode::Char('+'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key("shift+X").unwrap(),
+            (KeyCode::Char('X'), KeyModifiers::NONE)
+        );
+        for spec in ["", "hyper+j", "f13", "pgdn", "ctrl+"] {
+            assert!(parse_key(spec).is_err(), "{spec}");
+        }
+    }
+
+    #[test]
+    fn test_translate() {
+        let keys = key_map(&[("ctrl+j", "down"), ("Q", "esc")]).unwrap();
+
+        let remapped = keys.translate(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL));
+        assert_eq!(remapped.code, KeyCode::Down);
+        assert_eq!(remapped.modifiers, KeyModifiers::NONE);
+        assert_eq!(
+            keys.translate(KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::SHIFT))
+                .code,
+            KeyCode::Esc
+        );
+
+        let unbound = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+        assert_eq!(keys.translate(unbound), unbound);
+        assert_eq!(KeyMap::default().translate(unbound), unbound);
+    }
+
+    #[test]
+    fn test_invalid_bindings() {
+        assert!(key_map(&[("ctrl+j", "downward")]).is_err());
+        assert!(key_map(&[("super+j", "down")]).is_err());
+    }
+}
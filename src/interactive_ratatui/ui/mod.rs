@@ -2,8 +2,17 @@ pub mod app_state;
 pub mod commands;
 pub mod components;
 pub mod events;
+pub mod highlight;
+pub mod keymap;
 pub mod navigation;
 pub mod renderer;
+pub mod theme;
 
 #[cfg(test)]
 mod app_state_test;
+#[cfg(test)]
+mod highlight_test;
+#[cfg(test)]
+mod keymap_test;
+#[cfg(test)]
+mod theme_test;
@@ -1,5 +1,5 @@
 use crate::interactive_ratatui::domain::models::{Mode, SearchOrder, SearchTab, SessionOrder};
-use crate::query::condition::SearchResult;
+use crate::query::condition::{QueryCondition, SearchResult};
 
 /// Represents a complete navigation state that can be restored
 #[derive(Clone, Debug)]
@@ -36,6 +36,7 @@ pub struct SessionStateSnapshot {
     pub file_path: Option<String>,
     pub session_id: Option<String>,
     pub role_filter: Option<String>,
+    pub highlight: Option<QueryCondition>,
 }
 
 /// Snapshot of UI state
@@ -46,6 +47,7 @@ pub struct UiStateSnapshot {
     pub selected_result: Option<SearchResult>,
     pub truncation_enabled: bool,
     pub show_help: bool,
+    pub diff_pair: Option<(SearchResult, SearchResult)>,
 }
 
 /// Manages navigation history with back/forward capabilities
@@ -210,6 +212,7 @@ mod tests {
                 file_path: None,
                 session_id: None,
                 role_filter: None,
+                highlight: None,
             },
             ui_state: UiStateSnapshot {
                 message: None,
@@ -217,6 +220,7 @@ mod tests {
                 selected_result: None,
                 truncation_enabled: true,
                 show_help: false,
+                diff_pair: None,
             },
         }
     }
@@ -1,30 +1,56 @@
 use crate::interactive_ratatui::constants::*;
-use crate::interactive_ratatui::domain::models::SearchTab;
-use crate::interactive_ratatui::ui::app_state::{AppState, Mode};
+use crate::interactive_ratatui::domain::models::{PreviewPosition, SearchTab};
+use crate::interactive_ratatui::ui::app_state::{AppState, Mode, SearchState};
 use crate::interactive_ratatui::ui::components::{
-    Component, help_dialog::HelpDialog, is_exit_prompt, message_detail::MessageDetail,
-    message_preview::MessagePreview, result_list::ResultList, search_bar::SearchBar,
-    session_list::SessionList, session_preview::SessionPreview, session_viewer::SessionViewer,
-    tab_bar::TabBar,
+    Component, context_chart::ContextChart, diff_viewer::DiffViewer, help_dialog::HelpDialog,
+    is_exit_prompt, message_detail::MessageDetail, message_preview::MessagePreview,
+    project_list::ProjectList, result_list::ResultList, result_timeline::ResultTimeline,
+    saved_search_picker::SavedSearchPicker, search_bar::SearchBar, session_list::SessionList,
+    session_preview::SessionPreview, session_viewer::SessionViewer, tab_bar::TabBar,
+    theme_picker::ThemePicker, wheel_key,
 };
+use crate::interactive_ratatui::ui::events::Message;
+use crate::interactive_ratatui::ui::theme;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    widgets::Paragraph,
+    layout::{Constraint, Direction, Layout, Position, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, Paragraph},
 };
+use std::collections::HashSet;
+
+/// Key hints shown under the bookmark list
+const BOOKMARKS_STATUS_TEXT: &str = "Shift+Tab: Switch tabs | ↑/↓ or Ctrl+P/N: Navigate | Enter: View details | Ctrl+S: View full session | b/Del: Remove bookmark | Ctrl+T: Toggle preview | ?: Help";
+
+/// Where the last frame drew the list and the preview of the current tab
+#[derive(Debug, Default, Clone, Copy)]
+struct MouseAreas {
+    list: Rect,
+    preview: Option<Rect>,
+}
 
 #[derive(Default)]
 pub struct Renderer {
     search_bar: SearchBar,
     result_list: ResultList,
+    result_timeline: ResultTimeline,
+    bookmark_list: ResultList,
     message_detail: MessageDetail,
     message_preview: MessagePreview,
     session_viewer: SessionViewer,
+    diff_viewer: DiffViewer,
     session_list: SessionList,
     session_preview: SessionPreview,
+    project_list: ProjectList,
     tab_bar: TabBar,
     help_dialog: HelpDialog,
+    context_chart: ContextChart,
+    theme_picker: ThemePicker,
+    saved_search_picker: SavedSearchPicker,
+    mouse_areas: MouseAreas,
+    /// Set while the border of the preview is dragged, true once it moved
+    dragging_preview: Option<bool>,
 }
 
 impl Renderer {
@@ -32,22 +58,59 @@ impl Renderer {
         Self {
             search_bar: SearchBar::new(),
             result_list: ResultList::new(),
+            result_timeline: ResultTimeline::new(),
+            bookmark_list: ResultList::new()
+                .with_heading(
+                    "Bookmarks",
+                    "No bookmarks yet, press b on a message in its detail view or session",
+                )
+                .with_status_text(BOOKMARKS_STATUS_TEXT),
             message_detail: MessageDetail::new(),
             message_preview: MessagePreview::new(),
             session_viewer: SessionViewer::new(),
+            diff_viewer: DiffViewer::new(),
             session_list: SessionList::new(),
             session_preview: SessionPreview::new(),
+            project_list: ProjectList::new(),
             tab_bar: TabBar::new(),
             help_dialog: HelpDialog::new(),
+            context_chart: ContextChart::new(),
+            theme_picker: ThemePicker::new(),
+            saved_search_picker: SavedSearchPicker::new(),
+            mouse_areas: MouseAreas::default(),
+            dragging_preview: None,
         }
     }
 
     pub fn render(&mut self, f: &mut Frame, state: &AppState) {
+        // Paint the theme's background so light themes read on dark terminals
+        let colors = theme::current();
+        f.render_widget(
+            Block::default().style(Style::default().bg(colors.background).fg(colors.text)),
+            f.area(),
+        );
+
         // First render the current mode
         match state.mode {
             Mode::Search => self.render_search_mode(f, state),
             Mode::MessageDetail => self.render_detail_mode(f, state),
             Mode::SessionViewer => self.render_session_mode(f, state),
+            Mode::MessageDiff => self.render_diff_mode(f, state),
+        }
+
+        if let Some(points) = &state.ui.context_chart {
+            self.context_chart.set_points(points.clone());
+            self.context_chart.render(f, f.area());
+        }
+
+        if let Some(name) = state.ui.theme_picker {
+            self.theme_picker.set_highlighted(name);
+            self.theme_picker.render(f, f.area());
+        }
+
+        if let Some(palette) = &state.ui.saved_searches {
+            self.saved_search_picker.set_palette(palette.clone());
+            self.saved_search_picker.render(f, f.area());
         }
 
         // Then render help dialog on top if show_help is true
@@ -81,6 +144,8 @@ impl Renderer {
                 .split(f.area())
         };
 
+        self.mouse_areas = MouseAreas::default();
+
         // Update and render tab bar
         self.tab_bar.set_current_tab(state.search.current_tab);
         self.tab_bar.render(f, chunks[0]);
@@ -96,7 +161,18 @@ impl Renderer {
         }
         self.search_bar
             .set_role_filter(state.search.role_filter.clone());
+        self.search_bar
+            .set_hide_tool_results(state.ui.hide_tool_results);
         self.search_bar.set_search_order(state.search.order);
+        self.search_bar
+            .set_project(state.search.project.as_ref().map(|p| p.name.clone()));
+        self.search_bar
+            .set_history_search(state.history.search.as_ref().map(|search| {
+                (
+                    search.filter.clone(),
+                    state.history.current_match().map(str::to_string),
+                )
+            }));
 
         // Render search bar (only for Search tab)
         if state.search.current_tab == SearchTab::Search {
@@ -107,17 +183,22 @@ impl Renderer {
         match state.search.current_tab {
             SearchTab::Search => {
                 // For Search tab, content is in chunks[2]
-                let content_area = chunks[2];
+                let mut content_area = chunks[2];
+
+                if state.search.timeline_enabled && !state.search.results.is_empty() {
+                    let parts = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Length(TIMELINE_HEIGHT), Constraint::Min(0)])
+                        .split(content_area);
+                    self.result_timeline
+                        .set_results(&state.search.results, state.search.has_more_results);
+                    self.result_timeline.render(f, parts[0]);
+                    content_area = parts[1];
+                }
 
                 if state.search.preview_enabled && !state.search.results.is_empty() {
                     // Split content area into list and preview
-                    let content_chunks = Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([
-                            Constraint::Percentage(40), // Results list
-                            Constraint::Percentage(60), // Preview
-                        ])
-                        .split(content_area);
+                    let (list_area, preview_area) = split_preview(content_area, state);
 
                     // Update result list state
                     self.result_list.set_results(state.search.results.clone());
@@ -131,6 +212,13 @@ impl Renderer {
                         state.search.loading_more,
                         state.search.total_loaded,
                     );
+                    self.result_list.set_search_progress(state.search.progress);
+                    self.result_list.set_selection(
+                        selected_indices(&state.search),
+                        state.search.selection.len(),
+                    );
+                    self.result_list.set_dismissed(state.search.hidden.len());
+                    self.result_list.set_stale(state.search.stale);
 
                     // Update preview state
                     let selected_result = state
@@ -139,10 +227,16 @@ impl Renderer {
                         .get(state.search.selected_index)
                         .cloned();
                     self.message_preview.set_result(selected_result);
+                    self.message_preview
+                        .set_syntax_highlight(state.ui.syntax_highlight);
 
                     // Render both components
-                    self.result_list.render(f, content_chunks[0]);
-                    self.message_preview.render(f, content_chunks[1]);
+                    self.result_list.render(f, list_area);
+                    self.message_preview.render(f, preview_area);
+                    self.mouse_areas = MouseAreas {
+                        list: list_area,
+                        preview: Some(preview_area),
+                    };
                 } else {
                     // No preview - use full width for results
                     self.result_list.set_results(state.search.results.clone());
@@ -156,7 +250,15 @@ impl Renderer {
                         state.search.loading_more,
                         state.search.total_loaded,
                     );
+                    self.result_list.set_search_progress(state.search.progress);
+                    self.result_list.set_selection(
+                        selected_indices(&state.search),
+                        state.search.selection.len(),
+                    );
+                    self.result_list.set_dismissed(state.search.hidden.len());
+                    self.result_list.set_stale(state.search.stale);
                     self.result_list.render(f, content_area);
+                    self.mouse_areas.list = content_area;
                 }
             }
             SearchTab::SessionList => {
@@ -177,6 +279,8 @@ impl Renderer {
                     .set_is_typing(state.session_list.is_typing);
                 self.session_list
                     .set_preview_enabled(state.session_list.preview_enabled);
+                self.session_list
+                    .set_show_cost(state.session_list.show_cost);
 
                 // For SessionList tab, combine the search bar area and content area
                 // This uses chunks[1] (search bar area) and chunks[2] (content area)
@@ -191,13 +295,7 @@ impl Renderer {
                     && !state.session_list.filtered_sessions.is_empty()
                 {
                     // Split the combined area into list and preview
-                    let preview_chunks = Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([
-                            Constraint::Percentage(50), // Session list
-                            Constraint::Percentage(50), // Preview
-                        ])
-                        .split(combined_area);
+                    let (list_area, preview_area) = split_preview(combined_area, state);
 
                     // Update preview state
                     self.session_preview
@@ -206,13 +304,82 @@ impl Renderer {
                         .set_query(state.session_list.query.clone());
 
                     // Render both components
-                    self.session_list.render(f, preview_chunks[0]);
-                    self.session_preview.render(f, preview_chunks[1]);
+                    self.session_list.render(f, list_area);
+                    self.session_preview.render(f, preview_area);
+                    self.mouse_areas = MouseAreas {
+                        list: list_area,
+                        preview: Some(preview_area),
+                    };
                 } else {
                     // No preview - use full width for session list
                     self.session_list.render(f, combined_area);
+                    self.mouse_areas.list = combined_area;
+                }
+            }
+            SearchTab::Bookmarks => {
+                self.bookmark_list
+                    .set_results(state.bookmarks.results.clone());
+                self.bookmark_list
+                    .set_selected_index(state.bookmarks.selected_index);
+                self.bookmark_list
+                    .set_truncation_enabled(state.ui.truncation_enabled);
+                self.bookmark_list.set_pagination_state(
+                    false,
+                    false,
+                    state.bookmarks.results.len(),
+                );
+
+                // Like the session list, the bookmark list has no search bar
+                let combined_area = Rect {
+                    x: chunks[1].x,
+                    y: chunks[1].y,
+                    width: chunks[1].width,
+                    height: chunks[1].height + chunks[2].height,
+                };
+
+                if state.search.preview_enabled && !state.bookmarks.results.is_empty() {
+                    let (list_area, preview_area) = split_preview(combined_area, state);
+
+                    self.bookmark_list.set_preview_enabled(true);
+                    self.message_preview.set_result(
+                        state
+                            .bookmarks
+                            .results
+                            .get(state.bookmarks.selected_index)
+                            .cloned(),
+                    );
+                    self.message_preview
+                        .set_syntax_highlight(state.ui.syntax_highlight);
+
+                    self.bookmark_list.render(f, list_area);
+                    self.message_preview.render(f, preview_area);
+                    self.mouse_areas = MouseAreas {
+                        list: list_area,
+                        preview: Some(preview_area),
+                    };
+                } else {
+                    self.bookmark_list.set_preview_enabled(false);
+                    self.bookmark_list.render(f, combined_area);
+                    self.mouse_areas.list = combined_area;
                 }
             }
+            SearchTab::Projects => {
+                self.project_list
+                    .set_projects(state.projects.projects.clone());
+                self.project_list
+                    .set_scope(state.search.project.as_ref().map(|p| p.dir.clone()));
+                self.project_list.set_is_loading(state.projects.is_loading);
+
+                // No search bar here either
+                let combined_area = Rect {
+                    x: chunks[1].x,
+                    y: chunks[1].y,
+                    width: chunks[1].width,
+                    height: chunks[1].height + chunks[2].height,
+                };
+                self.project_list.render(f, combined_area);
+                self.mouse_areas.list = combined_area;
+            }
         }
 
         // Render exit prompt at bottom if needed
@@ -220,7 +387,7 @@ impl Renderer {
             let exit_prompt = Paragraph::new("Press Ctrl+C again to exit")
                 .style(
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme::current().secondary)
                         .add_modifier(Modifier::BOLD),
                 )
                 .alignment(ratatui::layout::Alignment::Center);
@@ -232,6 +399,8 @@ impl Renderer {
         if let Some(result) = &state.ui.selected_result {
             self.message_detail.set_result(result.clone());
             self.message_detail.set_message(state.ui.message.clone());
+            self.message_detail
+                .set_syntax_highlight(state.ui.syntax_highlight);
             self.message_detail.render(f, f.area());
         }
     }
@@ -259,10 +428,93 @@ impl Renderer {
             .set_selected_index(state.session.selected_index);
         self.session_viewer
             .set_truncation_enabled(state.ui.truncation_enabled);
+        self.session_viewer
+            .set_diff_marked(state.ui.diff_mark.is_some());
+        self.session_viewer.set_following(state.session.following);
+        self.session_viewer
+            .set_hide_tool_results(state.ui.hide_tool_results);
+        let shown = state
+            .session
+            .branches
+            .forks()
+            .iter()
+            .zip(&state.session.branch_choice)
+            .map(|(fork, &choice)| (choice + 1, fork.alternatives.len()))
+            .collect();
+        self.session_viewer
+            .set_branches(state.session.branch_labels(), shown);
 
         self.session_viewer.render(f, f.area());
     }
 
+    fn render_diff_mode(&mut self, f: &mut Frame, state: &AppState) {
+        if let Some((old, new)) = &state.ui.diff_pair {
+            self.diff_viewer.set_pair(old.clone(), new.clone());
+            self.diff_viewer.set_message(state.ui.message.clone());
+            self.diff_viewer.render(f, f.area());
+        }
+    }
+
+    /// The message a mouse event on the last frame stands for
+    pub fn handle_mouse(&mut self, mouse: MouseEvent, state: &AppState) -> Option<Message> {
+        match state.mode {
+            Mode::Search => self.handle_search_mouse(mouse, state),
+            Mode::MessageDetail => scroll_with_keys(&mut self.message_detail, mouse.kind),
+            Mode::SessionViewer => self.session_viewer.handle_mouse(mouse),
+            Mode::MessageDiff => scroll_with_keys(&mut self.diff_viewer, mouse.kind),
+        }
+    }
+
+    fn handle_search_mouse(&mut self, mouse: MouseEvent, state: &AppState) -> Option<Message> {
+        let position = Position::new(mouse.column, mouse.row);
+        let MouseAreas { list, preview } = self.mouse_areas;
+
+        match mouse.kind {
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging_preview.is_some() => {
+                self.dragging_preview = Some(true);
+                return preview.map(|preview| {
+                    Message::SetPreviewSize(dragged_preview_size(list, preview, position, state))
+                });
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.dragging_preview.is_some() => {
+                // Resizing by nothing saves the size the drag left
+                return self
+                    .dragging_preview
+                    .take()
+                    .filter(|&moved| moved)
+                    .map(|_| Message::ResizePreview(0));
+            }
+            MouseEventKind::Down(MouseButton::Left)
+                if preview.is_some_and(|preview| on_preview_border(preview, position, state)) =>
+            {
+                self.dragging_preview = Some(false);
+                return None;
+            }
+            _ => {}
+        }
+
+        if let Some(message) = self.tab_bar.handle_mouse(mouse) {
+            return Some(message);
+        }
+        if preview.is_some_and(|preview| preview.contains(position)) {
+            return match state.search.current_tab {
+                SearchTab::Search | SearchTab::Bookmarks => {
+                    self.message_preview.handle_mouse(mouse)
+                }
+                SearchTab::SessionList | SearchTab::Projects => None,
+            };
+        }
+        if !list.contains(position) {
+            return None;
+        }
+        match state.search.current_tab {
+            SearchTab::Search => self.result_list.handle_mouse(mouse),
+            SearchTab::Bookmarks => self.bookmark_list.handle_mouse(mouse),
+            SearchTab::SessionList => self.session_list.handle_key(wheel_key(mouse.kind)?),
+            SearchTab::Projects => self.project_list.handle_key(wheel_key(mouse.kind)?),
+        }
+    }
+
     pub fn get_search_bar_mut(&mut self) -> &mut SearchBar {
         &mut self.search_bar
     }
@@ -271,6 +523,14 @@ impl Renderer {
         &mut self.result_list
     }
 
+    pub fn get_bookmark_list_mut(&mut self) -> &mut ResultList {
+        &mut self.bookmark_list
+    }
+
+    pub fn get_project_list_mut(&mut self) -> &mut ProjectList {
+        &mut self.project_list
+    }
+
     pub fn get_message_detail_mut(&mut self) -> &mut MessageDetail {
         &mut self.message_detail
     }
@@ -279,10 +539,26 @@ impl Renderer {
         &mut self.session_viewer
     }
 
+    pub fn get_diff_viewer_mut(&mut self) -> &mut DiffViewer {
+        &mut self.diff_viewer
+    }
+
     pub fn get_help_dialog_mut(&mut self) -> &mut HelpDialog {
         &mut self.help_dialog
     }
 
+    pub fn get_context_chart_mut(&mut self) -> &mut ContextChart {
+        &mut self.context_chart
+    }
+
+    pub fn get_theme_picker_mut(&mut self) -> &mut ThemePicker {
+        &mut self.theme_picker
+    }
+
+    pub fn get_saved_search_picker_mut(&mut self) -> &mut SavedSearchPicker {
+        &mut self.saved_search_picker
+    }
+
     pub fn get_session_list_mut(&mut self) -> &mut SessionList {
         &mut self.session_list
     }
@@ -291,3 +567,64 @@ impl Renderer {
         &mut self.tab_bar
     }
 }
+
+/// Indices of the results picked for bulk actions
+fn selected_indices(search: &SearchState) -> HashSet<usize> {
+    if search.selection.is_empty() {
+        return HashSet::new();
+    }
+    search
+        .results
+        .iter()
+        .enumerate()
+        .filter(|(_, result)| search.is_selected(result))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Scroll a view that scrolls by line with the arrow keys
+fn scroll_with_keys(component: &mut impl Component, kind: MouseEventKind) -> Option<Message> {
+    let key = wheel_key(kind)?;
+    (0..MOUSE_SCROLL_LINES).fold(None, |_, _| component.handle_key(key))
+}
+
+/// Whether a cell is on the border between the list and the preview
+fn on_preview_border(preview: Rect, position: Position, state: &AppState) -> bool {
+    match state.ui.preview_position {
+        PreviewPosition::Right => {
+            (preview.y..preview.bottom()).contains(&position.y)
+                && (position.x == preview.x || position.x + 1 == preview.x)
+        }
+        PreviewPosition::Bottom => {
+            (preview.x..preview.right()).contains(&position.x)
+                && (position.y == preview.y || position.y + 1 == preview.y)
+        }
+    }
+}
+
+/// The preview size in percent that puts its border at a dragged-to cell
+fn dragged_preview_size(list: Rect, preview: Rect, position: Position, state: &AppState) -> u16 {
+    let (end, start, at) = match state.ui.preview_position {
+        PreviewPosition::Right => (preview.right(), list.x, position.x),
+        PreviewPosition::Bottom => (preview.bottom(), list.y, position.y),
+    };
+    let total = u32::from(end.saturating_sub(start)).max(1);
+    (u32::from(end.saturating_sub(at)) * 100 / total) as u16
+}
+
+/// `area` split into the list and the preview of the current tab
+fn split_preview(area: Rect, state: &AppState) -> (Rect, Rect) {
+    let direction = match state.ui.preview_position {
+        PreviewPosition::Right => Direction::Horizontal,
+        PreviewPosition::Bottom => Direction::Vertical,
+    };
+    let size = state.preview_size();
+    let chunks = Layout::default()
+        .direction(direction)
+        .constraints([
+            Constraint::Percentage(100 - size),
+            Constraint::Percentage(size),
+        ])
+        .split(area);
+    (chunks[0], chunks[1])
+}
@@ -0,0 +1,199 @@
+//! Colors of the interactive mode.
+//!
+//! Components take their colors from the current [`Theme`] when they render,
+//! so switching themes (F2, or `theme` in config.toml) recolors the whole
+//! screen on the next frame. A theme is one of the built-in palettes with
+//! the colors set under `[colors]` in config.toml laid over it.
+
+use anyhow::{Context, Result, bail};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// The built-in palettes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    Solarized,
+}
+
+impl ThemeName {
+    pub const ALL: [ThemeName; 3] = [ThemeName::Dark, ThemeName::Light, ThemeName::Solarized];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThemeName::Dark => "dark",
+            ThemeName::Light => "light",
+            ThemeName::Solarized => "solarized",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub name: ThemeName,
+    /// Titles, focused borders and other accents
+    pub primary: Color,
+    /// Labels and key hints
+    pub secondary: Color,
+    pub accent: Color,
+    pub text: Color,
+    /// Hints, separators and other secondary text
+    pub text_dim: Color,
+    pub background: Color,
+    /// Background of the selected row
+    pub selection: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+    pub user: Color,
+    pub assistant: Color,
+    pub system: Color,
+    pub summary: Color,
+    /// Query matches in message text
+    pub matched: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::builtin(ThemeName::Dark)
+    }
+}
+
+impl Theme {
+    pub const fn builtin(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Self {
+                name,
+                primary: Color::Cyan,
+                secondary: Color::Yellow,
+                accent: Color::Magenta,
+                text: Color::White,
+                text_dim: Color::DarkGray,
+                background: Color::Black,
+                selection: Color::DarkGray,
+                success: Color::Green,
+                warning: Color::Yellow,
+                error: Color::Red,
+                user: Color::Green,
+                assistant: Color::Blue,
+                system: Color::Yellow,
+                summary: Color::Magenta,
+                matched: Color::Red,
+            },
+            ThemeName::Light => {
+                let green = Color::Rgb(0, 128, 0);
+                let orange = Color::Rgb(175, 95, 0);
+                Self {
+                    name,
+                    primary: Color::Blue,
+                    secondary: orange,
+                    accent: Color::Magenta,
+                    text: Color::Black,
+                    text_dim: Color::DarkGray,
+                    background: Color::White,
+                    selection: Color::Rgb(210, 210, 210),
+                    success: green,
+                    warning: orange,
+                    error: Color::Red,
+                    user: green,
+                    assistant: Color::Blue,
+                    system: orange,
+                    summary: Color::Magenta,
+                    matched: Color::Red,
+                }
+            }
+            // https://ethanschoonover.com/solarized/
+            ThemeName::Solarized => Self {
+                name,
+                primary: Color::Rgb(0x2a, 0xa1, 0x98),
+                secondary: Color::Rgb(0xb5, 0x89, 0x00),
+                accent: Color::Rgb(0xd3, 0x36, 0x82),
+                text: Color::Rgb(0x93, 0xa1, 0xa1),
+                text_dim: Color::Rgb(0x58, 0x6e, 0x75),
+                background: Color::Rgb(0x00, 0x2b, 0x36),
+                selection: Color::Rgb(0x07, 0x36, 0x42),
+                success: Color::Rgb(0x85, 0x99, 0x00),
+                warning: Color::Rgb(0xcb, 0x4b, 0x16),
+                error: Color::Rgb(0xdc, 0x32, 0x2f),
+                user: Color::Rgb(0x85, 0x99, 0x00),
+                assistant: Color::Rgb(0x26, 0x8b, 0xd2),
+                system: Color::Rgb(0xb5, 0x89, 0x00),
+                summary: Color::Rgb(0x6c, 0x71, 0xc4),
+                matched: Color::Rgb(0xcb, 0x4b, 0x16),
+            },
+        }
+    }
+
+    /// The `name` palette with `colors` from the `[colors]` table of
+    /// config.toml laid over it. Colors are names such as "green" or
+    /// "lightblue", or "#rrggbb".
+    pub fn with_colors(name: ThemeName, colors: &BTreeMap<String, String>) -> Result<Self> {
+        let mut theme = Self::builtin(name);
+        for (slot, value) in colors {
+            let color = Color::from_str(value)
+                .ok()
+                .with_context(|| format!("{slot}: unknown color \"{value}\""))?;
+            *theme.slot_mut(slot)? = color;
+        }
+        Ok(theme)
+    }
+
+    fn slot_mut(&mut self, slot: &str) -> Result<&mut Color> {
+        Ok(match slot {
+            "primary" => &mut self.primary,
+            "secondary" => &mut self.secondary,
+            "accent" => &mut self.accent,
+            "text" => &mut self.text,
+            "text_dim" => &mut self.text_dim,
+            "background" => &mut self.background,
+            "selection" => &mut self.selection,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "user" => &mut self.user,
+            "assistant" => &mut self.assistant,
+            "system" => &mut self.system,
+            "summary" => &mut self.summary,
+            "match" => &mut self.matched,
+            _ => bail!("unknown color \"{slot}\""),
+        })
+    }
+
+    /// Color of the role column for `role`
+    pub fn role_color(&self, role: &str) -> Color {
+        match role {
+            "user" => self.user,
+            "assistant" => self.assistant,
+            "system" => self.system,
+            "summary" => self.summary,
+            _ => self.text,
+        }
+    }
+
+    /// Style query matches are drawn with, over the style of their text
+    pub fn match_style(&self) -> Style {
+        Style::default()
+            .fg(self.matched)
+            .add_modifier(Modifier::BOLD)
+    }
+}
+
+static CURRENT: RwLock<Theme> = RwLock::new(Theme::builtin(ThemeName::Dark));
+
+/// The theme components render with
+pub fn current() -> Theme {
+    CURRENT.read().map(|theme| *theme).unwrap_or_default()
+}
+
+/// Render with `theme` from the next frame on
+pub fn set(theme: Theme) {
+    if let Ok(mut current) = CURRENT.write() {
+        *current = theme;
+    }
+}
@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod tests {
+    use super::super::theme::*;
+    use ratatui::style::{Color, Modifier};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_dark_theme_is_the_default() {
+        let dark = Theme::builtin(ThemeName::Dark);
+        assert_eq!(Theme::default(), dark);
+        assert_eq!(dark.primary, Color::Cyan);
+        assert_eq!(dark.selection, Color::DarkGray);
+        assert_eq!(dark.role_color("user"), Color::Green);
+        assert_eq!(dark.role_color("assistant"), Color::Blue);
+        assert_eq!(dark.role_color("tool"), Color::White);
+
+        let match_style = dark.match_style();
+        assert_eq!(match_style.fg, Some(Color::Red));
+        assert!(match_style.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn test_colors_over_a_theme() {
+        let colors = BTreeMap::from([
+            ("match".to_string(), "#ff8700".to_string()),
+            ("assistant".to_string(), "lightblue".to_string()),
+        ]);
+        let theme = Theme::with_colors(ThemeName::Light, &colors).unwrap();
+        assert_eq!(theme.name, ThemeName::Light);
+        assert_eq!(theme.matched, Color::Rgb(0xff, 0x87, 0x00));
+        assert_eq!(theme.role_color("assistant"), Color::LightBlue);
+        assert_eq!(theme.text, Theme::builtin(ThemeName::Light).text);
+
+        let unknown_slot = BTreeMap::from([("border".to_string(), "red".to_string())]);
+        let error = Theme::with_colors(ThemeName::Dark, &unknown_slot).unwrap_err();
+        assert!(error.to_string().contains("unknown color \"border\""));
+        let unknown_color = BTreeMap::from([("user".to_string(), "reddish".to_string())]);
+        assert!(Theme::with_colors(ThemeName::Dark, &unknown_color).is_err());
+    }
+}
@@ -0,0 +1,338 @@
+use crate::links::LinkEntry;
+use crate::query::SearchResult;
+use crate::todos::SessionTodos;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Maximum number of characters shown in an item title
+const TITLE_MAX_CHARS: usize = 100;
+
+/// Maximum number of characters in a telescope/fzf preview column
+const PREVIEW_MAX_CHARS: usize = 200;
+
+/// Script-filter output understood by Alfred and Raycast script wrappers
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptFilter {
+    pub items: Vec<ScriptFilterItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptFilterItem {
+    pub uid: String,
+    pub title: String,
+    pub subtitle: String,
+    /// Value handed to the action when the item is chosen
+    pub arg: String,
+    pub icon: ScriptFilterIcon,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quicklookurl: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub text: BTreeMap<&'static str, String>,
+    /// Extra values exposed to the wrapper, including how to resume the session
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<&'static str, String>,
+}
+
+/// Icon path relative to the launcher workflow/extension directory
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptFilterIcon {
+    pub path: String,
+}
+
+impl ScriptFilterIcon {
+    fn named(name: &str) -> Self {
+        Self {
+            path: format!("icons/{name}.png"),
+        }
+    }
+}
+
+/// Shell command resuming a Claude session from its working directory
+pub fn resume_command(session_id: &str, cwd: &str) -> String {
+    if cwd.is_empty() {
+        format!("claude --resume {session_id}")
+    } else {
+        format!("cd {} && claude --resume {session_id}", shell_quote(cwd))
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn session_variables(session_id: &str, cwd: &str, file: &str) -> BTreeMap<&'static str, String> {
+    BTreeMap::from([
+        ("session_id", session_id.to_string()),
+        ("cwd", cwd.to_string()),
+        ("file", file.to_string()),
+        ("resume_command", resume_command(session_id, cwd)),
+    ])
+}
+
+fn title_from_text(text: &str) -> String {
+    let first_line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+    if first_line.chars().count() > TITLE_MAX_CHARS {
+        format!(
+            "{}…",
+            first_line.chars().take(TITLE_MAX_CHARS).collect::<String>()
+        )
+    } else {
+        first_line.to_string()
+    }
+}
+
+pub fn search_results_script_filter(results: &[SearchResult]) -> ScriptFilter {
+    let items = results
+        .iter()
+        .map(|result| {
+            let mut variables = session_variables(&result.session_id, &result.cwd, &result.file);
+            variables.insert("uuid", result.uuid.clone());
+
+            ScriptFilterItem {
+                uid: result.uuid.clone(),
+                title: title_from_text(&result.text),
+                subtitle: format!("{} · {} · {}", result.role, result.timestamp, result.cwd),
+                arg: result.session_id.to_string(),
+                icon: ScriptFilterIcon::named(&result.role),
+                quicklookurl: None,
+                text: BTreeMap::from([
+                    ("copy", result.text.clone()),
+                    ("largetype", result.text.clone()),
+                ]),
+                variables,
+            }
+        })
+        .collect();
+
+    ScriptFilter { items }
+}
+
+pub fn links_script_filter(links: &[LinkEntry]) -> ScriptFilter {
+    let items = links
+        .iter()
+        .map(|link| ScriptFilterItem {
+            uid: link.url.clone(),
+            title: link.title.clone().unwrap_or_else(|| link.url.clone()),
+            subtitle: format!("{} · x{} · {}", link.url, link.count, link.last_seen),
+            arg: link.url.clone(),
+            icon: ScriptFilterIcon::named("link"),
+            quicklookurl: Some(link.url.clone()),
+            text: BTreeMap::from([("copy", link.url.clone())]),
+            variables: BTreeMap::from([
+                ("session_id", link.session_id.clone()),
+                ("resume_command", resume_command(&link.session_id, "")),
+            ]),
+        })
+        .collect();
+
+    ScriptFilter { items }
+}
+
+pub fn todos_script_filter(sessions: &[SessionTodos]) -> ScriptFilter {
+    let items = sessions
+        .iter()
+        .map(|session| {
+            let next = session
+                .todos
+                .iter()
+                .find(|todo| todo.status == "in_progress")
+                .or_else(|| session.todos.iter().find(|todo| todo.status != "completed"))
+                .map(|todo| todo.content.as_str())
+                .unwrap_or("All todos completed");
+
+            ScriptFilterItem {
+                uid: session.session_id.clone(),
+                title: title_from_text(next),
+                subtitle: format!(
+                    "{} open of {} · {}",
+                    session.open_count(),
+                    session.todos.len(),
+                    session.cwd
+                ),
+                arg: session.session_id.clone(),
+                icon: ScriptFilterIcon::named("todo"),
+                quicklookurl: None,
+                text: BTreeMap::new(),
+                variables: session_variables(&session.session_id, &session.cwd, &session.file),
+            }
+        })
+        .collect();
+
+    ScriptFilter { items }
+}
+
+/// Collapse `text` onto a single line that cannot break tab-separated output
+fn single_line_preview(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() > PREVIEW_MAX_CHARS {
+        format!(
+            "{}…",
+            collapsed
+                .chars()
+                .take(PREVIEW_MAX_CHARS)
+                .collect::<String>()
+        )
+    } else {
+        collapsed
+    }
+}
+
+/// `file<TAB>line<TAB>preview` lines for telescope/fzf.vim pickers.
+///
+/// Results without a known line number point at line 1 so editors can still
+/// open the file.
+pub fn search_results_telescope(results: &[SearchResult]) -> String {
+    results
+        .iter()
+        .map(|result| {
+            format!(
+                "{}\t{}\t{}: {}\n",
+                result.file,
+                result.line_number.unwrap_or(1),
+                result.role,
+                single_line_preview(&result.text)
+            )
+        })
+        .collect()
+}
+
+pub fn todos_telescope(sessions: &[SessionTodos]) -> String {
+    sessions
+        .iter()
+        .map(|session| {
+            let next = session
+                .todos
+                .iter()
+                .find(|todo| todo.status != "completed")
+                .map(|todo| todo.content.as_str())
+                .unwrap_or("All todos completed");
+            format!("{}\t1\t{}\n", session.file, single_line_preview(next))
+        })
+        .collect()
+}
+
+/// Unique result files in the order they first appear
+pub fn files_with_matches(results: &[SearchResult]) -> Vec<&str> {
+    let mut seen = std::collections::HashSet::new();
+    results
+        .iter()
+        .map(|result| result.file.as_str())
+        .filter(|file| seen.insert(*file))
+        .collect()
+}
+
+/// Number of results in each file, in the order the files first appear, for
+/// `--count`
+pub fn match_counts(results: &[SearchResult]) -> Vec<(&str, usize)> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    let mut positions = std::collections::HashMap::new();
+    for result in results {
+        let position = *positions.entry(result.file.as_str()).or_insert_with(|| {
+            counts.push((result.file.as_str(), 0));
+            counts.len() - 1
+        });
+        counts[position].1 += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::QueryCondition;
+
+    #[test]
+    fn test_resume_command_quotes_cwd() {
+        assert_eq!(
+            resume_command("s1", "/home/me/it's here"),
+            r"cd '/home/me/it'\''s here' && claude --resume s1"
+        );
+        assert_eq!(resume_command("s1", ""), "claude --resume s1");
+    }
+
+    #[test]
+    fn test_search_results_script_filter() {
+        let result = SearchResult {
+            file: "/p/s1.jsonl".into(),
+            uuid: "u1".to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s1".into(),
+            role: "assistant".into(),
+            text: "\n  First line\nsecond".to_string(),
+            message_type: "assistant".into(),
+            query: QueryCondition::Literal {
+                pattern: String::new(),
+                case_sensitive: false,
+            },
+            cwd: "/proj".into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        };
+
+        let output = serde_json::to_value(search_results_script_filter(&[result])).unwrap();
+        let item = &output["items"][0];
+        assert_eq!(item["title"], "First line");
+        assert_eq!(item["subtitle"], "assistant · 2024-01-01T00:00:00Z · /proj");
+        assert_eq!(item["arg"], "s1");
+        assert_eq!(item["icon"]["path"], "icons/assistant.png");
+        assert_eq!(
+            item["variables"]["resume_command"],
+            "cd '/proj' && claude --resume s1"
+        );
+        assert!(item.get("quicklookurl").is_none());
+    }
+
+    #[test]
+    fn test_search_results_telescope() {
+        let mut result = SearchResult {
+            file: "/p/s1.jsonl".into(),
+            uuid: "u1".to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s1".into(),
+            role: "user".into(),
+            text: "fix\tthe\n\n  build ".to_string(),
+            message_type: "user".into(),
+            query: QueryCondition::Literal {
+                pattern: String::new(),
+                case_sensitive: false,
+            },
+            cwd: "/proj".into(),
+            raw_json: None,
+            line_number: Some(7),
+            matched_in: Vec::new(),
+            has_images: false,
+        };
+        let other = SearchResult {
+            file: "/p/s2.jsonl".into(),
+            line_number: None,
+            ..result.clone()
+        };
+        result.uuid = "u2".to_string();
+
+        assert_eq!(
+            search_results_telescope(&[result.clone(), other.clone()]),
+            "/p/s1.jsonl\t7\tuser: fix the build\n/p/s2.jsonl\t1\tuser: fix the build\n"
+        );
+        assert_eq!(
+            files_with_matches(&[result.clone(), other.clone(), result.clone()]),
+            vec!["/p/s1.jsonl", "/p/s2.jsonl"]
+        );
+        assert_eq!(
+            match_counts(&[result.clone(), other, result]),
+            vec![("/p/s1.jsonl", 2), ("/p/s2.jsonl", 1)]
+        );
+    }
+
+    #[test]
+    fn test_title_truncation() {
+        let title = title_from_text(&"x".repeat(150));
+        assert_eq!(title.chars().count(), TITLE_MAX_CHARS + 1);
+        assert!(title.ends_with('…'));
+    }
+}
@@ -1,18 +1,54 @@
+pub mod answers;
+pub mod bookmarks;
+pub mod branches;
+pub mod checkpoints;
+pub mod config;
 pub mod convert;
+pub mod cost;
+#[cfg(unix)]
+pub mod daemon;
+pub mod hooks;
+pub mod http;
+pub mod index;
 pub mod interactive_ratatui;
+pub mod launcher;
+pub mod links;
+pub mod maintenance;
+pub mod mcp;
+pub mod metrics;
+pub mod outcome;
+pub mod output;
 pub mod profiling;
 #[cfg(all(feature = "profiling", unix))]
 pub mod profiling_enhanced;
 pub mod query;
+pub mod recap;
+pub mod rpc;
+pub mod scheduler;
 pub mod schemas;
 pub mod search;
+pub mod service;
+pub mod share;
 pub mod stats;
+pub mod tail;
+pub mod todos;
+pub mod tool_calls;
+pub mod usage;
 pub mod utils;
+pub mod webhook;
 
-pub use query::{QueryCondition, SearchOptions, SearchResult, parse_query};
+pub use bookmarks::{Bookmark, BookmarkStore, ExportTemplate, export_bookmarks};
+pub use index::SearchIndex;
+pub use links::{LinkEntry, collect_links, format_links};
+pub use query::{QueryCondition, SearchOptions, SearchResult, SessionPosition, parse_query};
+pub use recap::{SessionRecap, format_recap, recap_sessions};
 pub use schemas::{SessionMessage, ToolResult};
 pub use search::{
-    RayonEngine, SearchEngineTrait, SmolEngine, default_claude_pattern, discover_claude_files,
-    expand_tilde, format_search_result,
+    Engine, RayonEngine, SearchBuilder, SearchEngineTrait, SearchOutcome, SmolEngine,
+    default_claude_pattern, discover_claude_files, expand_tilde, format_search_result,
 };
-pub use stats::{Statistics, format_statistics};
+pub use stats::{
+    SessionGroup, Statistics, format_session_groups, format_statistics, group_by_session,
+};
+pub use todos::{SessionTodos, collect_latest_todos, format_todos};
+pub use usage::{UsageReport, collect_usage, format_usage_report};
@@ -0,0 +1,384 @@
+use crate::query::QueryCondition;
+use crate::schemas::{WebSearchContent, WebSearchResultItem};
+use crate::search::compression::open_session;
+use crate::utils::path_encoding;
+use anyhow::Result;
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// How a URL ended up in a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkSource {
+    /// Requested through a WebFetch tool call
+    Fetched,
+    /// Returned by a WebSearch tool call
+    SearchResult,
+    /// Mentioned in assistant text
+    Cited,
+}
+
+impl LinkSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LinkSource::Fetched => "fetched",
+            LinkSource::SearchResult => "search",
+            LinkSource::Cited => "cited",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub url: String,
+    pub title: Option<String>,
+    pub source: LinkSource,
+}
+
+/// A web search issued through the WebSearch tool, with its titled results
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebSearchLinks {
+    pub query: String,
+    pub results: Vec<(String, String)>, // (title, url)
+}
+
+fn url_regex() -> &'static Regex {
+    static URL_REGEX: OnceLock<Regex> = OnceLock::new();
+    URL_REGEX.get_or_init(|| Regex::new(r#"https?://[^\s<>"'`()\[\]{}]+"#).unwrap())
+}
+
+/// Find http(s) URLs in free text, without trailing punctuation
+pub fn extract_urls_from_text(text: &str) -> Vec<String> {
+    url_regex()
+        .find_iter(text)
+        .map(|m| {
+            m.as_str()
+                .trim_end_matches(['.', ',', ';', ':', '!', '?', '*'])
+                .to_string()
+        })
+        .collect()
+}
+
+/// Extract the web search carried by a WebSearch tool result, if any
+pub fn extract_web_search(message: &Value) -> Option<WebSearchLinks> {
+    let tool_result = message.get("toolUseResult")?;
+    let query = tool_result.get("query")?.as_str()?.to_string();
+    let items: Vec<WebSearchResultItem> =
+        serde_json::from_value(tool_result.get("results")?.clone()).ok()?;
+
+    let results = items
+        .into_iter()
+        .flat_map(|item| match item {
+            WebSearchResultItem::Structured { content, .. } => content,
+            WebSearchResultItem::String(_) => Vec::new(),
+        })
+        .map(|WebSearchContent { title, url }| (title, url))
+        .collect();
+
+    Some(WebSearchLinks { query, results })
+}
+
+/// Extract every URL a session message fetched, received from a web search, or cited
+pub fn extract_links(message: &Value) -> Vec<Link> {
+    let mut links = Vec::new();
+
+    if let Some(search) = extract_web_search(message) {
+        links.extend(search.results.into_iter().map(|(title, url)| Link {
+            url,
+            title: Some(title),
+            source: LinkSource::SearchResult,
+        }));
+    }
+
+    let is_assistant = message.get("type").and_then(|t| t.as_str()) == Some("assistant");
+    let contents = message
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array());
+
+    for item in contents.into_iter().flatten() {
+        match item.get("type").and_then(|t| t.as_str()) {
+            Some("tool_use") if item.get("name").and_then(|n| n.as_str()) == Some("WebFetch") => {
+                if let Some(url) = item
+                    .get("input")
+                    .and_then(|i| i.get("url"))
+                    .and_then(|u| u.as_str())
+                {
+                    links.push(Link {
+                        url: url.to_string(),
+                        title: None,
+                        source: LinkSource::Fetched,
+                    });
+                }
+            }
+            Some("text") if is_assistant => {
+                if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
+                    links.extend(extract_urls_from_text(text).into_iter().map(|url| Link {
+                        url,
+                        title: None,
+                        source: LinkSource::Cited,
+                    }));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    links
+}
+
+/// A URL aggregated across sessions for the `links` report
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkEntry {
+    pub url: String,
+    pub title: Option<String>,
+    pub sources: BTreeSet<LinkSource>,
+    pub count: usize,
+    pub last_seen: String,
+    pub session_id: String,
+}
+
+/// Collect every URL found in `files`, newest first.
+///
+/// When `query` is given, only links whose URL or title match it are kept.
+pub fn collect_links(
+    files: &[PathBuf],
+    project_path: Option<&str>,
+    query: Option<&QueryCondition>,
+) -> Result<Vec<LinkEntry>> {
+    let per_file: Vec<Vec<LinkEntry>> = files
+        .par_iter()
+        .filter(|file| {
+            project_path.is_none_or(|project| {
+                path_encoding::file_belongs_to_project(&file.to_string_lossy(), project)
+            })
+        })
+        .map(|file| scan_file(file, query))
+        .collect::<Result<_>>()?;
+
+    let mut merged: HashMap<String, LinkEntry> = HashMap::new();
+    for entry in per_file.into_iter().flatten() {
+        merge_entry(&mut merged, entry);
+    }
+
+    let mut links: Vec<LinkEntry> = merged.into_values().collect();
+    links.sort_by(|a, b| b.last_seen.cmp(&a.last_seen).then(a.url.cmp(&b.url)));
+    Ok(links)
+}
+
+fn merge_entry(merged: &mut HashMap<String, LinkEntry>, entry: LinkEntry) {
+    match merged.get_mut(&entry.url) {
+        Some(existing) => {
+            existing.count += entry.count;
+            existing.sources.extend(entry.sources);
+            if existing.title.is_none() {
+                existing.title = entry.title;
+            }
+            if entry.last_seen > existing.last_seen {
+                existing.last_seen = entry.last_seen;
+                existing.session_id = entry.session_id;
+            }
+        }
+        None => {
+            merged.insert(entry.url.clone(), entry);
+        }
+    }
+}
+
+fn scan_file(file: &Path, query: Option<&QueryCondition>) -> Result<Vec<LinkEntry>> {
+    let reader = open_session(file)?;
+    let mut merged: HashMap<String, LinkEntry> = HashMap::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        // Cheap pre-filter before paying for a full JSON parse
+        if !line.contains("http") {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+
+        let timestamp = value
+            .get("timestamp")
+            .and_then(|t| t.as_str())
+            .unwrap_or("");
+        let session_id = value
+            .get("sessionId")
+            .and_then(|s| s.as_str())
+            .unwrap_or("");
+
+        for link in extract_links(&value) {
+            if let Some(query) = query {
+                let haystack = format!("{} {}", link.url, link.title.as_deref().unwrap_or(""));
+                if !query.evaluate(&haystack).unwrap_or(false) {
+                    continue;
+                }
+            }
+
+            merge_entry(
+                &mut merged,
+                LinkEntry {
+                    url: link.url,
+                    title: link.title,
+                    sources: BTreeSet::from([link.source]),
+                    count: 1,
+                    last_seen: timestamp.to_string(),
+                    session_id: session_id.to_string(),
+                },
+            );
+        }
+    }
+
+    Ok(merged.into_values().collect())
+}
+
+pub fn format_links(links: &[LinkEntry], use_color: bool) -> String {
+    use colored::Colorize;
+
+    let mut output = String::new();
+
+    for link in links {
+        let sources = link
+            .sources
+            .iter()
+            .map(LinkSource::label)
+            .collect::<Vec<_>>()
+            .join(",");
+        let details = format!(
+            "  [{sources}] x{} | session {}",
+            link.count, link.session_id
+        );
+
+        if use_color {
+            output.push_str(&link.url.bright_cyan().underline().to_string());
+            if let Some(title) = &link.title {
+                output.push_str(&format!(" {}", title.bright_white()));
+            }
+            output.push('\n');
+            output.push_str(&details.dimmed().to_string());
+        } else {
+            output.push_str(&link.url);
+            if let Some(title) = &link.title {
+                output.push_str(&format!(" {title}"));
+            }
+            output.push('\n');
+            output.push_str(&details);
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_query;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    const SEARCH_RESULT: &str = r#"{"type":"user","sessionId":"s1","timestamp":"2024-01-01T00:00:01Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]},"toolUseResult":{"query":"rust scrollbar","results":[{"tool_use_id":"srv","content":[{"title":"Scrollbar docs","url":"https://docs.rs/ratatui/scrollbar"},{"title":"Example","url":"https://example.com/a"}]},"Some text summary"],"durationSeconds":1.5}}"#;
+
+    const FETCH_AND_CITE: &str = r#"{"type":"assistant","sessionId":"s1","timestamp":"2024-01-01T00:00:02Z","message":{"content":[{"type":"text","text":"See https://example.com/a, and (https://blog.example.org/post)."},{"type":"tool_use","id":"t2","name":"WebFetch","input":{"url":"https://docs.rs/ratatui","prompt":"summarize"}}]}}"#;
+
+    #[test]
+    fn test_extract_urls_from_text() {
+        let urls = extract_urls_from_text("Go to https://a.com/x. Or <http://b.org/y?z=1>!");
+        assert_eq!(urls, vec!["https://a.com/x", "http://b.org/y?z=1"]);
+        assert!(extract_urls_from_text("no links here").is_empty());
+    }
+
+    #[test]
+    fn test_extract_web_search() {
+        let value: Value = serde_json::from_str(SEARCH_RESULT).unwrap();
+        let search = extract_web_search(&value).unwrap();
+        assert_eq!(search.query, "rust scrollbar");
+        assert_eq!(
+            search.results,
+            vec![
+                (
+                    "Scrollbar docs".to_string(),
+                    "https://docs.rs/ratatui/scrollbar".to_string()
+                ),
+                ("Example".to_string(), "https://example.com/a".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_links_sources() {
+        let value: Value = serde_json::from_str(FETCH_AND_CITE).unwrap();
+        let links = extract_links(&value);
+        let summary: Vec<(&str, LinkSource)> =
+            links.iter().map(|l| (l.url.as_str(), l.source)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("https://example.com/a", LinkSource::Cited),
+                ("https://blog.example.org/post", LinkSource::Cited),
+                ("https://docs.rs/ratatui", LinkSource::Fetched),
+            ]
+        );
+
+        // URLs typed by the user are not citations
+        let user: Value = serde_json::from_str(
+            r#"{"type":"user","message":{"content":[{"type":"text","text":"https://user.example"}]}}"#,
+        )
+        .unwrap();
+        assert!(extract_links(&user).is_empty());
+    }
+
+    #[test]
+    fn test_collect_links_merges_and_filters() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("session.jsonl");
+        let mut file = File::create(&path)?;
+        writeln!(file, "{SEARCH_RESULT}")?;
+        writeln!(file, "{FETCH_AND_CITE}")?;
+
+        let links = collect_links(std::slice::from_ref(&path), None, None)?;
+        assert_eq!(links.len(), 4);
+        let example = links
+            .iter()
+            .find(|l| l.url == "https://example.com/a")
+            .unwrap();
+        assert_eq!(example.count, 2);
+        assert_eq!(example.title.as_deref(), Some("Example"));
+        assert_eq!(
+            example.sources,
+            BTreeSet::from([LinkSource::SearchResult, LinkSource::Cited])
+        );
+
+        let query = parse_query("docs.rs")?;
+        let links = collect_links(&[path], None, Some(&query))?;
+        assert_eq!(links.len(), 2);
+        assert!(links.iter().all(|l| l.url.contains("docs.rs")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_links_without_color() {
+        let links = vec![LinkEntry {
+            url: "https://example.com".to_string(),
+            title: Some("Example".to_string()),
+            sources: BTreeSet::from([LinkSource::Fetched, LinkSource::Cited]),
+            count: 3,
+            last_seen: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s1".to_string(),
+        }];
+
+        let output = format_links(&links, false);
+        assert_eq!(
+            output,
+            "https://example.com Example\n  [fetched,cited] x3 | session s1\n"
+        );
+    }
+}
@@ -2,24 +2,72 @@
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+#[cfg(unix)]
+use ccms::daemon::{self, Daemon, DaemonSearch};
 #[cfg(all(feature = "profiling", unix))]
 use ccms::profiling_enhanced;
 use ccms::{
-    QueryCondition, RayonEngine, SearchEngineTrait, SearchOptions, SearchResult, SmolEngine,
-    Statistics,
+    BookmarkStore, Engine, ExportTemplate, QueryCondition, RayonEngine, SearchEngineTrait,
+    SearchIndex, SearchOptions, SearchResult, SessionPosition, SmolEngine, Statistics,
+    answers::final_answers,
+    checkpoints::{Checkpoints, files_modified_since},
+    collect_latest_todos, collect_links, collect_usage,
+    config::{self, Config},
     convert::{ConvertMode, ConvertRequest, convert_session_to_codex},
-    default_claude_pattern, format_search_result,
-    interactive_ratatui::InteractiveSearch,
+    cost::round_usd,
+    default_claude_pattern, discover_claude_files, export_bookmarks, format_links, format_recap,
+    format_search_result, format_session_groups, format_todos, format_usage_report,
+    group_by_session,
+    hooks::{MatchHook, SandboxConfig},
+    http,
+    index::lookup_message,
+    interactive_ratatui::{
+        InteractiveSearch, domain::models::SearchOrder, event_log::EventLog, script::load_actions,
+        ui::keymap::KeyMap,
+    },
+    launcher::{
+        files_with_matches, links_script_filter, match_counts, search_results_script_filter,
+        search_results_telescope, todos_script_filter, todos_telescope,
+    },
+    maintenance::{self, validate_lines},
+    mcp::McpServer,
+    metrics,
+    output::{FieldSelection, ResultTemplate},
     parse_query, profiling,
+    query::{SavedSearches, SearchFilters},
+    recap_sessions,
+    rpc::RpcServer,
+    search::{
+        AutoEngine, CorpusSize,
+        bench::{bench_engines, format_bench_table},
+        compression::is_compressed,
+        context::{MessageContext, collect_context},
+        discovery_cache,
+        ids::{resolve_message_id, resolve_session_id},
+        io::IoMode,
+        pattern_root, result_cache,
+        scan_stats::ScanStats,
+        timings::Timings,
+    },
+    service::Service,
+    share::{Scrubber, build_share_html},
+    tail,
+    tool_calls::{ToolFilter, ToolStatus},
+    usage::collect_costs,
+    utils::git_repo::GitRepo,
+    utils::time_range::{parse_time, parse_time_range},
+    webhook::{self, WebhookSink},
 };
-use chrono::{DateTime, Utc};
 use clap::{Args, Command, CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{Generator, Shell, generate};
-use parse_datetime::parse_datetime;
 use std::collections::HashMap;
 use std::io::{self, Write};
 use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Results printed by a search when neither --max-results nor config.toml says otherwise
+const DEFAULT_MAX_RESULTS: usize = 200;
 
 #[derive(Parser)]
 #[command(
@@ -61,9 +109,9 @@ struct Cli {
     #[arg(long)]
     message_id: Option<String>,
 
-    /// Maximum number of results to return
-    #[arg(short = 'n', long, default_value = "200")]
-    max_results: usize,
+    /// Maximum number of results to return (default: 200)
+    #[arg(short = 'n', long)]
+    max_results: Option<usize>,
 
     /// Filter messages before this timestamp (RFC3339 format)
     #[arg(long)]
@@ -77,14 +125,47 @@ struct Cli {
     #[arg(long)]
     since: Option<String>,
 
+    /// Filter messages to a time range like "last tuesday..yesterday", "2024-01-01.." or "..1 week ago"
+    #[arg(long, conflicts_with_all = ["before", "after", "since"])]
+    when: Option<String>,
+
+    /// Save the query with its --role, --since, --after, --before and --project as a named search
+    /// for `ccms run NAME`, then run it
+    #[arg(long, value_name = "NAME", requires = "query", conflicts_with = "when")]
+    save: Option<String>,
+
     /// Output format
     #[arg(short = 'f', long, value_enum, default_value = "text")]
     format: OutputFormat,
 
+    /// Print each result with a template such as '{timestamp}\t{role}\t{text:80}', or one read from a file
+    #[arg(long, value_name = "TEMPLATE", conflicts_with_all = ["format", "raw", "stats", "files_with_matches", "count", "group_by"])]
+    template: Option<String>,
+
+    /// Only include these comma-separated fields of each result in JSON output, e.g. uuid,timestamp,role,text
+    #[arg(long, value_name = "FIELDS", conflicts_with_all = ["raw", "template", "stats", "files_with_matches", "count", "group_by"])]
+    fields: Option<String>,
+
     /// Disable colored output
     #[arg(long)]
     no_color: bool,
 
+    /// Start the interactive mode without syntax highlighting of code blocks
+    #[arg(long)]
+    no_highlight: bool,
+
+    /// Record the interactive mode to FILE: an asciinema recording for *.cast, text dumps of each screen otherwise
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Write every message and command the interactive mode processes to FILE as JSONL, for automation tests and bug reports
+    #[arg(long, value_name = "FILE")]
+    event_log: Option<PathBuf>,
+
+    /// Describe state changes of the interactive mode in plain text for screen readers, on stderr or appended to FILE (e.g. a named pipe)
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+    announce: Option<String>,
+
     /// Enable verbose output
     #[arg(short, long)]
     verbose: bool,
@@ -101,10 +182,98 @@ struct Cli {
     #[arg(long)]
     raw: bool,
 
+    /// Also show N messages from the same session before each match
+    #[arg(short = 'B', long, value_name = "N", conflicts_with_all = ["raw", "template", "stats", "files_with_matches", "count", "group_by", "watch"])]
+    before_context: Option<usize>,
+
+    /// Also show N messages from the same session after each match
+    #[arg(short = 'A', long, value_name = "N", conflicts_with_all = ["raw", "template", "stats", "files_with_matches", "count", "group_by", "watch"])]
+    after_context: Option<usize>,
+
+    /// Also show N messages from the same session before and after each match
+    #[arg(short = 'C', long, value_name = "N", conflicts_with_all = ["raw", "template", "stats", "files_with_matches", "count", "group_by", "watch"])]
+    context: Option<usize>,
+
     /// Filter by working directory (cwd) path
     #[arg(long = "project")]
     project_path: Option<String>,
 
+    /// Only search sessions that ran in the git repository of the current directory, worktrees included
+    #[arg(long, conflicts_with = "project_path")]
+    repo: bool,
+
+    /// Leave out messages with this role; may be repeated or comma-separated
+    #[arg(long, value_name = "ROLE", value_delimiter = ',')]
+    exclude_role: Vec<String>,
+
+    /// Leave out messages from this session; may be repeated or comma-separated
+    #[arg(long, value_name = "SESSION_ID", value_delimiter = ',')]
+    exclude_session: Vec<String>,
+
+    /// Leave out sessions whose working directory is this path; may be repeated
+    #[arg(long, value_name = "PATH")]
+    exclude_project: Vec<String>,
+
+    /// Leave out messages matching this query, as in `<query> AND NOT (<not>)`
+    #[arg(long, value_name = "QUERY")]
+    not: Option<String>,
+
+    /// Return at most N matches from any single session
+    #[arg(long, value_name = "N")]
+    max_per_session: Option<usize>,
+
+    /// Show a message once even when resumed sessions repeat it, keeping the copy in the newest file
+    #[arg(long)]
+    dedupe: bool,
+
+    /// Also match inside structured tool results: edited file paths, diffs, todos, web search results
+    #[arg(long)]
+    include_tool_results: bool,
+
+    /// Also match inside the metadata of attached images: media type, source type, URL
+    #[arg(long)]
+    include_images: bool,
+
+    /// Leave out user messages that only carry tool results; `--context` and the interactive message detail still show them
+    #[arg(long)]
+    hide_tool_results: bool,
+
+    /// Also match subagent (sidechain) messages, which are left out unless the query has `is:sidechain`
+    #[arg(long)]
+    include_sidechain: bool,
+
+    /// Also match meta messages (`isMeta`), which are left out unless the query has `is:meta`
+    #[arg(long)]
+    include_meta: bool,
+
+    /// Only match calls to this tool and their results, e.g. `Bash` (case-insensitive)
+    #[arg(long, value_name = "NAME")]
+    tool: Option<String>,
+
+    /// Only match tool results that succeeded or failed; pair with `-B 1` to see the calls
+    #[arg(long, value_enum, value_name = "STATUS")]
+    tool_status: Option<ToolStatus>,
+
+    /// Show the assistant's final reply of each exchange with a match instead of the matches
+    #[arg(long, conflicts_with_all = ["stats", "watch"])]
+    answers_only: bool,
+
+    /// Match the query against assistant thinking blocks only (same as wrapping it in `thinking:(...)`)
+    #[arg(long)]
+    thinking: bool,
+
+    /// Only return bookmarked messages (bookmark with `b` in the interactive mode)
+    #[arg(long)]
+    bookmarked: bool,
+
+    /// Only match each session's opening prompt (first) or final message (last), like `is:first` / `is:last`
+    #[arg(long, value_enum, value_name = "POSITION")]
+    position: Option<SessionPosition>,
+
+    /// Print where the search spent its time (discovery, read, parse, match, sort, format) as JSON on stderr
+    #[arg(long)]
+    timings: bool,
+
     /// Generate profiling report (requires --features profiling)
     #[cfg(all(feature = "profiling", unix))]
     #[arg(long)]
@@ -118,485 +287,2160 @@ struct Cli {
     #[arg(long = "completion", value_enum)]
     generator: Option<Shell>,
 
-    /// Search engine to use
-    #[arg(long, value_enum, default_value = "smol")]
-    engine: EngineType,
+    /// Search engine to use (default: auto, which picks by the number and size of files)
+    #[arg(long, value_enum)]
+    engine: Option<EngineType>,
+
+    /// Order results newest first, oldest first, or best match first
+    #[arg(long, value_enum, default_value = "newest")]
+    sort: SearchOrder,
 
     /// Show only statistics
     #[arg(long)]
     stats: bool,
+
+    /// How to read session files; mmap maps them into memory (requires --features mmap)
+    #[arg(long, value_enum, default_value = "buffered")]
+    io: IoMode,
+
+    /// Consult the on-disk index built by `ccms index build` for unchanged files
+    #[arg(long)]
+    use_index: bool,
+
+    /// Run the search on the `ccms daemon` listening on its socket, searching
+    /// locally when none is running
+    #[arg(long)]
+    daemon: bool,
+
+    /// List every session directory again instead of reusing the file lists remembered from earlier runs
+    #[arg(long, global = true)]
+    refresh: bool,
+
+    /// Print only the paths of session files containing matches
+    #[arg(short = 'l', long)]
+    files_with_matches: bool,
+
+    /// Print the number of matches in each session file with matches, as path:count
+    #[arg(short = 'c', long, conflicts_with_all = ["stats", "files_with_matches"])]
+    count: bool,
+
+    /// End each path of -l and --count, or each --template result, with a NUL byte instead of a newline, for xargs -0
+    #[arg(short = '0', long, conflicts_with = "watch")]
+    null: bool,
+
+    /// Keep running and print matching messages as they are appended to session files
+    #[arg(short = 'w', long, conflicts_with_all = ["stats", "files_with_matches", "count"])]
+    watch: bool,
+
+    /// With --watch, also POST new matches as JSON to this URL (e.g. a Slack incoming webhook);
+    /// ignored without --watch
+    #[arg(long, env = "CCMS_WEBHOOK_URL")]
+    webhook: Option<String>,
+
+    /// Maximum number of messages per webhook request
+    #[arg(long, default_value_t = webhook::DEFAULT_BATCH_SIZE, requires = "webhook")]
+    webhook_batch_size: usize,
+
+    /// With --watch, run this command for each new match, e.g. "notify-send ccms {{text}}";
+    /// the program must be allowed in hooks.json
+    #[arg(long, value_name = "TEMPLATE", requires = "watch")]
+    exec: Option<String>,
+
+    /// Collapse results into one entry per group
+    #[arg(long, value_enum, conflicts_with_all = ["stats", "files_with_matches", "count", "watch"])]
+    group_by: Option<GroupBy>,
 }
 
 #[derive(Debug, Subcommand)]
 enum CliCommand {
     /// Convert Claude session messages into Codex rollout format
     Convert(ConvertCommand),
+    /// List the latest TodoWrite checklist of each session
+    Todos(TodosArgs),
+    /// List URLs the assistant fetched, searched for, or cited across sessions
+    Links(LinksArgs),
+    /// Summarize token usage and message counts by role, project, model and day
+    Stats(StatsArgs),
+    /// Tell what happened about a topic session by session: title, the prompt that raised it
+    /// and the assistant's last reply, earliest session first
+    Recap(RecapArgs),
+    /// Show only the messages written since a checkpoint or time, across all projects
+    New(NewArgs),
+    /// Manage named checkpoints that `ccms new --since` catches up from
+    Checkpoint(CheckpointCommand),
+    /// Manage bookmarked messages
+    Bookmarks(BookmarksCommand),
+    /// Manage the on-disk search index used by --use-index
+    Index(IndexCommand),
+    /// Manage saved searches that queries can reference as $name
+    Saved(SavedCommand),
+    /// Run a search saved with --save, with any further search flags added to its own
+    Run(RunArgs),
+    /// Serve search over JSON-RPC for editor extensions (see docs/json-rpc.md)
+    LspLike(LspLikeArgs),
+    /// Run ccms as a server for other tools
+    Serve(ServeArgs),
+    /// Keep session files parsed in memory and answer `ccms --daemon` searches over a unix socket
+    Daemon(DaemonArgs),
+    /// Write a session as a single scrubbed HTML page that opens in any browser
+    Share(ShareArgs),
+    /// Validate, merge or split session files
+    Sessions(SessionsCommand),
+    /// Replace text (e.g. a pasted secret) across session files, keeping backups
+    Rewrite(RewriteArgs),
+    /// Manage the config file that holds default options
+    Config(ConfigCommand),
+    /// Replay key presses against the interactive mode without a terminal and print what it showed
+    Script(ScriptArgs),
+    /// Time the search engines against each other on the same files and query
+    Bench(BenchArgs),
 }
 
 #[derive(Debug, Args)]
-struct ConvertCommand {
-    #[command(subcommand)]
-    command: ConvertSubcommand,
-}
+struct TodosArgs {
+    /// File pattern to search (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
 
-#[derive(Debug, Subcommand)]
-enum ConvertSubcommand {
-    /// Convert a Claude session to a Codex-compatible rollout file
-    ClaudeToCodex(ConvertClaudeToCodexArgs),
-}
+    /// Filter by working directory (cwd) path (default: current directory; use / for all projects)
+    #[arg(long = "project")]
+    project_path: Option<String>,
 
-#[derive(Debug, Args)]
-struct ConvertClaudeToCodexArgs {
-    /// Claude session ID to convert
-    #[arg(long = "session-id")]
-    session_id: String,
+    /// Filter by session ID
+    #[arg(short, long)]
+    session_id: Option<String>,
 
-    /// Override CODEX_HOME (default: $CODEX_HOME or ~/.codex)
-    #[arg(long = "codex-home")]
-    codex_home: Option<PathBuf>,
+    /// Include sessions whose todos are all completed
+    #[arg(short, long)]
+    all: bool,
 
-    /// Print rollout JSONL to stdout instead of writing a file
-    #[arg(long)]
-    stdout: bool,
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value = "text")]
+    format: OutputFormat,
 
-    /// Resolve source and output path without writing any content
+    /// Disable colored output
     #[arg(long)]
-    dry_run: bool,
+    no_color: bool,
 }
 
-#[derive(Clone, Copy, Debug, ValueEnum)]
-enum OutputFormat {
-    Text,
-    Json,
-    JsonL,
-}
+#[derive(Debug, Args)]
+struct LinksArgs {
+    /// Only list links whose URL or title match this query
+    query: Option<String>,
 
-#[derive(Clone, Copy, Debug, ValueEnum)]
-enum EngineType {
-    Smol,
-    Rayon,
-}
+    /// File pattern to search (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
 
-fn print_completions<G: Generator>(generator: G, cmd: &mut Command) {
-    generate(
-        generator,
-        cmd,
-        cmd.get_name().to_string(),
-        &mut io::stdout(),
-    );
-}
+    /// Filter by working directory (cwd) path (default: current directory; use / for all projects)
+    #[arg(long = "project")]
+    project_path: Option<String>,
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value = "text")]
+    format: OutputFormat,
 
-    // Handle completion generation
-    if let Some(generator) = cli.generator {
-        let mut cmd = Cli::command();
-        eprintln!("Generating completion file for {generator:?}...");
-        print_completions(generator, &mut cmd);
-        return Ok(());
-    }
+    /// Disable colored output
+    #[arg(long)]
+    no_color: bool,
+}
 
-    // Handle subcommands
-    if let Some(command) = &cli.command {
-        return handle_cli_command(command, cli.verbose);
-    }
+#[derive(Debug, Args)]
+struct RecapArgs {
+    /// Query whose matching sessions are recapped
+    query: String,
 
-    // Initialize tracing
-    profiling::init_tracing();
+    /// File pattern to search (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
 
-    if cli.help_query {
-        print_query_help();
-        return Ok(());
-    }
+    /// Filter by working directory (cwd) path (default: current directory; use / for all projects)
+    #[arg(long = "project")]
+    project_path: Option<String>,
 
-    // Initialize profiler if requested
-    #[cfg(all(feature = "profiling", unix))]
-    let mut profiler = if cli.profile.is_some() {
-        Some(profiling_enhanced::EnhancedProfiler::new("main")?)
-    } else {
-        None
-    };
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value = "text")]
+    format: OutputFormat,
 
-    #[cfg(not(all(feature = "profiling", unix)))]
-    if cli.profile.is_some() {
-        eprintln!(
-            "Warning: Profiling is not enabled. Build with --features profiling to enable profiling."
-        );
-    }
+    /// Disable colored output
+    #[arg(long)]
+    no_color: bool,
+}
 
-    // Parse --since flag if provided
-    let parsed_after = if let Some(since) = &cli.since {
-        match parse_since_time(since) {
-            Ok(dt) => Some(dt),
-            Err(e) => {
-                eprintln!("Error parsing --since: {e}");
-                std::process::exit(1);
-            }
-        }
-    } else {
-        cli.after.clone()
-    };
+#[derive(Debug, Args)]
+struct NewArgs {
+    /// Query the new messages must match (default: all of them)
+    query: Option<String>,
 
-    // Set default project_path to current directory if not specified
-    let project_path = cli.project_path.clone().or_else(|| {
-        std::env::current_dir()
-            .ok()
-            .and_then(|path| path.to_str().map(|s| s.to_string()))
-    });
+    /// Checkpoint name or time (e.g. "yesterday", "2 hours ago", Unix timestamp) to catch up from
+    #[arg(long, value_name = "CHECKPOINT|TIME")]
+    since: String,
 
-    // Get pattern
-    let default_pattern = default_claude_pattern();
-    let pattern = cli.pattern.as_deref().unwrap_or(&default_pattern);
+    /// Move the --since checkpoint to the start of this run afterwards
+    #[arg(long)]
+    advance: bool,
 
-    // Handle --message-id search
-    if let Some(message_id) = &cli.message_id {
-        // Create a special query to search for the UUID
-        let query = parse_query(message_id)?;
+    /// File pattern to search (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
 
-        // Create search options
-        let options = SearchOptions {
-            max_results: Some(1), // We only need one result
-            role: None,
-            session_id: None,
-            message_id: Some(message_id.clone()),
-            before: None,
-            after: None,
-            verbose: cli.verbose,
-            project_path: None,
-        };
+    /// Filter by working directory (cwd) path (default: all projects)
+    #[arg(long = "project")]
+    project_path: Option<String>,
 
-        if cli.verbose {
-            eprintln!("Searching for message ID: {message_id}");
-        }
+    /// Maximum number of messages to show, newest kept
+    #[arg(short = 'n', long)]
+    max_results: Option<usize>,
 
-        // Execute search
-        let engine = SmolEngine::new(options);
-        let (results, duration, _) = engine.search(pattern, query)?;
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value = "text")]
+    format: OutputFormat,
 
-        if results.is_empty() {
-            eprintln!("Message with ID '{message_id}' not found.");
-            std::process::exit(1);
-        }
+    /// Disable colored output
+    #[arg(long)]
+    no_color: bool,
 
-        // Pretty print the message
-        let result = &results[0];
-        print_message_details(result, !cli.no_color);
+    /// Show full message text without truncation
+    #[arg(long)]
+    full_text: bool,
+}
 
-        if cli.verbose {
-            eprintln!("\n⏱️  Search completed in {}ms", duration.as_millis());
-        }
+#[derive(Debug, Args)]
+struct CheckpointCommand {
+    #[command(subcommand)]
+    command: CheckpointSubcommand,
+}
 
-        return Ok(());
-    }
+#[derive(Debug, Subcommand)]
+enum CheckpointSubcommand {
+    /// Set a checkpoint to now or to --at, replacing any previous time
+    Set {
+        /// Name to pass to `ccms new --since`
+        name: String,
+        /// Time of the checkpoint (e.g. "9am", "1 day ago", Unix timestamp)
+        #[arg(long, value_name = "TIME")]
+        at: Option<String>,
+    },
+    /// Delete a checkpoint
+    Remove {
+        /// Name of the checkpoint
+        name: String,
+    },
+    /// List checkpoints, oldest first
+    List,
+}
 
-    // Handle --latest mode
-    if cli.latest {
-        if cli.query.as_ref().map(|q| !q.is_empty()).unwrap_or(false) {
-            eprintln!("Error: --latest cannot be used with a search query");
-            std::process::exit(1);
-        }
+#[derive(Debug, Args)]
+struct StatsArgs {
+    /// File pattern to search (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
 
-        let options = SearchOptions {
-            max_results: None, // Interactive mode should not be limited by max_results
-            role: cli.role,
-            session_id: None,
-            message_id: None,
-            before: cli.before,
-            after: parsed_after.clone(),
+    /// Filter by working directory (cwd) path (default: current directory; use / for all projects)
+    #[arg(long = "project")]
+    project_path: Option<String>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value = "text")]
+    format: OutputFormat,
+
+    /// Disable colored output
+    #[arg(long)]
+    no_color: bool,
+}
+
+#[derive(Debug, Args)]
+struct LspLikeArgs {
+    /// Speak JSON-RPC with Content-Length framing over stdin/stdout
+    #[arg(long)]
+    stdio: bool,
+
+    /// File pattern to search (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    /// Default working directory filter for searches (default: all projects)
+    #[arg(long = "project")]
+    project_path: Option<String>,
+
+    /// Use the on-disk index built by `ccms index build` for unchanged files
+    #[arg(long)]
+    use_index: bool,
+}
+
+#[derive(Debug, Args)]
+struct ServeArgs {
+    /// Speak the Model Context Protocol over stdin/stdout, exposing search as tools
+    #[arg(long)]
+    mcp: bool,
+
+    /// Serve Prometheus metrics at http://<ADDR>/metrics (e.g. 127.0.0.1:9464)
+    #[arg(long, value_name = "ADDR")]
+    metrics: Option<String>,
+
+    /// Serve a read-only JSON API at http://<ADDR> (e.g. 127.0.0.1:8787) with
+    /// /search, /sessions, /sessions/{id} and /messages/{uuid}
+    #[arg(long, value_name = "ADDR")]
+    http: Option<String>,
+
+    /// File pattern to search (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    /// Default working directory filter for searches (default: all projects)
+    #[arg(long = "project")]
+    project_path: Option<String>,
+
+    /// Use the on-disk index built by `ccms index build` for unchanged files
+    #[arg(long)]
+    use_index: bool,
+}
+
+#[derive(Debug, Args)]
+struct ScriptArgs {
+    /// JSON file of actions to replay (- for stdin), e.g. [{"type": "error"}, "wait", "dump"]
+    actions: PathBuf,
+
+    /// File pattern to search (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    /// Filter by working directory (cwd) path (default: current directory; use / for all projects)
+    #[arg(long = "project")]
+    project_path: Option<String>,
+
+    /// Screen width in columns
+    #[arg(long, default_value_t = 100)]
+    width: u16,
+
+    /// Screen height in rows
+    #[arg(long, default_value_t = 30)]
+    height: u16,
+
+    /// Write every message and command processed to FILE as JSONL
+    #[arg(long, value_name = "FILE")]
+    event_log: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value = "text")]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+struct DaemonArgs {
+    /// File pattern to keep in memory (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    /// Socket to listen on (default: ~/.cache/ccms/daemon.sock, or $CCMS_DAEMON_SOCKET)
+    #[arg(long)]
+    socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct BenchArgs {
+    /// Query to time (default: every message)
+    #[arg(short, long)]
+    query: Option<String>,
+
+    /// File pattern to search (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    /// Filter by working directory (cwd) path (default: current directory; use / for all projects)
+    #[arg(long = "project")]
+    project_path: Option<String>,
+
+    /// Engine to time; repeat to compare several (default: smol and rayon)
+    #[arg(long, value_enum)]
+    engine: Vec<EngineType>,
+
+    /// Measured runs per engine
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    runs: u32,
+
+    /// Unmeasured runs per engine first, to warm the page cache
+    #[arg(long, default_value_t = 1)]
+    warmup: u32,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value = "text")]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+struct ShareArgs {
+    /// Session ID to share
+    session_id: String,
+
+    /// File pattern to search (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    /// Output file (default: ccms-share-<session-id>.html; use - for stdout)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Additional regex to redact; may be repeated
+    #[arg(long, value_name = "REGEX")]
+    redact: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+struct BookmarksCommand {
+    #[command(subcommand)]
+    command: BookmarksSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum BookmarksSubcommand {
+    /// Export bookmarked messages into one file per topic (first tag)
+    Export(BookmarksExportArgs),
+}
+
+#[derive(Debug, Args)]
+struct BookmarksExportArgs {
+    /// Export format
+    #[arg(short = 'f', long, value_enum, default_value = "markdown")]
+    format: BookmarkExportFormat,
+
+    /// Template: obsidian, notion, plain, or the name of a template in ~/.config/ccms/templates
+    #[arg(short, long, default_value = "plain")]
+    template: String,
+
+    /// Directory to write the exported files into
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum BookmarkExportFormat {
+    Markdown,
+}
+
+#[derive(Debug, Args)]
+struct IndexCommand {
+    #[command(subcommand)]
+    command: IndexSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum IndexSubcommand {
+    /// Build or incrementally update the index, re-reading only changed files
+    Build(IndexBuildArgs),
+    /// Delete the index
+    Clear,
+}
+
+#[derive(Debug, Args)]
+struct IndexBuildArgs {
+    /// File pattern to index (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct SavedCommand {
+    #[command(subcommand)]
+    command: SavedSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum SavedSubcommand {
+    /// Save a query under a name, replacing any query saved under it
+    Add {
+        /// Name used to reference the query as $name
+        name: String,
+        /// Query to save; may itself reference other saved searches
+        query: String,
+    },
+    /// Delete a saved search
+    Remove {
+        /// Name of the saved search
+        name: String,
+    },
+    /// List saved searches
+    List,
+}
+
+#[derive(Debug, Args)]
+struct RunArgs {
+    /// Name of the saved search
+    name: String,
+
+    /// More search flags, e.g. -f json or -n 20
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+struct ConfigCommand {
+    #[command(subcommand)]
+    command: ConfigSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigSubcommand {
+    /// Write a commented config template to ~/.config/ccms/config.toml
+    Init {
+        /// Replace an existing config file
+        #[arg(long)]
+        force: bool,
+        /// Print the template instead of writing it
+        #[arg(long, conflicts_with = "force")]
+        stdout: bool,
+    },
+    /// Print the path of the config file
+    Path,
+}
+
+#[derive(Debug, Args)]
+struct RewriteArgs {
+    /// Regex matched against the raw JSON of each line
+    #[arg(long = "match", value_name = "REGEX")]
+    match_regex: String,
+
+    /// Literal text to put in place of every match
+    #[arg(long, value_name = "TEXT", default_value = "[REDACTED]")]
+    replace: String,
+
+    /// File pattern to rewrite (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    /// Only report what would change (the default)
+    #[arg(long, conflicts_with = "apply")]
+    dry_run: bool,
+
+    /// Rewrite the files, keeping each original as a .bak file
+    #[arg(long)]
+    apply: bool,
+}
+
+#[derive(Debug, Args)]
+struct SessionsCommand {
+    #[command(subcommand)]
+    command: SessionsSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum SessionsSubcommand {
+    /// Check session files for malformed lines, duplicate UUIDs and out-of-order timestamps
+    Validate(SessionsValidateArgs),
+    /// Merge a session whose messages ended up in several files into one file
+    Merge(SessionsMergeArgs),
+    /// Split a session file into one file per day
+    Split(SessionsSplitArgs),
+}
+
+#[derive(Debug, Args)]
+struct SessionsValidateArgs {
+    /// Session files to check
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct SessionsMergeArgs {
+    /// Session ID to merge
+    session_id: String,
+
+    /// File pattern to search (default: ~/.claude/projects/**/*.jsonl)
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    /// Write the merged file; without this only the plan is printed
+    #[arg(long)]
+    apply: bool,
+}
+
+#[derive(Debug, Args)]
+struct SessionsSplitArgs {
+    /// Session file to split
+    file: PathBuf,
+
+    /// Write the split files; without this only the plan is printed
+    #[arg(long)]
+    apply: bool,
+}
+
+#[derive(Debug, Args)]
+struct ConvertCommand {
+    #[command(subcommand)]
+    command: ConvertSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConvertSubcommand {
+    /// Convert a Claude session to a Codex-compatible rollout file
+    ClaudeToCodex(ConvertClaudeToCodexArgs),
+}
+
+#[derive(Debug, Args)]
+struct ConvertClaudeToCodexArgs {
+    /// Claude session ID to convert
+    #[arg(long = "session-id")]
+    session_id: String,
+
+    /// Override CODEX_HOME (default: $CODEX_HOME or ~/.codex)
+    #[arg(long = "codex-home")]
+    codex_home: Option<PathBuf>,
+
+    /// Print rollout JSONL to stdout instead of writing a file
+    #[arg(long)]
+    stdout: bool,
+
+    /// Resolve source and output path without writing any content
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    /// One JSON object per line
+    #[value(alias = "jsonl")]
+    JsonL,
+    /// Script-filter JSON for Raycast/Alfred launchers
+    #[value(alias = "alfred")]
+    Raycast,
+    /// `file<TAB>line<TAB>preview` lines for telescope/fzf.vim pickers
+    Telescope,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum EngineType {
+    /// Smol for small corpora, Rayon for large ones (see `auto_engine_*` in the config)
+    #[default]
+    Auto,
+    Smol,
+    Rayon,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum GroupBy {
+    Session,
+}
+
+/// The engine to search `pattern` with, measuring the files for `Auto`
+fn resolve_engine(
+    engine: EngineType,
+    pattern: &str,
+    auto: AutoEngine,
+    verbose: bool,
+) -> Result<Engine> {
+    Ok(match engine {
+        EngineType::Smol => Engine::Smol,
+        EngineType::Rayon => Engine::Rayon,
+        EngineType::Auto => {
+            let size = CorpusSize::of(&discover_claude_files(Some(pattern))?);
+            if verbose {
+                eprintln!(
+                    "Engine auto: {} files, {} MB",
+                    size.files,
+                    size.bytes / (1024 * 1024)
+                );
+            }
+            auto.pick(size)
+        }
+    })
+}
+
+fn print_completions<G: Generator>(generator: G, cmd: &mut Command) {
+    generate(
+        generator,
+        cmd,
+        cmd.get_name().to_string(),
+        &mut io::stdout(),
+    );
+}
+
+fn main() -> Result<()> {
+    let run_start = std::time::Instant::now();
+    let mut cli = Cli::parse();
+
+    // Session file lists are remembered between runs; --refresh starts over
+    if let Ok(path) = discovery_cache::DiscoveryCache::default_path() {
+        discovery_cache::enable(path, cli.refresh);
+    }
+
+    // Handle completion generation
+    if let Some(generator) = cli.generator {
+        let mut cmd = Cli::command();
+        eprintln!("Generating completion file for {generator:?}...");
+        print_completions(generator, &mut cmd);
+        return Ok(());
+    }
+
+    // `ccms run NAME` is the search saved as NAME, run like any other
+    if let Some(CliCommand::Run(run)) = &cli.command {
+        cli = saved_search_cli(run)?;
+    }
+
+    // Handle subcommands
+    if let Some(command) = &cli.command {
+        return handle_cli_command(command, cli.verbose);
+    }
+
+    if let (Some(name), Some(query)) = (&cli.save, &cli.query) {
+        save_search(name, query, &cli)?;
+    }
+
+    anyhow::ensure!(
+        cli.io.is_available(),
+        "--io mmap needs ccms built with the mmap feature (cargo install --features mmap)"
+    );
+
+    // Fill in what the command line leaves out from config.toml
+    let config = Config::load(&Config::default_path()?)?;
+    apply_config(&mut cli, &config)?;
+    let key_map =
+        KeyMap::from_config(&config.keybindings).context("invalid [keybindings] in config.toml")?;
+    result_cache::set_memory_cap(config.cache_memory_cap());
+
+    // Initialize tracing
+    profiling::init_tracing();
+
+    if cli.help_query {
+        print_query_help();
+        return Ok(());
+    }
+
+    // Initialize profiler if requested
+    #[cfg(all(feature = "profiling", unix))]
+    let mut profiler = if cli.profile.is_some() {
+        Some(profiling_enhanced::EnhancedProfiler::new("main")?)
+    } else {
+        None
+    };
+
+    #[cfg(not(all(feature = "profiling", unix)))]
+    if cli.profile.is_some() {
+        eprintln!(
+            "Warning: Profiling is not enabled. Build with --features profiling to enable profiling."
+        );
+    }
+
+    // Parse --since flag if provided
+    let parsed_after = if let Some(since) = &cli.since {
+        match parse_since_time(since) {
+            Ok(dt) => Some(dt),
+            Err(e) => {
+                eprintln!("Error parsing --since: {e}");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        cli.after.clone()
+    };
+
+    // --when sets both bounds at once
+    let (parsed_after, parsed_before) = match &cli.when {
+        Some(when) => match parse_time_range(when) {
+            Ok(range) => (
+                range.after.map(|dt| dt.to_rfc3339()),
+                range.before.map(|dt| dt.to_rfc3339()),
+            ),
+            Err(e) => {
+                eprintln!("Error parsing --when: {e}");
+                std::process::exit(1);
+            }
+        },
+        None => (parsed_after, cli.before.clone()),
+    };
+
+    // --repo matches by working directory instead, which also covers the
+    // repository's worktrees
+    let repo_dirs = if cli.repo {
+        GitRepo::discover(&std::env::current_dir()?)?.worktrees
+    } else {
+        Vec::new()
+    };
+
+    // Set default project_path to current directory if not specified
+    let project_path = cli.project_path.clone().or_else(|| {
+        if cli.repo {
+            return None;
+        }
+        std::env::current_dir()
+            .ok()
+            .and_then(|path| path.to_str().map(|s| s.to_string()))
+    });
+
+    let index_path = if cli.use_index {
+        Some(SearchIndex::default_path()?)
+    } else {
+        None
+    };
+
+    let bookmarked = if cli.bookmarked {
+        Some(BookmarkStore::load(&BookmarkStore::default_path()?)?.uuids())
+    } else {
+        None
+    };
+    let tool = ToolFilter::new(cli.tool.clone(), cli.tool_status);
+
+    let template = cli
+        .template
+        .as_deref()
+        .map(ResultTemplate::from_arg)
+        .transpose()?;
+    let fields = cli
+        .fields
+        .as_deref()
+        .map(FieldSelection::parse)
+        .transpose()?;
+    if fields.is_some() && !matches!(cli.format, OutputFormat::Json | OutputFormat::JsonL) {
+        anyhow::bail!("--fields applies to --format json or jsonl");
+    }
+    if cli.null && !(cli.files_with_matches || cli.count || template.is_some()) {
+        anyhow::bail!("-0 applies to -l, --count or --template");
+    }
+
+    // Get pattern
+    let default_pattern = default_claude_pattern();
+    let pattern = cli.pattern.as_deref().unwrap_or(&default_pattern);
+
+    // Session ids and message uuids may be given as unique prefixes
+    if cli.session_id.is_some() || cli.message_id.is_some() {
+        let files = discover_claude_files(Some(pattern))?;
+        if let Some(session_id) = &cli.session_id {
+            cli.session_id = Some(resolve_session_id(&files, session_id)?);
+        }
+        if let Some(message_id) = &cli.message_id {
+            cli.message_id = Some(resolve_message_id(&files, message_id)?);
+        }
+    }
+
+    // Handle --message-id search
+    if let Some(message_id) = &cli.message_id {
+        // Create a special query to search for the UUID
+        let query = parse_query(message_id)?;
+
+        // Create search options
+        let options = SearchOptions {
+            max_results: Some(1), // We only need one result
+            role: None,
+            session_id: None,
+            message_id: Some(message_id.clone()),
+            before: None,
+            after: None,
             verbose: cli.verbose,
-            project_path: project_path.clone(),
+            project_path: None,
+            index_path: None,
+            index: None,
+            cache_results: false,
+            exclude_roles: Vec::new(),
+            exclude_session_ids: Vec::new(),
+            exclude_projects: Vec::new(),
+            repo_dirs: Vec::new(),
+            max_per_session: None,
+            dedupe: false,
+            include_tool_results: false,
+            include_images: false,
+            session_summaries: false,
+            exclude_tool_result_messages: false,
+            thinking_only: false,
+            only_uuids: None,
+            position: None,
+            exclude_sidechain: false,
+            exclude_meta: false,
+            tool: None,
+            io: cli.io,
+        };
+
+        if cli.verbose {
+            eprintln!("Searching for message ID: {message_id}");
+        }
+
+        // The index knows where each message lives; scan only when it does not
+        let start = std::time::Instant::now();
+        let files = discover_claude_files(Some(pattern))?;
+        let (results, duration) =
+            match lookup_message(&SearchIndex::default_path()?, message_id, &files) {
+                Some(result) => (vec![result], start.elapsed()),
+                None => {
+                    let engine = SmolEngine::new(options);
+                    let (results, duration, _) = engine.search(pattern, query)?;
+                    (results, duration)
+                }
+            };
+
+        if results.is_empty() {
+            eprintln!("Message with ID '{message_id}' not found.");
+            std::process::exit(1);
+        }
+
+        // Pretty print the message
+        let result = &results[0];
+        print_message_details(result, !cli.no_color);
+
+        if cli.verbose {
+            eprintln!("\n⏱️  Search completed in {}ms", duration.as_millis());
+        }
+
+        return Ok(());
+    }
+
+    // Every interactive entry point searches with the same options
+    let interactive_options = |session_id: Option<String>| SearchOptions {
+        max_results: None, // Interactive mode should not be limited by max_results
+        role: cli.role.clone(),
+        session_id,
+        message_id: None,
+        before: parsed_before.clone(),
+        after: parsed_after.clone(),
+        verbose: cli.verbose,
+        project_path: project_path.clone(),
+        index_path: index_path.clone(),
+        index: None,
+        cache_results: true,
+        exclude_roles: cli.exclude_role.clone(),
+        exclude_session_ids: cli.exclude_session.clone(),
+        exclude_projects: cli.exclude_project.clone(),
+        repo_dirs: repo_dirs.clone(),
+        max_per_session: cli.max_per_session,
+        dedupe: cli.dedupe,
+        include_tool_results: cli.include_tool_results,
+        include_images: cli.include_images,
+        session_summaries: false,
+        exclude_tool_result_messages: cli.hide_tool_results,
+        thinking_only: cli.thinking,
+        only_uuids: bookmarked.clone(),
+        position: cli.position,
+        exclude_sidechain: !cli.include_sidechain,
+        exclude_meta: !cli.include_meta,
+        tool: tool.clone(),
+        io: cli.io,
+    };
+
+    // Handle --latest mode
+    if cli.latest {
+        if cli.query.as_ref().map(|q| !q.is_empty()).unwrap_or(false) {
+            eprintln!("Error: --latest cannot be used with a search query");
+            std::process::exit(1);
+        }
+
+        let mut interactive =
+            interactive_search(&cli, interactive_options(None), &key_map, &config)?;
+        interactive.set_start_latest_message_detail(true);
+        return interactive.run(pattern);
+    }
+
+    // Handle --latest-session mode
+    if cli.latest_session {
+        if cli.query.as_ref().map(|q| !q.is_empty()).unwrap_or(false) {
+            eprintln!("Error: --latest-session cannot be used with a search query");
+            std::process::exit(1);
+        }
+
+        let mut interactive =
+            interactive_search(&cli, interactive_options(None), &key_map, &config)?;
+        interactive.set_start_latest(true);
+        return interactive.run(pattern);
+    }
+
+    // Interactive mode when no query provided or query is empty (but not when --stats or --watch is used)
+    if !cli.stats
+        && !cli.watch
+        && cli.not.is_none()
+        && tool.is_none()
+        && (cli.query.is_none() || cli.query.as_ref().map(|s| s.is_empty()).unwrap_or(false))
+    {
+        let options = interactive_options(cli.session_id.clone());
+        return interactive_search(&cli, options, &key_map, &config)?.run(pattern);
+    }
+
+    if cli.record.is_some() {
+        anyhow::bail!("--record only applies to the interactive mode");
+    }
+    if cli.announce.is_some() {
+        anyhow::bail!("--announce only applies to the interactive mode");
+    }
+    if cli.event_log.is_some() {
+        anyhow::bail!("--event-log only applies to the interactive mode");
+    }
+
+    // Regular search mode - query is provided (or empty string for --stats)
+    let query_str = cli.query.unwrap_or_else(String::new);
+
+    // Parse the query (empty query for --stats, --watch, --not or --tool means match all)
+    let query = if (cli.stats || cli.watch || cli.not.is_some() || tool.is_some())
+        && query_str.is_empty()
+    {
+        // Empty query for stats: match everything
+        QueryCondition::Literal {
+            pattern: String::new(),
+            case_sensitive: false,
+        }
+    } else {
+        match parse_query(&query_str) {
+            Ok(q) => q,
+            Err(e) => {
+                eprintln!("Error parsing query: {e}");
+                eprintln!("Use --help-query for query syntax help");
+                std::process::exit(1);
+            }
+        }
+    };
+
+    // Combine --not with the main query as `<query> AND NOT (<not>)`
+    let query = match cli.not.as_deref().map(parse_query) {
+        None => query,
+        Some(Ok(excluded)) => QueryCondition::And {
+            conditions: vec![
+                query,
+                QueryCondition::Not {
+                    condition: Box::new(excluded),
+                },
+            ],
+        },
+        Some(Err(e)) => {
+            eprintln!("Error parsing --not: {e}");
+            eprintln!("Use --help-query for query syntax help");
+            std::process::exit(1);
+        }
+    };
+
+    // Create search options
+    let options = SearchOptions {
+        max_results: if cli.stats || cli.answers_only {
+            None // Don't limit results when calculating statistics or answers
+        } else if cli.files_with_matches || cli.count {
+            cli.max_results // Like grep, list every file unless asked otherwise
+        } else {
+            Some(cli.max_results.unwrap_or(DEFAULT_MAX_RESULTS))
+        },
+        role: cli.role,
+        session_id: cli.session_id,
+        message_id: None,
+        before: parsed_before,
+        after: parsed_after,
+        verbose: cli.verbose,
+        project_path,
+        index_path,
+        index: None,
+        cache_results: false,
+        exclude_roles: cli.exclude_role,
+        exclude_session_ids: cli.exclude_session,
+        exclude_projects: cli.exclude_project,
+        repo_dirs,
+        max_per_session: cli.max_per_session,
+        dedupe: cli.dedupe,
+        include_tool_results: cli.include_tool_results,
+        include_images: cli.include_images,
+        session_summaries: false,
+        exclude_tool_result_messages: cli.hide_tool_results,
+        thinking_only: cli.thinking,
+        only_uuids: bookmarked,
+        position: cli.position,
+        exclude_sidechain: !cli.include_sidechain,
+        exclude_meta: !cli.include_meta,
+        tool,
+        io: cli.io,
+    };
+
+    if cli.verbose {
+        eprintln!("Searching in: {pattern}");
+        eprintln!("Query: {query:?}");
+    }
+
+    if cli.watch {
+        let output = WatchOutput {
+            format: cli.format,
+            use_color: !cli.no_color,
+            full_text: cli.full_text,
+            raw: cli.raw,
+            template,
+            fields,
+            webhook: cli
+                .webhook
+                .as_ref()
+                .map(|url| WebhookSink::new(url.clone()).with_batch_size(cli.webhook_batch_size)),
+        };
+        let hook = match &cli.exec {
+            Some(template) => {
+                let config = SandboxConfig::load(&SandboxConfig::default_path()?)?;
+                Some(MatchHook::new(template, config).context("invalid --exec")?)
+            }
+            None => None,
+        };
+        return watch_and_print(pattern, &query, &options, &output, hook);
+    }
+
+    // Debug: only search specific file
+    let debug_file = "/Users/masatomokusaka/.claude/projects/-Users-masatomokusaka-src-github-com-mkusaka-bookmark-agent/9ca2db47-82d6-4da7-998e-3d7cd28ce5b5.jsonl";
+    let pattern_to_use = if std::env::var("DEBUG_SINGLE_FILE").is_ok() {
+        eprintln!("DEBUG: Searching only {debug_file}");
+        debug_file
+    } else {
+        pattern
+    };
+
+    let from_daemon = if cli.daemon {
+        search_on_daemon(pattern_to_use, &query, &options, cli.sort, cli.verbose)
+    } else {
+        None
+    };
+
+    // Execute search
+    let mut engine_name = "daemon";
+    let mut timings = Timings::default();
+    let (results, duration, total_count, scan) = match from_daemon {
+        Some(found) => found,
+        None => {
+            let engine = resolve_engine(
+                cli.engine.unwrap_or_default(),
+                pattern_to_use,
+                config.auto_engine(),
+                cli.verbose,
+            )?;
+            if cli.verbose {
+                eprintln!("Using {engine:?} engine");
+            }
+
+            // Create appropriate engine based on CLI flag
+            match engine {
+                Engine::Smol => {
+                    let engine = SmolEngine::new(options);
+                    let (results, duration, total_count) = engine
+                        .search_with_role_filter_and_order(pattern_to_use, query, None, cli.sort)?;
+                    engine_name = "smol";
+                    timings = engine.last_timings();
+                    (results, duration, total_count, engine.last_scan_stats())
+                }
+                Engine::Rayon => {
+                    let engine = RayonEngine::new(options);
+                    let (results, duration, total_count) = engine
+                        .search_with_role_filter_and_order(pattern_to_use, query, None, cli.sort)?;
+                    engine_name = "rayon";
+                    timings = engine.last_timings();
+                    (results, duration, total_count, engine.last_scan_stats())
+                }
+            }
+        }
+    };
+
+    // Replace the matches with the answers they lead to
+    let (results, total_count) = if cli.answers_only {
+        let mut answers = final_answers(&results)?;
+        let total_count = answers.len();
+        answers.truncate(cli.max_results.unwrap_or(DEFAULT_MAX_RESULTS));
+        (answers, total_count)
+    } else {
+        (results, total_count)
+    };
+
+    // Reported when this function returns, after whichever output below ran
+    let mut timings_report = cli.timings.then(|| TimingsReport {
+        timings,
+        engine: engine_name,
+        scan,
+        run_start,
+        format_start: std::time::Instant::now(),
+    });
+
+    // If stats flag is set, collect and display statistics
+    if cli.stats {
+        let stats = collect_statistics(&results);
+        println!("{}", ccms::stats::format_statistics(&stats, !cli.no_color));
+
+        eprintln!("\n⏱️  Search completed in {}ms", duration.as_millis());
+        if total_count > results.len() {
+            eprintln!(
+                "(Showing stats for {} of {} total results)",
+                results.len(),
+                total_count
+            );
+        }
+        return Ok(());
+    }
+
+    // Output results
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+
+    let terminator = if cli.null { '\0' } else { '\n' };
+
+    if cli.files_with_matches {
+        for file in files_with_matches(&results) {
+            write!(&mut handle, "{file}{terminator}")?;
+        }
+        return Ok(());
+    }
+
+    if cli.count {
+        for (file, count) in match_counts(&results) {
+            write!(&mut handle, "{file}:{count}{terminator}")?;
+        }
+        return Ok(());
+    }
+
+    if let Some(template) = &template {
+        for result in &results {
+            write!(&mut handle, "{}{terminator}", template.render(result))?;
+        }
+        return Ok(());
+    }
+
+    let before_context = cli.before_context.or(cli.context).unwrap_or(0);
+    let after_context = cli.after_context.or(cli.context).unwrap_or(0);
+    let contexts = if before_context > 0 || after_context > 0 {
+        if matches!(cli.format, OutputFormat::Raycast | OutputFormat::Telescope) {
+            anyhow::bail!("-A/-B/-C support --format text, json or jsonl");
+        }
+        Some(collect_context(&results, before_context, after_context)?)
+    } else {
+        None
+    };
+
+    let session_groups =
+        (cli.group_by == Some(GroupBy::Session)).then(|| group_by_session(&results));
+
+    if let Some(groups) = &session_groups {
+        match cli.format {
+            OutputFormat::Text => {
+                if groups.is_empty() {
+                    println!("No results found.");
+                } else {
+                    println!(
+                        "Found {} results in {} sessions:\n",
+                        results.len(),
+                        groups.len()
+                    );
+                    print!("{}", format_session_groups(groups, !cli.no_color));
+                    eprintln!("⏱️  Search completed in {}ms", duration.as_millis());
+                }
+                return Ok(());
+            }
+            OutputFormat::JsonL => {
+                for group in groups {
+                    serde_json::to_writer(&mut handle, group)?;
+                    writeln!(&mut handle)?;
+                }
+                return Ok(());
+            }
+            OutputFormat::Raycast | OutputFormat::Telescope => {
+                anyhow::bail!("--group-by session supports --format text, json or jsonl");
+            }
+            // JSON keeps the full results and swaps in the grouped sessions below
+            OutputFormat::Json => {}
+        }
+    }
+
+    match cli.format {
+        OutputFormat::Text => {
+            if results.is_empty() {
+                println!("No results found.");
+            } else if cli.raw {
+                // Raw mode: output raw JSON lines
+                for result in &results {
+                    if let Some(raw_json) = &result.raw_json {
+                        println!("{raw_json}");
+                    }
+                }
+            } else {
+                println!("Found {} results:\n", results.len());
+                for (i, result) in results.iter().enumerate() {
+                    let Some(context) = contexts.as_ref().map(|contexts| &contexts[i]) else {
+                        println!(
+                            "{}",
+                            format_search_result(result, !cli.no_color, cli.full_text)
+                        );
+                        continue;
+                    };
+                    // Separate each match and its context like grep does
+                    if i > 0 {
+                        println!("--");
+                    }
+                    for message in &context.before {
+                        println!(
+                            "{}",
+                            format_context_message(message, !cli.no_color, cli.full_text)
+                        );
+                    }
+                    println!(
+                        "{}",
+                        format_search_result(result, !cli.no_color, cli.full_text)
+                    );
+                    for message in &context.after {
+                        println!(
+                            "{}",
+                            format_context_message(message, !cli.no_color, cli.full_text)
+                        );
+                    }
+                }
+
+                // Print search statistics
+                eprintln!("\n⏱️  Search completed in {}ms", duration.as_millis());
+                if total_count > results.len() {
+                    eprintln!(
+                        "(Showing {} of {} total results)",
+                        results.len(),
+                        total_count
+                    );
+                } else {
+                    eprintln!("(Found {total_count} results)");
+                }
+            }
+        }
+        OutputFormat::Json => {
+            // Collect statistics
+            let mut session_counts: HashMap<String, usize> = HashMap::new();
+            let mut file_counts: HashMap<String, usize> = HashMap::new();
+
+            for result in &results {
+                *session_counts
+                    .entry(result.session_id.to_string())
+                    .or_insert(0) += 1;
+                *file_counts.entry(result.file.to_string()).or_insert(0) += 1;
+            }
+
+            // Estimated cost of the matched messages and of their sessions
+            let cost_files: Vec<PathBuf> = file_counts.keys().map(PathBuf::from).collect();
+            let cost_start = std::time::Instant::now();
+            let costs = collect_costs(&cost_files, &config.pricing());
+            for (file, error) in &costs.unreadable {
+                eprintln!("Warning: cost of {} left out: {error}", file.display());
+            }
+            if let Some(report) = &mut timings_report {
+                report.timings.cost = cost_start.elapsed();
+            }
+            let session_cost = |session_id: &str| {
+                round_usd(costs.by_session.get(session_id).copied().unwrap_or(0.0))
+            };
+
+            // Create detailed file information
+            let files_detail: Vec<_> = file_counts
+                .iter()
+                .map(|(file, count)| {
+                    serde_json::json!({
+                        "path": file,
+                        "message_count": count,
+                        "session_id": results.iter()
+                            .find(|r| r.file == *file)
+                            .map(|r| r.session_id.as_str())
+                            .unwrap_or("")
+                    })
+                })
+                .collect();
+
+            // Create detailed session information
+            let sessions_detail: Vec<_> = session_counts
+                .iter()
+                .map(|(session_id, count)| {
+                    serde_json::json!({
+                        "session_id": session_id,
+                        "message_count": count,
+                        "cost_usd": session_cost(session_id)
+                    })
+                })
+                .collect();
+
+            let mut results_json =
+                results_with_context(&results, contexts.as_deref(), fields.as_ref())?;
+            if fields.is_none() {
+                for (value, result) in results_json.iter_mut().zip(&results) {
+                    if let Some(cost) = costs.by_message.get(result.uuid.as_str()) {
+                        value["cost_usd"] = serde_json::json!(round_usd(*cost));
+                    }
+                }
+            }
+            let sessions_json = match &session_groups {
+                Some(groups) => {
+                    let mut groups = serde_json::to_value(groups)?;
+                    for group in groups.as_array_mut().into_iter().flatten() {
+                        let cost = session_cost(group["session_id"].as_str().unwrap_or(""));
+                        group["cost_usd"] = serde_json::json!(cost);
+                    }
+                    groups
+                }
+                None => serde_json::to_value(sessions_detail)?,
+            };
+            let total_cost = costs.sessions_total(session_counts.keys().map(String::as_str));
+
+            let output = serde_json::json!({
+                "results": results_json,
+                "summary": {
+                    "duration_ms": duration.as_millis(),
+                    "total_count": total_count,
+                    "returned_count": results.len(),
+                    "unique_sessions": session_counts.len(),
+                    "unique_files": file_counts.len(),
+                    "cost_usd": round_usd(total_cost),
+                    "scan": scan_summary(&scan)
+                },
+                "files": files_detail,
+                "sessions": sessions_json
+            });
+            serde_json::to_writer_pretty(&mut handle, &output)?;
+            writeln!(&mut handle)?;
+        }
+        OutputFormat::JsonL => {
+            for result in results_with_context(&results, contexts.as_deref(), fields.as_ref())? {
+                serde_json::to_writer(&mut handle, &result)?;
+                writeln!(&mut handle)?;
+            }
+            // Write metadata as last line
+            let metadata = serde_json::json!({
+                "_metadata": {
+                    "duration_ms": duration.as_millis(),
+                    "total_count": total_count,
+                    "returned_count": results.len(),
+                    "scan": scan_summary(&scan)
+                }
+            });
+            serde_json::to_writer(&mut handle, &metadata)?;
+            writeln!(&mut handle)?;
+        }
+        OutputFormat::Raycast => {
+            serde_json::to_writer(&mut handle, &search_results_script_filter(&results))?;
+            writeln!(&mut handle)?;
+        }
+        OutputFormat::Telescope => {
+            write!(&mut handle, "{}", search_results_telescope(&results))?;
+        }
+    }
+
+    // Generate profiling report if requested
+    #[cfg(all(feature = "profiling", unix))]
+    if let Some(ref mut profiler) = profiler
+        && let Some(profile_path) = &cli.profile
+    {
+        let report = profiler.generate_comprehensive_report(profile_path)?;
+        eprintln!("\n{report}");
+        eprintln!("\nDetailed profiling reports saved to {profile_path}_{{comprehensive.txt,svg}}");
+    }
+
+    Ok(())
+}
+
+/// A message shown around a match with -A/-B/-C, dimmed to set it apart
+/// The interactive mode searching with `options`, set up from the command
+/// line and config.toml the same way for every entry point
+fn interactive_search(
+    cli: &Cli,
+    options: SearchOptions,
+    key_map: &KeyMap,
+    config: &Config,
+) -> Result<InteractiveSearch> {
+    let mut interactive = InteractiveSearch::new(options);
+    interactive.set_syntax_highlight(!cli.no_highlight);
+    interactive.set_key_map(key_map.clone());
+    interactive.set_preview(config.preview, config.preview_size);
+    interactive.set_mouse(config.mouse.unwrap_or(false));
+    interactive.set_cache_memory_cap(config.cache_memory_cap());
+    interactive.set_persist_dismissals(config.persist_dismissals.unwrap_or(false));
+    interactive.set_theme(config.theme.unwrap_or_default(), config.colors.clone())?;
+    interactive.set_pricing(config.pricing());
+    interactive.set_search_order(cli.sort);
+    if let Some(path) = &cli.record {
+        interactive.set_record_path(path)?;
+    }
+    if let Some(target) = &cli.announce {
+        interactive.set_announce_target(target)?;
+    }
+    if let Some(path) = &cli.event_log {
+        interactive.set_event_log(EventLog::create(path)?);
+    }
+    Ok(interactive)
+}
+
+fn format_context_message(message: &SearchResult, use_color: bool, full_text: bool) -> String {
+    use colored::Colorize;
+
+    let formatted = format_search_result(message, false, full_text);
+    if use_color {
+        formatted.dimmed().to_string()
+    } else {
+        formatted
+    }
+}
+
+/// Results as JSON values, each with `context_before` and `context_after`
+/// arrays when -A/-B/-C asked for them, and only the `--fields` asked for
+fn results_with_context(
+    results: &[SearchResult],
+    contexts: Option<&[MessageContext]>,
+    fields: Option<&FieldSelection>,
+) -> Result<Vec<serde_json::Value>> {
+    let messages = |messages: &[SearchResult]| -> Result<serde_json::Value> {
+        Ok(serde_json::Value::Array(
+            messages
+                .iter()
+                .map(|message| result_value(message, fields))
+                .collect::<Result<_>>()?,
+        ))
+    };
+    results
+        .iter()
+        .enumerate()
+        .map(|(i, result)| {
+            let mut value = result_value(result, fields)?;
+            if let Some(context) = contexts.and_then(|contexts| contexts.get(i)) {
+                value["context_before"] = messages(&context.before)?;
+                value["context_after"] = messages(&context.after)?;
+            }
+            Ok(value)
+        })
+        .collect()
+}
+
+fn result_value(
+    result: &SearchResult,
+    fields: Option<&FieldSelection>,
+) -> Result<serde_json::Value> {
+    match fields {
+        Some(fields) => fields.select(result),
+        None => Ok(serde_json::to_value(result)?),
+    }
+}
+
+/// Scan stats as reported in JSON output, with the derived cache hit ratio
+fn scan_summary(scan: &ScanStats) -> serde_json::Value {
+    let mut summary = serde_json::to_value(scan).unwrap_or_default();
+    summary["cache_hit_ratio"] = serde_json::json!(scan.cache_hit_ratio());
+    summary
+}
+
+/// The `--timings` breakdown of a run, printed to stderr when dropped so
+/// that every way of writing the results is covered
+struct TimingsReport {
+    timings: Timings,
+    engine: &'static str,
+    scan: ScanStats,
+    run_start: std::time::Instant,
+    format_start: std::time::Instant,
+}
+
+impl Drop for TimingsReport {
+    fn drop(&mut self) {
+        let timings = Timings {
+            format: self
+                .format_start
+                .elapsed()
+                .saturating_sub(self.timings.cost),
+            total: self.run_start.elapsed(),
+            ..self.timings
         };
+        let mut report = timings.to_json();
+        report["version"] = serde_json::json!(env!("CARGO_PKG_VERSION"));
+        report["engine"] = serde_json::json!(self.engine);
+        report["threads"] = serde_json::json!(num_cpus::get());
+        report["scan"] = scan_summary(&self.scan);
+        eprintln!("{report}");
+    }
+}
 
-        let mut interactive = InteractiveSearch::new(options);
-        interactive.set_start_latest_message_detail(true);
-        return interactive.run(pattern);
+/// How `--watch` reports new matches
+struct WatchOutput {
+    format: OutputFormat,
+    use_color: bool,
+    full_text: bool,
+    raw: bool,
+    template: Option<ResultTemplate>,
+    fields: Option<FieldSelection>,
+    webhook: Option<WebhookSink>,
+}
+
+fn watch_and_print(
+    pattern: &str,
+    query: &QueryCondition,
+    options: &SearchOptions,
+    output: &WatchOutput,
+    mut hook: Option<MatchHook>,
+) -> Result<()> {
+    if matches!(output.format, OutputFormat::Json | OutputFormat::Raycast) {
+        anyhow::bail!("--watch streams results; use --format text, jsonl or telescope");
+    }
+
+    eprintln!(
+        "Watching {} for new matches (Ctrl+C to stop)...",
+        pattern_root(Some(pattern)).display()
+    );
+
+    tail::follow(pattern, query, options, |results| {
+        print_watch_results(results, output)?;
+        // A webhook outage should not stop the watch
+        if let Some(webhook) = &output.webhook
+            && let Err(e) = webhook.send(results)
+        {
+            eprintln!("Warning: {e:#}");
+        }
+        // Nor should a failing hook
+        if let Some(hook) = &mut hook {
+            for result in results {
+                match hook.run(result) {
+                    Ok(run) if run.success() => {}
+                    Ok(run) if run.timed_out => eprintln!("Warning: --exec command timed out"),
+                    Ok(run) => eprintln!(
+                        "Warning: --exec command failed ({}): {}",
+                        run.status
+                            .map_or("killed".to_string(), |code| format!("exit {code}")),
+                        run.stderr.trim()
+                    ),
+                    Err(e) => eprintln!("Warning: {e:#}"),
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+fn print_watch_results(results: &[SearchResult], output: &WatchOutput) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    if let Some(template) = &output.template {
+        for result in results {
+            writeln!(&mut handle, "{}", template.render(result))?;
+        }
+        handle.flush()?;
+        return Ok(());
+    }
+    match output.format {
+        OutputFormat::JsonL => {
+            for result in results {
+                serde_json::to_writer(&mut handle, &result_value(result, output.fields.as_ref())?)?;
+                writeln!(&mut handle)?;
+            }
+        }
+        OutputFormat::Telescope => {
+            write!(&mut handle, "{}", search_results_telescope(results))?;
+        }
+        _ if output.raw => {
+            for result in results.iter().filter_map(|r| r.raw_json.as_ref()) {
+                writeln!(&mut handle, "{result}")?;
+            }
+        }
+        _ => {
+            for result in results {
+                writeln!(
+                    &mut handle,
+                    "{}\n",
+                    format_search_result(result, output.use_color, output.full_text)
+                )?;
+            }
+        }
+    }
+    handle.flush()?;
+    Ok(())
+}
+
+fn parse_since_time(input: &str) -> Result<String> {
+    Ok(parse_time(input)?.to_rfc3339())
+}
+
+fn handle_cli_command(command: &CliCommand, verbose: bool) -> Result<()> {
+    match command {
+        CliCommand::Convert(convert) => match &convert.command {
+            ConvertSubcommand::ClaudeToCodex(args) => {
+                handle_convert_claude_to_codex(args, verbose)?;
+            }
+        },
+        CliCommand::Todos(args) => handle_todos(args)?,
+        CliCommand::Links(args) => handle_links(args)?,
+        CliCommand::Stats(args) => handle_stats(args)?,
+        CliCommand::Recap(args) => handle_recap(args)?,
+        CliCommand::New(args) => handle_new(args)?,
+        CliCommand::Checkpoint(checkpoint) => handle_checkpoint(&checkpoint.command)?,
+        CliCommand::Bookmarks(bookmarks) => match &bookmarks.command {
+            BookmarksSubcommand::Export(args) => handle_bookmarks_export(args)?,
+        },
+        CliCommand::Index(index) => match &index.command {
+            IndexSubcommand::Build(args) => handle_index_build(args)?,
+            IndexSubcommand::Clear => handle_index_clear()?,
+        },
+        CliCommand::Saved(saved) => handle_saved(&saved.command)?,
+        CliCommand::Run(_) => unreachable!("ccms run is turned into a search in main"),
+        CliCommand::LspLike(args) => handle_lsp_like(args, verbose)?,
+        CliCommand::Serve(args) => handle_serve(args, verbose)?,
+        CliCommand::Daemon(args) => handle_daemon(args)?,
+        CliCommand::Share(args) => handle_share(args)?,
+        CliCommand::Rewrite(args) => handle_rewrite(args)?,
+        CliCommand::Config(config) => handle_config(&config.command)?,
+        CliCommand::Script(args) => handle_script(args)?,
+        CliCommand::Bench(args) => handle_bench(args)?,
+        CliCommand::Sessions(sessions) => match &sessions.command {
+            SessionsSubcommand::Validate(args) => handle_sessions_validate(args)?,
+            SessionsSubcommand::Merge(args) => handle_sessions_merge(args)?,
+            SessionsSubcommand::Split(args) => handle_sessions_split(args)?,
+        },
+    }
+
+    Ok(())
+}
+
+/// Merge config.toml defaults into options the command line did not set
+fn apply_config(cli: &mut Cli, config: &Config) -> Result<()> {
+    if cli.pattern.is_none() {
+        cli.pattern = config.pattern.clone();
+    }
+    if cli.role.is_none() {
+        cli.role = config.role.clone();
+    }
+    if cli.max_results.is_none() {
+        cli.max_results = config.max_results;
+    }
+    if cli.engine.is_none()
+        && let Some(engine) = &config.engine
+    {
+        cli.engine = Some(
+            EngineType::from_str(engine, true)
+                .map_err(|e| anyhow::anyhow!("invalid engine in config.toml: {e}"))?,
+        );
+    }
+    if config.color == Some(false) {
+        cli.no_color = true;
+    }
+    if config.exclude_sidechain == Some(false) {
+        cli.include_sidechain = true;
+    }
+    if config.exclude_meta == Some(false) {
+        cli.include_meta = true;
+    }
+    if config.hide_tool_results == Some(true) {
+        cli.hide_tool_results = true;
+    }
+    Ok(())
+}
+
+fn handle_config(command: &ConfigSubcommand) -> Result<()> {
+    let path = Config::default_path()?;
+    match command {
+        ConfigSubcommand::Init { stdout: true, .. } => print!("{}", config::TEMPLATE),
+        ConfigSubcommand::Init { force, .. } => {
+            config::init(&path, *force)?;
+            println!("Wrote {}", path.display());
+        }
+        ConfigSubcommand::Path => println!("{}", path.display()),
     }
+    Ok(())
+}
 
-    // Handle --latest-session mode
-    if cli.latest_session {
-        if cli.query.as_ref().map(|q| !q.is_empty()).unwrap_or(false) {
-            eprintln!("Error: --latest-session cannot be used with a search query");
-            std::process::exit(1);
-        }
+fn handle_lsp_like(args: &LspLikeArgs, verbose: bool) -> Result<()> {
+    anyhow::ensure!(
+        args.stdio,
+        "only the stdio transport is supported; pass --stdio"
+    );
 
-        let options = SearchOptions {
-            max_results: None, // Interactive mode should not be limited by max_results
-            role: cli.role,
-            session_id: None,
-            message_id: None,
-            before: cli.before,
-            after: parsed_after.clone(),
-            verbose: cli.verbose,
-            project_path: project_path.clone(),
-        };
+    let pattern = args.pattern.clone().unwrap_or_else(default_claude_pattern);
+    let options = SearchOptions {
+        project_path: args.project_path.clone(),
+        index_path: if args.use_index {
+            Some(SearchIndex::default_path()?)
+        } else {
+            None
+        },
+        verbose,
+        ..Default::default()
+    };
 
-        let mut interactive = InteractiveSearch::new(options);
-        interactive.set_start_latest(true);
-        return interactive.run(pattern);
-    }
+    let stdin = io::stdin();
+    RpcServer::new(Service::new(pattern, options), io::stdout()).run(stdin.lock())
+}
 
-    // Interactive mode when no query provided or query is empty (but not when --stats is used)
-    if !cli.stats
-        && (cli.query.is_none() || cli.query.as_ref().map(|s| s.is_empty()).unwrap_or(false))
-    {
-        let options = SearchOptions {
-            max_results: None, // Interactive mode should not be limited by max_results
-            role: cli.role,
-            session_id: cli.session_id,
-            message_id: None,
-            before: cli.before,
-            after: parsed_after.clone(),
-            verbose: cli.verbose,
-            project_path: project_path.clone(),
-        };
+fn handle_serve(args: &ServeArgs, verbose: bool) -> Result<()> {
+    anyhow::ensure!(
+        args.mcp || args.metrics.is_some() || args.http.is_some(),
+        "no server mode selected; pass --mcp, --metrics <ADDR> and/or --http <ADDR>"
+    );
 
-        let mut interactive = InteractiveSearch::new(options);
-        return interactive.run(pattern);
+    let pattern = args.pattern.clone().unwrap_or_else(default_claude_pattern);
+    let options = SearchOptions {
+        project_path: args.project_path.clone(),
+        index_path: if args.use_index {
+            Some(SearchIndex::default_path()?)
+        } else {
+            None
+        },
+        verbose,
+        ..Default::default()
+    };
+
+    // Bind everything before serving so a bad address fails the command
+    let listen = |addr: &str| {
+        std::net::TcpListener::bind(addr).with_context(|| format!("failed to listen on {addr}"))
+    };
+    let metrics_listener = args.metrics.as_deref().map(listen).transpose()?;
+    let http_listener = args.http.as_deref().map(listen).transpose()?;
+
+    let mut servers = Vec::new();
+    if let Some(listener) = metrics_listener {
+        let index_path = SearchIndex::default_path()?;
+        let pattern = pattern.clone();
+        if !args.mcp {
+            eprintln!(
+                "Serving metrics at http://{}/metrics",
+                listener.local_addr()?
+            );
+        }
+        servers.push(std::thread::spawn(move || {
+            metrics::serve_on(listener, pattern, Some(index_path))
+        }));
+    }
+    if let Some(listener) = http_listener {
+        let service = Arc::new(Service::new(pattern.clone(), options.clone()));
+        if !args.mcp {
+            eprintln!("Serving the HTTP API at http://{}", listener.local_addr()?);
+        }
+        servers.push(std::thread::spawn(move || {
+            http::serve_on(listener, service)
+        }));
     }
 
-    // Regular search mode - query is provided (or empty string for --stats)
-    let query_str = cli.query.unwrap_or_else(String::new);
+    if args.mcp {
+        let stdin = io::stdin();
+        return McpServer::new(Service::new(pattern, options)).run(stdin.lock(), io::stdout());
+    }
+    for server in servers {
+        let _ = server.join();
+    }
+    Ok(())
+}
 
-    // Parse the query (empty query for --stats means match all)
-    let query = if cli.stats && query_str.is_empty() {
-        // Empty query for stats: match everything
-        QueryCondition::Literal {
-            pattern: String::new(),
-            case_sensitive: false,
+/// Results of the search run on `ccms daemon`, or `None` when no daemon
+/// answers and the search should run locally
+#[cfg(unix)]
+fn search_on_daemon(
+    pattern: &str,
+    query: &QueryCondition,
+    options: &SearchOptions,
+    order: SearchOrder,
+    verbose: bool,
+) -> Option<(Vec<SearchResult>, std::time::Duration, usize, ScanStats)> {
+    let request = DaemonSearch {
+        pattern: pattern.to_string(),
+        query: query.clone(),
+        options: options.clone(),
+        order,
+    };
+    let reply = daemon::default_socket_path().and_then(|socket| daemon::search(&socket, &request));
+    match reply {
+        Ok(reply) => {
+            if verbose {
+                eprintln!("Searched by the daemon: {}", reply.scan.summary());
+            }
+            Some((
+                reply.results,
+                std::time::Duration::from_millis(reply.duration_ms),
+                reply.total_count,
+                reply.scan,
+            ))
         }
-    } else {
-        match parse_query(&query_str) {
-            Ok(q) => q,
-            Err(e) => {
-                eprintln!("Error parsing query: {e}");
-                eprintln!("Use --help-query for query syntax help");
-                std::process::exit(1);
+        Err(e) => {
+            if verbose {
+                eprintln!("Searching locally: {e:#}");
             }
+            None
         }
-    };
+    }
+}
 
-    // Create search options
-    let options = SearchOptions {
-        max_results: if cli.stats {
-            None // Don't limit results when calculating statistics
-        } else {
-            Some(cli.max_results)
-        },
-        role: cli.role,
-        session_id: cli.session_id,
-        message_id: None,
-        before: cli.before,
-        after: parsed_after,
-        verbose: cli.verbose,
-        project_path,
+#[cfg(not(unix))]
+fn search_on_daemon(
+    _pattern: &str,
+    _query: &QueryCondition,
+    _options: &SearchOptions,
+    _order: SearchOrder,
+    verbose: bool,
+) -> Option<(Vec<SearchResult>, std::time::Duration, usize, ScanStats)> {
+    if verbose {
+        eprintln!("Searching locally: ccms daemon needs unix domain sockets");
+    }
+    None
+}
+
+#[cfg(unix)]
+fn handle_daemon(args: &DaemonArgs) -> Result<()> {
+    let socket = match &args.socket {
+        Some(socket) => socket.clone(),
+        None => daemon::default_socket_path()?,
     };
+    let pattern = args.pattern.clone().unwrap_or_else(default_claude_pattern);
+
+    let listener = daemon::bind(&socket)?;
+    let daemon = Arc::new(Daemon::new(pattern));
+    let stats = daemon.refresh()?;
+    eprintln!(
+        "Loaded {} session files; listening on {}",
+        stats.indexed,
+        socket.display()
+    );
+    daemon.serve(listener)
+}
 
-    if cli.verbose {
-        eprintln!("Searching in: {pattern}");
-        eprintln!("Query: {query:?}");
-    }
+#[cfg(not(unix))]
+fn handle_daemon(_args: &DaemonArgs) -> Result<()> {
+    anyhow::bail!("ccms daemon needs unix domain sockets, which this platform does not have")
+}
 
-    // Debug: only search specific file
-    let debug_file = "/Users/masatomokusaka/.claude/projects/-Users-masatomokusaka-src-github-com-mkusaka-bookmark-agent/9ca2db47-82d6-4da7-998e-3d7cd28ce5b5.jsonl";
-    let pattern_to_use = if std::env::var("DEBUG_SINGLE_FILE").is_ok() {
-        eprintln!("DEBUG: Searching only {debug_file}");
-        debug_file
+fn handle_script(args: &ScriptArgs) -> Result<()> {
+    let json = if args.actions.as_os_str() == "-" {
+        io::read_to_string(io::stdin())?
     } else {
-        pattern
+        std::fs::read_to_string(&args.actions)
+            .with_context(|| format!("failed to read {}", args.actions.display()))?
     };
+    let actions = load_actions(&json)?;
 
-    // Execute search
-    if cli.verbose {
-        eprintln!(
-            "Using {} engine",
-            match cli.engine {
-                EngineType::Smol => "Smol",
-                EngineType::Rayon => "Rayon",
-            }
-        );
+    let project_path = args.project_path.clone().or_else(|| {
+        std::env::current_dir()
+            .ok()
+            .and_then(|path| path.to_str().map(|s| s.to_string()))
+    });
+    let options = SearchOptions {
+        project_path,
+        cache_results: true,
+        ..Default::default()
+    };
+    let pattern = args.pattern.clone().unwrap_or_else(default_claude_pattern);
+
+    let mut interactive = InteractiveSearch::new(options);
+    if let Some(path) = &args.event_log {
+        interactive.set_event_log(EventLog::create(path)?);
     }
+    let snapshots = interactive.run_script(&pattern, &actions, args.width, args.height)?;
 
-    // Create appropriate engine based on CLI flag
-    let (results, duration, total_count) = match cli.engine {
-        EngineType::Smol => {
-            let engine = SmolEngine::new(options);
-            engine.search(pattern_to_use, query)?
+    match args.format {
+        OutputFormat::Text => {
+            for snapshot in &snapshots {
+                print!("{}", snapshot.to_text());
+            }
         }
-        EngineType::Rayon => {
-            let engine = RayonEngine::new(options);
-            engine.search(pattern_to_use, query)?
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&snapshots)?),
+        OutputFormat::JsonL => {
+            for snapshot in &snapshots {
+                println!("{}", serde_json::to_string(snapshot)?);
+            }
         }
-    };
+        OutputFormat::Raycast | OutputFormat::Telescope => {
+            anyhow::bail!("script supports --format text, json or jsonl");
+        }
+    }
+    Ok(())
+}
 
-    // If stats flag is set, collect and display statistics
-    if cli.stats {
-        let stats = collect_statistics(&results);
-        println!("{}", ccms::stats::format_statistics(&stats, !cli.no_color));
+fn handle_bench(args: &BenchArgs) -> Result<()> {
+    let query = match &args.query {
+        Some(query) => parse_query(query)?,
+        None => QueryCondition::Literal {
+            pattern: String::new(),
+            case_sensitive: false,
+        },
+    };
+    let project_path = args.project_path.clone().or_else(|| {
+        std::env::current_dir()
+            .ok()
+            .and_then(|path| path.to_str().map(|s| s.to_string()))
+    });
+    let options = SearchOptions {
+        max_results: None,
+        project_path,
+        ..Default::default()
+    };
+    let pattern = args.pattern.clone().unwrap_or_else(default_claude_pattern);
+
+    let config = Config::load(&Config::default_path()?)?;
+    let mut engines: Vec<Engine> = Vec::new();
+    for &engine in &args.engine {
+        let engine = resolve_engine(engine, &pattern, config.auto_engine(), false)?;
+        if !engines.contains(&engine) {
+            engines.push(engine);
+        }
+    }
+    if engines.is_empty() {
+        engines = vec![Engine::Smol, Engine::Rayon];
+    }
 
-        eprintln!("\n⏱️  Search completed in {}ms", duration.as_millis());
-        if total_count > results.len() {
-            eprintln!(
-                "(Showing stats for {} of {} total results)",
-                results.len(),
-                total_count
-            );
+    let benches = bench_engines(
+        &pattern,
+        &query,
+        &options,
+        &engines,
+        args.runs as usize,
+        args.warmup as usize,
+    )?;
+
+    match args.format {
+        OutputFormat::Text => print!("{}", format_bench_table(&benches)),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&benches)?),
+        OutputFormat::JsonL => {
+            for bench in &benches {
+                println!("{}", serde_json::to_string(bench)?);
+            }
         }
+        OutputFormat::Raycast | OutputFormat::Telescope => {
+            anyhow::bail!("bench supports --format text, json or jsonl");
+        }
+    }
+    Ok(())
+}
+
+fn handle_share(args: &ShareArgs) -> Result<()> {
+    let pattern = args.pattern.clone().unwrap_or_else(default_claude_pattern);
+    let scrubber = Scrubber::new(&args.redact)?;
+    let bundle = build_share_html(&pattern, &args.session_id, &scrubber)?;
+
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("ccms-share-{}.html", args.session_id)));
+    if output.as_os_str() == "-" {
+        io::stdout().write_all(bundle.html.as_bytes())?;
         return Ok(());
     }
 
-    // Output results
-    let stdout = io::stdout();
-    let mut handle = stdout.lock();
+    std::fs::write(&output, &bundle.html)
+        .with_context(|| format!("failed to write {}", output.display()))?;
+    println!("{}", output.display());
+    eprintln!(
+        "Shared {} messages. Personal paths, emails and credentials were scrubbed; \
+         review the page before sending it.",
+        bundle.message_count
+    );
+    Ok(())
+}
 
-    match cli.format {
-        OutputFormat::Text => {
-            if results.is_empty() {
-                println!("No results found.");
-            } else if cli.raw {
-                // Raw mode: output raw JSON lines
-                for result in &results {
-                    if let Some(raw_json) = &result.raw_json {
-                        println!("{raw_json}");
-                    }
-                }
-            } else {
-                println!("Found {} results:\n", results.len());
-                for result in &results {
-                    println!(
-                        "{}",
-                        format_search_result(result, !cli.no_color, cli.full_text)
-                    );
-                }
+/// Drop `.jsonl.gz` / `.jsonl.zst` archives from files about to be rewritten
+/// in place, saying so rather than silently leaving them out
+fn without_archives(mut files: Vec<PathBuf>) -> Vec<PathBuf> {
+    let before = files.len();
+    files.retain(|file| !is_compressed(file));
+    let skipped = before - files.len();
+    if skipped > 0 {
+        eprintln!(
+            "Skipping {skipped} compressed session archives; decompress them to modify their contents."
+        );
+    }
+    files
+}
 
-                // Print search statistics
-                eprintln!("\n⏱️  Search completed in {}ms", duration.as_millis());
-                if total_count > results.len() {
-                    eprintln!(
-                        "(Showing {} of {} total results)",
-                        results.len(),
-                        total_count
-                    );
-                } else {
-                    eprintln!("(Found {total_count} results)");
-                }
-            }
-        }
-        OutputFormat::Json => {
-            // Collect statistics
-            let mut session_counts: HashMap<String, usize> = HashMap::new();
-            let mut file_counts: HashMap<String, usize> = HashMap::new();
+fn handle_rewrite(args: &RewriteArgs) -> Result<()> {
+    let regex = regex::Regex::new(&args.match_regex)
+        .with_context(|| format!("invalid --match regex: {}", args.match_regex))?;
+    let files = without_archives(discover_claude_files(args.pattern.as_deref())?);
+    let plan = maintenance::plan_rewrite(&files, &regex, &args.replace)?;
+    for (path, reason) in &plan.skipped {
+        eprintln!(
+            "Warning: skipping {}: {reason}; it was not searched for matches",
+            path.display()
+        );
+    }
 
-            for result in &results {
-                *session_counts.entry(result.session_id.clone()).or_insert(0) += 1;
-                *file_counts.entry(result.file.clone()).or_insert(0) += 1;
-            }
+    if plan.files.is_empty() {
+        println!("No matches in {} session files.", files.len());
+        return Ok(());
+    }
 
-            // Create detailed file information
-            let files_detail: Vec<_> = file_counts
-                .iter()
-                .map(|(file, count)| {
-                    serde_json::json!({
-                        "path": file,
-                        "message_count": count,
-                        "session_id": results.iter()
-                            .find(|r| &r.file == file)
-                            .map(|r| &r.session_id)
-                            .unwrap_or(&String::new())
-                    })
-                })
-                .collect();
+    for file in &plan.files {
+        println!("{} ({} matches)", file.path.display(), file.matches);
+        for (line, preview) in &file.changes {
+            println!("  {line}: {preview}");
+        }
+    }
+    println!(
+        "\n{} matches on {} lines in {} files.",
+        plan.matches(),
+        plan.changed_lines(),
+        plan.files.len()
+    );
 
-            // Create detailed session information
-            let sessions_detail: Vec<_> = session_counts
-                .iter()
-                .map(|(session_id, count)| {
-                    serde_json::json!({
-                        "session_id": session_id,
-                        "message_count": count
-                    })
-                })
-                .collect();
+    if !args.apply {
+        println!("Dry run; re-run with --apply to rewrite the files.");
+        return Ok(());
+    }
 
-            let output = serde_json::json!({
-                "results": results,
-                "summary": {
-                    "duration_ms": duration.as_millis(),
-                    "total_count": total_count,
-                    "returned_count": results.len(),
-                    "unique_sessions": session_counts.len(),
-                    "unique_files": file_counts.len()
-                },
-                "files": files_detail,
-                "sessions": sessions_detail
-            });
-            serde_json::to_writer_pretty(&mut handle, &output)?;
-            writeln!(&mut handle)?;
-        }
-        OutputFormat::JsonL => {
-            for result in &results {
-                serde_json::to_writer(&mut handle, result)?;
-                writeln!(&mut handle)?;
+    maintenance::apply_rewrite(&plan)?;
+    println!(
+        "Rewrote {} files. The .bak copies still contain the original text; \
+         delete them once you have checked the result.",
+        plan.files.len()
+    );
+    Ok(())
+}
+
+fn handle_sessions_validate(args: &SessionsValidateArgs) -> Result<()> {
+    let mut failed = 0;
+    for file in &args.files {
+        let validation = validate_lines(&maintenance::read_lines(file)?, None);
+        if validation.is_ok() {
+            println!("{}: ok ({} lines)", file.display(), validation.lines);
+        } else {
+            failed += 1;
+            println!("{}: {} problems", file.display(), validation.errors.len());
+            for error in &validation.errors {
+                println!("  {error}");
             }
-            // Write metadata as last line
-            let metadata = serde_json::json!({
-                "_metadata": {
-                    "duration_ms": duration.as_millis(),
-                    "total_count": total_count,
-                    "returned_count": results.len()
-                }
-            });
-            serde_json::to_writer(&mut handle, &metadata)?;
-            writeln!(&mut handle)?;
+        }
+        if validation.unrecognized > 0 {
+            println!(
+                "  {} lines of a type ccms does not recognize (kept as-is)",
+                validation.unrecognized
+            );
         }
     }
 
-    // Generate profiling report if requested
-    #[cfg(all(feature = "profiling", unix))]
-    if let Some(ref mut profiler) = profiler
-        && let Some(profile_path) = &cli.profile
-    {
-        let report = profiler.generate_comprehensive_report(profile_path)?;
-        eprintln!("\n{report}");
-        eprintln!("\nDetailed profiling reports saved to {profile_path}_{{comprehensive.txt,svg}}");
-    }
-
+    anyhow::ensure!(
+        failed == 0,
+        "{failed} of {} files failed validation",
+        args.files.len()
+    );
     Ok(())
 }
 
-fn parse_since_time(input: &str) -> Result<String> {
-    use anyhow::Context;
+fn handle_sessions_merge(args: &SessionsMergeArgs) -> Result<()> {
+    let files = without_archives(discover_claude_files(args.pattern.as_deref())?);
+    let session_id = resolve_session_id(&files, &args.session_id)?;
+    let plan = maintenance::plan_merge(&files, &session_id)?;
 
-    // First, try to parse as Unix timestamp
-    if let Ok(timestamp) = input.parse::<i64>() {
-        let dt = DateTime::<Utc>::from_timestamp(timestamp, 0).context("Invalid Unix timestamp")?;
-        return Ok(dt.to_rfc3339());
+    println!(
+        "{} {} files into {} ({} lines, {} duplicates dropped):",
+        if args.apply { "Merging" } else { "Would merge" },
+        plan.sources.len(),
+        plan.target.display(),
+        plan.lines.len(),
+        plan.duplicates
+    );
+    for source in &plan.sources {
+        println!("  {}", source.display());
     }
 
-    // Try to parse as relative/absolute time using parse_datetime
-    match parse_datetime(input) {
-        Ok(dt) => {
-            let ts = dt.timestamp();
-            let utc_dt =
-                DateTime::<Utc>::from_timestamp(ts.as_second(), ts.subsec_nanosecond() as u32)
-                    .context("Failed to convert parsed time to UTC timestamp")?;
-            Ok(utc_dt.to_rfc3339())
-        }
-        Err(e) => Err(anyhow::anyhow!(
-            "Failed to parse time '{input}': {e}. Expected Unix timestamp or relative time like '1 day ago'"
-        )),
+    if args.apply {
+        maintenance::apply_merge(&plan)?;
+        println!("Done. Originals were kept as .bak files.");
+    } else {
+        println!("Dry run; re-run with --apply to write the merged file.");
     }
+    Ok(())
 }
 
-fn handle_cli_command(command: &CliCommand, verbose: bool) -> Result<()> {
-    match command {
-        CliCommand::Convert(convert) => match &convert.command {
-            ConvertSubcommand::ClaudeToCodex(args) => {
-                handle_convert_claude_to_codex(args, verbose)?;
-            }
+fn handle_sessions_split(args: &SessionsSplitArgs) -> Result<()> {
+    let plan = maintenance::plan_split(&args.file)?;
+
+    println!(
+        "{} {} into {} files:",
+        if args.apply {
+            "Splitting"
+        } else {
+            "Would split"
         },
+        plan.source.display(),
+        plan.parts.len()
+    );
+    for part in &plan.parts {
+        println!("  {} ({} lines)", part.path.display(), part.lines.len());
     }
 
+    if args.apply {
+        maintenance::apply_split(&plan)?;
+        println!(
+            "Done. The original was kept as {}.",
+            maintenance::backup_path(&plan.source).display()
+        );
+    } else {
+        println!("Dry run; re-run with --apply to write the files.");
+    }
     Ok(())
 }
 
@@ -646,6 +2490,437 @@ fn handle_convert_claude_to_codex(args: &ConvertClaudeToCodexArgs, verbose: bool
     Ok(())
 }
 
+fn handle_todos(args: &TodosArgs) -> Result<()> {
+    let project_path = args.project_path.clone().or_else(|| {
+        std::env::current_dir()
+            .ok()
+            .and_then(|path| path.to_str().map(|s| s.to_string()))
+    });
+
+    let files = discover_claude_files(args.pattern.as_deref())?;
+    let session_id = args
+        .session_id
+        .as_deref()
+        .map(|id| resolve_session_id(&files, id))
+        .transpose()?;
+    let mut sessions =
+        collect_latest_todos(&files, project_path.as_deref(), session_id.as_deref())?;
+    if !args.all {
+        sessions.retain(|session| session.open_count() > 0);
+    }
+
+    match args.format {
+        OutputFormat::Text => {
+            if sessions.is_empty() {
+                println!("No open todos found.");
+            } else {
+                print!("{}", format_todos(&sessions, !args.no_color));
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&sessions)?);
+        }
+        OutputFormat::JsonL => {
+            for session in &sessions {
+                println!("{}", serde_json::to_string(session)?);
+            }
+        }
+        OutputFormat::Raycast => {
+            println!(
+                "{}",
+                serde_json::to_string(&todos_script_filter(&sessions))?
+            );
+        }
+        OutputFormat::Telescope => {
+            print!("{}", todos_telescope(&sessions));
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_links(args: &LinksArgs) -> Result<()> {
+    let project_path = args.project_path.clone().or_else(|| {
+        std::env::current_dir()
+            .ok()
+            .and_then(|path| path.to_str().map(|s| s.to_string()))
+    });
+    let query = args.query.as_deref().map(parse_query).transpose()?;
+
+    let files = discover_claude_files(args.pattern.as_deref())?;
+    let links = collect_links(&files, project_path.as_deref(), query.as_ref())?;
+
+    match args.format {
+        OutputFormat::Text => {
+            if links.is_empty() {
+                println!("No links found.");
+            } else {
+                print!("{}", format_links(&links, !args.no_color));
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&links)?);
+        }
+        OutputFormat::JsonL => {
+            for link in &links {
+                println!("{}", serde_json::to_string(link)?);
+            }
+        }
+        OutputFormat::Raycast => {
+            println!("{}", serde_json::to_string(&links_script_filter(&links))?);
+        }
+        OutputFormat::Telescope => {
+            anyhow::bail!("links are not tied to a file line; use --format raycast or text");
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_recap(args: &RecapArgs) -> Result<()> {
+    let project_path = args.project_path.clone().or_else(|| {
+        std::env::current_dir()
+            .ok()
+            .and_then(|path| path.to_str().map(|s| s.to_string()))
+    });
+    let pattern = args.pattern.clone().unwrap_or_else(default_claude_pattern);
+    let options = SearchOptions {
+        project_path,
+        max_results: None,
+        ..Default::default()
+    };
+    let (results, _, _) = SmolEngine::new(options).search(&pattern, parse_query(&args.query)?)?;
+    let recaps = recap_sessions(&results)?;
+
+    match args.format {
+        OutputFormat::Text => {
+            if recaps.is_empty() {
+                println!("No sessions found.");
+            } else {
+                print!("{}", format_recap(&recaps, !args.no_color));
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&recaps)?);
+        }
+        OutputFormat::JsonL => {
+            for recap in &recaps {
+                println!("{}", serde_json::to_string(recap)?);
+            }
+        }
+        OutputFormat::Raycast | OutputFormat::Telescope => {
+            anyhow::bail!("recaps are only written as text, json or jsonl");
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_new(args: &NewArgs) -> Result<()> {
+    let run_start = chrono::Utc::now();
+    let path = Checkpoints::default_path()?;
+    let mut checkpoints = Checkpoints::load(&path)?;
+    let since = checkpoints.resolve(&args.since)?;
+    if args.advance && checkpoints.get(&args.since).is_none() {
+        anyhow::bail!(
+            "--advance needs a checkpoint name in --since; create one with `ccms checkpoint set`"
+        );
+    }
+
+    let query = match &args.query {
+        Some(query) => parse_query(query)?,
+        None => QueryCondition::Literal {
+            pattern: String::new(),
+            case_sensitive: false,
+        },
+    };
+
+    let files = files_modified_since(discover_claude_files(args.pattern.as_deref())?, since);
+    let options = SearchOptions {
+        project_path: args.project_path.clone(),
+        after: Some(since.to_rfc3339()),
+        max_results: args.max_results,
+        ..Default::default()
+    };
+    // Newest first so --max-results keeps the latest, then read in order
+    let (mut results, _, total_count) =
+        SmolEngine::new(options).search_files(files, query, None, SearchOrder::Descending)?;
+    results.reverse();
+
+    match args.format {
+        OutputFormat::Text => {
+            let since_local = since.with_timezone(&chrono::Local);
+            if results.is_empty() {
+                println!(
+                    "Nothing new since {}.",
+                    since_local.format("%Y-%m-%d %H:%M")
+                );
+            } else {
+                println!(
+                    "{total_count} new messages since {}:\n",
+                    since_local.format("%Y-%m-%d %H:%M")
+                );
+                for result in &results {
+                    println!(
+                        "{}",
+                        format_search_result(result, !args.no_color, args.full_text)
+                    );
+                }
+                if total_count > results.len() {
+                    eprintln!("(Showing the latest {} of {total_count})", results.len());
+                }
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        }
+        OutputFormat::JsonL => {
+            for result in &results {
+                println!("{}", serde_json::to_string(result)?);
+            }
+        }
+        OutputFormat::Raycast | OutputFormat::Telescope => {
+            anyhow::bail!("new messages are only written as text, json or jsonl");
+        }
+    }
+
+    // Messages written while this ran are shown again next time rather than missed
+    if args.advance {
+        checkpoints.set(&args.since, run_start);
+        checkpoints.save(&path)?;
+    }
+
+    Ok(())
+}
+
+fn handle_checkpoint(command: &CheckpointSubcommand) -> Result<()> {
+    let path = Checkpoints::default_path()?;
+    let mut checkpoints = Checkpoints::load(&path)?;
+
+    match command {
+        CheckpointSubcommand::Set { name, at } => {
+            let at = match at {
+                Some(at) => parse_time(at)?,
+                None => chrono::Utc::now(),
+            };
+            checkpoints.set(name, at);
+            checkpoints.save(&path)?;
+            println!("Checkpoint {name} set to {}", at.to_rfc3339());
+        }
+        CheckpointSubcommand::Remove { name } => {
+            if checkpoints.remove(name).is_none() {
+                anyhow::bail!("no checkpoint named '{name}'");
+            }
+            checkpoints.save(&path)?;
+            println!("Removed checkpoint {name}");
+        }
+        CheckpointSubcommand::List => {
+            if checkpoints.checkpoints.is_empty() {
+                println!("No checkpoints.");
+            }
+            let mut list: Vec<_> = checkpoints
+                .checkpoints
+                .keys()
+                .map(|name| (name, checkpoints.get(name)))
+                .collect();
+            list.sort_by_key(|(_, at)| *at);
+            for (name, at) in list {
+                let at = at.map_or_else(|| "(invalid time)".to_string(), |at| at.to_rfc3339());
+                println!("{name}\t{at}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_stats(args: &StatsArgs) -> Result<()> {
+    let project_path = args.project_path.clone().or_else(|| {
+        std::env::current_dir()
+            .ok()
+            .and_then(|path| path.to_str().map(|s| s.to_string()))
+    });
+
+    let files = discover_claude_files(args.pattern.as_deref())?;
+    let pricing = Config::load(&Config::default_path()?)?.pricing();
+    let report = collect_usage(&files, project_path.as_deref(), &pricing)?;
+
+    match args.format {
+        OutputFormat::Text => {
+            if report.messages == 0 {
+                println!("No messages found.");
+            } else {
+                print!("{}", format_usage_report(&report, !args.no_color));
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        OutputFormat::JsonL => {
+            println!("{}", serde_json::to_string(&report)?);
+        }
+        OutputFormat::Raycast | OutputFormat::Telescope => {
+            anyhow::bail!("stats are a single report; use --format text or json");
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_bookmarks_export(args: &BookmarksExportArgs) -> Result<()> {
+    let store = BookmarkStore::load(&BookmarkStore::default_path()?)?;
+    if store.bookmarks.is_empty() {
+        println!("No bookmarks found.");
+        return Ok(());
+    }
+
+    let written = match args.format {
+        BookmarkExportFormat::Markdown => {
+            let template = ExportTemplate::load(&args.template)?;
+            export_bookmarks(&store.bookmarks, &args.output, &template)?
+        }
+    };
+
+    for path in &written {
+        println!("{}", path.display());
+    }
+    eprintln!(
+        "Exported {} bookmarks into {} files.",
+        store.bookmarks.len(),
+        written.len()
+    );
+
+    Ok(())
+}
+
+fn handle_index_build(args: &IndexBuildArgs) -> Result<()> {
+    let start = std::time::Instant::now();
+    let index_path = SearchIndex::default_path()?;
+    let previous = match SearchIndex::load(&index_path) {
+        Ok(index) => Some(index),
+        Err(e) => {
+            eprintln!("Rebuilding unreadable index: {e:#}");
+            None
+        }
+    };
+
+    let files = discover_claude_files(args.pattern.as_deref())?;
+    let (index, stats) = SearchIndex::build(&files, previous);
+    index.save(&index_path)?;
+
+    println!(
+        "Indexed {} messages from {} files into {}",
+        index.message_count(),
+        index.files.len(),
+        index_path.display()
+    );
+    eprintln!(
+        "Re-read {} files, reused {}, removed {}, failed {} in {}ms",
+        stats.indexed,
+        stats.reused,
+        stats.removed,
+        stats.failed,
+        start.elapsed().as_millis()
+    );
+
+    Ok(())
+}
+
+fn handle_index_clear() -> Result<()> {
+    let index_path = SearchIndex::default_path()?;
+    if index_path.exists() {
+        std::fs::remove_file(&index_path)?;
+        println!("Removed {}", index_path.display());
+    } else {
+        println!("No index at {}", index_path.display());
+    }
+    Ok(())
+}
+
+fn handle_saved(command: &SavedSubcommand) -> Result<()> {
+    let path = SavedSearches::default_path()?;
+    let mut saved = SavedSearches::load(&path)?;
+
+    match command {
+        SavedSubcommand::Add { name, query } => {
+            saved.insert(name, query)?;
+            saved.save(&path)?;
+            println!("Saved ${name}");
+        }
+        SavedSubcommand::Remove { name } => {
+            if saved.remove(name).is_none() {
+                anyhow::bail!("no saved search named '{name}'");
+            }
+            saved.save(&path)?;
+            println!("Removed ${name}");
+        }
+        SavedSubcommand::List => {
+            if saved.searches.is_empty() {
+                println!("No saved searches.");
+            }
+            for (name, search) in &saved.searches {
+                let mut line = format!("${name}\t{}", search.query);
+                for arg in search.filters.to_args() {
+                    line.push(' ');
+                    line.push_str(&shell_quote(&arg));
+                }
+                println!("{line}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Save `query` as `name` with the filter flags of `cli` (`--save`)
+fn save_search(name: &str, query: &str, cli: &Cli) -> Result<()> {
+    let filters = SearchFilters {
+        role: cli.role.clone(),
+        since: cli.since.clone(),
+        after: cli.after.clone(),
+        before: cli.before.clone(),
+        project: cli.project_path.clone(),
+    };
+    let path = SavedSearches::default_path()?;
+    let mut saved = SavedSearches::load(&path)?;
+    saved.insert_with_filters(name, query, filters)?;
+    saved.save(&path)?;
+    eprintln!("Saved ${name}; run it again with `ccms run {name}`");
+    Ok(())
+}
+
+/// The command line of the search saved as `run.name`: its query and
+/// filters, then the flags given to `ccms run`
+fn saved_search_cli(run: &RunArgs) -> Result<Cli> {
+    let saved = SavedSearches::load(&SavedSearches::default_path()?)?;
+    let Some(search) = saved.searches.get(&run.name) else {
+        anyhow::bail!(
+            "no saved search named '{}' (see `ccms saved list`)",
+            run.name
+        );
+    };
+
+    let mut args = vec!["ccms".to_string()];
+    args.extend(search.filters.to_args());
+    args.extend(run.args.iter().cloned());
+    // After `--` so that a query starting with '-' is not taken for a flag
+    args.extend(["--".to_string(), search.query.clone()]);
+    Ok(Cli::parse_from(args))
+}
+
+/// `arg` as it would be typed in a shell
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:@,+=".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
 fn print_message_details(result: &SearchResult, use_color: bool) {
     use chrono::{DateTime, Local, TimeZone};
     use colored::Colorize;
@@ -827,49 +3102,58 @@ mod tests {
 
         let results = vec![
             SearchResult {
-                file: "file1.jsonl".to_string(),
+                file: "file1.jsonl".into(),
                 uuid: "uuid1".to_string(),
                 timestamp: "2024-01-01T00:00:00Z".to_string(),
-                session_id: "session1".to_string(),
-                role: "user".to_string(),
+                session_id: "session1".into(),
+                role: "user".into(),
                 text: "test message 1".to_string(),
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: "test".to_string(),
                     case_sensitive: false,
                 },
-                cwd: "/project1".to_string(),
+                cwd: "/project1".into(),
                 raw_json: None,
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
             SearchResult {
-                file: "file1.jsonl".to_string(),
+                file: "file1.jsonl".into(),
                 uuid: "uuid2".to_string(),
                 timestamp: "2024-01-01T01:00:00Z".to_string(),
-                session_id: "session1".to_string(),
-                role: "assistant".to_string(),
+                session_id: "session1".into(),
+                role: "assistant".into(),
                 text: "test response 1".to_string(),
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: "test".to_string(),
                     case_sensitive: false,
                 },
-                cwd: "/project1".to_string(),
+                cwd: "/project1".into(),
                 raw_json: None,
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
             SearchResult {
-                file: "file2.jsonl".to_string(),
+                file: "file2.jsonl".into(),
                 uuid: "uuid3".to_string(),
                 timestamp: "2024-01-02T00:00:00Z".to_string(),
-                session_id: "session2".to_string(),
-                role: "user".to_string(),
+                session_id: "session2".into(),
+                role: "user".into(),
                 text: "test message 2".to_string(),
-                message_type: "message".to_string(),
+                message_type: "message".into(),
                 query: QueryCondition::Literal {
                     pattern: "test".to_string(),
                     case_sensitive: false,
                 },
-                cwd: "/project2".to_string(),
+                cwd: "/project2".into(),
                 raw_json: None,
+                line_number: None,
+                matched_in: Vec::new(),
+                has_images: false,
             },
         ];
 
@@ -944,6 +3228,119 @@ mod tests {
         assert!(parsed.is_err());
     }
 
+    #[test]
+    fn test_cli_parse_exclusions() {
+        let parsed = Cli::try_parse_from([
+            "ccms",
+            "error",
+            "--exclude-role",
+            "system,summary",
+            "--exclude-role",
+            "user",
+            "--exclude-project",
+            "/a",
+            "--exclude-project",
+            "/b",
+            "--not",
+            "test OR mock",
+        ])
+        .expect("exclusion flags should parse");
+
+        assert_eq!(parsed.exclude_role, vec!["system", "summary", "user"]);
+        assert_eq!(parsed.exclude_project, vec!["/a", "/b"]);
+        assert!(parsed.exclude_session.is_empty());
+        assert_eq!(parsed.not.as_deref(), Some("test OR mock"));
+    }
+
+    #[test]
+    fn test_cli_parse_pipeline_output() {
+        let parsed = Cli::try_parse_from(["ccms", "-l0", "error"]).expect("-l0 should parse");
+        assert!(parsed.files_with_matches && parsed.null);
+        let parsed =
+            Cli::try_parse_from(["ccms", "--count", "error"]).expect("--count should parse");
+        assert!(parsed.count);
+
+        assert!(Cli::try_parse_from(["ccms", "-l", "-c", "error"]).is_err());
+        assert!(Cli::try_parse_from(["ccms", "-c", "-C", "2", "error"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_webhook_without_watch() {
+        // $CCMS_WEBHOOK_URL set in the shell must not break ordinary searches
+        let parsed = Cli::try_parse_from(["ccms", "--webhook", "https://example.com/x", "error"])
+            .expect("--webhook should parse without --watch");
+        assert!(!parsed.watch);
+    }
+
+    #[test]
+    fn test_cli_parse_jsonl_alias() {
+        // Error messages and the README spell it jsonl
+        for format in ["json-l", "jsonl"] {
+            let parsed = Cli::try_parse_from(["ccms", "-f", format, "error"])
+                .unwrap_or_else(|e| panic!("-f {format} should parse: {e}"));
+            assert!(matches!(parsed.format, OutputFormat::JsonL));
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_exec() {
+        let parsed = Cli::try_parse_from(["ccms", "-w", "--exec", "notify-send {{text}}", "error"])
+            .expect("--exec should parse with --watch");
+        assert_eq!(parsed.exec.as_deref(), Some("notify-send {{text}}"));
+        assert!(Cli::try_parse_from(["ccms", "--exec", "echo", "error"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_when() {
+        let parsed = Cli::try_parse_from(["ccms", "--when", "last tuesday..yesterday", "error"])
+            .expect("--when should parse");
+        assert_eq!(parsed.when.as_deref(), Some("last tuesday..yesterday"));
+
+        let conflict =
+            Cli::try_parse_from(["ccms", "--when", "..yesterday", "--since", "1 day ago"]);
+        assert!(conflict.is_err());
+    }
+
+    #[test]
+    fn test_apply_config() {
+        let config = Config::parse(
+            r#"
+            pattern = "/archive/**/*.jsonl"
+            engine = "rayon"
+            max_results = 20
+            color = false
+            role = "assistant"
+            exclude_meta = false
+            hide_tool_results = true
+            "#,
+        )
+        .unwrap();
+
+        let mut cli = Cli::try_parse_from(["ccms", "error"]).unwrap();
+        apply_config(&mut cli, &config).unwrap();
+        assert_eq!(cli.pattern.as_deref(), Some("/archive/**/*.jsonl"));
+        assert_eq!(cli.engine, Some(EngineType::Rayon));
+        assert_eq!(cli.max_results, Some(20));
+        assert!(cli.no_color);
+        assert_eq!(cli.role.as_deref(), Some("assistant"));
+        assert!(cli.include_meta);
+        assert!(!cli.include_sidechain);
+        assert!(cli.hide_tool_results);
+
+        // Flags win over the config
+        let mut cli =
+            Cli::try_parse_from(["ccms", "-n", "5", "--engine", "smol", "-r", "user", "error"])
+                .unwrap();
+        apply_config(&mut cli, &config).unwrap();
+        assert_eq!(cli.max_results, Some(5));
+        assert_eq!(cli.engine, Some(EngineType::Smol));
+        assert_eq!(cli.role.as_deref(), Some("user"));
+
+        let config = Config::parse(r#"engine = "turbo""#).unwrap();
+        let mut cli = Cli::try_parse_from(["ccms", "error"]).unwrap();
+        assert!(apply_config(&mut cli, &config).is_err());
+    }
+
     #[test]
     fn test_cli_parse_convert_subcommand() {
         let parsed = Cli::try_parse_from([
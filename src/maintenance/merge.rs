@@ -0,0 +1,167 @@
+use super::{SessionLine, back_up, backup_path, order_by_timestamp, read_lines, write_validated};
+use anyhow::{Context, Result, bail};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub struct MergePlan {
+    pub session_id: String,
+    /// File that will hold the merged session
+    pub target: PathBuf,
+    /// Every file holding part of the session, `target` included
+    pub sources: Vec<PathBuf>,
+    /// Merged lines in timestamp order
+    pub lines: Vec<SessionLine>,
+    /// Lines present in more than one source, written once
+    pub duplicates: usize,
+}
+
+/// Find the files among `files` that hold messages of `session_id` and
+/// compute their merged contents
+pub fn plan_merge(files: &[PathBuf], session_id: &str) -> Result<MergePlan> {
+    let mut sources: Vec<(PathBuf, Vec<SessionLine>)> = Vec::new();
+    for file in files {
+        let contents = fs::read_to_string(file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        // Cheap pre-filter before parsing every line of every file
+        if !contents.contains(session_id) {
+            continue;
+        }
+        let lines = read_lines(file)?;
+        if !lines
+            .iter()
+            .any(|line| line.session_id() == Some(session_id))
+        {
+            continue;
+        }
+        if let Some(other) = lines
+            .iter()
+            .filter_map(SessionLine::session_id)
+            .find(|id| *id != session_id)
+        {
+            bail!(
+                "{} also contains session {other}; merging it would mix sessions",
+                file.display()
+            );
+        }
+        sources.push((file.clone(), lines));
+    }
+
+    if sources.len() < 2 {
+        bail!(
+            "session {session_id} is stored in {} file(s); nothing to merge",
+            sources.len()
+        );
+    }
+
+    // Keep the file named after the session, or else the one that starts first
+    sources.sort_by_key(|(_, lines)| lines.iter().find_map(SessionLine::timestamp));
+    let target = sources
+        .iter()
+        .map(|(path, _)| path)
+        .find(|path| path.file_stem().is_some_and(|stem| stem == session_id))
+        .unwrap_or(&sources[0].0)
+        .clone();
+
+    let mut seen = HashSet::new();
+    let mut duplicates = 0;
+    let mut lines = Vec::new();
+    for (_, source_lines) in &sources {
+        for line in source_lines {
+            let key = line.uuid().unwrap_or(&line.text).to_string();
+            if seen.insert(key) {
+                lines.push(line.clone());
+            } else {
+                duplicates += 1;
+            }
+        }
+    }
+
+    Ok(MergePlan {
+        session_id: session_id.to_string(),
+        target,
+        sources: sources.into_iter().map(|(path, _)| path).collect(),
+        lines: order_by_timestamp(lines),
+        duplicates,
+    })
+}
+
+/// Write the merged file, then move every source aside as a `.bak` file
+pub fn apply_merge(plan: &MergePlan) -> Result<()> {
+    check_backups_free(&plan.sources)?;
+    let temp = write_validated(&plan.target, &plan.lines, Some(&plan.session_id), None)?;
+    for source in &plan.sources {
+        back_up(source)?;
+    }
+    fs::rename(&temp, &plan.target)
+        .with_context(|| format!("failed to move merged file to {}", plan.target.display()))
+}
+
+fn check_backups_free(paths: &[PathBuf]) -> Result<()> {
+    if let Some(backup) = paths
+        .iter()
+        .map(|path| backup_path(path))
+        .find(|backup| Path::exists(backup))
+    {
+        bail!(
+            "backup {} already exists; move it away first",
+            backup.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn line(uuid: &str, session: &str, second: u32) -> String {
+        format!(
+            r#"{{"type":"user","uuid":"{uuid}","sessionId":"{session}","timestamp":"2024-01-01T00:00:{second:02}Z"}}"#
+        )
+    }
+
+    #[test]
+    fn test_merge_split_session() -> Result<()> {
+        let dir = tempdir()?;
+        let main = dir.path().join("s1.jsonl");
+        let stray = dir.path().join("other.jsonl");
+        let unrelated = dir.path().join("s2.jsonl");
+        fs::write(
+            &main,
+            format!("{}\n{}\n", line("c", "s1", 3), line("d", "s1", 4)),
+        )?;
+        // The stray file repeats "c" and holds the start of the session
+        fs::write(
+            &stray,
+            format!("{}\n{}\n", line("a", "s1", 1), line("c", "s1", 3)),
+        )?;
+        fs::write(&unrelated, format!("{}\n", line("x", "s2", 1)))?;
+        let files = vec![main.clone(), stray.clone(), unrelated.clone()];
+
+        let plan = plan_merge(&files, "s1")?;
+        assert_eq!(plan.target, main);
+        assert_eq!(plan.sources.len(), 2);
+        assert_eq!(plan.duplicates, 1);
+        let uuids: Vec<_> = plan.lines.iter().filter_map(SessionLine::uuid).collect();
+        assert_eq!(uuids, vec!["a", "c", "d"]);
+
+        apply_merge(&plan)?;
+        let merged = read_lines(&main)?;
+        assert_eq!(merged.len(), 3);
+        assert!(!stray.exists());
+        assert!(backup_path(&stray).exists());
+        assert!(backup_path(&main).exists());
+        assert!(unrelated.exists());
+
+        // Backups are never overwritten
+        fs::write(&stray, format!("{}\n", line("e", "s1", 5)))?;
+        let plan = plan_merge(&files, "s1")?;
+        assert!(apply_merge(&plan).is_err());
+
+        assert!(plan_merge(&files, "s2").is_err());
+        Ok(())
+    }
+}
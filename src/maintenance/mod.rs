@@ -0,0 +1,218 @@
+//! Repairs for session files: merging a session that ended up split across
+//! several files, splitting a giant file by day, and rewriting text (e.g. a
+//! pasted secret) across files.
+//!
+//! All of them work in two steps. `plan_*` reads the inputs and computes the new
+//! files without writing anything, so callers can show a dry run. `apply_*`
+//! writes every output to a temporary file, reads it back through the
+//! validator, and only then moves the originals aside as `.bak` files and puts
+//! the outputs in place. Nothing is deleted.
+
+mod merge;
+mod rewrite;
+mod split;
+mod validate;
+
+pub use merge::{MergePlan, apply_merge, plan_merge};
+pub use rewrite::{RewriteFile, RewritePlan, apply_rewrite, plan_rewrite};
+pub use split::{SplitPart, SplitPlan, apply_split, plan_split};
+pub use validate::{Validation, validate_lines};
+
+use crate::search::compression::open_session;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// One line of a session file, kept verbatim alongside the fields we order by
+#[derive(Debug, Clone)]
+pub struct SessionLine {
+    pub text: String,
+    /// `None` when the line is not a JSON object
+    value: Option<Value>,
+    timestamp: Option<DateTime<Utc>>,
+}
+
+impl SessionLine {
+    pub fn parse(text: &str) -> Self {
+        let value = serde_json::from_str::<Value>(text)
+            .ok()
+            .filter(Value::is_object);
+        let timestamp = value
+            .as_ref()
+            .and_then(|value| value.get("timestamp"))
+            .and_then(Value::as_str)
+            .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+            .map(|timestamp| timestamp.with_timezone(&Utc));
+        Self {
+            text: text.to_string(),
+            value,
+            timestamp,
+        }
+    }
+
+    pub fn uuid(&self) -> Option<&str> {
+        self.value.as_ref()?.get("uuid")?.as_str()
+    }
+
+    pub fn session_id(&self) -> Option<&str> {
+        self.value.as_ref()?.get("sessionId")?.as_str()
+    }
+
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.timestamp
+    }
+}
+
+/// Non-empty lines of `path`
+pub fn read_lines(path: &Path) -> Result<Vec<SessionLine>> {
+    let mut lines = Vec::new();
+    for line in open_session(path)?.lines() {
+        let line = line.with_context(|| format!("failed to read {}", path.display()))?;
+        if !line.trim().is_empty() {
+            lines.push(SessionLine::parse(&line));
+        }
+    }
+    Ok(lines)
+}
+
+/// Stable sort by timestamp. Lines without one (summaries, snapshots) keep
+/// their place after the line they followed, so leading summaries stay first.
+pub fn order_by_timestamp(lines: Vec<SessionLine>) -> Vec<SessionLine> {
+    let mut current = None;
+    let mut keyed: Vec<(Option<DateTime<Utc>>, SessionLine)> = lines
+        .into_iter()
+        .map(|line| {
+            if line.timestamp.is_some() {
+                current = line.timestamp;
+            }
+            (current, line)
+        })
+        .collect();
+    keyed.sort_by_key(|(key, _)| *key);
+    keyed.into_iter().map(|(_, line)| line).collect()
+}
+
+/// Where the original of `path` is kept once it has been replaced
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Write `lines` to a temporary file next to `path`, read it back and validate
+/// it. With a `baseline`, problems the original file already had are
+/// tolerated as long as the output has no more of them. Returns the temporary
+/// path; the caller moves it into place.
+fn write_validated(
+    path: &Path,
+    lines: &[SessionLine],
+    session_id: Option<&str>,
+    baseline: Option<&Validation>,
+) -> Result<PathBuf> {
+    let mut contents = String::new();
+    for line in lines {
+        contents.push_str(&line.text);
+        contents.push('\n');
+    }
+    write_contents_validated(path, &contents, lines.len(), session_id, baseline)
+}
+
+/// [`write_validated`] for contents written as they are, holding `lines`
+/// non-empty lines
+fn write_contents_validated(
+    path: &Path,
+    contents: &str,
+    lines: usize,
+    session_id: Option<&str>,
+    baseline: Option<&Validation>,
+) -> Result<PathBuf> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let temp = path.with_file_name(name);
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp)
+            .with_context(|| format!("failed to create {}", temp.display()))?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+
+        let written = read_lines(&temp)?;
+        if written.len() != lines {
+            bail!(
+                "{}: wrote {} lines but read back {}",
+                path.display(),
+                lines,
+                written.len()
+            );
+        }
+        let validation = validate_lines(&written, session_id);
+        let tolerated = baseline.map_or(0, |baseline| baseline.errors.len());
+        if validation.errors.len() > tolerated {
+            bail!(
+                "{} failed validation, nothing was replaced:\n  {}",
+                path.display(),
+                validation.errors.join("\n  ")
+            );
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => Ok(temp),
+        Err(error) => {
+            let _ = fs::remove_file(&temp);
+            Err(error)
+        }
+    }
+}
+
+/// Move `path` to its backup location, refusing to overwrite an older backup
+fn back_up(path: &Path) -> Result<()> {
+    let backup = backup_path(path);
+    if backup.exists() {
+        bail!(
+            "backup {} already exists; move it away first",
+            backup.display()
+        );
+    }
+    fs::rename(path, &backup).with_context(|| format!("failed to back up {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_by_timestamp_keeps_untimed_lines_in_place() {
+        let lines = [
+            r#"{"type":"summary","summary":"first"}"#,
+            r#"{"uuid":"b","timestamp":"2024-01-01T00:00:02Z"}"#,
+            r#"{"type":"file-history-snapshot"}"#,
+            r#"{"uuid":"a","timestamp":"2024-01-01T00:00:01Z"}"#,
+        ]
+        .into_iter()
+        .map(SessionLine::parse)
+        .collect();
+
+        let ordered: Vec<String> = order_by_timestamp(lines)
+            .into_iter()
+            .map(|line| line.text)
+            .collect();
+        assert_eq!(
+            ordered,
+            vec![
+                r#"{"type":"summary","summary":"first"}"#,
+                r#"{"uuid":"a","timestamp":"2024-01-01T00:00:01Z"}"#,
+                r#"{"uuid":"b","timestamp":"2024-01-01T00:00:02Z"}"#,
+                r#"{"type":"file-history-snapshot"}"#,
+            ]
+        );
+        assert_eq!(
+            backup_path(Path::new("/x/s.jsonl")),
+            PathBuf::from("/x/s.jsonl.bak")
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use super::{
+    SessionLine, Validation, back_up, backup_path, read_lines, validate_lines,
+    write_contents_validated,
+};
+use anyhow::{Context, Result, bail};
+use regex::{NoExpand, Regex};
+use std::fs;
+use std::path::PathBuf;
+
+/// Characters of context shown on each side of a rewritten match
+const PREVIEW_CONTEXT_CHARS: usize = 40;
+
+#[derive(Debug)]
+pub struct RewriteFile {
+    pub path: PathBuf,
+    /// Contents after the rewrite. Untouched lines, blank lines and line
+    /// endings (`\n` or `\r\n`) are kept byte for byte.
+    pub contents: String,
+    /// Non-empty lines in `contents`, to check the written file against
+    line_count: usize,
+    /// 1-based number of each changed line with its rewritten text around the first match
+    pub changes: Vec<(usize, String)>,
+    pub matches: usize,
+    /// Validation of the original, so the rewrite is only held to not making it worse
+    baseline: Validation,
+}
+
+#[derive(Debug, Default)]
+pub struct RewritePlan {
+    pub files: Vec<RewriteFile>,
+    /// Files that could not be read as text, such as ones that are not valid
+    /// UTF-8, with the reason; they are left as they are
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+impl RewritePlan {
+    pub fn matches(&self) -> usize {
+        self.files.iter().map(|file| file.matches).sum()
+    }
+
+    pub fn changed_lines(&self) -> usize {
+        self.files.iter().map(|file| file.changes.len()).sum()
+    }
+}
+
+/// Replace every match of `pattern` in the raw JSON lines of `files` with the
+/// literal `replacement`. The replacement is JSON-escaped, and a rewrite that
+/// would leave a line unparseable is refused. Files that cannot be read are
+/// listed in `skipped` rather than holding up the others.
+pub fn plan_rewrite(files: &[PathBuf], pattern: &Regex, replacement: &str) -> Result<RewritePlan> {
+    let escaped = serde_json::to_string(replacement)?;
+    let escaped = &escaped[1..escaped.len() - 1];
+    let mut plan = RewritePlan::default();
+
+    for path in files {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                plan.skipped.push((path.clone(), e.to_string()));
+                continue;
+            }
+        };
+        if !pattern.is_match(&contents) {
+            continue;
+        }
+
+        let baseline = validate_lines(&read_lines(path)?, None);
+        let mut rewritten = String::with_capacity(contents.len());
+        let mut changes = Vec::new();
+        let mut matches = 0;
+
+        for (index, segment) in contents.split_inclusive('\n').enumerate() {
+            let line = segment.trim_end_matches(['\r', '\n']);
+            let ending = &segment[line.len()..];
+            let Some(first) = pattern.find(line) else {
+                rewritten.push_str(segment);
+                continue;
+            };
+            matches += pattern.find_iter(line).count();
+            let text = pattern.replace_all(line, NoExpand(escaped));
+            if SessionLine::parse(line).value.is_some() && SessionLine::parse(&text).value.is_none()
+            {
+                bail!(
+                    "{}:{}: replacing the match would break the JSON on this line; narrow --match",
+                    path.display(),
+                    index + 1
+                );
+            }
+            changes.push((index + 1, preview(&text, first.start(), escaped.len())));
+            rewritten.push_str(&text);
+            rewritten.push_str(ending);
+        }
+
+        let line_count = rewritten
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .count();
+        plan.files.push(RewriteFile {
+            path: path.clone(),
+            contents: rewritten,
+            line_count,
+            changes,
+            matches,
+            baseline,
+        });
+    }
+
+    Ok(plan)
+}
+
+/// Validate every rewritten file, then swap them in, keeping the originals as `.bak`
+pub fn apply_rewrite(plan: &RewritePlan) -> Result<()> {
+    if let Some(backup) = plan
+        .files
+        .iter()
+        .map(|file| backup_path(&file.path))
+        .find(|backup| backup.exists())
+    {
+        bail!(
+            "backup {} already exists; move it away first",
+            backup.display()
+        );
+    }
+
+    let mut temps = Vec::new();
+    for file in &plan.files {
+        match write_contents_validated(
+            &file.path,
+            &file.contents,
+            file.line_count,
+            None,
+            Some(&file.baseline),
+        ) {
+            Ok(temp) => temps.push(temp),
+            Err(error) => {
+                for temp in &temps {
+                    let _ = fs::remove_file(temp);
+                }
+                return Err(error);
+            }
+        }
+    }
+
+    for (temp, file) in temps.iter().zip(&plan.files) {
+        back_up(&file.path)?;
+        fs::rename(temp, &file.path)
+            .with_context(|| format!("failed to move rewritten {}", file.path.display()))?;
+    }
+    Ok(())
+}
+
+/// `text` around the replacement starting at byte `start`, on one line
+fn preview(text: &str, start: usize, replacement_len: usize) -> String {
+    let end = start + replacement_len;
+    let before: String = text[..start]
+        .chars()
+        .rev()
+        .take(PREVIEW_CONTEXT_CHARS)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    let after: String = text[end.min(text.len())..]
+        .chars()
+        .take(PREVIEW_CONTEXT_CHARS)
+        .collect();
+    format!("…{before}{}{after}…", &text[start..end.min(text.len())])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_rewrite_secret() -> Result<()> {
+        let dir = tempdir()?;
+        let leaked = dir.path().join("leaked.jsonl");
+        let clean = dir.path().join("clean.jsonl");
+        let untouched = r#"{"uuid":"a","timestamp":"2024-01-01T00:00:00Z","text":"hello"}"#;
+        fs::write(
+            &leaked,
+            format!(
+                "{untouched}\n{}\n",
+                r#"{"uuid":"b","timestamp":"2024-01-01T00:00:01Z","text":"key sk-123 and sk-456"}"#
+            ),
+        )?;
+        fs::write(&clean, format!("{untouched}\n"))?;
+        let files = vec![leaked.clone(), clean.clone()];
+
+        let plan = plan_rewrite(&files, &Regex::new(r"sk-\d+")?, "[\"redacted\"]")?;
+        assert_eq!(plan.files.len(), 1);
+        assert_eq!(plan.matches(), 2);
+        assert_eq!(plan.changed_lines(), 1);
+        assert_eq!(plan.files[0].changes[0].0, 2);
+        assert!(
+            plan.files[0].changes[0]
+                .1
+                .contains(r#"key [\"redacted\"] and"#)
+        );
+
+        apply_rewrite(&plan)?;
+        let contents = fs::read_to_string(&leaked)?;
+        assert!(!contents.contains("sk-"));
+        assert!(contents.starts_with(&format!("{untouched}\n")));
+        let rewritten: serde_json::Value = serde_json::from_str(contents.lines().nth(1).unwrap())?;
+        assert_eq!(rewritten["text"], r#"key ["redacted"] and ["redacted"]"#);
+        assert!(backup_path(&leaked).exists());
+        assert!(!backup_path(&clean).exists());
+
+        // A match spanning JSON structure cannot be replaced safely
+        let error = plan_rewrite(&[clean], &Regex::new(r#"hello"\}"#)?, "x").unwrap_err();
+        assert!(error.to_string().contains("would break the JSON"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_skips_unreadable_files() -> Result<()> {
+        let dir = tempdir()?;
+        let binary = dir.path().join("binary.jsonl");
+        let leaked = dir.path().join("leaked.jsonl");
+        fs::write(&binary, b"sk-123 \xff\xfe\n")?;
+        fs::write(
+            &leaked,
+            r#"{"uuid":"a","timestamp":"2024-01-01T00:00:00Z","text":"sk-123"}"#,
+        )?;
+
+        let plan = plan_rewrite(
+            &[binary.clone(), leaked.clone()],
+            &Regex::new(r"sk-\d+")?,
+            "x",
+        )?;
+        assert_eq!(plan.files.len(), 1);
+        assert_eq!(plan.files[0].path, leaked);
+        assert_eq!(plan.skipped.len(), 1);
+        assert_eq!(plan.skipped[0].0, binary);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_keeps_line_endings_and_blank_lines() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("crlf.jsonl");
+        let untouched = r#"{"uuid":"a","timestamp":"2024-01-01T00:00:00Z","text":"hello"}"#;
+        let leaked = r#"{"uuid":"b","timestamp":"2024-01-01T00:00:01Z","text":"key sk-123"}"#;
+        let original = format!("{untouched}\r\n\r\n{leaked}\r\n{untouched}");
+        fs::write(&path, &original)?;
+
+        let plan = plan_rewrite(std::slice::from_ref(&path), &Regex::new(r"sk-\d+")?, "x")?;
+        // Blank lines count, as in an editor
+        assert_eq!(plan.files[0].changes[0].0, 3);
+        apply_rewrite(&plan)?;
+
+        let expected = original.replace("sk-123", "x");
+        assert_eq!(fs::read_to_string(&path)?, expected);
+        assert_eq!(fs::read_to_string(backup_path(&path))?, original);
+        Ok(())
+    }
+}
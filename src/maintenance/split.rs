@@ -0,0 +1,152 @@
+use super::{SessionLine, back_up, backup_path, order_by_timestamp, read_lines, write_validated};
+use crate::search::compression::is_compressed;
+use anyhow::{Context, Result, bail};
+use chrono::NaiveDate;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub struct SplitPart {
+    pub path: PathBuf,
+    /// UTC day the part covers
+    pub day: NaiveDate,
+    pub lines: Vec<SessionLine>,
+}
+
+#[derive(Debug)]
+pub struct SplitPlan {
+    pub source: PathBuf,
+    pub parts: Vec<SplitPart>,
+}
+
+/// Split `path` into one `<stem>-<YYYY-MM-DD>.jsonl` file per UTC day.
+/// Lines without a timestamp stay with the line they followed.
+pub fn plan_split(path: &Path) -> Result<SplitPlan> {
+    if is_compressed(path) {
+        bail!(
+            "{} is a compressed archive; decompress it before splitting",
+            path.display()
+        );
+    }
+    let lines = order_by_timestamp(read_lines(path)?);
+    let Some(first_day) = lines
+        .iter()
+        .find_map(SessionLine::timestamp)
+        .map(|timestamp| timestamp.date_naive())
+    else {
+        bail!("{} has no timestamped messages to split by", path.display());
+    };
+
+    let stem = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let mut parts: Vec<SplitPart> = Vec::new();
+    for line in lines {
+        let day = line
+            .timestamp()
+            .map(|timestamp| timestamp.date_naive())
+            .or_else(|| parts.last().map(|part| part.day))
+            .unwrap_or(first_day);
+        match parts.last_mut() {
+            Some(part) if part.day == day => part.lines.push(line),
+            _ => parts.push(SplitPart {
+                path: path.with_file_name(format!("{stem}-{day}.jsonl")),
+                day,
+                lines: vec![line],
+            }),
+        }
+    }
+
+    if parts.len() < 2 {
+        bail!(
+            "{} only covers {first_day}; nothing to split",
+            path.display()
+        );
+    }
+    Ok(SplitPlan {
+        source: path.to_path_buf(),
+        parts,
+    })
+}
+
+/// Write every part, then move the source aside as a `.bak` file
+pub fn apply_split(plan: &SplitPlan) -> Result<()> {
+    if let Some(part) = plan.parts.iter().find(|part| part.path.exists()) {
+        bail!("{} already exists", part.path.display());
+    }
+    let backup = backup_path(&plan.source);
+    if backup.exists() {
+        bail!(
+            "backup {} already exists; move it away first",
+            backup.display()
+        );
+    }
+
+    let mut temps = Vec::new();
+    for part in &plan.parts {
+        match write_validated(&part.path, &part.lines, None, None) {
+            Ok(temp) => temps.push(temp),
+            Err(error) => {
+                for temp in &temps {
+                    let _ = fs::remove_file(temp);
+                }
+                return Err(error);
+            }
+        }
+    }
+
+    back_up(&plan.source)?;
+    for (temp, part) in temps.iter().zip(&plan.parts) {
+        fs::rename(temp, &part.path)
+            .with_context(|| format!("failed to move part to {}", part.path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_split_by_day() -> Result<()> {
+        let dir = tempdir()?;
+        let source = dir.path().join("big.jsonl");
+        fs::write(
+            &source,
+            [
+                r#"{"type":"summary","summary":"s"}"#,
+                r#"{"uuid":"a","timestamp":"2024-01-01T23:59:00Z"}"#,
+                r#"{"uuid":"c","timestamp":"2024-01-02T00:01:00Z"}"#,
+                r#"{"type":"file-history-snapshot"}"#,
+                r#"{"uuid":"b","timestamp":"2024-01-01T23:59:30Z"}"#,
+                "",
+            ]
+            .join("\n"),
+        )?;
+
+        let plan = plan_split(&source)?;
+        let days: Vec<String> = plan.parts.iter().map(|part| part.day.to_string()).collect();
+        assert_eq!(days, vec!["2024-01-01", "2024-01-02"]);
+        assert_eq!(plan.parts[0].lines.len(), 3);
+        assert_eq!(plan.parts[1].lines.len(), 2);
+
+        apply_split(&plan)?;
+        assert!(!source.exists());
+        assert!(backup_path(&source).exists());
+        let first = read_lines(&dir.path().join("big-2024-01-01.jsonl"))?;
+        let uuids: Vec<_> = first.iter().filter_map(SessionLine::uuid).collect();
+        assert_eq!(uuids, vec!["a", "b"]);
+        assert!(dir.path().join("big-2024-01-02.jsonl").exists());
+
+        let single = dir.path().join("single.jsonl");
+        fs::write(
+            &single,
+            r#"{"uuid":"a","timestamp":"2024-01-01T00:00:00Z"}"#,
+        )?;
+        assert!(plan_split(&single).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+//! Structural checks for session JSONL, run on every file the maintenance
+//! commands produce before any original is touched.
+
+use super::SessionLine;
+use crate::schemas::SessionMessage;
+use std::collections::HashSet;
+
+/// Errors reported per file before the rest are summarized
+const MAX_REPORTED_ERRORS: usize = 20;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validation {
+    pub lines: usize,
+    /// Valid JSON lines whose type ccms does not know (kept as-is, not an error)
+    pub unrecognized: usize,
+    pub errors: Vec<String>,
+}
+
+impl Validation {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    fn error(&mut self, message: String) {
+        if self.errors.len() == MAX_REPORTED_ERRORS {
+            self.errors.push("... further errors omitted".to_string());
+        } else if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(message);
+        }
+    }
+}
+
+/// Check that every line is a JSON object, UUIDs are unique, timestamps never
+/// go backwards and, when `session_id` is given, no other session is mixed in
+pub fn validate_lines(lines: &[SessionLine], session_id: Option<&str>) -> Validation {
+    let mut validation = Validation {
+        lines: lines.len(),
+        ..Default::default()
+    };
+    let mut uuids = HashSet::new();
+    let mut previous_timestamp = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        let number = index + 1;
+        let Some(value) = &line.value else {
+            validation.error(format!("line {number}: not a JSON object"));
+            continue;
+        };
+        if sonic_rs::from_str::<SessionMessage>(&line.text).is_err() {
+            validation.unrecognized += 1;
+        }
+
+        if let Some(uuid) = value.get("uuid").and_then(|uuid| uuid.as_str())
+            && !uuids.insert(uuid)
+        {
+            validation.error(format!("line {number}: duplicate uuid {uuid}"));
+        }
+
+        if let Some(timestamp) = line.timestamp {
+            if previous_timestamp.is_some_and(|previous| timestamp < previous) {
+                validation.error(format!("line {number}: timestamp goes backwards"));
+            }
+            previous_timestamp = Some(timestamp);
+        }
+
+        if let Some(expected) = session_id
+            && let Some(actual) = line.session_id()
+            && actual != expected
+        {
+            validation.error(format!(
+                "line {number}: belongs to session {actual}, expected {expected}"
+            ));
+        }
+    }
+
+    validation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_lines() {
+        let lines: Vec<SessionLine> = [
+            r#"{"type":"summary","summary":"s","leafUuid":"b"}"#,
+            r#"{"type":"user","uuid":"a","sessionId":"s1","timestamp":"2024-01-01T00:00:01Z"}"#,
+            r#"{"type":"user","uuid":"a","sessionId":"s2","timestamp":"2024-01-01T00:00:00Z"}"#,
+            "not json",
+        ]
+        .into_iter()
+        .map(SessionLine::parse)
+        .collect();
+
+        let validation = validate_lines(&lines, Some("s1"));
+        assert_eq!(validation.lines, 4);
+        // The test lines are valid JSON but not complete user messages
+        assert_eq!(validation.unrecognized, 2);
+        assert_eq!(
+            validation.errors,
+            vec![
+                "line 3: duplicate uuid a",
+                "line 3: timestamp goes backwards",
+                "line 3: belongs to session s2, expected s1",
+                "line 4: not a JSON object",
+            ]
+        );
+        assert!(validate_lines(&lines[..2], Some("s1")).is_ok());
+    }
+}
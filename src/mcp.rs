@@ -0,0 +1,439 @@
+//! `ccms serve --mcp`: a Model Context Protocol server over stdio.
+//!
+//! Messages are newline-delimited JSON-RPC 2.0, as the MCP stdio transport
+//! specifies. The server exposes past sessions to the model through three
+//! tools: `search_messages`, `get_session` and `get_message_by_id`.
+
+use crate::query::SearchResult;
+use crate::rpc::protocol::{
+    INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR, RpcError, error_response,
+    response,
+};
+use crate::service::{SearchParams, Service};
+use crate::utils::time_range::parse_time;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::io::{BufRead, Write};
+
+/// Protocol revision answered when the client asks for one we do not know
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+/// Results returned by `search_messages` when the model gives no limit
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+#[derive(Deserialize)]
+struct SearchArguments {
+    query: String,
+    role: Option<String>,
+    session_id: Option<String>,
+    project: Option<String>,
+    limit: Option<usize>,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SessionArguments {
+    session_id: String,
+    /// Only the last `limit` messages
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct MessageArguments {
+    uuid: String,
+}
+
+#[derive(Deserialize)]
+struct ToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+pub struct McpServer {
+    service: Service,
+}
+
+impl McpServer {
+    pub fn new(service: Service) -> Self {
+        Self { service }
+    }
+
+    /// Serve requests from `input` until it closes
+    pub fn run(&self, input: impl BufRead, mut output: impl Write) -> Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let reply = match serde_json::from_str::<Value>(&line) {
+                Ok(message) => self.handle(message),
+                Err(e) => Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, e.to_string()),
+                )),
+            };
+            if let Some(reply) = reply {
+                serde_json::to_writer(&mut output, &reply)?;
+                writeln!(output)?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one decoded message, returning the response for requests and
+    /// `None` for notifications
+    pub fn handle(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return Some(error_response(
+                id.unwrap_or(Value::Null),
+                RpcError::new(INVALID_REQUEST, "missing method"),
+            ));
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let id = id?;
+        Some(match self.dispatch(method, params) {
+            Ok(result) => response(id, result),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn dispatch(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => {
+                let requested = params.get("protocolVersion").and_then(Value::as_str);
+                let version = requested
+                    .filter(|version| SUPPORTED_PROTOCOL_VERSIONS.contains(version))
+                    .unwrap_or(PROTOCOL_VERSION);
+                Ok(json!({
+                    "protocolVersion": version,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "ccms", "version": env!("CARGO_PKG_VERSION") },
+                    "instructions": "Search the user's past Claude sessions. Use search_messages to find messages, then get_session or get_message_by_id for context.",
+                }))
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => {
+                let call: ToolCall = parse_arguments(params)?;
+                Ok(match self.call_tool(&call) {
+                    Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+                    // Tool failures are reported to the model rather than as protocol errors
+                    Err(ToolError::Failed(message)) => json!({
+                        "content": [{ "type": "text", "text": message }],
+                        "isError": true,
+                    }),
+                    Err(ToolError::Protocol(error)) => return Err(error),
+                })
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method: {method}"),
+            )),
+        }
+    }
+
+    fn call_tool(&self, call: &ToolCall) -> Result<String, ToolError> {
+        let arguments = call.arguments.clone();
+        match call.name.as_str() {
+            "search_messages" => {
+                let args: SearchArguments = parse_arguments(arguments)?;
+                // The engines would silently skip a bound they cannot parse
+                let time = |name: &str, value: Option<String>| {
+                    value
+                        .map(|value| {
+                            parse_time(&value)
+                                .map(|time| time.to_rfc3339())
+                                .map_err(|e| ToolError::Failed(format!("invalid {name}: {e:#}")))
+                        })
+                        .transpose()
+                };
+                let params = SearchParams {
+                    query: args.query,
+                    role: args.role,
+                    session_id: args.session_id,
+                    project: args.project,
+                    max_results: Some(args.limit.unwrap_or(DEFAULT_SEARCH_LIMIT)),
+                    before: time("before", args.before)?,
+                    after: time("after", args.after)?,
+                };
+                let page = self.service.search(&params)?;
+                Ok(to_text(&json!({
+                    "total_count": page.total_count,
+                    "messages": page.results.iter().map(tool_message).collect::<Vec<_>>(),
+                })))
+            }
+            "get_session" => {
+                let args: SessionArguments = parse_arguments(arguments)?;
+                let mut messages = self.service.get_session(&args.session_id)?;
+                if messages.is_empty() {
+                    return Err(ToolError::Failed(format!(
+                        "no session with id {}",
+                        args.session_id
+                    )));
+                }
+                let total = messages.len();
+                if let Some(limit) = args.limit {
+                    messages.drain(..total.saturating_sub(limit));
+                }
+                Ok(to_text(&json!({
+                    "session_id": args.session_id,
+                    "total_messages": total,
+                    "messages": messages.iter().map(tool_message).collect::<Vec<_>>(),
+                })))
+            }
+            "get_message_by_id" => {
+                let args: MessageArguments = parse_arguments(arguments)?;
+                match self.service.get_message(&args.uuid)? {
+                    Some(message) => Ok(to_text(&tool_message(&message))),
+                    None => Err(ToolError::Failed(format!(
+                        "no message with uuid {}",
+                        args.uuid
+                    ))),
+                }
+            }
+            name => Err(ToolError::Protocol(RpcError::new(
+                INVALID_PARAMS,
+                format!("unknown tool: {name}"),
+            ))),
+        }
+    }
+}
+
+enum ToolError {
+    /// Reported to the model as a tool result with `isError`
+    Failed(String),
+    Protocol(RpcError),
+}
+
+impl From<anyhow::Error> for ToolError {
+    fn from(error: anyhow::Error) -> Self {
+        ToolError::Failed(format!("{error:#}"))
+    }
+}
+
+impl From<RpcError> for ToolError {
+    fn from(error: RpcError) -> Self {
+        ToolError::Protocol(error)
+    }
+}
+
+/// The fields of a result that are useful to a model
+fn tool_message(result: &SearchResult) -> Value {
+    json!({
+        "uuid": result.uuid,
+        "session_id": result.session_id,
+        "role": result.role,
+        "timestamp": result.timestamp,
+        "cwd": result.cwd,
+        "file": result.file,
+        "line_number": result.line_number,
+        "text": result.text,
+    })
+}
+
+fn to_text(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+fn parse_arguments<T: DeserializeOwned>(arguments: Value) -> Result<T, RpcError> {
+    let arguments = if arguments.is_null() {
+        json!({})
+    } else {
+        arguments
+    };
+    serde_json::from_value(arguments).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "search_messages",
+            "description": "Search messages of past Claude sessions. Supports literal terms, \"quoted phrases\", /regex/flags, and AND/OR/NOT with parentheses, and \"a\" NEAR/5 \"b\" for terms with at most 5 words between them. Returns the newest matches first.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Search query, e.g. \"error AND rust\"" },
+                    "role": { "type": "string", "enum": ["user", "assistant", "system", "summary"] },
+                    "session_id": { "type": "string", "description": "Only search this session" },
+                    "project": { "type": "string", "description": "Only search sessions started in this directory" },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Maximum number of messages (default 20)" },
+                    "before": { "type": "string", "description": "Only messages before this time: an RFC3339 timestamp, a date like 2024-06-01, or a relative time like \"2 days ago\"" },
+                    "after": { "type": "string", "description": "Only messages after this time, written like before" }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "get_session",
+            "description": "Get the messages of a session in chronological order.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_id": { "type": "string" },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Only the last N messages" }
+                },
+                "required": ["session_id"]
+            }
+        },
+        {
+            "name": "get_message_by_id",
+            "description": "Get a single message by its uuid.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "uuid": { "type": "string" }
+                },
+                "required": ["uuid"]
+            }
+        }
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::SearchOptions;
+    use std::fs::File;
+    use std::io::Cursor;
+    use tempfile::tempdir;
+
+    fn server(dir: &std::path::Path) -> Result<McpServer> {
+        let session = dir.join("session.jsonl");
+        let mut file = File::create(&session)?;
+        for (uuid, content) in [("u1", "rust error"), ("u2", "fixed it")] {
+            writeln!(
+                file,
+                r#"{{"type":"user","message":{{"role":"user","content":"{content}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:0{}Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/test","version":"1"}}"#,
+                &uuid[1..]
+            )?;
+        }
+        Ok(McpServer::new(Service::new(
+            session.display().to_string(),
+            SearchOptions::default(),
+        )))
+    }
+
+    fn call(server: &McpServer, name: &str, arguments: Value) -> Value {
+        server
+            .handle(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": { "name": name, "arguments": arguments },
+            }))
+            .unwrap()
+    }
+
+    fn tool_json(reply: &Value) -> Value {
+        serde_json::from_str(reply["result"]["content"][0]["text"].as_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_initialize_and_list_tools() -> Result<()> {
+        let dir = tempdir()?;
+        let server = server(dir.path())?;
+
+        let reply = server
+            .handle(json!({"jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {"protocolVersion": "2024-11-05"}}))
+            .unwrap();
+        assert_eq!(reply["result"]["protocolVersion"], "2024-11-05");
+        assert!(reply["result"]["capabilities"]["tools"].is_object());
+        assert!(
+            server
+                .handle(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+                .is_none()
+        );
+
+        let reply = server
+            .handle(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}))
+            .unwrap();
+        let names: Vec<&str> = reply["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["search_messages", "get_session", "get_message_by_id"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tools() -> Result<()> {
+        let dir = tempdir()?;
+        let server = server(dir.path())?;
+
+        let found = tool_json(&call(&server, "search_messages", json!({"query": "error"})));
+        assert_eq!(found["total_count"], 1);
+        assert_eq!(found["messages"][0]["uuid"], "u1");
+
+        let session = tool_json(&call(
+            &server,
+            "get_session",
+            json!({"session_id": "s1", "limit": 1}),
+        ));
+        assert_eq!(session["total_messages"], 2);
+        assert_eq!(session["messages"][0]["uuid"], "u2");
+
+        let message = tool_json(&call(&server, "get_message_by_id", json!({"uuid": "u2"})));
+        assert_eq!(message["text"], "fixed it");
+
+        let missing = call(&server, "get_message_by_id", json!({"uuid": "nope"}));
+        assert_eq!(missing["result"]["isError"], true);
+
+        let invalid = call(&server, "search_messages", json!({}));
+        assert_eq!(invalid["error"]["code"], INVALID_PARAMS);
+
+        let after = tool_json(&call(
+            &server,
+            "search_messages",
+            json!({"query": "error OR fixed", "after": "2024-01-01T00:00:01.500Z"}),
+        ));
+        assert_eq!(after["total_count"], 1);
+        assert_eq!(after["messages"][0]["uuid"], "u2");
+
+        let bad_time = call(
+            &server,
+            "search_messages",
+            json!({"query": "error", "before": "someday"}),
+        );
+        assert_eq!(bad_time["result"]["isError"], true);
+        assert!(
+            bad_time["result"]["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("invalid before")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_line_delimited() -> Result<()> {
+        let dir = tempdir()?;
+        let server = server(dir.path())?;
+        let input = "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"ping\"}\n\nnot json\n";
+
+        let mut output = Vec::new();
+        server.run(Cursor::new(input), &mut output)?;
+
+        let replies: Vec<Value> = String::from_utf8(output)?
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[0]["result"], json!({}));
+        assert_eq!(replies[1]["error"]["code"], PARSE_ERROR);
+        Ok(())
+    }
+}
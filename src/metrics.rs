@@ -0,0 +1,241 @@
+//! Process-wide counters and the Prometheus `/metrics` endpoint of `ccms serve`.
+//!
+//! Counters are plain relaxed atomics bumped by the search engines, the index
+//! builder and the service layer, so they cost nothing noticeable when no
+//! endpoint is running. Gauges about the corpus and the index are measured
+//! when the endpoint is scraped.
+
+use crate::search::discover_claude_files;
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    pub const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    pub fn inc(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Requests answered by the service layer (`search`, `getSession`, MCP tools, ...)
+pub static SEARCHES_SERVED: Counter = Counter::new();
+/// Session files parsed by searches and index builds
+pub static FILES_INDEXED: Counter = Counter::new();
+/// Session lines that could not be parsed
+pub static PARSE_ERRORS: Counter = Counter::new();
+
+/// Sizes measured at scrape time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Gauges {
+    pub corpus_files: u64,
+    pub corpus_bytes: u64,
+    /// Size of the on-disk index file; 0 when none has been built
+    pub index_bytes: u64,
+}
+
+impl Gauges {
+    pub fn measure(pattern: &str, index_path: Option<&Path>) -> Self {
+        let files = discover_claude_files(Some(pattern)).unwrap_or_default();
+        let corpus_bytes = files
+            .iter()
+            .filter_map(|path| path.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        let index_bytes = index_path
+            .and_then(|path| path.metadata().ok())
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        Self {
+            corpus_files: files.len() as u64,
+            corpus_bytes,
+            index_bytes,
+        }
+    }
+}
+
+/// Render all metrics in the Prometheus text exposition format
+pub fn render(gauges: &Gauges) -> String {
+    let metrics: [(&str, &str, &str, u64); 6] = [
+        (
+            "ccms_searches_served_total",
+            "counter",
+            "Search requests answered by this process.",
+            SEARCHES_SERVED.get(),
+        ),
+        (
+            "ccms_files_indexed_total",
+            "counter",
+            "Session files parsed by searches and index builds.",
+            FILES_INDEXED.get(),
+        ),
+        (
+            "ccms_parse_errors_total",
+            "counter",
+            "Session file lines that could not be parsed.",
+            PARSE_ERRORS.get(),
+        ),
+        (
+            "ccms_corpus_files",
+            "gauge",
+            "Session files matched by the search pattern.",
+            gauges.corpus_files,
+        ),
+        (
+            "ccms_corpus_bytes",
+            "gauge",
+            "Total size of the session files matched by the search pattern.",
+            gauges.corpus_bytes,
+        ),
+        (
+            "ccms_index_bytes",
+            "gauge",
+            "Size of the on-disk search index.",
+            gauges.index_bytes,
+        ),
+    ];
+
+    let mut output = String::new();
+    for (name, kind, help, value) in metrics {
+        let _ = writeln!(output, "# HELP {name} {help}");
+        let _ = writeln!(output, "# TYPE {name} {kind}");
+        let _ = writeln!(output, "{name} {value}");
+    }
+    output
+}
+
+/// Serve `GET /metrics` on `addr` until the process exits
+pub fn serve(addr: &str, pattern: String, index_path: Option<PathBuf>) -> Result<()> {
+    let listener =
+        TcpListener::bind(addr).with_context(|| format!("failed to listen on {addr}"))?;
+    serve_on(listener, pattern, index_path);
+    Ok(())
+}
+
+/// Serve `GET /metrics` on an already bound listener
+pub fn serve_on(listener: TcpListener, pattern: String, index_path: Option<PathBuf>) {
+    for stream in listener.incoming().flatten() {
+        let pattern = pattern.clone();
+        let index_path = index_path.clone();
+        // Scrapes measure the corpus, so keep a slow one from blocking the next
+        thread::spawn(move || {
+            let _ = handle_connection(stream, &pattern, index_path.as_deref());
+        });
+    }
+}
+
+fn handle_connection(stream: TcpStream, pattern: &str, index_path: Option<&Path>) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers; requests to this endpoint carry no body
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    let path = path.split('?').next().unwrap_or("");
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => (
+            "200 OK",
+            "text/plain; version=0.0.4",
+            render(&Gauges::measure(pattern, index_path)),
+        ),
+        ("GET", _) => ("404 Not Found", "text/plain", "not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n".to_string(),
+        ),
+    };
+
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Read;
+    use tempfile::tempdir;
+
+    fn get(addr: &str, path: &str) -> Result<String> {
+        let mut stream = TcpStream::connect(addr)?;
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        Ok(response)
+    }
+
+    #[test]
+    fn test_render_and_measure() -> Result<()> {
+        let dir = tempdir()?;
+        File::create(dir.path().join("a.jsonl"))?.write_all(b"12345\n")?;
+        File::create(dir.path().join("b.jsonl"))?.write_all(b"123\n")?;
+        let index = dir.path().join("index.json");
+        File::create(&index)?.write_all(b"{}")?;
+
+        let pattern = format!("{}/**/*.jsonl", dir.path().display());
+        let gauges = Gauges::measure(&pattern, Some(&index));
+        assert_eq!(
+            gauges,
+            Gauges {
+                corpus_files: 2,
+                corpus_bytes: 10,
+                index_bytes: 2,
+            }
+        );
+
+        let text = render(&gauges);
+        assert!(text.contains("# TYPE ccms_searches_served_total counter\n"));
+        assert!(text.contains("\nccms_corpus_bytes 10\n"));
+        assert!(text.contains("\nccms_index_bytes 2\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_endpoint() -> Result<()> {
+        let dir = tempdir()?;
+        let pattern = format!("{}/**/*.jsonl", dir.path().display());
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?.to_string();
+        thread::spawn(move || serve_on(listener, pattern, None));
+
+        let response = get(&addr, "/metrics")?;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("ccms_corpus_files 0\n"));
+
+        assert!(get(&addr, "/")?.starts_with("HTTP/1.1 404"));
+        Ok(())
+    }
+}
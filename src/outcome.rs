@@ -0,0 +1,260 @@
+//! Heuristic session outcomes, judged from how a session ends.
+//!
+//! Only the main thread counts: summaries, system messages, meta messages and
+//! sidechain (subagent) messages are skipped. Of what is left:
+//! - a final assistant reply means the session **completed**, unless it is an
+//!   API error or the tool call it answers failed, which makes it an **error**
+//! - a failed tool call with no reply after it is an **error**
+//! - ending on a prompt, a tool call or a successful tool result that was never
+//!   answered means the session was **abandoned**
+
+use crate::schemas::session_message::{Content, SessionMessage, UserContent};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionOutcome {
+    Completed,
+    Abandoned,
+    Error,
+}
+
+impl SessionOutcome {
+    pub const ALL: [SessionOutcome; 3] = [
+        SessionOutcome::Completed,
+        SessionOutcome::Abandoned,
+        SessionOutcome::Error,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SessionOutcome::Completed => "completed",
+            SessionOutcome::Abandoned => "abandoned",
+            SessionOutcome::Error => "error",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|outcome| outcome.name() == name)
+    }
+}
+
+/// The last main-thread event of a session seen so far
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Ending {
+    Prompt,
+    ToolUse,
+    ToolResult { failed: bool },
+    Reply { failed: bool },
+}
+
+/// Works out a session's outcome from its messages, fed one at a time in
+/// file order, without keeping them around
+#[derive(Debug, Default, Clone)]
+pub struct OutcomeTracker {
+    ending: Option<Ending>,
+    /// Whether the latest tool result failed
+    tool_failed: bool,
+}
+
+impl OutcomeTracker {
+    pub fn observe(&mut self, message: &SessionMessage) {
+        let ending = match message {
+            SessionMessage::User {
+                base,
+                message,
+                is_meta,
+                ..
+            } if !base.is_sidechain && is_meta != &Some(true) => match &message.content {
+                UserContent::Array(items) => {
+                    let results: Vec<bool> = items
+                        .iter()
+                        .filter_map(|item| match item {
+                            Content::ToolResult { is_error, .. } => Some(*is_error == Some(true)),
+                            _ => None,
+                        })
+                        .collect();
+                    if results.is_empty() {
+                        Ending::Prompt
+                    } else {
+                        self.tool_failed = results.contains(&true);
+                        Ending::ToolResult {
+                            failed: self.tool_failed,
+                        }
+                    }
+                }
+                UserContent::String(_) => Ending::Prompt,
+            },
+            SessionMessage::Assistant {
+                base,
+                message,
+                is_api_error_message,
+                ..
+            } if !base.is_sidechain => {
+                if message
+                    .content
+                    .iter()
+                    .any(|item| matches!(item, Content::ToolUse { .. }))
+                {
+                    Ending::ToolUse
+                } else {
+                    Ending::Reply {
+                        failed: *is_api_error_message == Some(true)
+                            || matches!(self.ending, Some(Ending::ToolResult { .. }))
+                                && self.tool_failed,
+                    }
+                }
+            }
+            _ => return,
+        };
+        if ending == Ending::Prompt {
+            self.tool_failed = false;
+        }
+        self.ending = Some(ending);
+    }
+
+    /// The outcome of the messages observed so far; `None` before any
+    /// main-thread message
+    pub fn outcome(&self) -> Option<SessionOutcome> {
+        Some(match self.ending? {
+            Ending::Reply { failed: false } => SessionOutcome::Completed,
+            Ending::Reply { failed: true } | Ending::ToolResult { failed: true } => {
+                SessionOutcome::Error
+            }
+            Ending::Prompt | Ending::ToolUse | Ending::ToolResult { failed: false } => {
+                SessionOutcome::Abandoned
+            }
+        })
+    }
+}
+
+/// The outcome of a session with these messages, in file order
+pub fn classify<'a>(
+    messages: impl IntoIterator<Item = &'a SessionMessage>,
+) -> Option<SessionOutcome> {
+    let mut tracker = OutcomeTracker::default();
+    for message in messages {
+        tracker.observe(message);
+    }
+    tracker.outcome()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#""sessionId":"s1","parentUuid":null,"userType":"external","cwd":"/","version":"1","uuid":"u","timestamp":"2024-01-01T00:00:00Z""#;
+
+    fn prompt(text: &str) -> String {
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":"{text}"}},"isSidechain":false,{BASE}}}"#
+        )
+    }
+
+    fn tool_result(is_error: bool) -> String {
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"t1","content":"out","is_error":{is_error}}}]}},"isSidechain":false,{BASE}}}"#
+        )
+    }
+
+    fn assistant(content: &str, sidechain: bool) -> String {
+        format!(
+            r#"{{"type":"assistant","message":{{"id":"m","type":"message","role":"assistant","model":"claude","content":[{content}],"stop_reason":"end_turn","stop_sequence":null,"usage":{{"input_tokens":1,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":1}}}},"isSidechain":{sidechain},{BASE}}}"#
+        )
+    }
+
+    fn reply(text: &str) -> String {
+        assistant(&format!(r#"{{"type":"text","text":"{text}"}}"#), false)
+    }
+
+    fn tool_use() -> String {
+        assistant(
+            r#"{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"make"}}"#,
+            false,
+        )
+    }
+
+    fn outcome(lines: &[String]) -> Option<SessionOutcome> {
+        let messages: Vec<SessionMessage> = lines
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        classify(&messages)
+    }
+
+    #[test]
+    fn test_classify_sessions() {
+        use SessionOutcome::*;
+
+        assert_eq!(outcome(&[]), None);
+        assert_eq!(outcome(&[prompt("hi"), reply("Done")]), Some(Completed));
+        assert_eq!(
+            outcome(&[
+                prompt("build"),
+                tool_use(),
+                tool_result(false),
+                reply("Built")
+            ]),
+            Some(Completed)
+        );
+        assert_eq!(
+            outcome(&[
+                prompt("build"),
+                tool_use(),
+                tool_result(true),
+                reply("It failed")
+            ]),
+            Some(Error)
+        );
+        assert_eq!(
+            outcome(&[prompt("build"), tool_use(), tool_result(true)]),
+            Some(Error)
+        );
+        // A new prompt after a failure starts over
+        assert_eq!(
+            outcome(&[
+                tool_use(),
+                tool_result(true),
+                prompt("ok, skip it"),
+                reply("Skipped")
+            ]),
+            Some(Completed)
+        );
+        assert_eq!(
+            outcome(&[prompt("hi"), reply("Hello"), prompt("and?")]),
+            Some(Abandoned)
+        );
+        assert_eq!(outcome(&[prompt("build"), tool_use()]), Some(Abandoned));
+        assert_eq!(
+            outcome(&[prompt("build"), tool_use(), tool_result(false)]),
+            Some(Abandoned)
+        );
+        // Subagent messages do not decide the outcome
+        assert_eq!(
+            outcome(&[
+                prompt("hi"),
+                reply("Done"),
+                assistant(
+                    r#"{"type":"tool_use","id":"t2","name":"Read","input":{}}"#,
+                    true
+                )
+            ]),
+            Some(Completed)
+        );
+    }
+
+    #[test]
+    fn test_api_error_reply() {
+        let mut error = reply("API Error: overloaded");
+        error.insert_str(error.len() - 1, r#","isApiErrorMessage":true"#);
+        assert_eq!(outcome(&[prompt("hi"), error]), Some(SessionOutcome::Error));
+    }
+
+    #[test]
+    fn test_outcome_names() {
+        for outcome in SessionOutcome::ALL {
+            assert_eq!(SessionOutcome::from_name(outcome.name()), Some(outcome));
+        }
+        assert_eq!(SessionOutcome::from_name("done"), None);
+    }
+}
@@ -0,0 +1,113 @@
+//! Field selection for JSON output (`--fields`).
+//!
+//! `--fields uuid,timestamp,text` keeps only those keys of each result, so
+//! scripts get a smaller payload with a fixed set of keys. Selected fields a
+//! result does not have, such as `line_number`, are written as `null`.
+
+use crate::query::SearchResult;
+use anyhow::{Result, bail};
+use serde_json::{Map, Value};
+
+/// JSON keys of a search result
+const FIELDS: &[&str] = &[
+    "file",
+    "uuid",
+    "timestamp",
+    "session_id",
+    "role",
+    "text",
+    "message_type",
+    "query",
+    "cwd",
+    "raw_json",
+    "line_number",
+    "matched_in",
+    "has_images",
+];
+
+/// A parsed `--fields` list
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSelection {
+    fields: Vec<&'static str>,
+}
+
+impl FieldSelection {
+    /// Parse a comma-separated list of field names
+    pub fn parse(arg: &str) -> Result<Self> {
+        let mut fields = Vec::new();
+        for name in arg
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            let Some(field) = FIELDS.iter().find(|field| **field == name) else {
+                bail!("unknown field '{name}' (available: {})", FIELDS.join(", "));
+            };
+            if !fields.contains(field) {
+                fields.push(*field);
+            }
+        }
+        if fields.is_empty() {
+            bail!("no fields given (available: {})", FIELDS.join(", "));
+        }
+        Ok(Self { fields })
+    }
+
+    /// `result` as a JSON object with only the selected fields
+    pub fn select(&self, result: &SearchResult) -> Result<Value> {
+        let Value::Object(mut all) = serde_json::to_value(result)? else {
+            unreachable!("a search result serializes to an object");
+        };
+        let selected: Map<String, Value> = self
+            .fields
+            .iter()
+            .map(|field| (field.to_string(), all.remove(*field).unwrap_or(Value::Null)))
+            .collect();
+        Ok(Value::Object(selected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::QueryCondition;
+
+    fn result() -> SearchResult {
+        SearchResult {
+            file: "/tmp/session.jsonl".into(),
+            uuid: "u1".to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s1".into(),
+            role: "user".into(),
+            text: "hello".to_string(),
+            message_type: "user".into(),
+            query: QueryCondition::Literal {
+                pattern: "hello".to_string(),
+                case_sensitive: false,
+            },
+            cwd: "/".into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        }
+    }
+
+    #[test]
+    fn test_select_fields() -> Result<()> {
+        let selection = FieldSelection::parse("uuid, text,uuid,line_number")?;
+        assert_eq!(
+            selection.select(&result())?,
+            serde_json::json!({"uuid": "u1", "text": "hello", "line_number": null})
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_fields() {
+        let error = FieldSelection::parse("uuid,body").unwrap_err().to_string();
+        assert!(error.contains("unknown field 'body'"));
+        assert!(error.contains("available: file, uuid, timestamp"));
+        assert!(FieldSelection::parse(" , ").is_err());
+    }
+}
@@ -0,0 +1,7 @@
+//! User-defined output formats for search results.
+
+mod fields;
+mod template;
+
+pub use fields::FieldSelection;
+pub use template::ResultTemplate;
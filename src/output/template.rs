@@ -0,0 +1,259 @@
+//! Per-result output templates for `--template`.
+//!
+//! A template is text with `{field}` placeholders, rendered once per result:
+//! - `{field}` inserts the field as is
+//! - `{field:N}` inserts it on a single line, cut to at most N characters
+//! - `{{` and `}}` are literal braces
+//!
+//! Inline templates also understand `\t`, `\n` and `\\`, so reports can be
+//! written without shell quoting tricks.
+
+use crate::query::SearchResult;
+use crate::utils::git_repo::repo_name;
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+
+/// Fields a template can refer to
+const FIELDS: &[&str] = &[
+    "file",
+    "line",
+    "uuid",
+    "timestamp",
+    "session_id",
+    "role",
+    "cwd",
+    "repo",
+    "text",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field {
+        name: &'static str,
+        width: Option<usize>,
+    },
+}
+
+/// A parsed `--template`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultTemplate {
+    segments: Vec<Segment>,
+}
+
+impl ResultTemplate {
+    /// Load the template file at `arg` if there is one, otherwise parse `arg`
+    /// itself as an inline template
+    pub fn from_arg(arg: &str) -> Result<Self> {
+        let path = Path::new(arg);
+        if path.is_file() {
+            let source = fs::read_to_string(path)
+                .with_context(|| format!("failed to read template: {}", path.display()))?;
+            // Each rendered result is printed on its own line already
+            let source = source.strip_suffix('\n').unwrap_or(&source);
+            return Self::parse(source)
+                .with_context(|| format!("invalid template: {}", path.display()));
+        }
+        Self::parse(&unescape(arg)).context("invalid template")
+    }
+
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        placeholder.push(c);
+                    }
+                    if !closed {
+                        bail!("unclosed '{{{placeholder}'");
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(parse_field(&placeholder)?);
+                }
+                '}' => bail!("unmatched '}}'; write '}}}}' for a literal brace"),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    pub fn render(&self, result: &SearchResult) -> String {
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => output.push_str(text),
+                Segment::Field { name, width } => {
+                    let value = field_value(result, name);
+                    match width {
+                        Some(width) => output.push_str(&single_line(&value, *width)),
+                        None => output.push_str(&value),
+                    }
+                }
+            }
+        }
+        output
+    }
+}
+
+fn parse_field(placeholder: &str) -> Result<Segment> {
+    let (name, width) = match placeholder.split_once(':') {
+        Some((name, width)) => {
+            let width = width
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid width in '{{{placeholder}}}'"))?;
+            (name.trim(), Some(width))
+        }
+        None => (placeholder.trim(), None),
+    };
+    let Some(name) = FIELDS.iter().find(|field| **field == name) else {
+        bail!(
+            "unknown field '{{{name}}}' (available: {})",
+            FIELDS.join(", ")
+        );
+    };
+    Ok(Segment::Field { name, width })
+}
+
+fn field_value(result: &SearchResult, name: &str) -> String {
+    match name {
+        "file" => result.file.to_string(),
+        "line" => result
+            .line_number
+            .map(|line| line.to_string())
+            .unwrap_or_default(),
+        "uuid" => result.uuid.clone(),
+        "timestamp" => result.timestamp.clone(),
+        "session_id" => result.session_id.to_string(),
+        "role" => result.role.to_string(),
+        "cwd" => result.cwd.to_string(),
+        "repo" => repo_name(&result.cwd).unwrap_or_default(),
+        "text" => result.text.clone(),
+        _ => String::new(),
+    }
+}
+
+/// `text` with whitespace runs collapsed to single spaces, cut to `width`
+/// characters with a trailing `…` when it is longer
+fn single_line(text: &str, width: usize) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() <= width {
+        return line;
+    }
+    let mut cut: String = line.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Expand `\t`, `\n` and `\\`; other backslashes are kept as they are
+fn unescape(source: &str) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('t') => output.push('\t'),
+            Some('n') => output.push('\n'),
+            Some('\\') => output.push('\\'),
+            _ => {
+                output.push('\\');
+                continue;
+            }
+        }
+        chars.next();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::QueryCondition;
+
+    fn result() -> SearchResult {
+        SearchResult {
+            file: "/p/s.jsonl".into(),
+            uuid: "u1".to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s1".into(),
+            role: "user".into(),
+            text: "deploy the\n  service now".to_string(),
+            message_type: "user".into(),
+            query: QueryCondition::Literal {
+                pattern: "deploy".to_string(),
+                case_sensitive: false,
+            },
+            cwd: "/p".into(),
+            raw_json: None,
+            line_number: Some(3),
+            matched_in: Vec::new(),
+            has_images: false,
+        }
+    }
+
+    #[test]
+    fn test_render_fields_and_widths() -> Result<()> {
+        let template = ResultTemplate::from_arg(r"{timestamp}\t{role}\t{text:12}")?;
+        assert_eq!(
+            template.render(&result()),
+            "2024-01-01T00:00:00Z\tuser\tdeploy the …"
+        );
+
+        let template = ResultTemplate::parse("{file}:{line} {{{uuid}}} {text}")?;
+        assert_eq!(
+            template.render(&result()),
+            "/p/s.jsonl:3 {u1} deploy the\n  service now"
+        );
+        // Short values are left alone
+        let template = ResultTemplate::parse("{role:10}|")?;
+        assert_eq!(template.render(&result()), "user|");
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("report.tmpl");
+        fs::write(&path, "- {session_id}: {text:6}\\t\n")?;
+
+        let template = ResultTemplate::from_arg(path.to_str().unwrap())?;
+        // Escapes are only expanded in inline templates
+        assert_eq!(template.render(&result()), "- s1: deplo…\\t");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for source in ["{nope}", "{text:wide}", "{text", "a } b"] {
+            assert!(ResultTemplate::parse(source).is_err(), "{source}");
+        }
+    }
+}
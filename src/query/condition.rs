@@ -1,5 +1,165 @@
-use super::fast_lowercase::FastLowercase;
+use super::fast_lowercase::{FastLowercase, with_lowercase};
+use super::interner::SharedStr;
+use crate::index::SearchIndex;
+use crate::interactive_ratatui::ui::theme;
+use crate::outcome::SessionOutcome;
+use crate::search::io::IoMode;
+use crate::tool_calls::ToolFilter;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Starts a section appended to a message's searchable text; the character
+/// after it says which `TextSection` follows
+pub const SECTION_SEPARATOR: char = '\u{1e}';
+
+/// Part of a message that query scopes such as `thinking:` match against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextSection {
+    /// Assistant thinking blocks
+    Thinking,
+    /// Structured `toolUseResult` payloads
+    ToolResult,
+    /// Metadata of attached images: media type, source and file name
+    Images,
+}
+
+impl TextSection {
+    pub const ALL: [TextSection; 3] = [
+        TextSection::Thinking,
+        TextSection::ToolResult,
+        TextSection::Images,
+    ];
+
+    fn marker(self) -> char {
+        match self {
+            TextSection::Thinking => 't',
+            TextSection::ToolResult => 'r',
+            TextSection::Images => 'i',
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TextSection::Thinking => "thinking",
+            TextSection::ToolResult => "tool_result",
+            TextSection::Images => "images",
+        }
+    }
+
+    /// Query prefix scoping a term to this section
+    pub fn prefix(self) -> &'static str {
+        match self {
+            TextSection::Thinking => "thinking:",
+            TextSection::ToolResult => "tool_result:",
+            TextSection::Images => "images:",
+        }
+    }
+
+    /// Append `section_text` to a searchable text as this section
+    pub fn append_to(self, text: &mut String, section_text: &str) {
+        text.push(SECTION_SEPARATOR);
+        text.push(self.marker());
+        text.push_str(section_text);
+    }
+
+    /// This section of a searchable text, if it has one
+    pub fn find_in(self, text: &str) -> Option<&str> {
+        text.split(SECTION_SEPARATOR)
+            .skip(1)
+            .find_map(|part| part.strip_prefix(self.marker()))
+    }
+
+    /// `text` without this section
+    fn strip_from(self, text: &str) -> String {
+        let mut parts = text.split(SECTION_SEPARATOR);
+        let mut stripped = parts.next().unwrap_or_default().to_string();
+        for part in parts.filter(|part| !part.starts_with(self.marker())) {
+            stripped.push(SECTION_SEPARATOR);
+            stripped.push_str(part);
+        }
+        stripped
+    }
+}
+
+/// Where a message sits in its session, for `--position` and `is:first` /
+/// `is:last`. Summaries are not counted as messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionPosition {
+    /// The session's opening message
+    First,
+    /// The session's final message
+    Last,
+}
+
+impl SessionPosition {
+    /// Query token selecting this position
+    pub fn token(self) -> &'static str {
+        match self {
+            SessionPosition::First => "is:first",
+            SessionPosition::Last => "is:last",
+        }
+    }
+}
+
+/// Kinds of message searches leave out unless asked for: by the query with
+/// `is:sidechain` / `is:meta`, or by turning off `SearchOptions::exclude_sidechain`
+/// / `exclude_meta`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageKind {
+    /// Messages of subagent conversations (`isSidechain`)
+    Sidechain,
+    /// Messages injected around the conversation rather than typed (`isMeta`),
+    /// such as command caveats
+    Meta,
+}
+
+impl MessageKind {
+    pub const ALL: [MessageKind; 2] = [MessageKind::Sidechain, MessageKind::Meta];
+
+    /// Query token selecting this kind
+    pub fn token(self) -> &'static str {
+        match self {
+            MessageKind::Sidechain => "is:sidechain",
+            MessageKind::Meta => "is:meta",
+        }
+    }
+}
+
+/// Lines of the first and last messages of a session file, recorded while
+/// scanning it so matches can be narrowed to `SessionPosition`s afterwards
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SessionBounds {
+    first: Option<usize>,
+    last: Option<usize>,
+}
+
+impl SessionBounds {
+    /// Record the next non-summary message, found at `line`
+    pub fn record(&mut self, line: usize) {
+        self.first.get_or_insert(line);
+        self.last = Some(line);
+    }
+
+    /// Whether the message at `line` is at every one of `positions`
+    pub fn holds(&self, positions: &[SessionPosition], line: Option<usize>) -> bool {
+        positions.iter().all(|position| {
+            let bound = match position {
+                SessionPosition::First => self.first,
+                SessionPosition::Last => self.last,
+            };
+            line.is_some() && bound == line
+        })
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -24,10 +184,61 @@ pub enum QueryCondition {
     Or {
         conditions: Vec<QueryCondition>,
     },
+    /// `"error" NEAR/5 "timeout"`: both terms occur with at most `distance`
+    /// words between them
+    #[serde(rename = "NEAR")]
+    Near {
+        left: Box<QueryCondition>,
+        right: Box<QueryCondition>,
+        distance: usize,
+    },
+    /// `thinking:<condition>`, `tool_result:<condition>` or
+    /// `images:<condition>`, matched only against that section of the message
+    Scoped {
+        section: TextSection,
+        condition: Box<QueryCondition>,
+    },
+    /// `is:first` or `is:last`. Matches any text; the engines narrow results
+    /// to that message of each session instead (see `SearchOptions::positions_for`)
+    Position {
+        position: SessionPosition,
+    },
+    /// `is:sidechain` or `is:meta`. Matches any text; the engines keep only
+    /// messages of that kind instead (see `SearchOptions::admits`)
+    Kind {
+        kind: MessageKind,
+    },
+    /// `outcome:completed`, `outcome:abandoned` or `outcome:error`. Matches
+    /// any text; the engines keep a session's matches only when the session
+    /// ended that way (see `crate::outcome`)
+    Outcome {
+        outcome: SessionOutcome,
+    },
+    /// `after:` / `since:` bound: matches messages timestamped at or after
+    /// `timestamp` (RFC 3339)
+    After {
+        timestamp: String,
+    },
+    /// `before:` bound: matches messages timestamped at or before `timestamp`
+    /// (RFC 3339), or strictly before it when `exclusive`. A date without a
+    /// time of day is an exclusive bound at the start of the next day, so
+    /// the whole day is included.
+    Before {
+        timestamp: String,
+        #[serde(default)]
+        exclusive: bool,
+    },
 }
 
 impl QueryCondition {
+    /// Evaluate against a message's searchable `text` when its timestamp is
+    /// not known; `after:` and `before:` terms never match
     pub fn evaluate(&self, text: &str) -> Result<bool, regex::Error> {
+        self.evaluate_at(text, None)
+    }
+
+    /// Evaluate against a message's searchable `text` and RFC 3339 `timestamp`
+    pub fn evaluate_at(&self, text: &str, timestamp: Option<&str>) -> Result<bool, regex::Error> {
         match self {
             QueryCondition::Literal {
                 pattern,
@@ -43,10 +254,10 @@ impl QueryCondition {
                 let regex = super::regex_cache::get_or_compile_regex(pattern, flags)?;
                 Ok(regex.is_match(text))
             }
-            QueryCondition::Not { condition } => Ok(!condition.evaluate(text)?),
+            QueryCondition::Not { condition } => Ok(!condition.evaluate_at(text, timestamp)?),
             QueryCondition::And { conditions } => {
                 for condition in conditions {
-                    if !condition.evaluate(text)? {
+                    if !condition.evaluate_at(text, timestamp)? {
                         return Ok(false);
                     }
                 }
@@ -54,15 +265,178 @@ impl QueryCondition {
             }
             QueryCondition::Or { conditions } => {
                 for condition in conditions {
-                    if condition.evaluate(text)? {
+                    if condition.evaluate_at(text, timestamp)? {
                         return Ok(true);
                     }
                 }
                 Ok(false)
             }
+            QueryCondition::Near {
+                left,
+                right,
+                distance,
+            } => Ok(left.evaluate_at(text, timestamp)?
+                && right.evaluate_at(text, timestamp)?
+                && terms_near(text, left, right, *distance)),
+            QueryCondition::Scoped { section, condition } => match section.find_in(text) {
+                Some(section_text) => condition.evaluate_at(section_text, timestamp),
+                None => Ok(false),
+            },
+            QueryCondition::Position { .. }
+            | QueryCondition::Kind { .. }
+            | QueryCondition::Outcome { .. } => Ok(true),
+            QueryCondition::After { timestamp: bound } => {
+                Ok(compare_timestamps(timestamp, bound).is_some_and(|order| order.is_ge()))
+            }
+            QueryCondition::Before {
+                timestamp: bound,
+                exclusive,
+            } => Ok(compare_timestamps(timestamp, bound)
+                .is_some_and(|order| order.is_lt() || (!exclusive && order.is_eq()))),
+        }
+    }
+
+    /// Whether no text made of `segments` joined by whitespace can match,
+    /// judged from the segments without joining them. Only literal terms
+    /// without whitespace, which cannot match across two segments, are
+    /// decided this way; for other terms the joined text has to be evaluated.
+    pub fn rules_out(&self, segments: &[Cow<'_, str>]) -> bool {
+        match self {
+            QueryCondition::Literal {
+                pattern,
+                case_sensitive,
+            } => {
+                !pattern.is_empty()
+                    && !pattern.contains(char::is_whitespace)
+                    && !segments.iter().any(|segment| {
+                        if *case_sensitive {
+                            segment.contains(pattern.as_str())
+                        } else {
+                            segment.fast_contains_ignore_case(pattern)
+                        }
+                    })
+            }
+            QueryCondition::And { conditions } => conditions
+                .iter()
+                .any(|condition| condition.rules_out(segments)),
+            QueryCondition::Or { conditions } => conditions
+                .iter()
+                .all(|condition| condition.rules_out(segments)),
+            QueryCondition::Near { left, right, .. } => {
+                left.rules_out(segments) || right.rules_out(segments)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the query has `after:` / `before:` terms, so messages must be
+    /// evaluated with their timestamp
+    pub fn uses_timestamp(&self) -> bool {
+        match self {
+            QueryCondition::After { .. } | QueryCondition::Before { .. } => true,
+            QueryCondition::Not { condition } | QueryCondition::Scoped { condition, .. } => {
+                condition.uses_timestamp()
+            }
+            QueryCondition::And { conditions } | QueryCondition::Or { conditions } => {
+                conditions.iter().any(|c| c.uses_timestamp())
+            }
+            QueryCondition::Near { left, right, .. } => {
+                left.uses_timestamp() || right.uses_timestamp()
+            }
+            QueryCondition::Literal { .. }
+            | QueryCondition::Regex { .. }
+            | QueryCondition::Position { .. }
+            | QueryCondition::Kind { .. }
+            | QueryCondition::Outcome { .. } => false,
+        }
+    }
+
+    /// Whether the query scopes a term to `section`, so messages must be
+    /// searched with that section appended to their text
+    pub fn uses_section(&self, section: TextSection) -> bool {
+        match self {
+            QueryCondition::Scoped {
+                section: scoped,
+                condition,
+            } => *scoped == section || condition.uses_section(section),
+            QueryCondition::Not { condition } => condition.uses_section(section),
+            QueryCondition::And { conditions } | QueryCondition::Or { conditions } => {
+                conditions.iter().any(|c| c.uses_section(section))
+            }
+            QueryCondition::Near { left, right, .. } => {
+                left.uses_section(section) || right.uses_section(section)
+            }
+            QueryCondition::Literal { .. }
+            | QueryCondition::Regex { .. }
+            | QueryCondition::Position { .. }
+            | QueryCondition::Kind { .. }
+            | QueryCondition::Outcome { .. }
+            | QueryCondition::After { .. }
+            | QueryCondition::Before { .. } => false,
         }
     }
 
+    /// The conditions in the chain of ANDs at the top of the query. The
+    /// parser only accepts session filters (`is:first`, `outcome:error`, ...)
+    /// there, so nothing below needs looking at to find them. A query scoped
+    /// by `SearchOptions::scope_query` is looked through.
+    fn conjuncts(&self) -> Vec<&QueryCondition> {
+        match self {
+            QueryCondition::And { conditions } => {
+                conditions.iter().flat_map(|c| c.conjuncts()).collect()
+            }
+            QueryCondition::Scoped { condition, .. } => condition.conjuncts(),
+            _ => vec![self],
+        }
+    }
+
+    /// Positions the query requires through `is:first` / `is:last` terms
+    pub fn positions(&self) -> Vec<SessionPosition> {
+        self.conjuncts()
+            .into_iter()
+            .filter_map(|condition| match condition {
+                QueryCondition::Position { position } => Some(*position),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Message kinds the query asks for through `is:sidechain` / `is:meta` terms
+    pub fn kinds(&self) -> Vec<MessageKind> {
+        self.conjuncts()
+            .into_iter()
+            .filter_map(|condition| match condition {
+                QueryCondition::Kind { kind } => Some(*kind),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Session outcomes the query requires through `outcome:` terms
+    pub fn outcomes(&self) -> Vec<SessionOutcome> {
+        self.conjuncts()
+            .into_iter()
+            .filter_map(|condition| match condition {
+                QueryCondition::Outcome { outcome } => Some(*outcome),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Sections of a matching searchable `text` the match depends on: those
+    /// without which the query would no longer match
+    pub fn matched_sections(&self, text: &str, timestamp: Option<&str>) -> Vec<TextSection> {
+        TextSection::ALL
+            .into_iter()
+            .filter(|section| {
+                section.find_in(text).is_some()
+                    && !self
+                        .evaluate_at(&section.strip_from(text), timestamp)
+                        .unwrap_or(true)
+            })
+            .collect()
+    }
+
     pub fn find_match(&self, text: &str) -> Option<(usize, usize)> {
         match self {
             QueryCondition::Literal {
@@ -72,11 +446,13 @@ impl QueryCondition {
                 if *case_sensitive {
                     text.find(pattern).map(|pos| (pos, pattern.len()))
                 } else {
-                    let lower_text = text.fast_to_lowercase();
-                    let lower_pattern = pattern.fast_to_lowercase();
-                    lower_text
-                        .find(&lower_pattern)
-                        .map(|pos| (pos, pattern.len()))
+                    with_lowercase(text, |lower_text| {
+                        with_lowercase(pattern, |lower_pattern| {
+                            lower_text
+                                .find(lower_pattern)
+                                .map(|pos| (pos, pattern.len()))
+                        })
+                    })
                 }
             }
             QueryCondition::Regex { pattern, flags } => {
@@ -86,7 +462,18 @@ impl QueryCondition {
                     None
                 }
             }
-            QueryCondition::Not { .. } => None,
+            // Thinking is part of the displayed text, tool results are not
+            QueryCondition::Scoped {
+                section: TextSection::Thinking,
+                condition,
+            } => condition.find_match(text),
+            QueryCondition::Not { .. }
+            | QueryCondition::Scoped { .. }
+            | QueryCondition::Position { .. }
+            | QueryCondition::Kind { .. }
+            | QueryCondition::Outcome { .. }
+            | QueryCondition::After { .. }
+            | QueryCondition::Before { .. } => None,
             QueryCondition::And { conditions } => {
                 // Return the first match from any condition
                 for condition in conditions {
@@ -105,11 +492,212 @@ impl QueryCondition {
                 }
                 None
             }
+            QueryCondition::Near { left, right, .. } => {
+                left.find_match(text).or_else(|| right.find_match(text))
+            }
+        }
+    }
+
+    /// Every match of the query's terms in `text` as `(start, len)`, in
+    /// order and merged where they overlap, for marking them all in
+    /// displayed text. Like `find_match`, negated terms and filters mark
+    /// nothing.
+    pub fn find_matches(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        self.collect_matches(text, &mut spans);
+        spans.sort_unstable();
+
+        let mut merged: Vec<(usize, usize)> = Vec::new();
+        for (start, len) in spans {
+            match merged.last_mut() {
+                Some((last_start, last_len)) if start <= *last_start + *last_len => {
+                    *last_len = (*last_len).max(start + len - *last_start);
+                }
+                _ => merged.push((start, len)),
+            }
+        }
+        merged
+    }
+
+    fn collect_matches(&self, text: &str, spans: &mut Vec<(usize, usize)>) {
+        match self {
+            QueryCondition::Literal { .. } | QueryCondition::Regex { .. } => {
+                let mut from = 0;
+                while let Some((start, len)) =
+                    text.get(from..).and_then(|rest| self.find_match(rest))
+                {
+                    let start = from + start;
+                    // Case folding can shift offsets in non-ASCII text
+                    if len == 0
+                        || !text.is_char_boundary(start)
+                        || !text.is_char_boundary(start + len)
+                    {
+                        break;
+                    }
+                    spans.push((start, len));
+                    from = start + len;
+                }
+            }
+            QueryCondition::Scoped {
+                section: TextSection::Thinking,
+                condition,
+            } => condition.collect_matches(text, spans),
+            QueryCondition::And { conditions } | QueryCondition::Or { conditions } => {
+                for condition in conditions {
+                    condition.collect_matches(text, spans);
+                }
+            }
+            QueryCondition::Near { left, right, .. } => {
+                left.collect_matches(text, spans);
+                right.collect_matches(text, spans);
+            }
+            QueryCondition::Not { .. }
+            | QueryCondition::Scoped { .. }
+            | QueryCondition::Position { .. }
+            | QueryCondition::Kind { .. }
+            | QueryCondition::Outcome { .. }
+            | QueryCondition::After { .. }
+            | QueryCondition::Before { .. } => {}
+        }
+    }
+
+    /// How well a matching message's `text` fits the query, for
+    /// `SearchOrder::Relevance`.
+    ///
+    /// Each occurrence of a term scores a point, each distinct term found
+    /// scores [`TERM_SCORE`] more, and a quoted multi-word phrase found whole
+    /// adds [`PHRASE_SCORE`]. Negated terms and filters score nothing.
+    pub fn relevance(&self, text: &str) -> usize {
+        let mut score = 0;
+        self.visit_term_matches(text, &mut |occurrences, is_phrase| {
+            score += term_score(occurrences, is_phrase);
+        });
+        score
+    }
+
+    /// How many times the query's terms occur in `text`; negated terms and
+    /// filters are not counted
+    pub fn match_count(&self, text: &str) -> usize {
+        let mut count = 0;
+        self.visit_term_matches(text, &mut |occurrences, _| count += occurrences);
+        count
+    }
+
+    /// Call `visit` with the occurrences in `text` of every term that is not
+    /// negated, and whether the term is a multi-word phrase
+    fn visit_term_matches(&self, text: &str, visit: &mut impl FnMut(usize, bool)) {
+        match self {
+            QueryCondition::Literal {
+                pattern,
+                case_sensitive,
+            } => {
+                if pattern.is_empty() {
+                    return;
+                }
+                let count = if *case_sensitive {
+                    text.matches(pattern.as_str()).count()
+                } else {
+                    with_lowercase(text, |text| {
+                        with_lowercase(pattern, |pattern| text.matches(pattern).count())
+                    })
+                };
+                visit(count, pattern.contains(char::is_whitespace));
+            }
+            QueryCondition::Regex { pattern, flags } => {
+                if let Ok(regex) = super::regex_cache::get_or_compile_regex(pattern, flags) {
+                    visit(regex.find_iter(text).count(), false);
+                }
+            }
+            QueryCondition::Scoped { section, condition } => {
+                if let Some(section_text) = section.find_in(text) {
+                    condition.visit_term_matches(section_text, visit);
+                }
+            }
+            QueryCondition::And { conditions } | QueryCondition::Or { conditions } => {
+                for condition in conditions {
+                    condition.visit_term_matches(text, visit);
+                }
+            }
+            QueryCondition::Near { left, right, .. } => {
+                left.visit_term_matches(text, visit);
+                right.visit_term_matches(text, visit);
+            }
+            QueryCondition::Not { .. }
+            | QueryCondition::Position { .. }
+            | QueryCondition::Kind { .. }
+            | QueryCondition::Outcome { .. }
+            | QueryCondition::After { .. }
+            | QueryCondition::Before { .. } => {}
+        }
+    }
+}
+
+/// Bonus for each distinct query term found in a message
+const TERM_SCORE: usize = 10;
+
+/// Bonus for a multi-word phrase found as a whole
+const PHRASE_SCORE: usize = 20;
+
+fn term_score(occurrences: usize, is_phrase: bool) -> usize {
+    match occurrences {
+        0 => 0,
+        _ if is_phrase => occurrences + TERM_SCORE + PHRASE_SCORE,
+        _ => occurrences + TERM_SCORE,
+    }
+}
+
+/// Whether a match of `left` and one of `right` in `text` have at most
+/// `distance` words between them. Words are separated by whitespace, and a
+/// match covers every word it touches, so overlapping matches are 0 apart.
+fn terms_near(text: &str, left: &QueryCondition, right: &QueryCondition, distance: usize) -> bool {
+    let mut word_starts = Vec::new();
+    let mut in_word = false;
+    for (i, c) in text.char_indices() {
+        if !c.is_whitespace() && !in_word {
+            word_starts.push(i);
         }
+        in_word = !c.is_whitespace();
     }
+    let word_at = |offset: usize| {
+        word_starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1)
+    };
+    // (first word, last word) of each match
+    let words = |condition: &QueryCondition| -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        condition.collect_matches(text, &mut spans);
+        spans
+            .into_iter()
+            .map(|(start, len)| (word_at(start), word_at(start + len.max(1) - 1)))
+            .collect()
+    };
+
+    let right = words(right);
+    words(left).into_iter().any(|(left_first, left_last)| {
+        right.iter().any(|&(right_first, right_last)| {
+            let between = if right_first > left_last {
+                right_first - left_last - 1
+            } else if left_first > right_last {
+                left_first - right_last - 1
+            } else {
+                0
+            };
+            between <= distance
+        })
+    })
+}
+
+/// How a message's `timestamp` compares to a time-term `bound`; `None` when
+/// the message has no timestamp or either side does not parse
+fn compare_timestamps(timestamp: Option<&str>, bound: &str) -> Option<std::cmp::Ordering> {
+    let timestamp = chrono::DateTime::parse_from_rfc3339(timestamp?).ok()?;
+    let bound = chrono::DateTime::parse_from_rfc3339(bound).ok()?;
+    Some(timestamp.cmp(&bound))
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SearchOptions {
     pub max_results: Option<usize>,
     pub role: Option<String>,
@@ -119,6 +707,55 @@ pub struct SearchOptions {
     pub after: Option<String>,
     pub verbose: bool,
     pub project_path: Option<String>,
+    /// On-disk index to consult for unchanged files (see `ccms index build`)
+    pub index_path: Option<PathBuf>,
+    /// Index already in memory, consulted instead of loading `index_path`
+    /// (the warm corpus of `ccms daemon`)
+    #[serde(skip)]
+    pub index: Option<Arc<SearchIndex>>,
+    /// Remember per-file matches in memory so repeated and narrowing queries
+    /// can skip re-reading unchanged files (see `search::result_cache`)
+    pub cache_results: bool,
+    /// Roles left out of results
+    pub exclude_roles: Vec<String>,
+    /// Sessions left out of results
+    pub exclude_session_ids: Vec<String>,
+    /// Working directories whose sessions are left out, matched like `project_path`
+    pub exclude_projects: Vec<String>,
+    /// Keep only sessions whose working directory is inside one of these
+    /// directories (`--repo`: the worktrees of a git repository)
+    pub repo_dirs: Vec<PathBuf>,
+    /// Keep at most this many results from any one session
+    pub max_per_session: Option<usize>,
+    /// Collapse copies of the same message that resumed sessions carry over
+    /// (`--dedupe`, see `SearchOptions::dedupe`)
+    pub dedupe: bool,
+    /// Also match against structured tool results (edited files, diffs,
+    /// todos, web search results)
+    pub include_tool_results: bool,
+    /// Also match against the metadata of attached images (media type,
+    /// source, file name)
+    pub include_images: bool,
+    /// With `session_id`, also keep summary records, which carry no session
+    /// id of their own (the session viewer, which shows a whole file)
+    pub session_summaries: bool,
+    /// Leave out user messages that only carry tool results
+    pub exclude_tool_result_messages: bool,
+    /// Match the whole query against assistant thinking blocks only
+    pub thinking_only: bool,
+    /// Keep only messages with one of these uuids (`--bookmarked`)
+    pub only_uuids: Option<HashSet<String>>,
+    /// Keep only each session's first or last message (`--position`)
+    pub position: Option<SessionPosition>,
+    /// Leave out subagent (sidechain) messages unless the query has `is:sidechain`
+    pub exclude_sidechain: bool,
+    /// Leave out meta messages unless the query has `is:meta`
+    pub exclude_meta: bool,
+    /// Keep only messages with these tool calls or results (`--tool`,
+    /// `--tool-status`)
+    pub tool: Option<ToolFilter>,
+    /// How session files are read (`--io`)
+    pub io: IoMode,
 }
 
 impl Default for SearchOptions {
@@ -132,29 +769,259 @@ impl Default for SearchOptions {
             after: None,
             verbose: false,
             project_path: None,
+            index_path: None,
+            index: None,
+            cache_results: false,
+            exclude_roles: Vec::new(),
+            exclude_session_ids: Vec::new(),
+            exclude_projects: Vec::new(),
+            repo_dirs: Vec::new(),
+            max_per_session: None,
+            dedupe: false,
+            include_tool_results: false,
+            include_images: false,
+            session_summaries: false,
+            exclude_tool_result_messages: false,
+            thinking_only: false,
+            only_uuids: None,
+            position: None,
+            exclude_sidechain: true,
+            exclude_meta: true,
+            tool: None,
+            io: IoMode::Buffered,
         }
     }
 }
 
+impl SearchOptions {
+    /// Whether `result` is dropped by one of the `exclude_*` filters or
+    /// is missing from `only_uuids`
+    pub fn excludes(&self, result: &SearchResult) -> bool {
+        self.only_uuids
+            .as_ref()
+            .is_some_and(|uuids| !uuids.contains(&result.uuid))
+            || self.exclude_roles.iter().any(|role| result.role == *role)
+            || self
+                .exclude_session_ids
+                .iter()
+                .any(|session_id| result.session_id == *session_id)
+            || self.exclude_projects.iter().any(|project| {
+                crate::utils::path_encoding::file_belongs_to_project(&result.file, project)
+            })
+            || !self.repo_dirs.is_empty()
+                && !self
+                    .repo_dirs
+                    .iter()
+                    .any(|dir| Path::new(result.cwd.as_str()).starts_with(dir))
+    }
+
+    /// The query to run: `query` itself, or scoped to thinking blocks with
+    /// `thinking_only`
+    pub fn scope_query(&self, query: QueryCondition) -> QueryCondition {
+        if self.thinking_only {
+            QueryCondition::Scoped {
+                section: TextSection::Thinking,
+                condition: Box::new(query),
+            }
+        } else {
+            query
+        }
+    }
+
+    /// Whether a message of session `message_session_id` (`None` for
+    /// summaries) passes the `session_id` filter
+    pub fn matches_session(&self, message_session_id: Option<&str>) -> bool {
+        match (&self.session_id, message_session_id) {
+            (None, _) => true,
+            (Some(wanted), Some(id)) => wanted == id,
+            (Some(_), None) => self.session_summaries,
+        }
+    }
+
+    /// Sections to append to each message's searchable text for `query`
+    pub fn sections_for(&self, query: &QueryCondition) -> Vec<TextSection> {
+        TextSection::ALL
+            .into_iter()
+            .filter(|&section| {
+                query.uses_section(section)
+                    || (section == TextSection::ToolResult && self.include_tool_results)
+                    || (section == TextSection::Images && self.include_images)
+            })
+            .collect()
+    }
+
+    /// Session positions every result of `query` must be at, from
+    /// `position` and the query's own `is:first` / `is:last` terms
+    pub fn positions_for(&self, query: &QueryCondition) -> Vec<SessionPosition> {
+        let mut positions = query.positions();
+        positions.extend(self.position);
+        positions
+    }
+
+    /// Whether a message with these flags can match a query asking for
+    /// `kinds`: it must be of every kind asked for, and not of a kind left
+    /// out that was not asked for
+    pub fn admits(&self, kinds: &[MessageKind], is_sidechain: bool, is_meta: bool) -> bool {
+        MessageKind::ALL.into_iter().all(|kind| {
+            let (is_kind, excluded) = match kind {
+                MessageKind::Sidechain => (is_sidechain, self.exclude_sidechain),
+                MessageKind::Meta => (is_meta, self.exclude_meta),
+            };
+            if kinds.contains(&kind) {
+                is_kind
+            } else {
+                !(is_kind && excluded)
+            }
+        })
+    }
+
+    /// Apply `dedupe` to `results`: of the results sharing a uuid (or, for
+    /// messages without one, the same type and text), keep the one from the
+    /// most recently modified file, leaving the order of everything kept
+    pub fn dedupe(&self, results: &mut Vec<SearchResult>) {
+        if !self.dedupe {
+            return;
+        }
+
+        let mut modified: HashMap<&str, SystemTime> = HashMap::new();
+        let mut kept: HashMap<DedupeKey, usize> = HashMap::new();
+        for (i, result) in results.iter().enumerate() {
+            let mtime = *modified.entry(&result.file).or_insert_with(|| {
+                std::fs::metadata(result.file.as_str())
+                    .and_then(|metadata| metadata.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH)
+            });
+            match kept.entry(DedupeKey::of(result)) {
+                Entry::Vacant(entry) => {
+                    entry.insert(i);
+                }
+                Entry::Occupied(mut entry) => {
+                    if mtime > modified[results[*entry.get()].file.as_str()] {
+                        entry.insert(i);
+                    }
+                }
+            }
+        }
+
+        let kept: HashSet<usize> = kept.into_values().collect();
+        let mut i = 0;
+        results.retain(|_| {
+            i += 1;
+            kept.contains(&(i - 1))
+        });
+    }
+
+    /// Apply `max_per_session` to sorted `results`, keeping the first hits of
+    /// each session and the order of everything kept
+    pub fn cap_per_session(&self, results: &mut Vec<SearchResult>) {
+        let Some(cap) = self.max_per_session else {
+            return;
+        };
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        results.retain(|result| {
+            let count = seen.entry(result.session_id.to_string()).or_default();
+            *count += 1;
+            *count <= cap
+        });
+    }
+}
+
+/// What makes two results copies of one message for `SearchOptions::dedupe`
+#[derive(PartialEq, Eq, Hash)]
+enum DedupeKey {
+    Uuid(String),
+    Content(u64),
+}
+
+impl DedupeKey {
+    fn of(result: &SearchResult) -> Self {
+        if !result.uuid.is_empty() {
+            return DedupeKey::Uuid(result.uuid.clone());
+        }
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        (&result.message_type, &result.text).hash(&mut hasher);
+        DedupeKey::Content(hasher.finish())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SearchResult {
-    pub file: String,
+    pub file: SharedStr,
     pub uuid: String,
     pub timestamp: String,
-    pub session_id: String,
-    pub role: String,
+    pub session_id: SharedStr,
+    pub role: SharedStr,
     pub text: String,
-    pub message_type: String,
+    pub message_type: SharedStr,
     pub query: QueryCondition,
-    pub cwd: String,
+    pub cwd: SharedStr,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub raw_json: Option<String>,
+    /// 1-based line of the message in `file`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<usize>,
+    /// Sections outside the message text that the match depends on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched_in: Vec<TextSection>,
+    /// The message has images attached, e.g. pasted screenshots
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub has_images: bool,
 }
 
 use crate::interactive_ratatui::ui::components::list_item::{ListItem, wrap_text};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::Style;
 use ratatui::text::{Line, Span};
 
+impl SearchResult {
+    /// Where and how often the query matched, like `3×[text]` or `[tool]`,
+    /// shown in front of the message in the result list; empty when only
+    /// filters matched
+    pub fn match_indicator(&self) -> String {
+        let mut indicator = match self.query.match_count(&self.text) {
+            0 => String::new(),
+            count => format!("{count}×[text]"),
+        };
+        for section in &self.matched_in {
+            indicator.push_str(match section {
+                TextSection::Thinking => "[thinking]",
+                TextSection::ToolResult => "[tool]",
+                TextSection::Images => "[image]",
+            });
+        }
+        indicator
+    }
+
+    /// Whether the message is where the session continues after a context
+    /// compaction; only known for results that carry their raw JSON
+    pub fn starts_compaction(&self) -> bool {
+        self.raw_json.as_deref().is_some_and(|raw| {
+            (raw.contains("compact_boundary") || raw.contains("isCompactSummary"))
+                && serde_json::from_str::<crate::schemas::SessionMessage>(raw)
+                    .is_ok_and(|message| message.starts_compaction())
+        })
+    }
+
+    /// Column with the git repository the session ran in, when it ran in one
+    fn repo_span(&self) -> Option<Span<'static>> {
+        crate::utils::git_repo::repo_name(&self.cwd).map(|name| {
+            Span::styled(
+                format!("{name:12.12} "),
+                Style::default().fg(theme::current().accent),
+            )
+        })
+    }
+
+    fn match_indicator_span(&self) -> Option<Span<'static>> {
+        let indicator = self.match_indicator();
+        (!indicator.is_empty()).then(|| {
+            Span::styled(
+                format!("{indicator} "),
+                Style::default().fg(theme::current().primary),
+            )
+        })
+    }
+}
+
 impl ListItem for SearchResult {
     fn get_role(&self) -> &str {
         &self.role
@@ -176,13 +1043,15 @@ impl ListItem for SearchResult {
         let mut spans = vec![
             Span::styled(
                 format!("{timestamp:16} "),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme::current().text_dim),
             ),
             Span::styled(
                 format!("{:10} ", self.get_role()),
                 Style::default().fg(self.get_role_color()),
             ),
         ];
+        spans.extend(self.repo_span());
+        spans.extend(self.match_indicator_span());
 
         if let Some((start, len)) = self.query.find_match(&content) {
             let end = start + len;
@@ -191,7 +1060,7 @@ impl ListItem for SearchResult {
             }
             spans.push(Span::styled(
                 content[start..end].to_string(),
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                theme::current().match_style(),
             ));
             if end < content.len() {
                 spans.push(Span::raw(content[end..].to_string()));
@@ -213,13 +1082,17 @@ impl ListItem for SearchResult {
         let mut first_line_spans = vec![
             Span::styled(
                 format!("{timestamp:16} "),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme::current().text_dim),
             ),
             Span::styled(
                 format!("{:10} ", self.get_role()),
                 Style::default().fg(self.get_role_color()),
             ),
         ];
+        let repo = self.repo_span();
+        let repo_width = repo.as_ref().map_or(0, |span| span.width());
+        first_line_spans.extend(repo);
+        first_line_spans.extend(self.match_indicator_span());
 
         if let Some((start, len)) = self.query.find_match(&first_line_content) {
             let end = start + len;
@@ -228,7 +1101,7 @@ impl ListItem for SearchResult {
             }
             first_line_spans.push(Span::styled(
                 first_line_content[start..end].to_string(),
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                theme::current().match_style(),
             ));
             if end < first_line_content.len() {
                 first_line_spans.push(Span::raw(first_line_content[end..].to_string()));
@@ -240,7 +1113,7 @@ impl ListItem for SearchResult {
 
         // Additional lines (indented)
         for line in wrapped_lines.iter().skip(1) {
-            let indent = " ".repeat(29); // 16 + 1 + 10 + 1 + 1 spaces
+            let indent = " ".repeat(29 + repo_width); // 16 + 1 + 10 + 1 + 1 spaces
             let mut line_spans = vec![Span::raw(indent)];
 
             if let Some((start, len)) = self.query.find_match(line) {
@@ -250,7 +1123,7 @@ impl ListItem for SearchResult {
                 }
                 line_spans.push(Span::styled(
                     line[start..end].to_string(),
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    theme::current().match_style(),
                 ));
                 if end < line.len() {
                     line_spans.push(Span::raw(line[end..].to_string()));
@@ -336,6 +1209,129 @@ mod tests {
         assert!(!condition.evaluate("Error occurred").unwrap());
     }
 
+    #[test]
+    fn test_scoped_condition() {
+        let condition = QueryCondition::Scoped {
+            section: TextSection::ToolResult,
+            condition: Box::new(QueryCondition::Literal {
+                pattern: "main.rs".to_string(),
+                case_sensitive: false,
+            }),
+        };
+        let text = |tool_text: &str| {
+            let mut text = "edited main.rs".to_string();
+            TextSection::Thinking.append_to(&mut text, "checking lib.rs");
+            TextSection::ToolResult.append_to(&mut text, tool_text);
+            text
+        };
+
+        assert!(condition.evaluate(&text("/w/src/main.rs")).unwrap());
+        assert!(!condition.evaluate(&text("/w/lib.rs")).unwrap());
+        assert!(!condition.evaluate("edited main.rs").unwrap());
+        assert_eq!(
+            TextSection::Thinking.find_in(&text("x")),
+            Some("checking lib.rs")
+        );
+
+        let query = QueryCondition::Not {
+            condition: Box::new(condition),
+        };
+        assert!(query.uses_section(TextSection::ToolResult));
+        assert!(!query.uses_section(TextSection::Thinking));
+        let options = SearchOptions::default();
+        assert_eq!(options.sections_for(&query), vec![TextSection::ToolResult]);
+        let plain = QueryCondition::Literal {
+            pattern: "lib.rs".to_string(),
+            case_sensitive: false,
+        };
+        assert!(options.sections_for(&plain).is_empty());
+        let included = SearchOptions {
+            include_tool_results: true,
+            ..Default::default()
+        };
+        assert_eq!(included.sections_for(&plain), vec![TextSection::ToolResult]);
+        let images = SearchOptions {
+            include_tool_results: true,
+            include_images: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            images.sections_for(&plain),
+            vec![TextSection::ToolResult, TextSection::Images]
+        );
+    }
+
+    #[test]
+    fn test_matched_sections() {
+        let mut text = "deploy the app".to_string();
+        TextSection::Thinking.append_to(&mut text, "roll back first");
+
+        let literal = |pattern: &str| QueryCondition::Literal {
+            pattern: pattern.to_string(),
+            case_sensitive: false,
+        };
+        assert!(literal("deploy").matched_sections(&text, None).is_empty());
+        assert_eq!(
+            literal("roll back").matched_sections(&text, None),
+            vec![TextSection::Thinking]
+        );
+
+        let thinking = SearchOptions {
+            thinking_only: true,
+            ..Default::default()
+        };
+        let scoped = thinking.scope_query(literal("roll"));
+        assert!(scoped.evaluate(&text).unwrap());
+        assert!(
+            !thinking
+                .scope_query(literal("deploy"))
+                .evaluate(&text)
+                .unwrap()
+        );
+        assert_eq!(
+            scoped.matched_sections(&text, None),
+            vec![TextSection::Thinking]
+        );
+        assert_eq!(thinking.sections_for(&scoped), vec![TextSection::Thinking]);
+    }
+
+    #[test]
+    fn test_time_conditions() -> Result<(), regex::Error> {
+        let after = QueryCondition::After {
+            timestamp: "2024-05-01T00:00:00+00:00".to_string(),
+        };
+        let before = QueryCondition::Before {
+            timestamp: "2024-05-31T00:00:00+00:00".to_string(),
+            exclusive: false,
+        };
+        let in_may = QueryCondition::And {
+            conditions: vec![after.clone(), before],
+        };
+
+        assert!(in_may.evaluate_at("any", Some("2024-05-10T12:00:00Z"))?);
+        assert!(in_may.evaluate_at("any", Some("2024-05-01T00:00:00Z"))?);
+        assert!(!in_may.evaluate_at("any", Some("2024-06-01T00:00:00Z"))?);
+        assert!(in_may.evaluate_at("any", Some("2024-05-31T00:00:00Z"))?);
+        let exclusive = QueryCondition::Before {
+            timestamp: "2024-05-31T00:00:00+00:00".to_string(),
+            exclusive: true,
+        };
+        assert!(!exclusive.evaluate_at("any", Some("2024-05-31T00:00:00Z"))?);
+        assert!(exclusive.evaluate_at("any", Some("2024-05-30T23:59:59Z"))?);
+        // Without a usable timestamp, time terms do not match
+        assert!(!after.evaluate("any")?);
+        assert!(!after.evaluate_at("any", Some("yesterday"))?);
+        assert!(in_may.uses_timestamp());
+        assert!(
+            !QueryCondition::Literal {
+                pattern: "deploy".to_string(),
+                case_sensitive: false,
+            }
+            .uses_timestamp()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_and_condition() {
         let conditions = vec![
@@ -423,6 +1419,88 @@ mod tests {
         assert_eq!(&text[start..start + len], "error");
     }
 
+    #[test]
+    fn test_find_matches() {
+        let query =
+            crate::query::parse_query("error AND (timeout OR /re+try/) AND NOT warn").unwrap();
+        let text = "Error: timeout, retry after error; warn";
+        assert_eq!(
+            query.find_matches(text),
+            vec![(0, 5), (7, 7), (16, 5), (28, 5)]
+        );
+
+        // Overlapping terms are merged
+        let query = crate::query::parse_query("time OR timeout").unwrap();
+        assert_eq!(query.find_matches("a timeout"), vec![(2, 7)]);
+        assert!(
+            crate::query::parse_query("NOT error")
+                .unwrap()
+                .find_matches("error")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_rules_out() {
+        let segments = [
+            Cow::Borrowed("Fix the build"),
+            Cow::Owned("Bash: make".to_string()),
+        ];
+        let rules_out = |query: &str| {
+            crate::query::parse_query(query)
+                .unwrap()
+                .rules_out(&segments)
+        };
+
+        assert!(!rules_out("BUILD"));
+        assert!(!rules_out("make AND fix"));
+        assert!(rules_out("make AND deploy"));
+        assert!(!rules_out("deploy OR bash"));
+        assert!(rules_out("deploy OR release"));
+        assert!(rules_out("deploy NEAR/3 make"));
+        // Terms that may match across segments, or need more than the text,
+        // are left to the joined text
+        assert!(!rules_out("\"build Bash\""));
+        assert!(!rules_out("/build.Bash/"));
+        assert!(!rules_out("NOT build"));
+        assert!(!rules_out("after:2024-01-01"));
+    }
+
+    #[test]
+    fn test_near() {
+        let query = crate::query::parse_query("\"error\" NEAR/2 timeout").unwrap();
+        assert!(query.evaluate("an error after a timeout").unwrap());
+        assert!(query.evaluate("Timeout, then an ERROR").unwrap());
+        assert!(
+            !query
+                .evaluate("an error and then, much later, a timeout")
+                .unwrap()
+        );
+        assert!(!query.evaluate("only an error").unwrap());
+        // Any pair of matches will do
+        assert!(
+            query
+                .evaluate("error first, and far far away timeout error")
+                .unwrap()
+        );
+
+        // Phrases and regexes cover every word they touch
+        let query =
+            crate::query::parse_query("\"connection reset\" NEAR/0 /re+tr(y|ied)/").unwrap();
+        assert!(query.evaluate("connection reset; retried").unwrap());
+        assert!(!query.evaluate("connection reset, not retried").unwrap());
+        // Matches inside one word are 0 words apart
+        let query = crate::query::parse_query("time NEAR/0 out").unwrap();
+        assert!(query.evaluate("timeout").unwrap());
+
+        assert_eq!(
+            crate::query::parse_query("error NEAR/1 timeout")
+                .unwrap()
+                .find_matches("error: timeout"),
+            vec![(0, 5), (7, 7)]
+        );
+    }
+
     #[test]
     fn test_find_match_regex() {
         let condition = QueryCondition::Regex {
@@ -463,4 +1541,222 @@ mod tests {
         // Empty OR should return false (no conditions are satisfied)
         assert!(!condition.evaluate("anything").unwrap());
     }
+
+    #[test]
+    fn test_search_options_excludes() {
+        let result = SearchResult {
+            file: "/home/u/.claude/projects/-work-app/s1.jsonl".into(),
+            uuid: "u1".to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s1".into(),
+            role: "assistant".into(),
+            text: "hi".to_string(),
+            message_type: "assistant".into(),
+            query: QueryCondition::And { conditions: vec![] },
+            cwd: "/work/app".into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        };
+
+        assert!(!SearchOptions::default().excludes(&result));
+        for options in [
+            SearchOptions {
+                exclude_roles: vec!["system".to_string(), "assistant".to_string()],
+                ..Default::default()
+            },
+            SearchOptions {
+                exclude_session_ids: vec!["s1".to_string()],
+                ..Default::default()
+            },
+            SearchOptions {
+                exclude_projects: vec!["/work".to_string()],
+                ..Default::default()
+            },
+            SearchOptions {
+                only_uuids: Some(HashSet::from(["u2".to_string()])),
+                ..Default::default()
+            },
+            SearchOptions {
+                repo_dirs: vec![PathBuf::from("/work/lib"), PathBuf::from("/work/ap")],
+                ..Default::default()
+            },
+        ] {
+            assert!(options.excludes(&result), "{options:?}");
+        }
+
+        let other_project = SearchOptions {
+            exclude_projects: vec!["/work/lib".to_string()],
+            repo_dirs: vec![PathBuf::from("/other"), PathBuf::from("/work/app")],
+            exclude_roles: vec!["user".to_string()],
+            only_uuids: Some(HashSet::from(["u1".to_string()])),
+            ..Default::default()
+        };
+        assert!(!other_project.excludes(&result));
+    }
+
+    #[test]
+    fn test_admits_message_kinds() {
+        use MessageKind::*;
+        let defaults = SearchOptions::default();
+        assert!(defaults.admits(&[], false, false));
+        assert!(!defaults.admits(&[], true, false));
+        assert!(!defaults.admits(&[], false, true));
+        // Asking for a kind both brings it back and requires it
+        assert!(defaults.admits(&[Sidechain], true, false));
+        assert!(!defaults.admits(&[Sidechain], false, false));
+        assert!(!defaults.admits(&[Sidechain], true, true));
+        assert!(defaults.admits(&[Sidechain, Meta], true, true));
+
+        let everything = SearchOptions {
+            exclude_sidechain: false,
+            exclude_meta: false,
+            ..Default::default()
+        };
+        assert!(everything.admits(&[], true, true));
+        assert!(!everything.admits(&[Meta], true, false));
+    }
+
+    #[test]
+    fn test_cap_per_session_keeps_order() {
+        let result = |uuid: &str, session_id: &str| SearchResult {
+            file: "/s.jsonl".into(),
+            uuid: uuid.to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: session_id.into(),
+            role: "user".into(),
+            text: "hi".to_string(),
+            message_type: "user".into(),
+            query: QueryCondition::And { conditions: vec![] },
+            cwd: "/".into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        };
+        let all = vec![
+            result("1", "a"),
+            result("2", "a"),
+            result("3", "b"),
+            result("4", "a"),
+            result("5", "b"),
+            result("6", "c"),
+        ];
+
+        let mut uncapped = all.clone();
+        SearchOptions::default().cap_per_session(&mut uncapped);
+        assert_eq!(uncapped, all);
+
+        let mut capped = all;
+        SearchOptions {
+            max_per_session: Some(1),
+            ..Default::default()
+        }
+        .cap_per_session(&mut capped);
+        let uuids: Vec<&str> = capped.iter().map(|r| r.uuid.as_str()).collect();
+        assert_eq!(uuids, vec!["1", "3", "6"]);
+    }
+
+    #[test]
+    fn test_dedupe_keeps_newest_file() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let older = dir.path().join("older.jsonl");
+        let newer = dir.path().join("newer.jsonl");
+        for (path, age) in [(&older, 60), (&newer, 0)] {
+            std::fs::File::create(path)?
+                .set_modified(SystemTime::now() - std::time::Duration::from_secs(age))?;
+        }
+        let result = |file: &PathBuf, uuid: &str, text: &str| SearchResult {
+            file: file.display().to_string().into(),
+            uuid: uuid.to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s".into(),
+            role: "user".into(),
+            text: text.to_string(),
+            message_type: "user".into(),
+            query: QueryCondition::And { conditions: vec![] },
+            cwd: "/".into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        };
+        let all = vec![
+            result(&older, "u1", "resumed"),
+            result(&newer, "u1", "resumed"),
+            result(&older, "u2", "only once"),
+            result(&older, "", "summary"),
+            result(&newer, "", "summary"),
+        ];
+
+        let mut kept = all.clone();
+        SearchOptions::default().dedupe(&mut kept);
+        assert_eq!(kept, all);
+
+        SearchOptions {
+            dedupe: true,
+            ..Default::default()
+        }
+        .dedupe(&mut kept);
+        let kept: Vec<(&str, bool)> = kept
+            .iter()
+            .map(|r| (r.uuid.as_str(), r.file.ends_with("newer.jsonl")))
+            .collect();
+        assert_eq!(kept, vec![("u1", true), ("u2", false), ("", true)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_match_indicator() {
+        let mut result = SearchResult {
+            file: "/s.jsonl".into(),
+            uuid: "u1".to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            session_id: "s".into(),
+            role: "user".into(),
+            text: "Cache miss, then a cache hit".to_string(),
+            message_type: "user".into(),
+            query: crate::parse_query("cache AND NOT redis").unwrap(),
+            cwd: "/".into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        };
+        assert_eq!(result.match_indicator(), "2×[text]");
+
+        result.query = crate::parse_query("tool_result:Cargo.toml").unwrap();
+        result.matched_in = vec![TextSection::ToolResult];
+        assert_eq!(result.match_indicator(), "[tool]");
+
+        result.query = crate::parse_query("is:first").unwrap();
+        result.matched_in.clear();
+        assert_eq!(result.match_indicator(), "");
+    }
+
+    #[test]
+    fn test_relevance() {
+        let query = crate::parse_query("cache OR redis").unwrap();
+        // Occurrences add up, each distinct term adds a bonus
+        assert_eq!(query.relevance("Cache miss"), 1 + TERM_SCORE);
+        assert_eq!(query.relevance("cache the cache"), 2 + TERM_SCORE);
+        assert_eq!(query.relevance("redis cache"), 2 * (1 + TERM_SCORE));
+        assert_eq!(query.relevance("nothing here"), 0);
+
+        let phrase = crate::parse_query("\"connection pool\" OR pool").unwrap();
+        assert_eq!(
+            phrase.relevance("the connection pool"),
+            (1 + TERM_SCORE + PHRASE_SCORE) + (1 + TERM_SCORE)
+        );
+
+        let negated = crate::parse_query("cache AND NOT redis").unwrap();
+        assert_eq!(negated.relevance("cache, not redis"), 1 + TERM_SCORE);
+        assert_eq!(
+            crate::parse_query("/ca+che/")
+                .unwrap()
+                .relevance("caache cache"),
+            2 + TERM_SCORE
+        );
+    }
 }
@@ -1,3 +1,37 @@
+use std::cell::RefCell;
+
+/// Buffers longer than this are dropped rather than kept for reuse, so one
+/// huge message does not pin its size in memory for the thread's lifetime
+const MAX_POOLED_CAPACITY: usize = 1 << 20;
+
+thread_local! {
+    /// Spare lowercase buffers, reused by `with_lowercase` across searches
+    static LOWERCASE_BUFFERS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Call `f` with `text` lowercased into a buffer reused across calls on this
+/// thread, instead of allocating a fresh string on every evaluation. Calls
+/// may nest; each level gets its own buffer.
+pub fn with_lowercase<R>(text: &str, f: impl FnOnce(&str) -> R) -> R {
+    let mut buffer = LOWERCASE_BUFFERS
+        .with_borrow_mut(Vec::pop)
+        .unwrap_or_default();
+    buffer.clear();
+    if text.is_ascii() {
+        buffer.push_str(text);
+        buffer.make_ascii_lowercase();
+    } else {
+        buffer.extend(text.chars().flat_map(char::to_lowercase));
+    }
+
+    let result = f(&buffer);
+
+    if buffer.capacity() <= MAX_POOLED_CAPACITY {
+        LOWERCASE_BUFFERS.with_borrow_mut(|buffers| buffers.push(buffer));
+    }
+    result
+}
+
 /// Fast case-insensitive string comparison utilities
 /// Optimized for ASCII strings but handles Unicode correctly
 /// Trait for fast lowercase comparison
@@ -51,7 +85,9 @@ impl FastLowercase for str {
             false
         } else {
             // Unicode fallback
-            self.to_lowercase().contains(&pattern.to_lowercase())
+            with_lowercase(self, |text| {
+                with_lowercase(pattern, |pattern| text.contains(pattern))
+            })
         }
     }
 }
@@ -89,6 +125,22 @@ mod tests {
         assert!(!"Hello".fast_contains_ignore_case("привет"));
     }
 
+    #[test]
+    fn test_with_lowercase() {
+        assert_eq!(with_lowercase("Hello World", str::to_string), "hello world");
+        assert_eq!(with_lowercase("МОСКВА", str::to_string), "москва");
+
+        // Nested calls get separate buffers, and a reused buffer holds
+        // nothing from its last use
+        with_lowercase("A LONGER TEXT", |outer| {
+            with_lowercase("Short", |inner| {
+                assert_eq!(outer, "a longer text");
+                assert_eq!(inner, "short");
+            })
+        });
+        assert_eq!(with_lowercase("Ab", str::to_string), "ab");
+    }
+
     #[test]
     fn test_edge_cases() {
         assert!("".fast_contains_ignore_case(""));
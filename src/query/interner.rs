@@ -0,0 +1,203 @@
+//! Strings shared between the results of a search.
+//!
+//! A broad search returns tens of thousands of results that repeat a handful
+//! of file paths, session ids, roles and working directories. Results keep
+//! those as [`SharedStr`]s, and the [`Interner`] of a search hands out one
+//! allocation for all equal strings.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+/// An immutable string that is cheap to clone
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SharedStr(Arc<str>);
+
+impl SharedStr {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether `self` and `other` share their allocation
+    pub fn ptr_eq(&self, other: &SharedStr) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for SharedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for SharedStr {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for SharedStr {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl From<&str> for SharedStr {
+    fn from(s: &str) -> Self {
+        Self(Arc::from(s))
+    }
+}
+
+impl From<String> for SharedStr {
+    fn from(s: String) -> Self {
+        Self(Arc::from(s))
+    }
+}
+
+impl From<&String> for SharedStr {
+    fn from(s: &String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<SharedStr> for String {
+    fn from(s: SharedStr) -> Self {
+        s.0.to_string()
+    }
+}
+
+impl PartialEq<str> for SharedStr {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for SharedStr {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for SharedStr {
+    fn eq(&self, other: &String) -> bool {
+        &*self.0 == other.as_str()
+    }
+}
+
+impl Serialize for SharedStr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SharedStr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// The strings seen by one search, shared by the threads searching its files
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: Mutex<HashSet<SharedStr>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&self, s: &str) -> SharedStr {
+        let mut strings = self.strings.lock().unwrap();
+        if let Some(shared) = strings.get(s) {
+            return shared.clone();
+        }
+        let shared = SharedStr::from(s);
+        strings.insert(shared.clone());
+        shared
+    }
+
+    /// An interner for one file, which takes the lock only for strings the
+    /// file has not used recently
+    pub fn local(&self) -> LocalInterner<'_> {
+        LocalInterner {
+            shared: self,
+            recent: Vec::new(),
+        }
+    }
+}
+
+/// Number of strings a [`LocalInterner`] remembers; a file has one path, a
+/// session id or two, a few roles and usually one working directory
+const RECENT_STRINGS: usize = 8;
+
+pub struct LocalInterner<'a> {
+    shared: &'a Interner,
+    recent: Vec<SharedStr>,
+}
+
+impl LocalInterner<'_> {
+    pub fn intern(&mut self, s: &str) -> SharedStr {
+        if let Some(shared) = self.recent.iter().find(|shared| shared.as_str() == s) {
+            return shared.clone();
+        }
+        let shared = self.shared.intern(s);
+        if self.recent.len() == RECENT_STRINGS {
+            self.recent.remove(0);
+        }
+        self.recent.push(shared.clone());
+        shared
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interned_strings_share_their_allocation() {
+        let interner = Interner::new();
+        let a = interner.intern("/projects/a.jsonl");
+        let b = interner.intern(&String::from("/projects/a.jsonl"));
+        assert!(a.ptr_eq(&b));
+        assert!(!a.ptr_eq(&interner.intern("/projects/b.jsonl")));
+
+        let mut first = interner.local();
+        let mut second = interner.local();
+        let role = first.intern("assistant");
+        assert!(role.ptr_eq(&first.intern("assistant")));
+        assert!(role.ptr_eq(&second.intern("assistant")));
+        for i in 0..RECENT_STRINGS * 2 {
+            first.intern(&i.to_string());
+        }
+        assert!(role.ptr_eq(&first.intern("assistant")));
+    }
+
+    #[test]
+    fn test_shared_str_reads_like_a_string() {
+        let role = SharedStr::from("user");
+        assert_eq!(role, "user");
+        assert!(role.starts_with('u'));
+        assert_eq!(format!("{role}|{role:?}"), "user|\"user\"");
+        assert_eq!(serde_json::to_string(&role).unwrap(), "\"user\"");
+        let parsed: SharedStr = serde_json::from_str("\"user\"").unwrap();
+        assert_eq!(parsed, role);
+    }
+}
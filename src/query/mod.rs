@@ -1,7 +1,11 @@
 pub mod condition;
 pub mod fast_lowercase;
+pub mod interner;
 pub mod parser;
 mod regex_cache;
+pub mod saved;
 
 pub use condition::*;
-pub use parser::parse_query;
+pub use interner::{Interner, SharedStr};
+pub use parser::{parse_query, parse_query_with};
+pub use saved::{SavedSearch, SavedSearches, SearchFilters};
@@ -8,34 +8,114 @@ use nom::{
     sequence::{delimited, preceded, terminated},
 };
 
-use super::condition::QueryCondition;
+use super::condition::{MessageKind, QueryCondition, SessionPosition, TextSection};
+use super::saved::{SavedSearches, is_saved_name_char, is_valid_saved_name};
+use crate::outcome::SessionOutcome;
+use crate::utils::time_range::{is_whole_day, parse_time, start_of_next_day};
 
 use anyhow::{Result, anyhow};
+use std::cell::RefCell;
 
+/// Parse a query, expanding `$name` references from the saved searches in
+/// the ccms config directory
 pub fn parse_query(input: &str) -> Result<QueryCondition> {
-    match query(input) {
+    // Only touch the config dir when the query could reference a saved search
+    let saved = match SavedSearches::default_path() {
+        Ok(path) if input.contains('$') => SavedSearches::load(&path)?,
+        _ => SavedSearches::default(),
+    };
+    parse_query_with(input, &saved)
+}
+
+/// Parse a query, expanding `$name` references from `saved`
+pub fn parse_query_with(input: &str, saved: &SavedSearches) -> Result<QueryCondition> {
+    parse_with_context(
+        input,
+        &ParseContext {
+            saved,
+            expanding: Vec::new(),
+            error: RefCell::new(None),
+        },
+    )
+}
+
+/// State shared by the parser functions while expanding saved searches
+struct ParseContext<'s> {
+    saved: &'s SavedSearches,
+    /// Saved searches being expanded, outermost first, to reject cycles
+    expanding: Vec<&'s str>,
+    /// Why a saved search reference failed; nom errors cannot carry it
+    error: RefCell<Option<anyhow::Error>>,
+}
+
+fn parse_with_context(input: &str, ctx: &ParseContext) -> Result<QueryCondition> {
+    let result = query(input, ctx);
+    if let Some(error) = ctx.error.take() {
+        return Err(error);
+    }
+
+    match result {
         Ok((remaining, condition)) => {
-            if remaining.trim().is_empty() {
-                Ok(condition)
-            } else {
+            if !remaining.trim().is_empty() {
                 Err(anyhow!("Unexpected input: '{remaining}'"))
+            } else if has_nested_session_filter(&condition) {
+                Err(anyhow!(
+                    "is:first, is:last, is:sidechain, is:meta and outcome: can only be combined with the rest of the query using AND"
+                ))
+            } else {
+                Ok(condition)
             }
         }
         Err(e) => Err(anyhow!("Parse error: {e:?}")),
     }
 }
 
-fn query(input: &str) -> IResult<&str, QueryCondition> {
-    or_expression(input)
+/// Whether a session or message kind filter (`is:first`, `outcome:error`,
+/// `is:meta`, ...) appears
+/// anywhere but in a chain of ANDs from the top of the query, where it could
+/// not narrow the whole result set
+fn has_nested_session_filter(condition: &QueryCondition) -> bool {
+    fn contains_session_filter(condition: &QueryCondition) -> bool {
+        match condition {
+            QueryCondition::Position { .. }
+            | QueryCondition::Kind { .. }
+            | QueryCondition::Outcome { .. } => true,
+            QueryCondition::Not { condition } | QueryCondition::Scoped { condition, .. } => {
+                contains_session_filter(condition)
+            }
+            QueryCondition::And { conditions } | QueryCondition::Or { conditions } => {
+                conditions.iter().any(contains_session_filter)
+            }
+            QueryCondition::Near { left, right, .. } => {
+                contains_session_filter(left) || contains_session_filter(right)
+            }
+            QueryCondition::Literal { .. }
+            | QueryCondition::Regex { .. }
+            | QueryCondition::After { .. }
+            | QueryCondition::Before { .. } => false,
+        }
+    }
+
+    match condition {
+        QueryCondition::Position { .. }
+        | QueryCondition::Kind { .. }
+        | QueryCondition::Outcome { .. } => false,
+        QueryCondition::And { conditions } => conditions.iter().any(has_nested_session_filter),
+        _ => contains_session_filter(condition),
+    }
 }
 
-fn or_expression(input: &str) -> IResult<&str, QueryCondition> {
-    let (input, first) = and_expression(input)?;
+fn query<'a>(input: &'a str, ctx: &ParseContext) -> IResult<&'a str, QueryCondition> {
+    or_expression(input, ctx)
+}
+
+fn or_expression<'a>(input: &'a str, ctx: &ParseContext) -> IResult<&'a str, QueryCondition> {
+    let (input, first) = and_expression(input, ctx)?;
 
     fold_many0(
         preceded(
             preceded(multispace0, tag("OR")),
-            preceded(multispace1, and_expression),
+            preceded(multispace1, |i| and_expression(i, ctx)),
         ),
         move || first.clone(),
         |acc, next| match acc {
@@ -51,13 +131,13 @@ fn or_expression(input: &str) -> IResult<&str, QueryCondition> {
     .parse(input)
 }
 
-fn and_expression(input: &str) -> IResult<&str, QueryCondition> {
-    let (input, first) = not_expression(input)?;
+fn and_expression<'a>(input: &'a str, ctx: &ParseContext) -> IResult<&'a str, QueryCondition> {
+    let (input, first) = not_expression(input, ctx)?;
 
     fold_many0(
         preceded(
             preceded(multispace0, tag("AND")),
-            preceded(multispace1, not_expression),
+            preceded(multispace1, |i| not_expression(i, ctx)),
         ),
         move || first.clone(),
         |acc, next| match acc {
@@ -73,22 +153,84 @@ fn and_expression(input: &str) -> IResult<&str, QueryCondition> {
     .parse(input)
 }
 
-fn not_expression(input: &str) -> IResult<&str, QueryCondition> {
+fn not_expression<'a>(input: &'a str, ctx: &ParseContext) -> IResult<&'a str, QueryCondition> {
     alt((
         map(
-            preceded(terminated(tag("NOT"), multispace1), primary_expression),
+            preceded(terminated(tag("NOT"), multispace1), |i| {
+                near_expression(i, ctx)
+            }),
             |condition| QueryCondition::Not {
                 condition: Box::new(condition),
             },
         ),
-        primary_expression,
+        |i| near_expression(i, ctx),
     ))
     .parse(input)
 }
 
-fn primary_expression(input: &str) -> IResult<&str, QueryCondition> {
+/// `TERM NEAR/N TERM`: both terms with at most N words between them, e.g.
+/// `"error" NEAR/5 "timeout"`. Binds tighter than `AND`; the terms are
+/// literals or regexes.
+fn near_expression<'a>(input: &'a str, ctx: &ParseContext) -> IResult<&'a str, QueryCondition> {
+    let (rest, left) = primary_expression(input, ctx)?;
+    let Ok((rest, distance)) = near_operator(rest) else {
+        return Ok((rest, left));
+    };
+    let (rest, right) = preceded(multispace1, |i| primary_expression(i, ctx)).parse(rest)?;
+
+    let is_term = |condition: &QueryCondition| {
+        matches!(
+            condition,
+            QueryCondition::Literal { .. } | QueryCondition::Regex { .. }
+        )
+    };
+    if !is_term(&left) || !is_term(&right) {
+        *ctx.error.borrow_mut() = Some(anyhow!(
+            "NEAR/{distance} can only join two terms, such as \"error\" NEAR/{distance} /time(d )?out/"
+        ));
+        return Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
+    Ok((
+        rest,
+        QueryCondition::Near {
+            left: Box::new(left),
+            right: Box::new(right),
+            distance,
+        },
+    ))
+}
+
+/// ` NEAR/N`, giving N
+fn near_operator(input: &str) -> IResult<&str, usize> {
+    let (rest, digits) = preceded(
+        multispace0,
+        preceded(tag(NEAR_PREFIX), take_while1(|c: char| c.is_ascii_digit())),
+    )
+    .parse(input)?;
+    match digits.parse() {
+        Ok(distance) if !rest.starts_with(is_unquoted_char) => Ok((rest, distance)),
+        _ => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Digit,
+        ))),
+    }
+}
+
+/// Start of the `NEAR/N` operator, which is never read as a literal
+const NEAR_PREFIX: &str = "NEAR/";
+
+fn primary_expression<'a>(input: &'a str, ctx: &ParseContext) -> IResult<&'a str, QueryCondition> {
     alt((
-        preceded(multispace0, parenthesized_expression),
+        preceded(multispace0, |i| parenthesized_expression(i, ctx)),
+        preceded(multispace0, |i| saved_reference(i, ctx)),
+        preceded(multispace0, position_expression),
+        preceded(multispace0, kind_expression),
+        preceded(multispace0, |i| outcome_expression(i, ctx)),
+        preceded(multispace0, |i| time_expression(i, ctx)),
+        preceded(multispace0, |i| scoped_expression(i, ctx)),
         preceded(multispace0, regex_expression),
         preceded(multispace0, quoted_literal),
         preceded(multispace0, unquoted_literal),
@@ -96,15 +238,194 @@ fn primary_expression(input: &str) -> IResult<&str, QueryCondition> {
     .parse(input)
 }
 
-fn parenthesized_expression(input: &str) -> IResult<&str, QueryCondition> {
+fn parenthesized_expression<'a>(
+    input: &'a str,
+    ctx: &ParseContext,
+) -> IResult<&'a str, QueryCondition> {
     delimited(
         char('('),
-        preceded(multispace0, query),
+        preceded(multispace0, |i| query(i, ctx)),
         preceded(multispace0, char(')')),
     )
     .parse(input)
 }
 
+/// `thinking:TERM`, `tool_result:TERM` or `images:TERM` matches TERM against
+/// that section of a message only, e.g. `tool_result:src/main.rs`,
+/// `thinking:(rollback OR revert)` or `images:png`
+fn scoped_expression<'a>(input: &'a str, ctx: &ParseContext) -> IResult<&'a str, QueryCondition> {
+    let section = alt((
+        map(tag(TextSection::Thinking.prefix()), |_| {
+            TextSection::Thinking
+        }),
+        map(tag(TextSection::ToolResult.prefix()), |_| {
+            TextSection::ToolResult
+        }),
+        map(tag(TextSection::Images.prefix()), |_| TextSection::Images),
+    ));
+    map(
+        (section, |i| primary_expression(i, ctx)),
+        |(section, condition)| QueryCondition::Scoped {
+            section,
+            condition: Box::new(condition),
+        },
+    )
+    .parse(input)
+}
+
+/// `is:first` or `is:last`; words merely starting with them stay literals
+fn position_expression(input: &str) -> IResult<&str, QueryCondition> {
+    let (rest, position) = alt((
+        map(tag(SessionPosition::First.token()), |_| {
+            SessionPosition::First
+        }),
+        map(tag(SessionPosition::Last.token()), |_| {
+            SessionPosition::Last
+        }),
+    ))
+    .parse(input)?;
+    if rest.starts_with(is_unquoted_char) {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Tag,
+        )));
+    }
+    Ok((rest, QueryCondition::Position { position }))
+}
+
+/// `is:sidechain` or `is:meta`; words merely starting with them stay literals
+fn kind_expression(input: &str) -> IResult<&str, QueryCondition> {
+    let (rest, kind) = alt((
+        map(tag(MessageKind::Sidechain.token()), |_| {
+            MessageKind::Sidechain
+        }),
+        map(tag(MessageKind::Meta.token()), |_| MessageKind::Meta),
+    ))
+    .parse(input)?;
+    if rest.starts_with(is_unquoted_char) {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Tag,
+        )));
+    }
+    Ok((rest, QueryCondition::Kind { kind }))
+}
+
+/// `outcome:completed`, `outcome:abandoned` or `outcome:error`
+fn outcome_expression<'a>(input: &'a str, ctx: &ParseContext) -> IResult<&'a str, QueryCondition> {
+    let (rest, name) = preceded(tag("outcome:"), take_while1(is_unquoted_char)).parse(input)?;
+    let Some(outcome) = SessionOutcome::from_name(name) else {
+        let names: Vec<&str> = SessionOutcome::ALL.iter().map(|o| o.name()).collect();
+        *ctx.error.borrow_mut() = Some(anyhow!(
+            "Unknown outcome '{name}' (expected {}; quote it to search for the text)",
+            names.join(", ")
+        ));
+        return Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )));
+    };
+    Ok((rest, QueryCondition::Outcome { outcome }))
+}
+
+/// `after:TIME`, `since:TIME` or `before:TIME`, with TIME anything
+/// `--after` accepts, quoted when it has spaces: `since:"2 days ago"`
+fn time_expression<'a>(input: &'a str, ctx: &ParseContext) -> IResult<&'a str, QueryCondition> {
+    let (rest, (key, value)) = (
+        alt((tag("after:"), tag("since:"), tag("before:"))),
+        alt((
+            double_quoted_string,
+            single_quoted_string,
+            map(take_while1(is_unquoted_char), str::to_string),
+        )),
+    )
+        .parse(input)?;
+    let before = key == "before:";
+    // A date-only `before:` covers that whole day, as the end of `--when` does
+    let exclusive = before && is_whole_day(&value);
+    let bound = parse_time(&value).and_then(|time| {
+        if exclusive {
+            start_of_next_day(time)
+        } else {
+            Ok(time)
+        }
+    });
+    let timestamp = match bound {
+        Ok(time) => time.to_rfc3339(),
+        Err(error) => {
+            *ctx.error.borrow_mut() = Some(anyhow!(
+                "Invalid time in '{key}{value}': {error} (quote the term to search for the text)"
+            ));
+            return Err(nom::Err::Failure(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::Verify,
+            )));
+        }
+    };
+    let condition = if before {
+        QueryCondition::Before {
+            timestamp,
+            exclusive,
+        }
+    } else {
+        QueryCondition::After { timestamp }
+    };
+    Ok((rest, condition))
+}
+
+/// `$name` expands to the parsed query saved as `name`. Words such as `$5`
+/// that cannot be a name are left to `unquoted_literal`.
+fn saved_reference<'a>(input: &'a str, ctx: &ParseContext) -> IResult<&'a str, QueryCondition> {
+    let (rest, name) = preceded(char('$'), take_while1(is_saved_name_char)).parse(input)?;
+    if !is_valid_saved_name(name) || rest.starts_with(is_unquoted_char) {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
+
+    let fail = |error: anyhow::Error| {
+        *ctx.error.borrow_mut() = Some(error);
+        Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )))
+    };
+
+    let Some((name, saved)) = ctx.saved.searches.get_key_value(name) else {
+        return fail(anyhow!(
+            "Unknown saved search '${name}' (quote it to search for the text)"
+        ));
+    };
+    if ctx.expanding.contains(&name.as_str()) {
+        let chain: Vec<String> = ctx
+            .expanding
+            .iter()
+            .chain([&name.as_str()])
+            .map(|n| format!("${n}"))
+            .collect();
+        return fail(anyhow!(
+            "Saved search '${name}' refers to itself: {}",
+            chain.join(" -> ")
+        ));
+    }
+
+    let mut expanding = ctx.expanding.clone();
+    expanding.push(name);
+    let nested = ParseContext {
+        saved: ctx.saved,
+        expanding,
+        error: RefCell::new(None),
+    };
+    match parse_with_context(&saved.query, &nested) {
+        Ok(condition) => Ok((rest, condition)),
+        Err(error) if ctx.expanding.is_empty() => {
+            fail(error.context(format!("in saved search '${name}'")))
+        }
+        Err(error) => fail(error),
+    }
+}
+
 fn regex_expression(input: &str) -> IResult<&str, QueryCondition> {
     let (input, _) = char('/')(input)?;
     let (input, pattern) = regex_pattern(input)?;
@@ -231,7 +552,7 @@ fn unquoted_literal(input: &str) -> IResult<&str, QueryCondition> {
     let (_, word) = take_while1(is_unquoted_char)(input)?;
 
     // Check if it's a keyword
-    if matches!(word, "AND" | "OR" | "NOT") {
+    if matches!(word, "AND" | "OR" | "NOT") || word.starts_with(NEAR_PREFIX) {
         return Err(nom::Err::Error(nom::error::Error::new(
             input,
             nom::error::ErrorKind::Tag,
@@ -241,13 +562,47 @@ fn unquoted_literal(input: &str) -> IResult<&str, QueryCondition> {
     // If not a keyword, consume it
     let (input, word) = take_while1(is_unquoted_char)(input)?;
 
-    Ok((
-        input,
-        QueryCondition::Literal {
+    Ok((input, word_condition(word)))
+}
+
+/// An unquoted word: a glob when it has a `*` wildcard, so `deploy*` matches
+/// words starting with "deploy" and `*.rs` paths ending in ".rs"; quoting
+/// the word searches for a literal `*`
+fn word_condition(word: &str) -> QueryCondition {
+    if !word.contains('*') || word.chars().all(|c| c == '*') {
+        return QueryCondition::Literal {
             pattern: word.to_string(),
             case_sensitive: false,
-        },
-    ))
+        };
+    }
+    QueryCondition::Regex {
+        pattern: glob_regex(word),
+        flags: "i".to_string(),
+    }
+}
+
+/// What a `*` of a glob stands for: anything but whitespace, quotes,
+/// brackets and separators, so that it stays within one word or path
+const GLOB_WILDCARD: &str = r#"[^\s"'`()\[\]{}<>,;]*"#;
+
+/// `glob` as a regex anchored at word boundaries where it starts or ends
+/// with a word character, with everything but `*` matched literally
+fn glob_regex(glob: &str) -> String {
+    let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let mut pattern = String::new();
+    if is_word_char(glob.chars().next()) {
+        pattern.push_str(r"\b");
+    }
+    for (i, part) in glob.split('*').enumerate() {
+        if i > 0 {
+            pattern.push_str(GLOB_WILDCARD);
+        }
+        pattern.push_str(&regex::escape(part));
+    }
+    if is_word_char(glob.chars().next_back()) {
+        pattern.push_str(r"\b");
+    }
+    pattern
 }
 
 fn is_unquoted_char(c: char) -> bool {
@@ -673,4 +1028,310 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_glob_words() -> Result<()> {
+        let prefix = parse_query("deploy*")?;
+        assert!(prefix.evaluate("Deploying to staging")?);
+        assert!(prefix.evaluate("ran `deploy.sh` again")?);
+        assert!(!prefix.evaluate("redeploy it")?);
+
+        let suffix = parse_query("*.rs")?;
+        assert!(suffix.evaluate("edited src/main.rs, then")?);
+        assert!(suffix.evaluate("see `lib.rs`")?);
+        assert!(!suffix.evaluate("a main.rsx file")?);
+        assert!(!suffix.evaluate("nothing to match.")?);
+
+        let infix = parse_query("test_*_ok")?;
+        assert!(infix.evaluate("test_parse_ok passed")?);
+        assert!(!infix.evaluate("test_parse_ok_slow passed")?);
+        // The wildcard does not reach across words
+        assert!(!infix.evaluate("test_ one _ok")?);
+
+        // Quoted words and lone stars stay literal
+        for query in ["\"deploy*\"", "*"] {
+            assert!(matches!(
+                parse_query(query)?,
+                QueryCondition::Literal { .. }
+            ));
+        }
+        assert!(parse_query("\"a*\"")?.evaluate("a* b")?);
+        Ok(())
+    }
+
+    fn saved_searches() -> SavedSearches {
+        let mut saved = SavedSearches::default();
+        saved
+            .searches
+            .insert("failures".to_string(), "error OR panic".to_string().into());
+        saved.searches.insert(
+            "noisy".to_string(),
+            "$failures AND NOT test".to_string().into(),
+        );
+        saved
+    }
+
+    #[test]
+    fn test_saved_search_reference() -> Result<()> {
+        let saved = saved_searches();
+        assert_eq!(
+            parse_query_with("$failures AND retry", &saved)?,
+            parse_query("(error OR panic) AND retry")?
+        );
+        assert_eq!(
+            parse_query_with("NOT $noisy", &saved)?,
+            parse_query("NOT ((error OR panic) AND NOT test)")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_saved_search_reference_errors() {
+        let saved = saved_searches();
+        let err = parse_query_with("$missing AND retry", &saved).unwrap_err();
+        assert!(err.to_string().contains("Unknown saved search '$missing'"));
+
+        let mut looping = saved_searches();
+        looping
+            .searches
+            .insert("failures".to_string(), "$noisy".to_string().into());
+        let err = parse_query_with("$noisy", &looping).unwrap_err();
+        assert!(format!("{err:#}").contains("$noisy -> $failures -> $noisy"));
+    }
+
+    #[test]
+    fn test_scoped_expressions() -> Result<()> {
+        let scoped = |inner: &str| -> Result<QueryCondition> {
+            Ok(QueryCondition::Scoped {
+                section: TextSection::ToolResult,
+                condition: Box::new(parse_query(inner)?),
+            })
+        };
+        assert_eq!(
+            parse_query("tool_result:src/main.rs")?,
+            scoped("src/main.rs")?
+        );
+        assert_eq!(
+            parse_query("tool_result:(deploy OR rollback)")?,
+            scoped("(deploy OR rollback)")?
+        );
+        assert!(matches!(
+            parse_query("tool_result:/\\.rs$/i")?,
+            QueryCondition::Scoped { condition, .. }
+                if matches!(*condition, QueryCondition::Regex { .. })
+        ));
+        match parse_query("edit AND NOT tool_result:\"a b\"")? {
+            QueryCondition::And { conditions } => {
+                assert_eq!(conditions.len(), 2);
+                assert_eq!(
+                    conditions[1],
+                    QueryCondition::Not {
+                        condition: Box::new(scoped("\"a b\"")?)
+                    }
+                );
+            }
+            other => panic!("Expected AND, got {other:?}"),
+        }
+        assert_eq!(
+            parse_query("thinking:rollback AND deploy")?,
+            QueryCondition::And {
+                conditions: vec![
+                    QueryCondition::Scoped {
+                        section: TextSection::Thinking,
+                        condition: Box::new(parse_query("rollback")?),
+                    },
+                    parse_query("deploy")?,
+                ]
+            }
+        );
+        assert_eq!(
+            parse_query("images:image/png")?,
+            QueryCondition::Scoped {
+                section: TextSection::Images,
+                condition: Box::new(parse_query("image/png")?),
+            }
+        );
+        // Without a term the prefix is just a word
+        assert_eq!(
+            parse_query("tool_result:")?,
+            QueryCondition::Literal {
+                pattern: "tool_result:".to_string(),
+                case_sensitive: false,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_position_terms() -> Result<()> {
+        let first = QueryCondition::Position {
+            position: SessionPosition::First,
+        };
+        assert_eq!(parse_query("is:first")?, first);
+        assert_eq!(
+            parse_query("deploy AND (is:last AND is:first)")?.positions(),
+            vec![SessionPosition::Last, SessionPosition::First]
+        );
+        // Words merely starting with the token stay literal
+        assert_eq!(
+            parse_query("is:firstly")?,
+            QueryCondition::Literal {
+                pattern: "is:firstly".to_string(),
+                case_sensitive: false,
+            }
+        );
+        for query in ["deploy OR is:first", "NOT is:last", "thinking:is:first"] {
+            assert!(parse_query(query).is_err(), "{query}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_kind_terms() -> Result<()> {
+        assert_eq!(
+            parse_query("is:meta")?,
+            QueryCondition::Kind {
+                kind: MessageKind::Meta
+            }
+        );
+        assert_eq!(
+            parse_query("deploy AND is:sidechain AND is:meta")?.kinds(),
+            vec![MessageKind::Sidechain, MessageKind::Meta]
+        );
+        assert_eq!(
+            parse_query("is:metadata")?,
+            QueryCondition::Literal {
+                pattern: "is:metadata".to_string(),
+                case_sensitive: false,
+            }
+        );
+        for query in ["deploy OR is:sidechain", "NOT is:meta"] {
+            assert!(parse_query(query).is_err(), "{query}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_outcome_terms() -> Result<()> {
+        assert_eq!(
+            parse_query("build AND outcome:error")?.outcomes(),
+            vec![SessionOutcome::Error]
+        );
+        let error = parse_query("outcome:done").unwrap_err().to_string();
+        assert!(error.contains("Unknown outcome 'done'"), "{error}");
+        assert!(parse_query("outcome:error OR deploy").is_err());
+        // Quoted, it is just text
+        assert_eq!(
+            parse_query("\"outcome:done\"")?,
+            QueryCondition::Literal {
+                pattern: "outcome:done".to_string(),
+                case_sensitive: false,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_terms() -> Result<()> {
+        assert_eq!(
+            parse_query("after:\"2024-05-01T00:00:00Z\" AND before:2024-05-31T00:00:00Z")?,
+            QueryCondition::And {
+                conditions: vec![
+                    QueryCondition::After {
+                        timestamp: "2024-05-01T00:00:00+00:00".to_string(),
+                    },
+                    QueryCondition::Before {
+                        timestamp: "2024-05-31T00:00:00+00:00".to_string(),
+                        exclusive: false,
+                    },
+                ],
+            }
+        );
+        assert!(matches!(
+            parse_query("since:'2 days ago'")?,
+            QueryCondition::After { .. }
+        ));
+        // A date alone keeps the whole day, as the end of `--when` does
+        let in_may = parse_query("after:\"2024-05-01\" AND before:\"2024-05-31\"")?;
+        assert!(in_may.evaluate_at("any", Some("2024-05-31T12:00:00Z"))?);
+        assert!(!in_may.evaluate_at("any", Some("2024-06-01T12:00:00Z"))?);
+        assert!(matches!(
+            parse_query("before:2024-05-31")?,
+            QueryCondition::Before {
+                exclusive: true,
+                ..
+            }
+        ));
+        // Unlike session filters, time terms nest anywhere
+        assert!(parse_query("NOT before:1700000000 OR (deploy AND since:1700000000)").is_ok());
+
+        let error = parse_query("after:someday").unwrap_err().to_string();
+        assert!(error.contains("Invalid time in 'after:someday'"), "{error}");
+        assert!(matches!(
+            parse_query("\"after:someday\"")?,
+            QueryCondition::Literal { .. }
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_near_terms() -> Result<()> {
+        let literal = |pattern: &str| QueryCondition::Literal {
+            pattern: pattern.to_string(),
+            case_sensitive: false,
+        };
+        assert_eq!(
+            parse_query("\"error\" NEAR/5 timeout")?,
+            QueryCondition::Near {
+                left: Box::new(literal("error")),
+                right: Box::new(literal("timeout")),
+                distance: 5,
+            }
+        );
+        // Binds tighter than AND and NOT
+        assert_eq!(
+            parse_query("deploy AND NOT error NEAR/3 /time(d )?out/i")?,
+            QueryCondition::And {
+                conditions: vec![
+                    literal("deploy"),
+                    QueryCondition::Not {
+                        condition: Box::new(QueryCondition::Near {
+                            left: Box::new(literal("error")),
+                            right: Box::new(QueryCondition::Regex {
+                                pattern: "time(d )?out".to_string(),
+                                flags: "i".to_string(),
+                            }),
+                            distance: 3,
+                        }),
+                    },
+                ],
+            }
+        );
+
+        let error = parse_query("(a OR b) NEAR/2 c").unwrap_err().to_string();
+        assert!(error.contains("NEAR/2 can only join two terms"), "{error}");
+        for query in ["error NEAR/ timeout", "error NEAR/x timeout", "NEAR/2"] {
+            assert!(parse_query(query).is_err(), "{query}");
+        }
+        // Quoted, it is just text
+        assert_eq!(parse_query("\"NEAR/2\"")?, literal("NEAR/2"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dollar_words_stay_literal() -> Result<()> {
+        let saved = saved_searches();
+        for query in ["$5", "$", "\"$failures\"", "$failures,"] {
+            let expected = query.trim_matches('"');
+            assert_eq!(
+                parse_query_with(query, &saved)?,
+                QueryCondition::Literal {
+                    pattern: expected.to_string(),
+                    case_sensitive: false,
+                },
+                "{query}"
+            );
+        }
+        Ok(())
+    }
 }
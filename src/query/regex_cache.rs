@@ -1,29 +1,59 @@
+//! Compiled regexes, kept across searches.
+//!
+//! Each search thread looks in its own small cache first, so repeated
+//! evaluation of the same pattern never touches a lock. Misses fall through
+//! to a cache shared by all threads, which lets a pattern compiled for one
+//! keystroke in the TUI (or one query in the daemon) serve every later search
+//! on any worker.
+
 use lru::LruCache;
 use regex::Regex;
+use std::cell::RefCell;
 use std::num::NonZeroUsize;
 use std::sync::{Mutex, OnceLock};
 
+const SHARED_CAPACITY: NonZeroUsize = NonZeroUsize::new(128).unwrap();
+const THREAD_CAPACITY: NonZeroUsize = NonZeroUsize::new(32).unwrap();
+
 static REGEX_CACHE: OnceLock<Mutex<LruCache<String, Regex>>> = OnceLock::new();
 
+thread_local! {
+    static THREAD_CACHE: RefCell<LruCache<String, Regex>> =
+        RefCell::new(LruCache::new(THREAD_CAPACITY));
+}
+
 fn get_cache() -> &'static Mutex<LruCache<String, Regex>> {
-    REGEX_CACHE.get_or_init(|| {
-        let capacity =
-            NonZeroUsize::new(128).expect("128 is a valid non-zero capacity for regex cache");
-        Mutex::new(LruCache::new(capacity))
-    })
+    REGEX_CACHE.get_or_init(|| Mutex::new(LruCache::new(SHARED_CAPACITY)))
 }
 
 pub fn get_or_compile_regex(pattern: &str, flags: &str) -> Result<Regex, regex::Error> {
     let cache_key = format!("{pattern}\0{flags}");
 
-    // Try to get from cache first
-    if let Ok(mut cache) = get_cache().try_lock()
-        && let Some(regex) = cache.get(&cache_key)
-    {
-        return Ok(regex.clone());
+    if let Some(regex) = THREAD_CACHE.with_borrow_mut(|cache| cache.get(&cache_key).cloned()) {
+        return Ok(regex);
     }
 
-    // Compile regex
+    // Regexes are cheap to clone, so the lock is only held for the lookup
+    let shared = get_cache()
+        .lock()
+        .ok()
+        .and_then(|mut cache| cache.get(&cache_key).cloned());
+    let regex = match shared {
+        Some(regex) => regex,
+        None => {
+            let regex = compile(pattern, flags)?;
+            if let Ok(mut cache) = get_cache().lock() {
+                cache.put(cache_key.clone(), regex.clone());
+            }
+            regex
+        }
+    };
+
+    THREAD_CACHE.with_borrow_mut(|cache| cache.put(cache_key, regex.clone()));
+    Ok(regex)
+}
+
+fn compile(pattern: &str, flags: &str) -> Result<Regex, regex::Error> {
     let mut regex_builder = regex::RegexBuilder::new(pattern);
 
     if flags.contains('i') {
@@ -36,14 +66,7 @@ pub fn get_or_compile_regex(pattern: &str, flags: &str) -> Result<Regex, regex::
         regex_builder.dot_matches_new_line(true);
     }
 
-    let regex = regex_builder.build()?;
-
-    // Try to cache it
-    if let Ok(mut cache) = get_cache().try_lock() {
-        cache.put(cache_key, regex.clone());
-    }
-
-    Ok(regex)
+    regex_builder.build()
 }
 
 #[cfg(test)]
@@ -65,6 +88,23 @@ mod tests {
         assert!(regex3.is_match("OTHER"));
     }
 
+    #[test]
+    fn test_regex_cache_is_shared_across_threads() {
+        let key = "shared-[0-9]+\0i";
+        std::thread::spawn(|| get_or_compile_regex("shared-[0-9]+", "i").unwrap())
+            .join()
+            .unwrap();
+
+        // Compiled on another thread: not in this thread's cache, but in
+        // the shared one
+        assert!(!THREAD_CACHE.with_borrow(|cache| cache.contains(key)));
+        assert!(get_cache().lock().unwrap().contains(key));
+
+        let regex = get_or_compile_regex("shared-[0-9]+", "i").unwrap();
+        assert!(regex.is_match("SHARED-42"));
+        assert!(THREAD_CACHE.with_borrow(|cache| cache.contains(key)));
+    }
+
     #[test]
     fn test_regex_flags() {
         let regex_i = get_or_compile_regex("test", "i").unwrap();
@@ -76,4 +116,10 @@ mod tests {
         let regex_s = get_or_compile_regex("a.b", "s").unwrap();
         assert!(regex_s.is_match("a\nb"));
     }
+
+    #[test]
+    fn test_invalid_regex_is_not_cached() {
+        assert!(get_or_compile_regex("(unclosed", "").is_err());
+        assert!(!get_cache().lock().unwrap().contains("(unclosed\0"));
+    }
 }
@@ -0,0 +1,292 @@
+use crate::utils::config::config_dir;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Named queries that other queries can reference as `$name`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SavedSearches {
+    pub searches: BTreeMap<String, SavedSearch>,
+}
+
+/// A saved query and the search flags saved with it by `ccms --save`.
+///
+/// The flags only apply when the search is run by name (`ccms run`, the
+/// interactive palette); a `$name` reference inside another query expands
+/// to the query alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StoredSearch", into = "StoredSearch")]
+pub struct SavedSearch {
+    pub query: String,
+    pub filters: SearchFilters,
+}
+
+/// The filter flags of a search that `--save` keeps
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchFilters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+impl SearchFilters {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The filters as the command-line flags they were saved from
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        for (flag, value) in [
+            ("--role", &self.role),
+            ("--since", &self.since),
+            ("--after", &self.after),
+            ("--before", &self.before),
+            ("--project", &self.project),
+        ] {
+            if let Some(value) = value {
+                args.push(flag.to_string());
+                args.push(value.clone());
+            }
+        }
+        args
+    }
+}
+
+impl SavedSearch {
+    /// What the interactive search box gets for the search saved as `name`:
+    /// `$name` and the time filters as `since:`/`after:`/`before:` terms.
+    /// The role goes to the role filter; the project is not applied.
+    pub fn interactive_query(&self, name: &str) -> String {
+        let mut query = format!("${name}");
+        for (key, value) in [
+            ("since", &self.filters.since),
+            ("after", &self.filters.after),
+            ("before", &self.filters.before),
+        ] {
+            if let Some(value) = value {
+                query.push_str(&format!(" AND {key}:\"{value}\""));
+            }
+        }
+        query
+    }
+}
+
+impl From<String> for SavedSearch {
+    fn from(query: String) -> Self {
+        Self {
+            query,
+            filters: SearchFilters::default(),
+        }
+    }
+}
+
+/// On disk a search without filters stays a plain query string, as saved
+/// searches were before they could carry filters
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredSearch {
+    Query(String),
+    Filtered {
+        query: String,
+        #[serde(flatten)]
+        filters: SearchFilters,
+    },
+}
+
+impl From<StoredSearch> for SavedSearch {
+    fn from(stored: StoredSearch) -> Self {
+        match stored {
+            StoredSearch::Query(query) => query.into(),
+            StoredSearch::Filtered { query, filters } => Self { query, filters },
+        }
+    }
+}
+
+impl From<SavedSearch> for StoredSearch {
+    fn from(search: SavedSearch) -> Self {
+        if search.filters.is_empty() {
+            StoredSearch::Query(search.query)
+        } else {
+            StoredSearch::Filtered {
+                query: search.query,
+                filters: search.filters,
+            }
+        }
+    }
+}
+
+/// Whether `name` can be written as a `$name` reference: a letter or `_`
+/// followed by letters, digits, `_` or `-`
+pub fn is_valid_saved_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(is_saved_name_char)
+}
+
+pub(crate) fn is_saved_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+impl SavedSearches {
+    /// `saved_searches.json` in the ccms config directory
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(config_dir()?.join("saved_searches.json"))
+    }
+
+    /// Load the saved searches, treating a missing file as empty
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read saved searches: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse saved searches: {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write saved searches: {}", path.display()))
+    }
+
+    /// The query saved as `name`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.searches.get(name).map(|search| search.query.as_str())
+    }
+
+    /// Save `query` as `name`, replacing any previous query of that name.
+    ///
+    /// The query is parsed first so broken or self-referencing searches are
+    /// rejected here rather than every time they are used.
+    pub fn insert(&mut self, name: &str, query: &str) -> Result<()> {
+        self.insert_with_filters(name, query, SearchFilters::default())
+    }
+
+    /// Save `query` with `filters` as `name`, like [`SavedSearches::insert`]
+    pub fn insert_with_filters(
+        &mut self,
+        name: &str,
+        query: &str,
+        filters: SearchFilters,
+    ) -> Result<()> {
+        if !is_valid_saved_name(name) {
+            bail!(
+                "invalid saved search name '{name}': use a letter or '_' followed by letters, digits, '_' or '-'"
+            );
+        }
+
+        let mut updated = self.clone();
+        updated.searches.insert(
+            name.to_string(),
+            SavedSearch {
+                query: query.to_string(),
+                filters,
+            },
+        );
+        super::parser::parse_query_with(&format!("${name}"), &updated)?;
+
+        *self = updated;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<SavedSearch> {
+        self.searches.remove(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_valid_names() {
+        assert!(is_valid_saved_name("failures"));
+        assert!(is_valid_saved_name("_tmp-2"));
+        assert!(!is_valid_saved_name("2fast"));
+        assert!(!is_valid_saved_name("has space"));
+        assert!(!is_valid_saved_name(""));
+    }
+
+    #[test]
+    fn test_insert_rejects_cycles() {
+        let mut saved = SavedSearches::default();
+        saved.insert("errors", "error OR panic").unwrap();
+        saved.insert("a", "$errors AND retry").unwrap();
+
+        let err = saved.insert("errors", "$a").unwrap_err();
+        assert!(format!("{err:#}").contains("$errors -> $a -> $errors"));
+        assert_eq!(saved.get("errors"), Some("error OR panic"));
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("saved_searches.json");
+        assert_eq!(
+            SavedSearches::load(&path).unwrap(),
+            SavedSearches::default()
+        );
+
+        let mut saved = SavedSearches::default();
+        saved.insert("failures", "/fail(ed|ure)/i").unwrap();
+        saved.save(&path).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains(r#""failures": "/fail(ed|ure)/i""#));
+        assert_eq!(SavedSearches::load(&path).unwrap(), saved);
+    }
+
+    #[test]
+    fn test_filters_roundtrip_and_args() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("saved_searches.json");
+
+        let mut saved = SavedSearches::default();
+        let filters = SearchFilters {
+            role: Some("assistant".to_string()),
+            since: Some("1 day ago".to_string()),
+            ..Default::default()
+        };
+        saved
+            .insert_with_filters("nightly-errors", "error", filters.clone())
+            .unwrap();
+        saved.insert("plain", "panic").unwrap();
+        saved.save(&path).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains(r#""plain": "panic""#));
+        assert!(content.contains(r#""since": "1 day ago""#));
+        let loaded = SavedSearches::load(&path).unwrap();
+        assert_eq!(loaded, saved);
+
+        let search = &loaded.searches["nightly-errors"];
+        assert_eq!(search.filters, filters);
+        assert_eq!(
+            search.filters.to_args(),
+            ["--role", "assistant", "--since", "1 day ago"]
+        );
+        assert_eq!(
+            search.interactive_query("nightly-errors"),
+            r#"$nightly-errors AND since:"1 day ago""#
+        );
+        assert_eq!(loaded.get("nightly-errors"), Some("error"));
+    }
+}
@@ -0,0 +1,357 @@
+//! `ccms recap`: what happened about a topic, one entry per session.
+//!
+//! The sessions a query matches are told in the order they started, each by
+//! its title, the prompt that brought the topic up and the assistant's last
+//! reply. Prompts are the messages typed by the user: meta messages, tool
+//! results, compaction summaries and subagent conversations are left out.
+
+use crate::query::SearchResult;
+use crate::schemas::SessionMessage;
+use crate::schemas::session_message::{Content, UserContent};
+use crate::search::compression::open_session;
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::io::BufRead;
+use std::path::Path;
+
+/// Longest prompt or conclusion kept, in characters
+const RECAP_MAX_CHARS: usize = 300;
+
+/// One session's part in the story of a query
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionRecap {
+    pub session_id: String,
+    pub file: String,
+    /// Working directory of the session
+    pub project: String,
+    pub started: String,
+    pub ended: String,
+    /// Number of matching messages in the session
+    pub matches: usize,
+    /// Title Claude gave the session, if it has one
+    pub title: Option<String>,
+    /// The first matching prompt, or the prompt the first match answers
+    pub prompt: Option<String>,
+    /// The last thing the assistant said
+    pub conclusion: Option<String>,
+}
+
+/// Recap every session with a match in `results`, earliest session first
+pub fn recap_sessions(results: &[SearchResult]) -> Result<Vec<SessionRecap>> {
+    let mut matched: HashMap<&str, Vec<&SearchResult>> = HashMap::new();
+    for result in results {
+        matched
+            .entry(result.file.as_str())
+            .or_default()
+            .push(result);
+    }
+
+    let mut recaps: Vec<SessionRecap> = matched
+        .into_par_iter()
+        .map(|(file, matches)| recap_file(Path::new(file), &matches))
+        .collect::<Result<_>>()?;
+    recaps.sort_by(|a, b| {
+        a.started
+            .cmp(&b.started)
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+    Ok(recaps)
+}
+
+fn recap_file(file: &Path, matches: &[&SearchResult]) -> Result<SessionRecap> {
+    let matched_lines: BTreeSet<usize> = matches
+        .iter()
+        .filter_map(|result| result.line_number)
+        .collect();
+    let first_match = matched_lines.first().copied().unwrap_or(0);
+
+    let mut title = None;
+    let mut started = String::new();
+    let mut ended = String::new();
+    // (line, text) of each prompt up to the first matching one
+    let mut prompts: Vec<(usize, String)> = Vec::new();
+    let mut matching_prompt = None;
+    let mut conclusion = None;
+
+    for (index, line) in open_session(file)?.lines().enumerate() {
+        let line_number = index + 1;
+        let Ok(message) = serde_json::from_str::<SessionMessage>(&line?) else {
+            continue;
+        };
+        if let SessionMessage::Summary { summary, .. } = &message {
+            title.get_or_insert_with(|| condense(summary));
+            continue;
+        }
+        if let Some(timestamp) = message.get_timestamp() {
+            if started.is_empty() {
+                started = timestamp.to_string();
+            }
+            ended = timestamp.to_string();
+        }
+        if message.is_sidechain() || message.is_meta() {
+            continue;
+        }
+
+        if matching_prompt.is_none()
+            && let Some(text) = prompt_text(&message)
+        {
+            if matched_lines.contains(&line_number) {
+                matching_prompt = Some(text);
+            } else {
+                prompts.push((line_number, text));
+            }
+        } else if let Some(text) = reply_text(&message) {
+            conclusion = Some(text);
+        }
+    }
+
+    let prompt = matching_prompt.or_else(|| {
+        let before = prompts.iter().rposition(|(line, _)| *line <= first_match);
+        before
+            .or((!prompts.is_empty()).then_some(0))
+            .map(|position| prompts.swap_remove(position).1)
+    });
+
+    let first = matches[0];
+    Ok(SessionRecap {
+        session_id: first.session_id.to_string(),
+        file: first.file.to_string(),
+        project: first.cwd.to_string(),
+        started,
+        ended,
+        matches: matches.len(),
+        title,
+        prompt: prompt.map(|text| condense(&text)),
+        conclusion: conclusion.map(|text| condense(&text)),
+    })
+}
+
+/// Text the user typed, for a prompt
+fn prompt_text(message: &SessionMessage) -> Option<String> {
+    let SessionMessage::User {
+        message: content,
+        is_compact_summary,
+        ..
+    } = message
+    else {
+        return None;
+    };
+    if *is_compact_summary == Some(true) {
+        return None;
+    }
+    let text = match &content.content {
+        UserContent::String(text) => text.clone(),
+        UserContent::Array(items) => join_text(items),
+    };
+    // Slash commands are logged wrapped in tags
+    let text = text.trim();
+    (!text.is_empty() && !text.starts_with('<')).then(|| text.to_string())
+}
+
+/// Text of an assistant reply, leaving out tool calls and thinking
+pub(crate) fn reply_text(message: &SessionMessage) -> Option<String> {
+    let SessionMessage::Assistant {
+        message,
+        is_api_error_message,
+        ..
+    } = message
+    else {
+        return None;
+    };
+    if *is_api_error_message == Some(true) {
+        return None;
+    }
+    let text = join_text(&message.content);
+    (!text.trim().is_empty()).then_some(text)
+}
+
+fn join_text(items: &[Content]) -> String {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Content::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `text` on one line, cut at `RECAP_MAX_CHARS`
+fn condense(text: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > RECAP_MAX_CHARS {
+        format!(
+            "{}…",
+            line.chars().take(RECAP_MAX_CHARS).collect::<String>()
+        )
+    } else {
+        line
+    }
+}
+
+pub fn format_recap(recaps: &[SessionRecap], use_color: bool) -> String {
+    use colored::Colorize;
+
+    let mut output = String::new();
+
+    for recap in recaps {
+        let header = format!(
+            "{}  {} ({} {})",
+            format_timestamp(&recap.started),
+            recap
+                .title
+                .as_deref()
+                .or(recap.prompt.as_deref())
+                .unwrap_or("(untitled)"),
+            recap.matches,
+            if recap.matches == 1 {
+                "match"
+            } else {
+                "matches"
+            }
+        );
+        let details = format!("  Session {} | {}", recap.session_id, recap.project);
+
+        if use_color {
+            output.push_str(&header.bright_blue().bold().to_string());
+            output.push('\n');
+            output.push_str(&details.dimmed().to_string());
+        } else {
+            output.push_str(&header);
+            output.push('\n');
+            output.push_str(&details);
+        }
+        output.push('\n');
+
+        for (label, text) in [
+            ("Asked:   ", &recap.prompt),
+            ("Outcome: ", &recap.conclusion),
+        ] {
+            if let Some(text) = text {
+                if use_color {
+                    output.push_str(&format!("  {} {text}\n", label.green()));
+                } else {
+                    output.push_str(&format!("  {label} {text}\n"));
+                }
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+fn format_timestamp(timestamp: &str) -> String {
+    use chrono::{DateTime, Local, TimeZone};
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(timestamp) {
+        let local_dt = Local.from_utc_datetime(&dt.naive_utc());
+        local_dt.format("%Y-%m-%d %H:%M").to_string()
+    } else {
+        timestamp.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::QueryCondition;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn user(uuid: &str, time: &str, content: &str) -> String {
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":{content}}},"uuid":"{uuid}","timestamp":"2024-01-01T{time}:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/work","version":"1"}}"#
+        )
+    }
+
+    fn assistant(uuid: &str, time: &str, text: &str) -> String {
+        format!(
+            r#"{{"type":"assistant","message":{{"id":"m{uuid}","type":"message","role":"assistant","model":"claude","content":[{{"type":"text","text":"{text}"}}],"stop_reason":"end_turn","stop_sequence":null,"usage":{{"input_tokens":1,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":1}}}},"uuid":"{uuid}","timestamp":"2024-01-01T{time}:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/work","version":"1"}}"#
+        )
+    }
+
+    fn result(file: &Path, line_number: usize) -> SearchResult {
+        SearchResult {
+            file: file.display().to_string().into(),
+            uuid: format!("line{line_number}"),
+            timestamp: String::new(),
+            session_id: "s1".into(),
+            role: "assistant".into(),
+            text: String::new(),
+            message_type: "assistant".into(),
+            query: QueryCondition::Literal {
+                pattern: "cache".to_string(),
+                case_sensitive: false,
+            },
+            cwd: "/work".into(),
+            raw_json: None,
+            line_number: Some(line_number),
+            matched_in: Vec::new(),
+            has_images: false,
+        }
+    }
+
+    #[test]
+    fn test_recap_sessions() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("session.jsonl");
+        let mut file = File::create(&path)?;
+        for line in [
+            r#"{"type":"summary","summary":"Speed up   the build","leafUuid":"a3"}"#.to_string(),
+            user("u1", "10:00", r#""<command-name>/clear</command-name>""#),
+            user("u2", "10:01", r#""Why is CI slow?""#),
+            assistant("a1", "10:02", "The cache is never restored."),
+            user(
+                "u3",
+                "10:03",
+                r#"[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]"#,
+            ),
+            user("u4", "10:04", r#"[{"type":"text","text":"Fix it then"}]"#),
+            assistant(
+                "a3",
+                "10:05",
+                "Restored the cache key; builds take 2 minutes now.",
+            ),
+        ] {
+            writeln!(file, "{line}")?;
+        }
+
+        // The prompt the first match answers
+        let recaps = recap_sessions(&[result(&path, 7), result(&path, 4)])?;
+        assert_eq!(
+            recaps,
+            vec![SessionRecap {
+                session_id: "s1".to_string(),
+                file: path.display().to_string(),
+                project: "/work".to_string(),
+                started: "2024-01-01T10:00:00Z".to_string(),
+                ended: "2024-01-01T10:05:00Z".to_string(),
+                matches: 2,
+                title: Some("Speed up the build".to_string()),
+                prompt: Some("Why is CI slow?".to_string()),
+                conclusion: Some("Restored the cache key; builds take 2 minutes now.".to_string()),
+            }]
+        );
+
+        // A matching prompt is preferred
+        let recaps = recap_sessions(&[result(&path, 4), result(&path, 6)])?;
+        assert_eq!(recaps[0].prompt.as_deref(), Some("Fix it then"));
+
+        let text = format_recap(&recaps, false);
+        assert!(text.contains("Speed up the build (2 matches)\n  Session s1 | /work\n"));
+        assert!(text.contains("  Asked:    Fix it then\n"));
+        assert!(text.contains("  Outcome:  Restored the cache key"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_condense() {
+        assert_eq!(condense("a\n\n  b"), "a b");
+        let long = "x".repeat(RECAP_MAX_CHARS + 5);
+        assert_eq!(condense(&long).chars().count(), RECAP_MAX_CHARS + 1);
+    }
+}
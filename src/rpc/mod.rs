@@ -0,0 +1,407 @@
+//! `ccms lsp-like --stdio`: a small JSON-RPC server for editor extensions.
+//!
+//! See `docs/json-rpc.md` for the methods, their parameters and results.
+
+pub mod protocol;
+
+use crate::query::{QueryCondition, SearchOptions};
+use crate::service::{SearchParams, Service};
+use crate::tail::Tail;
+use anyhow::Result;
+use protocol::{
+    INTERNAL_ERROR, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR, RpcError,
+    error_response, notification, read_message, response, write_message,
+};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How often watched files are checked for appended messages
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Methods answered by the server, reported by `initialize`
+pub const METHODS: &[&str] = &[
+    "initialize",
+    "search",
+    "getSession",
+    "getMessage",
+    "watch",
+    "unwatch",
+    "shutdown",
+];
+
+struct Watch {
+    query: QueryCondition,
+    options: SearchOptions,
+    tail: Tail,
+}
+
+type Watches = Arc<Mutex<HashMap<u64, Watch>>>;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionParams {
+    session_id: String,
+}
+
+#[derive(Deserialize)]
+struct MessageParams {
+    uuid: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UnwatchParams {
+    watch_id: u64,
+}
+
+pub struct RpcServer<W: Write + Send + 'static> {
+    service: Arc<Service>,
+    output: Arc<Mutex<W>>,
+    watches: Watches,
+    next_watch_id: u64,
+    exit_requested: bool,
+}
+
+impl<W: Write + Send + 'static> RpcServer<W> {
+    pub fn new(service: Service, output: W) -> Self {
+        Self {
+            service: Arc::new(service),
+            output: Arc::new(Mutex::new(output)),
+            watches: Arc::default(),
+            next_watch_id: 1,
+            exit_requested: false,
+        }
+    }
+
+    /// Serve requests from `input` until it closes or `exit` is received
+    pub fn run(mut self, mut input: impl BufRead) -> Result<()> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let poller = {
+            let stop = Arc::clone(&stop);
+            let service = Arc::clone(&self.service);
+            let watches = Arc::clone(&self.watches);
+            let output = Arc::clone(&self.output);
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    thread::sleep(WATCH_POLL_INTERVAL);
+                    for message in poll_watches(&service, &watches) {
+                        let mut output = output.lock().unwrap();
+                        if write_message(&mut *output, &message).is_err() {
+                            return;
+                        }
+                    }
+                }
+            })
+        };
+
+        let result = self.serve(&mut input);
+        stop.store(true, Ordering::Relaxed);
+        let _ = poller.join();
+        result
+    }
+
+    fn serve(&mut self, input: &mut impl BufRead) -> Result<()> {
+        while let Some(body) = read_message(input)? {
+            let reply = match serde_json::from_slice::<Value>(&body) {
+                Ok(message) => self.handle(message),
+                Err(e) => Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, e.to_string()),
+                )),
+            };
+            if let Some(reply) = reply {
+                write_message(&mut *self.output.lock().unwrap(), &reply)?;
+            }
+            if self.exit_requested {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one decoded message, returning the response for requests and
+    /// `None` for notifications
+    pub fn handle(&mut self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return Some(error_response(
+                id.unwrap_or(Value::Null),
+                RpcError::new(INVALID_REQUEST, "missing method"),
+            ));
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = self.dispatch(method, params);
+        let id = id?;
+        Some(match result {
+            Ok(result) => response(id, result),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn dispatch(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(json!({
+                "serverInfo": { "name": "ccms", "version": env!("CARGO_PKG_VERSION") },
+                "capabilities": { "methods": METHODS },
+            })),
+            "search" => {
+                let params: SearchParams = parse_params(params)?;
+                let page = self.service.search(&params).map_err(internal_error)?;
+                to_value(page)
+            }
+            "getSession" => {
+                let params: SessionParams = parse_params(params)?;
+                let messages = self
+                    .service
+                    .get_session(&params.session_id)
+                    .map_err(internal_error)?;
+                Ok(json!({ "messages": messages }))
+            }
+            "getMessage" => {
+                let params: MessageParams = parse_params(params)?;
+                let message = self
+                    .service
+                    .get_message(&params.uuid)
+                    .map_err(internal_error)?;
+                to_value(message)
+            }
+            "watch" => {
+                let params: SearchParams = parse_params(params)?;
+                let watch_id = self.add_watch(&params)?;
+                Ok(json!({ "watchId": watch_id }))
+            }
+            "unwatch" => {
+                let params: UnwatchParams = parse_params(params)?;
+                let removed = self
+                    .watches
+                    .lock()
+                    .unwrap()
+                    .remove(&params.watch_id)
+                    .is_some();
+                Ok(json!({ "removed": removed }))
+            }
+            "shutdown" => {
+                self.watches.lock().unwrap().clear();
+                Ok(Value::Null)
+            }
+            "exit" => {
+                self.exit_requested = true;
+                Ok(Value::Null)
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method: {method}"),
+            )),
+        }
+    }
+
+    fn add_watch(&mut self, params: &SearchParams) -> Result<u64, RpcError> {
+        let query = self
+            .service
+            .parse(&params.query)
+            .map_err(|e| RpcError::new(INVALID_PARAMS, format!("{e:#}")))?;
+        let files = self.service.files().map_err(internal_error)?;
+
+        let watch_id = self.next_watch_id;
+        self.next_watch_id += 1;
+        self.watches.lock().unwrap().insert(
+            watch_id,
+            Watch {
+                query,
+                options: self.service.options_for(params),
+                tail: Tail::from_end(&files),
+            },
+        );
+        Ok(watch_id)
+    }
+
+    #[cfg(test)]
+    fn poll_watches(&self) -> Vec<Value> {
+        poll_watches(&self.service, &self.watches)
+    }
+}
+
+/// Check every watch for newly appended matches and build their notifications
+fn poll_watches(service: &Service, watches: &Watches) -> Vec<Value> {
+    let mut watches = watches.lock().unwrap();
+    if watches.is_empty() {
+        return Vec::new();
+    }
+    let Ok(files) = service.files() else {
+        return Vec::new();
+    };
+
+    let mut notifications = Vec::new();
+    for (watch_id, watch) in watches.iter_mut() {
+        let Ok(results) = watch.tail.poll(&files, &watch.query, &watch.options) else {
+            continue;
+        };
+        notifications.extend(results.into_iter().map(|message| {
+            notification(
+                "watch/message",
+                json!({ "watchId": watch_id, "message": message }),
+            )
+        }));
+    }
+    notifications
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn to_value(value: impl serde::Serialize) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))
+}
+
+fn internal_error(error: anyhow::Error) -> RpcError {
+    RpcError::new(INTERNAL_ERROR, format!("{error:#}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{File, OpenOptions};
+    use std::io::Cursor;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn user_line(uuid: &str, content: &str) -> String {
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":"{content}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/test","version":"1"}}"#
+        )
+    }
+
+    fn server(session: &Path) -> RpcServer<Vec<u8>> {
+        let service = Service::new(
+            session.display().to_string(),
+            SearchOptions {
+                max_results: None,
+                ..Default::default()
+            },
+        );
+        RpcServer::new(service, Vec::new())
+    }
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    #[test]
+    fn test_requests() -> Result<()> {
+        let dir = tempdir()?;
+        let session = dir.path().join("session.jsonl");
+        let mut file = File::create(&session)?;
+        writeln!(file, "{}", user_line("u1", "hello rpc"))?;
+        drop(file);
+        let mut server = server(&session);
+
+        let reply = server
+            .handle(request(1, "search", json!({"query": "hello"})))
+            .unwrap();
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["result"]["totalCount"], 1);
+        assert_eq!(reply["result"]["results"][0]["uuid"], "u1");
+
+        let reply = server
+            .handle(request(2, "getSession", json!({"sessionId": "s1"})))
+            .unwrap();
+        assert_eq!(reply["result"]["messages"][0]["line_number"], 1);
+
+        let reply = server
+            .handle(request(3, "getMessage", json!({"uuid": "u1"})))
+            .unwrap();
+        assert_eq!(reply["result"]["text"], "hello rpc");
+
+        let reply = server.handle(request(4, "getMessage", json!({}))).unwrap();
+        assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+
+        let reply = server.handle(request(5, "nope", Value::Null)).unwrap();
+        assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+
+        // Notifications get no reply
+        assert!(
+            server
+                .handle(json!({"jsonrpc": "2.0", "method": "initialize"}))
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_watch_notifies_appended_matches() -> Result<()> {
+        let dir = tempdir()?;
+        let session = dir.path().join("session.jsonl");
+        let mut file = File::create(&session)?;
+        writeln!(file, "{}", user_line("u1", "error earlier"))?;
+        drop(file);
+        let mut server = server(&session);
+
+        let reply = server
+            .handle(request(1, "watch", json!({"query": "error"})))
+            .unwrap();
+        let watch_id = reply["result"]["watchId"].clone();
+        assert!(server.poll_watches().is_empty());
+
+        let mut file = OpenOptions::new().append(true).open(&session)?;
+        writeln!(file, "{}", user_line("u2", "error now"))?;
+        drop(file);
+
+        let notifications = server.poll_watches();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0]["method"], "watch/message");
+        assert_eq!(notifications[0]["params"]["watchId"], watch_id);
+        assert_eq!(notifications[0]["params"]["message"]["uuid"], "u2");
+
+        let reply = server
+            .handle(request(2, "unwatch", json!({"watchId": watch_id})))
+            .unwrap();
+        assert_eq!(reply["result"]["removed"], true);
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_until_exit() -> Result<()> {
+        let dir = tempdir()?;
+        let session = dir.path().join("session.jsonl");
+        File::create(&session)?;
+
+        let mut input = Vec::new();
+        write_message(&mut input, &request(1, "initialize", Value::Null))?;
+        input.extend_from_slice(b"Content-Length: 3\r\n\r\n{x}");
+        write_message(&mut input, &json!({"jsonrpc": "2.0", "method": "exit"}))?;
+        write_message(&mut input, &request(2, "shutdown", Value::Null))?;
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let server = RpcServer {
+            service: Arc::new(Service::new(
+                session.display().to_string(),
+                SearchOptions::default(),
+            )),
+            output: Arc::clone(&output),
+            watches: Arc::default(),
+            next_watch_id: 1,
+            exit_requested: false,
+        };
+        server.run(Cursor::new(input))?;
+
+        let output = output.lock().unwrap().clone();
+        let mut output = Cursor::new(output);
+        let first: Value = serde_json::from_slice(&read_message(&mut output)?.unwrap())?;
+        assert_eq!(first["result"]["serverInfo"]["name"], "ccms");
+        let second: Value = serde_json::from_slice(&read_message(&mut output)?.unwrap())?;
+        assert_eq!(second["error"]["code"], PARSE_ERROR);
+        // Nothing is answered after `exit`
+        assert!(read_message(&mut output)?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+//! JSON-RPC 2.0 messages framed with LSP-style `Content-Length` headers.
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::io::{BufRead, Write};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Read the body of the next framed message, or `None` at end of input
+pub fn read_message(input: &mut impl BufRead) -> Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    let mut header = String::new();
+
+    loop {
+        header.clear();
+        if input.read_line(&mut header)? == 0 {
+            if content_length.is_none() {
+                return Ok(None);
+            }
+            bail!("unexpected end of input inside message headers");
+        }
+
+        let line = header.trim_end();
+        if line.is_empty() {
+            // Tolerate stray blank lines between messages
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = Some(
+                value
+                    .trim()
+                    .parse::<usize>()
+                    .with_context(|| format!("invalid Content-Length: {}", value.trim()))?,
+            );
+        }
+    }
+
+    let mut body = vec![0; content_length.unwrap_or_default()];
+    input.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> Result<()> {
+    let body = serde_json::to_vec(message)?;
+    write!(output, "Content-Length: {}\r\n\r\n", body.len())?;
+    output.write_all(&body)?;
+    output.flush()?;
+    Ok(())
+}
+
+pub fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+pub fn error_response(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": error })
+}
+
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_framing_round_trip() -> Result<()> {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &notification("a", json!({"x": 1})))?;
+        write_message(&mut buffer, &response(json!(2), json!("ok")))?;
+
+        let mut input = Cursor::new(buffer);
+        let first: Value = serde_json::from_slice(&read_message(&mut input)?.unwrap())?;
+        assert_eq!(first["method"], "a");
+        let second: Value = serde_json::from_slice(&read_message(&mut input)?.unwrap())?;
+        assert_eq!(second["result"], "ok");
+        assert!(read_message(&mut input)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_message_headers() -> Result<()> {
+        let mut input = Cursor::new(
+            "Content-Type: application/vscode-jsonrpc\r\ncontent-length: 2\r\n\r\n{}".as_bytes(),
+        );
+        assert_eq!(read_message(&mut input)?.unwrap(), b"{}");
+
+        let mut truncated = Cursor::new("Content-Length: 10\r\n".as_bytes());
+        assert!(read_message(&mut truncated).is_err());
+        Ok(())
+    }
+}
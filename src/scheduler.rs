@@ -0,0 +1,304 @@
+//! Blocking work for the interactive mode and `ccms serve --http`, run on a
+//! shared pool of worker threads.
+//!
+//! Work is queued by [`TaskKind`]. Interactive kinds are always started
+//! before background ones, and each kind has its own concurrency limit, so
+//! a slow session-list scan or a prefetch cannot hold every thread while a
+//! search is waiting.
+
+use smol::channel::{self, Receiver};
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Someone is waiting on the result
+    Interactive,
+    /// Work done ahead of time that nobody is waiting on yet
+    Background,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    Search,
+    SessionLoad,
+    SessionList,
+    /// Prefetching and index upkeep
+    Background,
+}
+
+impl TaskKind {
+    /// Every kind, in the order queued work is started
+    pub const ALL: [TaskKind; 4] = [
+        TaskKind::Search,
+        TaskKind::SessionLoad,
+        TaskKind::SessionList,
+        TaskKind::Background,
+    ];
+
+    pub fn priority(self) -> Priority {
+        match self {
+            TaskKind::Background => Priority::Background,
+            _ => Priority::Interactive,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Default number of tasks of this kind running at once on `threads`
+    /// workers. Background work always leaves a thread free when there is
+    /// more than one.
+    fn default_limit(self, threads: usize) -> usize {
+        match self {
+            TaskKind::Search | TaskKind::SessionLoad => threads,
+            TaskKind::SessionList => (threads / 2).max(1),
+            TaskKind::Background => (threads / 4).max(1),
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct State {
+    queues: [VecDeque<Job>; TaskKind::ALL.len()],
+    running: [usize; TaskKind::ALL.len()],
+    limits: [usize; TaskKind::ALL.len()],
+    shutdown: bool,
+}
+
+impl State {
+    /// The next job allowed to start, highest priority first
+    fn take_next(&mut self) -> Option<(TaskKind, Job)> {
+        let kind = TaskKind::ALL
+            .into_iter()
+            .filter(|kind| {
+                let i = kind.index();
+                !self.queues[i].is_empty() && self.running[i] < self.limits[i]
+            })
+            .min_by_key(|kind| kind.priority())?;
+        let job = self.queues[kind.index()].pop_front()?;
+        self.running[kind.index()] += 1;
+        Some((kind, job))
+    }
+}
+
+struct Shared {
+    state: Mutex<State>,
+    ready: Condvar,
+}
+
+pub struct Scheduler {
+    shared: Arc<Shared>,
+}
+
+/// Result of a task handed to [`Scheduler::spawn`]
+pub struct Task<T> {
+    result: Receiver<thread::Result<T>>,
+}
+
+impl<T> Task<T> {
+    /// Wait for the task to finish, re-raising its panic if it had one
+    pub async fn join(self) -> T {
+        Self::unwrap(self.result.recv().await)
+    }
+
+    /// Whether the task has finished, so `join` will not wait
+    pub fn is_finished(&self) -> bool {
+        !self.result.is_empty()
+    }
+
+    /// Like `join`, for callers outside an async runtime
+    pub fn join_blocking(self) -> T {
+        Self::unwrap(self.result.recv_blocking())
+    }
+
+    fn unwrap(received: Result<thread::Result<T>, channel::RecvError>) -> T {
+        match received.expect("scheduler worker exited without finishing the task") {
+            Ok(value) => value,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+impl Scheduler {
+    /// A scheduler with `threads` workers and the default limit per kind
+    pub fn new(threads: usize) -> Self {
+        let threads = threads.max(1);
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                queues: Default::default(),
+                running: [0; TaskKind::ALL.len()],
+                limits: TaskKind::ALL.map(|kind| kind.default_limit(threads)),
+                shutdown: false,
+            }),
+            ready: Condvar::new(),
+        });
+
+        for i in 0..threads {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name(format!("ccms-worker-{i}"))
+                .spawn(move || work(&shared))
+                .expect("failed to start scheduler worker");
+        }
+
+        Self { shared }
+    }
+
+    /// The scheduler shared by the whole process, one worker per CPU
+    pub fn global() -> &'static Scheduler {
+        static GLOBAL: OnceLock<Scheduler> = OnceLock::new();
+        GLOBAL.get_or_init(|| Scheduler::new(num_cpus::get()))
+    }
+
+    /// Allow at most `limit` tasks of `kind` to run at once
+    pub fn with_limit(self, kind: TaskKind, limit: usize) -> Self {
+        self.lock().limits[kind.index()] = limit.max(1);
+        self
+    }
+
+    /// Queue `f` to run on a worker
+    pub fn spawn<T, F>(&self, kind: TaskKind, f: F) -> Task<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (sender, result) = channel::bounded(1);
+        let job: Job = Box::new(move || {
+            let _ = sender.try_send(panic::catch_unwind(AssertUnwindSafe(f)));
+        });
+
+        self.lock().queues[kind.index()].push_back(job);
+        self.shared.ready.notify_one();
+        Task { result }
+    }
+
+    /// Run `f` on a worker and wait for it
+    pub fn run<T, F>(&self, kind: TaskKind, f: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        self.spawn(kind, f).join_blocking()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.shared.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        // Workers finish what is already queued, then exit
+        self.lock().shutdown = true;
+        self.shared.ready.notify_all();
+    }
+}
+
+fn work(shared: &Shared) {
+    let mut state = shared.state.lock().unwrap_or_else(|e| e.into_inner());
+    loop {
+        if let Some((kind, job)) = state.take_next() {
+            drop(state);
+            job();
+            state = shared.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.running[kind.index()] -= 1;
+            // A slot opened up, which may let a held-back job start
+            shared.ready.notify_all();
+        } else if state.shutdown && state.queues.iter().all(VecDeque::is_empty) {
+            return;
+        } else {
+            state = shared.ready.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_interactive_work_goes_first() {
+        let scheduler = Scheduler::new(1);
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        // Hold the only worker until everything else is queued
+        let (release, held) = mpsc::channel::<()>();
+        let blocker = scheduler.spawn(TaskKind::Search, move || held.recv().unwrap());
+
+        let tasks: Vec<Task<()>> = [
+            TaskKind::Background,
+            TaskKind::SessionList,
+            TaskKind::Search,
+        ]
+        .into_iter()
+        .map(|kind| {
+            let order = Arc::clone(&order);
+            scheduler.spawn(kind, move || order.lock().unwrap().push(kind))
+        })
+        .collect();
+
+        release.send(()).unwrap();
+        blocker.join_blocking();
+        for task in tasks {
+            task.join_blocking();
+        }
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec![
+                TaskKind::Search,
+                TaskKind::SessionList,
+                TaskKind::Background
+            ]
+        );
+    }
+
+    #[test]
+    fn test_limit_per_kind() {
+        let scheduler = Scheduler::new(4).with_limit(TaskKind::SessionList, 2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<Task<usize>> = (0..8)
+            .map(|i| {
+                let running = Arc::clone(&running);
+                let most = Arc::clone(&most);
+                scheduler.spawn(TaskKind::SessionList, move || {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    most.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(10));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    i
+                })
+            })
+            .collect();
+
+        let results: Vec<usize> = tasks.into_iter().map(Task::join_blocking).collect();
+        assert_eq!(results, (0..8).collect::<Vec<_>>());
+        assert_eq!(most.load(Ordering::SeqCst), 2);
+
+        // Other kinds are not held back by the limit
+        assert_eq!(scheduler.run(TaskKind::Search, || 42), 42);
+    }
+
+    #[test]
+    fn test_join_async_and_panics() {
+        let scheduler = Scheduler::new(2);
+        let value = smol::block_on(scheduler.spawn(TaskKind::SessionLoad, || "loaded").join());
+        assert_eq!(value, "loaded");
+
+        let task = scheduler.spawn(TaskKind::Search, || -> () { panic!("search failed") });
+        let caught = panic::catch_unwind(AssertUnwindSafe(|| task.join_blocking()));
+        assert!(caught.is_err());
+
+        // The worker survives the panic
+        assert_eq!(scheduler.run(TaskKind::Search, || 1 + 1), 2);
+    }
+}
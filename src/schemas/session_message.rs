@@ -1,5 +1,8 @@
+use super::tool_result::ToolResult;
+use crate::query::condition::TextSection;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Cow;
 
 // Base message fields common to most message types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +78,26 @@ pub struct ImageSource {
     pub data: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub media_type: Option<String>,
+    /// Set for images linked by URL instead of embedded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl ImageSource {
+    /// One line of image metadata, e.g. `image/png base64`; it always
+    /// contains "image", so `images:image` finds every image
+    fn describe(&self) -> String {
+        [
+            Some(self.media_type.as_deref().unwrap_or("image")),
+            Some(self.source_type.as_str()),
+            self.url.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
 }
 
 // Usage information
@@ -145,6 +168,9 @@ pub enum SessionMessage {
         git_branch: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none", rename = "requestId")]
         request_id: Option<String>,
+        /// Kind of system event, e.g. `compact_boundary`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subtype: Option<String>,
     },
     User {
         #[serde(flatten)]
@@ -172,6 +198,86 @@ pub enum SessionMessage {
     },
 }
 
+/// Append the text shown for `content` to `segments`
+fn push_content_segments<'a>(content: &'a Content, segments: &mut Vec<Cow<'a, str>>) {
+    match content {
+        Content::Text { text } => segments.push(Cow::Borrowed(text)),
+        Content::Thinking { thinking, .. } => segments.push(Cow::Borrowed(thinking)),
+        Content::ToolUse { name, input, .. } => {
+            segments.push(Cow::Owned(tool_use_text(name, input)))
+        }
+        Content::ToolResult {
+            tool_use_id,
+            content,
+            is_error,
+        } => {
+            let is_error = is_error.unwrap_or(false);
+            match content {
+                Some(ToolResultContent::String(s)) if !s.is_empty() => {
+                    segments.push(Cow::Borrowed(s));
+                }
+                Some(ToolResultContent::TextArray(items)) if !items.is_empty() => {
+                    segments.extend(items.iter().map(|item| Cow::Borrowed(item.text.as_str())));
+                }
+                Some(ToolResultContent::Value(value)) => match value.as_str() {
+                    Some(s) => segments.push(Cow::Borrowed(s)),
+                    None => segments.push(Cow::Owned(format!(
+                        "[Tool Result: {tool_use_id} - JSON value{}]",
+                        if is_error { " (error)" } else { "" }
+                    ))),
+                },
+                // Empty, image-only or missing content
+                _ => segments.push(Cow::Owned(format!(
+                    "[Tool Result: {tool_use_id}{}]",
+                    if is_error { " (error)" } else { "" }
+                ))),
+            }
+        }
+        // Placeholder for image entries
+        Content::Image { .. } => segments.push(Cow::Borrowed("[Image]")),
+    }
+}
+
+impl Content {
+    /// The text `get_content_text` shows for this block
+    pub fn display_text(&self) -> String {
+        let mut segments = Vec::new();
+        push_content_segments(self, &mut segments);
+        segments.join("\n")
+    }
+}
+
+/// A tool call as the tool name and the gist of its input
+fn tool_use_text(name: &str, input: &Value) -> String {
+    let mut tool_text = name.to_string();
+
+    // Extract key information from input based on tool type
+    let Some(obj) = input.as_object() else {
+        return tool_text;
+    };
+    let (field, max_chars) = match name {
+        "Bash" => ("command", Some(50)),
+        "Read" | "Write" | "Edit" => ("file_path", None),
+        "Grep" => ("pattern", Some(30)),
+        // For other tools, try to find a descriptive field
+        _ => ("description", Some(40)),
+    };
+    if let Some(value) = obj.get(field).and_then(|v| v.as_str()) {
+        tool_text.push_str(": ");
+        match max_chars {
+            Some(max_chars) => {
+                tool_text.extend(value.chars().take(max_chars));
+                if value.len() > max_chars {
+                    tool_text.push_str("...");
+                }
+            }
+            // File paths are shortened to the file name
+            None => tool_text.push_str(value.split('/').next_back().unwrap_or(value)),
+        }
+    }
+    tool_text
+}
+
 // Helper methods
 impl SessionMessage {
     pub fn get_type(&self) -> &'static str {
@@ -184,339 +290,48 @@ impl SessionMessage {
     }
 
     pub fn get_content_text(&self) -> String {
+        self.content_segments().join("\n")
+    }
+
+    /// The pieces `get_content_text` joins with newlines. Text is borrowed
+    /// from the message; only tool calls and placeholders are formatted.
+    pub fn content_segments(&self) -> Vec<Cow<'_, str>> {
+        let mut segments = Vec::new();
         match self {
-            SessionMessage::Summary { summary, .. } => summary.clone(),
-            SessionMessage::System { content, .. } => content.clone(),
-            SessionMessage::User { message, .. } => {
-                let mut texts = Vec::new();
-
-                // Extract content text
-                match &message.content {
-                    UserContent::String(s) => texts.push(s.clone()),
-                    UserContent::Array(contents) => {
-                        for content in contents {
-                            match content {
-                                Content::Text { text } => texts.push(text.clone()),
-                                Content::ToolResult {
-                                    tool_use_id,
-                                    content: Some(tool_content),
-                                    is_error,
-                                } => {
-                                    let result_text = match tool_content {
-                                        ToolResultContent::String(s) => {
-                                            if !s.is_empty() {
-                                                s.clone()
-                                            } else {
-                                                format!(
-                                                    "[Tool Result: {}{}]",
-                                                    tool_use_id,
-                                                    if is_error.unwrap_or(false) {
-                                                        " (error)"
-                                                    } else {
-                                                        ""
-                                                    }
-                                                )
-                                            }
-                                        }
-                                        ToolResultContent::TextArray(arr) => {
-                                            if !arr.is_empty() {
-                                                arr.iter()
-                                                    .map(|item| &item.text)
-                                                    .cloned()
-                                                    .collect::<Vec<_>>()
-                                                    .join("\n")
-                                            } else {
-                                                format!(
-                                                    "[Tool Result: {}{}]",
-                                                    tool_use_id,
-                                                    if is_error.unwrap_or(false) {
-                                                        " (error)"
-                                                    } else {
-                                                        ""
-                                                    }
-                                                )
-                                            }
-                                        }
-                                        ToolResultContent::Value(val) => {
-                                            if let Some(s) = val.as_str() {
-                                                s.to_string()
-                                            } else {
-                                                format!(
-                                                    "[Tool Result: {} - JSON value{}]",
-                                                    tool_use_id,
-                                                    if is_error.unwrap_or(false) {
-                                                        " (error)"
-                                                    } else {
-                                                        ""
-                                                    }
-                                                )
-                                            }
-                                        }
-                                        _ => format!(
-                                            "[Tool Result: {}{}]",
-                                            tool_use_id,
-                                            if is_error.unwrap_or(false) {
-                                                " (error)"
-                                            } else {
-                                                ""
-                                            }
-                                        ),
-                                    };
-                                    texts.push(result_text);
-                                }
-                                Content::ToolResult {
-                                    tool_use_id,
-                                    content: None,
-                                    is_error,
-                                } => {
-                                    // Show placeholder for tool results with no content
-                                    texts.push(format!(
-                                        "[Tool Result: {}{}]",
-                                        tool_use_id,
-                                        if is_error.unwrap_or(false) {
-                                            " (error)"
-                                        } else {
-                                            ""
-                                        }
-                                    ));
-                                }
-                                Content::ToolUse { name, input, .. } => {
-                                    let mut tool_text = name.clone();
-
-                                    // Extract key information from input based on tool type
-                                    if let Some(obj) = input.as_object() {
-                                        match name.as_str() {
-                                            "Bash" => {
-                                                if let Some(cmd) =
-                                                    obj.get("command").and_then(|v| v.as_str())
-                                                {
-                                                    tool_text.push_str(": ");
-                                                    tool_text.push_str(
-                                                        &cmd.chars().take(50).collect::<String>(),
-                                                    );
-                                                    if cmd.len() > 50 {
-                                                        tool_text.push_str("...");
-                                                    }
-                                                }
-                                            }
-                                            "Read" | "Write" | "Edit" => {
-                                                if let Some(path) =
-                                                    obj.get("file_path").and_then(|v| v.as_str())
-                                                {
-                                                    tool_text.push_str(": ");
-                                                    tool_text.push_str(
-                                                        path.split('/').next_back().unwrap_or(path),
-                                                    );
-                                                }
-                                            }
-                                            "Grep" => {
-                                                if let Some(pattern) =
-                                                    obj.get("pattern").and_then(|v| v.as_str())
-                                                {
-                                                    tool_text.push_str(": ");
-                                                    tool_text.push_str(
-                                                        &pattern
-                                                            .chars()
-                                                            .take(30)
-                                                            .collect::<String>(),
-                                                    );
-                                                    if pattern.len() > 30 {
-                                                        tool_text.push_str("...");
-                                                    }
-                                                }
-                                            }
-                                            _ => {
-                                                // For other tools, try to find a descriptive field
-                                                if let Some(desc) =
-                                                    obj.get("description").and_then(|v| v.as_str())
-                                                {
-                                                    tool_text.push_str(": ");
-                                                    tool_text.push_str(
-                                                        &desc.chars().take(40).collect::<String>(),
-                                                    );
-                                                    if desc.len() > 40 {
-                                                        tool_text.push_str("...");
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-
-                                    texts.push(tool_text);
-                                }
-                                Content::Image { .. } => {
-                                    // Add placeholder for image entries
-                                    texts.push("[Image]".to_string());
-                                }
-                                Content::Thinking { thinking, .. } => texts.push(thinking.clone()),
-                            }
-                        }
+            SessionMessage::Summary { summary, .. } => {
+                segments.push(Cow::Borrowed(summary.as_str()))
+            }
+            SessionMessage::System { content, .. } => {
+                segments.push(Cow::Borrowed(content.as_str()))
+            }
+            SessionMessage::User { message, .. } => match &message.content {
+                UserContent::String(s) => segments.push(Cow::Borrowed(s.as_str())),
+                UserContent::Array(contents) => {
+                    for content in contents {
+                        push_content_segments(content, &mut segments);
                     }
                 }
-
-                texts.join("\n")
-            }
+            },
             SessionMessage::Assistant { message, .. } => {
-                let mut texts = Vec::new();
-
                 for content in &message.content {
-                    match content {
-                        Content::Text { text } => texts.push(text.clone()),
-                        Content::Thinking { thinking, .. } => texts.push(thinking.clone()),
-                        Content::ToolUse { name, input, .. } => {
-                            let mut tool_text = name.clone();
-
-                            // Extract key information from input based on tool type
-                            if let Some(obj) = input.as_object() {
-                                match name.as_str() {
-                                    "Bash" => {
-                                        if let Some(cmd) =
-                                            obj.get("command").and_then(|v| v.as_str())
-                                        {
-                                            tool_text.push_str(": ");
-                                            tool_text.push_str(
-                                                &cmd.chars().take(50).collect::<String>(),
-                                            );
-                                            if cmd.len() > 50 {
-                                                tool_text.push_str("...");
-                                            }
-                                        }
-                                    }
-                                    "Read" | "Write" | "Edit" => {
-                                        if let Some(path) =
-                                            obj.get("file_path").and_then(|v| v.as_str())
-                                        {
-                                            tool_text.push_str(": ");
-                                            tool_text.push_str(
-                                                path.split('/').next_back().unwrap_or(path),
-                                            );
-                                        }
-                                    }
-                                    "Grep" => {
-                                        if let Some(pattern) =
-                                            obj.get("pattern").and_then(|v| v.as_str())
-                                        {
-                                            tool_text.push_str(": ");
-                                            tool_text.push_str(
-                                                &pattern.chars().take(30).collect::<String>(),
-                                            );
-                                            if pattern.len() > 30 {
-                                                tool_text.push_str("...");
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        // For other tools, try to find a descriptive field
-                                        if let Some(desc) =
-                                            obj.get("description").and_then(|v| v.as_str())
-                                        {
-                                            tool_text.push_str(": ");
-                                            tool_text.push_str(
-                                                &desc.chars().take(40).collect::<String>(),
-                                            );
-                                            if desc.len() > 40 {
-                                                tool_text.push_str("...");
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-
-                            texts.push(tool_text);
-                        }
-                        Content::ToolResult {
-                            tool_use_id,
-                            content: Some(tool_content),
-                            is_error,
-                        } => {
-                            let result_text = match tool_content {
-                                ToolResultContent::String(s) => {
-                                    if !s.is_empty() {
-                                        s.clone()
-                                    } else {
-                                        format!(
-                                            "[Tool Result: {}{}]",
-                                            tool_use_id,
-                                            if is_error.unwrap_or(false) {
-                                                " (error)"
-                                            } else {
-                                                ""
-                                            }
-                                        )
-                                    }
-                                }
-                                ToolResultContent::TextArray(arr) => {
-                                    if !arr.is_empty() {
-                                        arr.iter()
-                                            .map(|item| &item.text)
-                                            .cloned()
-                                            .collect::<Vec<_>>()
-                                            .join("\n")
-                                    } else {
-                                        format!(
-                                            "[Tool Result: {}{}]",
-                                            tool_use_id,
-                                            if is_error.unwrap_or(false) {
-                                                " (error)"
-                                            } else {
-                                                ""
-                                            }
-                                        )
-                                    }
-                                }
-                                ToolResultContent::Value(val) => {
-                                    if let Some(s) = val.as_str() {
-                                        s.to_string()
-                                    } else {
-                                        format!(
-                                            "[Tool Result: {} - JSON value{}]",
-                                            tool_use_id,
-                                            if is_error.unwrap_or(false) {
-                                                " (error)"
-                                            } else {
-                                                ""
-                                            }
-                                        )
-                                    }
-                                }
-                                _ => format!(
-                                    "[Tool Result: {}{}]",
-                                    tool_use_id,
-                                    if is_error.unwrap_or(false) {
-                                        " (error)"
-                                    } else {
-                                        ""
-                                    }
-                                ),
-                            };
-                            texts.push(result_text);
-                        }
-                        Content::ToolResult {
-                            tool_use_id,
-                            content: None,
-                            is_error,
-                        } => {
-                            // Show placeholder for tool results with no content
-                            texts.push(format!(
-                                "[Tool Result: {}{}]",
-                                tool_use_id,
-                                if is_error.unwrap_or(false) {
-                                    " (error)"
-                                } else {
-                                    ""
-                                }
-                            ));
-                        }
-                        Content::Image { .. } => {
-                            // Add placeholder for image entries
-                            texts.push("[Image]".to_string());
-                        }
-                    }
+                    push_content_segments(content, &mut segments);
                 }
-
-                texts.join("\n")
             }
         }
+        segments
+    }
+
+    /// The content blocks of a user or assistant message; empty for messages
+    /// that are plain text
+    pub fn content_blocks(&self) -> &[Content] {
+        match self {
+            SessionMessage::User { message, .. } => match &message.content {
+                UserContent::Array(contents) => contents,
+                UserContent::String(_) => &[],
+            },
+            SessionMessage::Assistant { message, .. } => &message.content,
+            SessionMessage::Summary { .. } | SessionMessage::System { .. } => &[],
+        }
     }
 
     pub fn get_uuid(&self) -> Option<&str> {
@@ -528,6 +343,17 @@ impl SessionMessage {
         }
     }
 
+    /// The message this one follows in the conversation, `None` for the
+    /// first message and for summaries
+    pub fn get_parent_uuid(&self) -> Option<&str> {
+        match self {
+            SessionMessage::Summary { .. } => None,
+            SessionMessage::System { base, .. }
+            | SessionMessage::User { base, .. }
+            | SessionMessage::Assistant { base, .. } => base.parent_uuid.as_deref(),
+        }
+    }
+
     pub fn get_timestamp(&self) -> Option<&str> {
         match self {
             SessionMessage::Summary { .. } => None,
@@ -555,6 +381,182 @@ impl SessionMessage {
         }
     }
 
+    /// Whether the message belongs to a subagent conversation
+    pub fn is_sidechain(&self) -> bool {
+        match self {
+            SessionMessage::Summary { .. } => false,
+            SessionMessage::System { base, .. }
+            | SessionMessage::User { base, .. }
+            | SessionMessage::Assistant { base, .. } => base.is_sidechain,
+        }
+    }
+
+    /// Whether the message is flagged `isMeta`
+    pub fn is_meta(&self) -> bool {
+        match self {
+            SessionMessage::System { is_meta, .. } => *is_meta,
+            SessionMessage::User { is_meta, .. } => *is_meta == Some(true),
+            SessionMessage::Summary { .. } | SessionMessage::Assistant { .. } => false,
+        }
+    }
+
+    /// Whether the message is where a session continues after its context
+    /// was compacted: the `compact_boundary` system event, or the summary
+    /// the conversation was continued from when no such event precedes it
+    pub fn starts_compaction(&self) -> bool {
+        match self {
+            SessionMessage::System { subtype, .. } => {
+                subtype.as_deref() == Some("compact_boundary")
+            }
+            SessionMessage::User {
+                base,
+                is_compact_summary,
+                ..
+            } => *is_compact_summary == Some(true) && base.parent_uuid.is_none(),
+            SessionMessage::Summary { .. } | SessionMessage::Assistant { .. } => false,
+        }
+    }
+
+    /// Whether this is a user message carrying nothing but tool results, as
+    /// sent back after each tool call
+    pub fn is_tool_result_only(&self) -> bool {
+        let SessionMessage::User { message, .. } = self else {
+            return false;
+        };
+        match &message.content {
+            UserContent::Array(contents) => {
+                !contents.is_empty()
+                    && contents
+                        .iter()
+                        .all(|content| matches!(content, Content::ToolResult { .. }))
+            }
+            UserContent::String(_) => false,
+        }
+    }
+
+    /// Searchable text of the structured `toolUseResult` attached to a user
+    /// message, if it has one
+    pub fn get_tool_result_text(&self) -> Option<String> {
+        let SessionMessage::User {
+            tool_use_result: Some(value),
+            ..
+        } = self
+        else {
+            return None;
+        };
+        let text = serde_json::from_value::<ToolResult>(value.clone())
+            .ok()?
+            .searchable_text();
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// Text of the thinking blocks of an assistant message, if it has any
+    pub fn get_thinking_text(&self) -> Option<String> {
+        let SessionMessage::Assistant { message, .. } = self else {
+            return None;
+        };
+        let texts: Vec<&str> = message
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                Content::Thinking { thinking, .. } => Some(thinking.as_str()),
+                _ => None,
+            })
+            .collect();
+        (!texts.is_empty()).then(|| texts.join("\n"))
+    }
+
+    /// Metadata of the images attached to the message, one line per image,
+    /// if it has any
+    pub fn get_image_text(&self) -> Option<String> {
+        let lines = self.image_descriptions();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Whether the message has images attached, e.g. pasted screenshots or
+    /// images read by a tool
+    pub fn has_images(&self) -> bool {
+        !self.image_descriptions().is_empty()
+    }
+
+    fn image_descriptions(&self) -> Vec<String> {
+        let (contents, tool_use_result) = match self {
+            SessionMessage::User {
+                message,
+                tool_use_result,
+                ..
+            } => match &message.content {
+                UserContent::Array(contents) => (contents.as_slice(), tool_use_result.as_ref()),
+                UserContent::String(_) => (&[][..], tool_use_result.as_ref()),
+            },
+            SessionMessage::Assistant { message, .. } => (message.content.as_slice(), None),
+            SessionMessage::Summary { .. } | SessionMessage::System { .. } => (&[][..], None),
+        };
+
+        let mut lines = Vec::new();
+        for content in contents {
+            match content {
+                Content::Image { source } => lines.push(source.describe()),
+                Content::ToolResult {
+                    content: Some(ToolResultContent::ImageArray(images)),
+                    ..
+                } => lines.extend(images.iter().map(|image| image.source.describe())),
+                // Text and images mixed in one tool result
+                Content::ToolResult {
+                    content: Some(ToolResultContent::Value(Value::Array(items))),
+                    ..
+                } => lines.extend(
+                    items
+                        .iter()
+                        .filter_map(|item| {
+                            serde_json::from_value::<ImageContent>(item.clone()).ok()
+                        })
+                        .filter(|image| image.content_type == "image")
+                        .map(|image| image.source.describe()),
+                ),
+                _ => {}
+            }
+        }
+        // An image file read by a tool
+        if let Some(value) = tool_use_result
+            && value.get("type").and_then(Value::as_str) == Some("image")
+        {
+            let media_type = value
+                .pointer("/file/type")
+                .and_then(Value::as_str)
+                .unwrap_or("image");
+            lines.push(format!("{media_type} file"));
+        }
+        lines
+    }
+
+    /// `get_searchable_text` followed by `sections`, which query scopes such
+    /// as `thinking:` and `tool_result:` match against
+    pub fn get_searchable_text_with(&self, sections: &[TextSection]) -> String {
+        let mut text = self.get_searchable_text();
+        for &section in sections {
+            let section_text = match section {
+                TextSection::Thinking => self.get_thinking_text(),
+                TextSection::ToolResult => self.get_tool_result_text(),
+                TextSection::Images => self.get_image_text(),
+            };
+            if let Some(section_text) = section_text {
+                section.append_to(&mut text, &section_text);
+            }
+        }
+        text
+    }
+
+    /// The pieces of `get_searchable_text`, borrowed where they can be. The
+    /// text is these pieces joined by whitespace, so a term without
+    /// whitespace matches the text only if it matches one of them.
+    pub fn searchable_segments(&self) -> Vec<Cow<'_, str>> {
+        let mut segments = self.content_segments();
+        segments.extend(self.get_session_id().map(Cow::Borrowed));
+        segments.extend(self.get_uuid().map(Cow::Borrowed));
+        segments
+    }
+
     pub fn get_searchable_text(&self) -> String {
         let mut parts = vec![self.get_content_text()];
 
@@ -693,6 +695,15 @@ mod tests {
             msg.get_content_text(),
             "Let me think about this problem...\nHere's my answer."
         );
+        assert_eq!(
+            msg.get_thinking_text().as_deref(),
+            Some("Let me think about this problem...")
+        );
+        let text = msg.get_searchable_text_with(&[TextSection::Thinking]);
+        assert_eq!(
+            TextSection::Thinking.find_in(&text),
+            Some("Let me think about this problem...")
+        );
     }
 
     #[test]
@@ -939,6 +950,53 @@ mod tests {
         assert!(searchable_text.contains("user-uuid-123"));
     }
 
+    #[test]
+    fn test_searchable_segments() {
+        let json = r#"{
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": "Run it"},
+                    {"type": "tool_result", "tool_use_id": "t1", "content": [
+                        {"type": "text", "text": "line 1"},
+                        {"type": "text", "text": "line 2"}
+                    ]},
+                    {"type": "tool_result", "tool_use_id": "t2", "content": "", "is_error": true}
+                ]
+            },
+            "uuid": "u1",
+            "timestamp": "2024-01-01T00:00:00Z",
+            "sessionId": "s1",
+            "parentUuid": null,
+            "isSidechain": false,
+            "userType": "external",
+            "cwd": "/test",
+            "version": "1.0"
+        }"#;
+
+        let msg: SessionMessage = serde_json::from_str(json).unwrap();
+        let segments = msg.searchable_segments();
+        assert_eq!(
+            segments,
+            vec![
+                "Run it",
+                "line 1",
+                "line 2",
+                "[Tool Result: t2 (error)]",
+                "s1",
+                "u1"
+            ]
+        );
+        // Text is borrowed, placeholders are not
+        assert!(matches!(segments[1], Cow::Borrowed(_)));
+        assert!(matches!(segments[3], Cow::Owned(_)));
+        assert_eq!(
+            msg.get_content_text(),
+            "Run it\nline 1\nline 2\n[Tool Result: t2 (error)]"
+        );
+    }
+
     #[test]
     fn test_get_searchable_text_summary() {
         // Test summary message (no session ID, but has UUID)
@@ -955,4 +1013,140 @@ mod tests {
         assert!(searchable_text.contains("leaf-uuid-789"));
         assert!(!searchable_text.contains("session")); // No session ID for summary
     }
+
+    #[test]
+    fn test_get_tool_result_text() {
+        let json = r#"{
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": [{"type": "tool_result", "tool_use_id": "tool_1", "content": "ok"}]
+            },
+            "toolUseResult": {
+                "filePath": "/w/src/main.rs",
+                "oldString": "let a = 1;",
+                "newString": "let a = 2;",
+                "originalFile": "fn main() { let a = 1; }",
+                "structuredPatch": [
+                    {"oldStart": 1, "oldLines": 1, "newStart": 1, "newLines": 1,
+                     "lines": ["-let a = 1;", "+let a = 2;"]}
+                ],
+                "userModified": false,
+                "replaceAll": false
+            },
+            "uuid": "user-uuid-4",
+            "timestamp": "2024-01-01T00:00:06Z",
+            "sessionId": "test-session",
+            "parentUuid": null,
+            "isSidechain": false,
+            "userType": "external",
+            "cwd": "/w",
+            "version": "1.0"
+        }"#;
+
+        let msg: SessionMessage = serde_json::from_str(json).unwrap();
+        let tool_text = msg.get_tool_result_text().unwrap();
+        assert!(tool_text.contains("/w/src/main.rs"));
+        assert!(tool_text.contains("+let a = 2;"));
+        // The original file is not part of what the edit changed
+        assert!(!tool_text.contains("fn main()"));
+
+        assert!(!msg.get_searchable_text().contains("src/main.rs"));
+        let text = msg.get_searchable_text_with(&[TextSection::Thinking, TextSection::ToolResult]);
+        assert!(text.starts_with(&msg.get_searchable_text()));
+        assert_eq!(
+            TextSection::ToolResult.find_in(&text),
+            Some(tool_text.as_str())
+        );
+        assert_eq!(TextSection::Thinking.find_in(&text), None);
+    }
+
+    #[test]
+    fn test_get_image_text() {
+        let json = r#"{
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": "What is wrong here?"},
+                    {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgo="}},
+                    {"type": "image", "source": {"type": "url", "url": "https://example.com/screenshot.jpg"}},
+                    {"type": "tool_result", "tool_use_id": "tool_1", "content": [
+                        {"type": "text", "text": "Rendered"},
+                        {"type": "image", "source": {"type": "base64", "media_type": "image/webp", "data": "UklGRg=="}}
+                    ]}
+                ]
+            },
+            "uuid": "user-uuid-5",
+            "timestamp": "2024-01-01T00:00:07Z",
+            "sessionId": "test-session",
+            "parentUuid": null,
+            "isSidechain": false,
+            "userType": "external",
+            "cwd": "/test",
+            "version": "1.0"
+        }"#;
+
+        let msg: SessionMessage = serde_json::from_str(json).unwrap();
+        assert!(msg.has_images());
+        assert_eq!(
+            msg.get_image_text().as_deref(),
+            Some(
+                "image/png base64\nimage url https://example.com/screenshot.jpg\nimage/webp base64"
+            )
+        );
+        let text = msg.get_searchable_text_with(&[TextSection::Images]);
+        assert!(!msg.get_searchable_text().contains("image/png"));
+        assert_eq!(
+            TextSection::Images.find_in(&text),
+            msg.get_image_text().as_deref()
+        );
+
+        // An image file read by a tool
+        let read: SessionMessage = serde_json::from_str(
+            &json.replace(
+                r#""cwd": "/test","#,
+                r#""cwd": "/test", "toolUseResult": {"type": "image", "file": {"base64": "", "type": "image/jpeg", "originalSize": 10}},"#,
+            ),
+        )
+        .unwrap();
+        assert!(
+            read.get_image_text()
+                .unwrap()
+                .ends_with("\nimage/jpeg file")
+        );
+
+        let plain: SessionMessage = serde_json::from_str(
+            r#"{"type":"user","message":{"role":"user","content":"no pictures"},"uuid":"u","timestamp":"2024-01-01T00:00:00Z","sessionId":"s","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+        )
+        .unwrap();
+        assert!(!plain.has_images());
+        assert_eq!(plain.get_image_text(), None);
+    }
+
+    #[test]
+    fn test_starts_compaction() {
+        let parse = |json: &str| serde_json::from_str::<SessionMessage>(json).unwrap();
+        let boundary = parse(
+            r#"{"type":"system","subtype":"compact_boundary","content":"Conversation compacted","isMeta":false,"level":"info","compactMetadata":{"trigger":"auto","preTokens":155000},"uuid":"b","timestamp":"2024-01-01T00:00:00Z","sessionId":"s","parentUuid":null,"logicalParentUuid":"a","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+        );
+        assert!(boundary.starts_compaction());
+
+        // The summary after a boundary does not start another one
+        let summary = r#"{"type":"user","message":{"role":"user","content":"This session is being continued from a previous conversation"},"isCompactSummary":true,"uuid":"c","timestamp":"2024-01-01T00:00:01Z","sessionId":"s","parentUuid":"b","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#;
+        assert!(!parse(summary).starts_compaction());
+        assert!(
+            parse(&summary.replace(r#""parentUuid":"b""#, r#""parentUuid":null"#))
+                .starts_compaction()
+        );
+
+        let other = parse(
+            r#"{"type":"system","content":"Running hook","isMeta":false,"uuid":"d","timestamp":"2024-01-01T00:00:02Z","sessionId":"s","parentUuid":"c","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+        );
+        assert!(!other.starts_compaction());
+        assert!(
+            !parse(r#"{"type":"summary","summary":"Fixing tests","leafUuid":"c"}"#)
+                .starts_compaction()
+        );
+    }
 }
@@ -2,11 +2,14 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 // Common types
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TodoItem {
     pub content: String,
-    pub status: String,   // "pending", "in_progress", "completed"
-    pub priority: String, // "high", "medium", "low"
+    pub status: String, // "pending", "in_progress", "completed"
+    // "high", "medium", "low"; newer sessions omit priority and id
+    #[serde(default)]
+    pub priority: String,
+    #[serde(default)]
     pub id: String,
 }
 
@@ -212,6 +215,104 @@ pub enum ToolResult {
     Other(Value),
 }
 
+impl ToolResult {
+    /// Paths, diffs, edits, todos and links of the result, for searches that
+    /// look inside tool results. File contents that were only read are left
+    /// out, as the tool_result content already carries them.
+    pub fn searchable_text(&self) -> String {
+        let mut parts: Vec<&str> = Vec::new();
+        let mut owned: Vec<String> = Vec::new();
+
+        match self {
+            ToolResult::Bash { stdout, stderr, .. } => parts.extend([stdout.as_str(), stderr]),
+            ToolResult::FileRead { file, .. } => parts.push(&file.file_path),
+            ToolResult::EditFile {
+                file_path,
+                old_string,
+                new_string,
+                structured_patch,
+                ..
+            } => {
+                parts.extend([file_path.as_str(), old_string, new_string]);
+                parts.extend(patch_lines(structured_patch));
+            }
+            ToolResult::TodoUpdate { new_todos, .. } => {
+                parts.extend(new_todos.iter().map(|todo| todo.content.as_str()));
+            }
+            ToolResult::CreateFile {
+                file_path, content, ..
+            }
+            | ToolResult::UpdateFile {
+                file_path, content, ..
+            } => parts.extend([file_path.as_str(), content]),
+            ToolResult::MultiEdit {
+                file_path,
+                edits,
+                structured_patch,
+                ..
+            } => {
+                parts.push(file_path);
+                for edit in edits {
+                    parts.extend([edit.old_string.as_str(), &edit.new_string]);
+                }
+                parts.extend(patch_lines(structured_patch));
+            }
+            ToolResult::Glob { filenames, .. } | ToolResult::SimpleFilenames { filenames, .. } => {
+                parts.extend(filenames.iter().map(String::as_str));
+            }
+            ToolResult::Grep {
+                filenames, content, ..
+            } => {
+                parts.extend(filenames.iter().map(String::as_str));
+                parts.extend(content.as_deref());
+            }
+            ToolResult::TaskResult { content, .. } => {
+                parts.extend(content.iter().map(|text| text.text.as_str()));
+            }
+            ToolResult::WebSearch { query, results, .. } => {
+                parts.push(query);
+                for item in results {
+                    match item {
+                        WebSearchResultItem::String(text) => parts.push(text),
+                        WebSearchResultItem::Structured { content, .. } => {
+                            for link in content {
+                                parts.extend([link.title.as_str(), &link.url]);
+                            }
+                        }
+                    }
+                }
+            }
+            ToolResult::WebFetch { url, result, .. } => parts.extend([url.as_str(), result]),
+            ToolResult::ImageResult { .. } => {}
+            ToolResult::Other(value) => collect_strings(value, &mut owned),
+        }
+
+        parts
+            .into_iter()
+            .chain(owned.iter().map(String::as_str))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn patch_lines(patch: &[StructuredPatchItem]) -> impl Iterator<Item = &str> {
+    patch
+        .iter()
+        .flat_map(|hunk| hunk.lines.iter().map(String::as_str))
+}
+
+fn collect_strings(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(text) => out.push(text.clone()),
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, out)),
+        Value::Object(fields) => fields
+            .values()
+            .for_each(|field| collect_strings(field, out)),
+        _ => {}
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TextContent {
     #[serde(rename = "type")]
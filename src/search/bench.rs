@@ -0,0 +1,224 @@
+//! `ccms bench`: the search engines timed against each other on the same
+//! corpus and query.
+
+use super::builder::Engine;
+use super::scan_stats::ScanStats;
+use super::{RayonEngine, SearchEngineTrait, SmolEngine};
+use crate::query::{QueryCondition, SearchOptions};
+use anyhow::Result;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Timings of one engine over the measured runs
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineBench {
+    pub engine: &'static str,
+    pub runs: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    /// Matches found by the last run
+    pub results: usize,
+    /// What the last run read
+    pub scan: ScanStats,
+    /// Per second at the median time
+    pub files_per_sec: f64,
+    pub lines_per_sec: f64,
+    pub mb_per_sec: f64,
+}
+
+/// Run `query` over `pattern` with each of `engines`, `warmup` times
+/// unmeasured and then `runs` times measured.
+///
+/// Engines take turns run by run, so a change in the machine's load or the
+/// page cache affects them alike.
+pub fn bench_engines(
+    pattern: &str,
+    query: &QueryCondition,
+    options: &SearchOptions,
+    engines: &[Engine],
+    runs: usize,
+    warmup: usize,
+) -> Result<Vec<EngineBench>> {
+    let mut times: Vec<Vec<Duration>> = vec![Vec::with_capacity(runs); engines.len()];
+    let mut last: Vec<(usize, ScanStats)> = vec![Default::default(); engines.len()];
+
+    for run in 0..warmup + runs {
+        for (index, &engine) in engines.iter().enumerate() {
+            let started = Instant::now();
+            let (results, scan) = run_engine(engine, pattern, query, options)?;
+            let elapsed = started.elapsed();
+            if run >= warmup {
+                times[index].push(elapsed);
+            }
+            last[index] = (results, scan);
+        }
+    }
+
+    Ok(engines
+        .iter()
+        .zip(times)
+        .zip(last)
+        .map(|((&engine, mut times), (results, scan))| {
+            times.sort();
+            let p50 = percentile(&times, 50.0);
+            let per_sec = |count: u64| {
+                if p50.is_zero() {
+                    0.0
+                } else {
+                    count as f64 / p50.as_secs_f64()
+                }
+            };
+            EngineBench {
+                engine: engine_name(engine),
+                runs: times.len(),
+                p50_ms: millis(p50),
+                p95_ms: millis(percentile(&times, 95.0)),
+                min_ms: millis(times.first().copied().unwrap_or_default()),
+                max_ms: millis(times.last().copied().unwrap_or_default()),
+                results,
+                scan,
+                files_per_sec: per_sec(scan.files_scanned),
+                lines_per_sec: per_sec(scan.lines_parsed),
+                mb_per_sec: per_sec(scan.bytes_read) / 1_000_000.0,
+            }
+        })
+        .collect())
+}
+
+/// Comparison table, fastest median first
+pub fn format_bench_table(benches: &[EngineBench]) -> String {
+    use std::fmt::Write;
+
+    let mut sorted: Vec<&EngineBench> = benches.iter().collect();
+    sorted.sort_by(|a, b| a.p50_ms.total_cmp(&b.p50_ms));
+
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "{:<8} {:>5} {:>10} {:>10} {:>10} {:>10} {:>8} {:>10} {:>12} {:>8}",
+        "engine", "runs", "p50", "p95", "min", "max", "results", "files/s", "lines/s", "MB/s"
+    );
+    for bench in sorted {
+        let _ = writeln!(
+            output,
+            "{:<8} {:>5} {:>8.2}ms {:>8.2}ms {:>8.2}ms {:>8.2}ms {:>8} {:>10.0} {:>12.0} {:>8.1}",
+            bench.engine,
+            bench.runs,
+            bench.p50_ms,
+            bench.p95_ms,
+            bench.min_ms,
+            bench.max_ms,
+            bench.results,
+            bench.files_per_sec,
+            bench.lines_per_sec,
+            bench.mb_per_sec
+        );
+    }
+    output
+}
+
+fn run_engine(
+    engine: Engine,
+    pattern: &str,
+    query: &QueryCondition,
+    options: &SearchOptions,
+) -> Result<(usize, ScanStats)> {
+    match engine {
+        Engine::Smol => {
+            let engine = SmolEngine::new(options.clone());
+            let (_, _, total_count) = engine.search(pattern, query.clone())?;
+            Ok((total_count, engine.last_scan_stats()))
+        }
+        Engine::Rayon => {
+            let engine = RayonEngine::new(options.clone());
+            let (_, _, total_count) = engine.search(pattern, query.clone())?;
+            Ok((total_count, engine.last_scan_stats()))
+        }
+    }
+}
+
+fn engine_name(engine: Engine) -> &'static str {
+    match engine {
+        Engine::Smol => "smol",
+        Engine::Rayon => "rayon",
+    }
+}
+
+/// Nearest-rank percentile of sorted `times`
+fn percentile(times: &[Duration], percent: f64) -> Duration {
+    if times.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percent / 100.0 * times.len() as f64).ceil() as usize;
+    times[rank.clamp(1, times.len()) - 1]
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_query;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_percentile() {
+        let times: Vec<Duration> = (1..=20).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&times, 50.0), Duration::from_millis(10));
+        assert_eq!(percentile(&times, 95.0), Duration::from_millis(19));
+        assert_eq!(percentile(&times, 100.0), Duration::from_millis(20));
+        assert_eq!(
+            percentile(&[Duration::from_millis(7)], 95.0),
+            Duration::from_millis(7)
+        );
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_bench_engines() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("session.jsonl");
+        let mut file = File::create(&path)?;
+        for (uuid, text) in [
+            ("u1", "first error"),
+            ("u2", "all good"),
+            ("u3", "error again"),
+        ] {
+            writeln!(
+                file,
+                r#"{{"type":"user","message":{{"role":"user","content":"{text}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+            )?;
+        }
+
+        let benches = bench_engines(
+            &path.display().to_string(),
+            &parse_query("error")?,
+            &SearchOptions::default(),
+            &[Engine::Smol, Engine::Rayon],
+            3,
+            1,
+        )?;
+        assert_eq!(benches.len(), 2);
+        for bench in &benches {
+            assert_eq!(bench.runs, 3);
+            assert_eq!(bench.results, 2);
+            assert_eq!(bench.scan.files_scanned, 1);
+            assert_eq!(bench.scan.lines_parsed, 3);
+            assert!(bench.min_ms <= bench.p50_ms && bench.p50_ms <= bench.p95_ms);
+            assert!(bench.p95_ms <= bench.max_ms);
+        }
+
+        let table = format_bench_table(&benches);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("engine"));
+        assert!(table.contains("smol") && table.contains("rayon"));
+        Ok(())
+    }
+}
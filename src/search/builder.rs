@@ -0,0 +1,299 @@
+//! Entry point for embedding ccms search in other Rust tools.
+//!
+//! ```no_run
+//! use ccms::{Engine, SearchBuilder};
+//!
+//! let outcome = SearchBuilder::new()
+//!     .query("error AND retry")
+//!     .role("assistant")
+//!     .since("1 week ago")
+//!     .engine(Engine::Rayon)
+//!     .limit(20)
+//!     .run()?;
+//! for result in &outcome.results {
+//!     println!("{} {}", result.timestamp, result.text);
+//! }
+//! # anyhow::Ok(())
+//! ```
+
+use super::file_discovery::default_claude_pattern;
+use super::scan_stats::ScanStats;
+use super::{RayonEngine, SearchEngineTrait, SmolEngine};
+use crate::query::{QueryCondition, SearchOptions, SearchResult, parse_query};
+use crate::utils::time_range::parse_time;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Search engine used to scan session files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Async file reads on smol's blocking pool
+    #[default]
+    Smol,
+    /// Rayon thread pool
+    Rayon,
+}
+
+/// Number and total size of the session files a search reads
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CorpusSize {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+impl CorpusSize {
+    /// Files that cannot be read count without a size
+    pub fn of(files: &[PathBuf]) -> Self {
+        Self {
+            files: files.len(),
+            bytes: files
+                .iter()
+                .filter_map(|file| file.metadata().ok())
+                .map(|metadata| metadata.len())
+                .sum(),
+        }
+    }
+}
+
+/// Corpus size from which `--engine auto` picks Rayon over Smol.
+///
+/// Smol starts up with less overhead and wins on small corpora, while Rayon's
+/// work stealing pays off once there are many or large files to spread over
+/// the cores. `ccms bench` shows where the line falls on a given machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoEngine {
+    pub min_files: usize,
+    pub min_bytes: u64,
+}
+
+impl Default for AutoEngine {
+    fn default() -> Self {
+        Self {
+            min_files: 1_000,
+            min_bytes: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+impl AutoEngine {
+    /// Rayon once the corpus reaches either threshold, Smol below both
+    pub fn pick(&self, size: CorpusSize) -> Engine {
+        if size.files >= self.min_files || size.bytes >= self.min_bytes {
+            Engine::Rayon
+        } else {
+            Engine::Smol
+        }
+    }
+}
+
+/// Results of `SearchBuilder::run`, newest first
+#[derive(Debug, Clone)]
+pub struct SearchOutcome {
+    pub results: Vec<SearchResult>,
+    /// Matches found before `limit` was applied
+    pub total_count: usize,
+    pub duration: Duration,
+    /// What the search read to produce them
+    pub scan: ScanStats,
+}
+
+/// Fluent configuration of a search.
+///
+/// Unlike the CLI, nothing is filtered by default: every project is searched
+/// and an unset query matches every message.
+#[derive(Debug, Clone)]
+pub struct SearchBuilder {
+    pattern: Option<String>,
+    query: Option<String>,
+    since: Option<String>,
+    before: Option<String>,
+    engine: Engine,
+    options: SearchOptions,
+}
+
+impl Default for SearchBuilder {
+    fn default() -> Self {
+        Self {
+            pattern: None,
+            query: None,
+            since: None,
+            before: None,
+            engine: Engine::default(),
+            options: SearchOptions {
+                max_results: None,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl SearchBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Glob, directory or file to search (default: `~/.claude/projects/**/*.jsonl`)
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    /// Query in the CLI syntax, e.g. `error AND NOT /warn(ing)?/i`
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    /// Only messages with this role (`user`, `assistant`, `system` or `summary`)
+    pub fn role(mut self, role: impl Into<String>) -> Self {
+        self.options.role = Some(role.into());
+        self
+    }
+
+    pub fn session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.options.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Only sessions whose working directory is `project_path` or below it
+    pub fn project(mut self, project_path: impl Into<String>) -> Self {
+        self.options.project_path = Some(project_path.into());
+        self
+    }
+
+    /// Only messages from this time on, written like `--since`: a Unix
+    /// timestamp, a date or a relative time such as "2 days ago"
+    pub fn since(mut self, time: impl Into<String>) -> Self {
+        self.since = Some(time.into());
+        self
+    }
+
+    /// Only messages up to this time, written like `since`
+    pub fn before(mut self, time: impl Into<String>) -> Self {
+        self.before = Some(time.into());
+        self
+    }
+
+    pub fn engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Return at most `limit` results (default: all of them)
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.options.max_results = Some(limit);
+        self
+    }
+
+    pub fn run(self) -> Result<SearchOutcome> {
+        let query = match &self.query {
+            Some(query) => parse_query(query).with_context(|| format!("invalid query: {query}"))?,
+            None => QueryCondition::Literal {
+                pattern: String::new(),
+                case_sensitive: false,
+            },
+        };
+
+        let mut options = self.options;
+        if let Some(since) = &self.since {
+            options.after = Some(parse_time(since)?.to_rfc3339());
+        }
+        if let Some(before) = &self.before {
+            options.before = Some(parse_time(before)?.to_rfc3339());
+        }
+
+        let default_pattern = default_claude_pattern();
+        let pattern = self.pattern.as_deref().unwrap_or(&default_pattern);
+        let ((results, duration, total_count), scan) = match self.engine {
+            Engine::Smol => {
+                let engine = SmolEngine::new(options);
+                (engine.search(pattern, query)?, engine.last_scan_stats())
+            }
+            Engine::Rayon => {
+                let engine = RayonEngine::new(options);
+                (engine.search(pattern, query)?, engine.last_scan_stats())
+            }
+        };
+
+        Ok(SearchOutcome {
+            results,
+            total_count,
+            duration,
+            scan,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn line(uuid: &str, role: &str, text: &str, timestamp: &str) -> String {
+        format!(
+            r#"{{"type":"{role}","message":{{"role":"{role}","content":"{text}"}},"uuid":"{uuid}","timestamp":"{timestamp}","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/w","version":"1"}}"#
+        )
+    }
+
+    #[test]
+    fn test_builder_filters_and_limits() -> Result<()> {
+        let dir = tempdir()?;
+        let lines = [
+            line("1", "user", "retry the build", "2024-01-01T00:00:00Z"),
+            line("2", "user", "retry again", "2024-02-01T00:00:00Z"),
+            r#"{"type":"summary","summary":"retry loop","leafUuid":"3"}"#.to_string(),
+            line("4", "user", "retry once more", "2024-04-01T00:00:00Z"),
+        ];
+        std::fs::write(dir.path().join("s1.jsonl"), lines.join("\n"))?;
+        let pattern = dir.path().to_string_lossy().to_string();
+
+        for engine in [Engine::Smol, Engine::Rayon] {
+            let outcome = SearchBuilder::new()
+                .pattern(&pattern)
+                .query("retry")
+                .role("user")
+                .since("1706745600") // 2024-02-01
+                .engine(engine)
+                .limit(1)
+                .run()?;
+
+            assert_eq!(outcome.total_count, 2, "{engine:?}");
+            assert_eq!(outcome.results.len(), 1);
+            assert_eq!(outcome.results[0].uuid, "4");
+            assert_eq!(outcome.scan.files_scanned, 1);
+        }
+
+        let everything = SearchBuilder::new().pattern(&pattern).run()?;
+        assert_eq!(everything.results.len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_engine_pick() -> Result<()> {
+        let auto = AutoEngine {
+            min_files: 3,
+            min_bytes: 100,
+        };
+        let size = |files, bytes| CorpusSize { files, bytes };
+        assert_eq!(auto.pick(size(2, 99)), Engine::Smol);
+        assert_eq!(auto.pick(size(3, 0)), Engine::Rayon);
+        assert_eq!(auto.pick(size(1, 100)), Engine::Rayon);
+
+        let dir = tempdir()?;
+        let file = dir.path().join("s1.jsonl");
+        std::fs::write(&file, "0123456789")?;
+        assert_eq!(
+            CorpusSize::of(&[file, dir.path().join("missing.jsonl")]),
+            size(2, 10)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_reports_bad_input() {
+        assert!(SearchBuilder::new().query("(unclosed").run().is_err());
+        assert!(SearchBuilder::new().since("not a time").run().is_err());
+    }
+}
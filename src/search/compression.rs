@@ -0,0 +1,145 @@
+//! Transparent reading of archived `.jsonl.gz` / `.jsonl.zst` session files.
+
+use super::io::IoMode;
+use anyhow::{Context, Result};
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Extensions of compressed session files, searched alongside `.jsonl` ones
+pub const COMPRESSED_EXTENSIONS: [&str; 2] = ["gz", "zst"];
+
+/// Buffer size used by `open_session`
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    fn of(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Whether `path` is a compressed archive rather than a plain JSONL file
+pub fn is_compressed(path: &Path) -> bool {
+    Compression::of(path) != Compression::None
+}
+
+/// Line reader over an already opened session file, decompressing it on the
+/// fly when its extension says it is an archive
+pub fn session_reader(path: &Path, file: File, capacity: usize) -> Result<Box<dyn BufRead + Send>> {
+    session_reader_with(path, file, capacity, IoMode::Buffered)
+}
+
+/// `session_reader`, mapping plain files into memory with `IoMode::Mmap`
+pub fn session_reader_with(
+    path: &Path,
+    file: File,
+    capacity: usize,
+    io: IoMode,
+) -> Result<Box<dyn BufRead + Send>> {
+    Ok(match Compression::of(path) {
+        #[cfg(feature = "mmap")]
+        Compression::None if io == IoMode::Mmap => Box::new(
+            super::io::MappedFile::open(&file)
+                .with_context(|| format!("failed to map {}", path.display()))?
+                .into_reader(),
+        ),
+        #[cfg(not(feature = "mmap"))]
+        Compression::None if io == IoMode::Mmap => {
+            anyhow::bail!("--io mmap needs ccms built with the mmap feature")
+        }
+        Compression::None => Box::new(BufReader::with_capacity(capacity, file)),
+        // Multi-member, so archives built by appending gzip streams read in full
+        Compression::Gzip => Box::new(BufReader::with_capacity(
+            capacity,
+            MultiGzDecoder::new(file),
+        )),
+        Compression::Zstd => Box::new(BufReader::with_capacity(
+            capacity,
+            zstd::Decoder::new(file)
+                .with_context(|| format!("failed to read zstd archive {}", path.display()))?,
+        )),
+    })
+}
+
+/// Open a session file for reading line by line, compressed or not
+pub fn open_session(path: &Path) -> Result<Box<dyn BufRead + Send>> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    session_reader(path, file, DEFAULT_CAPACITY)
+}
+
+/// Whole contents of a session file, compressed or not
+pub fn read_session(path: &Path) -> Result<String> {
+    let mut contents = String::new();
+    open_session(path)?
+        .read_to_string(&mut contents)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compression as GzLevel, write::GzEncoder};
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    const LINES: &str = "{\"a\":1}\n{\"b\":2}\n";
+
+    fn read_lines(path: &Path) -> Vec<String> {
+        open_session(path)
+            .unwrap()
+            .lines()
+            .collect::<std::io::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_reads_plain_gzip_and_zstd() {
+        let dir = tempdir().unwrap();
+
+        let plain = dir.path().join("s.jsonl");
+        std::fs::write(&plain, LINES).unwrap();
+
+        // Two gzip members, as produced by appending to an archive
+        let gzip = dir.path().join("s.jsonl.gz");
+        let mut bytes = Vec::new();
+        for part in ["{\"a\":1}\n", "{\"b\":2}\n"] {
+            let mut encoder = GzEncoder::new(Vec::new(), GzLevel::default());
+            encoder.write_all(part.as_bytes()).unwrap();
+            bytes.extend(encoder.finish().unwrap());
+        }
+        std::fs::write(&gzip, bytes).unwrap();
+
+        let zstd = dir.path().join("s.jsonl.zst");
+        std::fs::write(&zstd, zstd::encode_all(LINES.as_bytes(), 0).unwrap()).unwrap();
+
+        for path in [&plain, &gzip, &zstd] {
+            assert_eq!(read_lines(path), vec!["{\"a\":1}", "{\"b\":2}"], "{path:?}");
+        }
+        assert!(!is_compressed(&plain));
+        assert!(is_compressed(&gzip));
+        assert!(is_compressed(&zstd));
+    }
+
+    #[test]
+    fn test_corrupt_archive_is_an_error() {
+        let dir = tempdir().unwrap();
+        let gzip = dir.path().join("s.jsonl.gz");
+        std::fs::write(&gzip, LINES).unwrap();
+
+        let mut line = String::new();
+        assert!(open_session(&gzip).unwrap().read_line(&mut line).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+//! Surrounding messages for `-A`/`-B`/`-C`: like grep's context lines, the
+//! messages just before and after each match in the same session.
+
+use crate::query::{QueryCondition, SearchResult, SharedStr};
+use crate::schemas::SessionMessage;
+use crate::search::compression::open_session;
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::BufRead;
+use std::path::Path;
+
+/// Messages around one result, oldest first on both sides
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MessageContext {
+    pub before: Vec<SearchResult>,
+    pub after: Vec<SearchResult>,
+}
+
+impl MessageContext {
+    pub fn is_empty(&self) -> bool {
+        self.before.is_empty() && self.after.is_empty()
+    }
+}
+
+/// Up to `before` and `after` messages around each of `results`, in the same
+/// order as `results`.
+///
+/// Each file with a match is read once, keeping the last `before` messages of
+/// every session in it. Only messages of the matched message's session count,
+/// so a resumed session sharing the file does not leak in. Results without a
+/// line number get no context.
+pub fn collect_context(
+    results: &[SearchResult],
+    before: usize,
+    after: usize,
+) -> Result<Vec<MessageContext>> {
+    let mut contexts = vec![MessageContext::default(); results.len()];
+    if before == 0 && after == 0 {
+        return Ok(contexts);
+    }
+
+    // Line number -> indices of the results on that line, per file
+    let mut by_file: HashMap<&str, BTreeMap<usize, Vec<usize>>> = HashMap::new();
+    for (index, result) in results.iter().enumerate() {
+        if let Some(line_number) = result.line_number {
+            by_file
+                .entry(result.file.as_str())
+                .or_default()
+                .entry(line_number)
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let found = by_file
+        .into_par_iter()
+        .map(|(file, matches)| file_context(file, &matches, results, before, after))
+        .collect::<Result<Vec<_>>>()?;
+    for (index, context) in found.into_iter().flatten() {
+        contexts[index] = context;
+    }
+    Ok(contexts)
+}
+
+fn file_context(
+    file: &str,
+    matches: &BTreeMap<usize, Vec<usize>>,
+    results: &[SearchResult],
+    before: usize,
+    after: usize,
+) -> Result<Vec<(usize, MessageContext)>> {
+    let Some(&last_match) = matches.keys().next_back() else {
+        return Ok(Vec::new());
+    };
+
+    let mut found: Vec<(usize, MessageContext)> = Vec::new();
+    let mut windows: HashMap<String, VecDeque<SearchResult>> = HashMap::new();
+    // Entries of `found` still collecting messages after their match
+    let mut pending: Vec<usize> = Vec::new();
+
+    for (i, line) in open_session(Path::new(file))?.lines().enumerate() {
+        let line_number = i + 1;
+        if line_number > last_match && pending.is_empty() {
+            break;
+        }
+        let line = line?;
+        let Ok(message) = sonic_rs::from_str::<SessionMessage>(&line) else {
+            continue;
+        };
+        let Some(session_id) = message.get_session_id() else {
+            continue;
+        };
+        let message = message_result(file, line_number, &message, session_id);
+
+        for &index in &pending {
+            let (result_index, context) = &mut found[index];
+            if results[*result_index].session_id == session_id {
+                context.after.push(message.clone());
+            }
+        }
+        pending.retain(|&index| found[index].1.after.len() < after);
+
+        let window = windows.entry(session_id.to_string()).or_default();
+        if let Some(indices) = matches.get(&line_number) {
+            for &result_index in indices {
+                let context = MessageContext {
+                    before: window.iter().cloned().collect(),
+                    after: Vec::new(),
+                };
+                if after > 0 {
+                    pending.push(found.len());
+                }
+                found.push((result_index, context));
+            }
+        }
+
+        if before > 0 {
+            if window.len() == before {
+                window.pop_front();
+            }
+            window.push_back(message);
+        }
+    }
+    Ok(found)
+}
+
+/// A message in the shape of a result, so it prints like one
+pub(crate) fn message_result(
+    file: &str,
+    line_number: usize,
+    message: &SessionMessage,
+    session_id: &str,
+) -> SearchResult {
+    let message_type = SharedStr::from(message.get_type());
+    SearchResult {
+        file: file.into(),
+        uuid: message.get_uuid().unwrap_or("").to_string(),
+        timestamp: message.get_timestamp().unwrap_or("").to_string(),
+        session_id: session_id.into(),
+        role: message_type.clone(),
+        text: message.get_content_text(),
+        message_type,
+        query: QueryCondition::And { conditions: vec![] },
+        cwd: message.get_cwd().unwrap_or("").into(),
+        raw_json: None,
+        line_number: Some(line_number),
+        matched_in: Vec::new(),
+        has_images: message.has_images(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn line(session_id: &str, uuid: &str, text: &str) -> String {
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":"{text}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:00Z","sessionId":"{session_id}","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+        )
+    }
+
+    fn result(file: &str, session_id: &str, line_number: usize) -> SearchResult {
+        SearchResult {
+            file: file.into(),
+            uuid: String::new(),
+            timestamp: String::new(),
+            session_id: session_id.into(),
+            role: "user".into(),
+            text: String::new(),
+            message_type: "user".into(),
+            query: QueryCondition::Literal {
+                pattern: "match".to_string(),
+                case_sensitive: false,
+            },
+            cwd: SharedStr::default(),
+            raw_json: None,
+            line_number: Some(line_number),
+            matched_in: Vec::new(),
+            has_images: false,
+        }
+    }
+
+    fn uuids(messages: &[SearchResult]) -> Vec<&str> {
+        messages.iter().map(|m| m.uuid.as_str()).collect()
+    }
+
+    #[test]
+    fn test_collect_context() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("s.jsonl");
+        let mut file = File::create(&path)?;
+        for l in [
+            line("s1", "u1", "one"),
+            line("s1", "u2", "two"),
+            line("other", "x1", "resumed"),
+            "not json".to_string(),
+            line("s1", "u3", "match"),
+            line("s1", "u4", "four"),
+            line("other", "x2", "resumed"),
+            line("s1", "u5", "five"),
+            line("s1", "u6", "six"),
+        ] {
+            writeln!(file, "{l}")?;
+        }
+        let file = path.display().to_string();
+
+        let results = vec![result(&file, "s1", 5), result(&file, "s1", 1)];
+        let contexts = collect_context(&results, 2, 2)?;
+        assert_eq!(uuids(&contexts[0].before), ["u1", "u2"]);
+        // The other session's messages are skipped, not counted
+        assert_eq!(uuids(&contexts[0].after), ["u4", "u5"]);
+        assert_eq!(contexts[0].after[0].line_number, Some(6));
+
+        // Nothing before the first line
+        assert!(contexts[1].before.is_empty());
+        assert_eq!(uuids(&contexts[1].after), ["u2", "u3"]);
+
+        let contexts = collect_context(&results, 0, 1)?;
+        assert!(contexts[0].before.is_empty());
+        assert_eq!(uuids(&contexts[0].after), ["u4"]);
+
+        assert!(
+            collect_context(&results, 0, 0)?
+                .iter()
+                .all(MessageContext::is_empty)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+//! File lists of session directories, kept between runs.
+//!
+//! Finding session files walks every directory under the pattern's root on
+//! each search. A directory's mtime changes whenever an entry is added,
+//! removed or renamed in it, so its listing is remembered together with that
+//! mtime and only directories whose mtime moved are read again. Files that
+//! are merely written to, as live sessions are, keep their directory's mtime.
+//!
+//! The cache is off unless `enable` is called; the binary turns it on with
+//! its file at `~/.cache/ccms/discovery.json`, and `--refresh` starts it
+//! afresh.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DISCOVERY_CACHE_VERSION: u32 = 1;
+
+/// A directory listed this soon after its last change may have been changed
+/// again within the same mtime tick, so its listing is not trusted
+const RACY_WINDOW_MS: u64 = 2_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedDir {
+    mtime_ms: u64,
+    listed_ms: u64,
+    files: Vec<PathBuf>,
+    subdirs: Vec<PathBuf>,
+}
+
+impl CachedDir {
+    fn is_fresh(&self, mtime_ms: u64) -> bool {
+        self.mtime_ms == mtime_ms && self.listed_ms >= mtime_ms + RACY_WINDOW_MS
+    }
+
+    fn same_listing(&self, other: &CachedDir) -> bool {
+        self.mtime_ms == other.mtime_ms
+            && self.files == other.files
+            && self.subdirs == other.subdirs
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveryCache {
+    version: u32,
+    dirs: HashMap<PathBuf, CachedDir>,
+}
+
+impl Default for DiscoveryCache {
+    fn default() -> Self {
+        Self {
+            version: DISCOVERY_CACHE_VERSION,
+            dirs: HashMap::new(),
+        }
+    }
+}
+
+impl DiscoveryCache {
+    pub fn default_path() -> Result<PathBuf> {
+        if let Some(path) = std::env::var_os("CCMS_DISCOVERY_CACHE_PATH") {
+            return Ok(PathBuf::from(path));
+        }
+
+        let cache_dir = dirs::cache_dir().context("failed to resolve cache directory")?;
+        Ok(cache_dir.join("ccms").join("discovery.json"))
+    }
+
+    /// The cache at `path`; a missing, unreadable or outdated file gives an
+    /// empty cache, as everything in it can be found again
+    pub fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok())
+            .filter(|cache| cache.version == DISCOVERY_CACHE_VERSION)
+            .unwrap_or_default()
+    }
+
+    /// Write the cache through a temporary file named after this process, so
+    /// processes saving at the same time each rename a complete file into place
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        fs::write(&tmp_path, serde_json::to_vec(self)?)
+            .with_context(|| format!("failed to write discovery cache: {}", tmp_path.display()))?;
+        let renamed = fs::rename(&tmp_path, path);
+        if renamed.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        renamed.with_context(|| format!("failed to write discovery cache: {}", path.display()))
+    }
+
+    pub fn dir_count(&self) -> usize {
+        self.dirs.len()
+    }
+
+    /// Every file under `root`, reading only directories changed since they
+    /// were last listed. Returns the files and whether the cache changed.
+    ///
+    /// Like the full walk, hidden entries below `root` are skipped and
+    /// symbolic links are followed, except into a directory that contains
+    /// the link.
+    pub fn files_under(&mut self, root: &Path) -> (Vec<PathBuf>, bool) {
+        let now_ms = millis(SystemTime::now());
+        let mut files = Vec::new();
+        let mut changed = false;
+        let mut visited: HashSet<PathBuf> = HashSet::new();
+        let mut pending = vec![root.to_path_buf()];
+
+        while let Some(dir) = pending.pop() {
+            let Some(mtime_ms) = fs::metadata(&dir)
+                .ok()
+                .filter(|metadata| metadata.is_dir())
+                .and_then(|metadata| metadata.modified().ok())
+                .map(millis)
+            else {
+                continue;
+            };
+            if !visited.insert(dir.clone()) {
+                continue;
+            }
+
+            let fresh = self
+                .dirs
+                .get(&dir)
+                .is_some_and(|cached| cached.is_fresh(mtime_ms));
+            if !fresh {
+                let listing = list_dir(&dir, mtime_ms, now_ms);
+                changed |= self
+                    .dirs
+                    .get(&dir)
+                    .is_none_or(|cached| !cached.same_listing(&listing));
+                self.dirs.insert(dir.clone(), listing);
+            }
+            let cached = &self.dirs[&dir];
+            files.extend(cached.files.iter().cloned());
+            pending.extend(cached.subdirs.iter().cloned());
+        }
+
+        // Directories under `root` that are gone
+        let before = self.dirs.len();
+        self.dirs
+            .retain(|dir, _| !dir.starts_with(root) || visited.contains(dir));
+        changed |= self.dirs.len() != before;
+
+        (files, changed)
+    }
+}
+
+/// Files and subdirectories of `dir`, as of `mtime_ms`
+fn list_dir(dir: &Path, mtime_ms: u64, now_ms: u64) -> CachedDir {
+    let mut files = Vec::new();
+    let mut subdirs = Vec::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_symlink() {
+            match fs::metadata(&path) {
+                Ok(target) if target.is_dir() => {
+                    // A link back up the tree would be walked forever
+                    let loops = fs::canonicalize(&path)
+                        .ok()
+                        .zip(fs::canonicalize(dir).ok())
+                        .is_none_or(|(target, dir)| dir.starts_with(target));
+                    if !loops {
+                        subdirs.push(path);
+                    }
+                }
+                Ok(target) if target.is_file() => files.push(path),
+                _ => {}
+            }
+        } else if file_type.is_dir() {
+            subdirs.push(path);
+        } else if file_type.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    subdirs.sort();
+    CachedDir {
+        mtime_ms,
+        listed_ms: now_ms,
+        files,
+        subdirs,
+    }
+}
+
+fn millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+struct Enabled {
+    path: PathBuf,
+    cache: DiscoveryCache,
+}
+
+static GLOBAL: Mutex<Option<Enabled>> = Mutex::new(None);
+
+/// Use the cache at `path` for file discovery in this process, starting
+/// from an empty one when `refresh` is set
+pub fn enable(path: PathBuf, refresh: bool) {
+    let cache = if refresh {
+        DiscoveryCache::default()
+    } else {
+        DiscoveryCache::load(&path)
+    };
+    if let Ok(mut global) = GLOBAL.lock() {
+        *global = Some(Enabled { path, cache });
+    }
+}
+
+/// Forget every listing, so the next discovery walks the whole tree
+pub fn refresh() {
+    if let Ok(mut global) = GLOBAL.lock()
+        && let Some(enabled) = global.as_mut()
+    {
+        enabled.cache = DiscoveryCache::default();
+    }
+}
+
+/// `DiscoveryCache::files_under` on the process-wide cache, saving it when
+/// it changed; `None` when the cache is not enabled
+pub fn files_under(root: &Path) -> Option<Vec<PathBuf>> {
+    let mut global = GLOBAL.lock().ok()?;
+    let enabled = global.as_mut()?;
+    let (files, changed) = enabled.cache.files_under(root);
+    if changed {
+        // Not being able to save only costs the next run a full walk
+        let _ = enabled.cache.save(&enabled.path);
+    }
+    Some(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{File, create_dir_all};
+    use tempfile::tempdir;
+
+    /// Pretend every listing happened long after its directory changed
+    fn settle(cache: &mut DiscoveryCache) {
+        for dir in cache.dirs.values_mut() {
+            dir.listed_ms = dir.mtime_ms + RACY_WINDOW_MS;
+        }
+    }
+
+    fn sorted(mut files: Vec<PathBuf>) -> Vec<PathBuf> {
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_files_under() -> Result<()> {
+        let dir = tempdir()?;
+        let root = dir.path();
+        create_dir_all(root.join("a/nested"))?;
+        create_dir_all(root.join("b"))?;
+        create_dir_all(root.join(".hidden"))?;
+        File::create(root.join("a/one.jsonl"))?;
+        File::create(root.join("a/nested/two.jsonl"))?;
+        File::create(root.join("b/three.jsonl"))?;
+        File::create(root.join("b/.skipped.jsonl"))?;
+        File::create(root.join(".hidden/four.jsonl"))?;
+
+        let mut cache = DiscoveryCache::default();
+        let (files, changed) = cache.files_under(root);
+        assert!(changed);
+        assert_eq!(
+            sorted(files),
+            vec![
+                root.join("a/nested/two.jsonl"),
+                root.join("a/one.jsonl"),
+                root.join("b/three.jsonl"),
+            ]
+        );
+        assert_eq!(cache.dir_count(), 4);
+
+        // Listings just taken are read again, as the directory may still be
+        // changing within its mtime tick
+        let (_, changed) = cache.files_under(root);
+        assert!(!changed);
+
+        settle(&mut cache);
+        let (files, changed) = cache.files_under(root);
+        assert!(!changed);
+        assert_eq!(files.len(), 3);
+
+        // A stale listing is what gets served for a settled directory...
+        let listing = cache.dirs.get_mut(&root.join("b")).unwrap();
+        listing.files.clear();
+        let (files, _) = cache.files_under(root);
+        assert_eq!(files.len(), 2);
+
+        // ...until its mtime moves
+        let listing = cache.dirs.get_mut(&root.join("b")).unwrap();
+        listing.mtime_ms -= 1;
+        let (files, changed) = cache.files_under(root);
+        assert!(changed);
+        assert_eq!(files.len(), 3);
+
+        // Removed directories leave the cache
+        fs::remove_dir_all(root.join("a"))?;
+        let (files, changed) = cache.files_under(root);
+        assert!(changed);
+        assert_eq!(files, vec![root.join("b/three.jsonl")]);
+        assert_eq!(cache.dir_count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_and_load() -> Result<()> {
+        let dir = tempdir()?;
+        File::create(dir.path().join("one.jsonl"))?;
+        let path = dir.path().join("cache/discovery.json");
+
+        let mut cache = DiscoveryCache::default();
+        cache.files_under(dir.path());
+        cache.save(&path)?;
+        assert_eq!(DiscoveryCache::load(&path), cache);
+        // The temporary file was renamed into place
+        assert_eq!(fs::read_dir(path.parent().unwrap())?.count(), 1);
+
+        fs::write(&path, "{\"version\":0,\"dirs\":{}}")?;
+        assert_eq!(DiscoveryCache::load(&path).dir_count(), 0);
+        assert_eq!(
+            DiscoveryCache::load(&dir.path().join("missing.json")).dir_count(),
+            0
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loops_are_not_followed() -> Result<()> {
+        let dir = tempdir()?;
+        let root = dir.path();
+        create_dir_all(root.join("a"))?;
+        File::create(root.join("a/one.jsonl"))?;
+        std::os::unix::fs::symlink(root, root.join("a/up"))?;
+        std::os::unix::fs::symlink(root.join("a"), root.join("linked"))?;
+
+        let (files, _) = DiscoveryCache::default().files_under(root);
+        assert_eq!(
+            sorted(files),
+            vec![root.join("a/one.jsonl"), root.join("linked/one.jsonl")]
+        );
+        Ok(())
+    }
+}
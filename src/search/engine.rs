@@ -1,5 +1,6 @@
 use crate::interactive_ratatui::domain::models::SearchOrder;
 use crate::query::{QueryCondition, SearchResult};
+use crate::utils::git_repo::repo_name;
 use anyhow::Result;
 use chrono::DateTime;
 
@@ -47,19 +48,33 @@ pub fn format_search_result(result: &SearchResult, use_color: bool, full_text: b
         format_preview(&result.text, &result.query, 150)
     };
 
+    let matched_in = if result.matched_in.is_empty() {
+        String::new()
+    } else {
+        let names: Vec<&str> = result.matched_in.iter().map(|s| s.name()).collect();
+        format!(" (matched in {})", names.join(", "))
+    };
+
+    // Name of the git repository the session ran in
+    let repo = repo_name(&result.cwd)
+        .map(|name| format!("{name} "))
+        .unwrap_or_default();
+
     if use_color {
         format!(
-            "{} {} [{}] {}\n  {}",
+            "{} {} {}[{}] {}{}\n  {}",
             timestamp.bright_blue(),
             result.role.bright_yellow(),
+            repo.cyan(),
             result.file.bright_green(),
             result.uuid.dimmed(),
+            matched_in.bright_magenta(),
             text_preview
         )
     } else {
         format!(
-            "{} {} [{}] {}\n  {}",
-            timestamp, result.role, result.file, result.uuid, text_preview
+            "{} {} {}[{}] {}{}\n  {}",
+            timestamp, result.role, repo, result.file, result.uuid, matched_in, text_preview
         )
     }
 }
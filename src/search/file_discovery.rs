@@ -1,3 +1,5 @@
+use super::compression::COMPRESSED_EXTENSIONS;
+use super::discovery_cache;
 use anyhow::{Context, Result};
 use dirs::home_dir;
 use globset::{Glob, GlobSet, GlobSetBuilder};
@@ -29,7 +31,7 @@ impl FileDiscovery {
 
     pub fn discover_files(&self, base_path: &Path) -> Result<Vec<PathBuf>> {
         // Use jwalk for high-performance parallel file discovery
-        let mut files: Vec<PathBuf> = WalkDir::new(base_path)
+        let files: Vec<PathBuf> = WalkDir::new(base_path)
             .parallelism(jwalk::Parallelism::RayonNewPool(0)) // Use all CPUs
             .follow_links(true)
             .into_iter()
@@ -38,18 +40,32 @@ impl FileDiscovery {
             .map(|e| e.path())
             .collect();
 
-        // Sort by modification time (newest first)
-        files.sort_by_cached_key(|path| {
-            std::fs::metadata(path)
-                .and_then(|m| m.modified())
-                .map(std::cmp::Reverse)
-                .ok()
-        });
+        Ok(newest_first(files))
+    }
 
-        Ok(files)
+    /// Like `discover_files`, but only reads the directories under
+    /// `base_path` that changed since the discovery cache last listed them;
+    /// `None` when the cache is not enabled
+    pub fn discover_cached_files(&self, base_path: &Path) -> Option<Vec<PathBuf>> {
+        let files = discovery_cache::files_under(base_path)?
+            .into_iter()
+            .filter(|path| self.glob_set.is_match(path))
+            .collect();
+        Some(newest_first(files))
     }
 }
 
+/// Sort by modification time (newest first)
+fn newest_first(mut files: Vec<PathBuf>) -> Vec<PathBuf> {
+    files.sort_by_cached_key(|path| {
+        std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map(std::cmp::Reverse)
+            .ok()
+    });
+    files
+}
+
 pub fn expand_tilde(path: &str) -> PathBuf {
     if let Some(stripped) = path.strip_prefix("~/")
         && let Some(home) = home_dir()
@@ -63,31 +79,58 @@ pub fn default_claude_pattern() -> String {
     "~/.claude/projects/**/*.jsonl".to_string()
 }
 
-pub fn discover_claude_files(pattern: Option<&str>) -> Result<Vec<PathBuf>> {
-    let default_pattern = default_claude_pattern();
-    let pattern = pattern.unwrap_or(&default_pattern);
+/// Split a pattern into the directory to walk and the glob files must match.
+/// A pattern naming a single file has no glob.
+fn split_pattern(pattern: &str) -> (PathBuf, Option<String>) {
     let expanded_path = expand_tilde(pattern);
 
-    // Extract base path and glob pattern
     let path_str = expanded_path.to_string_lossy();
-    let (base_path, glob_pattern) = if let Some(pos) = path_str.find("**") {
+    if let Some(pos) = path_str.find("**") {
         let base = &path_str[..pos];
-        (PathBuf::from(base), path_str.to_string())
+        (PathBuf::from(base), Some(path_str.to_string()))
     } else if let Some(pos) = path_str.find('*') {
         let base = &path_str[..pos];
         let parent = Path::new(base).parent().unwrap_or(Path::new("/"));
-        (parent.to_path_buf(), path_str.to_string())
+        (parent.to_path_buf(), Some(path_str.to_string()))
     } else if expanded_path.is_dir() {
         // If it's a directory, append the jsonl pattern
         let glob_pattern = format!("{}/**/*.jsonl", expanded_path.display());
-        (expanded_path, glob_pattern)
+        (expanded_path, Some(glob_pattern))
     } else {
+        (expanded_path, None)
+    }
+}
+
+/// Path under which every file matched by `pattern` lives (the file itself
+/// for a single-file pattern)
+pub fn pattern_root(pattern: Option<&str>) -> PathBuf {
+    let default_pattern = default_claude_pattern();
+    split_pattern(pattern.unwrap_or(&default_pattern)).0
+}
+
+pub fn discover_claude_files(pattern: Option<&str>) -> Result<Vec<PathBuf>> {
+    let default_pattern = default_claude_pattern();
+    let pattern = pattern.unwrap_or(&default_pattern);
+
+    let (base_path, glob_pattern) = split_pattern(pattern);
+    let Some(glob_pattern) = glob_pattern else {
         // No glob pattern, treat as single file
-        return Ok(vec![expanded_path]);
+        return Ok(vec![base_path]);
     };
 
-    let discovery = FileDiscovery::from_pattern(&glob_pattern)?;
-    discovery.discover_files(&base_path)
+    // Archived sessions sit next to the live ones as .jsonl.gz / .jsonl.zst
+    let mut patterns = vec![glob_pattern.clone()];
+    if glob_pattern.ends_with(".jsonl") {
+        for extension in COMPRESSED_EXTENSIONS {
+            patterns.push(format!("{glob_pattern}.{extension}"));
+        }
+    }
+
+    let discovery = FileDiscovery::new(patterns)?;
+    match discovery.discover_cached_files(&base_path) {
+        Some(files) => Ok(files),
+        None => discovery.discover_files(&base_path),
+    }
 }
 
 #[cfg(test)]
@@ -161,4 +204,45 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_discover_includes_compressed_sessions() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let base_path = temp_dir.path();
+
+        for name in ["a.jsonl", "b.jsonl.gz", "c.jsonl.zst", "d.json.gz", "e.gz"] {
+            File::create(base_path.join(name))?;
+        }
+
+        let mut names: Vec<String> = discover_claude_files(Some(&base_path.to_string_lossy()))?
+            .iter()
+            .map(|file| file.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["a.jsonl", "b.jsonl.gz", "c.jsonl.zst"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern_root() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let base = temp_dir.path().display().to_string();
+
+        assert_eq!(
+            pattern_root(Some(&format!("{base}/projects/**/*.jsonl"))),
+            PathBuf::from(format!("{base}/projects/"))
+        );
+        assert_eq!(
+            pattern_root(Some(&format!("{base}/projects/session-*.jsonl"))),
+            PathBuf::from(format!("{base}/projects"))
+        );
+        assert_eq!(pattern_root(Some(&base)), temp_dir.path());
+        assert_eq!(
+            pattern_root(Some(&format!("{base}/one.jsonl"))),
+            temp_dir.path().join("one.jsonl")
+        );
+
+        Ok(())
+    }
 }
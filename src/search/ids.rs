@@ -0,0 +1,162 @@
+//! Short ids: a unique prefix of a session id or message uuid stands for the
+//! whole id wherever one is given (`--session-id`, `--message-id`,
+//! `ccms share`, the `serve` frontends).
+
+use crate::search::compression::open_session;
+use anyhow::{Result, bail};
+use rayon::prelude::*;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+/// Length of a full UUID; ids this long are taken as given
+const FULL_ID_LEN: usize = 36;
+
+/// Candidates listed in an ambiguity error before the rest are counted
+const MAX_LISTED_CANDIDATES: usize = 10;
+
+/// The session id in `files` that `id` is a prefix of
+pub fn resolve_session_id(files: &[PathBuf], id: &str) -> Result<String> {
+    resolve(files, id, "sessionId", "session id")
+}
+
+/// The message uuid in `files` that `id` is a prefix of
+pub fn resolve_message_id(files: &[PathBuf], id: &str) -> Result<String> {
+    resolve(files, id, "uuid", "message id")
+}
+
+/// Expand `id` to the one value of `field` it is a prefix of.
+///
+/// An exact match wins over longer ids sharing the prefix. An id matching
+/// nothing is returned unchanged so callers report it as not found the way
+/// they always have. Files that cannot be read are skipped with a warning,
+/// as the search engines skip them, rather than failing the lookup.
+fn resolve(files: &[PathBuf], id: &str, field: &str, label: &str) -> Result<String> {
+    if id.is_empty() || id.len() >= FULL_ID_LEN {
+        return Ok(id.to_string());
+    }
+
+    let scanned: Vec<(&PathBuf, Result<BTreeSet<String>>)> = files
+        .par_iter()
+        .map(|file| (file, ids_with_prefix(file, id, field)))
+        .collect();
+    let mut candidates = BTreeSet::new();
+    for (file, found) in scanned {
+        match found {
+            Ok(found) => candidates.extend(found),
+            Err(e) => eprintln!("Warning: skipping {}: {e:#}", file.display()),
+        }
+    }
+
+    if candidates.contains(id) {
+        return Ok(id.to_string());
+    }
+    let candidates: Vec<String> = candidates.into_iter().collect();
+    match candidates.as_slice() {
+        [] => Ok(id.to_string()),
+        [only] => Ok(only.clone()),
+        _ => {
+            let listed = &candidates[..candidates.len().min(MAX_LISTED_CANDIDATES)];
+            let mut message = format!(
+                "{label} '{id}' is ambiguous; it matches {}:\n  {}",
+                candidates.len(),
+                listed.join("\n  ")
+            );
+            if candidates.len() > listed.len() {
+                message.push_str(&format!(
+                    "\n  ... and {} more",
+                    candidates.len() - listed.len()
+                ));
+            }
+            bail!(message)
+        }
+    }
+}
+
+fn ids_with_prefix(file: &Path, prefix: &str, field: &str) -> Result<BTreeSet<String>> {
+    let mut found = BTreeSet::new();
+    for line in open_session(file)?.lines() {
+        let line = line?;
+        // Cheap pre-filter before paying for a full JSON parse
+        if !line.contains(prefix) {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if let Some(id) = value.get(field).and_then(|v| v.as_str())
+            && id.starts_with(prefix)
+        {
+            found.insert(id.to_string());
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn write_session(path: &Path, session_id: &str, uuids: &[&str]) -> Result<()> {
+        let mut file = File::create(path)?;
+        for uuid in uuids {
+            writeln!(
+                file,
+                r#"{{"type":"user","message":{{"role":"user","content":"hello"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:00Z","sessionId":"{session_id}","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_prefixes() -> Result<()> {
+        let dir = tempdir()?;
+        let first = dir.path().join("a.jsonl");
+        let second = dir.path().join("b.jsonl");
+        write_session(&first, "3f2a9c10-aaaa", &["7b1e-1", "7b1e-2"])?;
+        write_session(&second, "3f2b0000-bbbb", &["c0ffee-1"])?;
+        let files = vec![first, second];
+
+        assert_eq!(resolve_session_id(&files, "3f2a")?, "3f2a9c10-aaaa");
+        assert_eq!(resolve_message_id(&files, "c0f")?, "c0ffee-1");
+        assert_eq!(resolve_message_id(&files, "7b1e-2")?, "7b1e-2");
+        // Unknown ids are left for the caller to report
+        assert_eq!(resolve_session_id(&files, "ffff")?, "ffff");
+
+        let error = resolve_session_id(&files, "3f2").unwrap_err().to_string();
+        assert!(error.contains("session id '3f2' is ambiguous; it matches 2"));
+        assert!(error.contains("3f2a9c10-aaaa") && error.contains("3f2b0000-bbbb"));
+        assert!(resolve_message_id(&files, "7b1e").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exact_match_wins() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("s.jsonl");
+        write_session(&path, "session1", &["u1"])?;
+        let other = dir.path().join("t.jsonl");
+        write_session(&other, "session10", &["u2"])?;
+
+        let files = vec![path, other];
+        assert_eq!(resolve_session_id(&files, "session1")?, "session1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_unreadable_files_are_skipped() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("s.jsonl");
+        write_session(&path, "3f2a9c10-aaaa", &["u1"])?;
+        let corrupt = dir.path().join("broken.jsonl.gz");
+        std::fs::write(&corrupt, b"not gzip at all")?;
+
+        let files = vec![dir.path().join("missing.jsonl"), corrupt, path];
+        assert_eq!(resolve_session_id(&files, "3f2a")?, "3f2a9c10-aaaa");
+        Ok(())
+    }
+}
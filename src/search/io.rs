@@ -0,0 +1,113 @@
+//! How session files are read: through a read buffer, or memory-mapped
+//! (`--io mmap`, behind the `mmap` feature).
+//!
+//! Mapping a file lets lines be read straight out of the page cache instead
+//! of being copied through a read buffer first, which pays off for
+//! multi-hundred-megabyte sessions on fast disks. A file truncated while it
+//! is mapped can bring the process down with SIGBUS, so mapping stays opt-in.
+
+#[cfg(feature = "mmap")]
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "mmap")]
+use std::fs::File;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum IoMode {
+    /// Read through a buffer with regular read calls
+    #[default]
+    Buffered,
+    /// Map plain files into memory; archives are still read through a buffer
+    Mmap,
+}
+
+impl IoMode {
+    /// Whether this build of ccms can read files this way
+    pub fn is_available(self) -> bool {
+        match self {
+            IoMode::Buffered => true,
+            IoMode::Mmap => cfg!(feature = "mmap"),
+        }
+    }
+}
+
+/// A read-only memory map of a whole session file
+#[cfg(feature = "mmap")]
+pub struct MappedFile {
+    map: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedFile {
+    pub fn open(file: &File) -> Result<Self> {
+        // SAFETY: the map is only ever read. Session files are appended to,
+        // not rewritten in place; a concurrent truncation is the caveat
+        // documented on `IoMode::Mmap`.
+        let map = unsafe { memmap2::Mmap::map(file)? };
+        Ok(Self { map })
+    }
+
+    /// Lines of the file without their `\n`, borrowed from the map; a last
+    /// line without a newline is included
+    pub fn lines(&self) -> impl Iterator<Item = &[u8]> {
+        self.map
+            .split_inclusive(|&byte| byte == b'\n')
+            .map(|line| line.strip_suffix(b"\n").unwrap_or(line))
+    }
+
+    /// A `BufRead` over the map, for code written against readers
+    pub fn into_reader(self) -> std::io::Cursor<memmap2::Mmap> {
+        std::io::Cursor::new(self.map)
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+mod tests {
+    use super::*;
+    use crate::{SearchEngineTrait, SearchOptions, SmolEngine, parse_query};
+    use std::io::BufRead;
+
+    #[test]
+    fn test_mapped_lines() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("s.jsonl");
+        std::fs::write(&path, "{\"a\":1}\n\n{\"b\":2}")?;
+
+        let mapped = MappedFile::open(&File::open(&path)?)?;
+        let lines: Vec<&[u8]> = mapped.lines().collect();
+        assert_eq!(lines, vec![&b"{\"a\":1}"[..], b"", b"{\"b\":2}"]);
+        assert_eq!(mapped.into_reader().lines().count(), 3);
+
+        std::fs::write(&path, "")?;
+        let empty = MappedFile::open(&File::open(&path)?)?;
+        assert_eq!(empty.lines().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_engine_reads_mapped_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("s.jsonl");
+        let message = |uuid: &str, text: &str| {
+            format!(
+                r#"{{"type":"user","message":{{"role":"user","content":"{text}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+            )
+        };
+        // The last line has no newline
+        std::fs::write(
+            &path,
+            message("1", "deploy the app") + "\n" + &message("2", "deploy again"),
+        )?;
+
+        let engine = SmolEngine::new(SearchOptions {
+            io: IoMode::Mmap,
+            ..Default::default()
+        });
+        let (results, _, _) = engine.search(path.to_str().unwrap(), parse_query("deploy")?)?;
+        let mut lines: Vec<usize> = results.iter().filter_map(|r| r.line_number).collect();
+        lines.sort();
+        assert_eq!(lines, vec![1, 2]);
+        Ok(())
+    }
+}
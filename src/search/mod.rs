@@ -1,9 +1,22 @@
+pub mod bench;
+pub mod builder;
+pub mod compression;
+pub mod context;
+pub mod discovery_cache;
 pub mod engine;
 pub mod file_discovery;
+pub mod ids;
+pub mod io;
 pub mod rayon_engine;
+pub mod result_cache;
+pub mod scan_stats;
 pub mod smol_engine;
+pub mod timings;
 
+pub use builder::{AutoEngine, CorpusSize, Engine, SearchBuilder, SearchOutcome};
 pub use engine::{SearchEngineTrait, format_search_result};
-pub use file_discovery::{default_claude_pattern, discover_claude_files, expand_tilde};
+pub use file_discovery::{
+    default_claude_pattern, discover_claude_files, expand_tilde, pattern_root,
+};
 pub use rayon_engine::RayonEngine;
 pub use smol_engine::SmolEngine;
@@ -1,25 +1,67 @@
 use anyhow::Result;
 use chrono::DateTime;
-use crossbeam::channel;
+use rayon::prelude::*;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufRead;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::Mutex;
 
+use super::compression::session_reader_with;
 use super::engine::SearchEngineTrait;
 use super::file_discovery::{discover_claude_files, expand_tilde};
+use super::scan_stats::{ScanCounters, ScanStats};
+use super::timings::{FileTimer, Timings};
+use crate::index::load_for_search;
 use crate::interactive_ratatui::domain::models::SearchOrder;
-use crate::query::{QueryCondition, SearchOptions, SearchResult};
+use crate::metrics;
+use crate::outcome::OutcomeTracker;
+use crate::query::condition::{SECTION_SEPARATOR, SessionBounds};
+use crate::query::{Interner, QueryCondition, SearchOptions, SearchResult};
 use crate::schemas::SessionMessage;
+use crate::tool_calls::ToolCalls;
 use crate::utils::path_encoding;
 
 pub struct RayonEngine {
     options: SearchOptions,
+    last_scan: Mutex<ScanStats>,
+    last_timings: Mutex<Timings>,
 }
 
 impl RayonEngine {
     pub fn new(options: SearchOptions) -> Self {
-        Self { options }
+        Self {
+            options,
+            last_scan: Mutex::new(ScanStats::default()),
+            last_timings: Mutex::new(Timings::default()),
+        }
+    }
+
+    /// What the most recent search read
+    pub fn last_scan_stats(&self) -> ScanStats {
+        self.last_scan
+            .lock()
+            .map(|stats| *stats)
+            .unwrap_or_default()
+    }
+
+    /// Where the time of the most recent search went
+    pub fn last_timings(&self) -> Timings {
+        self.last_timings
+            .lock()
+            .map(|timings| *timings)
+            .unwrap_or_default()
+    }
+
+    fn record_timings(&self, timings: Timings) {
+        if let Ok(mut last_timings) = self.last_timings.lock() {
+            *last_timings = timings;
+        }
+    }
+
+    fn record_scan(&self, counters: &ScanCounters) {
+        if let Ok(mut last_scan) = self.last_scan.lock() {
+            *last_scan = counters.snapshot();
+        }
     }
 }
 
@@ -49,6 +91,7 @@ impl SearchEngineTrait for RayonEngine {
         order: SearchOrder,
     ) -> Result<(Vec<SearchResult>, std::time::Duration, usize)> {
         let start_time = std::time::Instant::now();
+        let query = self.options.scope_query(query);
 
         // Discover files
         let file_discovery_start = std::time::Instant::now();
@@ -68,60 +111,68 @@ impl SearchEngineTrait for RayonEngine {
             );
         }
 
+        let counters = ScanCounters::default();
         if files.is_empty() {
+            self.record_scan(&counters);
+            self.record_timings(Timings {
+                discovery: file_discovery_time,
+                total: start_time.elapsed(),
+                ..Default::default()
+            });
             return Ok((Vec::new(), start_time.elapsed(), 0));
         }
 
-        // Channel for collecting results
-        let (sender, receiver) = channel::unbounded();
-
-        // Process files in parallel using Rayon
+        // Process files in parallel
         let search_start = std::time::Instant::now();
 
-        let query = Arc::new(query);
-        let options = Arc::new(self.options.clone());
+        let index = load_for_search(&self.options);
+        if self.options.verbose
+            && let Some(index) = index.as_ref()
+        {
+            eprintln!("Using search index ({} files)", index.files.len());
+        }
 
-        // Process files in parallel
-        rayon::scope(|s| {
-            for file_path in files {
-                let sender = sender.clone();
-                let query = query.clone();
-                let options = options.clone();
-
-                s.spawn(move |_| {
-                    if let Ok(results) = search_file(&file_path, &query, &options) {
-                        for result in results {
-                            let _ = sender.send(result);
-                        }
-                    }
+        // Each rayon task reuses one line buffer for the files it searches,
+        // and the per-file results are merged once at the end
+        let interner = Interner::new();
+        let per_file: Vec<Vec<SearchResult>> = files
+            .par_iter()
+            .map_init(Vec::new, |line_buffer, file_path| {
+                let indexed = index.as_ref().and_then(|index| {
+                    index.search_file(file_path, &query, &self.options, &interner)
                 });
-            }
-        });
-
-        // Drop the original sender so the receiver knows when all tasks are done
-        drop(sender);
-
-        // Collect all results
-        let mut all_results = Vec::new();
-        while let Ok(result) = receiver.recv() {
-            all_results.push(result);
+                match indexed {
+                    Some(results) => {
+                        counters.cache_hit();
+                        results
+                    }
+                    None => search_file(
+                        file_path,
+                        &query,
+                        &self.options,
+                        &counters,
+                        &interner,
+                        line_buffer,
+                    )
+                    .unwrap_or_default(),
+                }
+            })
+            .collect();
+        let mut all_results = Vec::with_capacity(per_file.iter().map(Vec::len).sum());
+        for results in per_file {
+            all_results.extend(results);
         }
 
         let search_time = search_start.elapsed();
+        let sort_start = std::time::Instant::now();
 
         // Apply filters
         self.apply_filters(&mut all_results, role_filter)?;
 
-        // Sort by timestamp
-        match order {
-            SearchOrder::Descending => {
-                all_results.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-            }
-            SearchOrder::Ascending => {
-                all_results.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-            }
-        }
+        order.sort(&mut all_results);
 
+        self.options.dedupe(&mut all_results);
+        self.options.cap_per_session(&mut all_results);
         let total_count = all_results.len();
 
         // Only truncate if max_results is specified
@@ -129,13 +180,22 @@ impl SearchEngineTrait for RayonEngine {
             all_results.truncate(limit);
         }
 
+        let sort_time = sort_start.elapsed();
         let elapsed = start_time.elapsed();
+        self.record_scan(&counters);
+        self.record_timings(Timings {
+            discovery: file_discovery_time,
+            sort: sort_time,
+            total: elapsed,
+            ..counters.timings()
+        });
 
         if self.options.verbose {
             eprintln!("\nPerformance breakdown:");
             eprintln!("  File discovery: {}ms", file_discovery_time.as_millis());
             eprintln!("  Search: {}ms", search_time.as_millis());
             eprintln!("  Total: {}ms", elapsed.as_millis());
+            eprintln!("  Scanned: {}", self.last_scan_stats().summary());
         }
 
         Ok((all_results, elapsed, total_count))
@@ -159,9 +219,13 @@ impl RayonEngine {
         }
 
         // Apply session filter
-        if let Some(ref session_id) = self.options.session_id {
-            results.retain(|r| &r.session_id == session_id);
-        }
+        results.retain(|r| {
+            self.options
+                .matches_session(Some(r.session_id.as_str()).filter(|id| !id.is_empty()))
+        });
+
+        // Apply exclusion filters
+        results.retain(|r| !self.options.excludes(r));
 
         // Apply time filters
         if let Some(ref after) = self.options.after
@@ -188,16 +252,23 @@ impl RayonEngine {
     }
 }
 
-// Helper function to search a single file synchronously
+/// Search a single file synchronously, reading lines into `line_buffer` and
+/// sharing the strings of its results through `interner`
 pub(super) fn search_file(
     file_path: &Path,
     query: &QueryCondition,
     options: &SearchOptions,
+    counters: &ScanCounters,
+    interner: &Interner,
+    line_buffer: &mut Vec<u8>,
 ) -> Result<Vec<SearchResult>> {
+    let file_name = interner.intern(&file_path.display().to_string());
+    let mut strings = interner.local();
+    let mut timer = FileTimer::start();
     let file = File::open(file_path)?;
     let metadata = file.metadata()?;
     // Use same buffer size as Smol for fair comparison
-    let mut reader = BufReader::with_capacity(64 * 1024, file);
+    let mut reader = session_reader_with(file_path, file, 64 * 1024, options.io)?;
 
     // Get file creation time for fallback
     // Use platform-specific approach like main branch
@@ -233,19 +304,32 @@ pub(super) fn search_file(
             now
         });
 
+    metrics::FILES_INDEXED.inc();
+    counters.file_scanned();
     let mut results = Vec::with_capacity(256); // Same capacity as Smol
     let mut latest_timestamp: Option<String> = None;
     let mut first_timestamp: Option<String> = None;
-    let mut line_buffer = Vec::with_capacity(16 * 1024); // Same buffer size as Smol
+    line_buffer.reserve(16 * 1024); // Same buffer size as Smol
     let mut is_first_line = true;
     let mut found_summary_first = false;
+    let mut line_number = 0usize;
+    let sections = options.sections_for(query);
+    let positions = options.positions_for(query);
+    let mut bounds = SessionBounds::default();
+    let outcomes = query.outcomes();
+    let kinds = query.kinds();
+    let uses_timestamp = query.uses_timestamp();
+    let mut tracker = OutcomeTracker::default();
+    let mut tool_calls = ToolCalls::default();
 
     loop {
         line_buffer.clear();
-        let bytes_read = reader.read_until(b'\n', &mut line_buffer)?;
+        let bytes_read = timer.read(|| reader.read_until(b'\n', line_buffer))?;
         if bytes_read == 0 {
             break; // EOF
         }
+        counters.bytes_read(bytes_read);
+        line_number += 1;
 
         // Skip empty lines
         if line_buffer.trim_ascii().is_empty() {
@@ -262,7 +346,8 @@ pub(super) fn search_file(
 
         // Parse JSON - Always use sonic-rs for optimized engine
         // Use from_slice to avoid UTF-8 string conversion
-        let message: Result<SessionMessage, _> = sonic_rs::from_slice(&line_buffer);
+        let message: Result<SessionMessage, _> = timer.parse(|| sonic_rs::from_slice(line_buffer));
+        counters.line_parsed(message.is_ok());
 
         match message {
             Ok(message) => {
@@ -291,11 +376,54 @@ pub(super) fn search_file(
                     }
                 }
 
+                if !positions.is_empty() && message.get_type() != "summary" {
+                    bounds.record(line_number);
+                }
+                if !outcomes.is_empty() {
+                    tracker.observe(&message);
+                }
+                if options.tool.is_some() {
+                    tool_calls.observe(&message);
+                }
+
+                if !options.admits(&kinds, message.is_sidechain(), message.is_meta()) {
+                    continue;
+                }
+                if let Some(tool) = &options.tool
+                    && !tool_calls.matches(tool, &message)
+                {
+                    continue;
+                }
+
+                // Most messages do not match; rule them out from the
+                // borrowed text before copying it into one string
+                if sections.is_empty() && query.rules_out(&message.searchable_segments()) {
+                    continue;
+                }
+
                 // Get searchable text
-                let text = message.get_searchable_text();
+                let mut text = message.get_searchable_text_with(&sections);
+
+                let resolve_timestamp = || {
+                    if message.get_type() == "summary" {
+                        // Use first non-summary timestamp or file ctime
+                        first_timestamp
+                            .as_ref()
+                            .or(latest_timestamp.as_ref())
+                            .cloned()
+                            .unwrap_or_else(|| file_ctime.clone())
+                    } else {
+                        message
+                            .get_timestamp()
+                            .map(|s| s.to_string())
+                            .unwrap_or_else(|| file_ctime.clone())
+                    }
+                };
+                // Time terms compare against the timestamp the result is shown with
+                let query_timestamp = uses_timestamp.then(resolve_timestamp);
 
                 // Apply query condition
-                if let Ok(matches) = query.evaluate(&text)
+                if let Ok(matches) = query.evaluate_at(&text, query_timestamp.as_deref())
                     && matches
                 {
                     // Apply inline filters
@@ -310,9 +438,11 @@ pub(super) fn search_file(
                         }
                     }
 
-                    if let Some(session_id) = &options.session_id
-                        && message.get_session_id() != Some(session_id)
-                    {
+                    if !options.matches_session(message.get_session_id()) {
+                        continue;
+                    }
+
+                    if options.exclude_tool_result_messages && message.is_tool_result_only() {
                         continue;
                     }
 
@@ -325,42 +455,40 @@ pub(super) fn search_file(
                     }
 
                     // Create result
-                    let timestamp = if message.get_type() == "summary" {
-                        // Use first non-summary timestamp or file ctime
-                        first_timestamp
-                            .as_ref()
-                            .or(latest_timestamp.as_ref())
-                            .cloned()
-                            .unwrap_or_else(|| file_ctime.clone())
-                    } else {
-                        message
-                            .get_timestamp()
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|| file_ctime.clone())
-                    };
+                    let timestamp = query_timestamp.unwrap_or_else(resolve_timestamp);
 
                     // For SessionViewer and message details, we need raw_json
                     let raw_json = if options.session_id.is_some() || options.message_id.is_some() {
                         // Convert line_buffer to String for raw_json
-                        Some(String::from_utf8_lossy(&line_buffer).to_string())
+                        Some(String::from_utf8_lossy(line_buffer).to_string())
                     } else {
                         None
                     };
+                    let matched_in = query.matched_sections(&text, Some(&timestamp));
+                    // Sections are for matching only, not for display
+                    if let Some(end) = text.find(SECTION_SEPARATOR) {
+                        text.truncate(end);
+                    }
+                    let message_type = strings.intern(message.get_type());
                     results.push(SearchResult {
                         timestamp,
-                        role: message.get_type().to_string(),
+                        role: message_type.clone(),
                         text,
-                        file: file_path.display().to_string(),
+                        file: file_name.clone(),
                         uuid: message.get_uuid().unwrap_or("").to_string(),
-                        session_id: message.get_session_id().unwrap_or("").to_string(),
+                        session_id: strings.intern(message.get_session_id().unwrap_or("")),
                         query: query.clone(),
-                        cwd: message.get_cwd().unwrap_or("").to_string(),
-                        message_type: message.get_type().to_string(),
+                        cwd: strings.intern(message.get_cwd().unwrap_or("")),
+                        message_type,
                         raw_json,
+                        line_number: Some(line_number),
+                        matched_in,
+                        has_images: message.has_images(),
                     });
                 }
             }
             Err(e) => {
+                metrics::PARSE_ERRORS.inc();
                 if options.verbose {
                     eprintln!("Failed to parse JSON in {file_path:?}: {e}");
                 }
@@ -369,14 +497,28 @@ pub(super) fn search_file(
         }
     }
 
+    if !positions.is_empty() {
+        results.retain(|result| bounds.holds(&positions, result.line_number));
+    }
+    if !outcomes.is_empty()
+        && !tracker
+            .outcome()
+            .is_some_and(|outcome| outcomes.iter().all(|wanted| *wanted == outcome))
+    {
+        results.clear();
+    }
+
+    counters.file_timed(timer);
     Ok(results)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::query::condition::TextSection;
     use crate::query::parse_query;
     use std::io::Write;
+    use std::time::Duration;
     use tempfile::tempdir;
 
     #[test]
@@ -404,6 +546,192 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].role, "user");
         assert!(results[0].text.contains("Hello world"));
+        assert_eq!(results[0].line_number, Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_searches_compressed_sessions() -> Result<()> {
+        use flate2::{Compression, write::GzEncoder};
+
+        let temp_dir = tempdir()?;
+        let line = |text: &str, session: &str| {
+            format!(
+                r#"{{"type":"user","message":{{"role":"user","content":"{text}"}},"uuid":"{session}-1","timestamp":"2024-01-01T00:00:00Z","sessionId":"{session}","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+            ) + "\n"
+        };
+
+        let mut gzip = GzEncoder::new(
+            File::create(temp_dir.path().join("old.jsonl.gz"))?,
+            Compression::default(),
+        );
+        gzip.write_all(format!("{{invalid json\n{}", line("archived gzip", "gz")).as_bytes())?;
+        gzip.finish()?;
+        std::fs::write(
+            temp_dir.path().join("older.jsonl.zst"),
+            zstd::encode_all(line("archived zstd", "zst").as_bytes(), 0)?,
+        )?;
+        std::fs::write(
+            temp_dir.path().join("live.jsonl"),
+            line("live session", "live"),
+        )?;
+
+        let engine = RayonEngine::new(SearchOptions::default());
+        let (mut results, _, total) =
+            engine.search(temp_dir.path().to_str().unwrap(), parse_query("archived")?)?;
+        results.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+
+        assert_eq!(total, 2);
+        assert_eq!(results[0].session_id, "gz");
+        assert!(results[0].file.ends_with("old.jsonl.gz"));
+        assert_eq!(results[0].line_number, Some(2));
+        assert_eq!(results[1].session_id, "zst");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_last_timings() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        let line = r#"{"type":"user","message":{"role":"user","content":"time me"},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#;
+        std::fs::write(&test_file, format!("{line}\n{line}\n"))?;
+
+        let engine = RayonEngine::new(SearchOptions::default());
+        let (_, duration, _) = engine.search(test_file.to_str().unwrap(), parse_query("time")?)?;
+        let timings = engine.last_timings();
+        assert_eq!(timings.total, duration);
+        assert!(timings.read > Duration::ZERO);
+        assert!(timings.parse > Duration::ZERO);
+        assert!(timings.discovery + timings.sort <= timings.total);
+
+        let report = timings.to_json();
+        for phase in [
+            "discovery",
+            "read",
+            "parse",
+            "match",
+            "sort",
+            "format",
+            "total",
+        ] {
+            assert!(report[format!("{phase}_ms")].is_f64(), "{report}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_thinking_only_search() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        std::fs::write(
+            &test_file,
+            r#"{"type":"assistant","message":{"id":"msg1","type":"message","role":"assistant","model":"claude","content":[{"type":"thinking","thinking":"Roll back before the deploy","signature":"sig"},{"type":"text","text":"Deploying now"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":10,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":5}},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+        )?;
+        let path = test_file.to_str().unwrap();
+
+        let engine = RayonEngine::new(SearchOptions {
+            thinking_only: true,
+            ..Default::default()
+        });
+        let (results, _, _) = engine.search(path, parse_query("\"roll back\"")?)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matched_in, vec![TextSection::Thinking]);
+        // Sections are matched against but not displayed
+        assert!(!results[0].text.contains(SECTION_SEPARATOR));
+        let (results, _, _) = engine.search(path, parse_query("deploying")?)?;
+        assert!(results.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_position_search() -> Result<()> {
+        use crate::query::SessionPosition;
+
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        std::fs::write(
+            &test_file,
+            concat!(
+                r#"{"type":"summary","summary":"Deploy session","leafUuid":"3"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"please deploy"},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"and check the logs"},"uuid":"2","timestamp":"2024-01-01T00:00:01Z","sessionId":"s1","parentUuid":"1","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"deploy done"},"uuid":"3","timestamp":"2024-01-01T00:00:02Z","sessionId":"s1","parentUuid":"2","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+            ),
+        )?;
+        let path = test_file.to_str().unwrap();
+
+        let engine = RayonEngine::new(SearchOptions::default());
+        let (results, _, _) = engine.search(path, parse_query("deploy AND is:first")?)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].uuid, "1");
+
+        let engine = RayonEngine::new(SearchOptions {
+            position: Some(SessionPosition::Last),
+            ..Default::default()
+        });
+        let (results, _, _) = engine.search(path, parse_query("deploy")?)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].uuid, "3");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sidechain_messages() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        std::fs::write(
+            &test_file,
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"deploy the app"},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"deploy from a subagent"},"uuid":"2","timestamp":"2024-01-01T00:00:01Z","sessionId":"s1","parentUuid":"1","isSidechain":true,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+            ),
+        )?;
+        let path = test_file.to_str().unwrap();
+
+        let engine = RayonEngine::new(SearchOptions::default());
+        let (results, _, _) = engine.search(path, parse_query("deploy")?)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].uuid, "1");
+        let (results, _, _) = engine.search(path, parse_query("deploy AND is:sidechain")?)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].uuid, "2");
+
+        let engine = RayonEngine::new(SearchOptions {
+            exclude_sidechain: false,
+            ..Default::default()
+        });
+        assert_eq!(engine.search(path, parse_query("deploy")?)?.0.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_last_scan_stats() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        let line = r#"{"type":"user","message":{"role":"user","content":"scan me"},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#;
+        let contents = format!("{line}\n{{invalid json\n\n{line}\n");
+        std::fs::write(&test_file, &contents)?;
+
+        let engine = RayonEngine::new(SearchOptions::default());
+        assert_eq!(engine.last_scan_stats(), ScanStats::default());
+        engine.search(test_file.to_str().unwrap(), parse_query("scan")?)?;
+        let scan = engine.last_scan_stats();
+        assert_eq!(scan.files_scanned, 1);
+        assert_eq!(scan.lines_parsed, 3);
+        assert_eq!(scan.parse_failures, 1);
+        assert_eq!(scan.bytes_read, contents.len() as u64);
+        assert_eq!(scan.cache_hit_ratio(), 0.0);
 
         Ok(())
     }
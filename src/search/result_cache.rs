@@ -0,0 +1,577 @@
+//! In-memory cache of per-file match results.
+//!
+//! Interactive search re-runs the query on every keystroke, and each new query
+//! usually just extends the previous one ("err" → "error", "err" → "err AND
+//! retry"). Matches are remembered per file, keyed by the file's mtime and size
+//! plus the query, and kept with the text they were matched against. A repeated
+//! query reuses them as they are; a query that can only match a subset of what
+//! a cached query matched re-evaluates the cached matches instead of reading
+//! the file again.
+//!
+//! The cache is bounded by an approximate memory size (`cache_memory_mb` in
+//! config.toml): the queries stored longest ago are dropped first to make
+//! room, and the matches of a file that alone would not fit are not kept.
+
+use crate::index::FileStamp;
+use crate::outcome::SessionOutcome;
+use crate::query::condition::{MessageKind, SessionPosition, TextSection};
+use crate::query::fast_lowercase::FastLowercase;
+use crate::query::{QueryCondition, SearchOptions, SearchResult};
+use crate::tool_calls::ToolFilter;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Queries whose matches are remembered, most recent first
+const MAX_CACHED_QUERIES: usize = 16;
+/// Approximate memory all cached matches may take, unless configured
+pub const DEFAULT_MEMORY_CAP: usize = 512 * 1024 * 1024;
+
+/// A match together with the text the query was evaluated against
+#[derive(Debug, Clone)]
+pub struct CachedMatch {
+    pub result: SearchResult,
+    pub searchable_text: String,
+}
+
+impl CachedMatch {
+    /// Bytes held by the match, counting its strings but not the query
+    fn approximate_size(&self) -> usize {
+        // The file, session id, role and cwd are shared with the other
+        // matches of the file
+        let result = &self.result;
+        std::mem::size_of::<Self>()
+            + self.searchable_text.len()
+            + result.uuid.len()
+            + result.timestamp.len()
+            + result.text.len()
+            + result.raw_json.as_ref().map_or(0, String::len)
+    }
+}
+
+#[derive(Debug)]
+struct CachedFile {
+    stamp: FileStamp,
+    matches: Vec<CachedMatch>,
+    /// Approximate bytes held by `matches`
+    size: usize,
+}
+
+/// Matches of one query under one set of file-level filters
+#[derive(Debug)]
+struct CachedQuery {
+    query: QueryCondition,
+    role: Option<String>,
+    project_path: Option<String>,
+    /// Sections appended to the searchable texts
+    sections: Vec<TextSection>,
+    /// Session positions the matches were narrowed to
+    positions: Vec<SessionPosition>,
+    /// Session outcomes the matches were narrowed to
+    outcomes: Vec<SessionOutcome>,
+    /// Message kinds asked for, and those left out otherwise
+    kinds: (Vec<MessageKind>, bool, bool),
+    tool: Option<ToolFilter>,
+    files: HashMap<PathBuf, CachedFile>,
+}
+
+impl CachedQuery {
+    fn size(&self) -> usize {
+        self.files.values().map(|file| file.size).sum()
+    }
+
+    /// Whether matches cached for this query can answer `query` under `options`
+    fn same_filters(&self, query: &QueryCondition, options: &SearchOptions) -> bool {
+        self.role == options.role
+            && self.project_path == options.project_path
+            && self.sections == options.sections_for(query)
+            && self.positions == options.positions_for(query)
+            && self.outcomes == query.outcomes()
+            && self.kinds == kinds_for(query, options)
+            && self.tool == options.tool
+    }
+
+    fn fresh_file(
+        &self,
+        path: &Path,
+        stamp: FileStamp,
+        query: &QueryCondition,
+        options: &SearchOptions,
+    ) -> Option<&CachedFile> {
+        self.files
+            .get(path)
+            .filter(|file| file.stamp == stamp && self.same_filters(query, options))
+    }
+}
+
+/// How much the result cache holds, for the help overlay and `--verbose`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub queries: usize,
+    pub files: usize,
+    pub matches: usize,
+    /// Approximate bytes held by the matches
+    pub bytes: usize,
+    pub memory_cap: usize,
+}
+
+impl CacheStats {
+    pub fn summary(&self) -> String {
+        const MB: f64 = 1024.0 * 1024.0;
+        format!(
+            "{} queries, {} files, {} matches, {:.1} MB of {:.0} MB",
+            self.queries,
+            self.files,
+            self.matches,
+            self.bytes as f64 / MB,
+            self.memory_cap as f64 / MB
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct ResultCache {
+    queries: VecDeque<CachedQuery>,
+    /// Approximate bytes held by all cached matches
+    size: usize,
+    memory_cap: usize,
+}
+
+impl Default for ResultCache {
+    fn default() -> Self {
+        Self::with_memory_cap(DEFAULT_MEMORY_CAP)
+    }
+}
+
+impl ResultCache {
+    pub fn with_memory_cap(memory_cap: usize) -> Self {
+        Self {
+            queries: VecDeque::new(),
+            size: 0,
+            memory_cap,
+        }
+    }
+
+    /// Change the memory cap, dropping queries until the cache fits it
+    pub fn set_memory_cap(&mut self, memory_cap: usize) {
+        self.memory_cap = memory_cap;
+        self.evict(0);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            queries: self.queries.len(),
+            files: self.queries.iter().map(|cached| cached.files.len()).sum(),
+            matches: self
+                .queries
+                .iter()
+                .flat_map(|cached| cached.files.values())
+                .map(|file| file.matches.len())
+                .sum(),
+            bytes: self.size,
+            memory_cap: self.memory_cap,
+        }
+    }
+
+    /// Results of `query` for the file at `path`, if they can be answered
+    /// from the cache for a file still at `stamp`
+    pub fn lookup(
+        &self,
+        path: &Path,
+        stamp: FileStamp,
+        query: &QueryCondition,
+        options: &SearchOptions,
+    ) -> Option<Vec<SearchResult>> {
+        if let Some(file) = self
+            .queries
+            .iter()
+            .filter(|cached| &cached.query == query)
+            .find_map(|cached| cached.fresh_file(path, stamp, query, options))
+        {
+            return Some(file.matches.iter().map(|m| m.result.clone()).collect());
+        }
+
+        let file = self
+            .queries
+            .iter()
+            .filter(|cached| narrows(query, &cached.query))
+            .find_map(|cached| cached.fresh_file(path, stamp, query, options))?;
+        Some(
+            file.matches
+                .iter()
+                .filter(|m| {
+                    query
+                        .evaluate_at(&m.searchable_text, Some(&m.result.timestamp))
+                        .unwrap_or(false)
+                })
+                .map(|m| SearchResult {
+                    query: query.clone(),
+                    matched_in: query
+                        .matched_sections(&m.searchable_text, Some(&m.result.timestamp)),
+                    ..m.result.clone()
+                })
+                .collect(),
+        )
+    }
+
+    /// Remember the matches of `query` in the file at `path` as of `stamp`
+    pub fn store(
+        &mut self,
+        path: &Path,
+        stamp: FileStamp,
+        query: &QueryCondition,
+        options: &SearchOptions,
+        matches: Vec<CachedMatch>,
+    ) {
+        let position = self
+            .queries
+            .iter()
+            .position(|cached| &cached.query == query && cached.same_filters(query, options));
+        let mut cached = match position.and_then(|index| self.queries.remove(index)) {
+            Some(cached) => cached,
+            None => CachedQuery {
+                query: query.clone(),
+                role: options.role.clone(),
+                project_path: options.project_path.clone(),
+                sections: options.sections_for(query),
+                positions: options.positions_for(query),
+                outcomes: query.outcomes(),
+                kinds: kinds_for(query, options),
+                tool: options.tool.clone(),
+                files: HashMap::new(),
+            },
+        };
+
+        if let Some(previous) = cached.files.remove(path) {
+            self.size -= previous.size;
+        }
+        let size = matches.iter().map(CachedMatch::approximate_size).sum();
+        // Older queries make room, but this query has to fit on its own
+        if cached.size() + size <= self.memory_cap {
+            self.size += size;
+            cached.files.insert(
+                path.to_path_buf(),
+                CachedFile {
+                    stamp,
+                    matches,
+                    size,
+                },
+            );
+        }
+
+        self.queries.push_front(cached);
+        self.evict(1);
+    }
+
+    /// Drop the queries stored longest ago until the cache is within its
+    /// limits, keeping the `keep` most recent ones
+    fn evict(&mut self, keep: usize) {
+        while self.queries.len() > keep
+            && (self.queries.len() > MAX_CACHED_QUERIES || self.size > self.memory_cap)
+        {
+            if let Some(evicted) = self.queries.pop_back() {
+                self.size -= evicted.size();
+            }
+        }
+    }
+}
+
+/// Whether every text matching `narrower` is known to also match `broader`
+fn narrows(narrower: &QueryCondition, broader: &QueryCondition) -> bool {
+    if narrower == broader {
+        return true;
+    }
+
+    match (narrower, broader) {
+        (
+            QueryCondition::Literal {
+                pattern: narrower,
+                case_sensitive: narrower_cs,
+            },
+            QueryCondition::Literal {
+                pattern: broader,
+                case_sensitive: broader_cs,
+            },
+        ) => match (narrower_cs, broader_cs) {
+            (true, true) => narrower.contains(broader.as_str()),
+            (_, false) => narrower
+                .fast_to_lowercase()
+                .contains(&broader.fast_to_lowercase()),
+            (false, true) => false,
+        },
+        (
+            QueryCondition::Scoped {
+                section: narrower_section,
+                condition: narrower,
+            },
+            QueryCondition::Scoped {
+                section: broader_section,
+                condition: broader,
+            },
+        ) => narrower_section == broader_section && narrows(narrower, broader),
+        (
+            QueryCondition::Near {
+                left: narrower_left,
+                right: narrower_right,
+                distance: narrower_distance,
+            },
+            QueryCondition::Near {
+                left: broader_left,
+                right: broader_right,
+                distance: broader_distance,
+            },
+        ) if narrower_distance <= broader_distance
+            && narrows(narrower_left, broader_left)
+            && narrows(narrower_right, broader_right) =>
+        {
+            true
+        }
+        // Every text matching `a NEAR/n b` matches both `a` and `b`
+        (QueryCondition::Near { left, right, .. }, _)
+            if narrows(left, broader) || narrows(right, broader) =>
+        {
+            true
+        }
+        (QueryCondition::And { conditions }, _) if !conditions.is_empty() => conditions
+            .iter()
+            .any(|condition| narrows(condition, broader)),
+        (_, QueryCondition::Or { conditions }) => conditions
+            .iter()
+            .any(|condition| narrows(narrower, condition)),
+        _ => false,
+    }
+}
+
+/// The message kinds `query` asks for, and whether `options` leave out
+/// sidechain and meta messages otherwise
+fn kinds_for(query: &QueryCondition, options: &SearchOptions) -> (Vec<MessageKind>, bool, bool) {
+    (
+        query.kinds(),
+        options.exclude_sidechain,
+        options.exclude_meta,
+    )
+}
+
+fn global() -> &'static Mutex<ResultCache> {
+    static CACHE: OnceLock<Mutex<ResultCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(ResultCache::default()))
+}
+
+/// Size the process-wide cache may grow to, in approximate bytes
+pub fn set_memory_cap(memory_cap: usize) {
+    if let Ok(mut cache) = global().lock() {
+        cache.set_memory_cap(memory_cap);
+    }
+}
+
+/// What the process-wide cache holds
+pub fn stats() -> CacheStats {
+    global()
+        .lock()
+        .map(|cache| cache.stats())
+        .unwrap_or_default()
+}
+
+/// `ResultCache::lookup` on the process-wide cache
+pub fn lookup(
+    path: &Path,
+    stamp: FileStamp,
+    query: &QueryCondition,
+    options: &SearchOptions,
+) -> Option<Vec<SearchResult>> {
+    global().lock().ok()?.lookup(path, stamp, query, options)
+}
+
+/// `ResultCache::store` on the process-wide cache
+pub fn store(
+    path: &Path,
+    stamp: FileStamp,
+    query: &QueryCondition,
+    options: &SearchOptions,
+    matches: Vec<CachedMatch>,
+) {
+    if let Ok(mut cache) = global().lock() {
+        cache.store(path, stamp, query, options, matches);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::parse_query;
+
+    const STAMP: FileStamp = FileStamp {
+        mtime_ms: 1,
+        size: 10,
+    };
+
+    fn cached_match(uuid: &str, text: &str, query: &QueryCondition) -> CachedMatch {
+        CachedMatch {
+            result: SearchResult {
+                file: "/s.jsonl".into(),
+                uuid: uuid.to_string(),
+                timestamp: "2024-01-01T00:00:00Z".to_string(),
+                session_id: "s1".into(),
+                role: "user".into(),
+                text: text.to_string(),
+                message_type: "user".into(),
+                query: query.clone(),
+                cwd: "/".into(),
+                raw_json: None,
+                line_number: Some(1),
+                matched_in: Vec::new(),
+                has_images: false,
+            },
+            searchable_text: text.to_string(),
+        }
+    }
+
+    fn uuids(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.uuid.as_str()).collect()
+    }
+
+    fn cache_with_err() -> ResultCache {
+        let query = parse_query("err").unwrap();
+        let mut cache = ResultCache::default();
+        cache.store(
+            Path::new("/s.jsonl"),
+            STAMP,
+            &query,
+            &SearchOptions::default(),
+            vec![
+                cached_match("1", "an Error occurred", &query),
+                cached_match("2", "err: retry later", &query),
+            ],
+        );
+        cache
+    }
+
+    #[test]
+    fn test_exact_and_narrowed_hits() {
+        let cache = cache_with_err();
+        let path = Path::new("/s.jsonl");
+        let options = SearchOptions::default();
+
+        let exact = cache.lookup(path, STAMP, &parse_query("err").unwrap(), &options);
+        assert_eq!(uuids(&exact.unwrap()), vec!["1", "2"]);
+
+        let error = parse_query("error").unwrap();
+        let narrowed = cache.lookup(path, STAMP, &error, &options).unwrap();
+        assert_eq!(uuids(&narrowed), vec!["1"]);
+        assert_eq!(narrowed[0].query, error);
+
+        let and = parse_query("err AND retry").unwrap();
+        assert_eq!(
+            uuids(&cache.lookup(path, STAMP, &and, &options).unwrap()),
+            vec!["2"]
+        );
+
+        let near = parse_query("err NEAR/1 later").unwrap();
+        assert_eq!(
+            uuids(&cache.lookup(path, STAMP, &near, &options).unwrap()),
+            vec!["2"]
+        );
+    }
+
+    #[test]
+    fn test_misses() {
+        let cache = cache_with_err();
+        let path = Path::new("/s.jsonl");
+        let options = SearchOptions::default();
+
+        // Broader or unrelated queries need the file
+        for query in ["er", "warn", "err OR warn", "NOT err", "/err/"] {
+            let query = parse_query(query).unwrap();
+            assert!(cache.lookup(path, STAMP, &query, &options).is_none());
+        }
+
+        let query = parse_query("err").unwrap();
+        let changed = FileStamp {
+            mtime_ms: 2,
+            size: 10,
+        };
+        assert!(cache.lookup(path, changed, &query, &options).is_none());
+
+        let user_only = SearchOptions {
+            role: Some("user".to_string()),
+            ..Default::default()
+        };
+        assert!(cache.lookup(path, STAMP, &query, &user_only).is_none());
+    }
+
+    #[test]
+    fn test_eviction_keeps_size() {
+        let mut cache = cache_with_err();
+        let options = SearchOptions::default();
+        for n in 0..MAX_CACHED_QUERIES {
+            let query = parse_query(&format!("q{n}")).unwrap();
+            cache.store(
+                Path::new("/s.jsonl"),
+                STAMP,
+                &query,
+                &options,
+                vec![cached_match("x", "q", &query)],
+            );
+        }
+
+        assert_eq!(cache.queries.len(), MAX_CACHED_QUERIES);
+        assert_eq!(cache.stats().matches, MAX_CACHED_QUERIES);
+        assert_eq!(
+            cache.size,
+            cache.queries.iter().map(CachedQuery::size).sum::<usize>()
+        );
+        let err = parse_query("err").unwrap();
+        assert!(
+            cache
+                .lookup(Path::new("/s.jsonl"), STAMP, &err, &options)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_memory_cap() {
+        let path = Path::new("/s.jsonl");
+        let options = SearchOptions::default();
+        let store = |cache: &mut ResultCache, query: &str, text: &str| {
+            let query = parse_query(query).unwrap();
+            let matches = vec![cached_match("1", text, &query)];
+            cache.store(path, STAMP, &query, &options, matches);
+        };
+        let size = cached_match("1", "a", &parse_query("a").unwrap()).approximate_size();
+
+        // Room for two one-match queries
+        let mut cache = ResultCache::with_memory_cap(size * 2);
+        store(&mut cache, "a", "a");
+        store(&mut cache, "b", "b");
+        assert_eq!(cache.stats().queries, 2);
+        assert_eq!(cache.stats().bytes, size * 2);
+
+        // The query stored longest ago makes room
+        store(&mut cache, "c", "c");
+        let stats = cache.stats();
+        assert_eq!((stats.queries, stats.matches), (2, 2));
+        assert!(
+            cache
+                .lookup(path, STAMP, &parse_query("a").unwrap(), &options)
+                .is_none()
+        );
+
+        // Matches too big to ever fit are not kept
+        store(&mut cache, "d", &"d".repeat(size * 2));
+        let stats = cache.stats();
+        assert_eq!((stats.files, stats.matches), (2, 2));
+        assert!(stats.bytes <= stats.memory_cap);
+
+        // Shrinking the cap drops the oldest queries at once
+        cache.set_memory_cap(size);
+        assert!(
+            cache
+                .lookup(path, STAMP, &parse_query("b").unwrap(), &options)
+                .is_none()
+        );
+        assert_eq!(
+            cache.stats().summary(),
+            "2 queries, 1 files, 1 matches, 0.0 MB of 0 MB"
+        );
+    }
+}
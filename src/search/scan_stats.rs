@@ -0,0 +1,96 @@
+//! Per-search counters describing what a search actually read.
+
+use super::timings::{FileTimer, PhaseCounters, Timings};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Totals of one search, reported by `--verbose` and the JSON summary
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanStats {
+    /// Session files read from disk
+    pub files_scanned: u64,
+    /// Non-empty lines handed to the JSON parser, failures included
+    pub lines_parsed: u64,
+    /// Bytes read from session files, after decompression
+    pub bytes_read: u64,
+    pub parse_failures: u64,
+    /// Files answered from the search index or the result cache instead
+    pub cache_hits: u64,
+}
+
+impl ScanStats {
+    /// Share of searched files answered without reading them
+    pub fn cache_hit_ratio(&self) -> f64 {
+        let searched = self.files_scanned + self.cache_hits;
+        if searched == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / searched as f64
+        }
+    }
+
+    /// One-line description for `--verbose` output
+    pub fn summary(&self) -> String {
+        format!(
+            "{} files scanned, {} lines parsed ({} failed), {} bytes read, {} cache hits ({:.0}%)",
+            self.files_scanned,
+            self.lines_parsed,
+            self.parse_failures,
+            self.bytes_read,
+            self.cache_hits,
+            self.cache_hit_ratio() * 100.0
+        )
+    }
+}
+
+/// Counters shared by the tasks of one search
+#[derive(Debug, Default)]
+pub struct ScanCounters {
+    files_scanned: AtomicU64,
+    lines_parsed: AtomicU64,
+    bytes_read: AtomicU64,
+    parse_failures: AtomicU64,
+    cache_hits: AtomicU64,
+    phases: PhaseCounters,
+}
+
+impl ScanCounters {
+    pub fn file_scanned(&self) {
+        self.files_scanned.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn bytes_read(&self, bytes: usize) {
+        self.bytes_read.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn line_parsed(&self, ok: bool) {
+        self.lines_parsed.fetch_add(1, Ordering::Relaxed);
+        if !ok {
+            self.parse_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Add the read, parse and match time of a scanned file
+    pub fn file_timed(&self, timer: FileTimer) {
+        self.phases.add(timer);
+    }
+
+    /// Read, parse and match time of the files scanned so far
+    pub fn timings(&self) -> Timings {
+        self.phases.snapshot()
+    }
+
+    pub fn snapshot(&self) -> ScanStats {
+        ScanStats {
+            files_scanned: self.files_scanned.load(Ordering::Relaxed),
+            lines_parsed: self.lines_parsed.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            parse_failures: self.parse_failures.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+        }
+    }
+}
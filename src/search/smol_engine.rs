@@ -2,15 +2,25 @@ use anyhow::Result;
 use chrono::DateTime;
 use smol::channel;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
-use std::sync::Arc;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use super::compression::session_reader_with;
 use super::engine::SearchEngineTrait;
 use super::file_discovery::{discover_claude_files, expand_tilde};
+use super::result_cache::{self, CachedMatch};
+use super::scan_stats::{ScanCounters, ScanStats};
+use super::timings::{FileTimer, Timings};
+use crate::index::{FileStamp, load_for_search};
 use crate::interactive_ratatui::domain::models::SearchOrder;
-use crate::query::{QueryCondition, SearchOptions, SearchResult};
+use crate::metrics;
+use crate::outcome::OutcomeTracker;
+use crate::query::condition::SessionBounds;
+use crate::query::{Interner, QueryCondition, SearchOptions, SearchResult};
 use crate::schemas::SessionMessage;
+use crate::tool_calls::ToolCalls;
 use crate::utils::path_encoding;
 
 // Initialize blocking thread pool optimization
@@ -33,18 +43,40 @@ fn initialize_blocking_threads(verbose: bool) {
 
 pub struct SmolEngine {
     options: SearchOptions,
+    last_scan: Mutex<ScanStats>,
+    last_timings: Mutex<Timings>,
 }
 
 impl SmolEngine {
     pub fn new(options: SearchOptions) -> Self {
         // Initialize blocking threads optimization on first use
         initialize_blocking_threads(options.verbose);
-        Self { options }
+        Self {
+            options,
+            last_scan: Mutex::new(ScanStats::default()),
+            last_timings: Mutex::new(Timings::default()),
+        }
     }
 
     pub fn get_options(&self) -> &SearchOptions {
         &self.options
     }
+
+    /// What the most recent search read
+    pub fn last_scan_stats(&self) -> ScanStats {
+        self.last_scan
+            .lock()
+            .map(|stats| *stats)
+            .unwrap_or_default()
+    }
+
+    /// Where the time of the most recent search went
+    pub fn last_timings(&self) -> Timings {
+        self.last_timings
+            .lock()
+            .map(|timings| *timings)
+            .unwrap_or_default()
+    }
 }
 
 impl SearchEngineTrait for SmolEngine {
@@ -73,17 +105,58 @@ impl SearchEngineTrait for SmolEngine {
         order: SearchOrder,
     ) -> Result<(Vec<SearchResult>, std::time::Duration, usize)> {
         // Use smol's block_on to run the async search synchronously
-        smol::block_on(async { self.search_async(pattern, query, role_filter, order).await })
+        smol::block_on(async {
+            self.search_async(pattern, query, role_filter, order, None)
+                .await
+        })
     }
 }
 
+/// Called with the filtered results of each searched file, the number of files
+/// searched so far, and the total number of files
+pub type BatchCallback<'a> = &'a mut dyn FnMut(&[SearchResult], usize, usize);
+
 impl SmolEngine {
+    /// Like `search_with_role_filter_and_order`, but reports each file's
+    /// results through `on_batch` as soon as the file has been searched.
+    /// Batches arrive unsorted and before `max_results` is applied.
+    pub fn search_streaming(
+        &self,
+        pattern: &str,
+        query: QueryCondition,
+        role_filter: Option<String>,
+        order: SearchOrder,
+        on_batch: BatchCallback,
+    ) -> Result<(Vec<SearchResult>, std::time::Duration, usize)> {
+        smol::block_on(async {
+            self.search_async(pattern, query, role_filter, order, Some(on_batch))
+                .await
+        })
+    }
+
+    /// Search exactly `files` instead of the files a pattern matches
+    pub fn search_files(
+        &self,
+        files: Vec<PathBuf>,
+        query: QueryCondition,
+        role_filter: Option<String>,
+        order: SearchOrder,
+    ) -> Result<(Vec<SearchResult>, std::time::Duration, usize)> {
+        let start_time = std::time::Instant::now();
+        let (results, total_count) = smol::block_on(async {
+            self.search_in_files(files, query, role_filter, order, None)
+                .await
+        });
+        Ok((results, start_time.elapsed(), total_count))
+    }
+
     async fn search_async(
         &self,
         pattern: &str,
         query: QueryCondition,
         role_filter: Option<String>,
         order: SearchOrder,
+        on_batch: Option<BatchCallback<'_>>,
     ) -> Result<(Vec<SearchResult>, std::time::Duration, usize)> {
         let start_time = std::time::Instant::now();
 
@@ -105,18 +178,63 @@ impl SmolEngine {
             );
         }
 
+        let search_start = std::time::Instant::now();
+        let (all_results, total_count) = self
+            .search_in_files(files, query, role_filter, order, on_batch)
+            .await;
+        let search_time = search_start.elapsed();
+
+        let elapsed = start_time.elapsed();
+        if let Ok(mut timings) = self.last_timings.lock() {
+            timings.discovery = file_discovery_time;
+            timings.total = elapsed;
+        }
+
+        if self.options.verbose {
+            eprintln!("\nPerformance breakdown:");
+            eprintln!("  File discovery: {}ms", file_discovery_time.as_millis());
+            eprintln!("  Search: {}ms", search_time.as_millis());
+            eprintln!("  Total: {}ms", elapsed.as_millis());
+            eprintln!("  Scanned: {}", self.last_scan_stats().summary());
+            if self.options.cache_results {
+                eprintln!("  Result cache: {}", result_cache::stats().summary());
+            }
+        }
+
+        Ok((all_results, elapsed, total_count))
+    }
+
+    /// Search `files` and return the sorted, filtered results with the number
+    /// of matches before `max_results` was applied
+    async fn search_in_files(
+        &self,
+        files: Vec<PathBuf>,
+        query: QueryCondition,
+        role_filter: Option<String>,
+        order: SearchOrder,
+        mut on_batch: Option<BatchCallback<'_>>,
+    ) -> (Vec<SearchResult>, usize) {
+        let query = self.options.scope_query(query);
+
+        let counters = Arc::new(ScanCounters::default());
         if files.is_empty() {
-            return Ok((Vec::new(), start_time.elapsed(), 0));
+            self.record_scan(&counters, Duration::ZERO);
+            return (Vec::new(), 0);
         }
 
         // Channel for collecting results
         let (sender, receiver) = channel::unbounded();
 
         // Process files concurrently using multi-threaded executor
-        let search_start = std::time::Instant::now();
-
         let query = Arc::new(query);
         let options = Arc::new(self.options.clone());
+        let interner = Arc::new(Interner::new());
+        let index = load_for_search(&self.options);
+        if self.options.verbose
+            && let Some(index) = index.as_ref()
+        {
+            eprintln!("Using search index ({} files)", index.files.len());
+        }
 
         // Spawn tasks for each file on the global executor
         let mut tasks = Vec::new();
@@ -124,17 +242,31 @@ impl SmolEngine {
             let sender = sender.clone();
             let query = query.clone();
             let options = options.clone();
+            let index = index.clone();
+            let counters = counters.clone();
+            let interner = interner.clone();
 
             let task = smol::spawn(async move {
-                if let Ok(results) = search_file(&file_path, &query, &options).await {
-                    for result in results {
-                        let _ = sender.send(result).await;
+                let indexed = index
+                    .as_ref()
+                    .and_then(|index| index.search_file(&file_path, &query, &options, &interner));
+                let results = match indexed {
+                    Some(results) => {
+                        counters.cache_hit();
+                        Ok(results)
                     }
-                }
+                    None => {
+                        search_file_cached(&file_path, &query, &options, &counters, &interner).await
+                    }
+                };
+                // One batch per file, empty on failure, so every file is counted as searched
+                let _ = sender.send(results.unwrap_or_default()).await;
             });
             tasks.push(task);
         }
 
+        let tasks_len = tasks.len();
+
         // Drop the original sender so the receiver knows when all tasks are done
         drop(sender);
 
@@ -145,11 +277,18 @@ impl SmolEngine {
             }
         };
 
-        // Collect results while processing
+        // Collect and filter results while processing
+        let files_total = tasks_len;
         let collect_future = async {
             let mut all_results = Vec::new();
-            while let Ok(result) = receiver.recv().await {
-                all_results.push(result);
+            let mut files_searched = 0;
+            while let Ok(mut batch) = receiver.recv().await {
+                files_searched += 1;
+                self.apply_filters(&mut batch, role_filter.as_deref());
+                if let Some(on_batch) = on_batch.as_mut() {
+                    on_batch(&batch, files_searched, files_total);
+                }
+                all_results.append(&mut batch);
             }
             all_results
         };
@@ -157,21 +296,11 @@ impl SmolEngine {
         // Run search and collection concurrently
         let (_, mut all_results) = futures_lite::future::zip(search_future, collect_future).await;
 
-        let search_time = search_start.elapsed();
-
-        // Apply filters
-        self.apply_filters(&mut all_results, role_filter)?;
-
-        // Sort by timestamp
-        match order {
-            SearchOrder::Descending => {
-                all_results.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-            }
-            SearchOrder::Ascending => {
-                all_results.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-            }
-        }
+        let sort_start = Instant::now();
+        order.sort(&mut all_results);
 
+        self.options.dedupe(&mut all_results);
+        self.options.cap_per_session(&mut all_results);
         let total_count = all_results.len();
 
         // Only truncate if max_results is specified
@@ -179,23 +308,23 @@ impl SmolEngine {
             all_results.truncate(limit);
         }
 
-        let elapsed = start_time.elapsed();
+        self.record_scan(&counters, sort_start.elapsed());
+        (all_results, total_count)
+    }
 
-        if self.options.verbose {
-            eprintln!("\nPerformance breakdown:");
-            eprintln!("  File discovery: {}ms", file_discovery_time.as_millis());
-            eprintln!("  Search: {}ms", search_time.as_millis());
-            eprintln!("  Total: {}ms", elapsed.as_millis());
+    fn record_scan(&self, counters: &ScanCounters, sort: Duration) {
+        if let Ok(mut last_scan) = self.last_scan.lock() {
+            *last_scan = counters.snapshot();
+        }
+        if let Ok(mut last_timings) = self.last_timings.lock() {
+            *last_timings = Timings {
+                sort,
+                ..counters.timings()
+            };
         }
-
-        Ok((all_results, elapsed, total_count))
     }
 
-    fn apply_filters(
-        &self,
-        results: &mut Vec<SearchResult>,
-        role_filter: Option<String>,
-    ) -> Result<()> {
+    fn apply_filters(&self, results: &mut Vec<SearchResult>, role_filter: Option<&str>) {
         // Apply message ID filter (highest priority)
         if let Some(ref message_id) = self.options.message_id {
             results.retain(|r| &r.uuid == message_id);
@@ -207,9 +336,13 @@ impl SmolEngine {
         }
 
         // Apply session filter
-        if let Some(ref session_id) = self.options.session_id {
-            results.retain(|r| &r.session_id == session_id);
-        }
+        results.retain(|r| {
+            self.options
+                .matches_session(Some(r.session_id.as_str()).filter(|id| !id.is_empty()))
+        });
+
+        // Apply exclusion filters
+        results.retain(|r| !self.options.excludes(r));
 
         // Apply time filters
         if let Some(ref after) = self.options.after
@@ -231,17 +364,69 @@ impl SmolEngine {
                     .unwrap_or(false)
             });
         }
+    }
+}
 
-        Ok(())
+/// Search a file through the result cache when `options.cache_results` is set.
+///
+/// Searches that need the raw JSON of each message always scan the file,
+/// since cached matches do not keep it.
+async fn search_file_cached(
+    file_path: &Path,
+    query: &QueryCondition,
+    options: &SearchOptions,
+    counters: &Arc<ScanCounters>,
+    interner: &Arc<Interner>,
+) -> Result<Vec<SearchResult>> {
+    let stamp =
+        (options.cache_results && options.session_id.is_none() && options.message_id.is_none())
+            .then(|| FileStamp::of(file_path).ok())
+            .flatten();
+    let Some(stamp) = stamp else {
+        let matches = search_file(
+            file_path,
+            query,
+            options,
+            false,
+            counters.clone(),
+            interner.clone(),
+        )
+        .await?;
+        return Ok(matches.into_iter().map(|m| m.result).collect());
+    };
+
+    if let Some(results) = result_cache::lookup(file_path, stamp, query, options) {
+        counters.cache_hit();
+        return Ok(results);
     }
+
+    // The stamp is taken before reading, so a file written to mid-scan is
+    // cached as stale and read again next time
+    let matches = search_file(
+        file_path,
+        query,
+        options,
+        true,
+        counters.clone(),
+        interner.clone(),
+    )
+    .await?;
+    let results = matches.iter().map(|m| m.result.clone()).collect();
+    result_cache::store(file_path, stamp, query, options, matches);
+    Ok(results)
 }
 
-// Helper function to search a single file using blocking I/O with optimized buffer
+// Helper function to search a single file using blocking I/O with optimized buffer.
+// The searchable text of each match is only kept when `keep_text` is set, and
+// the strings results repeat are shared through `interner`.
 async fn search_file(
     file_path: &Path,
     query: &QueryCondition,
     options: &SearchOptions,
-) -> Result<Vec<SearchResult>> {
+    keep_text: bool,
+    counters: Arc<ScanCounters>,
+    interner: Arc<Interner>,
+) -> Result<Vec<CachedMatch>> {
     let file_path_owned = file_path.to_owned();
     let file_path_str = file_path_owned.to_string_lossy().to_string();
     let query_owned = query.clone();
@@ -257,10 +442,13 @@ async fn search_file(
 
     // Use smol's blocking executor with larger buffer for better throughput
     blocking::unblock(move || {
+        let file_name = interner.intern(&file_path_str);
+        let mut strings = interner.local();
+        let mut timer = FileTimer::start();
         let file = File::open(&file_path_owned)?;
         let metadata = file.metadata()?;
         // Increase buffer size for better I/O performance
-        let mut reader = BufReader::with_capacity(64 * 1024, file); // Changed to 64KB like basic Smol
+        let mut reader = session_reader_with(&file_path_owned, file, 64 * 1024, options_owned.io)?; // Changed to 64KB like basic Smol
 
         // Get file creation time for fallback
         // Use platform-specific approach like main branch
@@ -296,19 +484,32 @@ async fn search_file(
                 now
             });
 
+        metrics::FILES_INDEXED.inc();
+        counters.file_scanned();
         let mut results = Vec::with_capacity(256); // 4x larger initial capacity to reduce reallocations
         let mut latest_timestamp: Option<String> = None;
         let mut first_timestamp: Option<String> = None;
         let mut line_buffer = Vec::with_capacity(16 * 1024); // 2x larger reusable line buffer
         let mut is_first_line = true;
         let mut found_summary_first = false;
+        let mut line_number = 0usize;
+        let sections = options_owned.sections_for(&query_owned);
+        let positions = options_owned.positions_for(&query_owned);
+        let mut bounds = SessionBounds::default();
+        let outcomes = query_owned.outcomes();
+        let kinds = query_owned.kinds();
+        let uses_timestamp = query_owned.uses_timestamp();
+        let mut tracker = OutcomeTracker::default();
+        let mut tool_calls = ToolCalls::default();
 
         loop {
             line_buffer.clear();
-            let bytes_read = reader.read_until(b'\n', &mut line_buffer)?;
+            let bytes_read = timer.read(|| reader.read_until(b'\n', &mut line_buffer))?;
             if bytes_read == 0 {
                 break; // EOF
             }
+            counters.bytes_read(bytes_read);
+            line_number += 1;
 
             // Skip empty lines
             if line_buffer.trim_ascii().is_empty() {
@@ -325,7 +526,9 @@ async fn search_file(
 
             // Parse JSON - Always use sonic-rs for optimized engine
             // Use from_slice to avoid UTF-8 string conversion
-            let message: Result<SessionMessage, _> = sonic_rs::from_slice(&line_buffer);
+            let message: Result<SessionMessage, _> =
+                timer.parse(|| sonic_rs::from_slice(&line_buffer));
+            counters.line_parsed(message.is_ok());
 
             match message {
                 Ok(message) => {
@@ -358,11 +561,56 @@ async fn search_file(
                         }
                     }
 
+                    if !positions.is_empty() && message_type != "summary" {
+                        bounds.record(line_number);
+                    }
+                    if !outcomes.is_empty() {
+                        tracker.observe(&message);
+                    }
+                    if options_owned.tool.is_some() {
+                        tool_calls.observe(&message);
+                    }
+
+                    if !options_owned.admits(&kinds, message.is_sidechain(), message.is_meta()) {
+                        continue;
+                    }
+                    if let Some(tool) = &options_owned.tool
+                        && !tool_calls.matches(tool, &message)
+                    {
+                        continue;
+                    }
+
+                    // Most messages do not match; rule them out from the
+                    // borrowed text before copying it into one string
+                    if sections.is_empty()
+                        && query_owned.rules_out(&message.searchable_segments())
+                    {
+                        continue;
+                    }
+
                     // Get searchable text
-                    let text = message.get_searchable_text();
+                    let text = message.get_searchable_text_with(&sections);
+
+                    // Determine timestamp based on message type (matching main branch logic)
+                    let resolve_timestamp = || {
+                        message
+                            .get_timestamp()
+                            .map(|ts| ts.to_string())
+                            .or_else(|| {
+                                // For summary messages, prefer first_timestamp over latest_timestamp
+                                if message_type == "summary" {
+                                    first_timestamp.clone()
+                                } else {
+                                    latest_timestamp.clone()
+                                }
+                            })
+                            .unwrap_or_else(|| file_ctime.clone())
+                    };
+                    // Time terms compare against the timestamp the result is shown with
+                    let query_timestamp = uses_timestamp.then(resolve_timestamp);
 
                     // Apply query condition
-                    if let Ok(matches) = query_owned.evaluate(&text)
+                    if let Ok(matches) = query_owned.evaluate_at(&text, query_timestamp.as_deref())
                         && matches {
                             // Apply inline filters
                             if let Some(role) = &options_owned.role {
@@ -376,24 +624,19 @@ async fn search_file(
                                 }
                             }
 
-                            if let Some(session_id) = &options_owned.session_id
-                                && message.get_session_id() != Some(session_id) {
-                                    continue;
-                                }
+                            if !options_owned.matches_session(message.get_session_id()) {
+                                continue;
+                            }
 
-                            // Determine timestamp based on message type (matching main branch logic)
-                            let final_timestamp = message
-                                .get_timestamp()
-                                .map(|ts| ts.to_string())
-                                .or_else(|| {
-                                    // For summary messages, prefer first_timestamp over latest_timestamp
-                                    if message_type == "summary" {
-                                        first_timestamp.clone()
-                                    } else {
-                                        latest_timestamp.clone()
-                                    }
-                                })
-                                .unwrap_or_else(|| file_ctime.clone());
+                            if options_owned.exclude_tool_result_messages
+                                && message.is_tool_result_only()
+                            {
+                                continue;
+                            }
+
+                            let final_timestamp = query_timestamp.unwrap_or_else(resolve_timestamp);
+                            let matched_in =
+                                query_owned.matched_sections(&text, Some(&final_timestamp));
 
                             // For SessionViewer and message details, we need raw_json
                             let raw_json = if should_capture_raw_json {
@@ -403,24 +646,32 @@ async fn search_file(
                                 None
                             };
 
-                            let message_type_owned = message_type.to_string();
+                            let message_type = strings.intern(message_type);
 
                             let result = SearchResult {
-                                file: file_path_str.clone(),
+                                file: file_name.clone(),
                                 uuid: message.get_uuid().unwrap_or("").to_string(),
                                 timestamp: final_timestamp,
-                                session_id: message.get_session_id().unwrap_or("").to_string(),
-                                role: message_type_owned.clone(),
+                                session_id: strings
+                                    .intern(message.get_session_id().unwrap_or("")),
+                                role: message_type.clone(),
                                 text: message.get_content_text(),
-                                message_type: message_type_owned,
+                                message_type,
                                 query: query_owned.clone(),
-                                cwd: message.get_cwd().unwrap_or("").to_string(),
+                                cwd: strings.intern(message.get_cwd().unwrap_or("")),
                                 raw_json,
+                                line_number: Some(line_number),
+                                matched_in,
+                                has_images: message.has_images(),
                             };
-                            results.push(result);
+                            results.push(CachedMatch {
+                                result,
+                                searchable_text: if keep_text { text } else { String::new() },
+                            });
                         }
                 }
                 Err(e) => {
+                    metrics::PARSE_ERRORS.inc();
                     if options_owned.verbose {
                         eprintln!("Failed to parse JSON in {file_path_owned:?}: {e:?}");
                     }
@@ -434,6 +685,18 @@ async fn search_file(
             );
         }
 
+        if !positions.is_empty() {
+            results.retain(|m: &CachedMatch| bounds.holds(&positions, m.result.line_number));
+        }
+        if !outcomes.is_empty()
+            && !tracker
+                .outcome()
+                .is_some_and(|outcome| outcomes.iter().all(|wanted| *wanted == outcome))
+        {
+            results.clear();
+        }
+
+        counters.file_timed(timer);
         Ok(results)
     })
     .await
@@ -442,6 +705,7 @@ async fn search_file(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::query::condition::{SECTION_SEPARATOR, TextSection};
     use crate::query::parse_query;
     use std::io::Write;
     use tempfile::tempdir;
@@ -475,6 +739,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_results_share_repeated_strings() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let projects = temp_dir.path().join("projects");
+        std::fs::create_dir(&projects)?;
+        for name in ["a.jsonl", "b.jsonl"] {
+            let mut file = File::create(projects.join(name))?;
+            for uuid in ["1", "2"] {
+                writeln!(
+                    file,
+                    r#"{{"type":"user","message":{{"role":"user","content":"shared text"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:0{uuid}Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/work","version":"1"}}"#
+                )?;
+            }
+        }
+        let pattern = projects.join("*.jsonl").display().to_string();
+
+        let engine = SmolEngine::new(SearchOptions::default());
+        let (results, _, _) = engine.search(&pattern, parse_query("shared")?)?;
+        assert_eq!(results.len(), 4);
+        let first = &results[0];
+        for result in &results[1..] {
+            assert_eq!(result.file.ptr_eq(&first.file), result.file == first.file);
+            assert!(result.session_id.ptr_eq(&first.session_id));
+            assert!(result.role.ptr_eq(&first.role));
+            assert!(result.message_type.ptr_eq(&first.role));
+            assert!(result.cwd.ptr_eq(&first.cwd));
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_role_filter() -> Result<()> {
         let temp_dir = tempdir()?;
@@ -910,6 +1204,98 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_line_numbers_count_every_line() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+
+        let mut file = File::create(&test_file)?;
+        writeln!(file, "{{invalid json")?;
+        writeln!(file)?;
+        writeln!(
+            file,
+            r#"{{"type":"user","message":{{"role":"user","content":"Jump here"}},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+        )?;
+
+        let engine = SmolEngine::new(SearchOptions::default());
+        let (results, _, _) = engine.search(test_file.to_str().unwrap(), parse_query("Jump")?)?;
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line_number, Some(3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_searches_compressed_sessions() -> Result<()> {
+        use flate2::{Compression, write::GzEncoder};
+
+        let temp_dir = tempdir()?;
+        let line = |text: &str, session: &str| {
+            format!(
+                r#"{{"type":"user","message":{{"role":"user","content":"{text}"}},"uuid":"{session}-1","timestamp":"2024-01-01T00:00:00Z","sessionId":"{session}","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+            ) + "\n"
+        };
+
+        let mut gzip = GzEncoder::new(
+            File::create(temp_dir.path().join("old.jsonl.gz"))?,
+            Compression::default(),
+        );
+        gzip.write_all(format!("{{invalid json\n{}", line("archived gzip", "gz")).as_bytes())?;
+        gzip.finish()?;
+        std::fs::write(
+            temp_dir.path().join("older.jsonl.zst"),
+            zstd::encode_all(line("archived zstd", "zst").as_bytes(), 0)?,
+        )?;
+        std::fs::write(
+            temp_dir.path().join("live.jsonl"),
+            line("live session", "live"),
+        )?;
+
+        let engine = SmolEngine::new(SearchOptions::default());
+        let (mut results, _, total) =
+            engine.search(temp_dir.path().to_str().unwrap(), parse_query("archived")?)?;
+        results.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+
+        assert_eq!(total, 2);
+        assert_eq!(results[0].session_id, "gz");
+        assert!(results[0].file.ends_with("old.jsonl.gz"));
+        assert_eq!(results[0].line_number, Some(2));
+        assert_eq!(results[1].session_id, "zst");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_last_scan_stats() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        let line = r#"{"type":"user","message":{"role":"user","content":"scan me"},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#;
+        let contents = format!("{line}\n{{invalid json\n\n{line}\n");
+        std::fs::write(&test_file, &contents)?;
+
+        let engine = SmolEngine::new(SearchOptions {
+            cache_results: true,
+            ..Default::default()
+        });
+        engine.search(test_file.to_str().unwrap(), parse_query("scan")?)?;
+        let scan = engine.last_scan_stats();
+        assert_eq!(scan.files_scanned, 1);
+        assert_eq!(scan.lines_parsed, 3);
+        assert_eq!(scan.parse_failures, 1);
+        assert_eq!(scan.bytes_read, contents.len() as u64);
+        assert_eq!(scan.cache_hits, 0);
+
+        // The same query again is answered by the result cache
+        engine.search(test_file.to_str().unwrap(), parse_query("scan")?)?;
+        let scan = engine.last_scan_stats();
+        assert_eq!(scan.files_scanned, 0);
+        assert_eq!(scan.cache_hits, 1);
+        assert_eq!(scan.cache_hit_ratio(), 1.0);
+
+        Ok(())
+    }
+
     #[test]
     fn test_special_characters_in_content() -> Result<()> {
         let temp_dir = tempdir()?;
@@ -1064,6 +1450,81 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_search_streaming_reports_each_file() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let projects_dir = temp_dir.path().join("projects");
+        std::fs::create_dir_all(&projects_dir)?;
+        for i in 0..3 {
+            let mut file = File::create(projects_dir.join(format!("session{i}.jsonl")))?;
+            writeln!(
+                file,
+                r#"{{"type":"user","message":{{"role":"user","content":"Message {i}"}},"uuid":"{i}","timestamp":"2024-01-01T00:00:0{i}Z","sessionId":"s{i}","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+            )?;
+        }
+
+        let pattern = projects_dir.join("*.jsonl");
+        let options = SearchOptions {
+            max_results: Some(2),
+            ..Default::default()
+        };
+        let engine = SmolEngine::new(options);
+        let mut batches = Vec::new();
+        let (results, _, total) = engine.search_streaming(
+            pattern.to_str().unwrap(),
+            parse_query("Message")?,
+            None,
+            SearchOrder::Descending,
+            &mut |batch, searched, files| batches.push((batch.len(), searched, files)),
+        )?;
+
+        // Batches arrive before max_results is applied to the final results
+        assert_eq!(batches.len(), 3);
+        assert!(
+            batches
+                .iter()
+                .all(|&(len, _, files)| len == 1 && files == 3)
+        );
+        assert_eq!(batches.last().map(|batch| batch.1), Some(3));
+        assert_eq!(total, 3);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].uuid, "2");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_relevance_order() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        let mut file = File::create(&test_file)?;
+        for (i, content) in [
+            "the cache is cold",
+            "cache hit, cache miss, connection pool",
+            "the connection pool is exhausted",
+        ]
+        .iter()
+        .enumerate()
+        {
+            writeln!(
+                file,
+                r#"{{"type":"user","message":{{"role":"user","content":"{content}"}},"uuid":"{i}","timestamp":"2024-01-01T00:00:0{i}Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+            )?;
+        }
+
+        let engine = SmolEngine::new(SearchOptions::default());
+        let (results, _, _) = engine.search_with_role_filter_and_order(
+            test_file.to_str().unwrap(),
+            parse_query("cache OR \"connection pool\"")?,
+            None,
+            SearchOrder::Relevance,
+        )?;
+        let uuids: Vec<&str> = results.iter().map(|r| r.uuid.as_str()).collect();
+        // Both terms beat the phrase alone, which beats one mention of the other term
+        assert_eq!(uuids, vec!["1", "2", "0"]);
+        Ok(())
+    }
+
     #[test]
     fn test_session_with_thinking_and_tools() -> Result<()> {
         let temp_dir = tempdir()?;
@@ -1091,4 +1552,491 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_thinking_only_search() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        std::fs::write(
+            &test_file,
+            r#"{"type":"assistant","message":{"id":"msg1","type":"message","role":"assistant","model":"claude","content":[{"type":"thinking","thinking":"Roll back before the deploy","signature":"sig"},{"type":"text","text":"Deploying now"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":10,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":5}},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+        )?;
+        let path = test_file.to_str().unwrap();
+
+        let thinking = SmolEngine::new(SearchOptions {
+            thinking_only: true,
+            ..Default::default()
+        });
+        let (results, _, _) = thinking.search(path, parse_query("\"roll back\"")?)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matched_in, vec![TextSection::Thinking]);
+        // Text outside the thinking block does not count
+        let (results, _, _) = thinking.search(path, parse_query("deploying")?)?;
+        assert!(results.is_empty());
+
+        let engine = SmolEngine::new(SearchOptions::default());
+        let (results, _, _) =
+            engine.search(path, parse_query("deploying AND thinking:\"roll back\"")?)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matched_in, vec![TextSection::Thinking]);
+        assert!(!results[0].text.contains(SECTION_SEPARATOR));
+        // Plain terms find thinking text too, without depending on the section
+        let (results, _, _) = engine.search(path, parse_query("\"roll back\"")?)?;
+        assert!(results[0].matched_in.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_image_search() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        std::fs::write(
+            &test_file,
+            [
+                r#"{"type":"user","message":{"role":"user","content":[{"type":"text","text":"Why is this button misaligned?"},{"type":"image","source":{"type":"base64","media_type":"image/png","data":"iVBORw0KGgo="}}]},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                r#"{"type":"user","message":{"role":"user","content":"The button png export fails"},"uuid":"2","timestamp":"2024-01-01T00:00:01Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+            ]
+            .join("\n"),
+        )?;
+        let path = test_file.to_str().unwrap();
+
+        let engine = SmolEngine::new(SearchOptions::default());
+        let (results, _, _) = engine.search(path, parse_query("images:png")?)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].uuid, "1");
+        assert!(results[0].has_images);
+        assert_eq!(results[0].matched_in, vec![TextSection::Images]);
+        assert_eq!(results[0].match_indicator(), "[image]");
+
+        // Plain terms only see image metadata when asked to
+        let (results, _, _) = engine.search(path, parse_query("png")?)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].uuid, "2");
+        assert!(!results[0].has_images);
+        let images = SmolEngine::new(SearchOptions {
+            include_images: true,
+            ..Default::default()
+        });
+        let (results, _, _) = images.search(path, parse_query("png")?)?;
+        assert_eq!(results.len(), 2);
+
+        // Messages with a screenshot that mention the button
+        let (results, _, _) = engine.search(path, parse_query("button AND images:image")?)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].uuid, "1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_view_options() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        std::fs::write(
+            &test_file,
+            [
+                r#"{"type":"summary","summary":"Fix the login form","leafUuid":"leaf"}"#,
+                r#"{"type":"user","message":{"role":"user","content":"Why does login fail?"},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"login.rs: 42 lines"}]},"uuid":"2","timestamp":"2024-01-01T00:00:01Z","sessionId":"s1","parentUuid":"1","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                r#"{"type":"user","message":{"role":"user","content":"login works in s2"},"uuid":"3","timestamp":"2024-01-01T00:00:02Z","sessionId":"s2","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+            ]
+            .join("\n"),
+        )?;
+        let path = test_file.to_str().unwrap();
+        let uuids = |options: SearchOptions| -> Result<Vec<String>> {
+            let (results, _, _) = SmolEngine::new(options).search(path, parse_query("login")?)?;
+            let mut uuids: Vec<String> = results.into_iter().map(|result| result.uuid).collect();
+            uuids.sort();
+            Ok(uuids)
+        };
+
+        // Summaries carry no session ID and are left out of a session filter...
+        let session = SearchOptions {
+            session_id: Some("s1".to_string()),
+            include_tool_results: true,
+            ..Default::default()
+        };
+        assert_eq!(uuids(session.clone())?, vec!["1", "2"]);
+        // ...unless asked for, as the session viewer does
+        assert_eq!(
+            uuids(SearchOptions {
+                session_summaries: true,
+                ..session.clone()
+            })?,
+            vec!["1", "2", "leaf"]
+        );
+
+        // Tool-result-only user messages can be hidden
+        assert_eq!(
+            uuids(SearchOptions {
+                exclude_tool_result_messages: true,
+                ..session
+            })?,
+            vec!["1"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_position_search() -> Result<()> {
+        use crate::index::SearchIndex;
+        use crate::query::SessionPosition;
+
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        std::fs::write(
+            &test_file,
+            concat!(
+                r#"{"type":"summary","summary":"Deploy session","leafUuid":"leaf"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"please deploy"},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"and check the logs"},"uuid":"2","timestamp":"2024-01-01T00:00:01Z","sessionId":"s1","parentUuid":"1","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"deploy done"},"uuid":"3","timestamp":"2024-01-01T00:00:02Z","sessionId":"s1","parentUuid":"2","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+            ),
+        )?;
+        let path = test_file.to_str().unwrap();
+        let uuids = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.uuid).collect()
+        };
+
+        // The summary line does not count as the first message
+        let engine = SmolEngine::new(SearchOptions::default());
+        let (results, _, _) = engine.search(path, parse_query("deploy AND is:first")?)?;
+        assert_eq!(uuids(results), vec!["1"]);
+        let (results, _, _) = engine.search(path, parse_query("logs AND is:first")?)?;
+        assert!(results.is_empty());
+
+        let last = SmolEngine::new(SearchOptions {
+            position: Some(SessionPosition::Last),
+            ..Default::default()
+        });
+        let (results, _, _) = last.search(path, parse_query("deploy")?)?;
+        assert_eq!(uuids(results), vec!["3"]);
+        let (results, _, _) = last.search(path, parse_query("is:first")?)?;
+        assert!(results.is_empty());
+
+        // Cached matches of a broader query without a position are not reused
+        let cached = SmolEngine::new(SearchOptions {
+            cache_results: true,
+            ..Default::default()
+        });
+        assert_eq!(cached.search(path, parse_query("deploy")?)?.0.len(), 3);
+        let (results, _, _) = cached.search(path, parse_query("deploy AND is:last")?)?;
+        assert_eq!(uuids(results), vec!["3"]);
+
+        let index_path = temp_dir.path().join("index.json");
+        let (index, _) = SearchIndex::build(std::slice::from_ref(&test_file), None);
+        index.save(&index_path)?;
+        let indexed = SmolEngine::new(SearchOptions {
+            index_path: Some(index_path),
+            ..Default::default()
+        });
+        let (results, _, _) = indexed.search(path, parse_query("deploy AND is:first")?)?;
+        assert_eq!(uuids(results), vec!["1"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sidechain_and_meta_messages() -> Result<()> {
+        use crate::index::SearchIndex;
+
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        std::fs::write(
+            &test_file,
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"deploy the app"},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"deploy from a subagent"},"uuid":"2","timestamp":"2024-01-01T00:00:01Z","sessionId":"s1","parentUuid":"1","isSidechain":true,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"Caveat: deploy output below"},"uuid":"3","timestamp":"2024-01-01T00:00:02Z","sessionId":"s1","parentUuid":"2","isSidechain":false,"userType":"external","cwd":"/","version":"1","isMeta":true}"#,
+                "\n",
+            ),
+        )?;
+        let path = test_file.to_str().unwrap();
+        let uuids = |engine: &SmolEngine, query: &str| -> Result<Vec<String>> {
+            let (results, _, _) = engine.search(path, parse_query(query)?)?;
+            let mut uuids: Vec<String> = results.into_iter().map(|r| r.uuid).collect();
+            uuids.sort();
+            Ok(uuids)
+        };
+
+        let engine = SmolEngine::new(SearchOptions::default());
+        assert_eq!(uuids(&engine, "deploy")?, vec!["1"]);
+        assert_eq!(uuids(&engine, "deploy AND is:sidechain")?, vec!["2"]);
+        assert_eq!(uuids(&engine, "is:meta")?, vec!["3"]);
+
+        let everything = SmolEngine::new(SearchOptions {
+            exclude_sidechain: false,
+            exclude_meta: false,
+            ..Default::default()
+        });
+        assert_eq!(uuids(&everything, "deploy")?, vec!["1", "2", "3"]);
+
+        // Cached matches are kept apart from those of other exclusions
+        let cached = SmolEngine::new(SearchOptions {
+            cache_results: true,
+            exclude_meta: false,
+            ..Default::default()
+        });
+        assert_eq!(uuids(&cached, "deploy")?, vec!["1", "3"]);
+        let cached = SmolEngine::new(SearchOptions {
+            cache_results: true,
+            ..Default::default()
+        });
+        assert_eq!(uuids(&cached, "deploy")?, vec!["1"]);
+
+        let index_path = temp_dir.path().join("index.json");
+        let (index, _) = SearchIndex::build(std::slice::from_ref(&test_file), None);
+        index.save(&index_path)?;
+        let indexed = SmolEngine::new(SearchOptions {
+            index_path: Some(index_path),
+            ..Default::default()
+        });
+        assert_eq!(uuids(&indexed, "deploy")?, vec!["1"]);
+        assert_eq!(uuids(&indexed, "deploy AND is:sidechain")?, vec!["2"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_outcome_search() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let projects_dir = temp_dir.path().join("projects");
+        std::fs::create_dir_all(&projects_dir)?;
+        let failed = projects_dir.join("failed.jsonl");
+        let done = projects_dir.join("done.jsonl");
+        let prompt = r#"{"type":"user","message":{"role":"user","content":"run the build"},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"SID","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#;
+        let tool_use = r#"{"type":"assistant","message":{"id":"msg2","type":"message","role":"assistant","model":"claude","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"make"}}],"stop_reason":"tool_use","stop_sequence":null,"usage":{"input_tokens":10,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":5}},"uuid":"2","timestamp":"2024-01-01T00:00:01Z","sessionId":"SID","parentUuid":"1","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#;
+        let failure = r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"make: *** Error 2","is_error":true}]},"uuid":"3","timestamp":"2024-01-01T00:00:02Z","sessionId":"SID","parentUuid":"2","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#;
+        let reply = r#"{"type":"assistant","message":{"id":"msg4","type":"message","role":"assistant","model":"claude","content":[{"type":"text","text":"The build passes"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":10,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":5}},"uuid":"4","timestamp":"2024-01-01T00:00:03Z","sessionId":"SID","parentUuid":"1","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#;
+        std::fs::write(
+            &failed,
+            [prompt, tool_use, failure]
+                .join("\n")
+                .replace("SID", "failed"),
+        )?;
+        std::fs::write(&done, [prompt, reply].join("\n").replace("SID", "done"))?;
+        let pattern = projects_dir.join("*.jsonl").display().to_string();
+
+        let engine = SmolEngine::new(SearchOptions::default());
+        let session_ids = |query: &str| -> Result<Vec<String>> {
+            let (results, _, _) = engine.search(&pattern, parse_query(query)?)?;
+            let mut ids: Vec<String> = results.into_iter().map(|r| r.session_id.into()).collect();
+            ids.dedup();
+            Ok(ids)
+        };
+        assert_eq!(session_ids("build AND outcome:error")?, vec!["failed"]);
+        assert_eq!(session_ids("build AND outcome:completed")?, vec!["done"]);
+        assert!(session_ids("outcome:abandoned")?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tool_search() -> Result<()> {
+        use crate::tool_calls::{ToolFilter, ToolStatus};
+
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        let base = r#""sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1","timestamp":"2024-01-01T00:00:00Z""#;
+        let tool_use = |uuid: &str, id: &str, name: &str, input: &str| {
+            format!(
+                r#"{{"type":"assistant","message":{{"id":"m{uuid}","type":"message","role":"assistant","model":"claude","content":[{{"type":"tool_use","id":"{id}","name":"{name}","input":{input}}}],"stop_reason":"tool_use","stop_sequence":null,"usage":{{"input_tokens":1,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":1}}}},"uuid":"{uuid}",{base}}}"#
+            )
+        };
+        let tool_result = |uuid: &str, id: &str, content: &str, is_error: bool| {
+            format!(
+                r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"{id}","content":"{content}","is_error":{is_error}}}]}},"uuid":"{uuid}",{base}}}"#
+            )
+        };
+        std::fs::write(
+            &test_file,
+            [
+                format!(
+                    r#"{{"type":"user","message":{{"role":"user","content":"run make"}},"uuid":"1",{base}}}"#
+                ),
+                tool_use("2", "t1", "Bash", r#"{"command":"make"}"#),
+                tool_result("3", "t1", "make: *** Error 2", true),
+                tool_use("4", "t2", "Read", r#"{"file_path":"Makefile"}"#),
+                tool_result("5", "t2", "all: make", false),
+            ]
+            .join("\n"),
+        )?;
+        let path = test_file.to_str().unwrap();
+        let uuids = |name: Option<&str>, status: Option<ToolStatus>| -> Result<Vec<String>> {
+            let options = SearchOptions {
+                tool: ToolFilter::new(name.map(str::to_string), status),
+                ..Default::default()
+            };
+            let (results, _, _) = SmolEngine::new(options).search(path, parse_query("make")?)?;
+            let mut uuids: Vec<String> = results.into_iter().map(|result| result.uuid).collect();
+            uuids.sort();
+            Ok(uuids)
+        };
+
+        assert_eq!(uuids(None, None)?, vec!["1", "2", "3", "4", "5"]);
+        // Calls to a tool and their results
+        assert_eq!(uuids(Some("bash"), None)?, vec!["2", "3"]);
+        // A status keeps the results only
+        assert_eq!(uuids(Some("Bash"), Some(ToolStatus::Error))?, vec!["3"]);
+        assert_eq!(uuids(None, Some(ToolStatus::Success))?, vec!["5"]);
+        assert!(uuids(Some("Read"), Some(ToolStatus::Error))?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_with_index_matches_full_scan() -> Result<()> {
+        use crate::index::SearchIndex;
+
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        let mut file = File::create(&test_file)?;
+        writeln!(
+            file,
+            r#"{{"type":"summary","summary":"Indexed session","leafUuid":"2"}}"#
+        )?;
+        writeln!(
+            file,
+            r#"{{"type":"user","message":{{"role":"user","content":"index me"}},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+        )?;
+        writeln!(
+            file,
+            r#"{{"type":"assistant","message":{{"id":"msg2","type":"message","role":"assistant","model":"claude","content":[{{"type":"tool_use","id":"tool1","name":"Bash","input":{{"command":"ls index"}}}},{{"type":"text","text":"Listed"}}],"stop_reason":"end_turn","stop_sequence":null,"usage":{{"input_tokens":10,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":5}}}},"uuid":"2","timestamp":"2024-01-01T00:00:01Z","sessionId":"s1","parentUuid":"1","isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+        )?;
+        drop(file);
+
+        let index_path = temp_dir.path().join("index.json");
+        let (index, _) = SearchIndex::build(std::slice::from_ref(&test_file), None);
+        index.save(&index_path)?;
+
+        let pattern = test_file.to_str().unwrap();
+        let full_scan =
+            SmolEngine::new(SearchOptions::default()).search(pattern, parse_query("index")?)?;
+        let indexed = SmolEngine::new(SearchOptions {
+            index_path: Some(index_path),
+            ..Default::default()
+        })
+        .search(pattern, parse_query("index")?)?;
+
+        assert_eq!(full_scan.0.len(), 3);
+        assert_eq!(indexed.0, full_scan.0);
+        assert_eq!(indexed.2, full_scan.2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cached_results_follow_query_and_file_changes() -> Result<()> {
+        use std::fs::OpenOptions;
+
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        let line = |uuid: &str, text: &str| {
+            format!(
+                r#"{{"type":"user","message":{{"role":"user","content":"{text}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:0{uuid}Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}}"#
+            )
+        };
+        let mut file = File::create(&test_file)?;
+        writeln!(file, "{}", line("1", "an Error occurred"))?;
+        writeln!(file, "{}", line("2", "err: retry later"))?;
+        drop(file);
+
+        let engine = SmolEngine::new(SearchOptions {
+            cache_results: true,
+            ..Default::default()
+        });
+        let pattern = test_file.to_str().unwrap();
+        let uuids = |query: &str| -> Result<Vec<String>> {
+            let (results, _, _) = engine.search(pattern, parse_query(query)?)?;
+            let mut uuids: Vec<String> = results.into_iter().map(|r| r.uuid).collect();
+            uuids.sort();
+            Ok(uuids)
+        };
+
+        assert_eq!(uuids("err")?, vec!["1", "2"]);
+        assert_eq!(uuids("error")?, vec!["1"]);
+        assert_eq!(uuids("err AND retry")?, vec!["2"]);
+
+        // A changed file is read again rather than answered from the cache
+        let mut file = OpenOptions::new().append(true).open(&test_file)?;
+        writeln!(file, "{}", line("3", "another error"))?;
+        drop(file);
+        assert_eq!(uuids("error")?, vec!["1", "3"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_terms_search() -> Result<()> {
+        use crate::index::SearchIndex;
+
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.jsonl");
+        std::fs::write(
+            &test_file,
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"deploy one"},"uuid":"1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"deploy two"},"uuid":"2","timestamp":"2024-03-01T00:00:00Z","sessionId":"s1","parentUuid":"1","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":"deploy three"},"uuid":"3","timestamp":"2024-05-01T00:00:00Z","sessionId":"s1","parentUuid":"2","isSidechain":false,"userType":"external","cwd":"/","version":"1"}"#,
+                "\n",
+            ),
+        )?;
+        let path = test_file.to_str().unwrap();
+        let uuids = |results: Vec<SearchResult>| -> Vec<String> {
+            let mut uuids: Vec<String> = results.into_iter().map(|r| r.uuid).collect();
+            uuids.sort();
+            uuids
+        };
+
+        let engine = SmolEngine::new(SearchOptions::default());
+        let (results, _, _) = engine.search(
+            path,
+            parse_query(
+                r#"deploy AND after:"2024-02-01T00:00:00Z" AND before:2024-04-01T00:00:00Z"#,
+            )?,
+        )?;
+        assert_eq!(uuids(results), vec!["2"]);
+        let (results, _, _) = engine.search(
+            path,
+            parse_query("before:2024-02-01T00:00:00Z OR since:2024-04-01T00:00:00Z")?,
+        )?;
+        assert_eq!(uuids(results), vec!["1", "3"]);
+        // Bounds are inclusive
+        let (results, _, _) =
+            engine.search(path, parse_query("NOT after:2024-03-01T00:00:00Z")?)?;
+        assert_eq!(uuids(results), vec!["1"]);
+
+        // Narrowing a cached query by time reuses its matches
+        let cached = SmolEngine::new(SearchOptions {
+            cache_results: true,
+            ..Default::default()
+        });
+        assert_eq!(cached.search(path, parse_query("deploy")?)?.0.len(), 3);
+        let (results, _, _) =
+            cached.search(path, parse_query("deploy AND after:2024-02-01T00:00:00Z")?)?;
+        assert_eq!(uuids(results), vec!["2", "3"]);
+
+        let index_path = temp_dir.path().join("index.json");
+        let (index, _) = SearchIndex::build(std::slice::from_ref(&test_file), None);
+        index.save(&index_path)?;
+        let indexed = SmolEngine::new(SearchOptions {
+            index_path: Some(index_path),
+            ..Default::default()
+        });
+        let (results, _, _) =
+            indexed.search(path, parse_query("deploy AND before:2024-02-01T00:00:00Z")?)?;
+        assert_eq!(uuids(results), vec!["1"]);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,115 @@
+//! Where the time of one search went, reported by `--timings`.
+//!
+//! Unlike the `profiling` feature this is always built in: the engines time
+//! each phase with a few `Instant::now()` calls per line, which is noise next
+//! to parsing the line.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Time spent in each phase of a search. Reading, parsing and matching run
+/// on many threads at once and are summed over them, so together they can
+/// exceed `total`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// Listing the session files to search
+    pub discovery: Duration,
+    /// Reading and decompressing lines
+    pub read: Duration,
+    /// Decoding the JSON of each line
+    pub parse: Duration,
+    /// Filters and query evaluation on parsed messages
+    pub matching: Duration,
+    /// Filtering, sorting, deduplicating and capping the merged results
+    pub sort: Duration,
+    /// Writing the results, measured by the caller
+    pub format: Duration,
+    /// Reading the matched files again to estimate costs for `--format json`,
+    /// measured by the caller
+    pub cost: Duration,
+    pub total: Duration,
+}
+
+impl Timings {
+    /// The breakdown in milliseconds, as `--timings` prints it
+    pub fn to_json(&self) -> serde_json::Value {
+        // Microsecond precision, without float noise in the last digits
+        let ms = |duration: Duration| duration.as_micros() as f64 / 1000.0;
+        serde_json::json!({
+            "discovery_ms": ms(self.discovery),
+            "read_ms": ms(self.read),
+            "parse_ms": ms(self.parse),
+            "match_ms": ms(self.matching),
+            "sort_ms": ms(self.sort),
+            "format_ms": ms(self.format),
+            "cost_ms": ms(self.cost),
+            "total_ms": ms(self.total),
+        })
+    }
+}
+
+/// Read, parse and match time of the files of one search, summed over the
+/// tasks that scanned them
+#[derive(Debug, Default)]
+pub struct PhaseCounters {
+    read_nanos: AtomicU64,
+    parse_nanos: AtomicU64,
+    match_nanos: AtomicU64,
+}
+
+impl PhaseCounters {
+    pub(super) fn add(&self, timer: FileTimer) {
+        let scanned = timer.started.elapsed();
+        let matching = scanned.saturating_sub(timer.read + timer.parse);
+        for (counter, duration) in [
+            (&self.read_nanos, timer.read),
+            (&self.parse_nanos, timer.parse),
+            (&self.match_nanos, matching),
+        ] {
+            counter.fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// The read, parse and match phases of `Timings`
+    pub fn snapshot(&self) -> Timings {
+        let load = |counter: &AtomicU64| Duration::from_nanos(counter.load(Ordering::Relaxed));
+        Timings {
+            read: load(&self.read_nanos),
+            parse: load(&self.parse_nanos),
+            matching: load(&self.match_nanos),
+            ..Default::default()
+        }
+    }
+}
+
+/// Times the scan of one file; whatever is neither reading nor parsing
+/// counts as matching
+pub struct FileTimer {
+    started: Instant,
+    read: Duration,
+    parse: Duration,
+}
+
+impl FileTimer {
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            read: Duration::ZERO,
+            parse: Duration::ZERO,
+        }
+    }
+
+    pub fn read<T>(&mut self, read: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = read();
+        self.read += start.elapsed();
+        value
+    }
+
+    pub fn parse<T>(&mut self, parse: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = parse();
+        self.parse += start.elapsed();
+        value
+    }
+}
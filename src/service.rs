@@ -0,0 +1,251 @@
+//! Search operations shared by the long-running frontends (`ccms lsp-like`,
+//! `ccms serve`).
+//!
+//! The service owns the file pattern and the base search options given on the
+//! command line; each call only supplies what differs per request.
+
+use crate::index::lookup_message;
+use crate::interactive_ratatui::domain::models::SearchOrder;
+use crate::metrics;
+use crate::query::{QueryCondition, SearchOptions, SearchResult, parse_query};
+use crate::search::ids::{resolve_message_id, resolve_session_id};
+use crate::search::{SearchEngineTrait, SmolEngine, discover_claude_files};
+use crate::stats::{SessionGroup, group_by_session};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Parameters of a search request; unset fields fall back to the service defaults
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchParams {
+    /// Query in ccms syntax; empty matches every message
+    pub query: String,
+    pub role: Option<String>,
+    pub session_id: Option<String>,
+    /// Working directory whose sessions to search
+    pub project: Option<String>,
+    pub max_results: Option<usize>,
+    /// RFC3339 timestamps
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    /// Number of matches before `maxResults` was applied
+    pub total_count: usize,
+    pub duration_ms: u64,
+}
+
+pub struct Service {
+    pattern: String,
+    options: SearchOptions,
+}
+
+impl Service {
+    pub fn new(pattern: String, options: SearchOptions) -> Self {
+        Self { pattern, options }
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Session files currently matched by the service pattern
+    pub fn files(&self) -> Result<Vec<PathBuf>> {
+        discover_claude_files(Some(&self.pattern))
+    }
+
+    /// Parse `query`, treating an empty query as "match everything"
+    pub fn parse(&self, query: &str) -> Result<QueryCondition> {
+        if query.trim().is_empty() {
+            Ok(QueryCondition::And { conditions: vec![] })
+        } else {
+            parse_query(query)
+        }
+    }
+
+    /// Base options with the per-request overrides of `params` applied
+    pub fn options_for(&self, params: &SearchParams) -> SearchOptions {
+        let mut options = self.options.clone();
+        if params.role.is_some() {
+            options.role = params.role.clone();
+        }
+        if params.session_id.is_some() {
+            options.session_id = params.session_id.clone();
+        }
+        if params.project.is_some() {
+            options.project_path = params.project.clone();
+        }
+        if params.max_results.is_some() {
+            options.max_results = params.max_results;
+        }
+        if params.before.is_some() {
+            options.before = params.before.clone();
+        }
+        if params.after.is_some() {
+            options.after = params.after.clone();
+        }
+        options
+    }
+
+    pub fn search(&self, params: &SearchParams) -> Result<SearchPage> {
+        metrics::SEARCHES_SERVED.inc();
+        let query = self.parse(&params.query)?;
+        let engine = SmolEngine::new(self.options_for(params));
+        let (results, duration, total_count) = engine.search(&self.pattern, query)?;
+
+        Ok(SearchPage {
+            results,
+            total_count,
+            duration_ms: duration.as_millis() as u64,
+        })
+    }
+
+    /// Every message of a session, oldest first; `session_id` may be a
+    /// unique prefix
+    pub fn get_session(&self, session_id: &str) -> Result<Vec<SearchResult>> {
+        metrics::SEARCHES_SERVED.inc();
+        let session_id = resolve_session_id(&self.files()?, session_id)?;
+        let options = SearchOptions {
+            session_id: Some(session_id),
+            max_results: None,
+            // Sessions are addressed by ID, wherever their project lives
+            project_path: None,
+            // and are returned whole
+            exclude_sidechain: false,
+            exclude_meta: false,
+            ..self.options.clone()
+        };
+        let engine = SmolEngine::new(options);
+        let (results, _, _) = engine.search_with_role_filter_and_order(
+            &self.pattern,
+            QueryCondition::And { conditions: vec![] },
+            None,
+            SearchOrder::Ascending,
+        )?;
+        Ok(results)
+    }
+
+    /// Sessions with messages matching `params`, most recently active first.
+    ///
+    /// `max_results` limits the number of sessions rather than messages.
+    pub fn list_sessions(&self, params: &SearchParams) -> Result<Vec<SessionGroup>> {
+        metrics::SEARCHES_SERVED.inc();
+        let query = self.parse(&params.query)?;
+        let options = SearchOptions {
+            max_results: None,
+            ..self.options_for(params)
+        };
+        let engine = SmolEngine::new(options);
+        let (results, _, _) = engine.search(&self.pattern, query)?;
+
+        let mut sessions = group_by_session(&results);
+        if let Some(limit) = params.max_results {
+            sessions.truncate(limit);
+        }
+        Ok(sessions)
+    }
+
+    /// The message with `uuid`, which may be a unique prefix
+    pub fn get_message(&self, uuid: &str) -> Result<Option<SearchResult>> {
+        metrics::SEARCHES_SERVED.inc();
+        let files = self.files()?;
+        let uuid = resolve_message_id(&files, uuid)?;
+        if let Some(index_path) = &self.options.index_path
+            && let Some(result) = lookup_message(index_path, &uuid, &files)
+        {
+            return Ok(Some(result));
+        }
+        let options = SearchOptions {
+            max_results: Some(1),
+            message_id: Some(uuid.clone()),
+            role: None,
+            session_id: None,
+            project_path: None,
+            exclude_sidechain: false,
+            exclude_meta: false,
+            ..self.options.clone()
+        };
+        let engine = SmolEngine::new(options);
+        let (results, _, _) = engine.search(&self.pattern, parse_query(&uuid)?)?;
+        Ok(results.into_iter().next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_search_session_and_message() -> Result<()> {
+        let dir = tempdir()?;
+        let session = dir.path().join("session.jsonl");
+        let mut file = File::create(&session)?;
+        writeln!(
+            file,
+            r#"{{"type":"user","message":{{"role":"user","content":"first question"}},"uuid":"u1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/test","version":"1"}}"#
+        )?;
+        writeln!(
+            file,
+            r#"{{"type":"user","message":{{"role":"user","content":"second question"}},"uuid":"u2","timestamp":"2024-01-01T00:00:01Z","sessionId":"s1","parentUuid":"u1","isSidechain":false,"userType":"external","cwd":"/test","version":"1"}}"#
+        )?;
+        drop(file);
+
+        let service = Service::new(
+            session.display().to_string(),
+            SearchOptions {
+                max_results: None,
+                ..Default::default()
+            },
+        );
+
+        let page = service.search(&SearchParams {
+            query: "second".to_string(),
+            ..Default::default()
+        })?;
+        assert_eq!(page.total_count, 1);
+        assert_eq!(page.results[0].uuid, "u2");
+
+        let page = service.search(&SearchParams {
+            max_results: Some(1),
+            ..Default::default()
+        })?;
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.total_count, 2);
+
+        let messages = service.get_session("s1")?;
+        let uuids: Vec<&str> = messages.iter().map(|m| m.uuid.as_str()).collect();
+        assert_eq!(uuids, vec!["u1", "u2"]);
+        assert!(messages[0].raw_json.is_some());
+        assert_eq!(service.get_session("s")?.len(), 2);
+
+        let sessions = service.list_sessions(&SearchParams {
+            query: "question".to_string(),
+            ..Default::default()
+        })?;
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].session_id, "s1");
+        assert_eq!(sessions[0].message_count, 2);
+        assert!(
+            service
+                .list_sessions(&SearchParams {
+                    max_results: Some(0),
+                    ..Default::default()
+                })?
+                .is_empty()
+        );
+
+        assert_eq!(service.get_message("u1")?.unwrap().text, "first question");
+        assert!(service.get_message("missing")?.is_none());
+        // "u" is a prefix of both messages
+        assert!(service.get_message("u").is_err());
+        Ok(())
+    }
+}
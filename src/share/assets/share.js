@@ -0,0 +1,8 @@
+document.querySelectorAll("[data-toggle]").forEach((button) => {
+  button.addEventListener("click", () => {
+    const open = button.dataset.toggle === "open";
+    document.querySelectorAll("details").forEach((details) => {
+      details.open = open;
+    });
+  });
+});
@@ -0,0 +1,220 @@
+//! Minimal, dependency-free syntax highlighting for code blocks in shared sessions.
+//!
+//! This is deliberately a tokenizer rather than a parser: it recognizes
+//! comments, strings, numbers and a common set of keywords, which is enough to
+//! make code readable without shipping a highlighter to the browser.
+
+/// Keywords shared by the languages that show up most in sessions
+const KEYWORDS: &[&str] = &[
+    "as",
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "def",
+    "default",
+    "do",
+    "elif",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "fi",
+    "finally",
+    "fn",
+    "for",
+    "from",
+    "func",
+    "function",
+    "if",
+    "impl",
+    "import",
+    "in",
+    "interface",
+    "let",
+    "match",
+    "mod",
+    "mut",
+    "new",
+    "nil",
+    "None",
+    "null",
+    "package",
+    "pub",
+    "return",
+    "self",
+    "static",
+    "struct",
+    "switch",
+    "then",
+    "throw",
+    "trait",
+    "true",
+    "True",
+    "False",
+    "try",
+    "type",
+    "use",
+    "var",
+    "where",
+    "while",
+    "with",
+    "yield",
+];
+
+/// HTML-escape `code` and wrap tokens in `<span class="tok-...">`
+pub fn highlight(code: &str, language: &str) -> String {
+    let line_comment = line_comment_marker(language);
+    let block_comments = !matches!(line_comment, Some("#") | None);
+    let chars: Vec<char> = code.chars().collect();
+    let mut output = String::with_capacity(code.len() * 2);
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let rest = &chars[i..];
+
+        if let Some(marker) = line_comment
+            && starts_with(rest, marker)
+        {
+            let end = find_from(&chars, i, &['\n']).unwrap_or(chars.len());
+            push_span(&mut output, "com", &chars[i..end]);
+            i = end;
+        } else if block_comments && starts_with(rest, "/*") {
+            let end = find_str(&chars, i + 2, "*/").map_or(chars.len(), |end| end + 2);
+            push_span(&mut output, "com", &chars[i..end]);
+            i = end;
+        } else if c == '"' || c == '\'' || c == '`' {
+            let end = string_end(&chars, i, c);
+            push_span(&mut output, "str", &chars[i..end]);
+            i = end;
+        } else if c.is_ascii_digit() {
+            let end = scan(&chars, i, |c| {
+                c.is_ascii_alphanumeric() || c == '.' || c == '_'
+            });
+            push_span(&mut output, "num", &chars[i..end]);
+            i = end;
+        } else if c.is_alphabetic() || c == '_' {
+            let end = scan(&chars, i, |c| c.is_alphanumeric() || c == '_');
+            let word: String = chars[i..end].iter().collect();
+            if KEYWORDS.contains(&word.as_str()) {
+                push_span(&mut output, "kw", &chars[i..end]);
+            } else {
+                output.push_str(&escape_html(&word));
+            }
+            i = end;
+        } else {
+            push_escaped(&mut output, c);
+            i += 1;
+        }
+    }
+
+    output
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_escaped(&mut output, c);
+    }
+    output
+}
+
+fn line_comment_marker(language: &str) -> Option<&'static str> {
+    match language.to_ascii_lowercase().as_str() {
+        "python" | "py" | "sh" | "bash" | "zsh" | "shell" | "console" | "yaml" | "yml" | "toml"
+        | "ruby" | "rb" | "dockerfile" | "make" | "makefile" => Some("#"),
+        "sql" | "lua" | "haskell" => Some("--"),
+        "json" | "text" | "txt" | "markdown" | "md" | "diff" => None,
+        _ => Some("//"),
+    }
+}
+
+fn push_span(output: &mut String, class: &str, chars: &[char]) {
+    output.push_str("<span class=\"tok-");
+    output.push_str(class);
+    output.push_str("\">");
+    for c in chars {
+        push_escaped(output, *c);
+    }
+    output.push_str("</span>");
+}
+
+fn push_escaped(output: &mut String, c: char) {
+    match c {
+        '&' => output.push_str("&amp;"),
+        '<' => output.push_str("&lt;"),
+        '>' => output.push_str("&gt;"),
+        '"' => output.push_str("&quot;"),
+        '\'' => output.push_str("&#39;"),
+        c => output.push(c),
+    }
+}
+
+fn starts_with(chars: &[char], prefix: &str) -> bool {
+    let mut prefix_chars = prefix.chars();
+    chars
+        .iter()
+        .take(prefix.len())
+        .all(|c| prefix_chars.next() == Some(*c))
+        && prefix_chars.next().is_none()
+}
+
+fn find_from(chars: &[char], start: usize, targets: &[char]) -> Option<usize> {
+    (start..chars.len()).find(|&i| targets.contains(&chars[i]))
+}
+
+fn find_str(chars: &[char], start: usize, needle: &str) -> Option<usize> {
+    (start..chars.len()).find(|&i| starts_with(&chars[i..], needle))
+}
+
+fn scan(chars: &[char], start: usize, accept: impl Fn(char) -> bool) -> usize {
+    (start..chars.len())
+        .find(|&i| !accept(chars[i]))
+        .unwrap_or(chars.len())
+}
+
+/// End (exclusive) of the string literal opened by `quote` at `start`.
+/// Strings other than template literals stop at the end of the line when unterminated.
+fn string_end(chars: &[char], start: usize, quote: char) -> usize {
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return i + 1,
+            '\n' if quote != '`' => return i,
+            _ => i += 1,
+        }
+    }
+    chars.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_tokens() {
+        assert_eq!(
+            highlight("let x = \"a<b\"; // done", "rust"),
+            "<span class=\"tok-kw\">let</span> x = <span class=\"tok-str\">&quot;a&lt;b&quot;</span>; \
+             <span class=\"tok-com\">// done</span>"
+        );
+        assert_eq!(
+            highlight("if x > 42: # check", "python"),
+            "<span class=\"tok-kw\">if</span> x &gt; <span class=\"tok-num\">42</span>: \
+             <span class=\"tok-com\"># check</span>"
+        );
+        // URLs are not comments in JSON
+        assert_eq!(
+            highlight("{\"url\": \"https://x\"}", "json"),
+            "{<span class=\"tok-str\">&quot;url&quot;</span>: \
+             <span class=\"tok-str\">&quot;https://x&quot;</span>}"
+        );
+    }
+}
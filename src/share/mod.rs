@@ -0,0 +1,422 @@
+//! `ccms share`: a session as a single self-contained HTML page.
+//!
+//! The page embeds its own styles and script and loads nothing from the
+//! network, so it can be mailed or attached to an issue and opened anywhere.
+//! Every piece of text is passed through a [`Scrubber`] first. Tool calls,
+//! tool results and thinking blocks are rendered collapsed, and fenced code
+//! blocks are highlighted.
+
+mod highlight;
+mod scrub;
+
+pub use highlight::{escape_html, highlight};
+pub use scrub::Scrubber;
+
+use crate::query::SearchOptions;
+use crate::schemas::{Content, SessionMessage, ToolResultContent, UserContent};
+use crate::service::Service;
+use anyhow::{Result, bail};
+use chrono::DateTime;
+use serde_json::Value;
+use std::fmt::Write as _;
+
+/// Longest tool result kept in the page, in characters
+const MAX_TOOL_RESULT_CHARS: usize = 20_000;
+/// Longest hint shown next to a collapsed tool call
+const TOOL_HINT_MAX_CHARS: usize = 80;
+
+const STYLE: &str = include_str!("assets/share.css");
+const SCRIPT: &str = include_str!("assets/share.js");
+
+#[derive(Debug, Clone)]
+pub struct ShareBundle {
+    pub html: String,
+    pub message_count: usize,
+}
+
+/// Render the session `session_id` found under `pattern`
+pub fn build_share_html(
+    pattern: &str,
+    session_id: &str,
+    scrubber: &Scrubber,
+) -> Result<ShareBundle> {
+    let service = Service::new(
+        pattern.to_string(),
+        SearchOptions {
+            max_results: None,
+            ..Default::default()
+        },
+    );
+    let messages: Vec<SessionMessage> = service
+        .get_session(session_id)?
+        .iter()
+        .filter_map(|result| result.raw_json.as_deref())
+        .filter_map(|raw| sonic_rs::from_str(raw).ok())
+        .collect();
+
+    if messages.is_empty() {
+        bail!("no messages found for session_id '{session_id}'");
+    }
+
+    Ok(ShareBundle {
+        html: render_html(&messages, scrubber),
+        message_count: messages.len(),
+    })
+}
+
+pub fn render_html(messages: &[SessionMessage], scrubber: &Scrubber) -> String {
+    let title = messages
+        .iter()
+        .find(|message| message.get_type() == "user")
+        .map(|message| first_line(&message.get_content_text(), TOOL_HINT_MAX_CHARS))
+        .filter(|line| !line.is_empty())
+        .map(|line| scrubber.scrub(&line))
+        .unwrap_or_else(|| "Claude session".to_string());
+    let project = messages
+        .iter()
+        .find_map(|message| message.get_cwd())
+        .map(|cwd| scrubber.scrub(cwd))
+        .unwrap_or_default();
+    let timestamps: Vec<&str> = messages
+        .iter()
+        .filter_map(|message| message.get_timestamp())
+        .collect();
+
+    let mut body = String::new();
+    for message in messages {
+        render_message(&mut body, message, scrubber);
+    }
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <meta name=\"generator\" content=\"ccms share\">\n\
+         <title>{title}</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n\
+         <header class=\"page\">\n<h1>{title}</h1>\n\
+         <p class=\"meta\">{project} · {range} · {count} messages</p>\n\
+         <nav><button type=\"button\" data-toggle=\"open\">Expand all</button> \
+         <button type=\"button\" data-toggle=\"close\">Collapse all</button></nav>\n\
+         </header>\n<main>\n{body}</main>\n<script>\n{SCRIPT}</script>\n</body>\n</html>\n",
+        title = escape_html(&title),
+        project = escape_html(&project),
+        range = escape_html(&format_range(&timestamps)),
+        count = messages.len(),
+    );
+    html
+}
+
+fn render_message(output: &mut String, message: &SessionMessage, scrubber: &Scrubber) {
+    let mut body = String::new();
+    let role = match message {
+        SessionMessage::Summary { .. } => return,
+        SessionMessage::System {
+            content, is_meta, ..
+        } => {
+            if *is_meta {
+                return;
+            }
+            render_text(&mut body, content, scrubber);
+            "system"
+        }
+        SessionMessage::User {
+            message, is_meta, ..
+        } => {
+            if *is_meta == Some(true) {
+                return;
+            }
+            match &message.content {
+                UserContent::String(text) => {
+                    render_text(&mut body, text, scrubber);
+                    "user"
+                }
+                UserContent::Array(contents) => {
+                    for content in contents {
+                        render_content(&mut body, content, scrubber);
+                    }
+                    let only_results = contents
+                        .iter()
+                        .all(|content| matches!(content, Content::ToolResult { .. }));
+                    if only_results { "tool" } else { "user" }
+                }
+            }
+        }
+        SessionMessage::Assistant { message, .. } => {
+            for content in &message.content {
+                render_content(&mut body, content, scrubber);
+            }
+            "assistant"
+        }
+    };
+    if body.is_empty() {
+        return;
+    }
+
+    let label = match role {
+        "tool" => "Tool result",
+        "assistant" => "Assistant",
+        "system" => "System",
+        _ => "User",
+    };
+    let time = message
+        .get_timestamp()
+        .map(format_timestamp)
+        .unwrap_or_default();
+    let _ = write!(
+        output,
+        "<section class=\"message {role}\">\n<header><span class=\"role\">{label}</span>\
+         <time>{time}</time></header>\n{body}</section>\n"
+    );
+}
+
+fn render_content(output: &mut String, content: &Content, scrubber: &Scrubber) {
+    match content {
+        Content::Text { text } => render_text(output, text, scrubber),
+        Content::Thinking { thinking, .. } => {
+            output.push_str("<details class=\"thinking\"><summary>Thinking</summary>\n");
+            render_text(output, thinking, scrubber);
+            output.push_str("</details>\n");
+        }
+        Content::ToolUse { name, input, .. } => {
+            let hint = tool_hint(input)
+                .map(|hint| scrubber.scrub(&first_line(hint, TOOL_HINT_MAX_CHARS)))
+                .unwrap_or_default();
+            let _ = writeln!(
+                output,
+                "<details class=\"tool-use\"><summary><span class=\"tool-name\">{}</span> \
+                 <code>{}</code></summary>",
+                escape_html(name),
+                escape_html(&hint)
+            );
+            match input.get("command").and_then(Value::as_str) {
+                Some(command) if name == "Bash" => {
+                    render_code(output, &scrubber.scrub(command), "bash");
+                }
+                _ => {
+                    let pretty = serde_json::to_string_pretty(input).unwrap_or_default();
+                    render_code(output, &scrubber.scrub(&pretty), "json");
+                }
+            }
+            output.push_str("</details>\n");
+        }
+        Content::ToolResult {
+            content, is_error, ..
+        } => {
+            let text = content.as_ref().map(tool_result_text).unwrap_or_default();
+            let (class, label) = if *is_error == Some(true) {
+                ("tool-result error", "Error")
+            } else {
+                ("tool-result", "Result")
+            };
+            let _ = write!(
+                output,
+                "<details class=\"{class}\"><summary>{label} <span class=\"size\">{} lines</span>\
+                 </summary>\n<pre>{}</pre>\n</details>\n",
+                text.lines().count(),
+                escape_html(&truncate(&scrubber.scrub(&text), MAX_TOOL_RESULT_CHARS))
+            );
+        }
+        Content::Image { .. } => output.push_str("<p class=\"omitted\">[image omitted]</p>\n"),
+    }
+}
+
+/// Text with fenced code blocks highlighted and everything else kept verbatim
+fn render_text(output: &mut String, text: &str, scrubber: &Scrubber) {
+    let text = scrubber.scrub(text);
+    if text.trim().is_empty() {
+        return;
+    }
+
+    let mut prose = String::new();
+    let mut code: Option<(String, String)> = None;
+    for line in text.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+        match (&mut code, fence) {
+            (None, Some(language)) => {
+                flush_prose(output, &mut prose);
+                code = Some((language.trim().to_string(), String::new()));
+            }
+            (Some((language, block)), Some(_)) => {
+                render_code(output, block.trim_end_matches('\n'), language);
+                code = None;
+            }
+            (Some((_, block)), None) => {
+                block.push_str(line);
+                block.push('\n');
+            }
+            (None, None) => {
+                prose.push_str(line);
+                prose.push('\n');
+            }
+        }
+    }
+    // An unterminated fence still renders as code
+    if let Some((language, block)) = code {
+        render_code(output, block.trim_end_matches('\n'), &language);
+    }
+    flush_prose(output, &mut prose);
+}
+
+fn flush_prose(output: &mut String, prose: &mut String) {
+    let text = prose.trim_matches('\n');
+    if !text.is_empty() {
+        let _ = writeln!(output, "<div class=\"text\">{}</div>", inline_code(text));
+    }
+    prose.clear();
+}
+
+/// Escape `text`, turning `backtick spans` into `<code>`
+fn inline_code(text: &str) -> String {
+    let parts: Vec<&str> = text.split('`').collect();
+    // An odd number of backticks leaves the last one unmatched
+    let unmatched = parts.len().is_multiple_of(2);
+    let mut output = String::new();
+    for (i, part) in parts.iter().enumerate() {
+        let closed = !(unmatched && i == parts.len() - 1);
+        if i % 2 == 1 && closed && !part.contains('\n') {
+            let _ = write!(output, "<code>{}</code>", escape_html(part));
+        } else {
+            if i % 2 == 1 {
+                output.push('`');
+            }
+            output.push_str(&escape_html(part));
+        }
+    }
+    output
+}
+
+fn render_code(output: &mut String, code: &str, language: &str) {
+    let language = language.split_whitespace().next().unwrap_or("");
+    let _ = writeln!(
+        output,
+        "<pre class=\"code\" data-language=\"{}\"><code>{}</code></pre>",
+        escape_html(language),
+        highlight(code, language)
+    );
+}
+
+fn tool_result_text(content: &ToolResultContent) -> String {
+    match content {
+        ToolResultContent::String(text) => text.clone(),
+        ToolResultContent::TextArray(items) => items
+            .iter()
+            .map(|item| item.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ToolResultContent::ImageArray(_) => "[image omitted]".to_string(),
+        ToolResultContent::Value(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+    }
+}
+
+/// The input field that best describes a tool call at a glance
+fn tool_hint(input: &Value) -> Option<&str> {
+    [
+        "command",
+        "file_path",
+        "path",
+        "pattern",
+        "url",
+        "query",
+        "description",
+    ]
+    .iter()
+    .find_map(|key| input.get(key).and_then(Value::as_str))
+}
+
+fn first_line(text: &str, max_chars: usize) -> String {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+    truncate(line, max_chars)
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!(
+            "{}… ({} more characters)",
+            &text[..end],
+            text[end..].chars().count()
+        ),
+        None => text.to_string(),
+    }
+}
+
+fn format_timestamp(timestamp: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+fn format_range(timestamps: &[&str]) -> String {
+    match (timestamps.iter().min(), timestamps.iter().max()) {
+        (Some(first), Some(last)) if first == last => format_timestamp(first),
+        (Some(first), Some(last)) => {
+            format!("{} – {}", format_timestamp(first), format_timestamp(last))
+        }
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_build_share_html() -> Result<()> {
+        let dir = tempdir()?;
+        let session = dir.path().join("session.jsonl");
+        let mut file = File::create(&session)?;
+        writeln!(
+            file,
+            r#"{{"type":"user","message":{{"role":"user","content":"Why does <main> panic? key sk-ant-REDACTED"}},"uuid":"u1","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/Users/alice/app","version":"1"}}"#
+        )?;
+        writeln!(
+            file,
+            r#"{{"type":"assistant","message":{{"id":"m1","type":"message","role":"assistant","model":"claude","content":[{{"type":"text","text":"Let me check.\n```rust\nfn main() {{}}\n```"}},{{"type":"tool_use","id":"t1","name":"Bash","input":{{"command":"cat /Users/alice/app/src/main.rs"}}}}],"stop_reason":null,"stop_sequence":null,"usage":{{"input_tokens":1,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":1}}}},"uuid":"a1","timestamp":"2024-01-01T00:00:05Z","sessionId":"s1","parentUuid":"u1","isSidechain":false,"userType":"external","cwd":"/Users/alice/app","version":"1"}}"#
+        )?;
+        writeln!(
+            file,
+            r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"t1","content":"fn main() {{ panic!() }}"}}]}},"uuid":"u2","timestamp":"2024-01-01T00:00:06Z","sessionId":"s1","parentUuid":"a1","isSidechain":false,"userType":"external","cwd":"/Users/alice/app","version":"1"}}"#
+        )?;
+        drop(file);
+
+        let scrubber = Scrubber::new(&[])?;
+        let bundle = build_share_html(&session.display().to_string(), "s1", &scrubber)?;
+        let html = &bundle.html;
+        assert_eq!(bundle.message_count, 3);
+
+        assert!(html.contains("<title>Why does &lt;main&gt; panic? key [api-key]</title>"));
+        assert!(html.contains("/Users/user/app · 2024-01-01 00:00 UTC – 2024-01-01 00:00 UTC"));
+        assert!(html.contains(
+            "<details class=\"tool-use\"><summary><span class=\"tool-name\">Bash</span> \
+             <code>cat /Users/user/app/src/main.rs</code></summary>"
+        ));
+        assert!(html.contains("<span class=\"tok-kw\">fn</span> main()"));
+        assert!(html.contains("<section class=\"message tool\">"));
+        assert!(!html.contains("alice"));
+        assert!(!html.contains("sk-ant"));
+        // Self-contained: nothing is fetched when the page is opened
+        assert!(!html.contains("src=\"http"));
+        assert!(!html.contains("href=\"http"));
+
+        assert!(build_share_html(&session.display().to_string(), "missing", &scrubber).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_inline_code_and_truncate() {
+        assert_eq!(
+            inline_code("run `cargo <test>` now"),
+            "run <code>cargo &lt;test&gt;</code> now"
+        );
+        assert_eq!(inline_code("a ` b"), "a ` b");
+        assert_eq!(truncate("abcdef", 3), "abc… (3 more characters)");
+        assert_eq!(truncate("abc", 3), "abc");
+    }
+}
@@ -1,5 +1,10 @@
+use crate::query::SearchResult;
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 
+/// Longest summary line shown for a session group, in characters
+const SUMMARY_MAX_CHARS: usize = 100;
+
 #[derive(Debug, Default)]
 pub struct Statistics {
     pub total_messages: usize,
@@ -230,6 +235,126 @@ pub fn format_statistics(stats: &Statistics, use_color: bool) -> String {
     output
 }
 
+/// Matches of one session collapsed into a single entry (`--group-by session`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionGroup {
+    pub session_id: String,
+    /// Working directory of the session
+    pub project: String,
+    pub file: String,
+    pub first_timestamp: String,
+    pub last_timestamp: String,
+    /// Number of matching messages in the session
+    pub message_count: usize,
+    /// First line of the earliest match
+    pub summary: String,
+}
+
+/// Collapse `results` per session, most recently active session first
+pub fn group_by_session(results: &[SearchResult]) -> Vec<SessionGroup> {
+    let mut groups: HashMap<&str, (SessionGroup, &str)> = HashMap::new();
+
+    for result in results {
+        let (group, earliest_text) =
+            groups.entry(result.session_id.as_str()).or_insert_with(|| {
+                (
+                    SessionGroup {
+                        session_id: result.session_id.to_string(),
+                        project: result.cwd.to_string(),
+                        file: result.file.to_string(),
+                        first_timestamp: result.timestamp.clone(),
+                        last_timestamp: result.timestamp.clone(),
+                        message_count: 0,
+                        summary: String::new(),
+                    },
+                    result.text.as_str(),
+                )
+            });
+
+        group.message_count += 1;
+        if result.timestamp < group.first_timestamp {
+            group.first_timestamp = result.timestamp.clone();
+            *earliest_text = &result.text;
+        }
+        if result.timestamp > group.last_timestamp {
+            group.last_timestamp = result.timestamp.clone();
+        }
+    }
+
+    let mut groups: Vec<SessionGroup> = groups
+        .into_values()
+        .map(|(mut group, earliest_text)| {
+            group.summary = summary_line(earliest_text);
+            group
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.last_timestamp
+            .cmp(&a.last_timestamp)
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+    groups
+}
+
+pub fn format_session_groups(groups: &[SessionGroup], use_color: bool) -> String {
+    use colored::Colorize;
+
+    let mut output = String::new();
+
+    for group in groups {
+        let header = format!(
+            "Session {} ({} {})",
+            group.session_id,
+            group.message_count,
+            if group.message_count == 1 {
+                "match"
+            } else {
+                "matches"
+            }
+        );
+        let details = format!(
+            "  {} → {} | {}",
+            format_timestamp(&group.first_timestamp),
+            format_timestamp(&group.last_timestamp),
+            group.project
+        );
+        let summary = format!("  {}", group.summary);
+
+        if use_color {
+            output.push_str(&header.bright_blue().bold().to_string());
+            output.push('\n');
+            output.push_str(&details.dimmed().to_string());
+            output.push('\n');
+            output.push_str(&summary.bright_white().to_string());
+        } else {
+            output.push_str(&header);
+            output.push('\n');
+            output.push_str(&details);
+            output.push('\n');
+            output.push_str(&summary);
+        }
+        output.push_str("\n\n");
+    }
+
+    output
+}
+
+fn summary_line(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+    if line.chars().count() > SUMMARY_MAX_CHARS {
+        format!(
+            "{}…",
+            line.chars().take(SUMMARY_MAX_CHARS).collect::<String>()
+        )
+    } else {
+        line.to_string()
+    }
+}
+
 fn format_timestamp(timestamp: &str) -> String {
     use chrono::{DateTime, Local, TimeZone};
 
@@ -416,4 +541,56 @@ mod tests {
         assert!(output.contains("system: 1"));
         assert!(output.contains("summary: 1"));
     }
+
+    fn result(session_id: &str, timestamp: &str, text: &str) -> SearchResult {
+        SearchResult {
+            file: format!("/logs/{session_id}.jsonl").into(),
+            uuid: format!("{session_id}-{timestamp}"),
+            timestamp: timestamp.to_string(),
+            session_id: session_id.into(),
+            role: "user".into(),
+            text: text.to_string(),
+            message_type: "user".into(),
+            query: crate::query::QueryCondition::Literal {
+                pattern: String::new(),
+                case_sensitive: false,
+            },
+            cwd: format!("/project/{session_id}").into(),
+            raw_json: None,
+            line_number: None,
+            matched_in: Vec::new(),
+            has_images: false,
+        }
+    }
+
+    #[test]
+    fn test_group_by_session() {
+        let results = vec![
+            result("a", "2024-01-01T10:00:00Z", "later in a"),
+            result("b", "2024-01-02T00:00:00Z", "only b"),
+            result(
+                "a",
+                "2024-01-01T09:00:00Z",
+                "\n  fix the parser\nmore detail",
+            ),
+            result("a", "2024-01-01T11:00:00Z", "latest in a"),
+        ];
+
+        let groups = group_by_session(&results);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].session_id, "b");
+        assert_eq!(groups[0].message_count, 1);
+
+        let a = &groups[1];
+        assert_eq!(a.project, "/project/a");
+        assert_eq!(a.message_count, 3);
+        assert_eq!(a.first_timestamp, "2024-01-01T09:00:00Z");
+        assert_eq!(a.last_timestamp, "2024-01-01T11:00:00Z");
+        assert_eq!(a.summary, "fix the parser");
+
+        let text = format_session_groups(&groups, false);
+        assert!(text.starts_with("Session b (1 match)\n"));
+        assert!(text.contains("Session a (3 matches)\n"));
+        assert!(text.contains("| /project/a\n  fix the parser\n"));
+    }
 }
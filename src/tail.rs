@@ -0,0 +1,347 @@
+//! Follow session files and pick up messages appended since the last poll.
+//!
+//! Only complete lines are consumed, so a message that is still being written
+//! is returned by the poll after the writer finishes its line.
+
+use crate::query::{QueryCondition, SearchOptions, SearchResult};
+use crate::schemas::SessionMessage;
+use crate::search::compression::is_compressed;
+use crate::search::{discover_claude_files, pattern_root};
+use crate::tool_calls::ToolCalls;
+use crate::utils::path_encoding;
+use anyhow::{Context, Result, bail};
+use notify::{RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// How long to keep collecting file events before reading, so a burst of
+/// writes is handled in one pass
+pub(crate) const EVENT_SETTLE_TIME: Duration = Duration::from_millis(100);
+
+/// Read position inside a followed file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Cursor {
+    offset: u64,
+    /// Number of complete lines before `offset`
+    line: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Tail {
+    cursors: HashMap<PathBuf, Cursor>,
+    /// Tool calls read from each file, for `SearchOptions::tool`
+    tool_calls: HashMap<PathBuf, ToolCalls>,
+}
+
+impl Tail {
+    /// Start following `files` from their current end, so only messages
+    /// written afterwards are reported
+    pub fn from_end(files: &[PathBuf]) -> Self {
+        let cursors = files
+            .iter()
+            .filter_map(|path| Some((path.clone(), end_cursor(path).ok()?)))
+            .collect();
+        Self {
+            cursors,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_following(&self, path: &Path) -> bool {
+        self.cursors.contains_key(path)
+    }
+
+    /// Read lines appended to `files` since the previous poll and return the
+    /// messages matching `query` and the role/session/project filters of `options`.
+    ///
+    /// Files seen for the first time are read from the start, since they are
+    /// sessions created after following began. A file that shrank is treated
+    /// as rewritten and read again from the start.
+    pub fn poll(
+        &mut self,
+        files: &[PathBuf],
+        query: &QueryCondition,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>> {
+        let mut results = Vec::new();
+
+        for path in files {
+            // Archives are not appended to, and offsets into them are compressed bytes
+            if is_compressed(path) {
+                continue;
+            }
+            if let Some(project_path) = &options.project_path
+                && !path_encoding::file_belongs_to_project(&path.to_string_lossy(), project_path)
+            {
+                continue;
+            }
+
+            let Ok(len) = path.metadata().map(|metadata| metadata.len()) else {
+                continue;
+            };
+            let cursor = self.cursors.entry(path.clone()).or_default();
+            let tool_calls = self.tool_calls.entry(path.clone()).or_default();
+            if len < cursor.offset {
+                *cursor = Cursor::default();
+                *tool_calls = ToolCalls::default();
+            }
+            if len == cursor.offset {
+                continue;
+            }
+
+            read_appended(path, cursor, tool_calls, query, options, &mut results)?;
+        }
+
+        Ok(results)
+    }
+}
+
+/// Follow the session files matched by `pattern` until an error occurs,
+/// passing every batch of newly appended matches to `on_match`.
+///
+/// Files are watched with the platform file-notification API (inotify,
+/// FSEvents, ...), and sessions created while following are picked up too.
+pub fn follow(
+    pattern: &str,
+    query: &QueryCondition,
+    options: &SearchOptions,
+    mut on_match: impl FnMut(&[SearchResult]) -> Result<()>,
+) -> Result<()> {
+    if !options.positions_for(query).is_empty() || !query.outcomes().is_empty() {
+        bail!("--position, is:first, is:last and outcome: cannot be used when following sessions");
+    }
+    let query = &options.scope_query(query.clone());
+    let root = pattern_root(Some(pattern));
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .with_context(|| format!("failed to watch {}", root.display()))?;
+
+    let mut files = discover_claude_files(Some(pattern))?;
+    let mut tail = Tail::from_end(&files);
+
+    while let Ok(event) = events.recv() {
+        let mut rediscover = false;
+        let mut note = |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                rediscover |= event.paths.iter().any(|path| !tail.is_following(path));
+            }
+        };
+        note(event);
+        while let Ok(event) = events.recv_timeout(EVENT_SETTLE_TIME) {
+            note(event);
+        }
+
+        if rediscover {
+            files = discover_claude_files(Some(pattern))?;
+        }
+        let results = tail.poll(&files, query, options)?;
+        if !results.is_empty() {
+            on_match(&results)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn end_cursor(path: &Path) -> Result<Cursor> {
+    let mut cursor = Cursor::default();
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let bytes_read = reader.read_until(b'\n', &mut line)?;
+        if bytes_read == 0 || !line.ends_with(b"\n") {
+            break;
+        }
+        cursor.offset += bytes_read as u64;
+        cursor.line += 1;
+    }
+    Ok(cursor)
+}
+
+fn read_appended(
+    path: &Path,
+    cursor: &mut Cursor,
+    tool_calls: &mut ToolCalls,
+    query: &QueryCondition,
+    options: &SearchOptions,
+    results: &mut Vec<SearchResult>,
+) -> Result<()> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(cursor.offset))?;
+    let mut reader = BufReader::new(file);
+    let mut line_buffer = Vec::with_capacity(16 * 1024);
+
+    loop {
+        line_buffer.clear();
+        let bytes_read = reader.read_until(b'\n', &mut line_buffer)?;
+        // Leave a partially written line for the next poll
+        if bytes_read == 0 || !line_buffer.ends_with(b"\n") {
+            break;
+        }
+        cursor.offset += bytes_read as u64;
+        cursor.line += 1;
+
+        let line = line_buffer.trim_ascii();
+        if line.is_empty() {
+            continue;
+        }
+        let Ok(message) = sonic_rs::from_slice::<SessionMessage>(line) else {
+            continue;
+        };
+        if options.tool.is_some() {
+            tool_calls.observe(&message);
+        }
+
+        if let Some(result) = match_message(
+            &message,
+            line,
+            path,
+            cursor.line,
+            tool_calls,
+            query,
+            options,
+        ) {
+            results.push(result);
+        }
+    }
+
+    Ok(())
+}
+
+fn match_message(
+    message: &SessionMessage,
+    line: &[u8],
+    path: &Path,
+    line_number: usize,
+    tool_calls: &ToolCalls,
+    query: &QueryCondition,
+    options: &SearchOptions,
+) -> Option<SearchResult> {
+    if !options.admits(&query.kinds(), message.is_sidechain(), message.is_meta())
+        || options
+            .tool
+            .as_ref()
+            .is_some_and(|tool| !tool_calls.matches(tool, message))
+    {
+        return None;
+    }
+    let text = message.get_searchable_text_with(&options.sections_for(query));
+    if !query
+        .evaluate_at(&text, message.get_timestamp())
+        .unwrap_or(false)
+    {
+        return None;
+    }
+
+    let message_type = message.get_type();
+    if let Some(role) = &options.role
+        && message_type != role
+    {
+        return None;
+    }
+    if !options.matches_session(message.get_session_id())
+        || (options.exclude_tool_result_messages && message.is_tool_result_only())
+    {
+        return None;
+    }
+
+    let result = SearchResult {
+        file: path.display().to_string().into(),
+        uuid: message.get_uuid().unwrap_or("").to_string(),
+        timestamp: message.get_timestamp().unwrap_or("").to_string(),
+        session_id: message.get_session_id().unwrap_or("").into(),
+        role: message_type.into(),
+        text: message.get_content_text(),
+        message_type: message_type.into(),
+        query: query.clone(),
+        cwd: message.get_cwd().unwrap_or("").into(),
+        raw_json: Some(String::from_utf8_lossy(line).into_owned()),
+        line_number: Some(line_number),
+        matched_in: query.matched_sections(&text, message.get_timestamp()),
+        has_images: message.has_images(),
+    };
+    (!options.excludes(&result)).then_some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::parse_query;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn user_line(uuid: &str, content: &str) -> String {
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":"{content}"}},"uuid":"{uuid}","timestamp":"2024-01-01T00:00:00Z","sessionId":"s1","parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/test","version":"1"}}"#
+        )
+    }
+
+    fn append(path: &Path, data: &str) -> Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(data.as_bytes())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_poll_reports_only_appended_matches() -> Result<()> {
+        let dir = tempdir()?;
+        let session = dir.path().join("session.jsonl");
+        append(&session, &format!("{}\n", user_line("u1", "error before")))?;
+
+        let files = vec![session.clone()];
+        let mut tail = Tail::from_end(&files);
+        let query = parse_query("error")?;
+        let options = SearchOptions::default();
+        assert!(tail.poll(&files, &query, &options)?.is_empty());
+
+        append(
+            &session,
+            &format!(
+                "{}\n{}\n",
+                user_line("u2", "all good"),
+                user_line("u3", "error after")
+            ),
+        )?;
+        let results = tail.poll(&files, &query, &options)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].uuid, "u3");
+        assert_eq!(results[0].line_number, Some(3));
+        assert!(tail.poll(&files, &query, &options)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_poll_waits_for_complete_lines_and_new_files() -> Result<()> {
+        let dir = tempdir()?;
+        let session = dir.path().join("session.jsonl");
+        append(&session, "")?;
+        let mut tail = Tail::from_end(std::slice::from_ref(&session));
+        let query = parse_query("hello")?;
+        let options = SearchOptions::default();
+
+        let line = user_line("u1", "hello");
+        let (head, rest) = line.split_at(20);
+        append(&session, head)?;
+        let files = vec![session.clone()];
+        assert!(tail.poll(&files, &query, &options)?.is_empty());
+        append(&session, &format!("{rest}\n"))?;
+        assert_eq!(tail.poll(&files, &query, &options)?.len(), 1);
+
+        let created = dir.path().join("new.jsonl");
+        append(&created, &format!("{}\n", user_line("u2", "hello again")))?;
+        let files = vec![session, created.clone()];
+        let results = tail.poll(&files, &query, &options)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].uuid, "u2");
+        assert!(tail.is_following(&created));
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+use crate::schemas::TodoItem;
+use crate::search::compression::open_session;
+use crate::utils::path_encoding;
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+/// Latest todo list written in a session
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionTodos {
+    pub session_id: String,
+    pub file: String,
+    pub cwd: String,
+    pub timestamp: String,
+    pub todos: Vec<TodoItem>,
+}
+
+impl SessionTodos {
+    /// Number of items that are not completed yet
+    pub fn open_count(&self) -> usize {
+        self.todos
+            .iter()
+            .filter(|todo| todo.status != "completed")
+            .count()
+    }
+}
+
+/// Status glyph used when rendering a todo as a checklist entry
+pub fn todo_status_glyph(status: &str) -> &'static str {
+    match status {
+        "completed" => "☑",
+        "in_progress" => "◐",
+        _ => "☐",
+    }
+}
+
+/// Extract the todo list carried by a session message.
+///
+/// Assistant messages carry it as the `todos` input of a TodoWrite tool call and
+/// user messages as the `newTodos` of the matching tool result.
+pub fn extract_todos(message: &Value) -> Option<Vec<TodoItem>> {
+    if let Some(new_todos) = message
+        .get("toolUseResult")
+        .and_then(|result| result.get("newTodos"))
+    {
+        return serde_json::from_value(new_todos.clone()).ok();
+    }
+
+    message
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())?
+        .iter()
+        .rev()
+        .filter(|item| {
+            item.get("type").and_then(|t| t.as_str()) == Some("tool_use")
+                && item.get("name").and_then(|n| n.as_str()) == Some("TodoWrite")
+        })
+        .find_map(|item| serde_json::from_value(item.get("input")?.get("todos")?.clone()).ok())
+}
+
+/// Collect the latest todo state of every session found in `files`.
+///
+/// Sessions are returned newest first. `project_path` and `session_id` narrow the
+/// scan the same way they do for regular searches.
+pub fn collect_latest_todos(
+    files: &[PathBuf],
+    project_path: Option<&str>,
+    session_id: Option<&str>,
+) -> Result<Vec<SessionTodos>> {
+    let per_file: Vec<Vec<SessionTodos>> = files
+        .par_iter()
+        .filter(|file| {
+            project_path.is_none_or(|project| {
+                path_encoding::file_belongs_to_project(&file.to_string_lossy(), project)
+            })
+        })
+        .map(|file| scan_file(file, session_id))
+        .collect::<Result<_>>()?;
+
+    let mut latest: HashMap<String, SessionTodos> = HashMap::new();
+    for entry in per_file.into_iter().flatten() {
+        match latest.get(&entry.session_id) {
+            Some(existing) if existing.timestamp >= entry.timestamp => {}
+            _ => {
+                latest.insert(entry.session_id.clone(), entry);
+            }
+        }
+    }
+
+    let mut sessions: Vec<SessionTodos> = latest.into_values().collect();
+    sessions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(sessions)
+}
+
+fn scan_file(file: &Path, session_id: Option<&str>) -> Result<Vec<SessionTodos>> {
+    let reader = open_session(file)?;
+    let file_str = file.to_string_lossy().to_string();
+    let mut latest: HashMap<String, SessionTodos> = HashMap::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        // Cheap pre-filter before paying for a full JSON parse
+        if !line.contains("TodoWrite") && !line.contains("newTodos") {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let Some(todos) = extract_todos(&value) else {
+            continue;
+        };
+
+        let get = |key: &str| {
+            value
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string()
+        };
+        let entry = SessionTodos {
+            session_id: get("sessionId"),
+            file: file_str.clone(),
+            cwd: get("cwd"),
+            timestamp: get("timestamp"),
+            todos,
+        };
+
+        if session_id.is_some_and(|id| id != entry.session_id) {
+            continue;
+        }
+        match latest.get(&entry.session_id) {
+            Some(existing) if existing.timestamp > entry.timestamp => {}
+            _ => {
+                latest.insert(entry.session_id.clone(), entry);
+            }
+        }
+    }
+
+    Ok(latest.into_values().collect())
+}
+
+pub fn format_todos(sessions: &[SessionTodos], use_color: bool) -> String {
+    use colored::Colorize;
+
+    let mut output = String::new();
+
+    for session in sessions {
+        let done = session.todos.len() - session.open_count();
+        let header = format!(
+            "Session {} ({}/{} done)",
+            session.session_id,
+            done,
+            session.todos.len()
+        );
+        let details = format!(
+            "  {} | {}",
+            format_timestamp(&session.timestamp),
+            session.cwd
+        );
+
+        if use_color {
+            output.push_str(&header.bright_blue().bold().to_string());
+            output.push('\n');
+            output.push_str(&details.dimmed().to_string());
+        } else {
+            output.push_str(&header);
+            output.push('\n');
+            output.push_str(&details);
+        }
+        output.push('\n');
+
+        for todo in &session.todos {
+            let glyph = todo_status_glyph(&todo.status);
+            let line = format!("  {glyph} {}", todo.content);
+            if use_color {
+                let colored_line = match todo.status.as_str() {
+                    "completed" => line.dimmed().to_string(),
+                    "in_progress" => line.bright_yellow().to_string(),
+                    _ => line.normal().to_string(),
+                };
+                output.push_str(&colored_line);
+            } else {
+                output.push_str(&line);
+            }
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+fn format_timestamp(timestamp: &str) -> String {
+    use chrono::{DateTime, Local, TimeZone};
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(timestamp) {
+        let local_dt = Local.from_utc_datetime(&dt.naive_utc());
+        local_dt.format("%Y-%m-%d %H:%M:%S").to_string()
+    } else {
+        timestamp.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn todo_write_line(session: &str, timestamp: &str, todos: &str) -> String {
+        format!(
+            r#"{{"type":"assistant","sessionId":"{session}","cwd":"/proj","timestamp":"{timestamp}","message":{{"content":[{{"type":"tool_use","id":"t","name":"TodoWrite","input":{{"todos":{todos}}}}}]}}}}"#
+        )
+    }
+
+    #[test]
+    fn test_extract_todos_from_tool_use_and_result() {
+        let tool_use: Value = serde_json::from_str(&todo_write_line(
+            "s1",
+            "2024-01-01T00:00:00Z",
+            r#"[{"content":"Write tests","status":"pending","activeForm":"Writing tests"}]"#,
+        ))
+        .unwrap();
+        let todos = extract_todos(&tool_use).unwrap();
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].content, "Write tests");
+        assert_eq!(todos[0].priority, "");
+
+        let tool_result: Value = serde_json::from_str(
+            r#"{"type":"user","toolUseResult":{"oldTodos":[],"newTodos":[{"content":"Ship","status":"completed","priority":"high","id":"1"}]}}"#,
+        )
+        .unwrap();
+        let todos = extract_todos(&tool_result).unwrap();
+        assert_eq!(todos[0].status, "completed");
+
+        let other: Value = serde_json::from_str(r#"{"type":"user","message":{}}"#).unwrap();
+        assert!(extract_todos(&other).is_none());
+    }
+
+    #[test]
+    fn test_collect_latest_todos_keeps_newest_per_session() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("session.jsonl");
+        let mut file = File::create(&path)?;
+        writeln!(
+            file,
+            "{}",
+            todo_write_line(
+                "s1",
+                "2024-01-01T00:00:00Z",
+                r#"[{"content":"Old","status":"pending"}]"#
+            )
+        )?;
+        writeln!(
+            file,
+            "{}",
+            todo_write_line(
+                "s1",
+                "2024-01-02T00:00:00Z",
+                r#"[{"content":"New","status":"in_progress"},{"content":"Done","status":"completed"}]"#
+            )
+        )?;
+        writeln!(
+            file,
+            "{}",
+            todo_write_line(
+                "s2",
+                "2024-01-03T00:00:00Z",
+                r#"[{"content":"Other","status":"completed"}]"#
+            )
+        )?;
+        writeln!(file, r#"{{"type":"user","message":{{"content":"hi"}}}}"#)?;
+
+        let sessions = collect_latest_todos(std::slice::from_ref(&path), None, None)?;
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session_id, "s2");
+        assert_eq!(sessions[1].todos[0].content, "New");
+        assert_eq!(sessions[1].open_count(), 1);
+
+        let only_s1 = collect_latest_todos(&[path], None, Some("s1"))?;
+        assert_eq!(only_s1.len(), 1);
+        assert_eq!(only_s1[0].session_id, "s1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_todos_without_color() {
+        let sessions = vec![SessionTodos {
+            session_id: "s1".to_string(),
+            file: "/tmp/s1.jsonl".to_string(),
+            cwd: "/proj".to_string(),
+            timestamp: "not-a-timestamp".to_string(),
+            todos: vec![
+                TodoItem {
+                    content: "Write tests".to_string(),
+                    status: "completed".to_string(),
+                    priority: String::new(),
+                    id: String::new(),
+                },
+                TodoItem {
+                    content: "Ship it".to_string(),
+                    status: "pending".to_string(),
+                    priority: String::new(),
+                    id: String::new(),
+                },
+            ],
+        }];
+
+        let output = format_todos(&sessions, false);
+        assert!(output.contains("Session s1 (1/2 done)"));
+        assert!(output.contains("not-a-timestamp | /proj"));
+        assert!(output.contains("☑ Write tests"));
+        assert!(output.contains("☐ Ship it"));
+    }
+}
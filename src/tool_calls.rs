@@ -0,0 +1,162 @@
+//! Filtering messages by the tool calls they carry (`--tool`, `--tool-status`).
+//!
+//! An assistant message calls tools with `tool_use` blocks, and the results
+//! come back in a later user message as `tool_result` blocks that only refer
+//! to the call's id. Tool names are therefore remembered per session file as
+//! its messages go by, so results can be told apart by tool too.
+//!
+//! Whether a call failed is only known from its result, so a status keeps
+//! tool results alone; the calls themselves are a `-B 1` away.
+
+use crate::schemas::session_message::{Content, SessionMessage, UserContent};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How a tool call ended, for `--tool-status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolStatus {
+    /// The result was not flagged as an error
+    Success,
+    /// The result was flagged `is_error`
+    Error,
+}
+
+/// Which tool calls a message must carry to be kept
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolFilter {
+    /// Tool name, compared case-insensitively
+    pub name: Option<String>,
+    pub status: Option<ToolStatus>,
+}
+
+impl ToolFilter {
+    /// The filter for `--tool` and `--tool-status`; `None` without either
+    pub fn new(name: Option<String>, status: Option<ToolStatus>) -> Option<Self> {
+        (name.is_some() || status.is_some()).then_some(Self { name, status })
+    }
+
+    fn matches_name(&self, name: Option<&str>) -> bool {
+        match (&self.name, name) {
+            (None, _) => true,
+            (Some(wanted), Some(name)) => wanted.eq_ignore_ascii_case(name),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Names of the tool calls seen so far in a session file, fed its messages
+/// one at a time in file order
+#[derive(Debug, Default, Clone)]
+pub struct ToolCalls {
+    names: HashMap<String, String>,
+}
+
+impl ToolCalls {
+    pub fn observe(&mut self, message: &SessionMessage) {
+        if let SessionMessage::Assistant { message, .. } = message {
+            for item in &message.content {
+                if let Content::ToolUse { id, name, .. } = item {
+                    self.names.insert(id.clone(), name.clone());
+                }
+            }
+        }
+    }
+
+    /// Whether `message` calls a tool, or carries the result of a call,
+    /// that `filter` asks for
+    pub fn matches(&self, filter: &ToolFilter, message: &SessionMessage) -> bool {
+        match message {
+            SessionMessage::Assistant { message, .. } => {
+                filter.status.is_none()
+                    && message.content.iter().any(|item| {
+                        matches!(item, Content::ToolUse { name, .. } if filter.matches_name(Some(name)))
+                    })
+            }
+            SessionMessage::User { message, .. } => {
+                let UserContent::Array(items) = &message.content else {
+                    return false;
+                };
+                items.iter().any(|item| {
+                    let Content::ToolResult {
+                        tool_use_id,
+                        is_error,
+                        ..
+                    } = item
+                    else {
+                        return false;
+                    };
+                    let status = if *is_error == Some(true) {
+                        ToolStatus::Error
+                    } else {
+                        ToolStatus::Success
+                    };
+                    filter.status.is_none_or(|wanted| wanted == status)
+                        && filter.matches_name(self.names.get(tool_use_id).map(String::as_str))
+                })
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#""sessionId":"s1","parentUuid":null,"userType":"external","cwd":"/","version":"1","uuid":"u","timestamp":"2024-01-01T00:00:00Z","isSidechain":false"#;
+
+    fn tool_use(id: &str, name: &str) -> SessionMessage {
+        serde_json::from_str(&format!(
+            r#"{{"type":"assistant","message":{{"id":"m","type":"message","role":"assistant","model":"claude","content":[{{"type":"tool_use","id":"{id}","name":"{name}","input":{{}}}}],"stop_reason":"tool_use","stop_sequence":null,"usage":{{"input_tokens":1,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":1}}}},{BASE}}}"#
+        ))
+        .unwrap()
+    }
+
+    fn tool_result(id: &str, is_error: bool) -> SessionMessage {
+        serde_json::from_str(&format!(
+            r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"{id}","content":"out","is_error":{is_error}}}]}},{BASE}}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tool_filter() {
+        let mut calls = ToolCalls::default();
+        let bash = tool_use("t1", "Bash");
+        let read = tool_use("t2", "Read");
+        calls.observe(&bash);
+        calls.observe(&read);
+        let bash_failed = tool_result("t1", true);
+        let read_ok = tool_result("t2", false);
+        let prompt: SessionMessage = serde_json::from_str(&format!(
+            r#"{{"type":"user","message":{{"role":"user","content":"run it"}},{BASE}}}"#
+        ))
+        .unwrap();
+
+        let bash_calls = ToolFilter::new(Some("bash".to_string()), None).unwrap();
+        assert!(calls.matches(&bash_calls, &bash));
+        assert!(calls.matches(&bash_calls, &bash_failed));
+        assert!(!calls.matches(&bash_calls, &read));
+        assert!(!calls.matches(&bash_calls, &read_ok));
+        assert!(!calls.matches(&bash_calls, &prompt));
+
+        // A status keeps tool results only
+        let failed_bash =
+            ToolFilter::new(Some("Bash".to_string()), Some(ToolStatus::Error)).unwrap();
+        assert!(!calls.matches(&failed_bash, &bash));
+        assert!(calls.matches(&failed_bash, &bash_failed));
+        let succeeded = ToolFilter::new(None, Some(ToolStatus::Success)).unwrap();
+        assert!(calls.matches(&succeeded, &read_ok));
+        assert!(!calls.matches(&succeeded, &bash_failed));
+
+        // Results of calls never seen have no name
+        assert!(!calls.matches(&bash_calls, &tool_result("t3", true)));
+        assert!(calls.matches(
+            &ToolFilter::new(None, Some(ToolStatus::Error)).unwrap(),
+            &tool_result("t3", true)
+        ));
+
+        assert_eq!(ToolFilter::new(None, None), None);
+    }
+}
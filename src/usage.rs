@@ -0,0 +1,605 @@
+use crate::cost::PricingTable;
+use crate::schemas::Usage;
+use crate::search::compression::open_session;
+use crate::utils::path_encoding;
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+/// Token counts summed from the `usage` of assistant messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TokenTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+impl TokenTotals {
+    fn add_usage(&mut self, usage: &Usage) {
+        self.input_tokens += u64::from(usage.input_tokens);
+        self.output_tokens += u64::from(usage.output_tokens);
+        self.cache_creation_input_tokens += u64::from(usage.cache_creation_input_tokens);
+        self.cache_read_input_tokens += u64::from(usage.cache_read_input_tokens);
+    }
+
+    fn merge(&mut self, other: &TokenTotals) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+
+    /// Tokens the model was given to read: the whole context of the request
+    pub fn context(&self) -> u64 {
+        self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens
+    }
+}
+
+/// Message count, token totals and estimated cost for one role, project,
+/// model or day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct UsageBucket {
+    pub messages: usize,
+    pub tokens: TokenTotals,
+    /// Estimated cost in USD, see `crate::cost`
+    pub cost_usd: f64,
+}
+
+impl UsageBucket {
+    fn merge(&mut self, other: &UsageBucket) {
+        self.messages += other.messages;
+        self.tokens.merge(&other.tokens);
+        self.cost_usd += other.cost_usd;
+    }
+}
+
+/// Usage aggregated across sessions for the `stats` report
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageReport {
+    pub sessions: usize,
+    pub messages: usize,
+    pub tokens: TokenTotals,
+    /// Estimated cost in USD of the tokens of priced models
+    pub cost_usd: f64,
+    /// Models with token usage but no price, left out of `cost_usd`
+    pub unpriced_models: BTreeSet<String>,
+    pub by_role: BTreeMap<String, UsageBucket>,
+    pub by_project: BTreeMap<String, UsageBucket>,
+    pub by_model: BTreeMap<String, UsageBucket>,
+    pub by_day: BTreeMap<String, UsageBucket>,
+    /// Resumed sessions can span several files, so ids are kept until merged
+    #[serde(skip)]
+    session_ids: HashSet<String>,
+}
+
+impl UsageReport {
+    fn merge(&mut self, other: UsageReport) {
+        self.session_ids.extend(other.session_ids);
+        self.sessions = self.session_ids.len();
+        self.messages += other.messages;
+        self.tokens.merge(&other.tokens);
+        self.cost_usd += other.cost_usd;
+        self.unpriced_models.extend(other.unpriced_models);
+        for (ours, theirs) in [
+            (&mut self.by_role, other.by_role),
+            (&mut self.by_project, other.by_project),
+            (&mut self.by_model, other.by_model),
+            (&mut self.by_day, other.by_day),
+        ] {
+            for (key, bucket) in theirs {
+                ours.entry(key).or_default().merge(&bucket);
+            }
+        }
+    }
+
+    fn add_message(&mut self, value: &Value, tokens: Option<&TokenTotals>, pricing: &PricingTable) {
+        let role = value
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or("unknown");
+        let project = value.get("cwd").and_then(|c| c.as_str());
+        let model = value
+            .get("message")
+            .and_then(|m| m.get("model"))
+            .and_then(|m| m.as_str());
+        // RFC 3339 timestamps start with the UTC date
+        let day = value
+            .get("timestamp")
+            .and_then(|t| t.as_str())
+            .and_then(|t| t.get(..10));
+
+        let tokens = tokens.copied().unwrap_or_default();
+        let cost = match model {
+            Some(model) if tokens.total() > 0 => {
+                pricing.cost(model, &tokens).unwrap_or_else(|| {
+                    self.unpriced_models.insert(model.to_string());
+                    0.0
+                })
+            }
+            _ => 0.0,
+        };
+        let bucket = UsageBucket {
+            messages: 1,
+            tokens,
+            cost_usd: cost,
+        };
+        self.messages += 1;
+        self.tokens.merge(&bucket.tokens);
+        self.cost_usd += cost;
+        self.by_role
+            .entry(role.to_string())
+            .or_default()
+            .merge(&bucket);
+        if let Some(project) = project {
+            self.by_project
+                .entry(project.to_string())
+                .or_default()
+                .merge(&bucket);
+        }
+        if let Some(model) = model {
+            self.by_model
+                .entry(model.to_string())
+                .or_default()
+                .merge(&bucket);
+        }
+        if let Some(day) = day {
+            self.by_day
+                .entry(day.to_string())
+                .or_default()
+                .merge(&bucket);
+        }
+    }
+}
+
+/// Aggregate message counts, token usage and its cost at `pricing` across
+/// `files`
+pub fn collect_usage(
+    files: &[PathBuf],
+    project_path: Option<&str>,
+    pricing: &PricingTable,
+) -> Result<UsageReport> {
+    let per_file: Vec<UsageReport> = files
+        .par_iter()
+        .filter(|file| {
+            project_path.is_none_or(|project| {
+                path_encoding::file_belongs_to_project(&file.to_string_lossy(), project)
+            })
+        })
+        .map(|file| scan_file(file, pricing))
+        .collect::<Result<_>>()?;
+
+    let mut report = UsageReport::default();
+    for file_report in per_file {
+        report.merge(file_report);
+    }
+    Ok(report)
+}
+
+fn scan_file(file: &Path, pricing: &PricingTable) -> Result<UsageReport> {
+    let reader = open_session(file)?;
+    let mut report = UsageReport::default();
+    // One API response is logged as several assistant lines (one per content
+    // block) that repeat the same usage, so tokens are counted once per id
+    let mut counted_ids: HashSet<String> = HashSet::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let Ok(value) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+
+        if let Some(session_id) = value.get("sessionId").and_then(|s| s.as_str()) {
+            report.session_ids.insert(session_id.to_string());
+        }
+
+        let tokens = response_tokens(&value, &mut counted_ids);
+        report.add_message(&value, tokens.as_ref(), pricing);
+    }
+
+    report.sessions = report.session_ids.len();
+    Ok(report)
+}
+
+/// Estimated cost of messages and sessions, as `--format json` reports it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostIndex {
+    /// Cost by message uuid. A response logged over several lines is
+    /// counted on its first line only.
+    pub by_message: HashMap<String, f64>,
+    pub by_session: HashMap<String, f64>,
+    /// Files that could not be read, with the error, left out of the costs
+    pub unreadable: Vec<(PathBuf, String)>,
+}
+
+impl CostIndex {
+    /// Summed cost of `sessions`; 0, not -0, when none of them is priced
+    pub fn sessions_total<'a>(&self, sessions: impl IntoIterator<Item = &'a str>) -> f64 {
+        sessions
+            .into_iter()
+            .filter_map(|session_id| self.by_session.get(session_id))
+            // An empty `sum` of floats is -0.0
+            .fold(0.0, |total, cost| total + cost)
+    }
+}
+
+/// Running estimated cost of a session read line by line
+#[derive(Debug, Default)]
+pub struct CostTracker {
+    counted_ids: HashSet<String>,
+    total: Option<f64>,
+}
+
+impl CostTracker {
+    /// Add the response on this line, returning its cost unless it is not
+    /// priced or was already counted
+    pub fn observe(&mut self, value: &Value, pricing: &PricingTable) -> Option<f64> {
+        let tokens = response_tokens(value, &mut self.counted_ids)?;
+        let model = value
+            .get("message")
+            .and_then(|m| m.get("model"))
+            .and_then(|m| m.as_str())?;
+        let cost = pricing.cost(model, &tokens)?;
+        *self.total.get_or_insert(0.0) += cost;
+        Some(cost)
+    }
+
+    /// Cost of the responses seen so far, `None` when none was priced
+    pub fn total(&self) -> Option<f64> {
+        self.total
+    }
+}
+
+/// The estimated cost of every priced response in `files`.
+///
+/// Best effort: a file that cannot be read (rotated or removed since the
+/// search) is listed in `unreadable` rather than failing the whole estimate.
+pub fn collect_costs(files: &[PathBuf], pricing: &PricingTable) -> CostIndex {
+    let per_file: Vec<(&PathBuf, Result<CostIndex>)> = files
+        .par_iter()
+        .map(|file| (file, file_costs(file, pricing)))
+        .collect();
+
+    let mut index = CostIndex::default();
+    for (file, file_index) in per_file {
+        let file_index = match file_index {
+            Ok(file_index) => file_index,
+            Err(e) => {
+                index.unreadable.push((file.clone(), format!("{e:#}")));
+                continue;
+            }
+        };
+        index.by_message.extend(file_index.by_message);
+        for (session_id, cost) in file_index.by_session {
+            *index.by_session.entry(session_id).or_default() += cost;
+        }
+    }
+    index
+}
+
+fn file_costs(file: &Path, pricing: &PricingTable) -> Result<CostIndex> {
+    let reader = open_session(file)?;
+    let mut index = CostIndex::default();
+    let mut tracker = CostTracker::default();
+
+    for line in reader.lines() {
+        let line = line?;
+        let Ok(value) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let Some(cost) = tracker.observe(&value, pricing) else {
+            continue;
+        };
+
+        if let Some(uuid) = value.get("uuid").and_then(|u| u.as_str()) {
+            index.by_message.insert(uuid.to_string(), cost);
+        }
+        if let Some(session_id) = value.get("sessionId").and_then(|s| s.as_str()) {
+            *index.by_session.entry(session_id.to_string()).or_default() += cost;
+        }
+    }
+    Ok(index)
+}
+
+/// Tokens of one API response, in the order of the session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContextPoint {
+    pub timestamp: Option<String>,
+    pub tokens: TokenTotals,
+}
+
+/// How the context of the session in `file` grew, one point per API response
+///
+/// Subagent (sidechain) responses run in a context of their own and are left
+/// out.
+pub fn session_context(file: &Path) -> Result<Vec<ContextPoint>> {
+    let reader = open_session(file)?;
+    let mut points = Vec::new();
+    let mut counted_ids: HashSet<String> = HashSet::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let Ok(value) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if value.get("isSidechain").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+        if let Some(tokens) = response_tokens(&value, &mut counted_ids) {
+            points.push(ContextPoint {
+                timestamp: value
+                    .get("timestamp")
+                    .and_then(|t| t.as_str())
+                    .map(str::to_string),
+                tokens,
+            });
+        }
+    }
+    Ok(points)
+}
+
+/// Usage of the message on this line, unless a line of the same API response
+/// was already counted
+fn response_tokens(value: &Value, counted_ids: &mut HashSet<String>) -> Option<TokenTotals> {
+    let message = value.get("message");
+    let usage = message
+        .and_then(|m| m.get("usage"))
+        .and_then(|u| serde_json::from_value::<Usage>(u.clone()).ok())?;
+    let first_time = match message.and_then(|m| m.get("id")).and_then(|id| id.as_str()) {
+        Some(id) => counted_ids.insert(id.to_string()),
+        None => true,
+    };
+    first_time.then(|| {
+        let mut tokens = TokenTotals::default();
+        tokens.add_usage(&usage);
+        tokens
+    })
+}
+
+pub fn format_usage_report(report: &UsageReport, use_color: bool) -> String {
+    use crate::cost::format_usd;
+    use colored::Colorize;
+    use std::fmt::Write;
+
+    let heading = |title: &str| {
+        if use_color {
+            title.bright_yellow().bold().to_string()
+        } else {
+            title.to_string()
+        }
+    };
+
+    let mut output = String::new();
+    let _ = writeln!(output, "{}", heading("Usage Statistics"));
+    let _ = writeln!(output, "Sessions: {}", report.sessions);
+    let _ = writeln!(output, "Messages: {}", report.messages);
+    let _ = writeln!(
+        output,
+        "Tokens:   {} (input {}, output {}, cache write {}, cache read {})",
+        report.tokens.total(),
+        report.tokens.input_tokens,
+        report.tokens.output_tokens,
+        report.tokens.cache_creation_input_tokens,
+        report.tokens.cache_read_input_tokens,
+    );
+    let _ = writeln!(
+        output,
+        "Cost:     {} (estimated)",
+        format_usd(report.cost_usd)
+    );
+    if !report.unpriced_models.is_empty() {
+        let models: Vec<&str> = report.unpriced_models.iter().map(String::as_str).collect();
+        let _ = writeln!(output, "          no price for {}", models.join(", "));
+    }
+
+    for (title, buckets) in [
+        ("By Role", &report.by_role),
+        ("By Model", &report.by_model),
+        ("By Project", &report.by_project),
+        ("By Day", &report.by_day),
+    ] {
+        if buckets.is_empty() {
+            continue;
+        }
+        let width = buckets
+            .keys()
+            .map(|key| key.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        let _ = writeln!(output);
+        let _ = writeln!(output, "{}", heading(title));
+        let _ = writeln!(
+            output,
+            "  {:<width$}  {:>8}  {:>12}  {:>12}  {:>12}  {:>12}  {:>10}",
+            "", "messages", "input", "output", "cache write", "cache read", "cost"
+        );
+        for (key, bucket) in buckets {
+            let _ = writeln!(
+                output,
+                "  {:<width$}  {:>8}  {:>12}  {:>12}  {:>12}  {:>12}  {:>10}",
+                key,
+                bucket.messages,
+                bucket.tokens.input_tokens,
+                bucket.tokens.output_tokens,
+                bucket.tokens.cache_creation_input_tokens,
+                bucket.tokens.cache_read_input_tokens,
+                format_usd(bucket.cost_usd),
+            );
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::round_usd;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    const USER: &str = r#"{"type":"user","sessionId":"s1","cwd":"/work/app","timestamp":"2024-01-01T10:00:00Z","message":{"role":"user","content":"hi"}}"#;
+
+    // The same response split over two lines, as Claude logs it
+    const ASSISTANT_TEXT: &str = r#"{"type":"assistant","uuid":"u1","sessionId":"s1","cwd":"/work/app","timestamp":"2024-01-01T10:00:01Z","message":{"id":"msg_1","type":"message","role":"assistant","model":"claude-sonnet-4","content":[{"type":"text","text":"hello"}],"usage":{"input_tokens":10,"cache_creation_input_tokens":100,"cache_read_input_tokens":1000,"output_tokens":5}}}"#;
+    const ASSISTANT_TOOL: &str = r#"{"type":"assistant","uuid":"u2","sessionId":"s1","cwd":"/work/app","timestamp":"2024-01-01T10:00:02Z","message":{"id":"msg_1","type":"message","role":"assistant","model":"claude-sonnet-4","content":[{"type":"tool_use","id":"t1","name":"Read","input":{}}],"usage":{"input_tokens":10,"cache_creation_input_tokens":100,"cache_read_input_tokens":1000,"output_tokens":5}}}"#;
+
+    const NEXT_DAY: &str = r#"{"type":"assistant","sessionId":"s2","cwd":"/work/lib","timestamp":"2024-01-02T09:00:00Z","message":{"id":"msg_2","type":"message","role":"assistant","model":"claude-opus-4","content":[{"type":"text","text":"done"}],"usage":{"input_tokens":1,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":2}}}"#;
+
+    fn write_session(dir: &Path, name: &str, lines: &[&str]) -> PathBuf {
+        let path = dir.join(name);
+        let mut file = File::create(&path).unwrap();
+        for line in lines {
+            writeln!(file, "{line}").unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn test_collect_usage() {
+        let dir = tempdir().unwrap();
+        let files = vec![
+            write_session(
+                dir.path(),
+                "a.jsonl",
+                &[USER, ASSISTANT_TEXT, ASSISTANT_TOOL],
+            ),
+            write_session(dir.path(), "b.jsonl", &[NEXT_DAY, "not json"]),
+            write_session(dir.path(), "c.jsonl", &[USER]),
+        ];
+
+        let report = collect_usage(&files, None, &PricingTable::default()).unwrap();
+        assert_eq!(report.sessions, 2);
+        assert_eq!(report.messages, 5);
+        assert_eq!(
+            report.tokens,
+            TokenTotals {
+                input_tokens: 11,
+                output_tokens: 7,
+                cache_creation_input_tokens: 100,
+                cache_read_input_tokens: 1000,
+            }
+        );
+        assert_eq!(report.tokens.total(), 1118);
+
+        assert_eq!(report.by_role["user"].messages, 2);
+        assert_eq!(report.by_role["assistant"].messages, 3);
+        assert_eq!(report.by_model["claude-sonnet-4"].messages, 2);
+        assert_eq!(report.by_model["claude-sonnet-4"].tokens.output_tokens, 5);
+        assert_eq!(report.by_model["claude-opus-4"].tokens.output_tokens, 2);
+        assert_eq!(report.by_project["/work/app"].messages, 4);
+        assert_eq!(report.by_project["/work/lib"].tokens.input_tokens, 1);
+        assert_eq!(report.by_day["2024-01-01"].messages, 4);
+        assert_eq!(report.by_day["2024-01-02"].messages, 1);
+
+        // 10 * 3 + 5 * 15 + 100 * 3.75 + 1000 * 0.3, and 1 * 15 + 2 * 75,
+        // per million tokens
+        assert_eq!(
+            round_usd(report.by_model["claude-sonnet-4"].cost_usd),
+            0.00078
+        );
+        assert_eq!(
+            round_usd(report.by_model["claude-opus-4"].cost_usd),
+            0.000165
+        );
+        assert_eq!(round_usd(report.cost_usd), 0.000945);
+        assert!(report.unpriced_models.is_empty());
+    }
+
+    #[test]
+    fn test_collect_costs() {
+        let dir = tempdir().unwrap();
+        let files = vec![
+            write_session(
+                dir.path(),
+                "a.jsonl",
+                &[USER, ASSISTANT_TEXT, ASSISTANT_TOOL],
+            ),
+            write_session(dir.path(), "b.jsonl", &[NEXT_DAY]),
+        ];
+
+        let costs = collect_costs(&files, &PricingTable::default());
+        assert_eq!(round_usd(costs.by_message["u1"]), 0.00078);
+        // The second line of the same response is not counted again
+        assert!(!costs.by_message.contains_key("u2"));
+        assert_eq!(round_usd(costs.by_session["s1"]), 0.00078);
+        assert_eq!(round_usd(costs.by_session["s2"]), 0.000165);
+        assert!(costs.unreadable.is_empty());
+        assert_eq!(
+            round_usd(costs.sessions_total(["s1", "s2", "s3"])),
+            0.000945
+        );
+    }
+
+    #[test]
+    fn test_collect_costs_skips_unreadable_files() {
+        let dir = tempdir().unwrap();
+        let files = vec![
+            write_session(dir.path(), "a.jsonl", &[USER, ASSISTANT_TEXT]),
+            dir.path().join("rotated.jsonl"),
+        ];
+
+        let costs = collect_costs(&files, &PricingTable::default());
+        assert_eq!(round_usd(costs.by_session["s1"]), 0.00078);
+        assert_eq!(costs.unreadable.len(), 1);
+        assert_eq!(costs.unreadable[0].0, files[1]);
+    }
+
+    #[test]
+    fn test_sessions_total_of_nothing_is_zero() {
+        let costs = CostIndex::default();
+        let total = round_usd(costs.sessions_total(["s1"]));
+        assert!(total.is_sign_positive());
+        assert_eq!(serde_json::json!(total).to_string(), "0.0");
+    }
+
+    #[test]
+    fn test_session_context() {
+        const SIDECHAIN: &str = r#"{"type":"assistant","isSidechain":true,"sessionId":"s1","timestamp":"2024-01-01T10:00:03Z","message":{"id":"msg_sub","role":"assistant","content":[],"usage":{"input_tokens":50000,"output_tokens":1}}}"#;
+        let dir = tempdir().unwrap();
+        let file = write_session(
+            dir.path(),
+            "a.jsonl",
+            &[USER, ASSISTANT_TEXT, ASSISTANT_TOOL, SIDECHAIN, NEXT_DAY],
+        );
+
+        let points = session_context(&file).unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].timestamp.as_deref(), Some("2024-01-01T10:00:01Z"));
+        assert_eq!(points[0].tokens.context(), 1110);
+        assert_eq!(points[0].tokens.cache_read_input_tokens, 1000);
+        assert_eq!(points[1].tokens.context(), 1);
+    }
+
+    #[test]
+    fn test_format_usage_report() {
+        let dir = tempdir().unwrap();
+        let files = vec![write_session(
+            dir.path(),
+            "a.jsonl",
+            &[USER, ASSISTANT_TEXT],
+        )];
+        let report = collect_usage(&files, None, &PricingTable::default()).unwrap();
+
+        let output = format_usage_report(&report, false);
+        assert!(output.contains("Messages: 2"));
+        assert!(output.contains("Tokens:   1115 (input 10, output 5"));
+        assert!(output.contains("Cost:     <$0.01 (estimated)"));
+        assert!(output.contains("By Model"));
+        assert!(output.contains("claude-sonnet-4"));
+        assert!(output.contains("2024-01-01"));
+    }
+}
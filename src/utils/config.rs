@@ -0,0 +1,14 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// Directory holding ccms' own files (bookmarks, templates, ...).
+///
+/// Defaults to `~/.config/ccms` and can be overridden with `CCMS_CONFIG_DIR`.
+pub fn config_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("CCMS_CONFIG_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+
+    let home = dirs::home_dir().context("failed to resolve home directory for config dir")?;
+    Ok(home.join(".config").join("ccms"))
+}
@@ -0,0 +1,156 @@
+//! Git repositories that sessions ran in (`--repo`).
+//!
+//! A session belongs to a repository when its working directory is inside
+//! the repository or one of its worktrees. Repositories are found by walking
+//! up to the `.git` entry: a directory in the main checkout, or a file
+//! pointing into `<main>/.git/worktrees/<name>` in a linked worktree.
+
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitRepo {
+    /// Directory name of the main checkout
+    pub name: String,
+    /// The main checkout followed by its linked worktrees
+    pub worktrees: Vec<PathBuf>,
+}
+
+impl GitRepo {
+    /// The repository `dir` is in, with all of its worktrees
+    pub fn discover(dir: &Path) -> Result<Self> {
+        let Some(main) = main_checkout(dir) else {
+            bail!("{} is not inside a git repository", dir.display());
+        };
+        let mut worktrees = vec![main.clone()];
+        if let Ok(entries) = fs::read_dir(main.join(".git").join("worktrees")) {
+            // Each entry names the `.git` file of its worktree in `gitdir`
+            let mut linked: Vec<PathBuf> = entries
+                .flatten()
+                .filter_map(|entry| fs::read_to_string(entry.path().join("gitdir")).ok())
+                .filter_map(|gitdir| Some(Path::new(gitdir.trim()).parent()?.to_path_buf()))
+                .collect();
+            linked.sort();
+            worktrees.extend(linked);
+        }
+        Ok(Self {
+            name: dir_name(&main),
+            worktrees,
+        })
+    }
+}
+
+/// Name of the repository `cwd` is in, the same for all of its worktrees;
+/// `None` outside a repository or when `cwd` no longer exists
+pub fn repo_name(cwd: &str) -> Option<String> {
+    static NAMES: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+    if cwd.is_empty() {
+        return None;
+    }
+    let mut names = NAMES.get_or_init(Default::default).lock().unwrap();
+    names
+        .entry(cwd.to_string())
+        .or_insert_with(|| main_checkout(Path::new(cwd)).map(|main| dir_name(&main)))
+        .clone()
+}
+
+/// The main checkout of the repository `dir` is in
+fn main_checkout(dir: &Path) -> Option<PathBuf> {
+    let dir = dir.canonicalize().ok()?;
+    for ancestor in dir.ancestors() {
+        let git = ancestor.join(".git");
+        if git.is_dir() {
+            return Some(ancestor.to_path_buf());
+        }
+        if git.is_file() {
+            // `gitdir: <main>/.git/worktrees/<name>`; submodules point
+            // elsewhere and count as repositories of their own
+            let content = fs::read_to_string(&git).ok()?;
+            let gitdir = ancestor.join(content.strip_prefix("gitdir:")?.trim());
+            let worktrees = gitdir.parent()?;
+            if worktrees
+                .file_name()
+                .is_some_and(|name| name == "worktrees")
+            {
+                return Some(worktrees.parent()?.parent()?.to_path_buf());
+            }
+            return Some(ancestor.to_path_buf());
+        }
+    }
+    None
+}
+
+fn dir_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| dir.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// A repository `app` with a linked worktree `app-feature` next to it,
+    /// laid out the way `git worktree add` leaves them
+    fn repo_with_worktree(root: &Path) -> (PathBuf, PathBuf) {
+        let main = root.join("app");
+        let feature = root.join("app-feature");
+        let admin = main.join(".git").join("worktrees").join("app-feature");
+        fs::create_dir_all(main.join("src")).unwrap();
+        fs::create_dir_all(&admin).unwrap();
+        fs::create_dir_all(&feature).unwrap();
+        fs::write(
+            feature.join(".git"),
+            format!("gitdir: {}\n", admin.display()),
+        )
+        .unwrap();
+        fs::write(
+            admin.join("gitdir"),
+            format!("{}\n", feature.join(".git").display()),
+        )
+        .unwrap();
+        (
+            main.canonicalize().unwrap(),
+            feature.canonicalize().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_discover_includes_worktrees() {
+        let dir = tempdir().unwrap();
+        let (main, feature) = repo_with_worktree(dir.path());
+
+        for start in [main.join("src"), feature.clone()] {
+            let repo = GitRepo::discover(&start).unwrap();
+            assert_eq!(repo.name, "app");
+            assert_eq!(repo.worktrees, vec![main.clone(), feature.clone()]);
+        }
+
+        let outside = dir.path().join("elsewhere");
+        fs::create_dir(&outside).unwrap();
+        assert!(GitRepo::discover(&outside).is_err());
+    }
+
+    #[test]
+    fn test_repo_name() {
+        let dir = tempdir().unwrap();
+        let (main, feature) = repo_with_worktree(dir.path());
+        assert_eq!(
+            repo_name(&main.join("src").display().to_string()),
+            Some("app".to_string())
+        );
+        assert_eq!(
+            repo_name(&feature.display().to_string()),
+            Some("app".to_string())
+        );
+        assert_eq!(repo_name(""), None);
+        assert_eq!(
+            repo_name(&dir.path().join("gone").display().to_string()),
+            None
+        );
+    }
+}
@@ -1 +1,4 @@
+pub mod config;
+pub mod git_repo;
 pub mod path_encoding;
+pub mod time_range;
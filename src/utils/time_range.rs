@@ -0,0 +1,161 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Days, Local, Utc};
+use parse_datetime::parse_datetime;
+
+/// Bounds of a `--when` range; a missing bound leaves that side open
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+}
+
+/// Parse a Unix timestamp or a date/relative time such as "1 day ago",
+/// "last tuesday" or "2024-01-01 09:00"
+pub fn parse_time(input: &str) -> Result<DateTime<Utc>> {
+    let input = input.trim();
+    if let Ok(timestamp) = input.parse::<i64>() {
+        return DateTime::<Utc>::from_timestamp(timestamp, 0).context("Invalid Unix timestamp");
+    }
+
+    match parse_datetime(input) {
+        Ok(dt) => {
+            let ts = dt
+                .into_zoned()
+                .context("Parsed time is outside the supported range")?
+                .timestamp();
+            DateTime::<Utc>::from_timestamp(ts.as_second(), ts.subsec_nanosecond() as u32)
+                .context("Failed to convert parsed time to UTC timestamp")
+        }
+        Err(e) => Err(anyhow::anyhow!(
+            "Failed to parse time '{input}': {e}. Expected Unix timestamp or relative time like '1 day ago'"
+        )),
+    }
+}
+
+/// Parse `START..END`, `START..` or `..END`, each side accepted by
+/// `parse_time`. A lone `START` means from then until now.
+///
+/// An `END` without a time of day ("2024-06-30", "yesterday") includes that
+/// whole day, so the range stops at the start of the next one.
+pub fn parse_time_range(input: &str) -> Result<TimeRange> {
+    let (start, end) = input.split_once("..").unwrap_or((input, ""));
+    let bound = |side: &str| {
+        let side = side.trim();
+        (!side.is_empty()).then(|| parse_time(side)).transpose()
+    };
+    let before = match bound(end)? {
+        Some(before) if is_whole_day(end) => Some(start_of_next_day(before)?),
+        before => before,
+    };
+
+    let range = TimeRange {
+        after: bound(start)?,
+        before,
+    };
+    match (range.after, range.before) {
+        (None, None) => bail!("Empty time range '{input}': expected START..END, START.. or ..END"),
+        (Some(after), Some(before)) if before < after => bail!(
+            "Time range '{input}' ends ({}) before it starts ({})",
+            before.to_rfc3339(),
+            after.to_rfc3339()
+        ),
+        _ => Ok(range),
+    }
+}
+
+/// Whether `input` names a day rather than a moment: no Unix timestamp, clock
+/// time or offset in hours, minutes or seconds
+pub(crate) fn is_whole_day(input: &str) -> bool {
+    const MOMENT_WORDS: &[&str] = &[
+        "ago", "now", "am", "pm", "noon", "midnight", "hour", "hours", "minute", "minutes", "min",
+        "mins", "second", "seconds", "sec", "secs",
+    ];
+    let input = input.trim().to_lowercase();
+    input.parse::<i64>().is_err()
+        && !input.contains(':')
+        && !input
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|word| MOMENT_WORDS.contains(&word))
+}
+
+/// Local midnight after the day `time` falls on
+pub(crate) fn start_of_next_day(time: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    time.with_timezone(&Local)
+        .date_naive()
+        .checked_add_days(Days::new(1))
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map(|midnight| midnight.with_timezone(&Utc))
+        .context("Failed to find the end of the day")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_range_bounds() {
+        let range = parse_time_range("1704067200..1704153600").unwrap();
+        assert_eq!(
+            range.after.unwrap().to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            range.before.unwrap().to_rfc3339(),
+            "2024-01-02T00:00:00+00:00"
+        );
+
+        let open_end = parse_time_range("1704067200..").unwrap();
+        assert!(open_end.after.is_some() && open_end.before.is_none());
+        let open_start = parse_time_range(" .. 1704067200").unwrap();
+        assert!(open_start.after.is_none() && open_start.before.is_some());
+        assert_eq!(
+            parse_time_range("1704067200").unwrap(),
+            TimeRange {
+                before: None,
+                ..open_end
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_time_range_relative() {
+        let range = parse_time_range("last tuesday..yesterday").unwrap();
+        assert!(range.after.unwrap() < range.before.unwrap());
+        // Yesterday is included up to midnight, which has passed
+        assert_eq!(
+            range.before.unwrap(),
+            start_of_next_day(parse_time("yesterday").unwrap()).unwrap()
+        );
+        assert!(range.before.unwrap() <= Utc::now());
+    }
+
+    #[test]
+    fn test_parse_time_range_includes_end_day() {
+        let range = parse_time_range("2024-06-01..2024-06-30").unwrap();
+        let end = parse_time("2024-07-01").unwrap();
+        assert_eq!(range.before, Some(end));
+        assert!(parse_time("2024-06-30 12:00").unwrap() < end);
+
+        // A time of day, timestamp or offset is taken as given
+        let end = parse_time("2024-06-30 12:00").unwrap();
+        assert_eq!(
+            parse_time_range("..2024-06-30 12:00").unwrap().before,
+            Some(end)
+        );
+        assert_eq!(
+            parse_time_range("..1719748800").unwrap().before,
+            Some(parse_time("1719748800").unwrap())
+        );
+        assert!(!is_whole_day("3 hours ago"));
+        assert!(is_whole_day("last tuesday"));
+    }
+
+    #[test]
+    fn test_parse_time_range_errors() {
+        assert!(parse_time_range("..").is_err());
+        assert!(parse_time_range("not a time..yesterday").is_err());
+        let reversed = parse_time_range("yesterday..1 week ago").unwrap_err();
+        assert!(reversed.to_string().contains("ends"));
+    }
+}